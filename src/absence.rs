@@ -0,0 +1,91 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::import;
+
+/// Why part of a day was spent away from work. Absences are shown with the
+/// day's entries but never count as worked time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AbsenceReason {
+    Doctor,
+    Appointment,
+    Personal,
+    #[default]
+    Other,
+}
+
+impl AbsenceReason {
+    /// All reasons, in the order they are offered when adding an absence
+    pub const ALL: [AbsenceReason; 4] = [
+        AbsenceReason::Doctor,
+        AbsenceReason::Appointment,
+        AbsenceReason::Personal,
+        AbsenceReason::Other,
+    ];
+
+    /// Value stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AbsenceReason::Doctor => "doctor",
+            AbsenceReason::Appointment => "appointment",
+            AbsenceReason::Personal => "personal",
+            AbsenceReason::Other => "other",
+        }
+    }
+
+    /// Parses a stored value, falling back to `Other` for unknown ones
+    pub fn parse(value: &str) -> AbsenceReason {
+        AbsenceReason::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+            .unwrap_or_default()
+    }
+
+    /// Human readable name shown with the absence
+    pub fn label(self) -> &'static str {
+        match self {
+            AbsenceReason::Doctor => "Doctor",
+            AbsenceReason::Appointment => "Appointment",
+            AbsenceReason::Personal => "Personal",
+            AbsenceReason::Other => "Other",
+        }
+    }
+}
+
+/// Parses the start and end of an absence on `date`, such as "9:00" and
+/// "10:30", as local times. The end must come after the start.
+pub fn parse_time_range(date: NaiveDate, start: &str, end: &str) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let start_time = import::parse_time(start).ok_or_else(|| format!("Invalid start time \"{}\"", start.trim()))?;
+    let end_time = import::parse_time(end).ok_or_else(|| format!("Invalid end time \"{}\"", end.trim()))?;
+    if end_time <= start_time {
+        return Err("The end must come after the start".to_string());
+    }
+
+    Ok((
+        import::local_to_utc(date, start_time).ok_or("Start time does not exist in local time")?,
+        import::local_to_utc(date, end_time).ok_or("End time does not exist in local time")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reason_round_trip() {
+        for reason in AbsenceReason::ALL {
+            assert_eq!(AbsenceReason::parse(reason.as_str()), reason);
+        }
+        assert_eq!(AbsenceReason::parse("vacation"), AbsenceReason::Other);
+    }
+
+    #[test]
+    fn test_parse_time_range() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+
+        let (start, end) = parse_time_range(date, "9:00", " 10:30 ").unwrap();
+        assert_eq!((end - start).num_minutes(), 90);
+
+        assert!(parse_time_range(date, "10:30", "9:00").is_err());
+        assert!(parse_time_range(date, "soon", "9:00").is_err());
+    }
+}
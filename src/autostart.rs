@@ -0,0 +1,230 @@
+#[cfg(not(windows))]
+use std::fs;
+use std::io;
+use std::path::Path;
+#[cfg(not(windows))]
+use std::path::PathBuf;
+#[cfg(windows)]
+use std::process::Command;
+
+/// Command line flag starting in the tray without showing the window, as
+/// the app is started at login
+pub const HIDDEN_FLAG: &str = "--hidden";
+
+/// Whether starting at login passes `HIDDEN_FLAG`. Only Linux and the BSDs
+/// have a tray backend so far; elsewhere the app would start with nothing
+/// to show it from, so it opens its window instead.
+pub const STARTS_HIDDEN: bool = cfg!(all(unix, not(target_os = "macos")));
+
+/// File name of the autostart entry, after the application id
+#[cfg(not(any(windows, target_os = "macos")))]
+const ENTRY_FILE_NAME: &str = "com.example.time-tracking.desktop";
+
+/// File name of the launch agent, after its label
+#[cfg(target_os = "macos")]
+const ENTRY_FILE_NAME: &str = "com.example.time-tracking.plist";
+#[cfg(target_os = "macos")]
+const LAUNCH_AGENT_LABEL: &str = "com.example.time-tracking";
+
+/// Registry key of the programs Windows starts at login, and the app's
+/// value in it
+#[cfg(windows)]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+#[cfg(windows)]
+const RUN_VALUE: &str = "TimeTracking";
+
+/// Returns the directory of XDG autostart entries, ~/.config/autostart
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn autostart_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("autostart")
+}
+
+/// Returns the directory of the user's launch agents, which launchd starts
+/// at login, ~/Library/LaunchAgents
+#[cfg(target_os = "macos")]
+pub fn autostart_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("Library")
+        .join("LaunchAgents")
+}
+
+/// Whether the app runs in a Flatpak sandbox, which can't write the
+/// autostart directory and asks the Background portal instead
+#[cfg(target_os = "linux")]
+pub fn is_flatpak() -> bool {
+    Path::new("/.flatpak-info").exists()
+}
+
+/// Whether the app runs in a Flatpak sandbox, which only exists on Linux
+#[cfg(not(target_os = "linux"))]
+pub fn is_flatpak() -> bool {
+    false
+}
+
+/// Quotes an argument of an Exec line as the desktop entry specification
+/// asks, leaving plain arguments alone
+#[cfg(not(any(windows, target_os = "macos")))]
+fn exec_arg(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    let reserved = |c: char| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c);
+    if !arg.contains(reserved) {
+        return arg;
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The autostart entry running `exec` hidden in the tray
+#[cfg(not(any(windows, target_os = "macos")))]
+fn entry_contents(exec: &Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Time Tracking\n\
+         Comment=Start the time tracker in the system tray\n\
+         Exec={} {}\n\
+         Icon=appointment-soon\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec_arg(&exec.to_string_lossy()),
+        HIDDEN_FLAG
+    )
+}
+
+/// Escapes text for an XML property list
+#[cfg(target_os = "macos")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// The launch agent running `exec` at login. For an app bundle `exec` is
+/// the binary inside it, which launchd runs directly. Without a menu bar
+/// icon yet, the app starts with its window shown.
+#[cfg(target_os = "macos")]
+fn entry_contents(exec: &Path) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \t<key>Label</key>\n\
+         \t<string>{}</string>\n\
+         \t<key>ProgramArguments</key>\n\
+         \t<array>\n\
+         \t\t<string>{}</string>\n\
+         \t</array>\n\
+         \t<key>RunAtLoad</key>\n\
+         \t<true/>\n\
+         </dict>\n\
+         </plist>\n",
+        LAUNCH_AGENT_LABEL,
+        xml_escape(&exec.to_string_lossy())
+    )
+}
+
+/// Whether the app starts at login: an entry in the autostart directory,
+/// a launch agent on macOS or a value under the Run key of the registry on
+/// Windows
+pub fn is_enabled() -> bool {
+    #[cfg(windows)]
+    return Command::new("reg")
+        .args(["query", RUN_KEY, "/v", RUN_VALUE])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    #[cfg(not(windows))]
+    return entry_exists(&autostart_dir());
+}
+
+/// Makes the app start hidden at login by running `exec`, or stop doing so
+pub fn set_enabled(exec: &Path, enabled: bool) -> io::Result<()> {
+    #[cfg(windows)]
+    return set_run_value(exec, enabled);
+    #[cfg(not(windows))]
+    return set_entry(&autostart_dir(), exec, enabled);
+}
+
+/// Adds or deletes the app's value under the Run key with reg.exe, which
+/// every Windows has. Deleting a value that isn't there succeeds. Without a
+/// notification area icon yet, the app starts with its window shown.
+#[cfg(windows)]
+fn set_run_value(exec: &Path, enabled: bool) -> io::Result<()> {
+    if !enabled && !is_enabled() {
+        return Ok(());
+    }
+    let command = format!("\"{}\"", exec.display());
+    let args = if enabled {
+        vec!["add", RUN_KEY, "/v", RUN_VALUE, "/t", "REG_SZ", "/d", &command, "/f"]
+    } else {
+        vec!["delete", RUN_KEY, "/v", RUN_VALUE, "/f"]
+    };
+    let output = Command::new("reg").args(args).output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+}
+
+/// Whether `dir` holds the app's autostart entry or launch agent
+#[cfg(not(windows))]
+fn entry_exists(dir: &Path) -> bool {
+    dir.join(ENTRY_FILE_NAME).is_file()
+}
+
+/// Writes the autostart entry or launch agent running `exec` into `dir`,
+/// or removes it. Removing an entry that isn't there succeeds.
+#[cfg(not(windows))]
+fn set_entry(dir: &Path, exec: &Path, enabled: bool) -> io::Result<()> {
+    let path = dir.join(ENTRY_FILE_NAME);
+    if enabled {
+        fs::create_dir_all(dir)?;
+        return fs::write(path, entry_contents(exec));
+    }
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn test_exec_line_quoting() {
+        let entry = entry_contents(Path::new("/usr/bin/time-tracking"));
+        assert!(entry.contains("\nExec=/usr/bin/time-tracking --hidden\n"));
+
+        let entry = entry_contents(Path::new("/home/ana/My Apps/time$tracking 100%"));
+        assert!(entry.contains("\nExec=\"/home/ana/My Apps/time\\$tracking 100%%\" --hidden\n"));
+    }
+
+    #[test]
+    fn test_enable_and_disable() {
+        let dir = std::env::temp_dir().join(format!("time-tracking-autostart-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let exec = Path::new("/usr/bin/time-tracking");
+
+        assert!(!entry_exists(&dir));
+        set_entry(&dir, exec, true).unwrap();
+        assert!(entry_exists(&dir));
+        assert_eq!(fs::read_to_string(dir.join(ENTRY_FILE_NAME)).unwrap(), entry_contents(exec));
+
+        set_entry(&dir, exec, false).unwrap();
+        assert!(!entry_exists(&dir));
+        // Disabling again is fine
+        set_entry(&dir, exec, false).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::db;
+use crate::error;
+use crate::report::ReportRange;
+use crate::seed::{self, SeedOptions};
+
+/// Name of the command timing the app's main queries
+pub const BENCH_COMMAND: &str = "bench";
+
+const USAGE: &str = "Usage: time-tracking bench [--db PATH | --seeded N] [--runs N] [--search TEXT]";
+
+/// Where the benchmarked data comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BenchSource {
+    /// An existing database, opened read-only
+    Database(PathBuf),
+    /// A fresh in-memory database seeded with this many entries
+    Seeded(usize),
+}
+
+/// What to benchmark with the bench command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchOptions {
+    pub source: BenchSource,
+    /// How many times each query runs, the median being reported
+    pub runs: usize,
+    /// Text looked for by the search query
+    pub search: String,
+}
+
+impl BenchOptions {
+    /// Parses the arguments following the `bench` command. Without `--db`
+    /// or `--seeded` the app's own database is used.
+    pub fn parse(args: &[String]) -> Result<BenchOptions, String> {
+        let mut source = None;
+        let mut runs = 5;
+        let mut search = "review".to_string();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+            let number = |value: &String| {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid number for {}: {}", arg, value))
+            };
+            match arg.as_str() {
+                "--db" | "--seeded" if source.is_some() => {
+                    return Err("Use either --db or --seeded".to_string());
+                }
+                "--db" => source = Some(BenchSource::Database(PathBuf::from(value()?))),
+                "--seeded" => source = Some(BenchSource::Seeded(number(value()?)?)),
+                "--runs" => runs = number(value()?)?.max(1),
+                "--search" => search = value()?.clone(),
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        Ok(BenchOptions {
+            source: source.unwrap_or_else(|| BenchSource::Database(db::get_db_path())),
+            runs,
+            search,
+        })
+    }
+}
+
+/// Timings of one query over all runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTiming {
+    pub name: &'static str,
+    /// Rows the query returned
+    pub rows: usize,
+    pub fastest: Duration,
+    pub median: Duration,
+    pub slowest: Duration,
+}
+
+/// A query the app runs, returning how many rows it produced
+type Query = Box<dyn Fn(&Connection) -> error::Result<usize>>;
+
+/// The queries behind the views, reports and search, as of `today`
+fn queries(today: NaiveDate, search: &str) -> Vec<(&'static str, Query)> {
+    let week = ReportRange::week_of(today);
+    let month = ReportRange::month_of(today);
+    let year = ReportRange::year_of(today);
+    let search = search.to_string();
+    vec![
+        ("Running entry", Box::new(|conn| Ok(db::get_running_entry(conn)?.into_iter().count()))),
+        ("Today", Box::new(move |conn| Ok(db::get_entries_for_date(conn, today)?.len()))),
+        (
+            "This week",
+            Box::new(move |conn| Ok(db::get_entries_for_date_range(conn, week.start, week.end)?.len())),
+        ),
+        (
+            "This month",
+            Box::new(move |conn| Ok(db::get_entries_for_date_range(conn, month.start, month.end)?.len())),
+        ),
+        ("Recent entries", Box::new(|conn| Ok(db::get_recent_entries(conn, 50)?.len()))),
+        ("Search", Box::new(move |conn| Ok(db::search_entries(conn, &search, 50)?.len()))),
+        (
+            "Daily totals, this year",
+            Box::new(move |conn| Ok(db::get_daily_seconds_in_range(conn, year.start, year.end)?.len())),
+        ),
+        (
+            "Project totals, this year",
+            Box::new(move |conn| Ok(db::get_project_seconds_in_range(conn, year.start, year.end)?.len())),
+        ),
+    ]
+}
+
+/// Runs every query `runs` times against `conn`, in order
+pub fn run_benchmarks(
+    conn: &Connection,
+    today: NaiveDate,
+    runs: usize,
+    search: &str,
+) -> error::Result<Vec<QueryTiming>> {
+    let mut timings = Vec::new();
+    for (name, query) in queries(today, search) {
+        let mut durations = Vec::with_capacity(runs);
+        let mut rows = 0;
+        for _ in 0..runs.max(1) {
+            let started = Instant::now();
+            rows = query(conn)?;
+            durations.push(started.elapsed());
+        }
+        durations.sort();
+        timings.push(QueryTiming {
+            name,
+            rows,
+            fastest: durations[0],
+            median: durations[durations.len() / 2],
+            slowest: durations[durations.len() - 1],
+        });
+    }
+    Ok(timings)
+}
+
+/// Milliseconds with a fraction, such as "12.34"
+fn format_millis(duration: Duration) -> String {
+    format!("{:.2}", duration.as_secs_f64() * 1000.0)
+}
+
+/// Timings as an aligned table, in milliseconds
+pub fn format_timings(timings: &[QueryTiming]) -> String {
+    let width = timings.iter().map(|timing| timing.name.len()).max().unwrap_or(0).max(5);
+    let mut text = format!(
+        "{:<width$}  {:>8}  {:>9}  {:>9}  {:>9}\n",
+        "Query", "Rows", "Fastest", "Median", "Slowest"
+    );
+    for timing in timings {
+        text.push_str(&format!(
+            "{:<width$}  {:>8}  {:>9}  {:>9}  {:>9}\n",
+            timing.name,
+            timing.rows,
+            format_millis(timing.fastest),
+            format_millis(timing.median),
+            format_millis(timing.slowest)
+        ));
+    }
+    text.push_str("Times in milliseconds\n");
+    text
+}
+
+/// Opens the database the options name, seeding an in-memory one if asked
+fn open_source(source: &BenchSource, today: NaiveDate) -> error::Result<Connection> {
+    match source {
+        BenchSource::Database(path) => db::open_db_read_only(path),
+        BenchSource::Seeded(entries) => {
+            let conn = Connection::open_in_memory()?;
+            db::create_tables(&conn)?;
+            let options = SeedOptions {
+                db_path: PathBuf::from(":memory:"),
+                entries: *entries,
+                projects: 10,
+                years: 1,
+                seed: 1,
+            };
+            seed::seed_database(&conn, &options, today)?;
+            Ok(conn)
+        }
+    }
+}
+
+/// Runs the bench command with the arguments following it, returning the exit code
+pub fn run(args: &[String]) -> i32 {
+    let options = match BenchOptions::parse(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return 2;
+        }
+    };
+
+    let today = chrono::Local::now().date_naive();
+    let source = match options.source {
+        BenchSource::Database(ref path) => path.display().to_string(),
+        BenchSource::Seeded(entries) => format!("{} seeded entries in memory", entries),
+    };
+    let result = open_source(&options.source, today)
+        .and_then(|conn| run_benchmarks(&conn, today, options.runs, &options.search));
+    match result {
+        Ok(timings) => {
+            println!("Benchmarking {}, {} runs per query\n", source, options.runs);
+            print!("{}", format_timings(&timings));
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to benchmark {}: {}", source, e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let parsed = BenchOptions::parse(&args(&["--seeded", "5000", "--runs", "3", "--search", "invoices"])).unwrap();
+
+        assert_eq!(parsed.source, BenchSource::Seeded(5000));
+        assert_eq!(parsed.runs, 3);
+        assert_eq!(parsed.search, "invoices");
+        assert_eq!(
+            BenchOptions::parse(&args(&["--db", "/tmp/copy.db"])).unwrap().source,
+            BenchSource::Database(PathBuf::from("/tmp/copy.db"))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_arguments() {
+        assert!(BenchOptions::parse(&args(&["--db", "a.db", "--seeded", "10"])).is_err());
+        assert!(BenchOptions::parse(&args(&["--runs", "often"])).is_err());
+        assert!(BenchOptions::parse(&args(&["--verbose"])).is_err());
+    }
+
+    #[test]
+    fn test_run_benchmarks_on_seeded_database() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 14).unwrap();
+        let conn = open_source(&BenchSource::Seeded(500), today).unwrap();
+
+        let timings = run_benchmarks(&conn, today, 2, "review").unwrap();
+
+        assert_eq!(timings.len(), queries(today, "review").len());
+        for timing in &timings {
+            assert!(timing.fastest <= timing.median && timing.median <= timing.slowest);
+        }
+        let rows = |name: &str| timings.iter().find(|timing| timing.name == name).unwrap().rows;
+        assert!(rows("This month") > 0);
+        assert!(rows("Search") > 0);
+        assert_eq!(rows("Running entry"), 0);
+        assert!(format_timings(&timings).contains("Daily totals, this year"));
+    }
+}
@@ -1,7 +1,21 @@
-use chrono::{DateTime, NaiveDate, Utc};
-use rusqlite::{Connection, Result, params};
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use rusqlite::{Connection, OpenFlags, Row, params};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ffi::OsString;
 use std::fs;
-use std::path::PathBuf;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::absence::AbsenceReason;
+use crate::error::{Result, TimeTrackingError};
+use crate::profile;
+use crate::project_import::ProjectChange;
+use crate::reminder::Repeat;
+use crate::tags;
+
+#[cfg(test)]
+pub mod test_support;
 
 /// Represents a project in the time tracking system
 #[derive(Debug, Clone, PartialEq)]
@@ -10,6 +24,20 @@ pub struct Project {
     pub name: String,
     pub color: String,
     pub created_at: DateTime<Utc>,
+    /// Archived projects are hidden from the dropdown but keep their entries
+    pub archived: bool,
+    /// Deep work projects can silence notifications while they are tracked
+    pub deep_work: bool,
+    /// Code of the project in a project management tool, imported projects
+    /// are matched by it
+    pub code: Option<String>,
+    pub hourly_rate_cents: Option<i64>,
+    pub monthly_budget_minutes: Option<i64>,
+    /// Day the project is due, shown as the days left until it
+    pub deadline: Option<NaiveDate>,
+    /// Total time the project was expected to take, compared with the time
+    /// tracked on it in reports
+    pub estimate_seconds: Option<i64>,
 }
 
 /// Represents a time entry in the time tracking system
@@ -21,31 +49,221 @@ pub struct TimeEntry {
     pub start_time: DateTime<Utc>,
     pub end_time: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    /// True for entries created automatically (calendar, rules, watchers)
+    /// that have not been confirmed yet; they don't count toward totals
+    pub needs_review: bool,
+    /// Private entries count toward personal totals but are left out of
+    /// (or anonymized in) anything shared externally
+    pub private: bool,
 }
 
-/// Returns the path to the database file in XDG data directory
-pub fn get_db_path() -> PathBuf {
-    let data_dir = dirs::data_dir()
+/// A completed time entry to be inserted, e.g. from an import
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewEntry {
+    pub project_id: Option<i64>,
+    pub description: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+/// An entry of the review queue as confirmed, with the description and
+/// project it was given while reviewing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewedEntry {
+    pub id: i64,
+    pub project_id: Option<i64>,
+    pub description: String,
+}
+
+/// A description and project pinned for starting the timer with one click
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinnedTask {
+    pub id: i64,
+    pub description: String,
+    pub project_id: Option<i64>,
+}
+
+/// Reusable values pre-filled into the timer before starting it
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryTemplate {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub project_id: Option<i64>,
+    pub private: bool,
+}
+
+/// A notification shown on a schedule, offering to start the timer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub id: i64,
+    pub name: String,
+    pub repeat: Repeat,
+    /// Local time of day it fires at
+    pub time: NaiveTime,
+    /// Template the timer is started from, otherwise the name is the description
+    pub template_id: Option<i64>,
+    pub enabled: bool,
+}
+
+/// Part of a day spent away from work, such as a doctor's visit. Shown with
+/// the day's entries but never counted as worked time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Absence {
+    pub id: i64,
+    pub reason: AbsenceReason,
+    pub note: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+/// An event a plugin failed to take, kept to be sent again later
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboxItem {
+    pub id: i64,
+    pub plugin: String,
+    /// The event as the JSON line sent to the plugin
+    pub event: String,
+    /// How many times sending it failed
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: String,
+    /// Set once retrying is given up, until retried by hand
+    pub failed: bool,
+}
+
+/// Command line flag choosing the database file, e.g. one in a synced folder
+pub const DATABASE_FLAG: &str = "--database";
+
+/// Environment variable choosing the database file when the flag isn't given
+pub const DATABASE_ENV: &str = "TIME_TRACKING_DB";
+
+/// File name of the database, in the data directory or next to the
+/// executable for portable use
+const DB_FILE_NAME: &str = "time-tracking.db";
+
+/// Value of a command line flag given as `FLAG VALUE` or `FLAG=VALUE`
+pub fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next().cloned();
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Database file given with `--database PATH` or `--database=PATH`
+pub fn database_arg(args: &[String]) -> Option<PathBuf> {
+    flag_value(args, DATABASE_FLAG).map(PathBuf::from)
+}
+
+/// Database file chosen instead of the one in the data directory: the
+/// command line flag, then the environment variable, then a database next to
+/// the executable in `exe_dir`, which makes a portable install
+fn custom_db_path(args: &[String], env: Option<OsString>, exe_dir: Option<&Path>) -> Option<PathBuf> {
+    database_arg(args)
+        .or_else(|| env.filter(|value| !value.is_empty()).map(PathBuf::from))
+        .or_else(|| exe_dir.map(|dir| dir.join(DB_FILE_NAME)).filter(|path| path.is_file()))
+}
+
+/// Database file chosen with `--database`, `TIME_TRACKING_DB` or by placing
+/// it next to the executable. Profiles don't apply to it.
+pub fn chosen_db_path() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().collect();
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    custom_db_path(&args, std::env::var_os(DATABASE_ENV), exe_dir.as_deref())
+}
+
+/// Returns the app's directory in the platform's data directory:
+/// `$XDG_DATA_HOME` on Linux, `~/Library/Application Support` on macOS and
+/// `%APPDATA%` on Windows
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
-        .join("time-tracking");
+        .join("time-tracking")
+}
+
+/// Returns the path to the database file. Unless one is chosen with
+/// `chosen_db_path`, it is in the data directory, with a database of its own
+/// for each profile opened with `--profile`.
+pub fn get_db_path() -> PathBuf {
+    let path = chosen_db_path().unwrap_or_else(|| match profile::current() {
+        Some(name) => profile::db_path(&data_dir(), &name),
+        None => data_dir().join(DB_FILE_NAME),
+    });
 
-    fs::create_dir_all(&data_dir).expect("Failed to create data directory");
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).expect("Failed to create data directory");
+    }
 
-    data_dir.join("time-tracking.db")
+    path
 }
 
+/// How long a connection waits for another one, e.g. a second process
+/// writing, before failing with "database is locked"
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Initialize the database connection and create tables if they don't exist
 pub fn init_db() -> Result<Connection> {
-    let db_path = get_db_path();
-    let conn = Connection::open(&db_path)?;
+    open_db(&get_db_path())
+}
+
+/// Opens the database at the given path, creating tables and applying migrations.
+/// The write-ahead log lets snapshots and other processes read while the
+/// timer is started or stopped. With it, syncing only at checkpoints is
+/// still safe from corruption, at worst losing the last commits on power loss.
+pub fn open_db(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
 
     create_tables(&conn)?;
 
     Ok(conn)
 }
 
+/// Opens an existing database without creating tables or applying
+/// migrations, e.g. one written by a newer version of the app. Any write fails.
+pub fn open_db_read_only(path: &Path) -> Result<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+/// Opens a read-only connection for reports and exports, which may read
+/// for a long time. In write-ahead log mode its reads never block the
+/// connection starting and stopping the timer, nor wait behind it.
+pub fn open_snapshot(path: &Path) -> Result<Connection> {
+    let conn = open_db_read_only(path)?;
+    conn.pragma_update(None, "query_only", true)?;
+    Ok(conn)
+}
+
+/// Schema version of the database at the given path, 0 when it doesn't exist yet.
+/// Versions above [`SCHEMA_VERSION`] come from a newer version of the app.
+pub fn read_schema_version(path: &Path) -> Result<i64> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    schema_version(&open_db_read_only(path)?)
+}
+
+fn schema_version(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
 /// Create database tables if they don't exist
-fn create_tables(conn: &Connection) -> Result<()> {
+pub(crate) fn create_tables(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS projects (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -69,6 +287,195 @@ fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    run_migrations(conn)?;
+
+    Ok(())
+}
+
+/// Schema migrations applied on top of the base tables, in order.
+/// The number of applied migrations is stored in SQLite's `user_version` pragma.
+const MIGRATIONS: &[&str] = &[
+    // 1: review queue for automatically created entries
+    "ALTER TABLE time_entries ADD COLUMN needs_review INTEGER NOT NULL DEFAULT 0",
+    // 2: key/value store for user preferences
+    "CREATE TABLE settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    )",
+    // 3: archiving unused projects
+    "ALTER TABLE projects ADD COLUMN archived INTEGER NOT NULL DEFAULT 0",
+    // 4: private entries hidden from shared output
+    "ALTER TABLE time_entries ADD COLUMN private INTEGER NOT NULL DEFAULT 0",
+    // 5: full-text index of entry descriptions, kept in sync by triggers
+    "CREATE VIRTUAL TABLE entries_fts USING fts5(
+        description,
+        content = 'time_entries',
+        content_rowid = 'id',
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+    INSERT INTO entries_fts (entries_fts) VALUES ('rebuild');
+    CREATE TRIGGER entries_fts_insert AFTER INSERT ON time_entries BEGIN
+        INSERT INTO entries_fts (rowid, description) VALUES (new.id, new.description);
+    END;
+    CREATE TRIGGER entries_fts_delete AFTER DELETE ON time_entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, description)
+        VALUES ('delete', old.id, old.description);
+    END;
+    CREATE TRIGGER entries_fts_update AFTER UPDATE OF description ON time_entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, description)
+        VALUES ('delete', old.id, old.description);
+        INSERT INTO entries_fts (rowid, description) VALUES (new.id, new.description);
+    END;",
+    // 6: part-day absences, kept apart from worked time
+    "CREATE TABLE absences (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        reason TEXT NOT NULL,
+        note TEXT NOT NULL DEFAULT '',
+        start_time TEXT NOT NULL,
+        end_time TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )",
+    // 7: deep work projects, tracked with notifications silenced
+    "ALTER TABLE projects ADD COLUMN deep_work INTEGER NOT NULL DEFAULT 0",
+    // 8: codes, rates and budgets of projects imported from planning tools
+    "ALTER TABLE projects ADD COLUMN code TEXT;
+    ALTER TABLE projects ADD COLUMN hourly_rate_cents INTEGER;
+    ALTER TABLE projects ADD COLUMN monthly_budget_minutes INTEGER;",
+    // 9: project deadlines, as local dates
+    "ALTER TABLE projects ADD COLUMN deadline TEXT",
+    // 10: plugin events waiting to be sent again
+    "CREATE TABLE plugin_outbox (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        plugin TEXT NOT NULL,
+        event TEXT NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 1,
+        next_attempt_at TEXT NOT NULL,
+        last_error TEXT NOT NULL DEFAULT '',
+        failed INTEGER NOT NULL DEFAULT 0,
+        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )",
+    // 11: estimated durations of tasks, by description
+    "CREATE TABLE task_estimates (
+        description TEXT PRIMARY KEY COLLATE NOCASE,
+        seconds INTEGER NOT NULL
+    )",
+    // 12: estimated total duration of projects
+    "ALTER TABLE projects ADD COLUMN estimate_seconds INTEGER",
+    // 13: manual order of projects, 0 until the user reorders them
+    "ALTER TABLE projects ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0",
+    // 14: description and project pairs started with one click
+    "CREATE TABLE pinned_tasks (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        description TEXT NOT NULL,
+        project_id INTEGER REFERENCES projects(id) ON DELETE CASCADE,
+        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )",
+    // 15: named values pre-filled into the timer, kept when their project goes
+    "CREATE TABLE entry_templates (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL DEFAULT '',
+        project_id INTEGER REFERENCES projects(id) ON DELETE SET NULL,
+        private INTEGER NOT NULL DEFAULT 0,
+        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )",
+    // 16: recurring reminders, with the local time of day as HH:MM
+    "CREATE TABLE reminders (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        repeat TEXT NOT NULL,
+        time TEXT NOT NULL,
+        template_id INTEGER REFERENCES entry_templates(id) ON DELETE SET NULL,
+        enabled INTEGER NOT NULL DEFAULT 1,
+        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )",
+    // 17: timestamps in RFC 3339 instead of SQLite's "YYYY-MM-DD HH:MM:SS",
+    // which was read as UTC. Values that aren't timestamps are left for the
+    // integrity check. Column defaults still use datetime('now'), so inserts
+    // are converted by triggers, as are times written by other tools.
+    "UPDATE projects SET created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', created_at), created_at);
+    UPDATE time_entries SET
+        start_time = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', start_time), start_time),
+        end_time = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', end_time), end_time),
+        created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', created_at), created_at);
+    UPDATE absences SET
+        start_time = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', start_time), start_time),
+        end_time = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', end_time), end_time),
+        created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', created_at), created_at);
+    UPDATE plugin_outbox SET
+        next_attempt_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', next_attempt_at), next_attempt_at),
+        created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', created_at), created_at);
+    UPDATE pinned_tasks SET created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', created_at), created_at);
+    UPDATE entry_templates SET created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', created_at), created_at);
+    UPDATE reminders SET created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', created_at), created_at);
+    CREATE TRIGGER projects_timestamps AFTER INSERT ON projects BEGIN
+        UPDATE projects SET created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.created_at), new.created_at)
+        WHERE id = new.id;
+    END;
+    CREATE TRIGGER time_entries_timestamps AFTER INSERT ON time_entries BEGIN
+        UPDATE time_entries SET
+            start_time = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.start_time), new.start_time),
+            end_time = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.end_time), new.end_time),
+            created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.created_at), new.created_at)
+        WHERE id = new.id;
+    END;
+    CREATE TRIGGER absences_timestamps AFTER INSERT ON absences BEGIN
+        UPDATE absences SET
+            start_time = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.start_time), new.start_time),
+            end_time = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.end_time), new.end_time),
+            created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.created_at), new.created_at)
+        WHERE id = new.id;
+    END;
+    CREATE TRIGGER plugin_outbox_timestamps AFTER INSERT ON plugin_outbox BEGIN
+        UPDATE plugin_outbox SET
+            next_attempt_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.next_attempt_at), new.next_attempt_at),
+            created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.created_at), new.created_at)
+        WHERE id = new.id;
+    END;
+    CREATE TRIGGER pinned_tasks_timestamps AFTER INSERT ON pinned_tasks BEGIN
+        UPDATE pinned_tasks SET created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.created_at), new.created_at)
+        WHERE id = new.id;
+    END;
+    CREATE TRIGGER entry_templates_timestamps AFTER INSERT ON entry_templates BEGIN
+        UPDATE entry_templates SET created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.created_at), new.created_at)
+        WHERE id = new.id;
+    END;
+    CREATE TRIGGER reminders_timestamps AFTER INSERT ON reminders BEGIN
+        UPDATE reminders SET created_at = COALESCE(strftime('%Y-%m-%dT%H:%M:%SZ', new.created_at), new.created_at)
+        WHERE id = new.id;
+    END;",
+    // 18: indexes for date ranges, the running entry and project totals
+    "CREATE INDEX time_entries_start_time ON time_entries (start_time);
+    CREATE INDEX time_entries_end_time ON time_entries (end_time);
+    CREATE INDEX time_entries_project_id ON time_entries (project_id);",
+];
+
+/// Schema version this version of the app creates and understands
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Applies any migrations the database hasn't seen yet, each in its own
+/// transaction. Refuses databases migrated by a newer version of the app,
+/// whose data this version might damage.
+fn run_migrations(conn: &Connection) -> Result<()> {
+    let applied = schema_version(conn)?;
+    if applied > SCHEMA_VERSION {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+            Some(format!(
+                "database schema version {} is newer than the supported version {}",
+                applied, SCHEMA_VERSION
+            )),
+        )
+        .into());
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(applied.max(0) as usize) {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", (index + 1) as i64)?;
+        tx.commit()?;
+    }
+
     Ok(())
 }
 
@@ -81,46 +488,307 @@ pub fn create_project(conn: &Connection, name: &str, color: &str) -> Result<Proj
 
     let id = conn.last_insert_rowid();
 
-    conn.query_row(
-        "SELECT id, name, color, created_at FROM projects WHERE id = ?1",
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM projects WHERE id = ?1", PROJECT_COLUMNS),
         params![id],
-        |row| {
-            let created_at_str: String = row.get(3)?;
-            let created_at = DateTime::parse_from_rfc3339(&format!("{}Z", created_at_str.replace(' ', "T")))
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
-
-            Ok(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                created_at,
-            })
-        },
-    )
+        project_from_row,
+    )?)
 }
 
-/// Retrieves all projects from the database
+/// Retrieves all active (non-archived) projects from the database, in the
+/// order set in Manage Projects. Projects never reordered, such as new
+/// ones, come first by name.
 pub fn get_all_projects(conn: &Connection) -> Result<Vec<Project>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects WHERE archived = 0 ORDER BY sort_order, name",
+        PROJECT_COLUMNS
+    ))?;
+
+    let projects = stmt.query_map([], project_from_row)?;
+
+    Ok(projects.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Retrieves the active projects for the dropdown: the up to `limit`
+/// projects with the most entries started since `since` first, the more
+/// recently used first on a tie, then the rest in their usual order.
+/// Also returns how many projects lead the list as recently used.
+pub fn get_dropdown_projects(conn: &Connection, since: DateTime<Utc>, limit: usize) -> Result<(Vec<Project>, usize)> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, color, created_at FROM projects ORDER BY name"
+        "SELECT project_id FROM time_entries
+         JOIN projects ON projects.id = time_entries.project_id
+         WHERE projects.archived = 0 AND start_time >= ?1
+         GROUP BY project_id
+         ORDER BY COUNT(*) DESC, MAX(start_time) DESC
+         LIMIT ?2",
     )?;
+    let recent_ids = stmt
+        .query_map(
+            params![format_datetime(since), limit as i64],
+            |row| row.get::<_, i64>(0),
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut rest = get_all_projects(conn)?;
+    let mut projects = Vec::with_capacity(rest.len());
+    for id in &recent_ids {
+        if let Some(index) = rest.iter().position(|project| project.id == *id) {
+            projects.push(rest.remove(index));
+        }
+    }
+    let recent = projects.len();
+    projects.append(&mut rest);
+    Ok((projects, recent))
+}
 
-    let projects = stmt.query_map([], |row| {
-        let created_at_str: String = row.get(3)?;
-        let created_at = DateTime::parse_from_rfc3339(&format!("{}Z", created_at_str.replace(' ', "T")))
-            .map(|dt| dt.with_timezone(&Utc))
-            .unwrap_or_else(|_| Utc::now());
+/// Retrieves the archived projects, hidden from the dropdown
+pub fn get_archived_projects(conn: &Connection) -> Result<Vec<Project>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects WHERE archived = 1 ORDER BY name",
+        PROJECT_COLUMNS
+    ))?;
 
-        Ok(Project {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            color: row.get(2)?,
-            created_at,
-        })
-    })?;
+    let projects = stmt.query_map([], project_from_row)?;
+
+    Ok(projects.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Retrieves projects with a code, archived ones included
+pub fn get_coded_projects(conn: &Connection) -> Result<Vec<Project>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects WHERE code IS NOT NULL ORDER BY name",
+        PROJECT_COLUMNS
+    ))?;
+
+    let projects = stmt.query_map([], project_from_row)?;
+
+    Ok(projects.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Applies imported project changes in one transaction. Created projects
+/// take their colors from `colors` in turn.
+pub fn apply_project_changes(conn: &Connection, changes: &[ProjectChange], colors: &[&str]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let mut created = 0;
+    for change in changes {
+        match change {
+            ProjectChange::Create(planned) => {
+                tx.execute(
+                    "INSERT INTO projects (name, color, code, hourly_rate_cents, monthly_budget_minutes)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        planned.name,
+                        colors[created % colors.len()],
+                        planned.code,
+                        planned.hourly_rate_cents,
+                        planned.monthly_budget_minutes
+                    ],
+                )?;
+                created += 1;
+            }
+            ProjectChange::Update(id, planned) => {
+                tx.execute(
+                    "UPDATE projects SET name = ?1, code = ?2, hourly_rate_cents = ?3,
+                     monthly_budget_minutes = ?4 WHERE id = ?5",
+                    params![
+                        planned.name,
+                        planned.code,
+                        planned.hourly_rate_cents,
+                        planned.monthly_budget_minutes,
+                        id
+                    ],
+                )?;
+            }
+        }
+    }
+    Ok(tx.commit()?)
+}
+
+/// Retrieves active projects that have no time entries at all
+pub fn get_empty_projects(conn: &Connection) -> Result<Vec<Project>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects
+         WHERE archived = 0
+           AND NOT EXISTS (SELECT 1 FROM time_entries WHERE time_entries.project_id = projects.id)
+         ORDER BY name",
+        PROJECT_COLUMNS
+    ))?;
+
+    let projects = stmt.query_map([], project_from_row)?;
+
+    Ok(projects.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Retrieves active projects with no entry started since `cutoff`.
+/// Projects created after the cutoff and projects with a running entry are never included.
+pub fn get_unused_projects(conn: &Connection, cutoff: DateTime<Utc>) -> Result<Vec<Project>> {
+    let cutoff_str = format_datetime(cutoff);
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects
+         WHERE archived = 0
+           AND created_at < ?1
+           AND NOT EXISTS (
+               SELECT 1 FROM time_entries
+               WHERE time_entries.project_id = projects.id
+                 AND (start_time >= ?1 OR end_time IS NULL)
+           )
+         ORDER BY name",
+        PROJECT_COLUMNS
+    ))?;
+
+    let projects = stmt.query_map(params![cutoff_str], project_from_row)?;
+
+    Ok(projects.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Archives or unarchives projects in a single transaction
+pub fn set_projects_archived(conn: &Connection, ids: &[i64], archived: bool) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for id in ids {
+        tx.execute(
+            "UPDATE projects SET archived = ?1 WHERE id = ?2",
+            params![archived, id],
+        )?;
+    }
+    Ok(tx.commit()?)
+}
+
+/// Marks a project as deep work or ordinary work
+pub fn set_project_deep_work(conn: &Connection, id: i64, deep_work: bool) -> Result<()> {
+    conn.execute(
+        "UPDATE projects SET deep_work = ?1 WHERE id = ?2",
+        params![deep_work, id],
+    )?;
+    Ok(())
+}
+
+/// Sets or clears the day a project is due
+pub fn set_project_deadline(conn: &Connection, id: i64, deadline: Option<NaiveDate>) -> Result<()> {
+    conn.execute(
+        "UPDATE projects SET deadline = ?1 WHERE id = ?2",
+        params![deadline.map(|day| day.format("%Y-%m-%d").to_string()), id],
+    )?;
+    Ok(())
+}
+
+/// Sets or, with `None`, clears the estimated total duration of a project
+pub fn set_project_estimate(conn: &Connection, id: i64, seconds: Option<i64>) -> Result<()> {
+    conn.execute(
+        "UPDATE projects SET estimate_seconds = ?1 WHERE id = ?2",
+        params![seconds, id],
+    )?;
+    Ok(())
+}
+
+/// Moves active project `source_id` to the place of `target_id` in the
+/// order of `get_all_projects`, numbering every active project anew
+pub fn move_project(conn: &Connection, source_id: i64, target_id: i64) -> Result<()> {
+    let mut ids: Vec<i64> = get_all_projects(conn)?.iter().map(|p| p.id).collect();
+    let (Some(from), Some(to)) = (
+        ids.iter().position(|&id| id == source_id),
+        ids.iter().position(|&id| id == target_id),
+    ) else {
+        return Err(TimeTrackingError::NotFound("project"));
+    };
+    let moved = ids.remove(from);
+    ids.insert(to, moved);
+
+    let tx = conn.unchecked_transaction()?;
+    for (index, id) in ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE projects SET sort_order = ?1 WHERE id = ?2",
+            params![index as i64 + 1, id],
+        )?;
+    }
+    Ok(tx.commit()?)
+}
+
+/// Archives every project unused since `cutoff`, returning how many were archived
+pub fn archive_unused_projects(conn: &Connection, cutoff: DateTime<Utc>) -> Result<usize> {
+    let ids: Vec<i64> = get_unused_projects(conn, cutoff)?.iter().map(|p| p.id).collect();
+    set_projects_archived(conn, &ids, true)?;
+    Ok(ids.len())
+}
+
+/// Deletes several projects in a single transaction
+pub fn delete_projects(conn: &Connection, ids: &[i64]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for id in ids {
+        delete_project(&tx, *id)?;
+    }
+    Ok(tx.commit()?)
+}
+
+/// Counts the entries of a project
+pub fn count_project_entries(conn: &Connection, project_id: i64) -> Result<usize> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM time_entries WHERE project_id = ?1",
+        params![project_id],
+        |row| row.get(0),
+    )?)
+}
+
+/// The earliest entry of a project, or None when it has none
+pub fn get_first_project_entry(conn: &Connection, project_id: i64) -> Result<Option<TimeEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM time_entries
+         WHERE project_id = ?1
+         ORDER BY start_time, id
+         LIMIT 1",
+        ENTRY_COLUMNS
+    ))?;
+
+    let mut rows = stmt.query(params![project_id])?;
 
-    projects.collect()
+    match rows.next()? {
+        Some(row) => Ok(Some(entry_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Moves every entry of project `source_id` to `target_id`, or to no
+/// project, in one transaction. The source is kept. Returns the ids of the
+/// moved entries, so the move can be undone with `set_entries_project`.
+pub fn move_project_entries(conn: &Connection, source_id: i64, target_id: Option<i64>) -> Result<Vec<i64>> {
+    let tx = conn.unchecked_transaction()?;
+    let ids = {
+        let mut stmt = tx.prepare("SELECT id FROM time_entries WHERE project_id = ?1 ORDER BY id")?;
+        let ids = stmt.query_map(params![source_id], |row| row.get(0))?;
+        ids.collect::<rusqlite::Result<Vec<i64>>>()?
+    };
+    tx.execute(
+        "UPDATE time_entries SET project_id = ?1 WHERE project_id = ?2",
+        params![target_id, source_id],
+    )?;
+    tx.commit()?;
+    Ok(ids)
+}
+
+/// Moves every entry of project `source_id` to `target_id` and deletes the
+/// source, in one transaction. Returns how many entries were moved.
+pub fn merge_projects(conn: &Connection, source_id: i64, target_id: i64) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let moved = tx.execute(
+        "UPDATE time_entries SET project_id = ?1 WHERE project_id = ?2",
+        params![target_id, source_id],
+    )?;
+    // Pins of the source would go with it, so they follow its entries,
+    // unless the target has the same pin already
+    tx.execute(
+        "UPDATE pinned_tasks SET project_id = ?1
+         WHERE project_id = ?2
+           AND description NOT IN (SELECT description FROM pinned_tasks WHERE project_id = ?1)",
+        params![target_id, source_id],
+    )?;
+    tx.execute(
+        "UPDATE entry_templates SET project_id = ?1 WHERE project_id = ?2",
+        params![target_id, source_id],
+    )?;
+    delete_project(&tx, source_id)?;
+    tx.commit()?;
+    Ok(moved)
 }
 
 /// Deletes a project by ID
@@ -129,11 +797,89 @@ pub fn delete_project(conn: &Connection, id: i64) -> Result<()> {
     Ok(())
 }
 
-/// Helper function to parse SQLite datetime strings to DateTime<Utc>
-fn parse_datetime(datetime_str: &str) -> DateTime<Utc> {
-    DateTime::parse_from_rfc3339(&format!("{}Z", datetime_str.replace(' ', "T")))
-        .map(|dt| dt.with_timezone(&Utc))
-        .unwrap_or_else(|_| Utc::now())
+/// Format timestamps are stored in, RFC 3339 in UTC such as
+/// "2024-03-04T09:00:00Z". SQLite's `strftime()` takes it too, and stored
+/// timestamps sort in time order.
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// The current time in SQL, formatted like stored timestamps
+const NOW_SQL: &str = "strftime('%Y-%m-%dT%H:%M:%SZ', 'now')";
+
+/// Formats a time the way timestamps are stored
+pub fn format_datetime(time: DateTime<Utc>) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Parses a stored timestamp. Anything but RFC 3339 is an error, rather
+/// than a made up time that would corrupt durations.
+pub fn parse_datetime(datetime_str: &str) -> chrono::ParseResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(datetime_str).map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Reads the timestamp in column `index`, failing the row when it's malformed
+fn datetime_column(row: &Row, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let text: String = row.get(index)?;
+    parse_datetime(&text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+/// Like `datetime_column`, for a column that may be NULL
+fn optional_datetime_column(row: &Row, index: usize) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    match row.get_ref(index)? {
+        rusqlite::types::ValueRef::Null => Ok(None),
+        _ => datetime_column(row, index).map(Some),
+    }
+}
+
+/// Columns selected for every project query, in the order expected by `project_from_row`
+const PROJECT_COLUMNS: &str =
+    "id, name, color, created_at, archived, deep_work, code, hourly_rate_cents, monthly_budget_minutes, deadline, \
+     estimate_seconds";
+
+/// Maps a row selected with `PROJECT_COLUMNS` to a Project
+fn project_from_row(row: &Row) -> rusqlite::Result<Project> {
+    let deadline: Option<String> = row.get(9)?;
+
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        created_at: datetime_column(row, 3)?,
+        archived: row.get(4)?,
+        deep_work: row.get(5)?,
+        code: row.get(6)?,
+        hourly_rate_cents: row.get(7)?,
+        monthly_budget_minutes: row.get(8)?,
+        deadline: deadline.and_then(|day| NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()),
+        estimate_seconds: row.get(10)?,
+    })
+}
+
+/// Columns selected for every time entry query, in the order expected by `entry_from_row`
+const ENTRY_COLUMNS: &str =
+    "id, project_id, description, start_time, end_time, created_at, needs_review, private";
+
+/// `ENTRY_COLUMNS` prefixed with the table name, for queries joining projects
+fn qualified_entry_columns() -> String {
+    ENTRY_COLUMNS
+        .split(", ")
+        .map(|column| format!("time_entries.{}", column))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Maps a row selected with `ENTRY_COLUMNS` to a TimeEntry
+fn entry_from_row(row: &Row) -> rusqlite::Result<TimeEntry> {
+    Ok(TimeEntry {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        description: row.get(2)?,
+        start_time: datetime_column(row, 3)?,
+        end_time: optional_datetime_column(row, 4)?,
+        created_at: datetime_column(row, 5)?,
+        needs_review: row.get(6)?,
+        private: row.get(7)?,
+    })
 }
 
 /// Creates a new time entry with the given project_id, description, and start_time
@@ -143,7 +889,7 @@ pub fn create_entry(
     description: &str,
     start_time: DateTime<Utc>,
 ) -> Result<TimeEntry> {
-    let start_time_str = start_time.format("%Y-%m-%d %H:%M:%S").to_string();
+    let start_time_str = format_datetime(start_time);
 
     conn.execute(
         "INSERT INTO time_entries (project_id, description, start_time) VALUES (?1, ?2, ?3)",
@@ -152,29 +898,21 @@ pub fn create_entry(
 
     let id = conn.last_insert_rowid();
 
-    conn.query_row(
-        "SELECT id, project_id, description, start_time, end_time, created_at FROM time_entries WHERE id = ?1",
+    get_entry_by_id(conn, id)
+}
+
+/// Gets a time entry by ID
+pub fn get_entry_by_id(conn: &Connection, id: i64) -> Result<TimeEntry> {
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM time_entries WHERE id = ?1", ENTRY_COLUMNS),
         params![id],
-        |row| {
-            let start_time_str: String = row.get(3)?;
-            let end_time_str: Option<String> = row.get(4)?;
-            let created_at_str: String = row.get(5)?;
-
-            Ok(TimeEntry {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                description: row.get(2)?,
-                start_time: parse_datetime(&start_time_str),
-                end_time: end_time_str.map(|s| parse_datetime(&s)),
-                created_at: parse_datetime(&created_at_str),
-            })
-        },
-    )
+        entry_from_row,
+    )?)
 }
 
 /// Stops a time entry by setting its end_time
 pub fn stop_entry(conn: &Connection, id: i64, end_time: DateTime<Utc>) -> Result<()> {
-    let end_time_str = end_time.format("%Y-%m-%d %H:%M:%S").to_string();
+    let end_time_str = format_datetime(end_time);
 
     conn.execute(
         "UPDATE time_entries SET end_time = ?1 WHERE id = ?2",
@@ -184,131 +922,968 @@ pub fn stop_entry(conn: &Connection, id: i64, end_time: DateTime<Utc>) -> Result
     Ok(())
 }
 
+/// Stops an entry at `end_time` and logs `follow_up`, what happened since,
+/// in one transaction so neither happens without the other
+pub fn stop_entry_with_follow_up(
+    conn: &Connection,
+    id: i64,
+    end_time: DateTime<Utc>,
+    follow_up: Option<&NewEntry>,
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    stop_entry(&tx, id, end_time)?;
+    if let Some(follow_up) = follow_up {
+        create_completed_entry(&tx, follow_up)?;
+    }
+    Ok(tx.commit()?)
+}
+
 /// Gets the currently running time entry (entry with null end_time)
 pub fn get_running_entry(conn: &Connection) -> Result<Option<TimeEntry>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, description, start_time, end_time, created_at
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
          FROM time_entries
-         WHERE end_time IS NULL
-         ORDER BY start_time DESC
-         LIMIT 1"
-    )?;
+         WHERE end_time IS NULL AND needs_review = 0
+         ORDER BY start_time DESC, id DESC
+         LIMIT 1",
+        ENTRY_COLUMNS
+    ))?;
 
     let mut rows = stmt.query([])?;
 
     match rows.next()? {
-        Some(row) => {
-            let start_time_str: String = row.get(3)?;
-            let end_time_str: Option<String> = row.get(4)?;
-            let created_at_str: String = row.get(5)?;
-
-            Ok(Some(TimeEntry {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                description: row.get(2)?,
-                start_time: parse_datetime(&start_time_str),
-                end_time: end_time_str.map(|s| parse_datetime(&s)),
-                created_at: parse_datetime(&created_at_str),
-            }))
-        }
+        Some(row) => Ok(Some(entry_from_row(row)?)),
         None => Ok(None),
     }
 }
 
-/// Gets all time entries for a specific date
-pub fn get_entries_for_date(conn: &Connection, date: NaiveDate) -> Result<Vec<TimeEntry>> {
-    let date_str = date.format("%Y-%m-%d").to_string();
+/// The earliest entry overlapping `start_time` to `end_time`, a running
+/// entry lasting until now. Entries merely touching the range don't count.
+pub fn find_overlapping_entry(
+    conn: &Connection,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> Result<Option<TimeEntry>> {
+    find_overlapping_entry_except(conn, None, start_time, end_time)
+}
 
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, description, start_time, end_time, created_at
+/// Like `find_overlapping_entry`, ignoring the entry `except_id`, e.g. the
+/// one whose times are being changed
+pub fn find_overlapping_entry_except(
+    conn: &Connection,
+    except_id: Option<i64>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> Result<Option<TimeEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
          FROM time_entries
-         WHERE date(start_time) = ?1
-         ORDER BY start_time DESC"
+         WHERE start_time < ?2 AND COALESCE(end_time, {}) > ?1 AND needs_review = 0
+           AND id IS NOT ?3
+         ORDER BY start_time
+         LIMIT 1",
+        ENTRY_COLUMNS, NOW_SQL
+    ))?;
+
+    let mut rows = stmt.query(params![
+        format_datetime(start_time),
+        format_datetime(end_time),
+        except_id
+    ])?;
+
+    match rows.next()? {
+        Some(row) => Ok(Some(entry_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Marks an entry as private or shared
+pub fn set_entry_private(conn: &Connection, id: i64, private: bool) -> Result<()> {
+    conn.execute(
+        "UPDATE time_entries SET private = ?1 WHERE id = ?2",
+        params![private, id],
     )?;
+    Ok(())
+}
 
-    let entries = stmt.query_map(params![date_str], |row| {
-        let start_time_str: String = row.get(3)?;
-        let end_time_str: Option<String> = row.get(4)?;
-        let created_at_str: String = row.get(5)?;
+/// Moves an entry to another project, e.g. the running entry when the wrong project was picked
+pub fn set_entry_project(conn: &Connection, id: i64, project_id: Option<i64>) -> Result<()> {
+    conn.execute(
+        "UPDATE time_entries SET project_id = ?1 WHERE id = ?2",
+        params![project_id, id],
+    )?;
+    Ok(())
+}
 
-        Ok(TimeEntry {
-            id: row.get(0)?,
-            project_id: row.get(1)?,
-            description: row.get(2)?,
-            start_time: parse_datetime(&start_time_str),
-            end_time: end_time_str.map(|s| parse_datetime(&s)),
-            created_at: parse_datetime(&created_at_str),
-        })
-    })?;
+/// Moves several entries to a project at once, all or none of them
+pub fn set_entries_project(conn: &Connection, ids: &[i64], project_id: Option<i64>) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for id in ids {
+        tx.execute(
+            "UPDATE time_entries SET project_id = ?1 WHERE id = ?2",
+            params![project_id, id],
+        )?;
+    }
+    Ok(tx.commit()?)
+}
 
-    entries.collect()
+/// Finds entries without a project whose description contains `keyword`,
+/// ignoring case, newest first
+pub fn get_unassigned_entries_matching(conn: &Connection, keyword: &str) -> Result<Vec<TimeEntry>> {
+    let keyword = keyword.trim().to_lowercase();
+    if keyword.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM time_entries
+         WHERE project_id IS NULL AND needs_review = 0 AND instr(lower(description), ?1) > 0
+         ORDER BY start_time DESC, id DESC",
+        ENTRY_COLUMNS
+    ))?;
+
+    let entries = stmt.query_map(params![keyword], entry_from_row)?;
+
+    Ok(entries.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Start of the local day `day` in the given time zone. Where a DST change
+/// skips midnight, the day starts at the first hour that exists.
+pub fn local_day_start<Tz: TimeZone>(day: NaiveDate, tz: &Tz) -> DateTime<Utc> {
+    (0..24)
+        .find_map(|hour| tz.from_local_datetime(&day.and_hms_opt(hour, 0, 0)?).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| day.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
+
+/// Stored timestamps bounding the local days from `start_date` through
+/// `end_date`, to select entries with `start_time >= ?1 AND start_time < ?2`.
+/// Days shortened or lengthened by DST changes are covered exactly.
+fn local_days_bounds<Tz: TimeZone>(start_date: NaiveDate, end_date: NaiveDate, tz: &Tz) -> (String, String) {
+    (
+        format_datetime(local_day_start(start_date, tz)),
+        format_datetime(local_day_start(end_date + Days::new(1), tz)),
+    )
+}
+
+/// Gets all time entries started on a specific local date
+pub fn get_entries_for_date(conn: &Connection, date: NaiveDate) -> Result<Vec<TimeEntry>> {
+    get_entries_for_date_range(conn, date, date)
 }
 
-/// Gets all time entries for a date range (inclusive)
+/// Gets all time entries started in a range of local dates (inclusive)
 pub fn get_entries_for_date_range(
     conn: &Connection,
     start_date: NaiveDate,
     end_date: NaiveDate,
 ) -> Result<Vec<TimeEntry>> {
-    let start_date_str = start_date.format("%Y-%m-%d").to_string();
-    let end_date_str = end_date.format("%Y-%m-%d").to_string();
+    entries_for_days(conn, start_date, end_date, &Local)
+}
 
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, description, start_time, end_time, created_at
+/// `get_entries_for_date_range` with days in the given time zone
+fn entries_for_days<Tz: TimeZone>(
+    conn: &Connection,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    tz: &Tz,
+) -> Result<Vec<TimeEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
          FROM time_entries
-         WHERE date(start_time) >= ?1 AND date(start_time) <= ?2
-         ORDER BY start_time DESC"
-    )?;
+         WHERE start_time >= ?1 AND start_time < ?2 AND needs_review = 0
+         ORDER BY start_time DESC",
+        ENTRY_COLUMNS
+    ))?;
 
-    let entries = stmt.query_map(params![start_date_str, end_date_str], |row| {
-        let start_time_str: String = row.get(3)?;
-        let end_time_str: Option<String> = row.get(4)?;
-        let created_at_str: String = row.get(5)?;
-
-        Ok(TimeEntry {
-            id: row.get(0)?,
-            project_id: row.get(1)?,
-            description: row.get(2)?,
-            start_time: parse_datetime(&start_time_str),
-            end_time: end_time_str.map(|s| parse_datetime(&s)),
-            created_at: parse_datetime(&created_at_str),
-        })
-    })?;
+    let (start, end) = local_days_bounds(start_date, end_date, tz);
+    let entries = stmt.query_map(params![start, end], entry_from_row)?;
 
-    entries.collect()
+    Ok(entries.collect::<rusqlite::Result<_>>()?)
 }
 
-/// Deletes a time entry by ID
-pub fn delete_entry(conn: &Connection, id: i64) -> Result<()> {
-    conn.execute("DELETE FROM time_entries WHERE id = ?1", params![id])?;
-    Ok(())
+/// Gets up to `limit` entries, newest first, that come after `before` in that
+/// order (keyset pagination on start time and id). `None` starts from the
+/// newest entry; pass the last entry of a page to get the next one.
+pub fn get_entries_page(
+    conn: &Connection,
+    before: Option<&TimeEntry>,
+    limit: usize,
+) -> Result<Vec<TimeEntry>> {
+    let (before_start, before_id) = match before {
+        Some(entry) => (
+            Some(format_datetime(entry.start_time)),
+            entry.id,
+        ),
+        None => (None, 0),
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM time_entries
+         WHERE needs_review = 0
+           AND (?1 IS NULL OR start_time < ?1 OR (start_time = ?1 AND id < ?2))
+         ORDER BY start_time DESC, id DESC
+         LIMIT ?3",
+        ENTRY_COLUMNS
+    ))?;
+
+    let entries = stmt.query_map(params![before_start, before_id, limit as i64], entry_from_row)?;
+
+    Ok(entries.collect::<rusqlite::Result<_>>()?)
 }
 
-/// Gets a project by ID
-pub fn get_project_by_id(conn: &Connection, id: i64) -> Result<Option<Project>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, name, color, created_at FROM projects WHERE id = ?1"
-    )?;
+/// Gets the latest entry of each description and project pair, newest first,
+/// skipping entries without a description. Used to offer restarting recent work.
+pub fn get_recent_entries(conn: &Connection, limit: usize) -> Result<Vec<TimeEntry>> {
+    // SQLite takes the bare columns from the row holding MAX(start_time)
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, MAX(start_time)
+         FROM time_entries
+         WHERE needs_review = 0 AND trim(description) != ''
+         GROUP BY description, project_id
+         ORDER BY start_time DESC
+         LIMIT ?1",
+        ENTRY_COLUMNS
+    ))?;
 
-    let mut rows = stmt.query(params![id])?;
+    let entries = stmt.query_map(params![limit as i64], entry_from_row)?;
 
-    match rows.next()? {
-        Some(row) => {
-            let created_at_str: String = row.get(3)?;
-            Ok(Some(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                created_at: parse_datetime(&created_at_str),
-            }))
-        }
-        None => Ok(None),
+    Ok(entries.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Every tag written in an entry description, sorted
+pub fn get_tags(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT description FROM time_entries WHERE instr(description, '#') > 0")?;
+    let mut tags = BTreeSet::new();
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        tags.extend(tags::entry_tags(&row.get::<_, String>(0)?));
     }
+    Ok(tags.into_iter().collect())
 }
 
-#[cfg(test)]
+/// The entry that stopped last, to continue it, or None before any stopped
+pub fn get_last_stopped_entry(conn: &Connection) -> Result<Option<TimeEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM time_entries
+         WHERE end_time IS NOT NULL AND needs_review = 0
+         ORDER BY end_time DESC, id DESC
+         LIMIT 1",
+        ENTRY_COLUMNS
+    ))?;
+
+    let mut rows = stmt.query([])?;
+
+    match rows.next()? {
+        Some(row) => Ok(Some(entry_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Counts the entries of a range of local days, for progress reporting
+pub fn count_entries_in_range(conn: &Connection, start_date: NaiveDate, end_date: NaiveDate) -> Result<usize> {
+    let (start, end) = local_days_bounds(start_date, end_date, &Local);
+    let count = conn.query_row(
+        "SELECT COUNT(*) FROM time_entries
+         WHERE start_time >= ?1 AND start_time < ?2 AND needs_review = 0",
+        params![start, end],
+        |row| row.get::<_, i64>(0),
+    )?;
+    Ok(count as usize)
+}
+
+/// Seconds of an entry in SQL, running entries counted until now
+const ENTRY_SECONDS_SQL: &str =
+    "strftime('%s', COALESCE(end_time, 'now')) - strftime('%s', start_time)";
+
+/// Unrounded tracked seconds per local day of a range, in day order. Only
+/// the start and length of each entry are loaded, summed by the local day
+/// they start on. Days without entries are left out.
+pub fn get_daily_seconds_in_range(
+    conn: &Connection,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<Vec<(NaiveDate, i64)>> {
+    daily_seconds_for_days(conn, start_date, end_date, &Local)
+}
+
+/// `get_daily_seconds_in_range` with days in the given time zone
+fn daily_seconds_for_days<Tz: TimeZone>(
+    conn: &Connection,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    tz: &Tz,
+) -> Result<Vec<(NaiveDate, i64)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT start_time, {}
+         FROM time_entries
+         WHERE start_time >= ?1 AND start_time < ?2 AND needs_review = 0",
+        ENTRY_SECONDS_SQL
+    ))?;
+
+    let (start, end) = local_days_bounds(start_date, end_date, tz);
+    let mut rows = stmt.query(params![start, end])?;
+    let mut days = BTreeMap::new();
+    while let Some(row) = rows.next()? {
+        let day = datetime_column(row, 0)?.with_timezone(tz).date_naive();
+        *days.entry(day).or_insert(0) += row.get::<_, i64>(1)?;
+    }
+    Ok(days.into_iter().collect())
+}
+
+/// Unrounded tracked seconds per project of a range of local days, most tracked first,
+/// summed by SQLite. Entries without a project are summed under `None`.
+pub fn get_project_seconds_in_range(
+    conn: &Connection,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<Vec<(Option<i64>, i64)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT project_id, SUM({}) AS seconds
+         FROM time_entries
+         WHERE start_time >= ?1 AND start_time < ?2 AND needs_review = 0
+         GROUP BY project_id
+         ORDER BY seconds DESC",
+        ENTRY_SECONDS_SQL
+    ))?;
+    let (start, end) = local_days_bounds(start_date, end_date, &Local);
+    let projects = stmt.query_map(params![start, end], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(projects.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Unrounded tracked seconds per project over all time, summed by SQLite
+pub fn get_project_total_seconds(conn: &Connection) -> Result<HashMap<i64, i64>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT project_id, SUM({})
+         FROM time_entries
+         WHERE project_id IS NOT NULL AND needs_review = 0
+         GROUP BY project_id",
+        ENTRY_SECONDS_SQL
+    ))?;
+    let projects = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(projects.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Calls `f` with each entry of a range of local days and its project name, oldest
+/// first, reading rows one at a time so large ranges aren't held in memory.
+/// Stops early when `f` returns `ControlFlow::Break`; returns whether all rows were seen.
+pub fn for_each_entry_in_range(
+    conn: &Connection,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    mut f: impl FnMut(&TimeEntry, Option<&str>) -> ControlFlow<()>,
+) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, projects.name
+         FROM time_entries
+         LEFT JOIN projects ON projects.id = time_entries.project_id
+         WHERE time_entries.start_time >= ?1 AND time_entries.start_time < ?2
+           AND time_entries.needs_review = 0
+         ORDER BY time_entries.start_time, time_entries.id",
+        qualified_entry_columns()
+    ))?;
+
+    let (start, end) = local_days_bounds(start_date, end_date, &Local);
+    let mut rows = stmt.query(params![start, end])?;
+    while let Some(row) = rows.next()? {
+        let entry = entry_from_row(row)?;
+        let project_name: Option<String> = row.get(8)?;
+        if f(&entry, project_name.as_deref()).is_break() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Splits search text into words, each quoted as an FTS5 prefix query so
+/// punctuation in the text can't be read as query syntax
+fn search_terms(text: &str) -> Vec<(String, String)> {
+    text.split_whitespace()
+        .map(|word| {
+            (
+                format!("\"{}\"*", word.replace('"', "\"\"")),
+                word.to_lowercase(),
+            )
+        })
+        .collect()
+}
+
+/// Finds entries where every word of `text` starts a word of the description
+/// or appears in the project name, newest first, at most `limit` of them
+pub fn search_entries(conn: &Connection, text: &str, limit: usize) -> Result<Vec<TimeEntry>> {
+    let terms = search_terms(text);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut conditions = String::new();
+    let mut values: Vec<String> = Vec::new();
+    for (fts_query, word) in terms {
+        conditions.push_str(&format!(
+            " AND (time_entries.id IN (SELECT rowid FROM entries_fts WHERE entries_fts MATCH ?{})
+                   OR instr(lower(projects.name), ?{}) > 0)",
+            values.len() + 1,
+            values.len() + 2
+        ));
+        values.push(fts_query);
+        values.push(word);
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM time_entries
+         LEFT JOIN projects ON projects.id = time_entries.project_id
+         WHERE time_entries.needs_review = 0{}
+         ORDER BY time_entries.start_time DESC, time_entries.id DESC
+         LIMIT {}",
+        qualified_entry_columns(),
+        conditions,
+        limit
+    ))?;
+
+    let entries = stmt.query_map(rusqlite::params_from_iter(values), entry_from_row)?;
+
+    Ok(entries.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Deletes a time entry by ID
+pub fn delete_entry(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM time_entries WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Puts a deleted entry back with its id and every field it had, for undo
+pub fn restore_entry(conn: &Connection, entry: &TimeEntry) -> Result<()> {
+    conn.execute(
+        &format!("INSERT INTO time_entries ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)", ENTRY_COLUMNS),
+        params![
+            entry.id,
+            entry.project_id,
+            entry.description,
+            format_datetime(entry.start_time),
+            entry.end_time.map(format_datetime),
+            format_datetime(entry.created_at),
+            entry.needs_review,
+            entry.private
+        ],
+    )?;
+    Ok(())
+}
+
+/// Maps a row of `id, reason, note, start_time, end_time` to an Absence
+fn absence_from_row(row: &Row) -> rusqlite::Result<Absence> {
+    let reason: String = row.get(1)?;
+
+    Ok(Absence {
+        id: row.get(0)?,
+        reason: AbsenceReason::parse(&reason),
+        note: row.get(2)?,
+        start_time: datetime_column(row, 3)?,
+        end_time: datetime_column(row, 4)?,
+    })
+}
+
+/// Records an absence between two times
+pub fn create_absence(
+    conn: &Connection,
+    reason: AbsenceReason,
+    note: &str,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> Result<Absence> {
+    conn.execute(
+        "INSERT INTO absences (reason, note, start_time, end_time) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            reason.as_str(),
+            note,
+            format_datetime(start_time),
+            format_datetime(end_time)
+        ],
+    )?;
+
+    Ok(conn.query_row(
+        "SELECT id, reason, note, start_time, end_time FROM absences WHERE id = ?1",
+        params![conn.last_insert_rowid()],
+        absence_from_row,
+    )?)
+}
+
+/// Gets the absences starting on a specific local date, latest first like entries
+pub fn get_absences_for_date(conn: &Connection, date: NaiveDate) -> Result<Vec<Absence>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, reason, note, start_time, end_time
+         FROM absences
+         WHERE start_time >= ?1 AND start_time < ?2
+         ORDER BY start_time DESC",
+    )?;
+
+    let (start, end) = local_days_bounds(date, date, &Local);
+    let absences = stmt.query_map(params![start, end], absence_from_row)?;
+
+    Ok(absences.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Deletes an absence by ID
+pub fn delete_absence(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM absences WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Creates a completed time entry with both start and end times. An entry
+/// not ending after it starts is refused.
+pub fn create_completed_entry(conn: &Connection, entry: &NewEntry) -> Result<TimeEntry> {
+    if entry.end_time <= entry.start_time {
+        return Err(TimeTrackingError::Validation("The entry must end after it starts".to_string()));
+    }
+    let start_time_str = format_datetime(entry.start_time);
+    let end_time_str = format_datetime(entry.end_time);
+
+    conn.execute(
+        "INSERT INTO time_entries (project_id, description, start_time, end_time)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![entry.project_id, entry.description, start_time_str, end_time_str],
+    )?;
+
+    get_entry_by_id(conn, conn.last_insert_rowid())
+}
+
+/// Creates several completed entries in a single transaction.
+/// Either all entries are created or none are.
+pub fn create_completed_entries(conn: &Connection, entries: &[NewEntry]) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    for entry in entries {
+        create_completed_entry(&tx, entry)?;
+    }
+    tx.commit()?;
+    Ok(entries.len())
+}
+
+/// Creates a completed entry that must be reviewed before it counts toward totals.
+/// Used by integrations and imports that add entries without user interaction.
+pub fn create_review_entry(
+    conn: &Connection,
+    project_id: Option<i64>,
+    description: &str,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> Result<TimeEntry> {
+    let start_time_str = format_datetime(start_time);
+    let end_time_str = format_datetime(end_time);
+
+    conn.execute(
+        "INSERT INTO time_entries (project_id, description, start_time, end_time, needs_review)
+         VALUES (?1, ?2, ?3, ?4, 1)",
+        params![project_id, description, start_time_str, end_time_str],
+    )?;
+
+    get_entry_by_id(conn, conn.last_insert_rowid())
+}
+
+/// Creates several completed entries in the review queue in a single
+/// transaction. Either all entries are created or none are.
+pub fn create_review_entries(conn: &Connection, entries: &[NewEntry]) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    for entry in entries {
+        if entry.end_time <= entry.start_time {
+            return Err(TimeTrackingError::Validation("The entry must end after it starts".to_string()));
+        }
+        create_review_entry(&tx, entry.project_id, &entry.description, entry.start_time, entry.end_time)?;
+    }
+    tx.commit()?;
+    Ok(entries.len())
+}
+
+/// Gets all entries waiting in the review queue, most recent first
+pub fn get_review_entries(conn: &Connection) -> Result<Vec<TimeEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM time_entries
+         WHERE needs_review = 1
+         ORDER BY start_time DESC",
+        ENTRY_COLUMNS
+    ))?;
+
+    let entries = stmt.query_map([], entry_from_row)?;
+
+    Ok(entries.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Counts the entries waiting in the review queue
+pub fn count_review_entries(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM time_entries WHERE needs_review = 1",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Confirms reviewed entries with their edited details so they count
+/// toward totals, in a single transaction. Either all entries are
+/// confirmed or none are.
+pub fn confirm_entries(conn: &Connection, entries: &[ReviewedEntry]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for entry in entries {
+        tx.execute(
+            "UPDATE time_entries SET project_id = ?1, description = ?2, needs_review = 0 WHERE id = ?3",
+            params![entry.project_id, entry.description, entry.id],
+        )?;
+    }
+    Ok(tx.commit()?)
+}
+
+/// Rejects entries from the review queue, deleting them
+pub fn reject_entries(conn: &Connection, ids: &[i64]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for id in ids {
+        tx.execute(
+            "DELETE FROM time_entries WHERE id = ?1 AND needs_review = 1",
+            params![id],
+        )?;
+    }
+    Ok(tx.commit()?)
+}
+
+/// Updates the description and project of a time entry
+pub fn update_entry_details(
+    conn: &Connection,
+    id: i64,
+    project_id: Option<i64>,
+    description: &str,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE time_entries SET project_id = ?1, description = ?2 WHERE id = ?3",
+        params![project_id, description, id],
+    )?;
+    Ok(())
+}
+
+/// Retrieves the pinned tasks, in the order they were pinned
+pub fn get_pinned_tasks(conn: &Connection) -> Result<Vec<PinnedTask>> {
+    let mut stmt = conn.prepare("SELECT id, description, project_id FROM pinned_tasks ORDER BY id")?;
+    let tasks = stmt.query_map([], |row| {
+        Ok(PinnedTask {
+            id: row.get(0)?,
+            description: row.get(1)?,
+            project_id: row.get(2)?,
+        })
+    })?;
+    Ok(tasks.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Finds the pin of this description and project, if pinned
+pub fn find_pinned_task(conn: &Connection, description: &str, project_id: Option<i64>) -> Result<Option<PinnedTask>> {
+    Ok(get_pinned_tasks(conn)?
+        .into_iter()
+        .find(|task| task.description == description && task.project_id == project_id))
+}
+
+/// Pins a description and project, returning the existing pin when they
+/// are pinned already
+pub fn pin_task(conn: &Connection, description: &str, project_id: Option<i64>) -> Result<PinnedTask> {
+    if let Some(task) = find_pinned_task(conn, description, project_id)? {
+        return Ok(task);
+    }
+    conn.execute(
+        "INSERT INTO pinned_tasks (description, project_id) VALUES (?1, ?2)",
+        params![description, project_id],
+    )?;
+    Ok(PinnedTask {
+        id: conn.last_insert_rowid(),
+        description: description.to_string(),
+        project_id,
+    })
+}
+
+/// Removes a pinned task
+pub fn unpin_task(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM pinned_tasks WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Retrieves the entry templates, ordered by name
+pub fn get_templates(conn: &Connection) -> Result<Vec<EntryTemplate>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, description, project_id, private FROM entry_templates
+         ORDER BY name COLLATE NOCASE, id",
+    )?;
+    let templates = stmt.query_map([], |row| {
+        Ok(EntryTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            project_id: row.get(3)?,
+            private: row.get(4)?,
+        })
+    })?;
+    Ok(templates.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Creates an entry template
+pub fn create_template(
+    conn: &Connection,
+    name: &str,
+    description: &str,
+    project_id: Option<i64>,
+    private: bool,
+) -> Result<EntryTemplate> {
+    conn.execute(
+        "INSERT INTO entry_templates (name, description, project_id, private) VALUES (?1, ?2, ?3, ?4)",
+        params![name, description, project_id, private],
+    )?;
+    Ok(EntryTemplate {
+        id: conn.last_insert_rowid(),
+        name: name.to_string(),
+        description: description.to_string(),
+        project_id,
+        private,
+    })
+}
+
+/// Saves the name and values of an existing entry template
+pub fn update_template(conn: &Connection, template: &EntryTemplate) -> Result<()> {
+    let updated = conn.execute(
+        "UPDATE entry_templates SET name = ?1, description = ?2, project_id = ?3, private = ?4 WHERE id = ?5",
+        params![template.name, template.description, template.project_id, template.private, template.id],
+    )?;
+    if updated == 0 {
+        return Err(TimeTrackingError::NotFound("template"));
+    }
+    Ok(())
+}
+
+/// Deletes an entry template
+pub fn delete_template(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM entry_templates WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Format of a reminder's time of day as stored
+const REMINDER_TIME_FORMAT: &str = "%H:%M";
+
+/// Retrieves the reminders, ordered by time of day
+pub fn get_reminders(conn: &Connection) -> Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, repeat, time, template_id, enabled FROM reminders ORDER BY time, id",
+    )?;
+    let reminders = stmt.query_map([], |row| {
+        let repeat: String = row.get(2)?;
+        let time: String = row.get(3)?;
+        Ok(Reminder {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            repeat: Repeat::parse(&repeat),
+            time: NaiveTime::parse_from_str(&time, REMINDER_TIME_FORMAT).unwrap_or_default(),
+            template_id: row.get(4)?,
+            enabled: row.get(5)?,
+        })
+    })?;
+    Ok(reminders.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Creates an enabled reminder
+pub fn create_reminder(
+    conn: &Connection,
+    name: &str,
+    repeat: Repeat,
+    time: NaiveTime,
+    template_id: Option<i64>,
+) -> Result<Reminder> {
+    conn.execute(
+        "INSERT INTO reminders (name, repeat, time, template_id) VALUES (?1, ?2, ?3, ?4)",
+        params![name, repeat.as_str(), time.format(REMINDER_TIME_FORMAT).to_string(), template_id],
+    )?;
+    Ok(Reminder {
+        id: conn.last_insert_rowid(),
+        name: name.to_string(),
+        repeat,
+        time,
+        template_id,
+        enabled: true,
+    })
+}
+
+/// Saves the schedule, template and whether an existing reminder is enabled
+pub fn update_reminder(conn: &Connection, reminder: &Reminder) -> Result<()> {
+    let updated = conn.execute(
+        "UPDATE reminders SET name = ?1, repeat = ?2, time = ?3, template_id = ?4, enabled = ?5 WHERE id = ?6",
+        params![
+            reminder.name,
+            reminder.repeat.as_str(),
+            reminder.time.format(REMINDER_TIME_FORMAT).to_string(),
+            reminder.template_id,
+            reminder.enabled,
+            reminder.id
+        ],
+    )?;
+    if updated == 0 {
+        return Err(TimeTrackingError::NotFound("reminder"));
+    }
+    Ok(())
+}
+
+/// Deletes a reminder
+pub fn delete_reminder(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM reminders WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Gets a setting value by key
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
+    let mut rows = stmt.query(params![key])?;
+
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+/// Sets a setting value, replacing any previous value for the key
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+/// Sets or, with `None`, clears the estimated duration of the task with
+/// this description. Descriptions are matched ignoring case.
+pub fn set_task_estimate(conn: &Connection, description: &str, seconds: Option<i64>) -> Result<()> {
+    match seconds {
+        Some(seconds) => conn.execute(
+            "INSERT INTO task_estimates (description, seconds) VALUES (?1, ?2)
+             ON CONFLICT (description) DO UPDATE SET seconds = excluded.seconds",
+            params![description.trim(), seconds],
+        )?,
+        None => conn.execute(
+            "DELETE FROM task_estimates WHERE description = ?1",
+            params![description.trim()],
+        )?,
+    };
+    Ok(())
+}
+
+/// Estimated seconds per task, keyed by the lowercased description
+pub fn get_task_estimates(conn: &Connection) -> Result<HashMap<String, i64>> {
+    let mut stmt = conn.prepare("SELECT description, seconds FROM task_estimates")?;
+    let estimates = stmt.query_map([], |row| {
+        let description: String = row.get(0)?;
+        Ok((description.to_lowercase(), row.get(1)?))
+    })?;
+    Ok(estimates.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Columns selected for every outbox query, in the order expected by `outbox_item_from_row`
+const OUTBOX_COLUMNS: &str = "id, plugin, event, attempts, next_attempt_at, last_error, failed";
+
+/// Maps a row selected with `OUTBOX_COLUMNS` to an OutboxItem
+fn outbox_item_from_row(row: &Row) -> rusqlite::Result<OutboxItem> {
+    Ok(OutboxItem {
+        id: row.get(0)?,
+        plugin: row.get(1)?,
+        event: row.get(2)?,
+        attempts: row.get(3)?,
+        next_attempt_at: datetime_column(row, 4)?,
+        last_error: row.get(5)?,
+        failed: row.get(6)?,
+    })
+}
+
+/// Keeps an event `plugin` failed to take once, to send again at `next_attempt_at`
+pub fn add_outbox_item(
+    conn: &Connection,
+    plugin: &str,
+    event: &str,
+    error: &str,
+    next_attempt_at: DateTime<Utc>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO plugin_outbox (plugin, event, last_error, next_attempt_at) VALUES (?1, ?2, ?3, ?4)",
+        params![plugin, event, error, format_datetime(next_attempt_at)],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Outbox events due to be sent again by `now`, oldest first
+pub fn get_due_outbox_items(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<OutboxItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM plugin_outbox WHERE failed = 0 AND next_attempt_at <= ?1 ORDER BY id",
+        OUTBOX_COLUMNS
+    ))?;
+
+    let items = stmt.query_map(params![format_datetime(now)], outbox_item_from_row)?;
+
+    Ok(items.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Records another failed attempt at sending an outbox event, to be retried
+/// at `next_attempt_at` or, without one, given up
+pub fn record_outbox_failure(
+    conn: &Connection,
+    id: i64,
+    error: &str,
+    next_attempt_at: Option<DateTime<Utc>>,
+) -> Result<()> {
+    match next_attempt_at {
+        Some(next_attempt_at) => conn.execute(
+            "UPDATE plugin_outbox SET attempts = attempts + 1, last_error = ?1, next_attempt_at = ?2 WHERE id = ?3",
+            params![error, format_datetime(next_attempt_at), id],
+        )?,
+        None => conn.execute(
+            "UPDATE plugin_outbox SET attempts = attempts + 1, last_error = ?1, failed = 1 WHERE id = ?2",
+            params![error, id],
+        )?,
+    };
+    Ok(())
+}
+
+/// Removes an outbox event once it was sent
+pub fn delete_outbox_item(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM plugin_outbox WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Makes every outbox event due at `now`, including given up ones
+pub fn retry_outbox_now(conn: &Connection, now: DateTime<Utc>) -> Result<()> {
+    conn.execute(
+        "UPDATE plugin_outbox SET failed = 0, next_attempt_at = ?1",
+        params![format_datetime(now)],
+    )?;
+    Ok(())
+}
+
+/// Counts the outbox events still being retried and those given up
+pub fn count_outbox_items(conn: &Connection) -> Result<(i64, i64)> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FILTER (WHERE failed = 0), COUNT(*) FILTER (WHERE failed = 1) FROM plugin_outbox",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?)
+}
+
+/// Gets a project by ID
+pub fn get_project_by_id(conn: &Connection, id: i64) -> Result<Option<Project>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects WHERE id = ?1",
+        PROJECT_COLUMNS
+    ))?;
+
+    let mut rows = stmt.query(params![id])?;
+
+    match rows.next()? {
+        Some(row) => Ok(Some(project_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{FixedOffset, MappedLocalTime, NaiveDateTime, Weekday};
     use rusqlite::Connection;
     use std::collections::HashSet;
 
@@ -318,6 +1893,32 @@ mod tests {
         conn
     }
 
+    #[test]
+    fn test_custom_db_path() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let exe_dir = std::env::temp_dir().join(format!("time-tracking-portable-test-{}", std::process::id()));
+        fs::create_dir_all(&exe_dir).unwrap();
+
+        assert_eq!(database_arg(&args(&["app", "--database", "/sync/time.db"])), Some("/sync/time.db".into()));
+        assert_eq!(database_arg(&args(&["app", "--database=/sync/time.db"])), Some("/sync/time.db".into()));
+        assert_eq!(database_arg(&args(&["app", "--databases"])), None);
+
+        // The flag wins over the environment, which wins over a portable install
+        let env = Some(OsString::from("/env/time.db"));
+        assert_eq!(
+            custom_db_path(&args(&["app", "--database", "/sync/time.db"]), env.clone(), None),
+            Some("/sync/time.db".into())
+        );
+        assert_eq!(custom_db_path(&args(&["app"]), env, Some(&exe_dir)), Some("/env/time.db".into()));
+        assert_eq!(custom_db_path(&args(&["app"]), Some(OsString::new()), Some(&exe_dir)), None);
+
+        // A database next to the executable is only used once it exists
+        fs::write(exe_dir.join(DB_FILE_NAME), "").unwrap();
+        assert_eq!(custom_db_path(&args(&["app"]), None, Some(&exe_dir)), Some(exe_dir.join(DB_FILE_NAME)));
+
+        fs::remove_dir_all(&exe_dir).unwrap();
+    }
+
     #[test]
     fn test_tables_exist() {
         let conn = create_test_db();
@@ -398,7 +1999,8 @@ mod tests {
         assert_eq!(id, 1);
         assert_eq!(project_id, Some(1));
         assert_eq!(description, "Working on feature");
-        assert_eq!(start_time, "2024-01-15T10:00:00");
+        // Stored in the canonical format, whatever the insert used
+        assert_eq!(start_time, "2024-01-15T10:00:00Z");
         assert!(end_time.is_none());
         assert!(!created_at.is_empty());
     }
@@ -475,20 +2077,235 @@ mod tests {
     }
 
     #[test]
-    fn test_delete_nonexistent_project() {
+    fn test_merge_projects() {
         let conn = create_test_db();
-
-        // Deleting a non-existent project should not error
-        let result = delete_project(&conn, 999);
-        assert!(result.is_ok());
+        let typo = create_project(&conn, "Wrok", "#3498db").unwrap();
+        let work = create_project(&conn, "Work", "#e74c3c").unwrap();
+        let other = create_project(&conn, "Other", "#2ecc71").unwrap();
+        create_entry(&conn, Some(typo.id), "Review", Utc::now()).unwrap();
+        create_entry(&conn, Some(typo.id), "Planning", Utc::now()).unwrap();
+        create_entry(&conn, Some(work.id), "Meeting", Utc::now()).unwrap();
+        create_entry(&conn, Some(other.id), "Errands", Utc::now()).unwrap();
+
+        assert_eq!(merge_projects(&conn, typo.id, work.id).unwrap(), 2);
+
+        let names: Vec<String> = get_all_projects(&conn).unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["Other", "Work"]);
+        let in_project = |id: i64| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM time_entries WHERE project_id = ?1", [id], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(in_project(work.id), 3);
+        assert_eq!(in_project(other.id), 1);
     }
 
-    // Time Entry CRUD Tests
-
     #[test]
-    fn test_create_entry() {
+    fn test_move_project_entries() {
         let conn = create_test_db();
-        let start_time = Utc::now();
+        let old = create_project(&conn, "Old", "#3498db").unwrap();
+        let new = create_project(&conn, "New", "#e74c3c").unwrap();
+        let review = create_entry(&conn, Some(old.id), "Review", Utc::now()).unwrap();
+        let planning = create_entry(&conn, Some(old.id), "Planning", Utc::now()).unwrap();
+        create_entry(&conn, Some(new.id), "Meeting", Utc::now()).unwrap();
+
+        let moved = move_project_entries(&conn, old.id, Some(new.id)).unwrap();
+        assert_eq!(moved, vec![review.id, planning.id]);
+        assert_eq!(count_project_entries(&conn, old.id).unwrap(), 0);
+        assert_eq!(count_project_entries(&conn, new.id).unwrap(), 3);
+        // The source is kept
+        assert_eq!(get_all_projects(&conn).unwrap().len(), 2);
+
+        // Undoing moves exactly the moved entries back
+        set_entries_project(&conn, &moved, Some(old.id)).unwrap();
+        assert_eq!(count_project_entries(&conn, old.id).unwrap(), 2);
+        assert_eq!(move_project_entries(&conn, old.id, None).unwrap().len(), 2);
+        assert_eq!(get_entry_by_id(&conn, review.id).unwrap().project_id, None);
+    }
+
+    #[test]
+    fn test_get_first_project_entry() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Work", "#3498db").unwrap();
+        assert_eq!(get_first_project_entry(&conn, project.id).unwrap(), None);
+
+        let now = Utc::now();
+        create_entry(&conn, Some(project.id), "Later", now).unwrap();
+        let first = create_entry(&conn, Some(project.id), "Earlier", now - chrono::Duration::hours(2)).unwrap();
+        create_entry(&conn, None, "Earliest", now - chrono::Duration::hours(3)).unwrap();
+
+        assert_eq!(get_first_project_entry(&conn, project.id).unwrap().map(|e| e.id), Some(first.id));
+    }
+
+    #[test]
+    fn test_get_dropdown_projects() {
+        let conn = create_test_db();
+        let admin = create_project(&conn, "Admin", "#3498db").unwrap();
+        let client = create_project(&conn, "Client", "#e74c3c").unwrap();
+        let old = create_project(&conn, "Old", "#2ecc71").unwrap();
+        let work = create_project(&conn, "Work", "#9b59b6").unwrap();
+        let now = Utc::now();
+        let log = |project_id: i64, hours_ago: i64| {
+            let end_time = now - chrono::Duration::hours(hours_ago);
+            create_completed_entry(
+                &conn,
+                &NewEntry {
+                    project_id: Some(project_id),
+                    description: "Task".to_string(),
+                    start_time: end_time - chrono::Duration::minutes(30),
+                    end_time,
+                },
+            )
+            .unwrap();
+        };
+        log(client.id, 30);
+        log(work.id, 20);
+        log(work.id, 10);
+        log(admin.id, 5);
+        log(old.id, 24 * 60);
+        let names = |limit: usize| -> (Vec<String>, usize) {
+            let (projects, recent) = get_dropdown_projects(&conn, now - chrono::Duration::days(30), limit).unwrap();
+            (projects.into_iter().map(|p| p.name).collect(), recent)
+        };
+
+        // Most used first, then most recently used, then the usual order
+        assert_eq!(names(5), (vec!["Work".into(), "Admin".into(), "Client".into(), "Old".into()], 3));
+        assert_eq!(names(1), (vec!["Work".into(), "Admin".into(), "Client".into(), "Old".into()], 1));
+
+        set_projects_archived(&conn, &[work.id], true).unwrap();
+        assert_eq!(names(5), (vec!["Admin".into(), "Client".into(), "Old".into()], 2));
+    }
+
+    #[test]
+    fn test_move_project() {
+        let conn = create_test_db();
+        let admin = create_project(&conn, "Admin", "#3498db").unwrap();
+        let client = create_project(&conn, "Client", "#e74c3c").unwrap();
+        let work = create_project(&conn, "Work", "#2ecc71").unwrap();
+        let names = || -> Vec<String> { get_all_projects(&conn).unwrap().into_iter().map(|p| p.name).collect() };
+
+        move_project(&conn, work.id, admin.id).unwrap();
+        assert_eq!(names(), vec!["Work", "Admin", "Client"]);
+        move_project(&conn, work.id, client.id).unwrap();
+        assert_eq!(names(), vec!["Admin", "Client", "Work"]);
+
+        // New projects come first until moved
+        create_project(&conn, "Errands", "#9b59b6").unwrap();
+        assert_eq!(names(), vec!["Errands", "Admin", "Client", "Work"]);
+
+        assert!(matches!(
+            move_project(&conn, 999, client.id),
+            Err(TimeTrackingError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_pinned_tasks() {
+        let conn = create_test_db();
+        let work = create_project(&conn, "Work", "#3498db").unwrap();
+        let typo = create_project(&conn, "Wrok", "#e74c3c").unwrap();
+
+        let standup = pin_task(&conn, "Daily standup", Some(work.id)).unwrap();
+        let email = pin_task(&conn, "Email", None).unwrap();
+        pin_task(&conn, "Review", Some(typo.id)).unwrap();
+        pin_task(&conn, "Daily standup", Some(typo.id)).unwrap();
+        assert_eq!(pin_task(&conn, "Daily standup", Some(work.id)).unwrap(), standup);
+        assert_eq!(pin_task(&conn, "Email", None).unwrap(), email);
+
+        // Merging moves pins to the target, without duplicating its own
+        merge_projects(&conn, typo.id, work.id).unwrap();
+        let pinned = |conn: &Connection| -> Vec<(String, Option<i64>)> {
+            get_pinned_tasks(conn)
+                .unwrap()
+                .into_iter()
+                .map(|task| (task.description, task.project_id))
+                .collect()
+        };
+        assert_eq!(
+            pinned(&conn),
+            vec![
+                ("Daily standup".to_string(), Some(work.id)),
+                ("Email".to_string(), None),
+                ("Review".to_string(), Some(work.id)),
+            ]
+        );
+
+        unpin_task(&conn, email.id).unwrap();
+        delete_project(&conn, work.id).unwrap();
+        assert!(pinned(&conn).is_empty());
+    }
+
+    #[test]
+    fn test_entry_templates() {
+        let conn = create_test_db();
+        let work = create_project(&conn, "Work", "#3498db").unwrap();
+        let typo = create_project(&conn, "Wrok", "#e74c3c").unwrap();
+
+        let standup = create_template(&conn, "standup", "Daily standup", Some(typo.id), false).unwrap();
+        let doctor = create_template(&conn, "Doctor", "Appointment", None, true).unwrap();
+        assert_eq!(get_templates(&conn).unwrap(), vec![doctor.clone(), standup.clone()]);
+
+        // Merging moves templates to the target, deleting a project keeps them
+        merge_projects(&conn, typo.id, work.id).unwrap();
+        assert_eq!(get_templates(&conn).unwrap()[1].project_id, Some(work.id));
+        delete_project(&conn, work.id).unwrap();
+        assert_eq!(get_templates(&conn).unwrap()[1].project_id, None);
+
+        let renamed = EntryTemplate {
+            name: "Standup".to_string(),
+            project_id: None,
+            private: true,
+            ..standup
+        };
+        update_template(&conn, &renamed).unwrap();
+        delete_template(&conn, doctor.id).unwrap();
+        assert_eq!(get_templates(&conn).unwrap(), vec![renamed.clone()]);
+
+        delete_template(&conn, renamed.id).unwrap();
+        assert!(matches!(update_template(&conn, &renamed), Err(TimeTrackingError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_reminders() {
+        let conn = create_test_db();
+        let template = create_template(&conn, "Standup", "Daily standup", None, false).unwrap();
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+
+        let standup = create_reminder(&conn, "Standup", Repeat::Weekdays, at(9, 30), Some(template.id)).unwrap();
+        let timesheet = create_reminder(&conn, "Timesheet", Repeat::Weekly(Weekday::Fri), at(16, 0), None).unwrap();
+        let early = create_reminder(&conn, "Plan", Repeat::Daily, at(8, 45), None).unwrap();
+        assert_eq!(get_reminders(&conn).unwrap(), vec![early.clone(), standup.clone(), timesheet]);
+
+        let paused = Reminder {
+            enabled: false,
+            time: at(10, 0),
+            ..early
+        };
+        update_reminder(&conn, &paused).unwrap();
+        delete_template(&conn, template.id).unwrap();
+        let reminders = get_reminders(&conn).unwrap();
+        assert_eq!(reminders[0].template_id, None);
+        assert_eq!(reminders[1], paused);
+
+        delete_reminder(&conn, paused.id).unwrap();
+        assert_eq!(get_reminders(&conn).unwrap().len(), 2);
+        assert!(matches!(update_reminder(&conn, &paused), Err(TimeTrackingError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_delete_nonexistent_project() {
+        let conn = create_test_db();
+
+        // Deleting a non-existent project should not error
+        let result = delete_project(&conn, 999);
+        assert!(result.is_ok());
+    }
+
+    // Time Entry CRUD Tests
+
+    #[test]
+    fn test_create_entry() {
+        let conn = create_test_db();
+        let start_time = Utc::now();
 
         let entry = create_entry(&conn, None, "Working on task", start_time).unwrap();
 
@@ -569,7 +2386,7 @@ mod tests {
     #[test]
     fn test_get_entries_for_date_empty() {
         let conn = create_test_db();
-        let today = Utc::now().date_naive();
+        let today = Utc::now().with_timezone(&Local).date_naive();
 
         let entries = get_entries_for_date(&conn, today).unwrap();
 
@@ -585,7 +2402,7 @@ mod tests {
         create_entry(&conn, None, "Task 1", now).unwrap();
         create_entry(&conn, None, "Task 2", now).unwrap();
 
-        let today = now.date_naive();
+        let today = now.with_timezone(&Local).date_naive();
         let entries = get_entries_for_date(&conn, today).unwrap();
 
         assert_eq!(entries.len(), 2);
@@ -605,7 +2422,7 @@ mod tests {
             [],
         ).unwrap();
 
-        let today = now.date_naive();
+        let today = now.with_timezone(&Local).date_naive();
         let entries = get_entries_for_date(&conn, today).unwrap();
 
         // Should only get today's entry
@@ -613,6 +2430,100 @@ mod tests {
         assert_eq!(entries[0].description, "Today's task");
     }
 
+    /// Central European Time as in 2024, on summer time from March 31 to
+    /// October 27, so tests don't depend on the machine's time zone
+    #[derive(Debug, Clone, Copy)]
+    struct Cet2024;
+
+    impl TimeZone for Cet2024 {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Cet2024
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let at = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            let summer = (at(3, 31)..at(10, 27)).contains(utc);
+            FixedOffset::east_opt(if summer { 7200 } else { 3600 }).unwrap()
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+            let offsets: Vec<FixedOffset> = [7200, 3600]
+                .into_iter()
+                .map(|seconds| FixedOffset::east_opt(seconds).unwrap())
+                .filter(|offset| {
+                    let utc = *local - chrono::Duration::seconds(offset.local_minus_utc().into());
+                    self.offset_from_utc_datetime(&utc) == *offset
+                })
+                .collect();
+            match offsets[..] {
+                [offset] => MappedLocalTime::Single(offset),
+                [summer, winter] => MappedLocalTime::Ambiguous(summer, winter),
+                _ => MappedLocalTime::None,
+            }
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_local_day_bounds_follow_dst() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let utc = |month, day, hour| Utc.with_ymd_and_hms(2024, month, day, hour, 0, 0).unwrap();
+
+        assert_eq!(local_day_start(date(3, 4), &Cet2024), utc(3, 3, 23));
+        // The day clocks go forward lasts 23 hours, the day they go back 25
+        assert_eq!(local_day_start(date(3, 31), &Cet2024), utc(3, 30, 23));
+        assert_eq!(local_day_start(date(4, 1), &Cet2024), utc(3, 31, 22));
+        assert_eq!(
+            local_days_bounds(date(10, 27), date(10, 27), &Cet2024),
+            (format_datetime(utc(10, 26, 22)), format_datetime(utc(10, 27, 23)))
+        );
+    }
+
+    #[test]
+    fn test_entries_land_on_their_local_day() {
+        let conn = create_test_db();
+        let at = |month, day, hour, minute| Utc.with_ymd_and_hms(2024, month, day, hour, minute, 0).unwrap();
+        let log = |description: &str, start: DateTime<Utc>| {
+            let entry = create_entry(&conn, None, description, start).unwrap();
+            stop_entry(&conn, entry.id, start + chrono::Duration::minutes(20)).unwrap();
+        };
+        // 00:30 and 23:30 local time, an hour off the UTC date
+        log("Early", at(3, 3, 23, 30));
+        log("Late", at(3, 4, 22, 30));
+        log("Next day", at(3, 4, 23, 30));
+        // Around the switch to summer time, when midnight is at 22:00 UTC
+        log("Before switch", at(3, 31, 21, 30));
+        log("After switch", at(3, 31, 22, 30));
+
+        let descriptions = |month, day| {
+            let day = NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+            entries_for_days(&conn, day, day, &Cet2024)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.description)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(descriptions(3, 4), vec!["Late", "Early"]);
+        assert_eq!(descriptions(3, 5), vec!["Next day"]);
+        assert_eq!(descriptions(3, 31), vec!["Before switch"]);
+        assert_eq!(descriptions(4, 1), vec!["After switch"]);
+
+        let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        assert_eq!(
+            daily_seconds_for_days(&conn, date(3, 1), date(4, 1), &Cet2024).unwrap(),
+            vec![(date(3, 4), 2400), (date(3, 5), 1200), (date(3, 31), 1200), (date(4, 1), 1200)]
+        );
+    }
+
     #[test]
     fn test_delete_entry() {
         let conn = create_test_db();
@@ -621,11 +2532,48 @@ mod tests {
 
         delete_entry(&conn, entry.id).unwrap();
 
-        let today = start_time.date_naive();
+        let today = start_time.with_timezone(&Local).date_naive();
         let entries = get_entries_for_date(&conn, today).unwrap();
         assert!(entries.is_empty());
     }
 
+    #[test]
+    fn test_stop_entry_with_follow_up() {
+        let conn = create_test_db();
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        let stop = Utc.with_ymd_and_hms(2024, 3, 4, 17, 0, 0).unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 19, 30, 0).unwrap();
+        let entry = create_entry(&conn, None, "Forgot to stop", start).unwrap();
+        let follow_up = NewEntry {
+            project_id: None,
+            description: "Dinner prep".to_string(),
+            start_time: stop,
+            end_time: now,
+        };
+
+        stop_entry_with_follow_up(&conn, entry.id, stop, Some(&follow_up)).unwrap();
+
+        assert!(get_running_entry(&conn).unwrap().is_none());
+        let entries = entries_for_days(&conn, start.date_naive(), start.date_naive(), &Utc).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].description, "Dinner prep");
+        assert_eq!(entries[1].end_time, Some(stop));
+    }
+
+    #[test]
+    fn test_restore_deleted_entry() {
+        let conn = create_test_db();
+        let entry = create_entry(&conn, None, "Task to restore", Utc::now()).unwrap();
+        stop_entry(&conn, entry.id, Utc::now()).unwrap();
+        set_entry_private(&conn, entry.id, true).unwrap();
+        let entry = get_entry_by_id(&conn, entry.id).unwrap();
+
+        delete_entry(&conn, entry.id).unwrap();
+        restore_entry(&conn, &entry).unwrap();
+
+        assert_eq!(get_entry_by_id(&conn, entry.id).unwrap(), entry);
+    }
+
     #[test]
     fn test_delete_nonexistent_entry() {
         let conn = create_test_db();
@@ -658,6 +2606,41 @@ mod tests {
         assert!(found.is_none());
     }
 
+    #[test]
+    fn test_apply_project_changes() {
+        use crate::project_import::PlannedProject;
+
+        let conn = create_test_db();
+        let existing = create_project(&conn, "Old website", "#3498db").unwrap();
+        let planned = |code: &str, name: &str| PlannedProject {
+            code: code.to_string(),
+            name: name.to_string(),
+            hourly_rate_cents: Some(8550),
+            monthly_budget_minutes: Some(2400),
+        };
+
+        apply_project_changes(
+            &conn,
+            &[
+                ProjectChange::Update(existing.id, planned("ACME-1", "Website")),
+                ProjectChange::Create(planned("ACME-2", "Support")),
+            ],
+            &["#e01b24"],
+        )
+        .unwrap();
+
+        let projects = get_coded_projects(&conn).unwrap();
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].name, "Support");
+        assert_eq!(projects[0].color, "#e01b24");
+        assert_eq!(projects[1].id, existing.id);
+        assert_eq!(projects[1].name, "Website");
+        assert_eq!(projects[1].color, "#3498db");
+        assert_eq!(projects[1].code.as_deref(), Some("ACME-1"));
+        assert_eq!(projects[1].hourly_rate_cents, Some(8550));
+        assert_eq!(projects[1].monthly_budget_minutes, Some(2400));
+    }
+
     #[test]
     fn test_get_entries_for_date_range() {
         let conn = create_test_db();
@@ -682,7 +2665,7 @@ mod tests {
 
         let start = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
         let end = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
-        let entries = get_entries_for_date_range(&conn, start, end).unwrap();
+        let entries = entries_for_days(&conn, start, end, &Utc).unwrap();
 
         // Should get Monday and Wednesday tasks, not the one outside the range
         assert_eq!(entries.len(), 2);
@@ -692,6 +2675,208 @@ mod tests {
         assert!(!descriptions.contains(&"Outside range"));
     }
 
+    #[test]
+    fn test_absences_are_kept_apart_from_entries() {
+        let conn = create_test_db();
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 3, 4, 10, 30, 0).unwrap();
+
+        let absence = create_absence(&conn, AbsenceReason::Doctor, "Dentist", start, end).unwrap();
+        let date = start.with_timezone(&Local).date_naive();
+
+        assert_eq!(get_absences_for_date(&conn, date).unwrap(), std::slice::from_ref(&absence));
+        assert_eq!(absence.reason, AbsenceReason::Doctor);
+        assert!(get_entries_for_date(&conn, date).unwrap().is_empty());
+
+        delete_absence(&conn, absence.id).unwrap();
+        assert!(get_absences_for_date(&conn, date).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_entries_page() {
+        let conn = create_test_db();
+        for (description, start) in [
+            ("Oldest", "2024-01-15 09:00:00"),
+            ("Same time A", "2024-01-16 10:00:00"),
+            ("Same time B", "2024-01-16 10:00:00"),
+            ("Newest", "2024-01-17 08:00:00"),
+        ] {
+            conn.execute(
+                "INSERT INTO time_entries (project_id, description, start_time) VALUES (NULL, ?1, ?2)",
+                params![description, start],
+            )
+            .unwrap();
+        }
+
+        let first = get_entries_page(&conn, None, 2).unwrap();
+        let second = get_entries_page(&conn, first.last(), 2).unwrap();
+        let third = get_entries_page(&conn, second.last(), 2).unwrap();
+
+        let descriptions: Vec<&str> = first
+            .iter()
+            .chain(&second)
+            .map(|e| e.description.as_str())
+            .collect();
+        // Entries sharing a start time are split across pages without being repeated
+        assert_eq!(descriptions, ["Newest", "Same time B", "Same time A", "Oldest"]);
+        assert!(third.is_empty());
+    }
+
+    #[test]
+    fn test_get_recent_entries() {
+        let conn = create_test_db();
+        let website = create_project(&conn, "Website", "#3498db").unwrap();
+        for (project_id, description, start) in [
+            (Some(website.id), "Coding", "2024-01-15 09:00:00"),
+            (None, "Coding", "2024-01-16 09:00:00"),
+            (Some(website.id), "Coding", "2024-01-17 09:00:00"),
+            (None, "  ", "2024-01-18 09:00:00"),
+            (None, "Email", "2024-01-14 09:00:00"),
+        ] {
+            conn.execute(
+                "INSERT INTO time_entries (project_id, description, start_time, end_time)
+                 VALUES (?1, ?2, ?3, ?3)",
+                params![project_id, description, start],
+            )
+            .unwrap();
+        }
+
+        let recent = get_recent_entries(&conn, 10).unwrap();
+
+        let found: Vec<(Option<i64>, &str)> = recent
+            .iter()
+            .map(|e| (e.project_id, e.description.as_str()))
+            .collect();
+        assert_eq!(found, [(Some(website.id), "Coding"), (None, "Coding"), (None, "Email")]);
+        assert_eq!(recent[0].start_time.format("%d").to_string(), "17");
+        assert_eq!(get_recent_entries(&conn, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_tags() {
+        let conn = create_test_db();
+        for description in ["Mockups #design #Client", "Standup", "Call #client", "Issue#42"] {
+            conn.execute(
+                "INSERT INTO time_entries (description, start_time, end_time)
+                 VALUES (?1, '2024-01-15 09:00:00', '2024-01-15 10:00:00')",
+                params![description],
+            )
+            .unwrap();
+        }
+
+        assert_eq!(get_tags(&conn).unwrap(), ["client", "design"]);
+    }
+
+    #[test]
+    fn test_get_last_stopped_entry() {
+        let conn = create_test_db();
+        assert!(get_last_stopped_entry(&conn).unwrap().is_none());
+
+        // Started last but stopped first, so the review counts as last
+        for (description, start, end) in [
+            ("Review", "2024-01-15 09:00:00", Some("2024-01-15 12:00:00")),
+            ("Call", "2024-01-15 10:00:00", Some("2024-01-15 10:30:00")),
+            ("Running", "2024-01-15 13:00:00", None),
+        ] {
+            conn.execute(
+                "INSERT INTO time_entries (description, start_time, end_time) VALUES (?1, ?2, ?3)",
+                params![description, start, end],
+            )
+            .unwrap();
+        }
+
+        let last = get_last_stopped_entry(&conn).unwrap().unwrap();
+        assert_eq!(last.description, "Review");
+    }
+
+    #[test]
+    fn test_search_entries() {
+        let conn = create_test_db();
+        let website = create_project(&conn, "Website", "#3498db").unwrap();
+        let now = Utc::now();
+        create_entry(&conn, Some(website.id), "Fix login form", now).unwrap();
+        create_entry(&conn, None, "Logging cleanup", now).unwrap();
+        create_entry(&conn, None, "Invoices \"Q1\"", now).unwrap();
+
+        let descriptions = |text: &str| -> Vec<String> {
+            let mut found: Vec<String> = search_entries(&conn, text, 10)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.description)
+                .collect();
+            found.sort();
+            found
+        };
+
+        // Words match by prefix, case and accent insensitive
+        assert_eq!(descriptions("LOG"), ["Fix login form", "Logging cleanup"]);
+        assert_eq!(descriptions("lógin"), ["Fix login form"]);
+        // Each word may match the description or the project name
+        assert_eq!(descriptions("web log"), ["Fix login form"]);
+        // Quotes are searched as text rather than breaking the query
+        assert_eq!(descriptions("\"q1"), ["Invoices \"Q1\""]);
+        assert!(descriptions("   ").is_empty());
+    }
+
+    #[test]
+    fn test_search_index_follows_edits() {
+        let conn = create_test_db();
+        let entry = create_entry(&conn, None, "Draft", Utc::now()).unwrap();
+
+        update_entry_details(&conn, entry.id, None, "Final report").unwrap();
+        assert!(search_entries(&conn, "draft", 10).unwrap().is_empty());
+        assert_eq!(search_entries(&conn, "report", 10).unwrap().len(), 1);
+
+        delete_entry(&conn, entry.id).unwrap();
+        assert!(search_entries(&conn, "report", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_for_each_entry_in_range() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Website", "#3498db").unwrap();
+        for (project_id, description, start) in [
+            (Some(project.id), "Second", "2024-01-16 10:00:00"),
+            (None, "First", "2024-01-15 09:00:00"),
+            (None, "Outside", "2024-02-10 09:00:00"),
+        ] {
+            conn.execute(
+                "INSERT INTO time_entries (project_id, description, start_time) VALUES (?1, ?2, ?3)",
+                params![project_id, description, start],
+            )
+            .unwrap();
+        }
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+
+        let mut seen = Vec::new();
+        let complete = for_each_entry_in_range(&conn, start, end, |entry, project| {
+            seen.push((entry.description.clone(), project.map(str::to_string)));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+
+        assert!(complete);
+        assert_eq!(count_entries_in_range(&conn, start, end).unwrap(), 2);
+        assert_eq!(
+            seen,
+            [
+                ("First".to_string(), None),
+                ("Second".to_string(), Some("Website".to_string()))
+            ]
+        );
+
+        // Breaking stops after the first entry
+        let mut calls = 0;
+        let complete = for_each_entry_in_range(&conn, start, end, |_, _| {
+            calls += 1;
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert!(!complete);
+        assert_eq!(calls, 1);
+    }
+
     #[test]
     fn test_get_entries_for_date_range_empty() {
         let conn = create_test_db();
@@ -702,4 +2887,704 @@ mod tests {
 
         assert!(entries.is_empty());
     }
+
+    // Review Queue Tests
+
+    fn review_range() -> (DateTime<Utc>, DateTime<Utc>) {
+        let end = Utc::now();
+        (end - chrono::Duration::hours(1), end)
+    }
+
+    #[test]
+    fn test_migrations_set_user_version() {
+        let conn = create_test_db();
+
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(version, MIGRATIONS.len() as i64);
+
+        // Running the migrations again should be a no-op
+        run_migrations(&conn).unwrap();
+    }
+
+    #[test]
+    fn test_review_entries_excluded_from_totals() {
+        let conn = create_test_db();
+        let (start, end) = review_range();
+
+        create_entry(&conn, None, "Manual task", start).unwrap();
+        create_review_entry(&conn, None, "Calendar meeting", start, end).unwrap();
+
+        let entries = get_entries_for_date(&conn, start.with_timezone(&Local).date_naive()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].description, "Manual task");
+
+        let range = get_entries_for_date_range(&conn, start.with_timezone(&Local).date_naive(), end.with_timezone(&Local).date_naive()).unwrap();
+        assert_eq!(range.len(), 1);
+
+        assert_eq!(count_review_entries(&conn).unwrap(), 1);
+        let queue = get_review_entries(&conn).unwrap();
+        assert_eq!(queue.len(), 1);
+        assert!(queue[0].needs_review);
+        assert!(queue[0].end_time.is_some());
+    }
+
+    #[test]
+    fn test_confirm_entries() {
+        let conn = create_test_db();
+        let (start, end) = review_range();
+
+        let project = create_project(&conn, "Work", "#3498db").unwrap();
+        let entry = create_review_entry(&conn, None, "Calendar meeting", start, end).unwrap();
+        let reviewed = ReviewedEntry {
+            id: entry.id,
+            project_id: Some(project.id),
+            description: "Sprint planning".to_string(),
+        };
+        confirm_entries(&conn, &[reviewed]).unwrap();
+
+        assert_eq!(count_review_entries(&conn).unwrap(), 0);
+        let entries = get_entries_for_date(&conn, start.with_timezone(&Local).date_naive()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(!entries[0].needs_review);
+        assert_eq!(entries[0].project_id, Some(project.id));
+        assert_eq!(entries[0].description, "Sprint planning");
+    }
+
+    #[test]
+    fn test_create_review_entries_all_or_nothing() {
+        let conn = create_test_db();
+        let (start, end) = review_range();
+        let entry = |start_time, end_time| NewEntry {
+            project_id: None,
+            description: "Imported".to_string(),
+            start_time,
+            end_time,
+        };
+
+        assert!(create_review_entries(&conn, &[entry(start, end), entry(end, start)]).is_err());
+        assert_eq!(count_review_entries(&conn).unwrap(), 0);
+
+        assert_eq!(create_review_entries(&conn, &[entry(start, end), entry(start, end)]).unwrap(), 2);
+        assert_eq!(count_review_entries(&conn).unwrap(), 2);
+        assert!(get_entries_for_date(&conn, start.with_timezone(&Local).date_naive()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reject_entries_only_deletes_review_entries() {
+        let conn = create_test_db();
+        let (start, end) = review_range();
+
+        let manual = create_entry(&conn, None, "Manual task", start).unwrap();
+        let review = create_review_entry(&conn, None, "Watcher guess", start, end).unwrap();
+
+        reject_entries(&conn, &[manual.id, review.id]).unwrap();
+
+        assert_eq!(count_review_entries(&conn).unwrap(), 0);
+        let entries = get_entries_for_date(&conn, start.with_timezone(&Local).date_naive()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, manual.id);
+    }
+
+    #[test]
+    fn test_update_entry_details() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Work", "#3498db").unwrap();
+        let (start, end) = review_range();
+        let entry = create_review_entry(&conn, None, "Meeting", start, end).unwrap();
+
+        update_entry_details(&conn, entry.id, Some(project.id), "Sprint planning").unwrap();
+
+        let updated = get_entry_by_id(&conn, entry.id).unwrap();
+        assert_eq!(updated.project_id, Some(project.id));
+        assert_eq!(updated.description, "Sprint planning");
+        assert!(updated.needs_review);
+    }
+
+    #[test]
+    fn test_review_entry_is_not_running() {
+        let conn = create_test_db();
+        let (start, end) = review_range();
+        create_review_entry(&conn, None, "Meeting", start, end).unwrap();
+
+        assert!(get_running_entry(&conn).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_set_entry_private() {
+        let conn = create_test_db();
+        let entry = create_entry(&conn, None, "Dentist", Utc::now()).unwrap();
+        assert!(!entry.private);
+
+        set_entry_private(&conn, entry.id, true).unwrap();
+        assert!(get_entry_by_id(&conn, entry.id).unwrap().private);
+
+        // Private entries still count toward personal totals
+        let entries = get_entries_for_date(&conn, entry.start_time.with_timezone(&Local).date_naive()).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        set_entry_private(&conn, entry.id, false).unwrap();
+        assert!(!get_entry_by_id(&conn, entry.id).unwrap().private);
+    }
+
+    #[test]
+    fn test_set_entry_project_keeps_running() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Client", "#3498db").unwrap();
+        let entry = create_entry(&conn, None, "Call", Utc::now()).unwrap();
+
+        set_entry_project(&conn, entry.id, Some(project.id)).unwrap();
+
+        let running = get_running_entry(&conn).unwrap().unwrap();
+        assert_eq!(running.id, entry.id);
+        assert_eq!(running.project_id, Some(project.id));
+        assert_eq!(running.start_time, entry.start_time);
+
+        set_entry_project(&conn, entry.id, None).unwrap();
+        assert_eq!(get_entry_by_id(&conn, entry.id).unwrap().project_id, None);
+    }
+
+    #[test]
+    fn test_set_project_deep_work() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Writing", "#3498db").unwrap();
+        assert!(!project.deep_work);
+
+        set_project_deep_work(&conn, project.id, true).unwrap();
+
+        assert!(get_project_by_id(&conn, project.id).unwrap().unwrap().deep_work);
+    }
+
+    #[test]
+    fn test_set_project_deadline() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Launch", "#3498db").unwrap();
+        assert_eq!(project.deadline, None);
+
+        let deadline = NaiveDate::from_ymd_opt(2024, 3, 29).unwrap();
+        set_project_deadline(&conn, project.id, Some(deadline)).unwrap();
+        assert_eq!(get_project_by_id(&conn, project.id).unwrap().unwrap().deadline, Some(deadline));
+
+        set_project_deadline(&conn, project.id, None).unwrap();
+        assert_eq!(get_project_by_id(&conn, project.id).unwrap().unwrap().deadline, None);
+    }
+
+    #[test]
+    fn test_project_estimates() {
+        let conn = create_test_db();
+        let launch = create_project(&conn, "Launch", "#3498db").unwrap();
+        let other = create_project(&conn, "Other", "#e74c3c").unwrap();
+        assert_eq!(launch.estimate_seconds, None);
+        let at = |day: u32, hour: u32| Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap();
+        for (project_id, start, end) in [
+            (Some(launch.id), at(4, 9), at(4, 11)),
+            (Some(launch.id), at(20, 9), at(20, 10)),
+            (None, at(5, 9), at(5, 10)),
+        ] {
+            let entry = create_entry(&conn, project_id, "Work", start).unwrap();
+            stop_entry(&conn, entry.id, end).unwrap();
+        }
+
+        set_project_estimate(&conn, launch.id, Some(4 * 3600)).unwrap();
+        assert_eq!(get_project_by_id(&conn, launch.id).unwrap().unwrap().estimate_seconds, Some(4 * 3600));
+        assert_eq!(get_project_total_seconds(&conn).unwrap(), HashMap::from([(launch.id, 3 * 3600)]));
+
+        set_project_estimate(&conn, launch.id, None).unwrap();
+        assert_eq!(get_project_by_id(&conn, launch.id).unwrap().unwrap().estimate_seconds, None);
+        assert_eq!(get_project_by_id(&conn, other.id).unwrap().unwrap().estimate_seconds, None);
+    }
+
+    #[test]
+    fn test_plugin_outbox() {
+        let conn = create_test_db();
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        let later = now + chrono::Duration::minutes(1);
+        let id = add_outbox_item(&conn, "jira", "{\"event\":\"app_started\"}", "timed out", later).unwrap();
+        assert!(get_due_outbox_items(&conn, now).unwrap().is_empty());
+        assert_eq!(count_outbox_items(&conn).unwrap(), (1, 0));
+
+        let due = get_due_outbox_items(&conn, later).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].plugin.as_str(), due[0].attempts), ("jira", 1));
+
+        record_outbox_failure(&conn, id, "exited with 1", None).unwrap();
+        assert!(get_due_outbox_items(&conn, later).unwrap().is_empty());
+        assert_eq!(count_outbox_items(&conn).unwrap(), (0, 1));
+
+        retry_outbox_now(&conn, now).unwrap();
+        let due = get_due_outbox_items(&conn, now).unwrap();
+        assert_eq!((due[0].attempts, due[0].last_error.as_str()), (2, "exited with 1"));
+
+        delete_outbox_item(&conn, id).unwrap();
+        assert_eq!(count_outbox_items(&conn).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_task_estimates() {
+        let conn = create_test_db();
+        set_task_estimate(&conn, "Write report", Some(3600)).unwrap();
+        set_task_estimate(&conn, " write REPORT", Some(5400)).unwrap();
+        set_task_estimate(&conn, "Review", Some(1800)).unwrap();
+
+        let estimates = get_task_estimates(&conn).unwrap();
+        assert_eq!(estimates.len(), 2);
+        assert_eq!(estimates["write report"], 5400);
+
+        set_task_estimate(&conn, "review", None).unwrap();
+        assert!(!get_task_estimates(&conn).unwrap().contains_key("review"));
+    }
+
+    #[test]
+    fn test_unassigned_entries_matching() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Client", "#3498db").unwrap();
+        let standup = create_entry(&conn, None, "Daily Standup", Utc::now()).unwrap();
+        create_entry(&conn, Some(project.id), "Standup notes", Utc::now()).unwrap();
+        create_entry(&conn, None, "Code review", Utc::now()).unwrap();
+
+        let matching = get_unassigned_entries_matching(&conn, " standup ").unwrap();
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].id, standup.id);
+
+        assert!(get_unassigned_entries_matching(&conn, "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_set_entries_project() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Client", "#3498db").unwrap();
+        let first = create_entry(&conn, None, "Call", Utc::now()).unwrap();
+        let second = create_entry(&conn, None, "Mail", Utc::now()).unwrap();
+
+        set_entries_project(&conn, &[first.id, second.id], Some(project.id)).unwrap();
+        assert_eq!(get_entry_by_id(&conn, first.id).unwrap().project_id, Some(project.id));
+        assert_eq!(get_entry_by_id(&conn, second.id).unwrap().project_id, Some(project.id));
+
+        set_entries_project(&conn, &[first.id, second.id], None).unwrap();
+        assert_eq!(get_entry_by_id(&conn, first.id).unwrap().project_id, None);
+        assert_eq!(get_entry_by_id(&conn, second.id).unwrap().project_id, None);
+    }
+
+    #[test]
+    fn test_schema_version_after_migrations() {
+        let conn = create_test_db();
+
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_timestamps_migrate_to_rfc3339() {
+        // A database as version 16 left it, with SQLite's datetime format
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", 16).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE projects (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                color TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE TABLE time_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER,
+                description TEXT NOT NULL,
+                start_time TEXT NOT NULL,
+                end_time TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )
+        .unwrap();
+        for migration in &MIGRATIONS[..16] {
+            conn.execute_batch(migration).unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO time_entries (description, start_time, end_time, created_at)
+             VALUES ('Done', '2024-03-04 09:00:00', '2024-03-04 10:30:00', '2024-03-04 10:30:00');
+             INSERT INTO time_entries (description, start_time) VALUES ('Running', '2024-03-04 11:00:00');",
+        )
+        .unwrap();
+
+        create_tables(&conn).unwrap();
+
+        let stored: (String, String) = conn
+            .query_row("SELECT start_time, end_time FROM time_entries WHERE id = 1", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(stored, ("2024-03-04T09:00:00Z".to_string(), "2024-03-04T10:30:00Z".to_string()));
+        let entry = get_entry_by_id(&conn, 1).unwrap();
+        assert_eq!((entry.end_time.unwrap() - entry.start_time).num_seconds(), 5400);
+        assert_eq!(get_entry_by_id(&conn, 2).unwrap().end_time, None);
+
+        // Rows inserted afterwards with the column defaults are converted too
+        let project = create_project(&conn, "Work", "#3498db").unwrap();
+        let created_at: String = conn
+            .query_row("SELECT created_at FROM projects WHERE id = ?1", params![project.id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(parse_datetime(&created_at).map(format_datetime), Ok(created_at));
+    }
+
+    #[test]
+    fn test_malformed_timestamps_fail_to_read() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO time_entries (description, start_time) VALUES ('Broken', '04/03/2024 9am')",
+            [],
+        )
+        .unwrap();
+
+        assert!(parse_datetime("2024-03-04 09:00:00").is_err());
+        assert!(get_entry_by_id(&conn, 1).is_err());
+    }
+
+    #[test]
+    fn test_newer_schema_is_refused() {
+        let conn = create_test_db();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+
+        assert!(create_tables(&conn).is_err());
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION + 1);
+    }
+
+    #[test]
+    fn test_read_schema_version_of_missing_database() {
+        let path = std::env::temp_dir().join("time-tracking-missing-schema-test.db");
+
+        assert_eq!(read_schema_version(&path).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_entry_queries_use_indexes() {
+        let conn = create_test_db();
+        let plan = |sql: &str| -> String {
+            let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", sql)).unwrap();
+            let details = stmt.query_map([], |row| row.get::<_, String>(3)).unwrap();
+            details.collect::<rusqlite::Result<Vec<_>>>().unwrap().join("\n")
+        };
+
+        assert!(plan(
+            "SELECT * FROM time_entries WHERE start_time >= '2024-03-04T00:00:00Z' AND start_time < '2024-03-05T00:00:00Z'"
+        )
+        .contains("USING INDEX time_entries_start_time"));
+        assert!(plan("SELECT * FROM time_entries WHERE end_time IS NULL").contains("USING INDEX time_entries_end_time"));
+        assert!(plan("SELECT * FROM time_entries WHERE project_id = 1").contains("USING INDEX time_entries_project_id"));
+    }
+
+    #[test]
+    fn test_connection_settings() {
+        let path = std::env::temp_dir().join(format!("time-tracking-pragma-test-{}.db", std::process::id()));
+        let conn = open_db(&path).unwrap();
+        let pragma = |conn: &Connection, name: &str| -> i64 {
+            conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0)).unwrap()
+        };
+
+        let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(journal_mode, "wal");
+        // 1 is NORMAL
+        assert_eq!(pragma(&conn, "synchronous"), 1);
+        assert_eq!(pragma(&conn, "busy_timeout"), 5000);
+        assert_eq!(pragma(&open_snapshot(&path).unwrap(), "busy_timeout"), 5000);
+
+        drop(conn);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_reads_do_not_block_writes() {
+        let path = std::env::temp_dir().join(format!("time-tracking-snapshot-test-{}.db", std::process::id()));
+        let conn = open_db(&path).unwrap();
+        create_entry(&conn, None, "Before", Utc::now()).unwrap();
+
+        let snapshot = open_snapshot(&path).unwrap();
+        let read = snapshot.unchecked_transaction().unwrap();
+        let count = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM time_entries", [], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(count(&read), 1);
+
+        // Writing while the snapshot reads, which sees the data as it was
+        let running = create_entry(&conn, None, "During", Utc::now()).unwrap();
+        stop_entry(&conn, running.id, Utc::now()).unwrap();
+        assert_eq!(count(&read), 1);
+        assert!(create_entry(&snapshot, None, "Refused", Utc::now()).is_err());
+        drop(read);
+        assert_eq!(count(&snapshot), 2);
+
+        drop(snapshot);
+        drop(conn);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    // Settings Tests
+
+    #[test]
+    fn test_get_setting_missing() {
+        let conn = create_test_db();
+
+        assert_eq!(get_setting(&conn, "duration_format").unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_setting_replaces_value() {
+        let conn = create_test_db();
+
+        set_setting(&conn, "duration_format", "clock").unwrap();
+        set_setting(&conn, "duration_format", "decimal").unwrap();
+
+        assert_eq!(
+            get_setting(&conn, "duration_format").unwrap(),
+            Some("decimal".to_string())
+        );
+    }
+
+    // Aggregate Tests
+
+    #[test]
+    fn test_daily_and_project_seconds_in_range() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Work", "#3584e4").unwrap();
+        let at = |day: u32, hour: u32| Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap();
+        let entries = [
+            (Some(project.id), at(4, 9), at(4, 11)),
+            (None, at(4, 13), at(4, 14)),
+            (Some(project.id), at(6, 9), at(6, 12)),
+            (Some(project.id), at(12, 9), at(12, 10)),
+        ];
+        for (project_id, start_time, end_time) in entries {
+            create_completed_entry(
+                &conn,
+                &NewEntry {
+                    project_id,
+                    description: String::new(),
+                    start_time,
+                    end_time,
+                },
+            )
+            .unwrap();
+        }
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+
+        assert_eq!(
+            daily_seconds_for_days(&conn, start, end, &Utc).unwrap(),
+            vec![
+                (NaiveDate::from_ymd_opt(2024, 3, 4).unwrap(), 3 * 3600),
+                (NaiveDate::from_ymd_opt(2024, 3, 6).unwrap(), 3 * 3600),
+            ]
+        );
+        assert_eq!(
+            get_project_seconds_in_range(&conn, start, end).unwrap(),
+            vec![(Some(project.id), 5 * 3600), (None, 3600)]
+        );
+    }
+
+    // Completed Entry Tests
+
+    #[test]
+    fn test_create_completed_entry() {
+        let conn = create_test_db();
+        let (start, end) = review_range();
+
+        let entry = create_completed_entry(
+            &conn,
+            &NewEntry {
+                project_id: None,
+                description: "Standup".to_string(),
+                start_time: start,
+                end_time: end,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(entry.description, "Standup");
+        assert_eq!(entry.end_time.map(|e| e.timestamp()), Some(end.timestamp()));
+        assert!(!entry.needs_review);
+        assert!(get_running_entry(&conn).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_create_completed_entry_must_end_after_start() {
+        let conn = create_test_db();
+        let (start, _) = review_range();
+        let entry = NewEntry {
+            project_id: None,
+            description: "Backwards".to_string(),
+            start_time: start,
+            end_time: start,
+        };
+
+        let result = create_completed_entry(&conn, &entry);
+
+        assert!(matches!(result, Err(TimeTrackingError::Validation(_))));
+    }
+
+    #[test]
+    fn test_find_overlapping_entry() {
+        let conn = create_test_db();
+        let at = |hour: u32, minute: u32| Utc.with_ymd_and_hms(2024, 3, 4, hour, minute, 0).unwrap();
+        let standup = NewEntry {
+            project_id: None,
+            description: "Standup".to_string(),
+            start_time: at(9, 0),
+            end_time: at(9, 15),
+        };
+        let standup = create_completed_entry(&conn, &standup).unwrap();
+
+        let overlapping = find_overlapping_entry(&conn, at(9, 10), at(9, 40)).unwrap();
+        assert_eq!(overlapping.map(|entry| entry.description), Some("Standup".to_string()));
+        assert!(find_overlapping_entry(&conn, at(9, 15), at(9, 40)).unwrap().is_none());
+        assert!(find_overlapping_entry(&conn, at(8, 0), at(9, 0)).unwrap().is_none());
+        // An entry being lengthened doesn't overlap itself
+        assert!(find_overlapping_entry_except(&conn, Some(standup.id), at(9, 0), at(9, 30)).unwrap().is_none());
+
+        create_entry(&conn, None, "Running", at(10, 0)).unwrap();
+        assert!(find_overlapping_entry(&conn, at(11, 0), at(11, 30)).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_get_missing_entry_is_not_found() {
+        let conn = create_test_db();
+
+        assert!(matches!(get_entry_by_id(&conn, 42), Err(TimeTrackingError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_create_completed_entries() {
+        let conn = create_test_db();
+        let (start, end) = review_range();
+        let new_entry = NewEntry {
+            project_id: None,
+            description: "Block".to_string(),
+            start_time: start,
+            end_time: end,
+        };
+
+        let count = create_completed_entries(&conn, &[new_entry.clone(), new_entry]).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(get_entries_for_date(&conn, start.with_timezone(&Local).date_naive()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_create_completed_entries_rolls_back_on_error() {
+        let conn = create_test_db();
+        conn.execute_batch("PRAGMA foreign_keys = ON").unwrap();
+        let (start, end) = review_range();
+        let valid = NewEntry {
+            project_id: None,
+            description: "Valid".to_string(),
+            start_time: start,
+            end_time: end,
+        };
+        let invalid = NewEntry {
+            project_id: Some(999),
+            ..valid.clone()
+        };
+
+        assert!(create_completed_entries(&conn, &[valid, invalid]).is_err());
+        assert!(get_entries_for_date(&conn, start.with_timezone(&Local).date_naive()).unwrap().is_empty());
+    }
+
+    // Project Cleanup Tests
+
+    fn set_project_created_at(conn: &Connection, id: i64, created_at: &str) {
+        conn.execute(
+            "UPDATE projects SET created_at = ?1 WHERE id = ?2",
+            params![created_at, id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_get_empty_projects() {
+        let conn = create_test_db();
+        let used = create_project(&conn, "Used", "#3498db").unwrap();
+        let empty = create_project(&conn, "Empty", "#e74c3c").unwrap();
+        create_entry(&conn, Some(used.id), "Task", Utc::now()).unwrap();
+
+        let projects = get_empty_projects(&conn).unwrap();
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].id, empty.id);
+    }
+
+    #[test]
+    fn test_get_unused_projects() {
+        let conn = create_test_db();
+        let stale = create_project(&conn, "Stale", "#3498db").unwrap();
+        let active = create_project(&conn, "Active", "#e74c3c").unwrap();
+        let running = create_project(&conn, "Running", "#2ecc71").unwrap();
+        let fresh = create_project(&conn, "Fresh", "#f39c12").unwrap();
+        for project in [&stale, &active, &running] {
+            set_project_created_at(&conn, project.id, "2020-01-01T00:00:00Z");
+        }
+
+        conn.execute(
+            "INSERT INTO time_entries (project_id, description, start_time, end_time)
+             VALUES (?1, 'Old', '2020-02-01 10:00:00', '2020-02-01 11:00:00')",
+            params![stale.id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO time_entries (project_id, description, start_time)
+             VALUES (?1, 'Forgotten', '2020-02-01 10:00:00')",
+            params![running.id],
+        )
+        .unwrap();
+        create_entry(&conn, Some(active.id), "Recent", Utc::now()).unwrap();
+
+        let cutoff = Utc::now() - chrono::Duration::days(90);
+        let projects = get_unused_projects(&conn, cutoff).unwrap();
+
+        let ids: Vec<i64> = projects.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![stale.id]);
+        assert!(!ids.contains(&fresh.id));
+
+        assert_eq!(archive_unused_projects(&conn, cutoff).unwrap(), 1);
+        assert!(get_unused_projects(&conn, cutoff).unwrap().is_empty());
+        assert!(get_project_by_id(&conn, stale.id).unwrap().unwrap().archived);
+    }
+
+    #[test]
+    fn test_archived_projects_hidden() {
+        let conn = create_test_db();
+        let project = create_project(&conn, "Old", "#3498db").unwrap();
+        create_project(&conn, "Current", "#e74c3c").unwrap();
+
+        set_projects_archived(&conn, &[project.id], true).unwrap();
+
+        let projects = get_all_projects(&conn).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "Current");
+        assert!(get_empty_projects(&conn).unwrap().iter().all(|p| p.id != project.id));
+
+        // Archived projects can still be looked up for historical entries
+        let archived = get_project_by_id(&conn, project.id).unwrap().unwrap();
+        assert!(archived.archived);
+        assert_eq!(get_archived_projects(&conn).unwrap(), vec![archived]);
+
+        set_projects_archived(&conn, &[project.id], false).unwrap();
+        assert!(get_archived_projects(&conn).unwrap().is_empty());
+        assert_eq!(get_all_projects(&conn).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_projects() {
+        let conn = create_test_db();
+        let a = create_project(&conn, "A", "#3498db").unwrap();
+        let b = create_project(&conn, "B", "#e74c3c").unwrap();
+        create_project(&conn, "C", "#2ecc71").unwrap();
+
+        delete_projects(&conn, &[a.id, b.id]).unwrap();
+
+        let projects = get_all_projects(&conn).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "C");
+    }
 }
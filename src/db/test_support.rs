@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+
+use super::{Project, TimeEntry};
+
+/// A public entry without a project or description from `start` to `end`,
+/// still running without an end. Tests set the fields they look at with
+/// struct update syntax.
+pub fn entry(start: DateTime<Utc>, end: Option<DateTime<Utc>>) -> TimeEntry {
+    TimeEntry {
+        id: 1,
+        project_id: None,
+        description: String::new(),
+        start_time: start,
+        end_time: end,
+        created_at: start,
+        needs_review: false,
+        private: false,
+    }
+}
+
+/// A project with only an id and a name, nothing optional set
+pub fn project(id: i64, name: &str) -> Project {
+    Project {
+        id,
+        name: name.to_string(),
+        color: "#3584e4".to_string(),
+        created_at: Utc::now(),
+        archived: false,
+        deep_work: false,
+        code: None,
+        hourly_rate_cents: None,
+        monthly_budget_minutes: None,
+        deadline: None,
+        estimate_seconds: None,
+    }
+}
@@ -0,0 +1,129 @@
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::settings::DurationFormat;
+
+/// Object path of the status object on the session bus
+pub const STATUS_OBJECT_PATH: &str = "/com/example/time_tracking/Status";
+
+/// Interface exposing the timer status as cached, read-only properties
+pub const STATUS_INTERFACE: &str = "com.example.TimeTracking.Status";
+
+const STATUS_INTROSPECTION: &str = r#"
+<node>
+  <interface name="com.example.TimeTracking.Status">
+    <property name="IsRunning" type="b" access="read"/>
+    <property name="Description" type="s" access="read"/>
+    <property name="Elapsed" type="s" access="read"/>
+    <property name="ElapsedSeconds" type="x" access="read"/>
+    <property name="TodayTotal" type="s" access="read"/>
+    <property name="TodayTotalSeconds" type="x" access="read"/>
+  </interface>
+</node>
+"#;
+
+/// Snapshot of the values published on the status interface
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusSnapshot {
+    pub is_running: bool,
+    pub description: String,
+    pub elapsed_seconds: i64,
+    pub today_total_seconds: i64,
+}
+
+impl StatusSnapshot {
+    /// Returns the value of a property by its D-Bus name
+    fn property(&self, name: &str) -> Option<glib::Variant> {
+        match name {
+            "IsRunning" => Some(self.is_running.to_variant()),
+            "Description" => Some(self.description.to_variant()),
+            "Elapsed" => Some(DurationFormat::Clock.format(self.elapsed_seconds).to_variant()),
+            "ElapsedSeconds" => Some(self.elapsed_seconds.to_variant()),
+            "TodayTotal" => Some(DurationFormat::Clock.format(self.today_total_seconds).to_variant()),
+            "TodayTotalSeconds" => Some(self.today_total_seconds.to_variant()),
+            _ => None,
+        }
+    }
+
+    /// Names of the properties whose values differ from `other`
+    fn changed_properties(&self, other: &StatusSnapshot) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.is_running != other.is_running {
+            changed.push("IsRunning");
+        }
+        if self.description != other.description {
+            changed.push("Description");
+        }
+        if self.elapsed_seconds != other.elapsed_seconds {
+            changed.extend(["Elapsed", "ElapsedSeconds"]);
+        }
+        if self.today_total_seconds != other.today_total_seconds {
+            changed.extend(["TodayTotal", "TodayTotalSeconds"]);
+        }
+        changed
+    }
+}
+
+/// Publishes the timer status on the session bus so panel extensions
+/// (GNOME Shell, Waybar) can bind to properties instead of polling
+pub struct StatusService {
+    connection: gio::DBusConnection,
+    snapshot: Rc<RefCell<StatusSnapshot>>,
+}
+
+impl StatusService {
+    /// Registers the status object on the given connection
+    pub fn register(connection: gio::DBusConnection) -> Result<Self, glib::Error> {
+        let node_info = gio::DBusNodeInfo::for_xml(STATUS_INTROSPECTION)?;
+        let interface_info = node_info
+            .lookup_interface(STATUS_INTERFACE)
+            .expect("status interface is defined in the introspection data");
+
+        let snapshot = Rc::new(RefCell::new(StatusSnapshot::default()));
+        let snapshot_for_get = snapshot.clone();
+
+        connection
+            .register_object(STATUS_OBJECT_PATH, &interface_info)
+            .property(move |_, _, _, _, name| {
+                snapshot_for_get
+                    .borrow()
+                    .property(name)
+                    .unwrap_or_else(|| "".to_variant())
+            })
+            .build()?;
+
+        Ok(Self {
+            connection,
+            snapshot,
+        })
+    }
+
+    /// Updates the cached values and emits PropertiesChanged for those that changed
+    pub fn update(&self, new_snapshot: StatusSnapshot) {
+        let changed = new_snapshot.changed_properties(&self.snapshot.borrow());
+        if changed.is_empty() {
+            return;
+        }
+
+        let values: HashMap<String, glib::Variant> = changed
+            .iter()
+            .filter_map(|name| new_snapshot.property(name).map(|v| (name.to_string(), v)))
+            .collect();
+        *self.snapshot.borrow_mut() = new_snapshot;
+
+        let parameters = (STATUS_INTERFACE, values, Vec::<String>::new()).to_variant();
+        if let Err(e) = self.connection.emit_signal(
+            None,
+            STATUS_OBJECT_PATH,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            Some(&parameters),
+        ) {
+            eprintln!("Failed to emit status properties: {}", e);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+use std::collections::HashMap;
+
+/// GNOME's Do Not Disturb is its notification banners being hidden
+const GNOME_NOTIFICATIONS_SCHEMA: &str = "org.gnome.desktop.notifications";
+const GNOME_SHOW_BANNERS_KEY: &str = "show-banners";
+
+/// Plasma's notification server takes inhibitions, which last until released
+const NOTIFICATIONS_BUS_NAME: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_OBJECT_PATH: &str = "/org/freedesktop/Notifications";
+const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_TIMEOUT_MS: i32 = 1000;
+
+/// How Do Not Disturb was turned on, so it is turned off the same way
+#[cfg_attr(not(unix), allow(dead_code))]
+enum Activation {
+    /// GNOME banners were hidden; they are shown again only if they were before
+    Gnome {
+        settings: gio::Settings,
+        previous_show_banners: bool,
+    },
+    /// Cookie of a Plasma notification inhibition
+    Plasma { connection: gio::DBusConnection, cookie: u32 },
+}
+
+/// Turns the desktop's Do Not Disturb on and back to what it was before,
+/// on GNOME through its notification settings and on Plasma by inhibiting
+/// notifications over D-Bus
+#[derive(Default)]
+pub struct DoNotDisturb {
+    /// Last requested state, so a failure is reported once rather than on every request
+    requested: bool,
+    activation: Option<Activation>,
+}
+
+impl DoNotDisturb {
+    /// Turns Do Not Disturb on, or restores the state from before. Nothing
+    /// happens when the same state was requested last time.
+    pub fn set_active(&mut self, active: bool) -> Result<(), String> {
+        if active == self.requested {
+            return Ok(());
+        }
+        self.requested = active;
+        if active {
+            self.activation = Some(activate()?);
+            Ok(())
+        } else {
+            self.activation.take().map_or(Ok(()), restore)
+        }
+    }
+}
+
+impl Drop for DoNotDisturb {
+    fn drop(&mut self) {
+        if let Err(e) = self.set_active(false) {
+            eprintln!("Failed to turn off Do Not Disturb: {}", e);
+        }
+    }
+}
+
+/// True on GNOME sessions with the notification settings installed
+#[cfg(unix)]
+fn is_gnome() -> bool {
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    desktop.split(':').any(|name| name == "GNOME")
+        && gio::SettingsSchemaSource::default()
+            .and_then(|source| source.lookup(GNOME_NOTIFICATIONS_SCHEMA, true))
+            .is_some()
+}
+
+/// Other platforms have neither GNOME's settings nor a session bus, where
+/// looking for one could start a bus daemon
+#[cfg(not(unix))]
+fn activate() -> Result<Activation, String> {
+    Err("Do Not Disturb is only supported on GNOME and Plasma".to_string())
+}
+
+#[cfg(unix)]
+fn activate() -> Result<Activation, String> {
+    if is_gnome() {
+        let settings = gio::Settings::new(GNOME_NOTIFICATIONS_SCHEMA);
+        let previous_show_banners = settings.boolean(GNOME_SHOW_BANNERS_KEY);
+        settings
+            .set_boolean(GNOME_SHOW_BANNERS_KEY, false)
+            .map_err(|e| e.to_string())?;
+        gio::Settings::sync();
+        return Ok(Activation::Gnome {
+            settings,
+            previous_show_banners,
+        });
+    }
+
+    let connection = gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>)
+        .map_err(|e| e.to_string())?;
+    let hints: HashMap<String, glib::Variant> = HashMap::new();
+    let reply = connection
+        .call_sync(
+            Some(NOTIFICATIONS_BUS_NAME),
+            NOTIFICATIONS_OBJECT_PATH,
+            NOTIFICATIONS_INTERFACE,
+            "Inhibit",
+            Some(&("time-tracking", "Tracking deep work", hints).to_variant()),
+            Some(glib::VariantTy::new("(u)").unwrap()),
+            gio::DBusCallFlags::NONE,
+            NOTIFICATIONS_TIMEOUT_MS,
+            None::<&gio::Cancellable>,
+        )
+        .map_err(|_| "the desktop doesn't offer Do Not Disturb".to_string())?;
+    let (cookie,) = reply
+        .get::<(u32,)>()
+        .ok_or_else(|| "unexpected reply from the notification server".to_string())?;
+    Ok(Activation::Plasma { connection, cookie })
+}
+
+fn restore(activation: Activation) -> Result<(), String> {
+    match activation {
+        Activation::Gnome {
+            settings,
+            previous_show_banners,
+        } => {
+            settings
+                .set_boolean(GNOME_SHOW_BANNERS_KEY, previous_show_banners)
+                .map_err(|e| e.to_string())?;
+            gio::Settings::sync();
+            Ok(())
+        }
+        Activation::Plasma { connection, cookie } => connection
+            .call_sync(
+                Some(NOTIFICATIONS_BUS_NAME),
+                NOTIFICATIONS_OBJECT_PATH,
+                NOTIFICATIONS_INTERFACE,
+                "UnInhibit",
+                Some(&(cookie,).to_variant()),
+                None,
+                gio::DBusCallFlags::NONE,
+                NOTIFICATIONS_TIMEOUT_MS,
+                None::<&gio::Cancellable>,
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+    }
+}
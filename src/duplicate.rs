@@ -0,0 +1,94 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::db::{NewEntry, TimeEntry};
+
+/// Where a duplicated entry is placed. Keeping the exact times would
+/// overlap the original.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateTarget {
+    /// The same time of day, today
+    Today,
+    /// The same length, ending now
+    EndingNow,
+}
+
+/// A copy of a completed entry placed at `target`, or None for a running
+/// entry or a start time that doesn't exist today in the given time zone
+pub fn duplicate_entry<Tz: TimeZone>(
+    entry: &TimeEntry,
+    target: DuplicateTarget,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Option<NewEntry> {
+    let length = entry.end_time? - entry.start_time;
+    let start_time = match target {
+        DuplicateTarget::Today => {
+            let time_of_day = entry.start_time.with_timezone(tz).time();
+            let today = now.with_timezone(tz).date_naive();
+            tz.from_local_datetime(&today.and_time(time_of_day))
+                .earliest()?
+                .with_timezone(&Utc)
+        }
+        DuplicateTarget::EndingNow => now - length,
+    };
+    Some(NewEntry {
+        project_id: entry.project_id,
+        description: entry.description.clone(),
+        start_time,
+        end_time: start_time + length,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+    use crate::db::test_support;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap()
+    }
+
+    fn entry(start: DateTime<Utc>, end: Option<DateTime<Utc>>) -> TimeEntry {
+        TimeEntry {
+            project_id: Some(2),
+            description: "Review".to_string(),
+            ..test_support::entry(start, end)
+        }
+    }
+
+    fn times(entry: Option<NewEntry>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        entry.map(|entry| (entry.start_time, entry.end_time))
+    }
+
+    #[test]
+    fn test_duplicate_to_today() {
+        let yesterday = entry(at(4, 9, 30), Some(at(4, 11, 0)));
+        let copy = duplicate_entry(&yesterday, DuplicateTarget::Today, at(5, 15, 0), &Utc).unwrap();
+        assert_eq!((copy.start_time, copy.end_time), (at(5, 9, 30), at(5, 11, 0)));
+        assert_eq!((copy.project_id, copy.description.as_str()), (Some(2), "Review"));
+
+        // Days are local: 23:30 UTC on the 4th is 00:30 on the 5th in UTC+1
+        let tz = FixedOffset::east_opt(3600).unwrap();
+        let late = entry(at(4, 23, 30), Some(at(5, 1, 0)));
+        assert_eq!(
+            times(duplicate_entry(&late, DuplicateTarget::Today, at(6, 15, 0), &tz)),
+            Some((at(5, 23, 30), at(6, 1, 0)))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_ending_now() {
+        let block = entry(at(4, 9, 30), Some(at(4, 11, 0)));
+        assert_eq!(
+            times(duplicate_entry(&block, DuplicateTarget::EndingNow, at(5, 15, 0), &Utc)),
+            Some((at(5, 13, 30), at(5, 15, 0)))
+        );
+    }
+
+    #[test]
+    fn test_running_entries_are_not_duplicated() {
+        let running = entry(at(5, 9, 0), None);
+        assert_eq!(duplicate_entry(&running, DuplicateTarget::Today, at(5, 15, 0), &Utc), None);
+    }
+}
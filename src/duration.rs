@@ -0,0 +1,399 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub};
+
+use crate::db::TimeEntry;
+
+/// Longest expected interval between one-second timer ticks. A larger gap
+/// means the machine was suspended or the wall clock jumped.
+pub const MAX_TICK_INTERVAL_SECS: i64 = 5;
+
+/// Seconds elapsed between two instants, clamped at zero.
+/// Elapsed time is always derived from stored timestamps, never accumulated
+/// from ticks, so it stays correct across suspend and hibernate.
+pub fn elapsed_seconds(start: DateTime<Utc>, end: DateTime<Utc>) -> i64 {
+    TrackedDuration::between(start, end).seconds()
+}
+
+/// Duration of an entry in seconds, using `now` as the end of a running entry
+pub fn entry_seconds(entry: &TimeEntry, now: DateTime<Utc>) -> i64 {
+    elapsed_seconds(entry.start_time, entry.end_time.unwrap_or(now))
+}
+
+/// Direction used when rounding durations to an interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+impl RoundingMode {
+    /// All modes, in the order they are offered in preferences
+    pub const ALL: [RoundingMode; 3] = [RoundingMode::Nearest, RoundingMode::Up, RoundingMode::Down];
+
+    /// Human readable name shown in preferences
+    pub fn label(self) -> &'static str {
+        match self {
+            RoundingMode::Nearest => "To nearest",
+            RoundingMode::Up => "Up",
+            RoundingMode::Down => "Down",
+        }
+    }
+}
+
+/// Where rounding is applied. Stored timestamps are never modified either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingScope {
+    /// Every completed entry is rounded as it is stopped, totals add up rounded entries
+    #[default]
+    Entries,
+    /// Entries show raw durations, only totals and reports are rounded
+    Reports,
+}
+
+impl RoundingScope {
+    /// All scopes, in the order they are offered in preferences
+    pub const ALL: [RoundingScope; 2] = [RoundingScope::Entries, RoundingScope::Reports];
+
+    /// Human readable name shown in preferences
+    pub fn label(self) -> &'static str {
+        match self {
+            RoundingScope::Entries => "Each entry when stopped",
+            RoundingScope::Reports => "Totals and reports only",
+        }
+    }
+}
+
+/// Rounding rule for displayed durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rounding {
+    /// Rounding interval in minutes, 0 disables rounding
+    pub interval_minutes: i64,
+    pub mode: RoundingMode,
+    pub scope: RoundingScope,
+}
+
+impl Rounding {
+    /// Intervals offered in preferences, in minutes (0 = off)
+    pub const INTERVALS: [i64; 4] = [0, 5, 6, 15];
+
+    /// Rounds a duration in seconds to the configured interval
+    pub fn round(&self, seconds: i64) -> i64 {
+        if self.interval_minutes <= 0 {
+            return seconds;
+        }
+
+        let interval = self.interval_minutes * 60;
+        let rounded_down = seconds.div_euclid(interval) * interval;
+        let remainder = seconds - rounded_down;
+
+        match self.mode {
+            RoundingMode::Down => rounded_down,
+            RoundingMode::Up if remainder > 0 => rounded_down.saturating_add(interval),
+            RoundingMode::Up => rounded_down,
+            RoundingMode::Nearest if remainder * 2 >= interval => rounded_down.saturating_add(interval),
+            RoundingMode::Nearest => rounded_down,
+        }
+    }
+
+    /// Duration of an entry as displayed: completed entries are rounded when
+    /// the scope is per entry, running entries always show raw elapsed time
+    pub fn entry_seconds(&self, entry: &TimeEntry, now: DateTime<Utc>) -> i64 {
+        let seconds = entry_seconds(entry, now);
+        if self.scope == RoundingScope::Entries && entry.end_time.is_some() {
+            self.round(seconds)
+        } else {
+            seconds
+        }
+    }
+
+    /// Rounds a total when the scope is reports only
+    pub fn round_total(&self, seconds: i64) -> i64 {
+        match self.scope {
+            RoundingScope::Entries => seconds,
+            RoundingScope::Reports => self.round(seconds),
+        }
+    }
+
+    /// Total duration of a list of entries with this rule applied
+    pub fn total_seconds(&self, entries: &[TimeEntry], now: DateTime<Utc>) -> i64 {
+        let sum: TrackedDuration = entries
+            .iter()
+            .map(|entry| TrackedDuration::from_seconds(self.entry_seconds(entry, now)))
+            .sum();
+        self.round_total(sum.seconds())
+    }
+}
+
+/// A length of tracked time in whole seconds, never negative. Arithmetic
+/// saturates instead of overflowing, and subtraction stops at zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct TrackedDuration(i64);
+
+impl TrackedDuration {
+    pub const ZERO: TrackedDuration = TrackedDuration(0);
+
+    /// Duration of `seconds`, clamping negative values to zero
+    pub fn from_seconds(seconds: i64) -> Self {
+        TrackedDuration(seconds.max(0))
+    }
+
+    /// Time elapsed between two instants, zero when `end` is before `start`
+    pub fn between(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self::from_seconds(end.signed_duration_since(start).num_seconds())
+    }
+
+    pub fn seconds(self) -> i64 {
+        self.0
+    }
+
+    /// Hours, minutes and seconds, e.g. "07:45:00". Hours grow past two
+    /// digits rather than wrapping.
+    pub fn clock(self) -> String {
+        format!("{:02}:{:02}:{:02}", self.0 / 3600, self.0 % 3600 / 60, self.0 % 60)
+    }
+
+    /// Decimal hours with a unit, e.g. "7.75 h"
+    pub fn decimal(self) -> String {
+        format!("{} h", self.industrial())
+    }
+
+    /// Hours in hundredths without a unit, e.g. "7.75", as spreadsheets and
+    /// payroll systems expect. Half a hundredth (18 seconds) rounds up.
+    pub fn industrial(self) -> String {
+        let hundredths = self.0 / 36 + i64::from(self.0 % 36 >= 18);
+        format!("{}.{:02}", hundredths / 100, hundredths % 100)
+    }
+}
+
+impl fmt::Display for TrackedDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.clock())
+    }
+}
+
+impl Add for TrackedDuration {
+    type Output = TrackedDuration;
+
+    fn add(self, other: TrackedDuration) -> TrackedDuration {
+        TrackedDuration(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for TrackedDuration {
+    fn add_assign(&mut self, other: TrackedDuration) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for TrackedDuration {
+    type Output = TrackedDuration;
+
+    fn sub(self, other: TrackedDuration) -> TrackedDuration {
+        TrackedDuration(self.0.saturating_sub(other.0).max(0))
+    }
+}
+
+impl Sum for TrackedDuration {
+    fn sum<I: Iterator<Item = TrackedDuration>>(iter: I) -> TrackedDuration {
+        iter.fold(TrackedDuration::ZERO, Add::add)
+    }
+}
+
+/// Returns true when the time between two ticks shows the timer was paused
+/// (suspend, hibernate) or the clock moved backwards
+pub fn is_clock_gap(last_tick: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    let delta = now.signed_duration_since(last_tick).num_seconds();
+    !(0..=MAX_TICK_INTERVAL_SECS).contains(&delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use crate::db::test_support::entry;
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 15, hour, minute, second).unwrap()
+    }
+
+    #[test]
+    fn test_elapsed_seconds() {
+        assert_eq!(elapsed_seconds(at(9, 0, 0), at(9, 0, 0)), 0);
+        assert_eq!(elapsed_seconds(at(9, 0, 0), at(10, 30, 15)), 5415);
+    }
+
+    #[test]
+    fn test_elapsed_seconds_clamps_negative() {
+        assert_eq!(elapsed_seconds(at(10, 0, 0), at(9, 0, 0)), 0);
+    }
+
+    #[test]
+    fn test_running_entry_after_multi_hour_gap() {
+        // Simulate ticks at 09:00:01 and 09:00:02, then a suspend until 13:00:02
+        let running = entry(at(9, 0, 0), None);
+
+        assert_eq!(entry_seconds(&running, at(9, 0, 1)), 1);
+        assert_eq!(entry_seconds(&running, at(9, 0, 2)), 2);
+        assert_eq!(entry_seconds(&running, at(13, 0, 2)), 4 * 3600 + 2);
+    }
+
+    #[test]
+    fn test_completed_entry_ignores_now() {
+        let completed = entry(at(9, 0, 0), Some(at(9, 45, 0)));
+
+        assert_eq!(entry_seconds(&completed, at(9, 10, 0)), 2700);
+        assert_eq!(entry_seconds(&completed, at(23, 0, 0)), 2700);
+    }
+
+    #[test]
+    fn test_total_seconds() {
+        let entries = vec![
+            entry(at(9, 0, 0), Some(at(10, 0, 0))),
+            entry(at(11, 0, 0), None),
+        ];
+
+        let rule = Rounding::default();
+
+        assert_eq!(rule.total_seconds(&entries, at(11, 30, 0)), 5400);
+        // After an eight hour suspend the running entry catches up immediately
+        assert_eq!(rule.total_seconds(&entries, at(19, 30, 0)), 3600 + 8 * 3600 + 1800);
+    }
+
+    #[test]
+    fn test_tracked_duration_formats() {
+        let workday = TrackedDuration::from_seconds(27_900);
+        assert_eq!(workday.clock(), "07:45:00");
+        assert_eq!(workday.decimal(), "7.75 h");
+        assert_eq!(workday.industrial(), "7.75");
+        assert_eq!(workday.to_string(), "07:45:00");
+
+        assert_eq!(TrackedDuration::ZERO.clock(), "00:00:00");
+        assert_eq!(TrackedDuration::ZERO.industrial(), "0.00");
+        assert_eq!(TrackedDuration::from_seconds(3_661).clock(), "01:01:01");
+        assert_eq!(TrackedDuration::from_seconds(100 * 3600).clock(), "100:00:00");
+    }
+
+    #[test]
+    fn test_tracked_duration_industrial_rounding() {
+        assert_eq!(TrackedDuration::from_seconds(17).industrial(), "0.00");
+        assert_eq!(TrackedDuration::from_seconds(18).industrial(), "0.01");
+        assert_eq!(TrackedDuration::from_seconds(3599).industrial(), "1.00");
+        assert_eq!(TrackedDuration::from_seconds(1_800).industrial(), "0.50");
+    }
+
+    #[test]
+    fn test_tracked_duration_clamps_negative() {
+        assert_eq!(TrackedDuration::from_seconds(-90), TrackedDuration::ZERO);
+        assert_eq!(TrackedDuration::between(at(10, 0, 0), at(9, 0, 0)), TrackedDuration::ZERO);
+        assert_eq!(TrackedDuration::from_seconds(60) - TrackedDuration::from_seconds(90), TrackedDuration::ZERO);
+        assert_eq!(TrackedDuration::from_seconds(-1).clock(), "00:00:00");
+    }
+
+    #[test]
+    fn test_tracked_duration_saturates() {
+        let max = TrackedDuration::from_seconds(i64::MAX);
+        assert_eq!(max + TrackedDuration::from_seconds(1), max);
+        assert_eq!([max, max].into_iter().sum::<TrackedDuration>(), max);
+        assert_eq!(rounding(15, RoundingMode::Up, RoundingScope::Entries).round(i64::MAX), i64::MAX);
+        assert_eq!(max.clock(), format!("{}:{:02}:{:02}", i64::MAX / 3600, i64::MAX % 3600 / 60, i64::MAX % 60));
+    }
+
+    #[test]
+    fn test_tracked_duration_sum() {
+        let total: TrackedDuration = [600, 1200, 300].into_iter().map(TrackedDuration::from_seconds).sum();
+        assert_eq!(total.seconds(), 2100);
+
+        let mut running = TrackedDuration::between(at(9, 0, 0), at(9, 22, 0));
+        running += TrackedDuration::from_seconds(60);
+        assert_eq!(running.seconds(), 23 * 60);
+    }
+
+    #[test]
+    fn test_is_clock_gap() {
+        let last = at(9, 0, 0);
+
+        assert!(!is_clock_gap(last, last + Duration::seconds(1)));
+        assert!(!is_clock_gap(last, last + Duration::seconds(MAX_TICK_INTERVAL_SECS)));
+        assert!(is_clock_gap(last, last + Duration::hours(3)));
+        assert!(is_clock_gap(last, last - Duration::seconds(30)));
+    }
+
+    fn rounding(interval_minutes: i64, mode: RoundingMode, scope: RoundingScope) -> Rounding {
+        Rounding {
+            interval_minutes,
+            mode,
+            scope,
+        }
+    }
+
+    #[test]
+    fn test_rounding_off() {
+        let rule = Rounding::default();
+
+        assert_eq!(rule.round(3601), 3601);
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let nearest = rounding(15, RoundingMode::Nearest, RoundingScope::Entries);
+        let up = rounding(15, RoundingMode::Up, RoundingScope::Entries);
+        let down = rounding(15, RoundingMode::Down, RoundingScope::Entries);
+
+        // 22 minutes
+        assert_eq!(nearest.round(22 * 60), 15 * 60);
+        assert_eq!(up.round(22 * 60), 30 * 60);
+        assert_eq!(down.round(22 * 60), 15 * 60);
+
+        // 23 min 30 s rounds to nearest 30
+        assert_eq!(nearest.round(23 * 60 + 30), 30 * 60);
+
+        // Exact multiples stay untouched
+        assert_eq!(up.round(30 * 60), 30 * 60);
+        assert_eq!(down.round(30 * 60), 30 * 60);
+    }
+
+    #[test]
+    fn test_rounding_six_minutes() {
+        let rule = rounding(6, RoundingMode::Up, RoundingScope::Entries);
+
+        assert_eq!(rule.round(1), 6 * 60);
+        assert_eq!(rule.round(61 * 60), 66 * 60);
+    }
+
+    #[test]
+    fn test_rounding_scope_entries() {
+        let rule = rounding(15, RoundingMode::Up, RoundingScope::Entries);
+        let entries = vec![
+            entry(at(9, 0, 0), Some(at(9, 10, 0))),
+            entry(at(10, 0, 0), Some(at(10, 10, 0))),
+        ];
+
+        // Each ten minute entry rounds up to 15
+        assert_eq!(rule.total_seconds(&entries, at(12, 0, 0)), 30 * 60);
+    }
+
+    #[test]
+    fn test_rounding_scope_reports() {
+        let rule = rounding(15, RoundingMode::Up, RoundingScope::Reports);
+        let entries = vec![
+            entry(at(9, 0, 0), Some(at(9, 10, 0))),
+            entry(at(10, 0, 0), Some(at(10, 10, 0))),
+        ];
+
+        // Entries keep raw durations, the 20 minute total rounds up to 30
+        assert_eq!(rule.entry_seconds(&entries[0], at(12, 0, 0)), 10 * 60);
+        assert_eq!(rule.total_seconds(&entries, at(12, 0, 0)), 30 * 60);
+    }
+
+    #[test]
+    fn test_rounding_skips_running_entries() {
+        let rule = rounding(15, RoundingMode::Up, RoundingScope::Entries);
+        let running = entry(at(9, 0, 0), None);
+
+        assert_eq!(rule.entry_seconds(&running, at(9, 1, 0)), 60);
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::NaiveDate;
+use thiserror::Error;
+
+/// Why an operation on the app's data failed, telling apart what the user
+/// can act on (a missing record, a conflict, invalid input) from failures
+/// of the database or the file system
+#[derive(Debug, Error)]
+pub enum TimeTrackingError {
+    /// The entry, project or other record doesn't exist, e.g. because it
+    /// was deleted meanwhile
+    #[error("The {0} no longer exists")]
+    NotFound(&'static str),
+    #[error(transparent)]
+    Conflict(#[from] Conflict),
+    /// Input that can't be saved, with a message for the user
+    #[error("{0}")]
+    Validation(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Sqlite(rusqlite::Error),
+}
+
+/// A change refused because of other data
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Conflict {
+    /// The entry would overlap another one, named by its description
+    #[error("The entry would overlap \"{0}\"")]
+    Overlap(String),
+    /// The entry would start on or before the last locked day
+    #[error("The entry would start in the locked period, which ends {}", .0.format("%b %-d, %Y"))]
+    Locked(NaiveDate),
+}
+
+impl From<rusqlite::Error> for TimeTrackingError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => TimeTrackingError::NotFound("record"),
+            e => TimeTrackingError::Sqlite(e),
+        }
+    }
+}
+
+/// Result of the app's operations on its data
+pub type Result<T> = std::result::Result<T, TimeTrackingError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_rows_are_not_found() {
+        let error = TimeTrackingError::from(rusqlite::Error::QueryReturnedNoRows);
+        assert!(matches!(error, TimeTrackingError::NotFound(_)));
+
+        let error = TimeTrackingError::from(rusqlite::Error::InvalidQuery);
+        assert!(matches!(error, TimeTrackingError::Sqlite(_)));
+    }
+
+    #[test]
+    fn test_conflict_messages() {
+        let locked = TimeTrackingError::from(Conflict::Locked(NaiveDate::from_ymd_opt(2024, 3, 31).unwrap()));
+        assert_eq!(
+            locked.to_string(),
+            "The entry would start in the locked period, which ends Mar 31, 2024"
+        );
+        let overlap = TimeTrackingError::from(Conflict::Overlap("Standup".to_string()));
+        assert_eq!(overlap.to_string(), "The entry would overlap \"Standup\"");
+    }
+}
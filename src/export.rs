@@ -0,0 +1,554 @@
+use chrono::{DateTime, FixedOffset, Local, Offset, TimeZone, Utc};
+use rusqlite::Connection;
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use crate::db::{self, TimeEntry};
+use crate::duration::{Rounding, TrackedDuration};
+use crate::error;
+use crate::privacy::{self, PrivacyMode};
+use crate::report::ReportRange;
+use crate::stats::DayPresence;
+
+/// Entries written between two progress reports
+const PROGRESS_INTERVAL: usize = 500;
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One row per entry, with the headers the importer recognizes
+    Csv,
+    /// An array of entry objects
+    Json,
+}
+
+impl ExportFormat {
+    /// All formats, in the order they are offered when saving
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Csv, ExportFormat::Json];
+
+    /// Human readable name shown in the save dialog
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    /// Picks the format from a file name, defaulting to CSV
+    pub fn from_path(path: &Path) -> ExportFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::Csv,
+        }
+    }
+}
+
+/// Offsets offered besides local time, in minutes east of UTC: whole hours
+/// plus the half and quarter hour zones in common use
+const OFFSET_CHOICES: [i32; 33] = [
+    -720, -660, -600, -570, -540, -480, -420, -360, -300, -240, -210, -180, -120, -60, 0, 60, 120, 180,
+    210, 240, 270, 300, 330, 345, 360, 390, 420, 480, 540, 570, 600, 660, 720,
+];
+
+/// Time zone the timestamps of an export are written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportTimeZone {
+    /// The system time zone, with the offset in effect at each entry
+    Local,
+    /// A fixed offset from UTC, such as UTC itself or a client's time zone
+    Fixed(FixedOffset),
+}
+
+impl ExportTimeZone {
+    /// Local time followed by the fixed offsets, in the order they are offered
+    pub fn choices() -> Vec<ExportTimeZone> {
+        std::iter::once(ExportTimeZone::Local)
+            .chain(
+                OFFSET_CHOICES
+                    .iter()
+                    .filter_map(|minutes| FixedOffset::east_opt(minutes * 60))
+                    .map(ExportTimeZone::Fixed),
+            )
+            .collect()
+    }
+
+    /// Identifier stored in the save dialog, "local" or an offset like "+05:30"
+    pub fn id(self) -> String {
+        match self {
+            ExportTimeZone::Local => "local".to_string(),
+            ExportTimeZone::Fixed(offset) => offset.to_string(),
+        }
+    }
+
+    /// Parses an identifier made by `id`
+    pub fn from_id(id: &str) -> Option<ExportTimeZone> {
+        if id == "local" {
+            return Some(ExportTimeZone::Local);
+        }
+        DateTime::parse_from_rfc3339(&format!("2000-01-01T00:00:00{}", id))
+            .ok()
+            .map(|time| ExportTimeZone::Fixed(*time.offset()))
+    }
+
+    /// Human readable name shown in the save dialog
+    pub fn label(self) -> String {
+        match self {
+            ExportTimeZone::Local => "Local time".to_string(),
+            ExportTimeZone::Fixed(offset) if offset.local_minus_utc() == 0 => "UTC".to_string(),
+            ExportTimeZone::Fixed(offset) => format!("UTC{}", offset),
+        }
+    }
+
+    /// `time` in this time zone, keeping the offset so it can be written out
+    fn convert(self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            ExportTimeZone::Local => {
+                let local = time.with_timezone(&Local);
+                local.with_timezone(&local.offset().fix())
+            }
+            ExportTimeZone::Fixed(offset) => time.with_timezone(&offset),
+        }
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Encodes a string as a JSON string literal
+pub(crate) fn json_string(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() + 2);
+    encoded.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\r' => encoded.push_str("\\r"),
+            '\t' => encoded.push_str("\\t"),
+            c if (c as u32) < 0x20 => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// Writes entries one at a time in an export format
+pub struct ExportWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    time_zone: ExportTimeZone,
+    rounding: Rounding,
+    written: usize,
+}
+
+impl<W: Write> ExportWriter<W> {
+    /// Starts an export, writing the CSV header or opening the JSON array
+    pub fn new(mut out: W, format: ExportFormat, time_zone: ExportTimeZone, rounding: Rounding) -> io::Result<Self> {
+        match format {
+            ExportFormat::Csv => writeln!(
+                out,
+                "Date,Start,End,UTC Offset,Duration (seconds),Duration (hours),Project,Description"
+            )?,
+            ExportFormat::Json => write!(out, "[")?,
+        }
+        Ok(Self {
+            out,
+            format,
+            time_zone,
+            rounding,
+            written: 0,
+        })
+    }
+
+    /// Writes one entry. Timestamps are ISO 8601 with the offset of the
+    /// export's time zone; running entries have no end or duration. Being
+    /// a report, the duration is rounded whatever the rounding scope, while
+    /// the timestamps stay as tracked.
+    pub fn write_entry(&mut self, entry: &TimeEntry, project: Option<&str>) -> io::Result<()> {
+        let start = self.time_zone.convert(entry.start_time);
+        let end = entry.end_time.map(|end| self.time_zone.convert(end));
+        let seconds = entry
+            .end_time
+            .map(|end| self.rounding.round((end - entry.start_time).num_seconds().max(0)));
+
+        match self.format {
+            ExportFormat::Csv => writeln!(
+                self.out,
+                "{},{},{},{},{},{},{},{}",
+                start.format("%Y-%m-%d"),
+                start.to_rfc3339(),
+                end.map(|end| end.to_rfc3339()).unwrap_or_default(),
+                start.offset(),
+                seconds.map(|seconds| seconds.to_string()).unwrap_or_default(),
+                seconds
+                    .map(|seconds| TrackedDuration::from_seconds(seconds).industrial())
+                    .unwrap_or_default(),
+                csv_field(project.unwrap_or("")),
+                csv_field(&entry.description)
+            )?,
+            ExportFormat::Json => write!(
+                self.out,
+                "{}\n  {{\"id\": {}, \"start\": {}, \"end\": {}, \"utc_offset\": {}, \"duration_seconds\": {}, \"project\": {}, \"description\": {}}}",
+                if self.written == 0 { "" } else { "," },
+                entry.id,
+                json_string(&start.to_rfc3339()),
+                end.map(|end| json_string(&end.to_rfc3339()))
+                    .unwrap_or_else(|| "null".to_string()),
+                json_string(&start.offset().to_string()),
+                seconds
+                    .map(|seconds| seconds.to_string())
+                    .unwrap_or_else(|| "null".to_string()),
+                project.map(json_string).unwrap_or_else(|| "null".to_string()),
+                json_string(&entry.description)
+            )?,
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Closes the JSON array and flushes, returning the number of entries written
+    pub fn finish(mut self) -> io::Result<usize> {
+        if self.format == ExportFormat::Json {
+            writeln!(self.out, "{}]", if self.written == 0 { "" } else { "\n" })?;
+        }
+        self.out.flush()?;
+        Ok(self.written)
+    }
+}
+
+/// Streams the entries of `range` to `out`, reading and writing one entry at
+/// a time, with timestamps in `time_zone` and durations rounded by `rounding`.
+/// Private entries follow the privacy mode. `progress` is called with the
+/// entries done and the total every few hundred entries and returns false to
+/// cancel. Returns the number written, or `None` when cancelled.
+#[allow(clippy::too_many_arguments)]
+pub fn export_range<W: Write>(
+    conn: &Connection,
+    range: ReportRange,
+    format: ExportFormat,
+    time_zone: ExportTimeZone,
+    rounding: Rounding,
+    privacy_mode: PrivacyMode,
+    out: W,
+    mut progress: impl FnMut(usize, usize) -> bool,
+) -> error::Result<Option<usize>> {
+    // One read transaction, so the count and the rows come from the same snapshot
+    let snapshot = conn.unchecked_transaction()?;
+    let total = db::count_entries_in_range(&snapshot, range.start, range.end)?;
+    let mut writer = ExportWriter::new(out, format, time_zone, rounding)?;
+    let mut done = 0;
+    let mut write_error = None;
+
+    let complete = db::for_each_entry_in_range(&snapshot, range.start, range.end, |entry, project| {
+        if let Some(shared) = privacy::shared_entry(entry, privacy_mode) {
+            // Anonymized entries lose their project
+            let project = shared.project_id.and(project);
+            if let Err(e) = writer.write_entry(&shared, project) {
+                write_error = Some(e);
+                return ControlFlow::Break(());
+            }
+        }
+        done += 1;
+        if done % PROGRESS_INTERVAL == 0 && !progress(done, total) {
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    })?;
+
+    if let Some(e) = write_error {
+        return Err(e.into());
+    }
+    if !complete {
+        return Ok(None);
+    }
+    progress(total, total);
+    Ok(Some(writer.finish()?))
+}
+
+/// File name suggested when saving an export of `range`
+pub fn default_file_name(range: ReportRange, format: ExportFormat) -> String {
+    format!(
+        "time-entries-{}-to-{}.{}",
+        range.start.format("%Y-%m-%d"),
+        range.end.format("%Y-%m-%d"),
+        format.extension()
+    )
+}
+
+/// Daily presence as CSV: the date, arrival and departure as times in `tz`,
+/// then presence and tracked time in decimal hours. Days with a running
+/// entry have no departure.
+pub fn presence_csv<Tz: TimeZone>(days: &[DayPresence], tz: &Tz) -> String
+where
+    Tz::Offset: fmt::Display,
+{
+    let hours = |seconds: i64| TrackedDuration::from_seconds(seconds).industrial();
+    let mut csv = String::from("date,arrived,left,presence_hours,tracked_hours\n");
+    for day in days {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            day.date.format("%Y-%m-%d"),
+            day.arrived.with_timezone(tz).format("%H:%M"),
+            day.left
+                .map(|left| left.with_timezone(tz).format("%H:%M").to_string())
+                .unwrap_or_default(),
+            hours(day.presence_seconds),
+            hours(day.tracked_seconds)
+        ));
+    }
+    csv
+}
+
+/// File name suggested when saving the presence of `range`
+pub fn presence_file_name(range: ReportRange) -> String {
+    format!(
+        "presence-{}-to-{}.csv",
+        range.start.format("%Y-%m-%d"),
+        range.end.format("%Y-%m-%d")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use rusqlite::params;
+    use crate::db::test_support;
+    use crate::duration::RoundingScope;
+
+    fn entry(description: &str, private: bool, running: bool) -> TimeEntry {
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 8, 0, 0).unwrap();
+        TimeEntry {
+            id: 7,
+            project_id: Some(1),
+            description: description.to_string(),
+            private,
+            ..test_support::entry(start, (!running).then(|| start + chrono::Duration::minutes(90)))
+        }
+    }
+
+    fn plus_one_hour() -> ExportTimeZone {
+        ExportTimeZone::Fixed(FixedOffset::east_opt(3600).unwrap())
+    }
+
+    fn export_to_string(format: ExportFormat, entries: &[(TimeEntry, Option<&str>)]) -> String {
+        let mut out = Vec::new();
+        let mut writer = ExportWriter::new(&mut out, format, plus_one_hour(), Rounding::default()).unwrap();
+        for (entry, project) in entries {
+            writer.write_entry(entry, *project).unwrap();
+        }
+        writer.finish().unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let text = export_to_string(
+            ExportFormat::Csv,
+            &[
+                (entry("Review, \"v2\"", false, false), Some("Website")),
+                (entry("Ongoing", false, true), None),
+            ],
+        );
+
+        assert_eq!(
+            text,
+            "Date,Start,End,UTC Offset,Duration (seconds),Duration (hours),Project,Description\n\
+             2024-03-04,2024-03-04T09:00:00+01:00,2024-03-04T10:30:00+01:00,+01:00,5400,1.50,Website,\"Review, \"\"v2\"\"\"\n\
+             2024-03-04,2024-03-04T09:00:00+01:00,,+01:00,,,,Ongoing\n"
+        );
+    }
+
+    #[test]
+    fn test_json_export() {
+        let text = export_to_string(ExportFormat::Json, &[(entry("Line\nbreak \"quoted\"", false, true), None)]);
+
+        assert!(text.starts_with("[\n  {\"id\": 7, \"start\": \"2024-03-04T09:00:00+01:00\""));
+        assert!(text.contains("\"end\": null, \"utc_offset\": \"+01:00\", \"duration_seconds\": null, \"project\": null"));
+        assert!(text.contains("\"description\": \"Line\\nbreak \\\"quoted\\\"\"}"));
+        assert!(text.ends_with("}\n]\n"));
+    }
+
+    #[test]
+    fn test_empty_json_export() {
+        assert_eq!(export_to_string(ExportFormat::Json, &[]), "[]\n");
+    }
+
+    #[test]
+    fn test_time_zone_ids() {
+        for time_zone in ExportTimeZone::choices() {
+            assert_eq!(ExportTimeZone::from_id(&time_zone.id()), Some(time_zone));
+        }
+        assert_eq!(plus_one_hour().id(), "+01:00");
+        assert_eq!(ExportTimeZone::from_id("-09:30").map(ExportTimeZone::label).as_deref(), Some("UTC-09:30"));
+        assert_eq!(ExportTimeZone::Fixed(FixedOffset::east_opt(0).unwrap()).label(), "UTC");
+        assert_eq!(ExportTimeZone::from_id("Europe/Paris"), None);
+    }
+
+    #[test]
+    fn test_utc_export_keeps_the_instant() {
+        let entry = entry("Standup", false, false);
+        let utc = ExportTimeZone::Fixed(FixedOffset::east_opt(0).unwrap());
+        let mut out = Vec::new();
+        let mut writer = ExportWriter::new(&mut out, ExportFormat::Csv, utc, Rounding::default()).unwrap();
+        writer.write_entry(&entry, None).unwrap();
+        writer.finish().unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.ends_with("\n2024-03-04,2024-03-04T08:00:00+00:00,2024-03-04T09:30:00+00:00,+00:00,5400,1.50,,Standup\n"));
+    }
+
+    #[test]
+    fn test_export_rounds_durations() {
+        let entry = TimeEntry {
+            end_time: Some(Utc.with_ymd_and_hms(2024, 3, 4, 9, 38, 0).unwrap()),
+            ..entry("Standup", false, false)
+        };
+        let rounding = Rounding {
+            interval_minutes: 15,
+            scope: RoundingScope::Reports,
+            ..Rounding::default()
+        };
+        let mut out = Vec::new();
+        let mut writer = ExportWriter::new(&mut out, ExportFormat::Csv, plus_one_hour(), rounding).unwrap();
+        writer.write_entry(&entry, None).unwrap();
+        writer.finish().unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.ends_with("\n2024-03-04,2024-03-04T09:00:00+01:00,2024-03-04T10:38:00+01:00,+01:00,6300,1.75,,Standup\n"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ExportFormat::from_path(Path::new("a.JSON")), ExportFormat::Json);
+        assert_eq!(ExportFormat::from_path(Path::new("a.csv")), ExportFormat::Csv);
+        assert_eq!(ExportFormat::from_path(Path::new("a")), ExportFormat::Csv);
+    }
+
+    fn range() -> ReportRange {
+        ReportRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+        )
+    }
+
+    fn insert_entries(conn: &Connection, count: usize) {
+        let project = db::create_project(conn, "Website", "#3498db").unwrap();
+        for index in 0..count {
+            conn.execute(
+                "INSERT INTO time_entries (project_id, description, start_time, end_time, private)
+                 VALUES (?1, ?2, '2024-05-01 09:00:00', '2024-05-01 10:00:00', ?3)",
+                params![project.id, format!("Task {}", index), index == 0],
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_export_range_respects_privacy() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::create_tables(&conn).unwrap();
+        insert_entries(&conn, 3);
+
+        let mut out = Vec::new();
+        let written = export_range(
+            &conn,
+            range(),
+            ExportFormat::Csv,
+            ExportTimeZone::Local,
+            Rounding::default(),
+            PrivacyMode::Exclude,
+            &mut out,
+            |_, _| true,
+        )
+        .unwrap();
+        assert_eq!(written, Some(2));
+
+        let mut out = Vec::new();
+        let written = export_range(
+            &conn,
+            range(),
+            ExportFormat::Csv,
+            ExportTimeZone::Local,
+            Rounding::default(),
+            PrivacyMode::Anonymize,
+            &mut out,
+            |_, _| true,
+        )
+        .unwrap();
+        assert_eq!(written, Some(3));
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains(",,Private\n"));
+        assert!(!text.contains("Task 0"));
+    }
+
+    #[test]
+    fn test_export_range_cancel() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::create_tables(&conn).unwrap();
+        insert_entries(&conn, PROGRESS_INTERVAL + 10);
+
+        let mut reports = Vec::new();
+        let written = export_range(
+            &conn,
+            range(),
+            ExportFormat::Json,
+            ExportTimeZone::Local,
+            Rounding::default(),
+            PrivacyMode::Anonymize,
+            Vec::new(),
+            |done, total| {
+                reports.push((done, total));
+                false
+            },
+        )
+        .unwrap();
+
+        assert_eq!(written, None);
+        assert_eq!(reports, [(PROGRESS_INTERVAL, PROGRESS_INTERVAL + 10)]);
+    }
+
+    #[test]
+    fn test_presence_csv() {
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 3, 4, h, m, 0).unwrap();
+        let days = [
+            DayPresence {
+                date: NaiveDate::from_ymd_opt(2024, 3, 4).unwrap(),
+                arrived: at(8, 45),
+                left: Some(at(17, 15)),
+                presence_seconds: 8 * 3600 + 1800,
+                tracked_seconds: 7 * 3600 + 900,
+            },
+            DayPresence {
+                date: NaiveDate::from_ymd_opt(2024, 3, 5).unwrap(),
+                arrived: at(9, 0) + chrono::Duration::days(1),
+                left: None,
+                presence_seconds: 3600,
+                tracked_seconds: 3600,
+            },
+        ];
+
+        assert_eq!(
+            presence_csv(&days, &Utc),
+            "date,arrived,left,presence_hours,tracked_hours\n\
+             2024-03-04,08:45,17:15,8.50,7.25\n\
+             2024-03-05,09:00,,1.00,1.00\n"
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::NaiveDate;
+
+use crate::db::Project;
+
+/// Projects the week's total from the pace so far. Work days are the first
+/// `work_days` days of the week from `week_start`. The pace is the average
+/// tracked per work day before today, or the expected daily time on the
+/// first work day. Today is counted as at least that pace, and the
+/// remaining work days at that pace.
+///
+/// Returns `None` when `today` is outside the week, as past weeks have
+/// their final total and future weeks nothing to go on.
+pub fn projected_week_seconds(
+    week_start: NaiveDate,
+    today: NaiveDate,
+    work_days: u32,
+    seconds_before_today: i64,
+    seconds_today: i64,
+    expected_daily_seconds: i64,
+) -> Option<i64> {
+    let day_index = (today - week_start).num_days();
+    if !(0..7).contains(&day_index) {
+        return None;
+    }
+    let day_index = day_index as u32;
+
+    let days_done = day_index.min(work_days) as i64;
+    let days_after_today = work_days.saturating_sub(day_index + 1) as i64;
+    let pace = if days_done > 0 {
+        seconds_before_today / days_done
+    } else {
+        expected_daily_seconds
+    };
+    let rest_of_today = if day_index < work_days {
+        (pace - seconds_today).max(0)
+    } else {
+        0
+    };
+
+    Some(seconds_before_today + seconds_today + rest_of_today + pace * days_after_today)
+}
+
+/// Whole hours for "on track for 41h", rounded to the nearest hour
+pub fn format_projection(seconds: i64) -> String {
+    format!("On track for {}h", (seconds + 1800) / 3600)
+}
+
+/// One day of a weekly target's burndown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BurndownDay {
+    /// What is left of the target at the end of the day, `None` for days
+    /// still to come
+    pub remaining: Option<i64>,
+    /// What would be left at an even pace over the work days
+    pub ideal: i64,
+}
+
+/// Burns the weekly target down day by day from `week_start`, given the
+/// seconds tracked on each day of the week. Days after `today` have no
+/// remaining time yet, so a future week shows only the ideal pace.
+pub fn week_burndown(
+    week_start: NaiveDate,
+    today: NaiveDate,
+    work_days: u32,
+    target_seconds: i64,
+    daily_seconds: &[i64; 7],
+) -> [BurndownDay; 7] {
+    let work_days = work_days.clamp(1, 7) as i64;
+    let mut tracked = 0;
+    std::array::from_fn(|index| {
+        tracked += daily_seconds[index];
+        let day = week_start + chrono::Days::new(index as u64);
+        let days_worked = (index as i64 + 1).min(work_days);
+        BurndownDay {
+            remaining: (day <= today).then(|| (target_seconds - tracked).max(0)),
+            ideal: target_seconds - target_seconds * days_worked / work_days,
+        }
+    })
+}
+
+/// How far along the daily goal the day is, from 0 to 1. A goal of
+/// nothing is met from the start.
+pub fn goal_fraction(tracked_seconds: i64, goal_seconds: i64) -> f64 {
+    if goal_seconds <= 0 {
+        return 1.0;
+    }
+    (tracked_seconds.max(0) as f64 / goal_seconds as f64).min(1.0)
+}
+
+/// How many days before its deadline a project is warned about
+pub const DEADLINE_WARNING_DAYS: i64 = 7;
+
+/// Describes how far `today` is from `deadline`, e.g. "3 days left", "Due
+/// today" or "2 days overdue"
+pub fn format_deadline(deadline: NaiveDate, today: NaiveDate) -> String {
+    let days = (deadline - today).num_days();
+    match days {
+        0 => "Due today".to_string(),
+        1 => "1 day left".to_string(),
+        -1 => "1 day overdue".to_string(),
+        days if days > 0 => format!("{} days left", days),
+        days => format!("{} days overdue", -days),
+    }
+}
+
+/// Active projects due within `DEADLINE_WARNING_DAYS` of `today` or
+/// overdue, the soonest first
+pub fn deadline_warnings(projects: &[Project], today: NaiveDate) -> Vec<(&Project, NaiveDate)> {
+    let mut warnings: Vec<(&Project, NaiveDate)> = projects
+        .iter()
+        .filter(|project| !project.archived)
+        .filter_map(|project| Some((project, project.deadline?)))
+        .filter(|(_, deadline)| (*deadline - today).num_days() <= DEADLINE_WARNING_DAYS)
+        .collect();
+    warnings.sort_by_key(|(project, deadline)| (*deadline, project.name.clone()));
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    const HOUR: i64 = 3600;
+
+    fn monday() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()
+    }
+
+    fn day(offset: u64) -> NaiveDate {
+        monday() + chrono::Days::new(offset)
+    }
+
+    #[test]
+    fn test_first_work_day_uses_expected_time() {
+        let projected = projected_week_seconds(monday(), monday(), 5, 0, 2 * HOUR, 8 * HOUR);
+
+        assert_eq!(projected, Some(40 * HOUR));
+    }
+
+    #[test]
+    fn test_pace_of_previous_days_is_extrapolated() {
+        // Wednesday, after two 9 hour days and 3 hours so far today
+        let projected = projected_week_seconds(monday(), day(2), 5, 18 * HOUR, 3 * HOUR, 8 * HOUR);
+
+        assert_eq!(projected, Some(45 * HOUR));
+    }
+
+    #[test]
+    fn test_today_beyond_pace_counts_as_tracked() {
+        let projected = projected_week_seconds(monday(), day(1), 5, 6 * HOUR, 10 * HOUR, 8 * HOUR);
+
+        assert_eq!(projected, Some(6 * HOUR + 10 * HOUR + 3 * 6 * HOUR));
+    }
+
+    #[test]
+    fn test_weekend_adds_only_what_is_tracked() {
+        let projected = projected_week_seconds(monday(), day(5), 5, 40 * HOUR, HOUR, 8 * HOUR);
+
+        assert_eq!(projected, Some(41 * HOUR));
+    }
+
+    #[test]
+    fn test_no_projection_outside_the_week() {
+        assert_eq!(projected_week_seconds(monday(), day(7), 5, 0, 0, 8 * HOUR), None);
+        assert_eq!(
+            projected_week_seconds(monday(), monday() - chrono::Days::new(1), 5, 0, 0, 8 * HOUR),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_projection_rounds_to_hours() {
+        assert_eq!(format_projection(41 * HOUR + 1799), "On track for 41h");
+        assert_eq!(format_projection(41 * HOUR + 1800), "On track for 42h");
+    }
+
+    #[test]
+    fn test_week_burndown() {
+        let daily = [8 * HOUR, 10 * HOUR, 4 * HOUR, 0, 0, 0, 0];
+
+        let burndown = week_burndown(monday(), day(2), 4, 32 * HOUR, &daily);
+
+        let remaining: Vec<Option<i64>> = burndown.iter().map(|day| day.remaining).collect();
+        let ideal: Vec<i64> = burndown.iter().map(|day| day.ideal).collect();
+        assert_eq!(
+            remaining,
+            vec![Some(24 * HOUR), Some(14 * HOUR), Some(10 * HOUR), None, None, None, None]
+        );
+        assert_eq!(ideal, vec![24 * HOUR, 16 * HOUR, 8 * HOUR, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_week_burndown_stops_at_target() {
+        let daily = [30 * HOUR, 20 * HOUR, 0, 0, 0, 0, 0];
+
+        let burndown = week_burndown(monday(), day(10), 5, 40 * HOUR, &daily);
+
+        assert_eq!(burndown[1].remaining, Some(0));
+        assert_eq!(burndown[6].remaining, Some(0));
+    }
+
+    #[test]
+    fn test_goal_fraction_is_capped() {
+        assert_eq!(goal_fraction(3 * HOUR, 6 * HOUR), 0.5);
+        assert_eq!(goal_fraction(7 * HOUR, 6 * HOUR), 1.0);
+        assert_eq!(goal_fraction(HOUR, 0), 1.0);
+    }
+
+    #[test]
+    fn test_format_deadline() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+        assert_eq!(format_deadline(today, today), "Due today");
+        assert_eq!(format_deadline(today + chrono::Days::new(1), today), "1 day left");
+        assert_eq!(format_deadline(today + chrono::Days::new(9), today), "9 days left");
+        assert_eq!(format_deadline(today - chrono::Days::new(1), today), "1 day overdue");
+        assert_eq!(format_deadline(today - chrono::Days::new(3), today), "3 days overdue");
+    }
+
+    #[test]
+    fn test_deadline_warnings() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+        let project = |name: &str, days: Option<i64>, archived: bool| Project {
+            archived,
+            deadline: days.map(|days| today + chrono::Duration::days(days)),
+            ..test_support::project(0, name)
+        };
+        let projects = vec![
+            project("Later", Some(DEADLINE_WARNING_DAYS + 1), false),
+            project("Soon", Some(DEADLINE_WARNING_DAYS), false),
+            project("Overdue", Some(-2), false),
+            project("Archived", Some(1), true),
+            project("Open-ended", None, false),
+        ];
+
+        let names: Vec<&str> = deadline_warnings(&projects, today)
+            .iter()
+            .map(|(project, _)| project.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Overdue", "Soon"]);
+    }
+}
@@ -0,0 +1,80 @@
+/// Scores how well `query` matches `text` when its characters appear in
+/// order, not necessarily next to each other, ignoring case. Matches at the
+/// start of words and runs of adjacent characters score higher. Returns
+/// `None` when `text` doesn't contain the query; an empty query matches
+/// everything with a score of zero.
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let mut query_chars = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in text.chars() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        let matched = c.to_lowercase().eq(std::iter::once(wanted));
+        if matched {
+            query_chars.next();
+            score += 1;
+            if previous_matched {
+                score += 4;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 6;
+            }
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+
+    query_chars.peek().is_none().then_some(score)
+}
+
+/// Keeps the items matching `query`, best match first. Items scoring the
+/// same keep their order.
+pub fn filter<T>(query: &str, items: Vec<T>, text: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut scored: Vec<(i64, T)> = items
+        .into_iter()
+        .filter_map(|item| score(query, text(&item)).map(|score| (score, item)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_requires_characters_in_order() {
+        assert!(score("stt", "Start timer").is_some());
+        assert!(score("STAR", "start timer").is_some());
+        assert!(score("tts", "Start timer").is_none());
+        assert_eq!(score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn test_score_prefers_word_starts_and_runs() {
+        let word_start = score("st", "Stop timer").unwrap();
+        let inside = score("st", "Last entry").unwrap();
+        assert!(word_start > inside);
+
+        let run = score("rev", "Open review queue").unwrap();
+        let scattered = score("rev", "Ordered events").unwrap();
+        assert!(run > scattered);
+    }
+
+    #[test]
+    fn test_filter_orders_by_score() {
+        let items = vec!["Stop timer", "Switch to Month view", "Open projects"];
+
+        let matches = filter("op", items, |item| item);
+
+        assert_eq!(matches, ["Open projects", "Stop timer"]);
+    }
+}
@@ -0,0 +1,403 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+
+/// Meaning of a column in imported delimited text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Ignore,
+    Date,
+    Start,
+    End,
+    Description,
+    Project,
+}
+
+impl Column {
+    /// All column kinds, in the order they are offered in the mapping dialog
+    pub const ALL: [Column; 6] = [
+        Column::Ignore,
+        Column::Date,
+        Column::Start,
+        Column::End,
+        Column::Description,
+        Column::Project,
+    ];
+
+    /// Human readable name shown in the mapping dialog
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Ignore => "Ignore",
+            Column::Date => "Date",
+            Column::Start => "Start",
+            Column::End => "End",
+            Column::Description => "Description",
+            Column::Project => "Project",
+        }
+    }
+
+    /// Recognizes a header cell such as "Start time" or "Task" by its words,
+    /// so "Weekend" or "Calendar" don't pass for the end. Start and end are
+    /// matched first, so "Start date" is the start rather than the date.
+    fn from_header(cell: &str) -> Option<Column> {
+        let cell = cell.trim().to_lowercase();
+        let words: Vec<&str> = cell.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+        let has_word = |names: &[&str]| words.iter().any(|word| names.contains(word));
+
+        if has_word(&["start", "started", "starttime"]) || cell == "from" {
+            Some(Column::Start)
+        } else if has_word(&["end", "ended", "endtime", "stop", "stopped"]) || cell == "to" {
+            Some(Column::End)
+        } else if has_word(&["date", "day"]) {
+            Some(Column::Date)
+        } else if has_word(&["desc", "description", "task", "note", "notes"]) {
+            Some(Column::Description)
+        } else if has_word(&["project", "client"]) {
+            Some(Column::Project)
+        } else {
+            None
+        }
+    }
+}
+
+/// An entry parsed from a row of delimited text, ready to be created
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedEntry {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub description: String,
+    pub project_name: Option<String>,
+}
+
+/// Splits one line into cells. Double quotes group cells containing the
+/// delimiter, and a doubled quote inside quotes is a literal quote.
+fn split_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+
+    cells.into_iter().map(|c| c.trim().to_string()).collect()
+}
+
+/// Splits text into rows of cells, skipping blank lines
+pub fn split_rows(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| split_line(line, delimiter))
+        .collect()
+}
+
+/// Detects the delimiter of pasted text: tabs from spreadsheets, otherwise commas
+pub fn detect_delimiter(text: &str) -> char {
+    if text.contains('\t') {
+        '\t'
+    } else {
+        ','
+    }
+}
+
+/// Guesses the column mapping for the rows. Returns the mapping and whether
+/// the first row is a header that should be skipped.
+pub fn guess_columns(rows: &[Vec<String>]) -> (Vec<Column>, bool) {
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+
+    if let Some(first) = rows.first() {
+        let from_header: Vec<Option<Column>> = first.iter().map(|c| Column::from_header(c)).collect();
+        if from_header.iter().filter(|c| c.is_some()).count() >= 2 {
+            let mut columns: Vec<Column> = from_header
+                .into_iter()
+                .map(|c| c.unwrap_or(Column::Ignore))
+                .collect();
+            columns.resize(width, Column::Ignore);
+            return (columns, true);
+        }
+    }
+
+    // Without a header, assume the common "date, start, end, description, project" layout,
+    // dropping the date when the first cell doesn't look like one
+    let has_date = rows
+        .first()
+        .and_then(|r| r.first())
+        .is_some_and(|c| parse_date(c).is_some());
+    let layout: &[Column] = if has_date {
+        &[Column::Date, Column::Start, Column::End, Column::Description, Column::Project]
+    } else {
+        &[Column::Start, Column::End, Column::Description, Column::Project]
+    };
+
+    let columns = (0..width)
+        .map(|i| layout.get(i).copied().unwrap_or(Column::Ignore))
+        .collect();
+    (columns, false)
+}
+
+/// Parses a date in ISO (2024-01-15) or day-first (15/01/2024, 15.01.2024) form
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%d/%m/%Y", "%d.%m.%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value.trim(), format).ok())
+}
+
+/// Parses a time of day such as 9:00, 09:00 or 09:00:30
+pub(crate) fn parse_time(value: &str) -> Option<NaiveTime> {
+    ["%H:%M", "%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value.trim(), format).ok())
+}
+
+/// Parses a full ISO 8601 timestamp with an offset, as written by exports
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Converts a local date and time to UTC
+pub(crate) fn local_to_utc(date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Parses one row using the column mapping. Rows without a date column use
+/// `default_date`. An end time before the start time is taken as the next day.
+/// Start and end timestamps with an offset are taken as they are.
+pub fn parse_row(
+    cells: &[String],
+    columns: &[Column],
+    default_date: NaiveDate,
+) -> Result<ImportedEntry, String> {
+    let cell = |column: Column| -> Option<&str> {
+        columns
+            .iter()
+            .position(|c| *c == column)
+            .and_then(|i| cells.get(i))
+            .map(|s| s.as_str())
+            .filter(|s| !s.is_empty())
+    };
+
+    let start_value = cell(Column::Start).ok_or("Missing start time")?;
+    let end_value = cell(Column::End).ok_or("Missing end time")?;
+
+    let (start_time, end_time) = match (parse_timestamp(start_value), parse_timestamp(end_value)) {
+        (Some(start_time), Some(end_time)) => (start_time, end_time),
+        _ => {
+            let date = match cell(Column::Date) {
+                Some(value) => parse_date(value).ok_or_else(|| format!("Invalid date \"{}\"", value))?,
+                None => default_date,
+            };
+            let start =
+                parse_time(start_value).ok_or_else(|| format!("Invalid start time \"{}\"", start_value))?;
+            let end = parse_time(end_value).ok_or_else(|| format!("Invalid end time \"{}\"", end_value))?;
+
+            let end_date = if end < start {
+                date.succ_opt().ok_or("Invalid end date")?
+            } else {
+                date
+            };
+
+            (
+                local_to_utc(date, start).ok_or("Start time does not exist in local time")?,
+                local_to_utc(end_date, end).ok_or("End time does not exist in local time")?,
+            )
+        }
+    };
+
+    Ok(ImportedEntry {
+        start_time,
+        end_time,
+        description: cell(Column::Description).unwrap_or_default().to_string(),
+        project_name: cell(Column::Project).map(|s| s.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn local(date: NaiveDate, h: u32, min: u32) -> DateTime<Utc> {
+        local_to_utc(date, NaiveTime::from_hms_opt(h, min, 0).unwrap()).unwrap()
+    }
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_split_rows_tab_separated() {
+        let rows = split_rows("09:00\t10:00\tStandup\n\n10:00\t12:00\tReview\n", '\t');
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], row(&["09:00", "10:00", "Standup"]));
+    }
+
+    #[test]
+    fn test_split_line_quotes() {
+        let cells = split_line("\"Write, review\",\"say \"\"hi\"\"\",x", ',');
+
+        assert_eq!(cells, row(&["Write, review", "say \"hi\"", "x"]));
+    }
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(detect_delimiter("a\tb"), '\t');
+        assert_eq!(detect_delimiter("a,b"), ',');
+    }
+
+    #[test]
+    fn test_guess_columns_from_header() {
+        let rows = vec![row(&["Task", "Start", "End", "Notes?", "Project"])];
+
+        let (columns, has_header) = guess_columns(&rows);
+
+        assert!(has_header);
+        assert_eq!(
+            columns,
+            vec![
+                Column::Description,
+                Column::Start,
+                Column::End,
+                Column::Description,
+                Column::Project
+            ]
+        );
+    }
+
+    #[test]
+    fn test_guess_columns_from_start_date_header() {
+        let rows = vec![row(&["Date", "Start date", "End date", "Description"])];
+
+        let (columns, has_header) = guess_columns(&rows);
+
+        assert!(has_header);
+        assert_eq!(columns, vec![Column::Date, Column::Start, Column::End, Column::Description]);
+    }
+
+    #[test]
+    fn test_guess_columns_matches_whole_words() {
+        let rows = vec![row(&["Calendar", "start_time", "End-Time", "Attendees", "Weekend", "Task notes"])];
+
+        let (columns, has_header) = guess_columns(&rows);
+
+        assert!(has_header);
+        assert_eq!(
+            columns,
+            vec![
+                Column::Ignore,
+                Column::Start,
+                Column::End,
+                Column::Ignore,
+                Column::Ignore,
+                Column::Description
+            ]
+        );
+    }
+
+    #[test]
+    fn test_guess_columns_without_header() {
+        let with_date = vec![row(&["2024-01-15", "09:00", "10:00", "Standup"])];
+        let without_date = vec![row(&["09:00", "10:00", "Standup", "Work", "extra"])];
+
+        assert_eq!(
+            guess_columns(&with_date),
+            (vec![Column::Date, Column::Start, Column::End, Column::Description], false)
+        );
+        assert_eq!(
+            guess_columns(&without_date),
+            (
+                vec![
+                    Column::Start,
+                    Column::End,
+                    Column::Description,
+                    Column::Project,
+                    Column::Ignore
+                ],
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_row_with_default_date() {
+        let today = date(2024, 1, 15);
+        let columns = [Column::Start, Column::End, Column::Description, Column::Project];
+
+        let entry = parse_row(&row(&["9:00", "10:15", "Standup", "Work"]), &columns, today).unwrap();
+
+        assert_eq!(entry.start_time, local(today, 9, 0));
+        assert_eq!(entry.end_time, local(today, 10, 15));
+        assert_eq!(entry.description, "Standup");
+        assert_eq!(entry.project_name, Some("Work".to_string()));
+    }
+
+    #[test]
+    fn test_parse_row_with_date_column() {
+        let columns = [Column::Date, Column::Start, Column::End, Column::Ignore];
+
+        let entry = parse_row(
+            &row(&["16/01/2024", "13:00", "14:00", "ignored"]),
+            &columns,
+            date(2024, 1, 15),
+        )
+        .unwrap();
+
+        assert_eq!(entry.start_time, local(date(2024, 1, 16), 13, 0));
+        assert_eq!(entry.description, "");
+        assert_eq!(entry.project_name, None);
+    }
+
+    #[test]
+    fn test_parse_row_crossing_midnight() {
+        let today = date(2024, 1, 15);
+        let columns = [Column::Start, Column::End];
+
+        let entry = parse_row(&row(&["23:00", "01:00"]), &columns, today).unwrap();
+
+        assert_eq!(entry.end_time, local(date(2024, 1, 16), 1, 0));
+    }
+
+    #[test]
+    fn test_parse_exported_row() {
+        let rows = split_rows(
+            "Date,Start,End,UTC Offset,Duration (seconds),Duration (hours),Project,Description\n\
+             2024-03-04,2024-03-04T23:30:00-05:00,2024-03-05T00:15:00-05:00,-05:00,2700,0.75,Work,Late fix\n",
+            ',',
+        );
+        let (columns, has_header) = guess_columns(&rows);
+        assert!(has_header);
+
+        let entry = parse_row(&rows[1], &columns, date(2024, 1, 15)).unwrap();
+
+        assert_eq!(entry.start_time, Utc.with_ymd_and_hms(2024, 3, 5, 4, 30, 0).unwrap());
+        assert_eq!(entry.end_time, Utc.with_ymd_and_hms(2024, 3, 5, 5, 15, 0).unwrap());
+        assert_eq!(entry.description, "Late fix");
+        assert_eq!(entry.project_name, Some("Work".to_string()));
+    }
+
+    #[test]
+    fn test_parse_row_errors() {
+        let today = date(2024, 1, 15);
+        let columns = [Column::Start, Column::End];
+
+        assert!(parse_row(&row(&["9:00"]), &columns, today).is_err());
+        assert!(parse_row(&row(&["nine", "10:00"]), &columns, today).is_err());
+        assert!(parse_row(&row(&["09:00", "10:00"]), &[Column::Date, Column::Start], today).is_err());
+    }
+}
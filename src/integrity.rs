@@ -0,0 +1,276 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use rusqlite::{params, Connection};
+
+use crate::db;
+use crate::error::Result;
+
+/// Setting holding the day integrity was last checked
+const LAST_CHECK_KEY: &str = "last_integrity_check";
+
+/// Days between automatic integrity checks
+pub const CHECK_INTERVAL_DAYS: i64 = 7;
+
+/// A running entry older than this was most likely left running by mistake
+const STALE_RUNNING_HOURS: i64 = 24;
+
+/// Kinds of damaged or suspicious entries the integrity check finds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Problem {
+    /// A start or end time that isn't a valid timestamp, so the entry can't
+    /// be read
+    MalformedTimestamp,
+    /// Still running after more than a day
+    StaleRunning,
+    /// Ends before it starts
+    NegativeDuration,
+    /// Refers to a project that no longer exists
+    OrphanProject,
+    /// Same project, description, start and end as an older entry
+    Duplicate,
+}
+
+impl Problem {
+    /// Human readable name shown in the integrity report
+    pub fn label(self) -> &'static str {
+        match self {
+            Problem::MalformedTimestamp => "Unreadable time",
+            Problem::StaleRunning => "Running for over a day",
+            Problem::NegativeDuration => "Ends before it starts",
+            Problem::OrphanProject => "Project no longer exists",
+            Problem::Duplicate => "Duplicate entry",
+        }
+    }
+
+    /// What `repair` does, as the label of its button
+    pub fn repair_label(self) -> &'static str {
+        match self {
+            Problem::MalformedTimestamp | Problem::Duplicate => "Delete",
+            Problem::StaleRunning => "Stop at Day's End",
+            Problem::NegativeDuration => "Swap Start and End",
+            Problem::OrphanProject => "Clear Project",
+        }
+    }
+}
+
+/// An entry with a problem, with its stored values for display
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub problem: Problem,
+    pub entry_id: i64,
+    pub description: String,
+    /// Start time exactly as stored, which may not be a valid timestamp
+    pub start_time: String,
+}
+
+/// Entries matching `condition`, as findings of `problem`
+fn find(conn: &Connection, problem: Problem, condition: &str, args: impl rusqlite::Params) -> Result<Vec<Finding>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, description, start_time FROM time_entries WHERE {} ORDER BY id",
+        condition
+    ))?;
+    let findings = stmt.query_map(args, |row| {
+        Ok(Finding {
+            problem,
+            entry_id: row.get(0)?,
+            description: row.get(1)?,
+            start_time: row.get(2)?,
+        })
+    })?;
+    Ok(findings.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Checks every entry, returning what is wrong ordered by problem. Entries
+/// with unreadable times are reported for that alone.
+pub fn check(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<Finding>> {
+    let well_formed = format!(
+        "strftime('{0}', start_time) IS start_time
+         AND (end_time IS NULL OR strftime('{0}', end_time) IS end_time)",
+        db::TIMESTAMP_FORMAT
+    );
+    let stale_before = db::format_datetime(now - Duration::hours(STALE_RUNNING_HOURS));
+
+    let mut findings = find(conn, Problem::MalformedTimestamp, &format!("NOT ({})", well_formed), [])?;
+    findings.extend(find(
+        conn,
+        Problem::StaleRunning,
+        &format!("{} AND end_time IS NULL AND start_time < ?1", well_formed),
+        params![stale_before],
+    )?);
+    findings.extend(find(
+        conn,
+        Problem::NegativeDuration,
+        &format!("{} AND end_time < start_time", well_formed),
+        [],
+    )?);
+    findings.extend(find(
+        conn,
+        Problem::OrphanProject,
+        "project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects)",
+        [],
+    )?);
+    findings.extend(find(
+        conn,
+        Problem::Duplicate,
+        "EXISTS (
+             SELECT 1 FROM time_entries AS original
+             WHERE original.id < time_entries.id
+               AND original.project_id IS time_entries.project_id
+               AND original.description = time_entries.description
+               AND original.start_time = time_entries.start_time
+               AND original.end_time IS time_entries.end_time
+         )",
+        [],
+    )?);
+    Ok(findings)
+}
+
+/// Last second of the day `start_time` falls on in the given time zone,
+/// where an entry left running is stopped
+pub fn end_of_start_day<Tz: TimeZone>(start_time: DateTime<Utc>, tz: &Tz) -> DateTime<Utc> {
+    let last_second = start_time
+        .with_timezone(tz)
+        .date_naive()
+        .and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+    tz.from_local_datetime(&last_second)
+        .latest()
+        .map(|end| end.with_timezone(&Utc))
+        .unwrap_or(start_time)
+}
+
+/// Applies the repair offered for a finding. Days are taken in the given
+/// time zone.
+pub fn repair<Tz: TimeZone>(conn: &Connection, finding: &Finding, tz: &Tz) -> Result<()> {
+    match finding.problem {
+        Problem::MalformedTimestamp | Problem::Duplicate => db::delete_entry(conn, finding.entry_id),
+        Problem::StaleRunning => {
+            let entry = db::get_entry_by_id(conn, finding.entry_id)?;
+            let end_time = end_of_start_day(entry.start_time, tz);
+            conn.execute(
+                "UPDATE time_entries SET end_time = ?1 WHERE id = ?2 AND end_time IS NULL",
+                params![db::format_datetime(end_time), finding.entry_id],
+            )?;
+            Ok(())
+        }
+        Problem::NegativeDuration => {
+            conn.execute(
+                "UPDATE time_entries SET start_time = end_time, end_time = start_time
+                 WHERE id = ?1 AND end_time < start_time",
+                params![finding.entry_id],
+            )?;
+            Ok(())
+        }
+        Problem::OrphanProject => {
+            conn.execute(
+                "UPDATE time_entries SET project_id = NULL WHERE id = ?1",
+                params![finding.entry_id],
+            )?;
+            Ok(())
+        }
+    }
+}
+
+/// Whether the automatic check is due: never run, or last run at least
+/// `CHECK_INTERVAL_DAYS` before `today`
+pub fn is_check_due(conn: &Connection, today: NaiveDate) -> bool {
+    db::get_setting(conn, LAST_CHECK_KEY)
+        .ok()
+        .flatten()
+        .and_then(|day| NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok())
+        .is_none_or(|last| (today - last).num_days() >= CHECK_INTERVAL_DAYS)
+}
+
+/// Records that integrity was checked on `today`
+pub fn mark_checked(conn: &Connection, today: NaiveDate) -> Result<()> {
+    db::set_setting(conn, LAST_CHECK_KEY, &today.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    /// A database written without foreign keys, e.g. by another tool,
+    /// where entries can refer to deleted projects
+    fn create_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::create_tables(&conn).unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = OFF").unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, project_id: Option<i64>, start: &str, end: Option<&str>) -> i64 {
+        conn.execute(
+            "INSERT INTO time_entries (project_id, description, start_time, end_time) VALUES (?1, 'Task', ?2, ?3)",
+            params![project_id, start, end],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn problems(conn: &Connection) -> Vec<(Problem, i64)> {
+        check(conn, at(10, 12))
+            .unwrap()
+            .into_iter()
+            .map(|finding| (finding.problem, finding.entry_id))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_finds_problems() {
+        let conn = create_test_db();
+        let project = db::create_project(&conn, "Work", "#3498db").unwrap();
+        insert(&conn, Some(project.id), "2024-03-04 09:00:00", Some("2024-03-04 10:00:00"));
+        insert(&conn, None, "2024-03-10 11:00:00", None);
+        let malformed = insert(&conn, None, "04/03/2024 9am", Some("2024-03-04 10:00:00"));
+        let stale = insert(&conn, None, "2024-03-08 09:00:00", None);
+        let negative = insert(&conn, None, "2024-03-05 10:00:00", Some("2024-03-05 09:00:00"));
+        let orphan = insert(&conn, Some(999), "2024-03-06 09:00:00", Some("2024-03-06 10:00:00"));
+        let duplicate = insert(&conn, Some(project.id), "2024-03-04 09:00:00", Some("2024-03-04 10:00:00"));
+
+        assert_eq!(
+            problems(&conn),
+            vec![
+                (Problem::MalformedTimestamp, malformed),
+                (Problem::StaleRunning, stale),
+                (Problem::NegativeDuration, negative),
+                (Problem::OrphanProject, orphan),
+                (Problem::Duplicate, duplicate),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repairs_clear_findings() {
+        let conn = create_test_db();
+        insert(&conn, None, "04/03/2024 9am", None);
+        let stale = insert(&conn, None, "2024-03-08 09:00:00", None);
+        let negative = insert(&conn, None, "2024-03-05 10:00:00", Some("2024-03-05 09:00:00"));
+        insert(&conn, Some(999), "2024-03-06 09:00:00", Some("2024-03-06 10:00:00"));
+
+        for finding in check(&conn, at(10, 12)).unwrap() {
+            repair(&conn, &finding, &Utc).unwrap();
+        }
+
+        assert!(problems(&conn).is_empty());
+        assert_eq!(
+            db::get_entry_by_id(&conn, stale).unwrap().end_time,
+            Some(Utc.with_ymd_and_hms(2024, 3, 8, 23, 59, 59).unwrap())
+        );
+        let swapped = db::get_entry_by_id(&conn, negative).unwrap();
+        assert_eq!((swapped.start_time, swapped.end_time), (at(5, 9), Some(at(5, 10))));
+    }
+
+    #[test]
+    fn test_check_is_due_weekly() {
+        let conn = create_test_db();
+        let day = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        assert!(is_check_due(&conn, day));
+
+        mark_checked(&conn, day).unwrap();
+        assert!(!is_check_due(&conn, day + Duration::days(6)));
+        assert!(is_check_due(&conn, day + Duration::days(7)));
+    }
+}
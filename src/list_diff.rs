@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Hashes everything a row displays, so a row only needs rebuilding when its fingerprint changes
+pub fn fingerprint(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Changes needed to turn one keyed list into another
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ListDiff<K> {
+    /// Keys that are no longer present
+    pub removed: Vec<K>,
+    /// Keys present in both lists whose fingerprint changed
+    pub changed: Vec<K>,
+    /// Keys that are new, in their new order
+    pub added: Vec<K>,
+}
+
+/// Compares two lists of `(key, fingerprint)` pairs. Ordering is not part of
+/// the diff: callers place rows by walking the new list.
+pub fn diff<K: Copy + Eq + Hash>(old: &[(K, u64)], new: &[(K, u64)]) -> ListDiff<K> {
+    let old_map: HashMap<K, u64> = old.iter().copied().collect();
+    let new_map: HashMap<K, u64> = new.iter().copied().collect();
+
+    let removed = old
+        .iter()
+        .filter(|(key, _)| !new_map.contains_key(key))
+        .map(|(key, _)| *key)
+        .collect();
+
+    let mut changed = Vec::new();
+    let mut added = Vec::new();
+    for (key, fingerprint) in new {
+        match old_map.get(key) {
+            Some(old_fingerprint) if old_fingerprint != fingerprint => changed.push(*key),
+            Some(_) => {}
+            None => added.push(*key),
+        }
+    }
+
+    ListDiff {
+        removed,
+        changed,
+        added,
+    }
+}
+
+/// Index in `new` of the row that should take focus after an update, given
+/// the key that had focus: the same key when it is still present, otherwise
+/// the next surviving row, or the new last row when nothing followed it.
+pub fn focus_target<K: Copy + Eq>(old: &[K], focused: K, new: &[K]) -> Option<usize> {
+    if let Some(index) = new.iter().position(|k| *k == focused) {
+        return Some(index);
+    }
+
+    let old_index = old.iter().position(|k| *k == focused)?;
+    old[old_index + 1..]
+        .iter()
+        .find_map(|key| new.iter().position(|k| k == key))
+        .or_else(|| new.len().checked_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_unchanged() {
+        let rows = [(1, 10), (2, 20)];
+
+        assert_eq!(diff(&rows, &rows), ListDiff::default());
+    }
+
+    #[test]
+    fn test_diff_added_removed_changed() {
+        let old = [(1, 10), (2, 20), (3, 30)];
+        let new = [(4, 40), (1, 10), (3, 31)];
+
+        let result = diff(&old, &new);
+
+        assert_eq!(result.removed, vec![2]);
+        assert_eq!(result.changed, vec![3]);
+        assert_eq!(result.added, vec![4]);
+    }
+
+    #[test]
+    fn test_diff_ignores_order() {
+        let old = [(1, 10), (2, 20)];
+        let new = [(2, 20), (1, 10)];
+
+        assert_eq!(diff(&old, &new), ListDiff::default());
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_content() {
+        assert_eq!(fingerprint(&("Task", 60)), fingerprint(&("Task", 60)));
+        assert_ne!(fingerprint(&("Task", 60)), fingerprint(&("Task", 61)));
+    }
+
+    #[test]
+    fn test_focus_target_keeps_focused_key() {
+        assert_eq!(focus_target(&[1, 2, 3], 2, &[4, 1, 2, 3]), Some(2));
+    }
+
+    #[test]
+    fn test_focus_target_moves_to_next_row() {
+        assert_eq!(focus_target(&[1, 2, 3], 2, &[1, 3]), Some(1));
+        // The next row was deleted too
+        assert_eq!(focus_target(&[1, 2, 3, 4], 2, &[1, 4]), Some(1));
+    }
+
+    #[test]
+    fn test_focus_target_last_row() {
+        assert_eq!(focus_target(&[1, 2, 3], 3, &[1, 2]), Some(1));
+        assert_eq!(focus_target(&[1], 1, &[] as &[i32]), None);
+    }
+}
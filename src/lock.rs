@@ -0,0 +1,70 @@
+use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
+
+/// Whether `time` falls in the locked period, which covers every day up to
+/// and including `locked_until`. Days are taken in the given time zone.
+pub fn is_locked<Tz: TimeZone>(time: DateTime<Utc>, locked_until: Option<NaiveDate>, tz: &Tz) -> bool {
+    locked_until.is_some_and(|last_day| time.with_timezone(tz).date_naive() <= last_day)
+}
+
+/// Correction offered for a time inside the locked period: the same time of
+/// day on the first day after it. Times outside the period are returned as is.
+pub fn first_unlocked_time<Tz: TimeZone>(
+    time: DateTime<Utc>,
+    locked_until: Option<NaiveDate>,
+    tz: &Tz,
+) -> DateTime<Utc> {
+    let Some(last_day) = locked_until else {
+        return time;
+    };
+    if !is_locked(time, locked_until, tz) {
+        return time;
+    }
+
+    let first_day = last_day + Days::new(1);
+    let local = first_day.and_time(time.with_timezone(tz).time());
+    // A time skipped by a DST change falls back to the start of the day
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&first_day.and_hms_opt(0, 0, 0).unwrap())
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_is_locked() {
+        let time = Utc.with_ymd_and_hms(2024, 3, 31, 23, 59, 0).unwrap();
+
+        assert!(is_locked(time, Some(date(2024, 3, 31)), &Utc));
+        assert!(!is_locked(time, Some(date(2024, 3, 30)), &Utc));
+        assert!(!is_locked(time, None, &Utc));
+    }
+
+    #[test]
+    fn test_first_unlocked_time_keeps_time_of_day() {
+        let time = Utc.with_ymd_and_hms(2023, 11, 14, 9, 30, 0).unwrap();
+
+        assert_eq!(
+            first_unlocked_time(time, Some(date(2024, 3, 31)), &Utc),
+            Utc.with_ymd_and_hms(2024, 4, 1, 9, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_first_unlocked_time_outside_period() {
+        let time = Utc.with_ymd_and_hms(2024, 4, 2, 9, 30, 0).unwrap();
+
+        assert_eq!(first_unlocked_time(time, Some(date(2024, 3, 31)), &Utc), time);
+        assert_eq!(first_unlocked_time(time, None, &Utc), time);
+    }
+}
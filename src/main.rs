@@ -1,9 +1,46 @@
-use adw::prelude::*;
+// Windows release builds are GUI programs, without a console window
+#![cfg_attr(all(windows, not(debug_assertions)), windows_subsystem = "windows")]
 
+mod absence;
+mod autostart;
+mod bench;
 mod db;
+mod dbus;
+mod dnd;
+mod duplicate;
+mod duration;
+mod error;
+mod export;
+mod forecast;
+mod fuzzy;
+mod import;
+mod integrity;
+mod list_diff;
+mod lock;
+mod month;
+mod plugins;
+mod privacy;
+mod profile;
+mod reminder;
+mod project_import;
+mod report;
+mod seed;
+mod settings;
+mod smart_syntax;
+mod stats;
+mod tags;
+mod timeline;
 mod tray;
 mod ui;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some(seed::SEED_COMMAND) {
+        std::process::exit(seed::run(&args[2..]));
+    }
+    if args.get(1).map(String::as_str) == Some(bench::BENCH_COMMAND) {
+        std::process::exit(bench::run(&args[2..]));
+    }
+
     std::process::exit(ui::run_app());
 }
@@ -0,0 +1,197 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+/// Darkest shade of the heatmap calendar; 0 is a day with nothing tracked
+pub const HEATMAP_LEVELS: u8 = 4;
+
+/// Weeks of the month containing `date`, Monday first. Days outside the
+/// month are `None`, so every week has seven slots.
+pub fn month_weeks(date: NaiveDate) -> Vec<[Option<NaiveDate>; 7]> {
+    let first = date.with_day(1).unwrap();
+    range_weeks(first, first + Months::new(1) - Days::new(1))
+}
+
+/// Weeks from `start` to `end` inclusive, Monday first. Days outside the
+/// range are `None`, so every week has seven slots.
+pub fn range_weeks(start: NaiveDate, end: NaiveDate) -> Vec<[Option<NaiveDate>; 7]> {
+    let mut weeks = Vec::new();
+    let mut week = [None; 7];
+    for day in start.iter_days().take_while(|day| *day <= end) {
+        let weekday = day.weekday().num_days_from_monday() as usize;
+        week[weekday] = Some(day);
+        if weekday == 6 {
+            weeks.push(week);
+            week = [None; 7];
+        }
+    }
+    if week.iter().any(Option::is_some) {
+        weeks.push(week);
+    }
+    weeks
+}
+
+/// First day of the month before or after the one containing `date`
+pub fn shift_month(date: NaiveDate, forward: bool) -> NaiveDate {
+    let first = date.with_day(1).unwrap();
+    if forward {
+        first + Months::new(1)
+    } else {
+        first - Months::new(1)
+    }
+}
+
+/// Shade of a heatmap calendar day, from 0 for nothing tracked up to
+/// [`HEATMAP_LEVELS`] for days close to the busiest one
+pub fn heatmap_level(seconds: i64, max_seconds: i64) -> u8 {
+    if seconds <= 0 || max_seconds <= 0 {
+        return 0;
+    }
+    let level = (seconds as f64 / max_seconds as f64 * HEATMAP_LEVELS as f64).ceil();
+    (level as u8).clamp(1, HEATMAP_LEVELS)
+}
+
+/// How a day's total compares to the expected hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Heat {
+    /// Nothing tracked
+    Empty,
+    /// Less than half of the expected hours
+    FarUnder,
+    Under,
+    /// Within 10% of the expected hours
+    OnTarget,
+    Over,
+    /// More than one and a half times the expected hours
+    FarOver,
+}
+
+impl Heat {
+    /// Levels shown in the legend, from cool to warm
+    pub const LEGEND: [Heat; 5] = [
+        Heat::FarUnder,
+        Heat::Under,
+        Heat::OnTarget,
+        Heat::Over,
+        Heat::FarOver,
+    ];
+
+    /// Classifies a day's total against the expected seconds
+    pub fn of(seconds: i64, expected_seconds: i64) -> Heat {
+        if seconds <= 0 {
+            return Heat::Empty;
+        }
+        if expected_seconds <= 0 {
+            return Heat::OnTarget;
+        }
+
+        let ratio = seconds as f64 / expected_seconds as f64;
+        if ratio < 0.5 {
+            Heat::FarUnder
+        } else if ratio < 0.9 {
+            Heat::Under
+        } else if ratio <= 1.1 {
+            Heat::OnTarget
+        } else if ratio <= 1.5 {
+            Heat::Over
+        } else {
+            Heat::FarOver
+        }
+    }
+
+    /// Style class tinting a day cell
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Heat::Empty => "heat-empty",
+            Heat::FarUnder => "heat-far-under",
+            Heat::Under => "heat-under",
+            Heat::OnTarget => "heat-on-target",
+            Heat::Over => "heat-over",
+            Heat::FarOver => "heat-far-over",
+        }
+    }
+
+    /// Legend text
+    pub fn label(self) -> &'static str {
+        match self {
+            Heat::Empty => "Nothing tracked",
+            Heat::FarUnder => "Under half",
+            Heat::Under => "Under",
+            Heat::OnTarget => "On target",
+            Heat::Over => "Over",
+            Heat::FarOver => "Far over",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_month_weeks_starts_on_monday() {
+        // March 2024 starts on a Friday and ends on a Sunday
+        let weeks = month_weeks(date(2024, 3, 15));
+
+        assert_eq!(weeks.len(), 5);
+        assert_eq!(weeks[0][..4], [None; 4]);
+        assert_eq!(weeks[0][4], Some(date(2024, 3, 1)));
+        assert_eq!(weeks[4][6], Some(date(2024, 3, 31)));
+    }
+
+    #[test]
+    fn test_month_weeks_partial_last_week() {
+        // February 2024 ends on a Thursday
+        let weeks = month_weeks(date(2024, 2, 1));
+
+        let last = weeks.last().unwrap();
+        assert_eq!(last[3], Some(date(2024, 2, 29)));
+        assert_eq!(last[4..], [None; 3]);
+        let days: usize = weeks.iter().map(|w| w.iter().flatten().count()).sum();
+        assert_eq!(days, 29);
+    }
+
+    #[test]
+    fn test_range_weeks() {
+        // Wednesday to the Tuesday after next
+        let weeks = range_weeks(date(2024, 3, 6), date(2024, 3, 19));
+
+        assert_eq!(weeks.len(), 3);
+        assert_eq!(weeks[0][..2], [None; 2]);
+        assert_eq!(weeks[0][2], Some(date(2024, 3, 6)));
+        assert_eq!(weeks[2][1], Some(date(2024, 3, 19)));
+        assert_eq!(weeks[2][2..], [None; 5]);
+    }
+
+    #[test]
+    fn test_heatmap_levels() {
+        let max = 8 * 3600;
+
+        assert_eq!(heatmap_level(0, max), 0);
+        assert_eq!(heatmap_level(60, max), 1);
+        assert_eq!(heatmap_level(4 * 3600, max), 2);
+        assert_eq!(heatmap_level(5 * 3600, max), 3);
+        assert_eq!(heatmap_level(max, max), HEATMAP_LEVELS);
+        assert_eq!(heatmap_level(60, 0), 0);
+    }
+
+    #[test]
+    fn test_shift_month() {
+        assert_eq!(shift_month(date(2024, 1, 31), true), date(2024, 2, 1));
+        assert_eq!(shift_month(date(2024, 1, 31), false), date(2023, 12, 1));
+    }
+
+    #[test]
+    fn test_heat_levels() {
+        let expected = 8 * 3600;
+
+        assert_eq!(Heat::of(0, expected), Heat::Empty);
+        assert_eq!(Heat::of(2 * 3600, expected), Heat::FarUnder);
+        assert_eq!(Heat::of(6 * 3600, expected), Heat::Under);
+        assert_eq!(Heat::of(8 * 3600, expected), Heat::OnTarget);
+        assert_eq!(Heat::of(10 * 3600, expected), Heat::Over);
+        assert_eq!(Heat::of(13 * 3600, expected), Heat::FarOver);
+    }
+}
@@ -0,0 +1,677 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::Chars;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::export::json_string;
+
+/// How long a plugin may take to handle an event before it is killed
+pub const PLUGIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a running plugin is checked for having exited
+const WAIT_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Most output read from a plugin, in bytes; anything after it is ignored
+const MAX_OUTPUT_BYTES: u64 = 64 * 1024;
+
+/// How many times an event is sent to a plugin before giving up on it
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 6;
+
+/// Wait before sending a failed event again, doubled after each failure
+const FIRST_RETRY_DELAY: chrono::Duration = chrono::Duration::minutes(1);
+
+/// Returns the directory plugins are discovered in,
+/// ~/.config/time-tracking/plugins
+pub fn plugins_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("time-tracking")
+        .join("plugins")
+}
+
+/// An executable in the plugins directory, named after its file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
+/// Lists the executable files in `dir` by name, skipping hidden files.
+/// A missing directory has no plugins.
+pub fn discover_plugins(dir: &Path) -> Vec<Plugin> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut plugins: Vec<Plugin> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            std::fs::metadata(entry.path()).is_ok_and(|metadata| metadata.is_file() && is_executable(&metadata))
+        })
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            (!name.starts_with('.')).then(|| Plugin { name, path: entry.path() })
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+/// A time entry as plugins see it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginEntry {
+    pub id: i64,
+    pub description: String,
+    /// Used to scope what plugins see, not sent
+    pub project_id: Option<i64>,
+    pub project: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl PluginEntry {
+    fn to_json(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+        format!(
+            "{{\"id\":{},\"description\":{},\"project\":{},\"start\":{},\"end\":{}}}",
+            self.id,
+            json_string(&self.description),
+            optional(self.project.as_deref().map(json_string)),
+            json_string(&self.start_time.to_rfc3339()),
+            optional(self.end_time.map(|end| json_string(&end.to_rfc3339()))),
+        )
+    }
+}
+
+/// Something that happened in the app, sent to every enabled plugin
+#[derive(Debug, Clone, PartialEq)]
+pub enum PluginEvent {
+    AppStarted,
+    TimerStarted(PluginEntry),
+    TimerStopped(PluginEntry),
+}
+
+impl PluginEvent {
+    /// Encodes the event as the single JSON object written to a plugin's
+    /// stdin, e.g. {"event":"timer_started","entry":{...}}
+    pub fn to_json(&self) -> String {
+        match self {
+            PluginEvent::AppStarted => "{\"event\":\"app_started\"}".to_string(),
+            PluginEvent::TimerStarted(entry) => {
+                format!("{{\"event\":\"timer_started\",\"entry\":{}}}", entry.to_json())
+            }
+            PluginEvent::TimerStopped(entry) => {
+                format!("{{\"event\":\"timer_stopped\",\"entry\":{}}}", entry.to_json())
+            }
+        }
+    }
+
+    fn entry_mut(&mut self) -> Option<&mut PluginEntry> {
+        match self {
+            PluginEvent::AppStarted => None,
+            PluginEvent::TimerStarted(entry) | PluginEvent::TimerStopped(entry) => Some(entry),
+        }
+    }
+}
+
+/// What one plugin may see and do. Events are scoped before they are sent
+/// and commands checked before they are applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginPermissions {
+    /// Projects whose entries the plugin is told about, all entries when
+    /// `None`. A scoped plugin never sees entries without a project.
+    pub project_ids: Option<Vec<i64>>,
+    /// Whether entries are sent with their description
+    pub descriptions: bool,
+    pub notify: bool,
+    pub start_timers: bool,
+    pub annotate_entries: bool,
+}
+
+impl Default for PluginPermissions {
+    fn default() -> Self {
+        Self {
+            project_ids: None,
+            descriptions: true,
+            notify: true,
+            start_timers: true,
+            annotate_entries: true,
+        }
+    }
+}
+
+/// Permission flags in their stored order, with their setting names
+const PERMISSION_FLAGS: [&str; 4] = ["descriptions", "notify", "start_timers", "annotate_entries"];
+
+impl PluginPermissions {
+    /// Whether the plugin may see or act on entries of `project_id`
+    pub fn covers(&self, project_id: Option<i64>) -> bool {
+        match self.project_ids {
+            None => true,
+            Some(ref ids) => project_id.is_some_and(|id| ids.contains(&id)),
+        }
+    }
+
+    /// The event as the plugin may see it, `None` when its entry is out of scope
+    pub fn scope(&self, event: &PluginEvent) -> Option<PluginEvent> {
+        let mut event = event.clone();
+        if let Some(entry) = event.entry_mut() {
+            if !self.covers(entry.project_id) {
+                return None;
+            }
+            if !self.descriptions {
+                entry.description.clear();
+            }
+        }
+        Some(event)
+    }
+
+    /// Whether the plugin may run `command`. Commands naming a project or
+    /// entry are further checked against the scope by the caller.
+    pub fn allows(&self, command: &PluginCommand) -> bool {
+        match command {
+            PluginCommand::Notify { .. } => self.notify,
+            PluginCommand::StartTemplate { .. } => self.start_timers,
+            PluginCommand::AnnotateEntry { .. } => self.annotate_entries,
+        }
+    }
+
+    fn flags(&self) -> [bool; 4] {
+        [self.descriptions, self.notify, self.start_timers, self.annotate_entries]
+    }
+
+    /// Encodes the permissions as "flag,flag<TAB>project ids or all"
+    fn to_setting(&self) -> String {
+        let flags: Vec<&str> = PERMISSION_FLAGS
+            .iter()
+            .zip(self.flags())
+            .filter(|(_, granted)| *granted)
+            .map(|(name, _)| *name)
+            .collect();
+        let projects = match self.project_ids {
+            None => "all".to_string(),
+            Some(ref ids) => ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(","),
+        };
+        format!("{}\t{}", flags.join(","), projects)
+    }
+
+    fn from_setting(value: &str) -> Option<Self> {
+        let (flags, projects) = value.split_once('\t')?;
+        let granted = |flag: &str| flags.split(',').any(|f| f == flag);
+        let project_ids = match projects {
+            "all" => None,
+            "" => Some(Vec::new()),
+            ids => Some(ids.split(',').map(|id| id.parse().ok()).collect::<Option<Vec<i64>>>()?),
+        };
+        Some(Self {
+            project_ids,
+            descriptions: granted(PERMISSION_FLAGS[0]),
+            notify: granted(PERMISSION_FLAGS[1]),
+            start_timers: granted(PERMISSION_FLAGS[2]),
+            annotate_entries: granted(PERMISSION_FLAGS[3]),
+        })
+    }
+}
+
+/// Encodes the permissions of each plugin, one plugin per line
+pub fn permissions_to_setting(permissions: &BTreeMap<String, PluginPermissions>) -> String {
+    permissions
+        .iter()
+        .map(|(name, permissions)| format!("{}\t{}", name, permissions.to_setting()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Decodes the permissions stored by `permissions_to_setting`, skipping
+/// lines it can't read
+pub fn permissions_from_setting(value: &str) -> BTreeMap<String, PluginPermissions> {
+    value
+        .lines()
+        .filter_map(|line| {
+            let (name, permissions) = line.split_once('\t')?;
+            Some((name.to_string(), PluginPermissions::from_setting(permissions)?))
+        })
+        .collect()
+}
+
+/// Something a plugin asks the app to do in reply to an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum PluginCommand {
+    /// {"command":"notify","title":"...","body":"..."} shows a desktop
+    /// notification; the body is optional
+    Notify { title: String, body: String },
+    /// {"command":"start_template","description":"...","project":"..."}
+    /// starts a timer, stopping the running one; the project is optional
+    /// and matched by name
+    StartTemplate {
+        description: String,
+        project: Option<String>,
+    },
+    /// {"command":"annotate_entry","entry_id":42,"text":"..."} appends the
+    /// text to the entry's description
+    AnnotateEntry { entry_id: i64, text: String },
+}
+
+/// A value of a flat JSON object; nested arrays and objects aren't part of
+/// the plugin contract
+#[derive(Debug, Clone, PartialEq)]
+enum JsonValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), String> {
+    skip_whitespace(chars);
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        Some(c) => Err(format!("expected '{}' but found '{}'", expected, c)),
+        None => Err(format!("expected '{}' but the line ended", expected)),
+    }
+}
+
+fn parse_hex_escape(chars: &mut Peekable<Chars>) -> Result<u32, String> {
+    let hex: String = chars.by_ref().take(4).collect();
+    u32::from_str_radix(&hex, 16).map_err(|_| format!("invalid escape \\u{}", hex))
+}
+
+/// Parses a JSON string, the opening quote already consumed
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    let mut value = String::new();
+    loop {
+        match chars.next().ok_or("unterminated string")? {
+            '"' => return Ok(value),
+            '\\' => match chars.next().ok_or("unterminated string")? {
+                '"' => value.push('"'),
+                '\\' => value.push('\\'),
+                '/' => value.push('/'),
+                'b' => value.push('\u{8}'),
+                'f' => value.push('\u{c}'),
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                'u' => {
+                    let mut code = parse_hex_escape(chars)?;
+                    // Characters outside the basic plane come as a surrogate pair
+                    if (0xd800..0xdc00).contains(&code) && chars.next() == Some('\\') && chars.next() == Some('u') {
+                        let low = parse_hex_escape(chars)?;
+                        code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                    }
+                    value.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                c => return Err(format!("invalid escape \\{}", c)),
+            },
+            c => value.push(c),
+        }
+    }
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+    skip_whitespace(chars);
+    match chars.peek().copied() {
+        Some('"') => {
+            chars.next();
+            parse_string(chars).map(JsonValue::String)
+        }
+        Some(c) if c == '-' || c.is_ascii_digit() => {
+            let mut number = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+                number.push(c);
+            }
+            number
+                .parse()
+                .map(JsonValue::Number)
+                .map_err(|_| format!("invalid number {}", number))
+        }
+        Some(c) if c.is_ascii_alphabetic() => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                word.push(c);
+            }
+            match word.as_str() {
+                "true" => Ok(JsonValue::Bool(true)),
+                "false" => Ok(JsonValue::Bool(false)),
+                "null" => Ok(JsonValue::Null),
+                _ => Err(format!("unexpected {}", word)),
+            }
+        }
+        Some(c) => Err(format!("unsupported value starting with '{}'", c)),
+        None => Err("expected a value but the line ended".to_string()),
+    }
+}
+
+/// Parses a JSON object whose values are strings, numbers, booleans or null
+fn parse_flat_object(text: &str) -> Result<HashMap<String, JsonValue>, String> {
+    let mut chars = text.chars().peekable();
+    let mut object = HashMap::new();
+    expect(&mut chars, '{')?;
+    skip_whitespace(&mut chars);
+    if chars.next_if_eq(&'}').is_none() {
+        loop {
+            expect(&mut chars, '"')?;
+            let key = parse_string(&mut chars)?;
+            expect(&mut chars, ':')?;
+            object.insert(key, parse_value(&mut chars)?);
+            skip_whitespace(&mut chars);
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err("expected ',' or '}'".to_string()),
+            }
+        }
+    }
+    skip_whitespace(&mut chars);
+    match chars.next() {
+        None => Ok(object),
+        Some(c) => Err(format!("unexpected '{}' after the object", c)),
+    }
+}
+
+/// Parses one line of plugin output into a command
+pub fn parse_command(line: &str) -> Result<PluginCommand, String> {
+    let object = parse_flat_object(line)?;
+    let string = |key: &str| match object.get(key) {
+        Some(JsonValue::String(value)) => Ok(Some(value.clone())),
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(_) => Err(format!("\"{}\" must be a string", key)),
+    };
+    let required = |key: &str| string(key)?.ok_or_else(|| format!("missing \"{}\"", key));
+
+    match required("command")?.as_str() {
+        "notify" => Ok(PluginCommand::Notify {
+            title: required("title")?,
+            body: string("body")?.unwrap_or_default(),
+        }),
+        "start_template" => Ok(PluginCommand::StartTemplate {
+            description: required("description")?,
+            project: string("project")?,
+        }),
+        "annotate_entry" => match object.get("entry_id") {
+            Some(JsonValue::Number(id)) if id.fract() == 0.0 => Ok(PluginCommand::AnnotateEntry {
+                entry_id: *id as i64,
+                text: required("text")?,
+            }),
+            _ => Err("\"entry_id\" must be a whole number".to_string()),
+        },
+        other => Err(format!("unknown command \"{}\"", other)),
+    }
+}
+
+/// Parses a plugin's output, one command per non-blank line
+pub fn parse_output(output: &str) -> Vec<Result<PluginCommand, String>> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_command)
+        .collect()
+}
+
+/// How long to wait before sending an event again after it failed
+/// `attempts` times, or `None` once it is given up
+pub fn retry_delay(attempts: u32) -> Option<chrono::Duration> {
+    if attempts == 0 || attempts >= MAX_DELIVERY_ATTEMPTS {
+        return None;
+    }
+    Some(FIRST_RETRY_DELAY * 2i32.pow(attempts - 1))
+}
+
+/// Runs `plugin` for one event, given as its JSON line. The plugin contract:
+///
+/// - The executable runs in the plugins directory and gets the event as a
+///   single line of JSON on stdin, which is then closed.
+/// - It may write commands to stdout, one JSON object per line, and should
+///   exit with status 0. Stderr is ignored.
+/// - It is killed when it takes longer than `timeout`, and nothing it
+///   wrote is used. Output beyond 64 KiB is ignored.
+///
+/// Returns the commands, each possibly invalid, or why the run failed.
+pub fn run_plugin(
+    plugin: &Plugin,
+    event_json: &str,
+    timeout: Duration,
+) -> Result<Vec<Result<PluginCommand, String>>, String> {
+    let mut child = Command::new(&plugin.path)
+        .current_dir(plugin.path.parent().unwrap_or(Path::new(".")))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to start: {}", e))?;
+
+    // A plugin that doesn't read its input only loses the event
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = writeln!(stdin, "{}", event_json);
+    }
+
+    // Read on another thread, so a plugin filling the pipe doesn't block
+    let stdout = child.stdout.take();
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(stdout) = stdout {
+            let _ = stdout.take(MAX_OUTPUT_BYTES).read_to_string(&mut output);
+        }
+        output
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {} seconds", timeout.as_secs_f32()));
+            }
+            None => thread::sleep(WAIT_INTERVAL),
+        }
+    };
+    let output = reader.join().unwrap_or_default();
+    if !status.success() {
+        return Err(format!("exited with {}", status));
+    }
+    Ok(parse_output(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry() -> PluginEntry {
+        PluginEntry {
+            id: 42,
+            description: "Fix \"login\"".to_string(),
+            project_id: Some(1),
+            project: Some("Work".to_string()),
+            start_time: Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap(),
+            end_time: None,
+        }
+    }
+
+    /// Writes an executable shell script into a fresh directory
+    #[cfg(unix)]
+    fn script(name: &str, body: &str) -> Plugin {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("time-tracking-plugin-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        Plugin {
+            name: name.to_string(),
+            path,
+        }
+    }
+
+    #[test]
+    fn test_event_json() {
+        assert_eq!(
+            PluginEvent::TimerStarted(entry()).to_json(),
+            "{\"event\":\"timer_started\",\"entry\":{\"id\":42,\"description\":\"Fix \\\"login\\\"\",\
+             \"project\":\"Work\",\"start\":\"2024-03-04T09:00:00+00:00\",\"end\":null}}"
+        );
+        assert_eq!(PluginEvent::AppStarted.to_json(), "{\"event\":\"app_started\"}");
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            parse_command(r#"{"command": "notify", "title": "Break é", "body": null}"#),
+            Ok(PluginCommand::Notify {
+                title: "Break é".to_string(),
+                body: String::new(),
+            })
+        );
+        assert_eq!(
+            parse_command(r#"{"command":"start_template","description":"Standup","project":"Work"}"#),
+            Ok(PluginCommand::StartTemplate {
+                description: "Standup".to_string(),
+                project: Some("Work".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_command(r#"{"command":"annotate_entry","entry_id":42,"text":"JIRA-7","extra":true}"#),
+            Ok(PluginCommand::AnnotateEntry {
+                entry_id: 42,
+                text: "JIRA-7".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_command_errors() {
+        assert!(parse_command(r#"{"command":"format_disk"}"#).is_err());
+        assert!(parse_command(r#"{"command":"notify"}"#).is_err());
+        assert!(parse_command(r#"{"command":"annotate_entry","entry_id":1.5,"text":"x"}"#).is_err());
+        assert!(parse_command(r#"{"command":"notify","title":{"nested":1}}"#).is_err());
+        assert!(parse_command(r#"{"command":"notify","title":"x"} trailing"#).is_err());
+        assert!(parse_command("not json").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_plugin_replies_to_event() {
+        let plugin = script(
+            "echo",
+            r#"read event
+case "$event" in
+  *timer_started*) echo '{"command":"notify","title":"Started"}' ;;
+esac
+echo
+echo 'garbage'"#,
+        );
+
+        let commands = run_plugin(&plugin, &PluginEvent::TimerStarted(entry()).to_json(), PLUGIN_TIMEOUT).unwrap();
+
+        assert_eq!(commands.len(), 2);
+        assert_eq!(
+            commands[0],
+            Ok(PluginCommand::Notify {
+                title: "Started".to_string(),
+                body: String::new(),
+            })
+        );
+        assert!(commands[1].is_err());
+        assert_eq!(discover_plugins(plugin.path.parent().unwrap()), vec![plugin]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_plugin_failures() {
+        let slow = script("slow", "sleep 5");
+        let failing = script("failing", "exit 3");
+
+        let event = PluginEvent::AppStarted.to_json();
+        assert!(run_plugin(&slow, &event, Duration::from_millis(100)).is_err());
+        assert!(run_plugin(&failing, &event, PLUGIN_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), Some(chrono::Duration::minutes(1)));
+        assert_eq!(retry_delay(2), Some(chrono::Duration::minutes(2)));
+        assert_eq!(retry_delay(MAX_DELIVERY_ATTEMPTS - 1), Some(chrono::Duration::minutes(16)));
+        assert_eq!(retry_delay(MAX_DELIVERY_ATTEMPTS), None);
+    }
+
+    #[test]
+    fn test_permissions_scope_events() {
+        let scoped = PluginPermissions {
+            project_ids: Some(vec![2]),
+            ..PluginPermissions::default()
+        };
+        let private = PluginPermissions {
+            descriptions: false,
+            ..PluginPermissions::default()
+        };
+        let event = PluginEvent::TimerStarted(entry());
+
+        assert_eq!(scoped.scope(&event), None);
+        assert_eq!(scoped.scope(&PluginEvent::AppStarted), Some(PluginEvent::AppStarted));
+        assert_eq!(
+            private.scope(&event),
+            Some(PluginEvent::TimerStarted(PluginEntry {
+                description: String::new(),
+                ..entry()
+            }))
+        );
+        assert!(!scoped.covers(None));
+        assert!(PluginPermissions::default().covers(None));
+    }
+
+    #[test]
+    fn test_permissions_setting_round_trip() {
+        let permissions = BTreeMap::from([
+            ("jira".to_string(), PluginPermissions::default()),
+            (
+                "mqtt".to_string(),
+                PluginPermissions {
+                    project_ids: Some(vec![1, 3]),
+                    descriptions: false,
+                    notify: true,
+                    start_timers: false,
+                    annotate_entries: false,
+                },
+            ),
+            (
+                "none".to_string(),
+                PluginPermissions {
+                    project_ids: Some(Vec::new()),
+                    ..PluginPermissions::default()
+                },
+            ),
+        ]);
+
+        let decoded = permissions_from_setting(&permissions_to_setting(&permissions));
+
+        assert_eq!(decoded, permissions);
+        assert!(permissions_from_setting("broken line\nx\tnotify\tone,two").is_empty());
+    }
+
+    #[test]
+    fn test_discover_plugins_in_missing_directory() {
+        assert!(discover_plugins(Path::new("/nonexistent/time-tracking/plugins")).is_empty());
+    }
+}
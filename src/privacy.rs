@@ -0,0 +1,79 @@
+use crate::db::TimeEntry;
+
+/// Description shown instead of the real one for anonymized private entries
+pub const PRIVATE_DESCRIPTION: &str = "Private";
+
+/// How private entries appear in output shared with others, such as
+/// exports, client reports and the viewer mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrivacyMode {
+    /// Private entries are left out entirely
+    #[default]
+    Exclude,
+    /// Private entries keep their time but lose their description and project
+    Anonymize,
+}
+
+impl PrivacyMode {
+    /// All modes, in the order they are offered in preferences
+    pub const ALL: [PrivacyMode; 2] = [PrivacyMode::Exclude, PrivacyMode::Anonymize];
+
+    /// Human readable name shown in preferences
+    pub fn label(self) -> &'static str {
+        match self {
+            PrivacyMode::Exclude => "Leave out",
+            PrivacyMode::Anonymize => "Show as \"Private\"",
+        }
+    }
+}
+
+/// Prepares one entry for output shared with others, `None` when it is left out
+pub fn shared_entry(entry: &TimeEntry, mode: PrivacyMode) -> Option<TimeEntry> {
+    if entry.private && mode == PrivacyMode::Exclude {
+        return None;
+    }
+    let mut entry = entry.clone();
+    if entry.private {
+        entry.description = PRIVATE_DESCRIPTION.to_string();
+        entry.project_id = None;
+    }
+    Some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::db::test_support;
+
+    fn entry(id: i64, description: &str, private: bool) -> TimeEntry {
+        let now = Utc::now();
+        TimeEntry {
+            id,
+            project_id: Some(1),
+            description: description.to_string(),
+            private,
+            ..test_support::entry(now, Some(now))
+        }
+    }
+
+    #[test]
+    fn test_shared_entry_exclude() {
+        let public = entry(1, "Client work", false);
+
+        assert_eq!(shared_entry(&public, PrivacyMode::Exclude).as_ref(), Some(&public));
+        assert_eq!(shared_entry(&entry(2, "Doctor call", true), PrivacyMode::Exclude), None);
+    }
+
+    #[test]
+    fn test_shared_entry_anonymize() {
+        let public = entry(1, "Client work", false);
+        let private = entry(2, "Doctor call", true);
+
+        assert_eq!(shared_entry(&public, PrivacyMode::Anonymize).as_ref(), Some(&public));
+        let shared = shared_entry(&private, PrivacyMode::Anonymize).unwrap();
+        assert_eq!(shared.description, PRIVATE_DESCRIPTION);
+        assert_eq!(shared.project_id, None);
+        assert_eq!(shared.start_time, private.start_time);
+    }
+}
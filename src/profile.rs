@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::db;
+
+/// Command line flag opening a named profile, such as "work" or "personal",
+/// which has its own database and settings
+pub const PROFILE_FLAG: &str = "--profile";
+
+/// Application id of the default profile
+const APPLICATION_ID: &str = "com.example.time-tracking";
+
+/// Directory of the profile databases, inside the data directory
+const PROFILES_DIR: &str = "profiles";
+
+/// Longest profile name accepted, so it fits in file names and menus
+const MAX_NAME_LENGTH: usize = 32;
+
+/// Checks a profile name, returning it without surrounding spaces. Names are
+/// used in file names and application ids, so only letters, digits, "-" and
+/// "_" are allowed.
+pub fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Enter a name for the profile".to_string());
+    }
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(format!("Use at most {} characters", MAX_NAME_LENGTH));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("Use only letters, digits, \"-\" and \"_\"".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Profile opened with `--profile NAME`, `None` for the default profile
+pub fn profile_arg(args: &[String]) -> Option<String> {
+    db::flag_value(args, PROFILE_FLAG)
+}
+
+/// Profile this process runs, when its name is valid
+pub fn current() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    profile_arg(&args).filter(|name| validate_name(name).is_ok())
+}
+
+/// Database of the profile `name` in `data_dir`
+pub fn db_path(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join(PROFILES_DIR).join(format!("{}.db", name))
+}
+
+/// Names of the profiles with a database in `data_dir`, sorted
+pub fn list_profiles(data_dir: &Path) -> Vec<String> {
+    let Ok(files) = fs::read_dir(data_dir.join(PROFILES_DIR)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = files
+        .filter_map(|file| file.ok())
+        .filter_map(|file| {
+            let path = file.path();
+            let name = path.file_stem()?.to_str()?.to_string();
+            (path.extension()? == "db" && validate_name(&name).is_ok()).then_some(name)
+        })
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+/// Application id of a profile, or of a database chosen with `--database` or
+/// `TIME_TRACKING_DB`, which takes precedence like it does for the path.
+/// Every profile and database has its own, so they run side by side and
+/// launching one doesn't activate another. Ids may only hold letters,
+/// digits, "-" and "_", so a database is told apart by a hash of its path.
+pub fn application_id(profile: Option<&str>, database: Option<&Path>) -> String {
+    match (database, profile) {
+        (Some(path), _) => format!("{}.db-{:016x}", APPLICATION_ID, path_hash(path)),
+        (None, Some(name)) => format!("{}.profile-{}", APPLICATION_ID, name),
+        (None, None) => APPLICATION_ID.to_string(),
+    }
+}
+
+/// FNV-1a hash of a path, the same in every build unlike `DefaultHasher`
+fn path_hash(path: &Path) -> u64 {
+    path.to_string_lossy().bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name(" work "), Ok("work".to_string()));
+        assert_eq!(validate_name("Client_A-2"), Ok("Client_A-2".to_string()));
+
+        for name in ["", "  ", "my profile", "../work", "work.db", "ärger", &"x".repeat(33)] {
+            assert!(validate_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_list_profiles() {
+        let dir = std::env::temp_dir().join(format!("time-tracking-profiles-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(list_profiles(&dir).is_empty());
+
+        fs::create_dir_all(dir.join(PROFILES_DIR)).unwrap();
+        for name in ["work", "Personal", "notes"] {
+            let file = db_path(&dir, name);
+            fs::write(if name == "notes" { file.with_extension("txt") } else { file }, "").unwrap();
+        }
+        assert_eq!(list_profiles(&dir), vec!["Personal", "work"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_application_id() {
+        let args = ["app", "--profile", "work"].map(String::from);
+
+        assert_eq!(profile_arg(&args), Some("work".to_string()));
+        assert_eq!(application_id(None, None), "com.example.time-tracking");
+        assert_eq!(application_id(Some("work"), None), "com.example.time-tracking.profile-work");
+    }
+
+    #[test]
+    fn test_application_id_of_database() {
+        let other = application_id(None, Some(Path::new("/home/me/other.db")));
+
+        assert!(other.starts_with("com.example.time-tracking.db-"));
+        assert_eq!(application_id(Some("work"), Some(Path::new("/home/me/other.db"))), other);
+        assert_ne!(application_id(None, Some(Path::new("/home/me/work.db"))), other);
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashSet;
+
+use crate::db::Project;
+use crate::import;
+
+/// A project as planned in a project management tool: its code, name,
+/// hourly rate in cents and monthly budget in minutes
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedProject {
+    pub code: String,
+    pub name: String,
+    pub hourly_rate_cents: Option<i64>,
+    pub monthly_budget_minutes: Option<i64>,
+}
+
+/// What importing a planned project does to the projects
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectChange {
+    Create(PlannedProject),
+    /// Updates the project with the given ID, which has the same code
+    Update(i64, PlannedProject),
+}
+
+impl ProjectChange {
+    pub fn planned(&self) -> &PlannedProject {
+        match self {
+            ProjectChange::Create(planned) | ProjectChange::Update(_, planned) => planned,
+        }
+    }
+}
+
+/// Meaning of a column of the projects file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Code,
+    Name,
+    Rate,
+    Budget,
+}
+
+impl Column {
+    /// Recognizes a header cell such as "Project code" or "Monthly budget (h)"
+    fn from_header(cell: &str) -> Option<Column> {
+        let cell = cell.trim().to_lowercase();
+        if cell.contains("code") || cell == "key" || cell == "id" {
+            Some(Column::Code)
+        } else if cell.contains("rate") {
+            Some(Column::Rate)
+        } else if cell.contains("budget") || cell.contains("hours") {
+            Some(Column::Budget)
+        } else if cell.contains("name") || cell.contains("project") {
+            Some(Column::Name)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses an amount such as "85", "$85.50", "1,200" or "85,50" into
+/// hundredths. A comma is the decimal separator when there is no point and
+/// at most two digits follow it, otherwise it separates thousands.
+fn parse_hundredths(value: &str) -> Option<i64> {
+    let number: String = value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-'))
+        .collect();
+    let decimal_comma = !number.contains('.')
+        && number.rsplit_once(',').is_some_and(|(_, decimals)| decimals.len() <= 2);
+    let number = if decimal_comma {
+        number.replace(',', ".")
+    } else {
+        number.replace(',', "")
+    };
+    let amount: f64 = number.parse().ok()?;
+    (amount >= 0.0).then(|| (amount * 100.0).round() as i64)
+}
+
+/// Parses the projects in a CSV or tab separated file exported from a
+/// project management tool. The header names the columns: a code and a
+/// name are required, an hourly rate and a monthly budget in hours are
+/// optional. Rows with problems are kept as errors for the preview.
+pub fn parse_projects(text: &str) -> Result<Vec<Result<PlannedProject, String>>, String> {
+    let rows = import::split_rows(text, import::detect_delimiter(text));
+    let Some((header, rows)) = rows.split_first() else {
+        return Err("The file is empty".to_string());
+    };
+    let columns: Vec<Option<Column>> = header.iter().map(|cell| Column::from_header(cell)).collect();
+    if !columns.contains(&Some(Column::Code)) || !columns.contains(&Some(Column::Name)) {
+        return Err("The first row must name a code and a name column".to_string());
+    }
+
+    let mut seen_codes = HashSet::new();
+    let projects = rows
+        .iter()
+        .map(|cells| {
+            let cell = |column: Column| -> Option<&str> {
+                columns
+                    .iter()
+                    .position(|c| *c == Some(column))
+                    .and_then(|i| cells.get(i))
+                    .map(|s| s.as_str())
+                    .filter(|s| !s.is_empty())
+            };
+            let code = cell(Column::Code).ok_or("Missing code")?;
+            let name = cell(Column::Name).ok_or_else(|| format!("{}: missing name", code))?;
+            if !seen_codes.insert(code.to_lowercase()) {
+                return Err(format!("{}: code appears more than once", code));
+            }
+            let hourly_rate_cents = cell(Column::Rate)
+                .map(|value| {
+                    parse_hundredths(value).ok_or_else(|| format!("{}: invalid rate \"{}\"", code, value))
+                })
+                .transpose()?;
+            let monthly_budget_minutes = cell(Column::Budget)
+                .map(|value| {
+                    parse_hundredths(value)
+                        .map(|hundredths| hundredths * 60 / 100)
+                        .ok_or_else(|| format!("{}: invalid budget \"{}\"", code, value))
+                })
+                .transpose()?;
+            Ok(PlannedProject {
+                code: code.to_string(),
+                name: name.to_string(),
+                hourly_rate_cents,
+                monthly_budget_minutes,
+            })
+        })
+        .collect();
+    Ok(projects)
+}
+
+/// Matches planned projects to existing ones by code, ignoring case.
+/// Projects with a known code are updated, the others created.
+pub fn plan_changes(planned: Vec<PlannedProject>, existing: &[Project]) -> Vec<ProjectChange> {
+    planned
+        .into_iter()
+        .map(|planned| {
+            let matching = existing.iter().find(|project| {
+                project
+                    .code
+                    .as_deref()
+                    .is_some_and(|code| code.eq_ignore_ascii_case(&planned.code))
+            });
+            match matching {
+                Some(project) => ProjectChange::Update(project.id, planned),
+                None => ProjectChange::Create(planned),
+            }
+        })
+        .collect()
+}
+
+/// Formats a rate in cents as "85.50/h"
+pub fn format_rate(cents: i64) -> String {
+    format!("{}.{:02}/h", cents / 100, cents % 100)
+}
+
+/// Formats a budget in minutes as "40h/month" or "12.5h/month"
+pub fn format_budget(minutes: i64) -> String {
+    let tenths = minutes * 10 / 60;
+    match tenths % 10 {
+        0 => format!("{}h/month", tenths / 10),
+        rest => format!("{}.{}h/month", tenths / 10, rest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    fn planned(code: &str, name: &str) -> PlannedProject {
+        PlannedProject {
+            code: code.to_string(),
+            name: name.to_string(),
+            hourly_rate_cents: None,
+            monthly_budget_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_projects() {
+        let text = "Project code,Name,Hourly rate,Monthly budget (h)\n\
+                    ACME-1,Website,$85.50,40\n\
+                    ACME-2,\"Support, level 2\",\"1,200\",12.5\n\
+                    ACME-3,Internal,,\n";
+
+        let projects = parse_projects(text).unwrap();
+
+        assert_eq!(
+            projects[0],
+            Ok(PlannedProject {
+                code: "ACME-1".to_string(),
+                name: "Website".to_string(),
+                hourly_rate_cents: Some(8550),
+                monthly_budget_minutes: Some(40 * 60),
+            })
+        );
+        assert_eq!(projects[1].as_ref().unwrap().name, "Support, level 2");
+        assert_eq!(projects[1].as_ref().unwrap().hourly_rate_cents, Some(120_000));
+        assert_eq!(projects[1].as_ref().unwrap().monthly_budget_minutes, Some(750));
+        assert_eq!(projects[2], Ok(planned("ACME-3", "Internal")));
+    }
+
+    #[test]
+    fn test_parse_projects_errors() {
+        assert!(parse_projects("Name,Rate\nWebsite,85\n").is_err());
+
+        let projects = parse_projects("Code\tName\tRate\nA\tOne\tlots\nB\t\t\nA\tAgain\t\n").unwrap();
+
+        assert_eq!(projects[0], Err("A: invalid rate \"lots\"".to_string()));
+        assert_eq!(projects[1], Err("B: missing name".to_string()));
+        assert_eq!(projects[2], Err("A: code appears more than once".to_string()));
+    }
+
+    #[test]
+    fn test_plan_changes_matches_codes() {
+        let existing = vec![Project {
+            code: Some("acme-1".to_string()),
+            ..test_support::project(7, "Old website")
+        }];
+
+        let changes = plan_changes(vec![planned("ACME-1", "Website"), planned("ACME-2", "Support")], &existing);
+
+        assert_eq!(
+            changes,
+            vec![
+                ProjectChange::Update(7, planned("ACME-1", "Website")),
+                ProjectChange::Create(planned("ACME-2", "Support")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_rate_and_budget() {
+        assert_eq!(format_rate(8550), "85.50/h");
+        assert_eq!(format_budget(40 * 60), "40h/month");
+        assert_eq!(format_budget(750), "12.5h/month");
+    }
+
+    #[test]
+    fn test_parse_hundredths_separators() {
+        assert_eq!(parse_hundredths("85,50"), Some(8550));
+        assert_eq!(parse_hundredths("1,200"), Some(120_000));
+        assert_eq!(parse_hundredths("-5"), None);
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::db::Reminder;
+
+/// How late a reminder may still fire, e.g. after the computer woke from
+/// sleep. Older occurrences are skipped rather than reminding of a
+/// standup hours after it ended.
+pub const LATE_GRACE_MINUTES: i64 = 15;
+
+/// Days a reminder fires on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Repeat {
+    Daily,
+    #[default]
+    Weekdays,
+    Weekends,
+    Weekly(Weekday),
+}
+
+impl Repeat {
+    /// All repeats, in the order they are offered when editing a reminder
+    pub const ALL: [Repeat; 10] = [
+        Repeat::Daily,
+        Repeat::Weekdays,
+        Repeat::Weekends,
+        Repeat::Weekly(Weekday::Mon),
+        Repeat::Weekly(Weekday::Tue),
+        Repeat::Weekly(Weekday::Wed),
+        Repeat::Weekly(Weekday::Thu),
+        Repeat::Weekly(Weekday::Fri),
+        Repeat::Weekly(Weekday::Sat),
+        Repeat::Weekly(Weekday::Sun),
+    ];
+
+    /// Value stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Repeat::Daily => "daily",
+            Repeat::Weekdays => "weekdays",
+            Repeat::Weekends => "weekends",
+            Repeat::Weekly(Weekday::Mon) => "mon",
+            Repeat::Weekly(Weekday::Tue) => "tue",
+            Repeat::Weekly(Weekday::Wed) => "wed",
+            Repeat::Weekly(Weekday::Thu) => "thu",
+            Repeat::Weekly(Weekday::Fri) => "fri",
+            Repeat::Weekly(Weekday::Sat) => "sat",
+            Repeat::Weekly(Weekday::Sun) => "sun",
+        }
+    }
+
+    /// Parses a stored value, falling back to weekdays for unknown ones
+    pub fn parse(value: &str) -> Repeat {
+        Repeat::ALL
+            .into_iter()
+            .find(|repeat| repeat.as_str() == value)
+            .unwrap_or_default()
+    }
+
+    /// Human readable name shown with the reminder
+    pub fn label(self) -> &'static str {
+        match self {
+            Repeat::Daily => "Every day",
+            Repeat::Weekdays => "Weekdays",
+            Repeat::Weekends => "Weekends",
+            Repeat::Weekly(Weekday::Mon) => "Mondays",
+            Repeat::Weekly(Weekday::Tue) => "Tuesdays",
+            Repeat::Weekly(Weekday::Wed) => "Wednesdays",
+            Repeat::Weekly(Weekday::Thu) => "Thursdays",
+            Repeat::Weekly(Weekday::Fri) => "Fridays",
+            Repeat::Weekly(Weekday::Sat) => "Saturdays",
+            Repeat::Weekly(Weekday::Sun) => "Sundays",
+        }
+    }
+
+    /// Whether the reminder fires on `day`
+    pub fn includes(self, day: Weekday) -> bool {
+        let weekend = matches!(day, Weekday::Sat | Weekday::Sun);
+        match self {
+            Repeat::Daily => true,
+            Repeat::Weekdays => !weekend,
+            Repeat::Weekends => weekend,
+            Repeat::Weekly(weekday) => weekday == day,
+        }
+    }
+}
+
+/// Describes when a reminder fires, e.g. "Weekdays 09:30"
+pub fn schedule_label(repeat: Repeat, time: NaiveTime) -> String {
+    format!("{} {}", repeat.label(), time.format("%H:%M"))
+}
+
+/// The enabled reminders with an occurrence after `since` and up to `now`,
+/// in local time, skipping occurrences more than `LATE_GRACE_MINUTES` old.
+/// Each fires at most once per call.
+pub fn due_reminders(reminders: &[Reminder], since: NaiveDateTime, now: NaiveDateTime) -> Vec<&Reminder> {
+    let since = since.max(now - Duration::minutes(LATE_GRACE_MINUTES));
+    reminders
+        .iter()
+        .filter(|reminder| reminder.enabled)
+        .filter(|reminder| {
+            since
+                .date()
+                .iter_days()
+                .take_while(|day| *day <= now.date())
+                .filter(|day| reminder.repeat.includes(day.weekday()))
+                .map(|day| day.and_time(reminder.time))
+                .any(|occurrence| occurrence > since && occurrence <= now)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn reminder(id: i64, repeat: Repeat, hour: u32, minute: u32) -> Reminder {
+        Reminder {
+            id,
+            name: format!("Reminder {}", id),
+            repeat,
+            time: NaiveTime::from_hms_opt(hour, minute, 0).unwrap(),
+            template_id: None,
+            enabled: true,
+        }
+    }
+
+    /// A time on the week of Monday, March 4th 2024
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn due_ids(reminders: &[Reminder], since: NaiveDateTime, now: NaiveDateTime) -> Vec<i64> {
+        due_reminders(reminders, since, now).iter().map(|r| r.id).collect()
+    }
+
+    #[test]
+    fn test_repeat_round_trips() {
+        for repeat in Repeat::ALL {
+            assert_eq!(Repeat::parse(repeat.as_str()), repeat);
+        }
+        assert_eq!(Repeat::parse("fortnightly"), Repeat::Weekdays);
+        assert!(Repeat::Weekdays.includes(Weekday::Fri));
+        assert!(!Repeat::Weekdays.includes(Weekday::Sat));
+        assert!(Repeat::Weekends.includes(Weekday::Sun));
+    }
+
+    #[test]
+    fn test_due_reminders() {
+        let mut disabled = reminder(4, Repeat::Daily, 9, 30);
+        disabled.enabled = false;
+        let reminders = [
+            reminder(1, Repeat::Weekdays, 9, 30),
+            reminder(2, Repeat::Weekly(Weekday::Tue), 9, 30),
+            reminder(3, Repeat::Daily, 17, 0),
+            disabled,
+            reminder(5, Repeat::Daily, 0, 0),
+        ];
+
+        // Monday 09:30, checked every half minute
+        assert_eq!(due_ids(&reminders, at(4, 9, 29), at(4, 9, 30)), vec![1]);
+        assert!(due_ids(&reminders, at(4, 9, 30), at(4, 9, 31)).is_empty());
+        // Tuesday, when the weekly one fires too, and Saturday
+        assert_eq!(due_ids(&reminders, at(5, 9, 25), at(5, 9, 35)), vec![1, 2]);
+        assert_eq!(due_ids(&reminders, at(9, 16, 59), at(9, 17, 0)), vec![3]);
+        // Across midnight
+        assert_eq!(due_ids(&reminders, at(4, 23, 58), at(5, 0, 2)), vec![5]);
+    }
+
+    #[test]
+    fn test_late_reminders_are_skipped() {
+        let reminders = [reminder(1, Repeat::Daily, 9, 30)];
+
+        // Woke up at 09:40, within the grace period
+        assert_eq!(due_ids(&reminders, at(4, 9, 0), at(4, 9, 40)), vec![1]);
+        // Woke up at noon, or the next day
+        assert!(due_ids(&reminders, at(4, 9, 0), at(4, 12, 0)).is_empty());
+        assert!(due_ids(&reminders, at(4, 9, 0), at(5, 8, 0)).is_empty());
+    }
+}
@@ -0,0 +1,867 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+
+use crate::db::{Project, TimeEntry};
+use crate::duration::Rounding;
+use crate::settings::DurationFormat;
+use crate::tags;
+
+/// Inclusive range of days covered by a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReportRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl ReportRange {
+    /// Creates a range, swapping the dates when they are reversed
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        if start <= end {
+            Self { start, end }
+        } else {
+            Self { start: end, end: start }
+        }
+    }
+
+    /// Monday to Sunday of the week containing `date`
+    pub fn week_of(date: NaiveDate) -> Self {
+        let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+        Self::new(monday, monday + Days::new(6))
+    }
+
+    /// First to last day of the month containing `date`
+    pub fn month_of(date: NaiveDate) -> Self {
+        let first = date.with_day(1).unwrap();
+        Self::new(first, first + Months::new(1) - Days::new(1))
+    }
+
+    /// January 1 to December 31 of the year containing `date`
+    pub fn year_of(date: NaiveDate) -> Self {
+        let first = date.with_ordinal(1).unwrap();
+        Self::new(first, first + Months::new(12) - Days::new(1))
+    }
+
+    /// Number of days in the range
+    pub fn days(&self) -> u64 {
+        (self.end - self.start).num_days() as u64 + 1
+    }
+
+    /// True when the range spans whole calendar months
+    fn is_whole_months(&self) -> bool {
+        self.start.day() == 1 && (self.end + Days::new(1)).day() == 1
+    }
+
+    /// The adjacent range of the same length: whole months move by months,
+    /// anything else by its number of days
+    pub fn shifted(&self, forward: bool) -> Self {
+        if self.is_whole_months() {
+            let months = (self.end.year() - self.start.year()) * 12
+                + self.end.month() as i32
+                - self.start.month() as i32
+                + 1;
+            let months = Months::new(months as u32);
+            let start = if forward {
+                self.start + months
+            } else {
+                self.start - months
+            };
+            return Self::new(start, start + months - Days::new(1));
+        }
+
+        let days = Days::new(self.days());
+        if forward {
+            Self::new(self.start + days, self.end + days)
+        } else {
+            Self::new(self.start - days, self.end - days)
+        }
+    }
+
+    /// Short description such as "Mar 4 – Mar 10, 2024"
+    pub fn label(&self) -> String {
+        if self.start == self.end {
+            return self.start.format("%b %-d, %Y").to_string();
+        }
+        if self.is_whole_months() && self.days() <= 31 {
+            return self.start.format("%B %Y").to_string();
+        }
+        if self.start.year() == self.end.year() {
+            format!(
+                "{} – {}",
+                self.start.format("%b %-d"),
+                self.end.format("%b %-d, %Y")
+            )
+        } else {
+            format!(
+                "{} – {}",
+                self.start.format("%b %-d, %Y"),
+                self.end.format("%b %-d, %Y")
+            )
+        }
+    }
+}
+
+/// What report rows are grouped by. Clients aren't tracked yet, so entries
+/// group by project, by description, by "#tag" in the description or by day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Grouping {
+    #[default]
+    Project,
+    Description,
+    Tag,
+    Day,
+}
+
+impl Grouping {
+    /// All groupings, in the order they are offered in the reports window
+    pub const ALL: [Grouping; 4] = [Grouping::Project, Grouping::Description, Grouping::Tag, Grouping::Day];
+
+    /// Human readable name shown in the reports window
+    pub fn label(self) -> &'static str {
+        match self {
+            Grouping::Project => "Project",
+            Grouping::Description => "Description",
+            Grouping::Tag => "Tag",
+            Grouping::Day => "Day",
+        }
+    }
+}
+
+/// One line of a report
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReportRow {
+    pub label: String,
+    /// Project of the row when grouping by project, `None` for No Project
+    pub project_id: Option<i64>,
+    /// Project color, only set when grouping by project
+    pub color: Option<String>,
+    pub seconds: i64,
+    pub entries: usize,
+}
+
+/// Groups entries into report rows. Durations follow the rounding rule;
+/// rows are ordered by date when grouping by day, otherwise longest first.
+/// Days are taken in the given time zone. When grouping by tag an entry
+/// counts toward each of its tags, so rows can add up to more than the total.
+pub fn group_entries<Tz: TimeZone>(
+    entries: &[TimeEntry],
+    projects: &HashMap<i64, Project>,
+    grouping: Grouping,
+    rounding: &Rounding,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Vec<ReportRow> {
+    // (sort key, row) per group
+    let mut groups: HashMap<String, (Option<NaiveDate>, ReportRow)> = HashMap::new();
+
+    for entry in entries {
+        for (key, date, label, color) in entry_groups(entry, projects, grouping, tz) {
+            let (_, row) = groups.entry(key).or_insert_with(|| {
+                (
+                    date,
+                    ReportRow {
+                        label,
+                        project_id: if grouping == Grouping::Project {
+                            entry.project_id.filter(|id| projects.contains_key(id))
+                        } else {
+                            None
+                        },
+                        color,
+                        seconds: 0,
+                        entries: 0,
+                    },
+                )
+            });
+            row.seconds += rounding.entry_seconds(entry, now);
+            row.entries += 1;
+        }
+    }
+
+    let mut rows: Vec<(Option<NaiveDate>, ReportRow)> = groups.into_values().collect();
+    if grouping == Grouping::Day {
+        rows.sort_by_key(|(date, _)| *date);
+    } else {
+        rows.sort_by(|(_, a), (_, b)| b.seconds.cmp(&a.seconds).then_with(|| a.label.cmp(&b.label)));
+    }
+
+    rows.into_iter()
+        .map(|(_, mut row)| {
+            row.seconds = rounding.round_total(row.seconds);
+            row
+        })
+        .collect()
+}
+
+/// Key, sort date, label and color of each group an entry belongs to: one,
+/// except when grouping by tag
+fn entry_groups<Tz: TimeZone>(
+    entry: &TimeEntry,
+    projects: &HashMap<i64, Project>,
+    grouping: Grouping,
+    tz: &Tz,
+) -> Vec<(String, Option<NaiveDate>, String, Option<String>)> {
+    match grouping {
+        Grouping::Project => match entry.project_id.and_then(|id| projects.get(&id)) {
+            Some(project) => vec![(
+                project.id.to_string(),
+                None,
+                project.name.clone(),
+                Some(project.color.clone()),
+            )],
+            None => vec![(String::new(), None, "No Project".to_string(), None)],
+        },
+        // Like task estimates, descriptions differing only in case are one task,
+        // labeled as first seen
+        Grouping::Description => {
+            let description = entry.description.trim();
+            let label = if description.is_empty() {
+                "(no description)"
+            } else {
+                description
+            };
+            vec![(description.to_lowercase(), None, label.to_string(), None)]
+        }
+        Grouping::Tag => {
+            let tags = tags::entry_tags(&entry.description);
+            if tags.is_empty() {
+                return vec![(String::new(), None, "(no tag)".to_string(), None)];
+            }
+            tags.into_iter().map(|tag| (tag.clone(), None, format!("#{}", tag), None)).collect()
+        }
+        Grouping::Day => {
+            let date = entry.start_time.with_timezone(tz).date_naive();
+            vec![(
+                date.to_string(),
+                Some(date),
+                date.format("%a, %b %-d").to_string(),
+                None,
+            )]
+        }
+    }
+}
+
+/// Tracked time per day with the rounding rule applied, days taken in the
+/// given time zone. Days without entries are missing.
+pub fn daily_totals<Tz: TimeZone>(
+    entries: &[TimeEntry],
+    rounding: &Rounding,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> HashMap<NaiveDate, i64> {
+    let mut seconds_by_day: HashMap<NaiveDate, i64> = HashMap::new();
+    for entry in entries {
+        let day = entry.start_time.with_timezone(tz).date_naive();
+        *seconds_by_day.entry(day).or_insert(0) += rounding.entry_seconds(entry, now);
+    }
+    seconds_by_day
+        .into_iter()
+        .map(|(day, seconds)| (day, rounding.round_total(seconds)))
+        .collect()
+}
+
+/// Whether going from `previous` to `next` interrupted the work: the timer
+/// was stopped before `next` started, or `next` is a different task. An entry
+/// continuing the same task right where the previous one ended is not one.
+pub fn is_interruption(previous: &TimeEntry, next: &TimeEntry) -> bool {
+    let stopped = previous.end_time.is_some_and(|end| end < next.start_time);
+    let switched = previous.project_id != next.project_id
+        || previous.description.trim() != next.description.trim();
+    stopped || switched
+}
+
+/// Counts the interruptions of each day with entries, oldest day first.
+/// Entries are ordered by start time within a day; days are taken in the
+/// given time zone.
+pub fn interruptions_by_day<Tz: TimeZone>(entries: &[TimeEntry], tz: &Tz) -> Vec<(NaiveDate, usize)> {
+    let mut by_day: HashMap<NaiveDate, Vec<&TimeEntry>> = HashMap::new();
+    for entry in entries {
+        let date = entry.start_time.with_timezone(tz).date_naive();
+        by_day.entry(date).or_default().push(entry);
+    }
+
+    let mut days: Vec<(NaiveDate, usize)> = by_day
+        .into_iter()
+        .map(|(date, mut day_entries)| {
+            day_entries.sort_by_key(|entry| (entry.start_time, entry.id));
+            let count = day_entries
+                .windows(2)
+                .filter(|pair| is_interruption(pair[0], pair[1]))
+                .count();
+            (date, count)
+        })
+        .collect();
+    days.sort_by_key(|(date, _)| *date);
+    days
+}
+
+/// Longest duration among the projects not hidden from a chart, which sets
+/// the scale of its bars so the remaining projects fill the width. Never
+/// less than one second, so it can be divided by.
+pub fn visible_max_seconds(
+    project_seconds: impl IntoIterator<Item = (Option<i64>, i64)>,
+    hidden: &HashSet<Option<i64>>,
+) -> i64 {
+    project_seconds
+        .into_iter()
+        .filter(|(project_id, _)| !hidden.contains(project_id))
+        .map(|(_, seconds)| seconds)
+        .max()
+        .unwrap_or(0)
+        .max(1)
+}
+
+/// Highlights of a year, from its tracked seconds per day
+#[derive(Debug, Clone, PartialEq)]
+pub struct YearReview {
+    pub total_seconds: i64,
+    /// Days with anything tracked
+    pub tracked_days: usize,
+    /// Average over the tracked days
+    pub average_daily_seconds: i64,
+    /// First day of the busiest month, with its total
+    pub busiest_month: Option<(NaiveDate, i64)>,
+    /// Monday of the busiest week, with its total
+    pub busiest_week: Option<(NaiveDate, i64)>,
+    pub busiest_day: Option<(NaiveDate, i64)>,
+}
+
+/// Summarizes a year from its tracked seconds per day. Ties go to the
+/// earlier month, week or day.
+pub fn year_review(daily_seconds: &[(NaiveDate, i64)]) -> YearReview {
+    let mut months: HashMap<NaiveDate, i64> = HashMap::new();
+    let mut weeks: HashMap<NaiveDate, i64> = HashMap::new();
+    for &(day, seconds) in daily_seconds {
+        *months.entry(ReportRange::month_of(day).start).or_insert(0) += seconds;
+        *weeks.entry(ReportRange::week_of(day).start).or_insert(0) += seconds;
+    }
+    let busiest = |totals: HashMap<NaiveDate, i64>| {
+        totals
+            .into_iter()
+            .max_by(|(a_day, a), (b_day, b)| a.cmp(b).then(b_day.cmp(a_day)))
+    };
+
+    let tracked: Vec<_> = daily_seconds.iter().filter(|(_, seconds)| *seconds > 0).collect();
+    let total_seconds: i64 = tracked.iter().map(|(_, seconds)| seconds).sum();
+    YearReview {
+        total_seconds,
+        tracked_days: tracked.len(),
+        average_daily_seconds: total_seconds / tracked.len().max(1) as i64,
+        busiest_month: busiest(months),
+        busiest_week: busiest(weeks),
+        busiest_day: busiest(daily_seconds.iter().copied().collect()),
+    }
+}
+
+/// Total of the projects not hidden from the charts, which their shares
+/// are taken of
+pub fn visible_total_seconds(
+    project_seconds: impl IntoIterator<Item = (Option<i64>, i64)>,
+    hidden: &HashSet<Option<i64>>,
+) -> i64 {
+    project_seconds
+        .into_iter()
+        .filter(|(project_id, _)| !hidden.contains(project_id))
+        .map(|(_, seconds)| seconds)
+        .sum()
+}
+
+/// Percentage of the total, rounded to whole percents
+pub fn format_share(seconds: i64, total: i64) -> String {
+    if total <= 0 {
+        return "–".to_string();
+    }
+    format!("{}%", (seconds as f64 / total as f64 * 100.0).round() as i64)
+}
+
+/// Estimated against tracked time of one task, a description, or of a
+/// project, its name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EstimateRow {
+    pub description: String,
+    pub estimated_seconds: Option<i64>,
+    pub tracked_seconds: i64,
+}
+
+impl EstimateRow {
+    /// Time tracked beyond the estimate, `None` without an estimate or overrun
+    pub fn overrun_seconds(&self) -> Option<i64> {
+        self.estimated_seconds
+            .map(|estimate| self.tracked_seconds - estimate)
+            .filter(|overrun| *overrun > 0)
+    }
+}
+
+/// Tracked time per task against its estimate, for the tasks tracked in
+/// `entries`. Tasks are descriptions, matched ignoring case and surrounding
+/// space, and `estimates` is keyed by the lowercased description. Estimated
+/// tasks come first, the biggest overrun first, then the others longest first.
+pub fn estimate_rows(
+    entries: &[TimeEntry],
+    estimates: &HashMap<String, i64>,
+    rounding: &Rounding,
+    now: DateTime<Utc>,
+) -> Vec<EstimateRow> {
+    let mut tasks: HashMap<String, EstimateRow> = HashMap::new();
+    for entry in entries {
+        let description = entry.description.trim();
+        if description.is_empty() {
+            continue;
+        }
+        let key = description.to_lowercase();
+        let row = tasks.entry(key.clone()).or_insert_with(|| EstimateRow {
+            description: description.to_string(),
+            estimated_seconds: estimates.get(&key).copied(),
+            tracked_seconds: 0,
+        });
+        row.tracked_seconds += rounding.entry_seconds(entry, now);
+    }
+
+    let mut rows: Vec<EstimateRow> = tasks
+        .into_values()
+        .map(|mut row| {
+            row.tracked_seconds = rounding.round_total(row.tracked_seconds);
+            row
+        })
+        .collect();
+    rows.sort_by(compare_estimate_rows);
+    rows
+}
+
+/// Tracked time of each project against its estimated total, with the
+/// project's id. `tracked` is the unrounded time tracked on each project,
+/// by id, over all time. Sorted like `estimate_rows`.
+pub fn project_estimate_rows(
+    projects: &[Project],
+    tracked: &HashMap<i64, i64>,
+    rounding: &Rounding,
+) -> Vec<(i64, EstimateRow)> {
+    let mut rows: Vec<(i64, EstimateRow)> = projects
+        .iter()
+        .map(|project| {
+            let row = EstimateRow {
+                description: project.name.clone(),
+                estimated_seconds: project.estimate_seconds,
+                tracked_seconds: rounding.round_total(tracked.get(&project.id).copied().unwrap_or(0)),
+            };
+            (project.id, row)
+        })
+        .collect();
+    rows.sort_by(|(_, a), (_, b)| compare_estimate_rows(a, b));
+    rows
+}
+
+/// Estimated rows first, the biggest overrun first, then the others longest first
+fn compare_estimate_rows(a: &EstimateRow, b: &EstimateRow) -> std::cmp::Ordering {
+    let difference = |row: &EstimateRow| row.estimated_seconds.map(|estimate| row.tracked_seconds - estimate);
+    a.estimated_seconds
+        .is_none()
+        .cmp(&b.estimated_seconds.is_none())
+        .then_with(|| difference(b).cmp(&difference(a)))
+        .then_with(|| b.tracked_seconds.cmp(&a.tracked_seconds))
+        .then_with(|| a.description.cmp(&b.description))
+}
+
+/// Tracked time relative to the estimate, e.g. "+25%" or "-10%"
+pub fn format_estimate_difference(estimated_seconds: i64, tracked_seconds: i64) -> String {
+    if estimated_seconds <= 0 {
+        return "–".to_string();
+    }
+    let percent = ((tracked_seconds - estimated_seconds) as f64 / estimated_seconds as f64 * 100.0).round() as i64;
+    format!("{:+}%", percent)
+}
+
+/// Report rows as tab separated text, which pastes into spreadsheets as a
+/// table: the title, a header line, one line per row and the total
+pub fn rows_as_text(
+    title: &str,
+    heading: &str,
+    rows: &[ReportRow],
+    total: i64,
+    format: DurationFormat,
+) -> String {
+    let mut text = format!("{}\n{}\tEntries\tShare\tDuration\n", title, heading);
+    for row in rows {
+        text.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            row.label.replace(['\t', '\n'], " "),
+            row.entries,
+            format_share(row.seconds, total),
+            format.format(row.seconds)
+        ));
+    }
+    let entries: usize = rows.iter().map(|row| row.entries).sum();
+    text.push_str(&format!(
+        "Total\t{}\t{}\t{}\n",
+        entries,
+        format_share(total, total),
+        format.format(total)
+    ));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::db::test_support;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn entry(project_id: Option<i64>, description: &str, day: u32, minutes: i64) -> TimeEntry {
+        let start = Utc.with_ymd_and_hms(2024, 3, day, 9, 0, 0).unwrap();
+        TimeEntry {
+            id: 0,
+            project_id,
+            description: description.to_string(),
+            ..test_support::entry(start, Some(start + chrono::Duration::minutes(minutes)))
+        }
+    }
+
+    fn projects() -> HashMap<i64, Project> {
+        HashMap::from([(1, test_support::project(1, "Work"))])
+    }
+
+    #[test]
+    fn test_range_week_and_month() {
+        // 2024-03-06 is a Wednesday
+        assert_eq!(
+            ReportRange::week_of(date(2024, 3, 6)),
+            ReportRange::new(date(2024, 3, 4), date(2024, 3, 10))
+        );
+        assert_eq!(
+            ReportRange::month_of(date(2024, 2, 14)),
+            ReportRange::new(date(2024, 2, 1), date(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn test_range_year() {
+        let year = ReportRange::year_of(date(2024, 3, 6));
+
+        assert_eq!(year, ReportRange::new(date(2024, 1, 1), date(2024, 12, 31)));
+        assert_eq!(year.days(), 366);
+        assert_eq!(year.shifted(false), ReportRange::year_of(date(2023, 7, 1)));
+    }
+
+    #[test]
+    fn test_range_new_orders_dates() {
+        let range = ReportRange::new(date(2024, 3, 10), date(2024, 3, 4));
+
+        assert_eq!(range.start, date(2024, 3, 4));
+        assert_eq!(range.days(), 7);
+    }
+
+    #[test]
+    fn test_range_shifted_by_days() {
+        let week = ReportRange::week_of(date(2024, 3, 6));
+
+        assert_eq!(week.shifted(true), ReportRange::week_of(date(2024, 3, 13)));
+        assert_eq!(week.shifted(false), ReportRange::week_of(date(2024, 2, 28)));
+    }
+
+    #[test]
+    fn test_range_shifted_by_months() {
+        let february = ReportRange::month_of(date(2024, 2, 1));
+
+        assert_eq!(february.shifted(true), ReportRange::month_of(date(2024, 3, 1)));
+        assert_eq!(february.shifted(false), ReportRange::month_of(date(2024, 1, 1)));
+
+        let quarter = ReportRange::new(date(2024, 1, 1), date(2024, 3, 31));
+        assert_eq!(
+            quarter.shifted(true),
+            ReportRange::new(date(2024, 4, 1), date(2024, 6, 30))
+        );
+    }
+
+    #[test]
+    fn test_range_label() {
+        assert_eq!(ReportRange::month_of(date(2024, 3, 1)).label(), "March 2024");
+        assert_eq!(ReportRange::week_of(date(2024, 3, 6)).label(), "Mar 4 – Mar 10, 2024");
+        assert_eq!(
+            ReportRange::new(date(2023, 12, 30), date(2024, 1, 2)).label(),
+            "Dec 30, 2023 – Jan 2, 2024"
+        );
+    }
+
+    #[test]
+    fn test_group_by_project() {
+        let entries = vec![
+            entry(Some(1), "Coding", 4, 30),
+            entry(None, "Email", 4, 45),
+            entry(Some(1), "Review", 5, 30),
+        ];
+
+        let rows = group_entries(&entries, &projects(), Grouping::Project, &Rounding::default(), Utc::now(), &Utc);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].label, "Work");
+        assert_eq!(rows[0].project_id, Some(1));
+        assert_eq!(rows[0].color.as_deref(), Some("#3584e4"));
+        assert_eq!(rows[0].seconds, 3600);
+        assert_eq!(rows[0].entries, 2);
+        assert_eq!(rows[1].label, "No Project");
+        assert_eq!(rows[1].project_id, None);
+        assert_eq!(rows[1].seconds, 2700);
+    }
+
+    #[test]
+    fn test_group_by_description() {
+        let entries = vec![
+            entry(Some(1), "Coding", 4, 30),
+            entry(None, "Coding ", 5, 30),
+            entry(None, "", 5, 10),
+        ];
+
+        let rows = group_entries(&entries, &projects(), Grouping::Description, &Rounding::default(), Utc::now(), &Utc);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].label, "Coding");
+        assert_eq!(rows[0].entries, 2);
+        assert_eq!(rows[1].label, "(no description)");
+    }
+
+    #[test]
+    fn test_group_by_description_ignores_case() {
+        let entries = vec![
+            entry(None, "Code review", 5, 30),
+            entry(None, "code Review", 4, 20),
+            entry(None, " CODE REVIEW", 4, 10),
+        ];
+
+        let rows = group_entries(&entries, &projects(), Grouping::Description, &Rounding::default(), Utc::now(), &Utc);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].label, "Code review");
+        assert_eq!(rows[0].entries, 3);
+        assert_eq!(rows[0].seconds, 3600);
+    }
+
+    #[test]
+    fn test_group_by_tag() {
+        let entries = vec![
+            entry(None, "Mockups #design #ClientA", 4, 30),
+            entry(None, "Review #design", 5, 60),
+            entry(None, "Email", 5, 10),
+        ];
+
+        let rows = group_entries(&entries, &projects(), Grouping::Tag, &Rounding::default(), Utc::now(), &Utc);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].label, "#design");
+        assert_eq!(rows[0].entries, 2);
+        assert_eq!(rows[0].seconds, 5400);
+        assert_eq!(rows[1].label, "#clienta");
+        assert_eq!(rows[1].seconds, 1800);
+        assert_eq!(rows[2].label, "(no tag)");
+        assert_eq!(rows[2].seconds, 600);
+    }
+
+    #[test]
+    fn test_group_by_day_in_date_order() {
+        let entries = vec![
+            entry(None, "A", 5, 60),
+            entry(None, "B", 4, 10),
+            entry(None, "C", 5, 30),
+        ];
+
+        let rows = group_entries(&entries, &projects(), Grouping::Day, &Rounding::default(), Utc::now(), &Utc);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].label, "Mon, Mar 4");
+        assert_eq!(rows[1].label, "Tue, Mar 5");
+        assert_eq!(rows[1].seconds, 5400);
+    }
+
+    #[test]
+    fn test_daily_totals() {
+        let entries = vec![
+            entry(None, "A", 5, 60),
+            entry(Some(1), "B", 4, 10),
+            entry(None, "C", 5, 30),
+        ];
+
+        let totals = daily_totals(&entries, &Rounding::default(), Utc::now(), &Utc);
+
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[&date(2024, 3, 4)], 600);
+        assert_eq!(totals[&date(2024, 3, 5)], 5400);
+    }
+
+    #[test]
+    fn test_interruptions_by_day() {
+        let at = |day: u32, hour: u32, minute: u32| Utc.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap();
+        let span = |description: &str, start: DateTime<Utc>, end: DateTime<Utc>| TimeEntry {
+            end_time: Some(end),
+            start_time: start,
+            ..entry(Some(1), description, 4, 0)
+        };
+        let entries = vec![
+            // Continued right away: not an interruption
+            span("Coding", at(4, 9, 0), at(4, 10, 0)),
+            span("Coding", at(4, 10, 0), at(4, 11, 0)),
+            // Switched task without a break
+            span("Email", at(4, 11, 0), at(4, 11, 30)),
+            // Stopped for lunch, then back to the same task
+            span("Email", at(4, 13, 0), at(4, 13, 30)),
+            span("Review", at(5, 9, 0), at(5, 10, 0)),
+        ];
+
+        let days = interruptions_by_day(&entries, &Utc);
+
+        assert_eq!(
+            days,
+            [
+                (NaiveDate::from_ymd_opt(2024, 3, 4).unwrap(), 2),
+                (NaiveDate::from_ymd_opt(2024, 3, 5).unwrap(), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rows_as_text() {
+        let rows = vec![
+            ReportRow {
+                label: "Work\tstuff".to_string(),
+                project_id: Some(1),
+                color: None,
+                seconds: 2700,
+                entries: 2,
+            },
+            ReportRow {
+                label: "No Project".to_string(),
+                project_id: None,
+                color: None,
+                seconds: 900,
+                entries: 1,
+            },
+        ];
+
+        let text = rows_as_text("March 2024", "Project", &rows, 3600, DurationFormat::Clock);
+
+        assert_eq!(
+            text,
+            "March 2024\n\
+             Project\tEntries\tShare\tDuration\n\
+             Work stuff\t2\t75%\t00:45:00\n\
+             No Project\t1\t25%\t00:15:00\n\
+             Total\t3\t100%\t01:00:00\n"
+        );
+    }
+
+    #[test]
+    fn test_year_review() {
+        let daily = [
+            (date(2024, 1, 30), 8 * 3600),
+            (date(2024, 2, 1), 6 * 3600),
+            (date(2024, 2, 2), 4 * 3600),
+            (date(2024, 3, 4), 9 * 3600),
+        ];
+
+        let review = year_review(&daily);
+
+        assert_eq!(review.total_seconds, 27 * 3600);
+        assert_eq!(review.tracked_days, 4);
+        assert_eq!(review.average_daily_seconds, 27 * 3600 / 4);
+        assert_eq!(review.busiest_month, Some((date(2024, 2, 1), 10 * 3600)));
+        // Jan 30 and Feb 1-2 share the week of Monday Jan 29
+        assert_eq!(review.busiest_week, Some((date(2024, 1, 29), 18 * 3600)));
+        assert_eq!(review.busiest_day, Some((date(2024, 3, 4), 9 * 3600)));
+    }
+
+    #[test]
+    fn test_year_review_ties_go_to_the_earlier_day() {
+        let review = year_review(&[(date(2024, 5, 2), 3600), (date(2024, 5, 1), 3600)]);
+
+        assert_eq!(review.busiest_day, Some((date(2024, 5, 1), 3600)));
+    }
+
+    #[test]
+    fn test_year_review_of_empty_year() {
+        let review = year_review(&[]);
+
+        assert_eq!(review.total_seconds, 0);
+        assert_eq!(review.average_daily_seconds, 0);
+        assert_eq!(review.busiest_day, None);
+    }
+
+    #[test]
+    fn test_visible_total_seconds_skips_hidden_projects() {
+        let seconds = [(Some(1), 3600), (Some(2), 1800), (None, 900)];
+
+        assert_eq!(visible_total_seconds(seconds, &HashSet::new()), 6300);
+        assert_eq!(visible_total_seconds(seconds, &HashSet::from([Some(1)])), 2700);
+        assert_eq!(visible_total_seconds(seconds, &HashSet::from([Some(1), Some(2), None])), 0);
+    }
+
+    #[test]
+    fn test_format_share_of_empty_total() {
+        assert_eq!(format_share(0, 0), "–");
+    }
+
+    #[test]
+    fn test_visible_max_seconds_skips_hidden_projects() {
+        let seconds = [(Some(1), 36000), (Some(2), 1800), (None, 900)];
+
+        assert_eq!(visible_max_seconds(seconds, &HashSet::new()), 36000);
+        assert_eq!(visible_max_seconds(seconds, &HashSet::from([Some(1)])), 1800);
+        assert_eq!(visible_max_seconds(seconds, &HashSet::from([Some(1), Some(2), None])), 1);
+    }
+
+    #[test]
+    fn test_estimate_rows() {
+        let entries = vec![
+            entry(None, "Write report", 4, 90),
+            entry(None, " write report", 5, 30),
+            entry(None, "Review", 4, 20),
+            entry(None, "Standup", 4, 15),
+            entry(None, "Email", 4, 45),
+            entry(None, "", 4, 60),
+        ];
+        let estimates = HashMap::from([
+            ("write report".to_string(), 90 * 60),
+            ("review".to_string(), 30 * 60),
+            ("planning".to_string(), 60 * 60),
+        ]);
+
+        let rows = estimate_rows(&entries, &estimates, &Rounding::default(), Utc::now());
+
+        let descriptions: Vec<&str> = rows.iter().map(|row| row.description.as_str()).collect();
+        assert_eq!(descriptions, vec!["Write report", "Review", "Email", "Standup"]);
+        assert_eq!(rows[0].tracked_seconds, 120 * 60);
+        assert_eq!(rows[0].overrun_seconds(), Some(30 * 60));
+        assert_eq!(rows[1].overrun_seconds(), None);
+        assert_eq!(rows[2].estimated_seconds, None);
+    }
+
+    #[test]
+    fn test_project_estimate_rows() {
+        let project = |id: i64, name: &str, estimate_hours: Option<i64>| Project {
+            estimate_seconds: estimate_hours.map(|hours| hours * 3600),
+            ..test_support::project(id, name)
+        };
+        let projects = vec![
+            project(1, "Website", Some(10)),
+            project(2, "Launch", Some(4)),
+            project(3, "Support", None),
+        ];
+        let tracked = HashMap::from([(1, 8 * 3600), (2, 5 * 3600), (3, 3600)]);
+
+        let rows = project_estimate_rows(&projects, &tracked, &Rounding::default());
+
+        let ids: Vec<i64> = rows.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![2, 1, 3]);
+        assert_eq!(rows[0].1.description, "Launch");
+        assert_eq!(rows[0].1.overrun_seconds(), Some(3600));
+        assert_eq!(rows[1].1.overrun_seconds(), None);
+        assert_eq!(rows[2].1.tracked_seconds, 3600);
+    }
+
+    #[test]
+    fn test_format_estimate_difference() {
+        assert_eq!(format_estimate_difference(3600, 4500), "+25%");
+        assert_eq!(format_estimate_difference(3600, 3240), "-10%");
+        assert_eq!(format_estimate_difference(3600, 3600), "+0%");
+        assert_eq!(format_estimate_difference(0, 3600), "–");
+    }
+}
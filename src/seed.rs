@@ -0,0 +1,350 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use rusqlite::Connection;
+use std::path::PathBuf;
+
+use crate::db::{self, NewEntry};
+use crate::error;
+
+/// Name of the developer command generating synthetic data
+pub const SEED_COMMAND: &str = "seed";
+
+const USAGE: &str = "Usage: time-tracking seed --db PATH [--entries N] [--projects N] [--years N] [--seed N]";
+
+/// Colors given to generated projects, cycled in order
+const SEED_COLORS: &[&str] = &[
+    "#3498db", "#e74c3c", "#2ecc71", "#f39c12", "#9b59b6", "#1abc9c", "#e91e63", "#607d8b",
+];
+
+const PROJECT_WORDS: &[&str] = &[
+    "Website", "Mobile App", "Billing", "Onboarding", "Analytics", "Platform", "Support",
+    "Research", "Migration", "Marketing", "Infrastructure", "Design System",
+];
+
+const TASK_VERBS: &[&str] = &[
+    "Review", "Implement", "Fix", "Plan", "Discuss", "Write", "Test", "Refactor", "Deploy",
+    "Document",
+];
+
+const TASK_SUBJECTS: &[&str] = &[
+    "login flow", "invoices", "release notes", "search", "dashboard", "API client",
+    "database schema", "sprint board", "customer feedback", "CI pipeline", "settings page",
+];
+
+/// Hours of a generated work day that entries are spread over
+const WORK_DAY_START_HOUR: u32 = 8;
+const WORK_DAY_SECONDS: i64 = 10 * 3600;
+
+/// What to generate with the seed command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedOptions {
+    pub db_path: PathBuf,
+    pub entries: usize,
+    pub projects: usize,
+    pub years: u32,
+    /// Seed of the random generator, the same options always give the same data
+    pub seed: u64,
+}
+
+impl SeedOptions {
+    /// Parses the arguments following the `seed` command. The database path is
+    /// required so synthetic data never ends up in the real database by accident.
+    pub fn parse(args: &[String]) -> Result<SeedOptions, String> {
+        let mut db_path = None;
+        let mut entries = 1000;
+        let mut projects = 10;
+        let mut years = 1;
+        let mut seed = 1;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+            let number = |value: &String| {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid number for {}: {}", arg, value))
+            };
+            match arg.as_str() {
+                "--db" => db_path = Some(PathBuf::from(value()?)),
+                "--entries" => entries = number(value()?)? as usize,
+                "--projects" => projects = number(value()?)? as usize,
+                "--years" => years = number(value()?)?.max(1) as u32,
+                "--seed" => seed = number(value()?)?,
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        Ok(SeedOptions {
+            db_path: db_path.ok_or("Missing --db PATH")?,
+            entries,
+            projects,
+            years,
+            seed,
+        })
+    }
+}
+
+/// Small deterministic random generator (SplitMix64), good enough for test data
+struct Random(u64);
+
+impl Random {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound`
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+
+    /// Uniform value in `0.0..1.0`
+    fn fraction(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+/// Generates project names and entries. Entries are spread over the work days
+/// of the last `years` years before `today`, a few projects get most of the
+/// time, and some entries have no project. `project_index` of each entry
+/// refers to the returned names.
+pub fn generate<Tz: TimeZone>(
+    options: &SeedOptions,
+    today: NaiveDate,
+    tz: &Tz,
+) -> (Vec<String>, Vec<(Option<usize>, NewEntry)>) {
+    let mut random = Random(options.seed);
+
+    let projects: Vec<String> = (0..options.projects)
+        .map(|index| {
+            let word = PROJECT_WORDS[index % PROJECT_WORDS.len()];
+            match index / PROJECT_WORDS.len() {
+                0 => word.to_string(),
+                round => format!("{} {}", word, round + 1),
+            }
+        })
+        .collect();
+
+    // Work days, with the occasional weekend
+    let first_day = today - Days::new(365 * options.years as u64);
+    let days: Vec<NaiveDate> = first_day
+        .iter_days()
+        .take_while(|day| *day < today)
+        .filter(|day| !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) || random.below(20) == 0)
+        .collect();
+    if days.is_empty() {
+        return (projects, Vec::new());
+    }
+
+    let mut entries = Vec::with_capacity(options.entries);
+    let mut index = 0;
+    for (day_index, day) in days.iter().enumerate() {
+        // Entries are distributed evenly across the days
+        let day_end = (day_index + 1) * options.entries / days.len();
+        let count = day_end - index;
+        if count == 0 {
+            continue;
+        }
+
+        let slot = WORK_DAY_SECONDS / count as i64;
+        let Some(day_start) = tz
+            .from_local_datetime(&day.and_time(NaiveTime::from_hms_opt(WORK_DAY_START_HOUR, 0, 0).unwrap()))
+            .earliest()
+        else {
+            index = day_end;
+            continue;
+        };
+        let day_start: DateTime<Utc> = day_start.with_timezone(&Utc);
+
+        for slot_index in 0..count as i64 {
+            let slot_start = day_start + chrono::Duration::seconds(slot * slot_index);
+            let length = ((slot as f64) * (0.5 + random.fraction() * 0.5)).max(1.0) as i64;
+            let offset = random.below((slot - length + 1).max(1) as usize) as i64;
+            let start_time = slot_start + chrono::Duration::seconds(offset);
+
+            // Squaring favors the first projects, like real usage
+            let project_index = if projects.is_empty() || random.below(10) == 0 {
+                None
+            } else {
+                let skew = random.fraction() * random.fraction();
+                Some((skew * projects.len() as f64) as usize)
+            };
+
+            entries.push((
+                project_index,
+                NewEntry {
+                    project_id: None,
+                    description: format!("{} {}", random.pick(TASK_VERBS), random.pick(TASK_SUBJECTS)),
+                    start_time,
+                    end_time: start_time + chrono::Duration::seconds(length),
+                },
+            ));
+        }
+        index = day_end;
+    }
+
+    (projects, entries)
+}
+
+/// Writes generated projects and entries into the database in one transaction
+pub fn seed_database(conn: &Connection, options: &SeedOptions, today: NaiveDate) -> error::Result<usize> {
+    let (names, generated) = generate(options, today, &chrono::Local);
+
+    let tx = conn.unchecked_transaction()?;
+    let mut project_ids = Vec::with_capacity(names.len());
+    for (index, name) in names.iter().enumerate() {
+        let project = db::create_project(&tx, name, SEED_COLORS[index % SEED_COLORS.len()])?;
+        project_ids.push(project.id);
+    }
+
+    for (project_index, mut entry) in generated.iter().cloned() {
+        entry.project_id = project_index.map(|index| project_ids[index]);
+        db::create_completed_entry(&tx, &entry)?;
+    }
+    tx.commit()?;
+
+    Ok(generated.len())
+}
+
+/// Runs the seed command with the arguments following it, returning the exit code
+pub fn run(args: &[String]) -> i32 {
+    let options = match SeedOptions::parse(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return 2;
+        }
+    };
+
+    let result = db::open_db(&options.db_path)
+        .and_then(|conn| seed_database(&conn, &options, chrono::Local::now().date_naive()));
+    match result {
+        Ok(count) => {
+            println!(
+                "Created {} projects and {} entries in {}",
+                options.projects,
+                count,
+                options.db_path.display()
+            );
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to seed {}: {}", options.db_path.display(), e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn options(entries: usize, projects: usize) -> SeedOptions {
+        SeedOptions {
+            db_path: PathBuf::from("test.db"),
+            entries,
+            projects,
+            years: 1,
+            seed: 7,
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let parsed = SeedOptions::parse(&args(&[
+            "--entries", "100000", "--projects", "40", "--years", "3", "--db", "/tmp/seed.db",
+        ]))
+        .unwrap();
+
+        assert_eq!(parsed.db_path, PathBuf::from("/tmp/seed.db"));
+        assert_eq!(parsed.entries, 100000);
+        assert_eq!(parsed.projects, 40);
+        assert_eq!(parsed.years, 3);
+    }
+
+    #[test]
+    fn test_parse_requires_db_path() {
+        assert!(SeedOptions::parse(&args(&["--entries", "10"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_arguments() {
+        assert!(SeedOptions::parse(&args(&["--db", "x.db", "--entries", "many"])).is_err());
+        assert!(SeedOptions::parse(&args(&["--db", "x.db", "--verbose"])).is_err());
+        assert!(SeedOptions::parse(&args(&["--db"])).is_err());
+    }
+
+    #[test]
+    fn test_generate_counts_and_range() {
+        let (projects, entries) = generate(&options(500, 12), today(), &Utc);
+
+        assert_eq!(projects.len(), 12);
+        assert_eq!(entries.len(), 500);
+        let first_day = today() - Days::new(365);
+        for (project_index, entry) in &entries {
+            assert!(project_index.is_none_or(|index| index < 12));
+            assert!(entry.end_time > entry.start_time);
+            assert!(entry.start_time.date_naive() >= first_day);
+            assert!(entry.start_time.date_naive() < today());
+        }
+    }
+
+    #[test]
+    fn test_generate_entries_do_not_overlap() {
+        let (_, entries) = generate(&options(2000, 3), today(), &Utc);
+
+        for pair in entries.windows(2) {
+            assert!(pair[0].1.end_time <= pair[1].1.start_time);
+        }
+    }
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let first = generate(&options(50, 4), today(), &Utc);
+        let second = generate(&options(50, 4), today(), &Utc);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_generate_unique_project_names() {
+        let (projects, _) = generate(&options(0, 30), today(), &Utc);
+
+        let mut unique = projects.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 30);
+    }
+
+    #[test]
+    fn test_seed_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::create_tables(&conn).unwrap();
+
+        let count = seed_database(&conn, &options(200, 5), today()).unwrap();
+
+        assert_eq!(count, 200);
+        assert_eq!(db::get_all_projects(&conn).unwrap().len(), 5);
+        let stored: i64 = conn
+            .query_row("SELECT COUNT(*) FROM time_entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, 200);
+    }
+}
@@ -0,0 +1,680 @@
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+
+use crate::db;
+use crate::duration::{Rounding, RoundingMode, RoundingScope, TrackedDuration};
+use crate::error::Result;
+use crate::plugins::{self, PluginPermissions};
+use crate::privacy::PrivacyMode;
+use crate::report::ReportRange;
+
+const DURATION_FORMAT_KEY: &str = "duration_format";
+const ROUNDING_INTERVAL_KEY: &str = "rounding_interval_minutes";
+const ROUNDING_MODE_KEY: &str = "rounding_mode";
+const ROUNDING_SCOPE_KEY: &str = "rounding_scope";
+const AUTO_ARCHIVE_MONTHS_KEY: &str = "auto_archive_months";
+const COMPACT_KEY: &str = "compact";
+const PRIVACY_MODE_KEY: &str = "privacy_mode";
+const EXPECTED_DAILY_MINUTES_KEY: &str = "expected_daily_minutes";
+const WORK_DAYS_PER_WEEK_KEY: &str = "work_days_per_week";
+const WEEKLY_TARGET_MINUTES_KEY: &str = "weekly_target_minutes";
+const LOCKED_UNTIL_KEY: &str = "locked_until";
+const TRAY_CLICK_ACTION_KEY: &str = "tray_click_action";
+const CLOSE_ACTION_KEY: &str = "close_action";
+const GROUP_TODAY_BY_PROJECT_KEY: &str = "group_today_by_project";
+const REMEMBER_VIEW_DATES_KEY: &str = "remember_view_dates";
+const DO_NOT_DISTURB_KEY: &str = "do_not_disturb";
+const ENABLED_PLUGINS_KEY: &str = "enabled_plugins";
+const PLUGIN_PERMISSIONS_KEY: &str = "plugin_permissions";
+const MINI_TIMER_OPEN_KEY: &str = "mini_timer_open";
+const MINI_TIMER_CLICK_THROUGH_KEY: &str = "mini_timer_click_through";
+const PORTAL_AUTOSTART_KEY: &str = "portal_autostart";
+const DEFAULT_PROJECT_KEY: &str = "default_project_id";
+const VIEW_WEEK_KEY: &str = "view_week";
+const VIEW_MONTH_KEY: &str = "view_month";
+const VIEW_CUSTOM_START_KEY: &str = "view_custom_start";
+const VIEW_CUSTOM_END_KEY: &str = "view_custom_end";
+
+/// Inactivity periods offered for auto-archiving projects, in months (0 = off)
+pub const AUTO_ARCHIVE_MONTHS: [u32; 4] = [0, 3, 6, 12];
+
+/// Expected working time per day offered in preferences, in minutes
+pub const EXPECTED_DAILY_MINUTES: [u32; 8] = [240, 300, 360, 420, 450, 480, 540, 600];
+
+/// Expected working time per day until the user picks another one
+const DEFAULT_EXPECTED_DAILY_MINUTES: u32 = 480;
+
+/// Work days per week offered in preferences, counted from Monday
+pub const WORK_DAYS_PER_WEEK: [u32; 4] = [4, 5, 6, 7];
+
+/// Work days per week until the user picks another number, Monday to Friday
+const DEFAULT_WORK_DAYS_PER_WEEK: u32 = 5;
+
+/// Weekly targets offered in preferences, in minutes (0 = no target)
+pub const WEEKLY_TARGET_MINUTES: [u32; 13] =
+    [0, 600, 900, 1200, 1440, 1500, 1800, 1920, 2100, 2250, 2400, 2700, 3000];
+
+/// How durations are displayed in totals and summaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DurationFormat {
+    /// Hours, minutes and seconds (07:45:00)
+    #[default]
+    Clock,
+    /// Decimal hours (7.75 h), as used for invoicing
+    Decimal,
+}
+
+impl DurationFormat {
+    /// All formats, in the order they are offered in preferences
+    pub const ALL: [DurationFormat; 2] = [DurationFormat::Clock, DurationFormat::Decimal];
+
+    /// Human readable name shown in preferences
+    pub fn label(self) -> &'static str {
+        match self {
+            DurationFormat::Clock => "Hours:Minutes:Seconds",
+            DurationFormat::Decimal => "Decimal Hours",
+        }
+    }
+
+    /// Formats a duration in seconds using this format
+    pub fn format(self, total_seconds: i64) -> String {
+        let duration = TrackedDuration::from_seconds(total_seconds);
+        match self {
+            DurationFormat::Clock => duration.clock(),
+            DurationFormat::Decimal => duration.decimal(),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            DurationFormat::Clock => "clock",
+            DurationFormat::Decimal => "decimal",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "clock" => Some(DurationFormat::Clock),
+            "decimal" => Some(DurationFormat::Decimal),
+            _ => None,
+        }
+    }
+}
+
+/// What a primary click on the tray icon does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrayClickAction {
+    /// Shows the window, or hides it when it is already in front
+    #[default]
+    ToggleWindow,
+    /// Starts or stops the timer
+    ToggleTimer,
+    /// Opens the command palette, to start a recent entry
+    QuickStart,
+}
+
+impl TrayClickAction {
+    /// All actions, in the order they are offered in preferences
+    pub const ALL: [TrayClickAction; 3] = [
+        TrayClickAction::ToggleWindow,
+        TrayClickAction::ToggleTimer,
+        TrayClickAction::QuickStart,
+    ];
+
+    /// Human readable name shown in preferences
+    pub fn label(self) -> &'static str {
+        match self {
+            TrayClickAction::ToggleWindow => "Show or hide the window",
+            TrayClickAction::ToggleTimer => "Start or stop the timer",
+            TrayClickAction::QuickStart => "Open quick start",
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            TrayClickAction::ToggleWindow => "toggle_window",
+            TrayClickAction::ToggleTimer => "toggle_timer",
+            TrayClickAction::QuickStart => "quick_start",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        TrayClickAction::ALL.into_iter().find(|action| action.as_str() == value)
+    }
+}
+
+/// What the window's close button does while a tray icon is shown;
+/// without one it always quits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CloseAction {
+    /// Hides the window, leaving the app running in the tray
+    #[default]
+    HideToTray,
+    /// Quits the app
+    Quit,
+    /// Asks whether to hide or quit each time
+    Ask,
+}
+
+impl CloseAction {
+    /// All actions, in the order they are offered in preferences
+    pub const ALL: [CloseAction; 3] = [CloseAction::HideToTray, CloseAction::Quit, CloseAction::Ask];
+
+    /// Human readable name shown in preferences
+    pub fn label(self) -> &'static str {
+        match self {
+            CloseAction::HideToTray => "Minimize to tray",
+            CloseAction::Quit => "Quit",
+            CloseAction::Ask => "Ask every time",
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            CloseAction::HideToTray => "hide_to_tray",
+            CloseAction::Quit => "quit",
+            CloseAction::Ask => "ask",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        CloseAction::ALL.into_iter().find(|action| action.as_str() == value)
+    }
+}
+
+fn rounding_mode_as_str(mode: RoundingMode) -> &'static str {
+    match mode {
+        RoundingMode::Nearest => "nearest",
+        RoundingMode::Up => "up",
+        RoundingMode::Down => "down",
+    }
+}
+
+fn parse_rounding_mode(value: &str) -> Option<RoundingMode> {
+    match value {
+        "nearest" => Some(RoundingMode::Nearest),
+        "up" => Some(RoundingMode::Up),
+        "down" => Some(RoundingMode::Down),
+        _ => None,
+    }
+}
+
+fn rounding_scope_as_str(scope: RoundingScope) -> &'static str {
+    match scope {
+        RoundingScope::Entries => "entries",
+        RoundingScope::Reports => "reports",
+    }
+}
+
+fn parse_rounding_scope(value: &str) -> Option<RoundingScope> {
+    match value {
+        "entries" => Some(RoundingScope::Entries),
+        "reports" => Some(RoundingScope::Reports),
+        _ => None,
+    }
+}
+
+fn privacy_mode_as_str(mode: PrivacyMode) -> &'static str {
+    match mode {
+        PrivacyMode::Exclude => "exclude",
+        PrivacyMode::Anonymize => "anonymize",
+    }
+}
+
+fn parse_privacy_mode(value: &str) -> Option<PrivacyMode> {
+    match value {
+        "exclude" => Some(PrivacyMode::Exclude),
+        "anonymize" => Some(PrivacyMode::Anonymize),
+        _ => None,
+    }
+}
+
+/// User preferences persisted in the settings table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub duration_format: DurationFormat,
+    pub rounding: Rounding,
+    /// Projects without entries for this many months are archived at startup, 0 disables it
+    pub auto_archive_months: u32,
+    /// Compact density: smaller timer and rows without start/end times
+    pub compact: bool,
+    /// How private entries appear in shared output
+    pub privacy_mode: PrivacyMode,
+    /// Hours expected on a work day, used to color the month view
+    pub expected_daily_minutes: u32,
+    /// Days per week that are worked, from Monday, used to project the week's total
+    pub work_days_per_week: u32,
+    /// Hours to work in a week, burned down in the Week view (0 = no target)
+    pub weekly_target_minutes: u32,
+    /// Last day of the locked (e.g. invoiced) period; no entries are added on or before it
+    pub locked_until: Option<NaiveDate>,
+    /// What a primary click on the tray icon does
+    pub tray_click_action: TrayClickAction,
+    /// What the window's close button does
+    pub close_action: CloseAction,
+    /// Today's entries are sectioned by project instead of listed by time
+    pub group_today_by_project: bool,
+    /// The Week, Month and Custom views reopen where they were after a restart
+    pub remember_view_dates: bool,
+    /// Do Not Disturb is turned on while a deep work project is tracked
+    pub do_not_disturb: bool,
+    /// Names of the plugins receiving events; discovered plugins are off
+    /// until enabled
+    pub enabled_plugins: Vec<String>,
+    /// What each plugin may see and do, by name; plugins without an entry
+    /// have the default permissions
+    pub plugin_permissions: BTreeMap<String, PluginPermissions>,
+    /// The mini timer was open when the app last quit, and reopens at startup
+    pub mini_timer_open: bool,
+    /// The mini timer lets clicks through to the windows below it and
+    /// hides its buttons, so it never takes focus
+    pub mini_timer_click_through: bool,
+    /// Starting at login was granted by the Background portal. A Flatpak
+    /// can't read that back; elsewhere the autostart entry is checked.
+    pub portal_autostart: bool,
+    /// Project selected at startup and after the timer stops, instead of
+    /// "No Project"
+    pub default_project_id: Option<i64>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            duration_format: DurationFormat::default(),
+            rounding: Rounding::default(),
+            auto_archive_months: 0,
+            compact: false,
+            privacy_mode: PrivacyMode::default(),
+            expected_daily_minutes: DEFAULT_EXPECTED_DAILY_MINUTES,
+            work_days_per_week: DEFAULT_WORK_DAYS_PER_WEEK,
+            weekly_target_minutes: 0,
+            locked_until: None,
+            tray_click_action: TrayClickAction::default(),
+            close_action: CloseAction::default(),
+            group_today_by_project: false,
+            remember_view_dates: false,
+            do_not_disturb: false,
+            enabled_plugins: Vec::new(),
+            plugin_permissions: BTreeMap::new(),
+            mini_timer_open: false,
+            mini_timer_click_through: false,
+            portal_autostart: false,
+            default_project_id: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads settings from the database, using defaults for missing or invalid values
+    pub fn load(conn: &Connection) -> Self {
+        let mut settings = Settings::default();
+
+        if let Some(format) = read(conn, DURATION_FORMAT_KEY).and_then(|v| DurationFormat::parse(&v)) {
+            settings.duration_format = format;
+        }
+
+        if let Some(interval) = read(conn, ROUNDING_INTERVAL_KEY)
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|v| Rounding::INTERVALS.contains(v))
+        {
+            settings.rounding.interval_minutes = interval;
+        }
+        if let Some(mode) = read(conn, ROUNDING_MODE_KEY).and_then(|v| parse_rounding_mode(&v)) {
+            settings.rounding.mode = mode;
+        }
+        if let Some(scope) = read(conn, ROUNDING_SCOPE_KEY).and_then(|v| parse_rounding_scope(&v)) {
+            settings.rounding.scope = scope;
+        }
+
+        if let Some(months) = read(conn, AUTO_ARCHIVE_MONTHS_KEY)
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|v| AUTO_ARCHIVE_MONTHS.contains(v))
+        {
+            settings.auto_archive_months = months;
+        }
+        if let Some(compact) = read(conn, COMPACT_KEY) {
+            settings.compact = compact == "true";
+        }
+        if let Some(mode) = read(conn, PRIVACY_MODE_KEY).and_then(|v| parse_privacy_mode(&v)) {
+            settings.privacy_mode = mode;
+        }
+        if let Some(minutes) = read(conn, EXPECTED_DAILY_MINUTES_KEY)
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|v| EXPECTED_DAILY_MINUTES.contains(v))
+        {
+            settings.expected_daily_minutes = minutes;
+        }
+        if let Some(days) = read(conn, WORK_DAYS_PER_WEEK_KEY)
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|v| WORK_DAYS_PER_WEEK.contains(v))
+        {
+            settings.work_days_per_week = days;
+        }
+        if let Some(minutes) = read(conn, WEEKLY_TARGET_MINUTES_KEY)
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|v| WEEKLY_TARGET_MINUTES.contains(v))
+        {
+            settings.weekly_target_minutes = minutes;
+        }
+        settings.locked_until = read(conn, LOCKED_UNTIL_KEY)
+            .and_then(|v| NaiveDate::parse_from_str(&v, "%Y-%m-%d").ok());
+        if let Some(action) = read(conn, TRAY_CLICK_ACTION_KEY).and_then(|v| TrayClickAction::parse(&v)) {
+            settings.tray_click_action = action;
+        }
+        if let Some(action) = read(conn, CLOSE_ACTION_KEY).and_then(|v| CloseAction::parse(&v)) {
+            settings.close_action = action;
+        }
+        if let Some(grouped) = read(conn, GROUP_TODAY_BY_PROJECT_KEY) {
+            settings.group_today_by_project = grouped == "true";
+        }
+        if let Some(remember) = read(conn, REMEMBER_VIEW_DATES_KEY) {
+            settings.remember_view_dates = remember == "true";
+        }
+        if let Some(do_not_disturb) = read(conn, DO_NOT_DISTURB_KEY) {
+            settings.do_not_disturb = do_not_disturb == "true";
+        }
+        if let Some(plugins) = read(conn, ENABLED_PLUGINS_KEY) {
+            settings.enabled_plugins = plugins.lines().map(|name| name.to_string()).collect();
+        }
+        if let Some(permissions) = read(conn, PLUGIN_PERMISSIONS_KEY) {
+            settings.plugin_permissions = plugins::permissions_from_setting(&permissions);
+        }
+        if let Some(open) = read(conn, MINI_TIMER_OPEN_KEY) {
+            settings.mini_timer_open = open == "true";
+        }
+        if let Some(click_through) = read(conn, MINI_TIMER_CLICK_THROUGH_KEY) {
+            settings.mini_timer_click_through = click_through == "true";
+        }
+        if let Some(autostart) = read(conn, PORTAL_AUTOSTART_KEY) {
+            settings.portal_autostart = autostart == "true";
+        }
+        settings.default_project_id = read(conn, DEFAULT_PROJECT_KEY).and_then(|v| v.parse::<i64>().ok());
+
+        settings
+    }
+
+    /// Saves all settings to the database
+    pub fn save(&self, conn: &Connection) -> Result<()> {
+        db::set_setting(conn, DURATION_FORMAT_KEY, self.duration_format.as_str())?;
+        db::set_setting(
+            conn,
+            ROUNDING_INTERVAL_KEY,
+            &self.rounding.interval_minutes.to_string(),
+        )?;
+        db::set_setting(conn, ROUNDING_MODE_KEY, rounding_mode_as_str(self.rounding.mode))?;
+        db::set_setting(conn, ROUNDING_SCOPE_KEY, rounding_scope_as_str(self.rounding.scope))?;
+        db::set_setting(
+            conn,
+            AUTO_ARCHIVE_MONTHS_KEY,
+            &self.auto_archive_months.to_string(),
+        )?;
+        db::set_setting(conn, COMPACT_KEY, if self.compact { "true" } else { "false" })?;
+        db::set_setting(conn, PRIVACY_MODE_KEY, privacy_mode_as_str(self.privacy_mode))?;
+        db::set_setting(
+            conn,
+            EXPECTED_DAILY_MINUTES_KEY,
+            &self.expected_daily_minutes.to_string(),
+        )?;
+        db::set_setting(conn, WORK_DAYS_PER_WEEK_KEY, &self.work_days_per_week.to_string())?;
+        db::set_setting(
+            conn,
+            WEEKLY_TARGET_MINUTES_KEY,
+            &self.weekly_target_minutes.to_string(),
+        )?;
+        db::set_setting(
+            conn,
+            LOCKED_UNTIL_KEY,
+            &self
+                .locked_until
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        )?;
+        db::set_setting(conn, TRAY_CLICK_ACTION_KEY, self.tray_click_action.as_str())?;
+        db::set_setting(conn, CLOSE_ACTION_KEY, self.close_action.as_str())?;
+        db::set_setting(
+            conn,
+            GROUP_TODAY_BY_PROJECT_KEY,
+            if self.group_today_by_project { "true" } else { "false" },
+        )?;
+        db::set_setting(
+            conn,
+            REMEMBER_VIEW_DATES_KEY,
+            if self.remember_view_dates { "true" } else { "false" },
+        )?;
+        db::set_setting(
+            conn,
+            DO_NOT_DISTURB_KEY,
+            if self.do_not_disturb { "true" } else { "false" },
+        )?;
+        db::set_setting(conn, ENABLED_PLUGINS_KEY, &self.enabled_plugins.join("\n"))?;
+        db::set_setting(
+            conn,
+            PLUGIN_PERMISSIONS_KEY,
+            &plugins::permissions_to_setting(&self.plugin_permissions),
+        )?;
+        db::set_setting(
+            conn,
+            MINI_TIMER_OPEN_KEY,
+            if self.mini_timer_open { "true" } else { "false" },
+        )?;
+        db::set_setting(
+            conn,
+            MINI_TIMER_CLICK_THROUGH_KEY,
+            if self.mini_timer_click_through { "true" } else { "false" },
+        )?;
+        db::set_setting(
+            conn,
+            PORTAL_AUTOSTART_KEY,
+            if self.portal_autostart { "true" } else { "false" },
+        )?;
+        db::set_setting(
+            conn,
+            DEFAULT_PROJECT_KEY,
+            &self.default_project_id.map(|id| id.to_string()).unwrap_or_default(),
+        )
+    }
+}
+
+/// Dates shown by the Week, Month and Custom views, kept across restarts
+/// when `Settings::remember_view_dates` is set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewDates {
+    /// A day in the week shown by the Week view
+    pub week: NaiveDate,
+    /// A day in the month shown by the Month view
+    pub month: NaiveDate,
+    /// Days shown by the Custom view
+    pub custom_range: ReportRange,
+}
+
+impl ViewDates {
+    /// Loads the saved dates, or `None` when they were never saved or are invalid
+    pub fn load(conn: &Connection) -> Option<Self> {
+        let date = |key| read(conn, key).and_then(|v| NaiveDate::parse_from_str(&v, "%Y-%m-%d").ok());
+        Some(Self {
+            week: date(VIEW_WEEK_KEY)?,
+            month: date(VIEW_MONTH_KEY)?,
+            custom_range: ReportRange::new(date(VIEW_CUSTOM_START_KEY)?, date(VIEW_CUSTOM_END_KEY)?),
+        })
+    }
+
+    /// Saves the dates to the database
+    pub fn save(&self, conn: &Connection) -> Result<()> {
+        for (key, date) in [
+            (VIEW_WEEK_KEY, self.week),
+            (VIEW_MONTH_KEY, self.month),
+            (VIEW_CUSTOM_START_KEY, self.custom_range.start),
+            (VIEW_CUSTOM_END_KEY, self.custom_range.end),
+        ] {
+            db::set_setting(conn, key, &date.format("%Y-%m-%d").to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a raw setting value, treating database errors as missing
+fn read(conn: &Connection, key: &str) -> Option<String> {
+    db::get_setting(conn, key).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::create_tables(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_clock_format() {
+        assert_eq!(DurationFormat::Clock.format(0), "00:00:00");
+        assert_eq!(DurationFormat::Clock.format(27_900), "07:45:00");
+        assert_eq!(DurationFormat::Clock.format(3_661), "01:01:01");
+    }
+
+    #[test]
+    fn test_decimal_format() {
+        assert_eq!(DurationFormat::Decimal.format(0), "0.00 h");
+        assert_eq!(DurationFormat::Decimal.format(27_900), "7.75 h");
+        assert_eq!(DurationFormat::Decimal.format(1_800), "0.50 h");
+    }
+
+    #[test]
+    fn test_load_defaults() {
+        let conn = create_test_db();
+
+        assert_eq!(Settings::load(&conn), Settings::default());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let conn = create_test_db();
+        let settings = Settings {
+            duration_format: DurationFormat::Decimal,
+            rounding: Rounding {
+                interval_minutes: 15,
+                mode: RoundingMode::Up,
+                scope: RoundingScope::Reports,
+            },
+            auto_archive_months: 6,
+            compact: true,
+            privacy_mode: PrivacyMode::Anonymize,
+            expected_daily_minutes: 450,
+            work_days_per_week: 4,
+            weekly_target_minutes: 1200,
+            locked_until: NaiveDate::from_ymd_opt(2024, 3, 31),
+            tray_click_action: TrayClickAction::QuickStart,
+            close_action: CloseAction::Ask,
+            group_today_by_project: true,
+            remember_view_dates: true,
+            do_not_disturb: true,
+            enabled_plugins: vec!["jira".to_string(), "notify-send".to_string()],
+            plugin_permissions: BTreeMap::from([(
+                "jira".to_string(),
+                PluginPermissions {
+                    project_ids: Some(vec![2]),
+                    descriptions: false,
+                    ..PluginPermissions::default()
+                },
+            )]),
+            mini_timer_open: true,
+            mini_timer_click_through: true,
+            portal_autostart: true,
+            default_project_id: Some(3),
+        };
+
+        settings.save(&conn).unwrap();
+
+        assert_eq!(Settings::load(&conn), settings);
+    }
+
+    #[test]
+    fn test_load_ignores_invalid_values() {
+        let conn = create_test_db();
+        db::set_setting(&conn, DURATION_FORMAT_KEY, "sundial").unwrap();
+
+        assert_eq!(Settings::load(&conn).duration_format, DurationFormat::Clock);
+    }
+
+    #[test]
+    fn test_load_ignores_unsupported_rounding_interval() {
+        let conn = create_test_db();
+        db::set_setting(&conn, ROUNDING_INTERVAL_KEY, "7").unwrap();
+
+        assert_eq!(Settings::load(&conn).rounding.interval_minutes, 0);
+    }
+
+    #[test]
+    fn test_load_ignores_unsupported_auto_archive_months() {
+        let conn = create_test_db();
+        db::set_setting(&conn, AUTO_ARCHIVE_MONTHS_KEY, "2").unwrap();
+
+        assert_eq!(Settings::load(&conn).auto_archive_months, 0);
+    }
+
+    #[test]
+    fn test_load_ignores_unsupported_expected_daily_minutes() {
+        let conn = create_test_db();
+        db::set_setting(&conn, EXPECTED_DAILY_MINUTES_KEY, "1").unwrap();
+
+        assert_eq!(Settings::load(&conn).expected_daily_minutes, 480);
+    }
+
+    #[test]
+    fn test_load_ignores_unsupported_work_days_per_week() {
+        let conn = create_test_db();
+        db::set_setting(&conn, WORK_DAYS_PER_WEEK_KEY, "9").unwrap();
+
+        assert_eq!(Settings::load(&conn).work_days_per_week, 5);
+    }
+
+    #[test]
+    fn test_load_ignores_unsupported_weekly_target() {
+        let conn = create_test_db();
+        db::set_setting(&conn, WEEKLY_TARGET_MINUTES_KEY, "61").unwrap();
+
+        assert_eq!(Settings::load(&conn).weekly_target_minutes, 0);
+    }
+
+    #[test]
+    fn test_clearing_locked_until() {
+        let conn = create_test_db();
+        let mut settings = Settings {
+            locked_until: NaiveDate::from_ymd_opt(2024, 3, 31),
+            ..Settings::default()
+        };
+        settings.save(&conn).unwrap();
+
+        settings.locked_until = None;
+        settings.save(&conn).unwrap();
+
+        assert_eq!(Settings::load(&conn).locked_until, None);
+    }
+
+    #[test]
+    fn test_view_dates_missing() {
+        let conn = create_test_db();
+
+        assert_eq!(ViewDates::load(&conn), None);
+    }
+
+    #[test]
+    fn test_save_and_load_view_dates() {
+        let conn = create_test_db();
+        let dates = ViewDates {
+            week: NaiveDate::from_ymd_opt(2024, 3, 6).unwrap(),
+            month: NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            custom_range: ReportRange::new(
+                NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 2, 20).unwrap(),
+            ),
+        };
+
+        dates.save(&conn).unwrap();
+
+        assert_eq!(ViewDates::load(&conn), Some(dates));
+    }
+}
@@ -0,0 +1,288 @@
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Utc};
+
+use crate::db::Project;
+use crate::import;
+
+/// A description typed with inline syntax, split into its parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedDescription {
+    /// The description with the recognized tokens taken out
+    pub description: String,
+    /// Project named with "@Name"
+    pub project_id: Option<i64>,
+}
+
+/// Takes an "@ProjectName" mention out of a description, Toggl-style. The
+/// mention must start a word and name a known project, ignoring case; names
+/// with spaces work, and the longest matching name wins. Only the first
+/// mention is used; other "@" text, such as email addresses, is left alone.
+pub fn parse_description(text: &str, projects: &[Project]) -> ParsedDescription {
+    let mut previous: Option<char> = None;
+
+    for (index, c) in text.char_indices() {
+        let starts_word = previous.is_none_or(char::is_whitespace);
+        previous = Some(c);
+        if c != '@' || !starts_word {
+            continue;
+        }
+
+        let rest = &text[index + 1..];
+        let mention = projects
+            .iter()
+            .filter(|project| !project.name.is_empty())
+            .filter(|project| {
+                let length = project.name.len();
+                rest.get(..length)
+                    .is_some_and(|name| name.to_lowercase() == project.name.to_lowercase())
+                    && rest[length..].chars().next().is_none_or(|next| !next.is_alphanumeric())
+            })
+            .max_by_key(|project| project.name.len());
+
+        if let Some(project) = mention {
+            let end = index + 1 + project.name.len();
+            let description = format!("{} {}", &text[..index], &text[end..]);
+            return ParsedDescription {
+                description: description.split_whitespace().collect::<Vec<_>>().join(" "),
+                project_id: Some(project.id),
+            };
+        }
+    }
+
+    ParsedDescription {
+        description: text.to_string(),
+        project_id: None,
+    }
+}
+
+/// Time typed at the start of a description to log work already done
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickTime {
+    /// Work that ended just now, such as "1h30m" or "45m", in seconds
+    Duration(i64),
+    /// Work between two times of today, such as "9:00-10:15"
+    Range(NaiveTime, NaiveTime),
+}
+
+/// Parses a duration such as "1h30m", "2h" or "45m" into seconds
+pub fn parse_duration(token: &str) -> Option<i64> {
+    let (hours, minutes) = match token.split_once('h') {
+        Some((hours, "")) => (hours, "0"),
+        Some((hours, minutes)) => (hours, minutes.strip_suffix('m')?),
+        None => ("0", token.strip_suffix('m')?),
+    };
+    if [hours, minutes].iter().any(|part| part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit())) {
+        return None;
+    }
+
+    let seconds = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+    (seconds > 0).then_some(seconds)
+}
+
+/// Parses the length typed for an entry, either as hours and minutes such
+/// as "1:30" or like `parse_duration`, into seconds
+pub fn parse_entry_duration(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let Some((hours, minutes)) = text.split_once(':') else {
+        return parse_duration(text);
+    };
+    if [hours, minutes].iter().any(|part| part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()))
+        || minutes.len() != 2
+    {
+        return None;
+    }
+
+    let (hours, minutes) = (hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?);
+    let seconds = hours * 3600 + minutes * 60;
+    (minutes < 60 && seconds > 0).then_some(seconds)
+}
+
+/// Formats seconds the way `parse_duration` reads them, such as "1h30m",
+/// dropping leftover seconds
+pub fn format_duration(seconds: i64) -> String {
+    let (hours, minutes) = (seconds / 3600, seconds % 3600 / 60);
+    match (hours, minutes) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{}m", hours, minutes),
+    }
+}
+
+/// Splits a leading duration ("1h30m standup") or time range ("9:00-10:15
+/// review") off a description, returning it with the rest of the text.
+/// Descriptions not starting with either return `None`.
+pub fn parse_quick_time(text: &str) -> Option<(QuickTime, &str)> {
+    let text = text.trim_start();
+    let (token, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+
+    let quick = match token.split_once('-') {
+        Some((start, end)) => QuickTime::Range(import::parse_time(start)?, import::parse_time(end)?),
+        None => QuickTime::Duration(parse_duration(token)?),
+    };
+    Some((quick, rest.trim()))
+}
+
+/// Start and end of a quick entry logged at `now`. Durations end at `now`;
+/// ranges are taken on today's date and must end after they start.
+pub fn quick_entry_times(quick: QuickTime, now: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    match quick {
+        QuickTime::Duration(seconds) => Ok((now - chrono::Duration::seconds(seconds), now)),
+        QuickTime::Range(start, end) => {
+            if end <= start {
+                return Err("The end must come after the start".to_string());
+            }
+            let today = now.with_timezone(&Local).date_naive();
+            Ok((
+                import::local_to_utc(today, start).ok_or("Start time does not exist in local time")?,
+                import::local_to_utc(today, end).ok_or("End time does not exist in local time")?,
+            ))
+        }
+    }
+}
+
+/// When a running timer stopped if it was stopped at `time`: the latest
+/// moment with that time of day not after `now`, so a time later than now
+/// means yesterday. It must come after the entry's `start`.
+pub fn resolve_stop_time<Tz: TimeZone>(
+    time: NaiveTime,
+    start: DateTime<Utc>,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Result<DateTime<Utc>, String> {
+    let today = now.with_timezone(tz).date_naive();
+    let at = |day: chrono::NaiveDate| {
+        tz.from_local_datetime(&day.and_time(time))
+            .earliest()
+            .map(|time| time.with_timezone(&Utc))
+    };
+    let stop = match at(today).filter(|stop| *stop <= now) {
+        Some(stop) => stop,
+        None => today
+            .pred_opt()
+            .and_then(at)
+            .ok_or("That time does not exist in local time")?,
+    };
+    if stop <= start {
+        return Err(format!(
+            "The timer started at {}, stop after that",
+            start.with_timezone(tz).time().format("%H:%M")
+        ));
+    }
+    Ok(stop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    fn projects() -> Vec<Project> {
+        ["Website", "Client Work", "Client"]
+            .iter()
+            .enumerate()
+            .map(|(index, name)| test_support::project(index as i64 + 1, name))
+            .collect()
+    }
+
+    #[test]
+    fn test_mention_sets_project() {
+        let parsed = parse_description("Fix login @website form", &projects());
+
+        assert_eq!(parsed.description, "Fix login form");
+        assert_eq!(parsed.project_id, Some(1));
+    }
+
+    #[test]
+    fn test_longest_project_name_wins() {
+        let parsed = parse_description("@Client Work invoices", &projects());
+
+        assert_eq!(parsed.description, "invoices");
+        assert_eq!(parsed.project_id, Some(2));
+
+        let parsed = parse_description("Call @client", &projects());
+        assert_eq!(parsed.project_id, Some(3));
+    }
+
+    #[test]
+    fn test_unknown_mentions_are_left_alone() {
+        for text in ["Mail bob@website.com", "Ask @Websites team", "Review #design"] {
+            let parsed = parse_description(text, &projects());
+
+            assert_eq!(parsed.description, text);
+            assert_eq!(parsed.project_id, None);
+        }
+    }
+
+    #[test]
+    fn test_parse_quick_duration() {
+        assert_eq!(
+            parse_quick_time("1h30m standup"),
+            Some((QuickTime::Duration(5400), "standup"))
+        );
+        assert_eq!(parse_quick_time("2h"), Some((QuickTime::Duration(7200), "")));
+        assert_eq!(
+            parse_quick_time("45m  Fix @Website form"),
+            Some((QuickTime::Duration(2700), "Fix @Website form"))
+        );
+
+        for text in ["0m nothing", "h review", "1h30 review", "3d printing", "Review 1h"] {
+            assert_eq!(parse_quick_time(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_format_duration_round_trip() {
+        for seconds in [2700, 3600, 5400, 36000] {
+            assert_eq!(parse_duration(&format_duration(seconds)), Some(seconds));
+        }
+        assert_eq!(format_duration(5400), "1h30m");
+    }
+
+    #[test]
+    fn test_parse_entry_duration() {
+        assert_eq!(parse_entry_duration("1:30"), Some(5400));
+        assert_eq!(parse_entry_duration(" 0:45 "), Some(2700));
+        assert_eq!(parse_entry_duration("10:00"), Some(36000));
+        assert_eq!(parse_entry_duration("1h30m"), Some(5400));
+        assert_eq!(parse_entry_duration("45m"), Some(2700));
+
+        for text in ["", "0:00", "1:5", "1:60", ":30", "1:30:00", "-1:30", "1.5"] {
+            assert_eq!(parse_entry_duration(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_quick_range() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+        assert_eq!(
+            parse_quick_time("9:00-10:15 review"),
+            Some((QuickTime::Range(time(9, 0), time(10, 15)), "review"))
+        );
+        assert_eq!(parse_quick_time("9:00-later review"), None);
+    }
+
+    #[test]
+    fn test_quick_entry_times() {
+        let now = Utc::now();
+
+        let (start, end) = quick_entry_times(QuickTime::Duration(5400), now).unwrap();
+        assert_eq!(end, now);
+        assert_eq!((end - start).num_minutes(), 90);
+
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert!(quick_entry_times(QuickTime::Range(time(10, 15), time(9, 0)), now).is_err());
+    }
+
+    #[test]
+    fn test_resolve_stop_time() {
+        let at = |d, h, m| Utc.with_ymd_and_hms(2024, 3, d, h, m, 0).unwrap();
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let now = at(4, 19, 30);
+
+        assert_eq!(resolve_stop_time(time(17, 0), at(4, 9, 0), now, &Utc), Ok(at(4, 17, 0)));
+        // A time after now is taken as yesterday, for timers left running overnight
+        assert_eq!(resolve_stop_time(time(22, 0), at(3, 9, 0), now, &Utc), Ok(at(3, 22, 0)));
+        assert!(resolve_stop_time(time(8, 0), at(4, 9, 0), now, &Utc).is_err());
+        assert!(resolve_stop_time(time(20, 0), at(4, 9, 0), now, &Utc).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::db::TimeEntry;
+use crate::duration::Rounding;
+use crate::report::{self, ReportRange};
+
+/// Average tracked time per weekday over the range, Monday first. Every
+/// occurrence of a weekday in the range counts, including days off, so
+/// the averages add up to a typical week.
+pub fn weekday_averages(daily_totals: &HashMap<NaiveDate, i64>, range: ReportRange) -> [i64; 7] {
+    let mut totals = [0i64; 7];
+    let mut days = [0i64; 7];
+    for day in range.start.iter_days().take(range.days() as usize) {
+        let weekday = day.weekday().num_days_from_monday() as usize;
+        totals[weekday] += daily_totals.get(&day).copied().unwrap_or(0);
+        days[weekday] += 1;
+    }
+    std::array::from_fn(|weekday| totals[weekday] / days[weekday].max(1))
+}
+
+/// Consecutive days with tracked time up to today. Today not being tracked
+/// yet doesn't end the streak, it still counts from yesterday.
+pub fn current_streak(tracked_days: &HashSet<NaiveDate>, today: NaiveDate) -> u32 {
+    let mut day = if tracked_days.contains(&today) {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut streak = 0;
+    while tracked_days.contains(&day) {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+    streak
+}
+
+/// Median of times of day given as seconds since midnight
+fn median_time(mut seconds: Vec<u32>) -> Option<NaiveTime> {
+    if seconds.is_empty() {
+        return None;
+    }
+    seconds.sort_unstable();
+    let middle = seconds.len() / 2;
+    let median = if seconds.len().is_multiple_of(2) {
+        (seconds[middle - 1] + seconds[middle]) / 2
+    } else {
+        seconds[middle]
+    };
+    NaiveTime::from_num_seconds_from_midnight_opt(median, 0)
+}
+
+/// First start and last end of each day, days taken in the given time
+/// zone. Days with a running entry have no end yet.
+fn day_bounds<Tz: TimeZone>(
+    entries: &[TimeEntry],
+    tz: &Tz,
+) -> BTreeMap<NaiveDate, (DateTime<Tz>, Option<DateTime<Tz>>)> {
+    let mut bounds: BTreeMap<NaiveDate, (DateTime<Tz>, Option<DateTime<Tz>>)> = BTreeMap::new();
+    for entry in entries {
+        let start = entry.start_time.with_timezone(tz);
+        let end = entry.end_time.map(|end| end.with_timezone(tz));
+        bounds
+            .entry(start.date_naive())
+            .and_modify(|(first_start, last_end)| {
+                if start < *first_start {
+                    *first_start = start.clone();
+                }
+                *last_end = match (last_end.take(), end.clone()) {
+                    (Some(last), Some(end)) => Some(last.max(end)),
+                    _ => None,
+                };
+            })
+            .or_insert((start.clone(), end));
+    }
+    bounds
+}
+
+/// Typical start and stop of a work day: the median of each day's first
+/// start and of each day's last stop, days taken in the given time zone.
+/// Days with a running entry have no stop yet and only count for the start.
+pub fn median_day_bounds<Tz: TimeZone>(
+    entries: &[TimeEntry],
+    tz: &Tz,
+) -> (Option<NaiveTime>, Option<NaiveTime>) {
+    let bounds = day_bounds(entries, tz);
+    let starts = bounds
+        .values()
+        .map(|(start, _)| start.num_seconds_from_midnight())
+        .collect();
+    let stops = bounds
+        .values()
+        .filter_map(|(_, end)| end.as_ref())
+        .map(|end| end.num_seconds_from_midnight())
+        .collect();
+    (median_time(starts), median_time(stops))
+}
+
+/// When a day's work began and ended, against the time tracked in between
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayPresence {
+    pub date: NaiveDate,
+    /// Start of the day's first entry
+    pub arrived: DateTime<Utc>,
+    /// End of the day's last entry, `None` while one is running
+    pub left: Option<DateTime<Utc>>,
+    /// From arriving until leaving, or until `now` while an entry runs
+    pub presence_seconds: i64,
+    /// Tracked time of the day, following the rounding rule
+    pub tracked_seconds: i64,
+}
+
+/// Arrival, departure and presence of each day with entries, in date
+/// order. Days are taken in the given time zone, and entries count for the
+/// day they start on.
+pub fn day_presence<Tz: TimeZone>(
+    entries: &[TimeEntry],
+    rounding: &Rounding,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Vec<DayPresence> {
+    let tracked = report::daily_totals(entries, rounding, now, tz);
+    day_bounds(entries, tz)
+        .into_iter()
+        .map(|(date, (arrived, left))| {
+            let arrived = arrived.with_timezone(&Utc);
+            let left = left.map(|left| left.with_timezone(&Utc));
+            DayPresence {
+                date,
+                arrived,
+                left,
+                presence_seconds: (left.unwrap_or(now) - arrived).num_seconds().max(0),
+                tracked_seconds: tracked.get(&date).copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+/// Longest stretch of work without an interruption: consecutive entries
+/// continuing the same task without stopping the timer count as one
+/// session. Returns when it started and its length in seconds.
+pub fn longest_session(entries: &[TimeEntry], now: DateTime<Utc>) -> Option<(DateTime<Utc>, i64)> {
+    let mut sorted: Vec<&TimeEntry> = entries.iter().collect();
+    sorted.sort_by_key(|entry| entry.start_time);
+
+    let mut longest: Option<(DateTime<Utc>, i64)> = None;
+    let mut current: Option<(DateTime<Utc>, i64)> = None;
+    let mut previous: Option<&TimeEntry> = None;
+    for entry in sorted {
+        let seconds = (entry.end_time.unwrap_or(now) - entry.start_time).num_seconds().max(0);
+        current = match (current, previous) {
+            (Some((start, total)), Some(previous)) if !report::is_interruption(previous, entry) => {
+                Some((start, total + seconds))
+            }
+            _ => Some((entry.start_time, seconds)),
+        };
+        if current.map(|(_, total)| total) > longest.map(|(_, total)| total) {
+            longest = current;
+        }
+        previous = Some(entry);
+    }
+    longest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn entry(description: &str, start: (u32, u32, u32), end: Option<(u32, u32, u32)>) -> TimeEntry {
+        let at = |(d, h, m): (u32, u32, u32)| Utc.with_ymd_and_hms(2024, 3, d, h, m, 0).unwrap();
+        TimeEntry {
+            description: description.to_string(),
+            ..test_support::entry(at(start), end.map(at))
+        }
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_weekday_averages_count_days_off() {
+        // Two weeks from Monday, March 4; only the first Monday is tracked
+        let range = ReportRange::new(date(2024, 3, 4), date(2024, 3, 17));
+        let totals = HashMap::from([(date(2024, 3, 4), 8 * 3600), (date(2024, 3, 6), 2 * 3600)]);
+
+        let averages = weekday_averages(&totals, range);
+
+        assert_eq!(averages, [4 * 3600, 0, 3600, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_current_streak() {
+        let days = HashSet::from([date(2024, 3, 2), date(2024, 3, 3), date(2024, 3, 4), date(2024, 2, 28)]);
+
+        assert_eq!(current_streak(&days, date(2024, 3, 4)), 3);
+        // Today isn't tracked yet, the streak up to yesterday still holds
+        assert_eq!(current_streak(&days, date(2024, 3, 5)), 3);
+        assert_eq!(current_streak(&days, date(2024, 3, 6)), 0);
+    }
+
+    #[test]
+    fn test_median_day_bounds() {
+        let entries = vec![
+            entry("a", (4, 9, 0), Some((4, 12, 0))),
+            entry("b", (4, 13, 0), Some((4, 17, 0))),
+            entry("c", (5, 8, 30), Some((5, 18, 0))),
+            entry("d", (6, 10, 0), Some((6, 16, 0))),
+            // Still running, so the 7th has no stop
+            entry("e", (7, 7, 0), None),
+        ];
+
+        let (start, stop) = median_day_bounds(&entries, &Utc);
+
+        // Starts 07:00, 08:30, 09:00, 10:00; stops 16:00, 17:00, 18:00
+        assert_eq!(start, Some(time(8, 45)));
+        assert_eq!(stop, Some(time(17, 0)));
+    }
+
+    #[test]
+    fn test_median_day_bounds_without_entries() {
+        assert_eq!(median_day_bounds(&[], &Utc), (None, None));
+    }
+
+    #[test]
+    fn test_longest_session_joins_continued_entries() {
+        let entries = vec![
+            entry("Write", (4, 9, 0), Some((4, 10, 0))),
+            entry("Write", (4, 10, 0), Some((4, 11, 30))),
+            entry("Email", (4, 11, 30), Some((4, 13, 0))),
+            entry("Write", (4, 14, 0), Some((4, 16, 0))),
+        ];
+
+        let longest = longest_session(&entries, Utc::now());
+
+        assert_eq!(longest, Some((entries[0].start_time, 150 * 60)));
+    }
+
+    #[test]
+    fn test_day_presence() {
+        let entries = vec![
+            entry("Review", (4, 13, 0), Some((4, 17, 30))),
+            entry("Email", (4, 9, 0), Some((4, 10, 0))),
+            entry("Plan", (5, 8, 30), None),
+        ];
+        let now = Utc.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap();
+
+        let days = day_presence(&entries, &Rounding::default(), now, &Utc);
+
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, date(2024, 3, 4));
+        assert_eq!(days[0].arrived, Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap());
+        assert_eq!(days[0].left, Some(Utc.with_ymd_and_hms(2024, 3, 4, 17, 30, 0).unwrap()));
+        assert_eq!(days[0].presence_seconds, 8 * 3600 + 1800);
+        assert_eq!(days[0].tracked_seconds, 5 * 3600 + 1800);
+        assert_eq!(days[1].left, None);
+        assert_eq!(days[1].presence_seconds, 1800);
+    }
+}
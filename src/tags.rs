@@ -0,0 +1,64 @@
+/// Tags of an entry, written as "#tag" words in its description. A tag
+/// must start a word and runs over letters, digits, "-" and "_"; tags are
+/// compared ignoring case, so they are returned lowercased, each once, in
+/// the order they first appear.
+pub fn entry_tags(description: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+
+    for (index, c) in description.char_indices() {
+        let starts_word = previous.is_none_or(char::is_whitespace);
+        previous = Some(c);
+        if c != '#' || !starts_word {
+            continue;
+        }
+
+        let rest = &description[index + 1..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        let tag = rest[..end].to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    tags
+}
+
+/// Returns true when the description has every tag of the filter; an empty
+/// filter matches everything
+pub fn matches_all(description: &str, filter: &[String]) -> bool {
+    if filter.is_empty() {
+        return true;
+    }
+    let tags = entry_tags(description);
+    filter.iter().all(|tag| tags.contains(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_tags() {
+        assert_eq!(
+            entry_tags("Review #Design and #client-a notes, #design again"),
+            vec!["design", "client-a"]
+        );
+        assert_eq!(entry_tags("#meeting"), vec!["meeting"]);
+
+        for text in ["No tags", "Issue#42", "# heading", "C# code"] {
+            assert!(entry_tags(text).is_empty(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_matches_all_tags() {
+        let filter = vec!["design".to_string(), "client".to_string()];
+
+        assert!(matches_all("Mockups #Client #design", &filter));
+        assert!(!matches_all("Mockups #design", &filter));
+        assert!(matches_all("Anything", &[]));
+    }
+}
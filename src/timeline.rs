@@ -0,0 +1,102 @@
+use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
+
+use crate::db::{self, TimeEntry};
+
+/// Tracked part of a day, as fractions of the day from midnight (0.0) to the
+/// next midnight (1.0)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimelineBlock {
+    pub start: f64,
+    pub end: f64,
+    pub project_id: Option<i64>,
+}
+
+/// Position of `time` within `day`, as a fraction of the day
+pub fn day_fraction<Tz: TimeZone>(time: DateTime<Utc>, day: NaiveDate, tz: &Tz) -> f64 {
+    let start = db::local_day_start(day, tz);
+    let end = db::local_day_start(day + Days::new(1), tz);
+    let length = (end - start).num_seconds().max(1) as f64;
+    ((time - start).num_seconds() as f64 / length).clamp(0.0, 1.0)
+}
+
+/// Blocks of the entries tracked on `day`, in the given time zone. Running
+/// entries end at `now`; entries crossing midnight are cut at the day's
+/// bounds, and those outside the day are left out.
+pub fn day_blocks<Tz: TimeZone>(
+    entries: &[TimeEntry],
+    day: NaiveDate,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Vec<TimelineBlock> {
+    let mut blocks: Vec<TimelineBlock> = entries
+        .iter()
+        .map(|entry| TimelineBlock {
+            start: day_fraction(entry.start_time, day, tz),
+            end: day_fraction(entry.end_time.unwrap_or(now), day, tz),
+            project_id: entry.project_id,
+        })
+        .filter(|block| block.end > block.start)
+        .collect();
+    blocks.sort_by(|a, b| a.start.total_cmp(&b.start));
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    fn entry(project_id: Option<i64>, start: (u32, u32), end: Option<(u32, u32)>) -> TimeEntry {
+        let at = |(h, m): (u32, u32)| Utc.with_ymd_and_hms(2024, 3, 4, h, m, 0).unwrap();
+        TimeEntry {
+            project_id,
+            ..test_support::entry(at(start), end.map(at))
+        }
+    }
+
+    fn day() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()
+    }
+
+    #[test]
+    fn test_blocks_are_fractions_of_the_day() {
+        let entries = vec![
+            entry(Some(2), (12, 0), Some((18, 0))),
+            entry(Some(1), (6, 0), Some((12, 0))),
+        ];
+
+        let blocks = day_blocks(&entries, day(), Utc::now(), &Utc);
+
+        assert_eq!(
+            blocks,
+            vec![
+                TimelineBlock { start: 0.25, end: 0.5, project_id: Some(1) },
+                TimelineBlock { start: 0.5, end: 0.75, project_id: Some(2) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_running_entry_ends_now() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 4, 18, 0, 0).unwrap();
+        let entries = vec![entry(None, (12, 0), None)];
+
+        let blocks = day_blocks(&entries, day(), now, &Utc);
+
+        assert_eq!(blocks, vec![TimelineBlock { start: 0.5, end: 0.75, project_id: None }]);
+    }
+
+    #[test]
+    fn test_blocks_are_cut_at_midnight() {
+        let mut overnight = entry(None, (22, 0), None);
+        overnight.start_time = overnight.start_time - Days::new(1);
+        overnight.end_time = Some(Utc.with_ymd_and_hms(2024, 3, 4, 6, 0, 0).unwrap());
+        let mut yesterday = entry(None, (8, 0), Some((9, 0)));
+        yesterday.start_time = yesterday.start_time - Days::new(1);
+        yesterday.end_time = yesterday.end_time.map(|end| end - Days::new(1));
+
+        let blocks = day_blocks(&[overnight, yesterday], day(), Utc::now(), &Utc);
+
+        assert_eq!(blocks, vec![TimelineBlock { start: 0.0, end: 0.25, project_id: None }]);
+    }
+}
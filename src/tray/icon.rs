@@ -0,0 +1,152 @@
+/// Side of the square tray icon in pixels; panels scale it to their size
+pub const ICON_SIZE: usize = 64;
+
+/// Background of the icon while the timer runs, the red of a recording
+const RUNNING_COLOR: [u8; 3] = [0xe0, 0x1b, 0x24];
+const TEXT_COLOR: [u8; 3] = [0xff, 0xff, 0xff];
+const CORNER_RADIUS: f32 = 12.0;
+/// Space kept free around the text
+const MARGIN: usize = 4;
+/// Largest scale of the font, so short labels don't fill the whole icon
+const MAX_SCALE: usize = 4;
+
+/// Digits of a 3×5 pixel font, one row per byte with the leftmost pixel in
+/// the highest of the three bits
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const COLON: [u8; 5] = [0b0, 0b1, 0b0, 0b1, 0b0];
+
+/// An icon drawn at runtime, in the ARGB32 format with network byte order
+/// that StatusNotifierItem pixmaps use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IconImage {
+    pub width: i32,
+    pub height: i32,
+    pub data: Vec<u8>,
+}
+
+/// Hours and minutes shown on the icon, such as "1:05"
+pub fn elapsed_label(seconds: i64) -> String {
+    let minutes = seconds.max(0) / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Width in pixels and rows of a character of the font; other characters
+/// than digits and colons are left out
+fn glyph(c: char) -> Option<(usize, &'static [u8; 5])> {
+    match c {
+        ':' => Some((1, &COLON)),
+        _ => c.to_digit(10).map(|digit| (3, &DIGITS[digit as usize])),
+    }
+}
+
+/// Whether the pixel at `x`, `y` lies on the rounded square filling the icon
+fn on_background(x: usize, y: usize) -> bool {
+    let (x, y) = (x as f32 + 0.5, y as f32 + 0.5);
+    let far = ICON_SIZE as f32 - CORNER_RADIUS;
+    let (dx, dy) = (x - x.clamp(CORNER_RADIUS, far), y - y.clamp(CORNER_RADIUS, far));
+    dx * dx + dy * dy <= CORNER_RADIUS * CORNER_RADIUS
+}
+
+/// Draws `label` in white, as large as fits, on a rounded square of the
+/// running color
+pub fn render_label(label: &str) -> IconImage {
+    let mut data = vec![0; ICON_SIZE * ICON_SIZE * 4];
+    let mut paint = |x: usize, y: usize, [r, g, b]: [u8; 3]| {
+        let offset = (y * ICON_SIZE + x) * 4;
+        data[offset..offset + 4].copy_from_slice(&[0xff, r, g, b]);
+    };
+
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            if on_background(x, y) {
+                paint(x, y, RUNNING_COLOR);
+            }
+        }
+    }
+
+    let glyphs: Vec<_> = label.chars().filter_map(glyph).collect();
+    let text_width = glyphs.iter().map(|(width, _)| width + 1).sum::<usize>().saturating_sub(1);
+    if let Some(scale) = (ICON_SIZE - 2 * MARGIN).checked_div(text_width) {
+        let scale = scale.clamp(1, MAX_SCALE);
+        let mut left = ICON_SIZE.saturating_sub(text_width * scale) / 2;
+        let top = (ICON_SIZE - 5 * scale) / 2;
+        for (width, rows) in glyphs {
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..width {
+                    if bits >> (width - 1 - column) & 1 == 0 {
+                        continue;
+                    }
+                    for y in 0..scale {
+                        for x in 0..scale {
+                            let (x, y) = (left + column * scale + x, top + row * scale + y);
+                            if x < ICON_SIZE {
+                                paint(x, y, TEXT_COLOR);
+                            }
+                        }
+                    }
+                }
+            }
+            left += (width + 1) * scale;
+        }
+    }
+
+    IconImage {
+        width: ICON_SIZE as i32,
+        height: ICON_SIZE as i32,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(icon: &IconImage, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * ICON_SIZE + x) * 4;
+        icon.data[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_elapsed_label() {
+        assert_eq!(elapsed_label(0), "0:00");
+        assert_eq!(elapsed_label(65 * 60 + 59), "1:05");
+        assert_eq!(elapsed_label(12 * 3600 + 34 * 60), "12:34");
+    }
+
+    #[test]
+    fn test_render_label() {
+        let icon = render_label("1:05");
+        assert_eq!((icon.width, icon.height), (ICON_SIZE as i32, ICON_SIZE as i32));
+        assert_eq!(icon.data.len(), ICON_SIZE * ICON_SIZE * 4);
+
+        // Rounded corners stay transparent around the colored square
+        assert_eq!(pixel(&icon, 0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&icon, 2, ICON_SIZE / 2), [0xff, 0xe0, 0x1b, 0x24]);
+
+        // "1:05" is 13 pixels wide, drawn at the largest scale of 4 and
+        // centered: the top of the "1" is its middle column
+        let (left, top) = ((ICON_SIZE - 13 * 4) / 2, (ICON_SIZE - 5 * 4) / 2);
+        assert_eq!(pixel(&icon, left + 4, top), [0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(pixel(&icon, left, top), [0xff, 0xe0, 0x1b, 0x24]);
+    }
+
+    #[test]
+    fn test_long_labels_fit() {
+        let icon = render_label("123:45");
+        for y in 0..ICON_SIZE {
+            assert_ne!(pixel(&icon, 0, y), [0xff, 0xff, 0xff, 0xff]);
+            assert_ne!(pixel(&icon, ICON_SIZE - 1, y), [0xff, 0xff, 0xff, 0xff]);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::sync::{Arc, Mutex};
+
+use crate::settings::TrayClickAction;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod icon;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod sni;
+
+/// Shared state for the system tray
+pub struct TrayState {
+    pub is_running: bool,
+    pub elapsed_seconds: i64,
+    pub elapsed_time: String,
+    pub description: String,
+    /// Time tracked today including the running entry, formatted
+    pub today_total: String,
+    pub click_action: TrayClickAction,
+    /// Pinned tasks offered in the menu, as their id and label
+    pub pinned_tasks: Vec<(i64, String)>,
+    /// Recently tracked tasks offered in the "Recent" submenu
+    pub recent_entries: Vec<RecentEntry>,
+}
+
+/// A recently tracked task the tray menu can start again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentEntry {
+    pub label: String,
+    pub description: String,
+    pub project_id: Option<i64>,
+}
+
+impl Default for TrayState {
+    fn default() -> Self {
+        Self {
+            is_running: false,
+            elapsed_seconds: 0,
+            elapsed_time: "00:00:00".to_string(),
+            description: String::new(),
+            today_total: String::new(),
+            click_action: TrayClickAction::default(),
+            pinned_tasks: Vec::new(),
+            recent_entries: Vec::new(),
+        }
+    }
+}
+
+/// Callback type for tray actions
+pub type TrayCallback = Box<dyn Fn() + Send + Sync>;
+
+/// Callback for logging the block that just ended, given its length in minutes
+pub type TrayLogCallback = Box<dyn Fn(u32) + Send + Sync>;
+
+/// Callback for starting the pinned task with the given id
+pub type TrayPinnedCallback = Box<dyn Fn(i64) + Send + Sync>;
+
+/// Callback for starting a recent task, given its description and project
+pub type TrayRecentCallback = Box<dyn Fn(String, Option<i64>) + Send + Sync>;
+
+/// How many recent tasks the tray menu offers
+pub const RECENT_ENTRIES_LIMIT: usize = 5;
+
+/// Lengths offered for logging the block that just ended, in minutes
+pub const LOG_RECENT_MINUTES: [u32; 3] = [15, 30, 60];
+
+/// What the tray's icon and menu items call back, on the backend's thread
+pub struct TrayCallbacks {
+    pub toggle_timer: TrayCallback,
+    pub show_window: TrayCallback,
+    pub toggle_window: TrayCallback,
+    pub quick_start: TrayCallback,
+    pub continue_last: TrayCallback,
+    pub log_recent: TrayLogCallback,
+    pub start_pinned: TrayPinnedCallback,
+    pub start_recent: TrayRecentCallback,
+    pub quit: TrayCallback,
+}
+
+/// A platform's tray icon. It shows the shared `TrayState` and its menu
+/// offers the actions in `TrayCallbacks`.
+pub trait TrayBackend: Send {
+    /// Shows the icon, running the backend on its own thread
+    fn spawn(&mut self, state: Arc<Mutex<TrayState>>, callbacks: TrayCallbacks);
+
+    /// Redraws the icon, tooltip and menu after the state changed
+    fn refresh(&self);
+
+    /// Whether the desktop has something to show the icon in. Checked
+    /// before spawning, since an icon without a host can't be clicked.
+    fn host_available(&self) -> bool;
+}
+
+/// Why the tray icon isn't shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayUnavailable {
+    /// This platform has no tray backend
+    Unsupported,
+    /// The desktop shows no tray icons, such as GNOME without an extension
+    NoHost,
+}
+
+/// The tray backend of the platform the app was built for. Linux and the
+/// BSDs use a StatusNotifierItem. Windows (a notification area icon) and
+/// macOS (a status bar item) have no backend yet, so the app runs there
+/// without a tray: closing quits and starting at login shows the window.
+fn platform_backend() -> Option<Box<dyn TrayBackend>> {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    return Some(Box::new(sni::SniBackend::default()));
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    return None;
+}
+
+/// Manages the system tray service
+pub struct TrayManager {
+    state: Arc<Mutex<TrayState>>,
+    backend: Option<Box<dyn TrayBackend>>,
+    started: bool,
+}
+
+impl TrayManager {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(TrayState::default())),
+            backend: platform_backend(),
+            started: false,
+        }
+    }
+
+    /// Starts the tray service with the given callbacks. Fails when this
+    /// platform or desktop has no tray, so the app has no icon to hide into.
+    pub fn start(&mut self, callbacks: TrayCallbacks) -> Result<(), TrayUnavailable> {
+        let Some(ref mut backend) = self.backend else {
+            return Err(TrayUnavailable::Unsupported);
+        };
+        if !backend.host_available() {
+            return Err(TrayUnavailable::NoHost);
+        }
+        backend.spawn(self.state.clone(), callbacks);
+        self.started = true;
+        Ok(())
+    }
+
+    /// Whether the tray icon was started, so the window can hide into it
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Sets what a primary click on the icon does
+    pub fn set_click_action(&self, click_action: TrayClickAction) {
+        self.state.lock().unwrap().click_action = click_action;
+    }
+
+    /// Sets the pinned tasks offered in the menu, as their id and label
+    pub fn set_pinned_tasks(&self, pinned_tasks: Vec<(i64, String)>) {
+        self.state.lock().unwrap().pinned_tasks = pinned_tasks;
+        if let Some(ref backend) = self.backend {
+            backend.refresh();
+        }
+    }
+
+    /// Sets the recent tasks offered in the menu, refreshing it only when
+    /// they changed since entries are saved far more often
+    pub fn set_recent_entries(&self, recent_entries: Vec<RecentEntry>) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.recent_entries == recent_entries {
+                return;
+            }
+            state.recent_entries = recent_entries;
+        }
+        if let Some(ref backend) = self.backend {
+            backend.refresh();
+        }
+    }
+
+    /// Updates the tray state and refreshes the tray
+    pub fn update(
+        &self,
+        is_running: bool,
+        elapsed_seconds: i64,
+        elapsed_time: &str,
+        description: &str,
+        today_total: &str,
+    ) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.is_running = is_running;
+            state.elapsed_seconds = elapsed_seconds;
+            state.elapsed_time = elapsed_time.to_string();
+            state.description = description.to_string();
+            state.today_total = today_total.to_string();
+        }
+
+        // Request tray update
+        if let Some(ref backend) = self.backend {
+            backend.refresh();
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+use ksni::{self, Handle, Tray, TrayService};
+use std::sync::{Arc, Mutex};
+
+use super::icon;
+use super::{
+    TrayBackend, TrayCallback, TrayCallbacks, TrayLogCallback, TrayPinnedCallback, TrayRecentCallback, TrayState,
+    LOG_RECENT_MINUTES,
+};
+use crate::settings::TrayClickAction;
+
+/// Tray icon shown as a StatusNotifierItem over D-Bus, as KDE Plasma,
+/// GNOME with the AppIndicator extension and most other panels expect
+pub struct TimeTrackingTray {
+    state: Arc<Mutex<TrayState>>,
+    on_toggle_timer: Option<Arc<TrayCallback>>,
+    on_show_window: Option<Arc<TrayCallback>>,
+    on_toggle_window: Option<Arc<TrayCallback>>,
+    on_quick_start: Option<Arc<TrayCallback>>,
+    on_continue_last: Option<Arc<TrayCallback>>,
+    on_log_recent: Option<Arc<TrayLogCallback>>,
+    on_start_pinned: Option<Arc<TrayPinnedCallback>>,
+    on_start_recent: Option<Arc<TrayRecentCallback>>,
+    on_quit: Option<Arc<TrayCallback>>,
+}
+
+impl TimeTrackingTray {
+    pub fn new(state: Arc<Mutex<TrayState>>) -> Self {
+        Self {
+            state,
+            on_toggle_timer: None,
+            on_show_window: None,
+            on_toggle_window: None,
+            on_quick_start: None,
+            on_continue_last: None,
+            on_log_recent: None,
+            on_start_pinned: None,
+            on_start_recent: None,
+            on_quit: None,
+        }
+    }
+
+    pub fn with_toggle_timer(mut self, callback: TrayCallback) -> Self {
+        self.on_toggle_timer = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_show_window(mut self, callback: TrayCallback) -> Self {
+        self.on_show_window = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_toggle_window(mut self, callback: TrayCallback) -> Self {
+        self.on_toggle_window = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_quick_start(mut self, callback: TrayCallback) -> Self {
+        self.on_quick_start = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_continue_last(mut self, callback: TrayCallback) -> Self {
+        self.on_continue_last = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_log_recent(mut self, callback: TrayLogCallback) -> Self {
+        self.on_log_recent = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_start_pinned(mut self, callback: TrayPinnedCallback) -> Self {
+        self.on_start_pinned = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_start_recent(mut self, callback: TrayRecentCallback) -> Self {
+        self.on_start_recent = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_quit(mut self, callback: TrayCallback) -> Self {
+        self.on_quit = Some(Arc::new(callback));
+        self
+    }
+}
+
+impl Tray for TimeTrackingTray {
+    /// Primary click on the icon, doing what the user picked in preferences
+    fn activate(&mut self, _x: i32, _y: i32) {
+        let click_action = self.state.lock().unwrap().click_action;
+        let callback = match click_action {
+            TrayClickAction::ToggleWindow => &self.on_toggle_window,
+            TrayClickAction::ToggleTimer => &self.on_toggle_timer,
+            TrayClickAction::QuickStart => &self.on_quick_start,
+        };
+        if let Some(callback) = callback {
+            callback();
+        }
+    }
+
+    fn icon_name(&self) -> String {
+        let state = self.state.lock().unwrap();
+        if state.is_running {
+            // Hosts prefer a named icon, so none is given to show the pixmap
+            String::new()
+        } else {
+            // Use a timer/clock icon when stopped
+            "appointment-soon".to_string()
+        }
+    }
+
+    /// While the timer runs, the icon shows the elapsed hours and minutes
+    fn icon_pixmap(&self) -> Vec<ksni::Icon> {
+        let state = self.state.lock().unwrap();
+        if !state.is_running {
+            return Vec::new();
+        }
+        let image = icon::render_label(&icon::elapsed_label(state.elapsed_seconds));
+        vec![ksni::Icon {
+            width: image.width,
+            height: image.height,
+            data: image.data,
+        }]
+    }
+
+    fn title(&self) -> String {
+        "Time Tracking".to_string()
+    }
+
+    fn tool_tip(&self) -> ksni::ToolTip {
+        let state = self.state.lock().unwrap();
+        let description = if state.is_running {
+            if state.description.is_empty() {
+                format!("Running: {}", state.elapsed_time)
+            } else {
+                format!("{}: {}", state.description, state.elapsed_time)
+            }
+        } else {
+            "Timer stopped".to_string()
+        };
+        let description = format!("{}\nToday: {}", description, state.today_total);
+
+        ksni::ToolTip {
+            icon_name: String::new(),
+            icon_pixmap: Vec::new(),
+            title: "Time Tracking".to_string(),
+            description,
+        }
+    }
+
+    fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+        use ksni::menu::*;
+
+        let state = self.state.lock().unwrap();
+        let is_running = state.is_running;
+        let elapsed = state.elapsed_time.clone();
+        let description = state.description.clone();
+        let today_total = state.today_total.clone();
+        let pinned_tasks = state.pinned_tasks.clone();
+        let recent_entries = state.recent_entries.clone();
+        drop(state);
+
+        let mut items: Vec<ksni::MenuItem<Self>> = Vec::new();
+
+        // Status items (non-clickable)
+        if is_running {
+            let status_text = if description.is_empty() {
+                format!("Timer: {}", elapsed)
+            } else {
+                format!("{}: {}", description, elapsed)
+            };
+            items.push(StandardItem {
+                label: status_text,
+                enabled: false,
+                ..Default::default()
+            }.into());
+        }
+        items.push(StandardItem {
+            label: format!("Today: {}", today_total),
+            enabled: false,
+            ..Default::default()
+        }.into());
+        items.push(MenuItem::Separator);
+
+        // Start/Stop timer
+        let toggle_label = if is_running { "Stop Timer" } else { "Start Timer" };
+        items.push(StandardItem {
+            label: toggle_label.to_string(),
+            icon_name: if is_running {
+                "media-playback-stop".to_string()
+            } else {
+                "media-playback-start".to_string()
+            },
+            activate: Box::new(|tray: &mut Self| {
+                if let Some(ref callback) = tray.on_toggle_timer {
+                    callback();
+                }
+            }),
+            ..Default::default()
+        }.into());
+
+        // Restart whatever was stopped last, switching back to it if running
+        items.push(StandardItem {
+            label: "Continue Last Entry".to_string(),
+            icon_name: "media-seek-backward".to_string(),
+            activate: Box::new(|tray: &mut Self| {
+                if let Some(ref callback) = tray.on_continue_last {
+                    callback();
+                }
+            }),
+            ..Default::default()
+        }.into());
+
+        // Pinned tasks start with one click, stopping a running timer
+        for (id, label) in pinned_tasks {
+            items.push(StandardItem {
+                label,
+                icon_name: "view-pin".to_string(),
+                activate: Box::new(move |tray: &mut Self| {
+                    if let Some(ref callback) = tray.on_start_pinned {
+                        callback(id);
+                    }
+                }),
+                ..Default::default()
+            }.into());
+        }
+
+        // Recent tasks start again with their description and project
+        if !recent_entries.is_empty() {
+            let recent_items = recent_entries
+                .into_iter()
+                .map(|recent| {
+                    StandardItem {
+                        label: recent.label,
+                        activate: Box::new(move |tray: &mut Self| {
+                            if let Some(ref callback) = tray.on_start_recent {
+                                callback(recent.description.clone(), recent.project_id);
+                            }
+                        }),
+                        ..Default::default()
+                    }
+                    .into()
+                })
+                .collect();
+            items.push(SubMenu {
+                label: "Recent".to_string(),
+                icon_name: "document-open-recent".to_string(),
+                submenu: recent_items,
+                ..Default::default()
+            }.into());
+        }
+
+        // Log a block that just ended, such as a phone call, without the timer
+        let log_items = LOG_RECENT_MINUTES
+            .iter()
+            .map(|&minutes| {
+                StandardItem {
+                    label: format!("{} Minutes…", minutes),
+                    activate: Box::new(move |tray: &mut Self| {
+                        if let Some(ref callback) = tray.on_log_recent {
+                            callback(minutes);
+                        }
+                    }),
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect();
+        items.push(SubMenu {
+            label: "Log Last".to_string(),
+            icon_name: "document-new".to_string(),
+            submenu: log_items,
+            ..Default::default()
+        }.into());
+
+        items.push(MenuItem::Separator);
+
+        // Show window
+        items.push(StandardItem {
+            label: "Show Window".to_string(),
+            icon_name: "view-restore".to_string(),
+            activate: Box::new(|tray: &mut Self| {
+                if let Some(ref callback) = tray.on_show_window {
+                    callback();
+                }
+            }),
+            ..Default::default()
+        }.into());
+
+        items.push(MenuItem::Separator);
+
+        // Quit
+        items.push(StandardItem {
+            label: "Quit".to_string(),
+            icon_name: "application-exit".to_string(),
+            activate: Box::new(|tray: &mut Self| {
+                if let Some(ref callback) = tray.on_quit {
+                    callback();
+                }
+            }),
+            ..Default::default()
+        }.into());
+
+        items
+    }
+
+    fn id(&self) -> String {
+        "time-tracking".to_string()
+    }
+
+    fn category(&self) -> ksni::Category {
+        ksni::Category::ApplicationStatus
+    }
+}
+
+/// Bus name of the watcher that hosts such as panels register with
+const WATCHER_BUS_NAME: &str = "org.kde.StatusNotifierWatcher";
+const WATCHER_OBJECT_PATH: &str = "/StatusNotifierWatcher";
+const WATCHER_TIMEOUT_MS: i32 = 1000;
+
+/// Asks the watcher whether a host is registered. Without a watcher, as on
+/// GNOME without the AppIndicator extension, the call fails.
+fn host_registered() -> Result<bool, glib::Error> {
+    let connection = gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>)?;
+    let reply = connection.call_sync(
+        Some(WATCHER_BUS_NAME),
+        WATCHER_OBJECT_PATH,
+        "org.freedesktop.DBus.Properties",
+        "Get",
+        Some(&(WATCHER_BUS_NAME, "IsStatusNotifierHostRegistered").to_variant()),
+        Some(glib::VariantTy::new("(v)").unwrap()),
+        gio::DBusCallFlags::NONE,
+        WATCHER_TIMEOUT_MS,
+        None::<&gio::Cancellable>,
+    )?;
+    Ok(reply
+        .child_value(0)
+        .as_variant()
+        .and_then(|value| value.get::<bool>())
+        .unwrap_or(false))
+}
+
+/// The StatusNotifierItem tray backend
+#[derive(Default)]
+pub struct SniBackend {
+    handle: Option<Handle<TimeTrackingTray>>,
+}
+
+impl TrayBackend for SniBackend {
+    fn spawn(&mut self, state: Arc<Mutex<TrayState>>, callbacks: TrayCallbacks) {
+        let tray = TimeTrackingTray::new(state)
+            .with_toggle_timer(callbacks.toggle_timer)
+            .with_show_window(callbacks.show_window)
+            .with_toggle_window(callbacks.toggle_window)
+            .with_quick_start(callbacks.quick_start)
+            .with_continue_last(callbacks.continue_last)
+            .with_log_recent(callbacks.log_recent)
+            .with_start_pinned(callbacks.start_pinned)
+            .with_start_recent(callbacks.start_recent)
+            .with_quit(callbacks.quit);
+
+        let service = TrayService::new(tray);
+        self.handle = Some(service.handle());
+        service.spawn();
+    }
+
+    fn refresh(&self) {
+        if let Some(ref handle) = self.handle {
+            handle.update(|_| {});
+        }
+    }
+
+    fn host_available(&self) -> bool {
+        host_registered().unwrap_or(false)
+    }
+}
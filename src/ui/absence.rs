@@ -0,0 +1,250 @@
+use adw::prelude::*;
+use chrono::Local;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{compat, refresh_view, AppState};
+use crate::absence::{self, AbsenceReason};
+use crate::db;
+use crate::list_diff;
+
+/// Fingerprint of what an absence row displays, so unchanged rows are kept
+pub fn absence_row_fingerprint(absence: &db::Absence, compact: bool) -> u64 {
+    list_diff::fingerprint(&(
+        absence.id,
+        absence.reason,
+        &absence.note,
+        absence.start_time,
+        absence.end_time,
+        compact,
+    ))
+}
+
+/// Creates a Today list row for an absence: its reason and note, how long it
+/// lasted and a delete button. Dimmed, since it isn't worked time.
+pub fn create_absence_row(
+    absence: &db::Absence,
+    state: Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+) -> gtk::ListBoxRow {
+    let row = gtk::ListBoxRow::builder()
+        .selectable(false)
+        .activatable(false)
+        .css_classes(["absence-row"])
+        .build();
+
+    let compact = state.borrow().settings.compact;
+    let row_padding = if compact { 3 } else { 8 };
+
+    let hbox = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(12)
+        .margin_top(row_padding)
+        .margin_bottom(row_padding)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+
+    hbox.append(
+        &gtk::Image::builder()
+            .icon_name("x-office-calendar-symbolic")
+            .valign(gtk::Align::Center)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+
+    let content_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(2)
+        .hexpand(true)
+        .build();
+    content_box.append(
+        &gtk::Label::builder()
+            .label(format!("Away · {}", absence.reason.label()))
+            .halign(gtk::Align::Start)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+    if !absence.note.is_empty() {
+        content_box.append(
+            &gtk::Label::builder()
+                .label(&absence.note)
+                .halign(gtk::Align::Start)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .css_classes(["dim-label", "caption"])
+                .build(),
+        );
+    }
+    hbox.append(&content_box);
+
+    let time_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(2)
+        .halign(gtk::Align::End)
+        .build();
+    let seconds = (absence.end_time - absence.start_time).num_seconds().max(0);
+    time_box.append(
+        &gtk::Label::builder()
+            .label(state.borrow().settings.duration_format.format(seconds))
+            .halign(gtk::Align::End)
+            .css_classes(["monospace", "dim-label"])
+            .build(),
+    );
+    time_box.append(
+        &gtk::Label::builder()
+            .label(format!(
+                "{} - {}",
+                absence.start_time.with_timezone(&Local).format("%H:%M"),
+                absence.end_time.with_timezone(&Local).format("%H:%M")
+            ))
+            .halign(gtk::Align::End)
+            .css_classes(["dim-label", "caption"])
+            .visible(!compact)
+            .build(),
+    );
+    hbox.append(&time_box);
+
+    let delete_button = gtk::Button::builder()
+        .icon_name("user-trash-symbolic")
+        .tooltip_text("Delete this absence")
+        .valign(gtk::Align::Center)
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+    let absence_id = absence.id;
+    let window_for_delete = window.clone();
+    delete_button.connect_clicked(move |_| {
+        let state = state.clone();
+        let window = window_for_delete.clone();
+        compat::confirm_destructive(
+            &window_for_delete,
+            "Delete Absence?",
+            "The absence will be removed from the day.",
+            "Delete",
+            move || {
+                if let Err(e) = db::delete_absence(&state.borrow().db_conn, absence_id) {
+                    state.borrow().show_error(&format!("Failed to delete absence: {}", e));
+                }
+                refresh_view(state.clone(), &window);
+            },
+        );
+    });
+    hbox.append(&delete_button);
+
+    row.set_child(Some(&hbox));
+    row
+}
+
+/// Opens a dialog recording a part of today spent away from work, such as a
+/// doctor's visit, with a reason and an optional note
+pub fn show_add_absence_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow) {
+    let dialog = adw::Window::builder()
+        .title("Add Absence")
+        .modal(true)
+        .transient_for(parent)
+        .default_width(360)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(18)
+        .margin_bottom(18)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+    content.append(
+        &gtk::Label::builder()
+            .label("Time away today is shown with your entries but not counted as work.")
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+
+    let reason_labels: Vec<&str> = AbsenceReason::ALL.iter().map(|r| r.label()).collect();
+    let reason_dropdown = gtk::DropDown::from_strings(&reason_labels);
+    reason_dropdown.set_tooltip_text(Some("Reason"));
+    content.append(&reason_dropdown);
+
+    let note_entry = gtk::Entry::builder().placeholder_text("Note (optional)").build();
+    content.append(&note_entry);
+
+    let times_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .homogeneous(true)
+        .build();
+    let start_entry = gtk::Entry::builder().placeholder_text("From, e.g. 9:00").build();
+    let end_entry = gtk::Entry::builder().placeholder_text("To, e.g. 10:30").build();
+    times_box.append(&start_entry);
+    times_box.append(&end_entry);
+    content.append(&times_box);
+
+    let error_label = gtk::Label::builder()
+        .xalign(0.0)
+        .wrap(true)
+        .visible(false)
+        .css_classes(["error"])
+        .build();
+    content.append(&error_label);
+
+    let buttons_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .build();
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let add_button = gtk::Button::builder()
+        .label("Add")
+        .css_classes(["suggested-action"])
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&add_button);
+    content.append(&buttons_box);
+
+    dialog.set_content(Some(&content));
+
+    let dialog_for_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_for_cancel.close());
+
+    let dialog_for_add = dialog.clone();
+    let parent = parent.clone();
+    add_button.connect_clicked(move |_| {
+        let today = Local::now().date_naive();
+        let (start_time, end_time) = match absence::parse_time_range(today, &start_entry.text(), &end_entry.text()) {
+            Ok(times) => times,
+            Err(e) => {
+                error_label.set_label(&e);
+                error_label.set_visible(true);
+                return;
+            }
+        };
+        let reason = AbsenceReason::ALL
+            .get(reason_dropdown.selected() as usize)
+            .copied()
+            .unwrap_or_default();
+
+        let result = db::create_absence(
+            &state.borrow().db_conn,
+            reason,
+            note_entry.text().trim(),
+            start_time,
+            end_time,
+        );
+        match result {
+            Ok(_) => {
+                dialog_for_add.close();
+                refresh_view(state.clone(), &parent);
+            }
+            Err(e) => {
+                error_label.set_label(&format!("Failed to add absence: {}", e));
+                error_label.set_visible(true);
+            }
+        }
+    });
+
+    dialog.present();
+}
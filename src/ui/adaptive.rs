@@ -0,0 +1,122 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+
+use super::compat::{self, WidthCondition};
+
+/// Window width below which the timer and the entries become separate pages
+const NARROW_WIDTH_SP: f64 = 380.0;
+
+/// Window width from which the project sidebar is shown next to the column
+const WIDE_WIDTH_SP: f64 = 720.0;
+
+/// Builds the main layout below the header bar. By default the timer sits
+/// above the entries in a single column. Wide windows add the project sidebar
+/// to the left, and on narrow screens (Linux phones) a breakpoint splits the
+/// column into two pages switched from a bottom bar.
+pub fn create_adaptive_layout(
+    window: &adw::ApplicationWindow,
+    timer_page: &gtk::Box,
+    entries_page: &gtk::Box,
+    sidebar: &gtk::Box,
+) -> gtk::Box {
+    let layout = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .vexpand(true)
+        .build();
+
+    // Single column: timer, separator, then the entries
+    let column = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .vexpand(true)
+        .build();
+    let separator = gtk::Separator::new(gtk::Orientation::Horizontal);
+    separator.set_margin_top(10);
+    column.append(timer_page);
+    column.append(&separator);
+    column.append(entries_page);
+
+    // Sidebar and column side by side, the sidebar is only visible on wide windows
+    let panes = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .vexpand(true)
+        .build();
+    let sidebar_separator = gtk::Separator::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .visible(false)
+        .build();
+    column.set_hexpand(true);
+    panes.append(sidebar);
+    panes.append(&sidebar_separator);
+    panes.append(&column);
+    layout.append(&panes);
+
+    let sidebar_for_apply = sidebar.clone();
+    let sidebar_separator_for_apply = sidebar_separator.clone();
+    let sidebar_for_unapply = sidebar.clone();
+    compat::add_width_breakpoint(
+        window,
+        WidthCondition::MinWidth(WIDE_WIDTH_SP),
+        move || {
+            sidebar_for_apply.set_visible(true);
+            sidebar_separator_for_apply.set_visible(true);
+        },
+        move || {
+            sidebar_for_unapply.set_visible(false);
+            sidebar_separator.set_visible(false);
+        },
+    );
+
+    // Narrow layout: one page at a time with a bottom switcher bar
+    let view_stack = adw::ViewStack::builder().vexpand(true).build();
+    let switcher_bar = adw::ViewSwitcherBar::builder()
+        .stack(&view_stack)
+        .reveal(true)
+        .build();
+
+    let layout_for_apply = layout.clone();
+    let column_for_apply = column.clone();
+    let panes_for_apply = panes.clone();
+    let view_stack_for_apply = view_stack.clone();
+    let switcher_bar_for_apply = switcher_bar.clone();
+    let timer_page_for_apply = timer_page.clone();
+    let entries_page_for_apply = entries_page.clone();
+    let apply = move || {
+        column_for_apply.remove(&timer_page_for_apply);
+        column_for_apply.remove(&entries_page_for_apply);
+        layout_for_apply.remove(&panes_for_apply);
+
+        compat::add_view_page(
+            &view_stack_for_apply,
+            &timer_page_for_apply,
+            "timer",
+            "Timer",
+            "alarm-symbolic",
+        );
+        compat::add_view_page(
+            &view_stack_for_apply,
+            &entries_page_for_apply,
+            "entries",
+            "Entries",
+            "view-list-symbolic",
+        );
+        layout_for_apply.append(&view_stack_for_apply);
+        layout_for_apply.append(&switcher_bar_for_apply);
+    };
+
+    let layout_for_unapply = layout.clone();
+    let timer_page_for_unapply = timer_page.clone();
+    let entries_page_for_unapply = entries_page.clone();
+    let unapply = move || {
+        view_stack.remove(&timer_page_for_unapply);
+        view_stack.remove(&entries_page_for_unapply);
+        layout_for_unapply.remove(&view_stack);
+        layout_for_unapply.remove(&switcher_bar);
+
+        column.prepend(&timer_page_for_unapply);
+        column.append(&entries_page_for_unapply);
+        layout_for_unapply.append(&panes);
+    };
+
+    compat::add_width_breakpoint(window, WidthCondition::MaxWidth(NARROW_WIDTH_SP), apply, unapply);
+    layout
+}
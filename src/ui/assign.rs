@@ -0,0 +1,172 @@
+use adw::prelude::*;
+use chrono::Local;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+use crate::db;
+
+/// Matching entries listed in the preview before assigning them
+const PREVIEW_SAMPLES: usize = 5;
+
+/// Describes what assigning would change: how many entries match and a few
+/// of them, newest first
+fn preview_text(entries: &[db::TimeEntry]) -> String {
+    if entries.is_empty() {
+        return "No entries without a project match.".to_string();
+    }
+
+    let mut text = if entries.len() == 1 {
+        "1 entry without a project matches:".to_string()
+    } else {
+        format!("{} entries without a project match:", entries.len())
+    };
+    for entry in entries.iter().take(PREVIEW_SAMPLES) {
+        text.push_str(&format!(
+            "\n{}  {}",
+            entry.start_time.with_timezone(&Local).format("%b %-d, %Y"),
+            entry.description
+        ));
+    }
+    if entries.len() > PREVIEW_SAMPLES {
+        text.push_str(&format!("\n…and {} more", entries.len() - PREVIEW_SAMPLES));
+    }
+    text
+}
+
+/// Opens a dialog moving past entries without a project whose description
+/// contains a keyword to `project`, previewing them first. The change is
+/// made in one transaction and can be undone from the toast that follows.
+pub fn show_assign_dialog(
+    state: Rc<RefCell<AppState>>,
+    parent: &impl IsA<gtk::Window>,
+    project: &db::Project,
+) {
+    let dialog = adw::Window::builder()
+        .title("Assign Matching Entries")
+        .modal(true)
+        .transient_for(parent)
+        .default_width(380)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(18)
+        .margin_bottom(18)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+    content.append(
+        &gtk::Label::builder()
+            .label(format!(
+                "Past entries without a project whose description contains the keyword are moved to {}.",
+                project.name
+            ))
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+
+    let keyword_entry = gtk::Entry::builder().placeholder_text("Keyword, e.g. standup").build();
+    content.append(&keyword_entry);
+
+    let preview_label = gtk::Label::builder()
+        .xalign(0.0)
+        .wrap(true)
+        .selectable(true)
+        .visible(false)
+        .build();
+    content.append(&preview_label);
+
+    let buttons_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .build();
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let assign_button = gtk::Button::builder()
+        .label("Assign")
+        .sensitive(false)
+        .css_classes(["suggested-action"])
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&assign_button);
+    content.append(&buttons_box);
+
+    dialog.set_content(Some(&content));
+
+    // Entries the Assign button would move, updated while typing
+    let matching: Rc<RefCell<Vec<db::TimeEntry>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let state_for_preview = state.clone();
+    let matching_for_preview = matching.clone();
+    let assign_button_for_preview = assign_button.clone();
+    keyword_entry.connect_changed(move |entry| {
+        let keyword = entry.text();
+        let entries = if keyword.trim().is_empty() {
+            Vec::new()
+        } else {
+            let state_borrow = state_for_preview.borrow();
+            db::get_unassigned_entries_matching(&state_borrow.db_conn, &keyword).unwrap_or_else(|e| {
+                state_borrow.show_error(&format!("Failed to find matching entries: {}", e));
+                Vec::new()
+            })
+        };
+        preview_label.set_label(&preview_text(&entries));
+        preview_label.set_visible(!keyword.trim().is_empty());
+        assign_button_for_preview.set_sensitive(!entries.is_empty());
+        *matching_for_preview.borrow_mut() = entries;
+    });
+
+    let dialog_for_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_for_cancel.close());
+
+    let dialog_for_assign = dialog.clone();
+    let project_id = project.id;
+    let project_name = project.name.clone();
+    assign_button.connect_clicked(move |_| {
+        let ids: Vec<i64> = matching.borrow().iter().map(|e| e.id).collect();
+        let result = {
+            let state_borrow = state.borrow();
+            matching
+                .borrow()
+                .iter()
+                .try_for_each(|entry| state_borrow.ensure_unlocked(entry.start_time))
+                .and_then(|()| db::set_entries_project(&state_borrow.db_conn, &ids, Some(project_id)))
+        };
+        if let Err(e) = result {
+            state.borrow().show_failure("assign entries", &e);
+            return;
+        }
+        dialog_for_assign.close();
+
+        let window = state.borrow().window.clone();
+        if let Some(ref window) = window {
+            refresh_view(state.clone(), window);
+        }
+
+        // The entries had no project before, so undoing clears it again
+        let state_for_undo = state.clone();
+        let message = if ids.len() == 1 {
+            format!("1 entry moved to {}", project_name)
+        } else {
+            format!("{} entries moved to {}", ids.len(), project_name)
+        };
+        state.borrow().show_undo(&message, move || {
+            if let Err(e) = db::set_entries_project(&state_for_undo.borrow().db_conn, &ids, None) {
+                state_for_undo.borrow().show_error(&format!("Failed to undo: {}", e));
+                return;
+            }
+            let window = state_for_undo.borrow().window.clone();
+            if let Some(ref window) = window {
+                refresh_view(state_for_undo.clone(), window);
+            }
+        });
+    });
+
+    dialog.present();
+}
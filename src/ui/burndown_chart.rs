@@ -0,0 +1,102 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::forecast::BurndownDay;
+
+/// Height of the bars area, in pixels
+const CHART_HEIGHT: i32 = 48;
+
+/// Color of the remaining time bars, the Adwaita blue
+const REMAINING_COLOR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+/// Color of the even pace line
+const IDEAL_COLOR: (f64, f64, f64) = (0.5, 0.5, 0.5);
+
+/// Initials of the weekdays under the bars, Monday first
+const DAY_INITIALS: [&str; 7] = ["M", "T", "W", "T", "F", "S", "S"];
+
+/// Burndown of a weekly target: a bar per day with what is left of the
+/// target at its end, against a line of the even pace over the work days
+#[derive(Clone)]
+pub struct BurndownChart {
+    pub container: gtk::Box,
+    area: gtk::DrawingArea,
+    burndown: Rc<RefCell<(i64, Vec<BurndownDay>)>>,
+}
+
+impl BurndownChart {
+    pub fn new() -> Self {
+        let area = gtk::DrawingArea::builder()
+            .content_height(CHART_HEIGHT)
+            .hexpand(true)
+            .build();
+        let initials = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .homogeneous(true)
+            .build();
+        for initial in DAY_INITIALS {
+            initials.append(
+                &gtk::Label::builder()
+                    .label(initial)
+                    .css_classes(["caption", "dim-label"])
+                    .build(),
+            );
+        }
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(2)
+            .margin_top(4)
+            .build();
+        container.append(&area);
+        container.append(&initials);
+
+        let burndown: Rc<RefCell<(i64, Vec<BurndownDay>)>> = Rc::new(RefCell::new((0, Vec::new())));
+        let burndown_for_draw = burndown.clone();
+        area.set_draw_func(move |_, cr, width, height| {
+            let (target, ref days) = *burndown_for_draw.borrow();
+            if target <= 0 || days.is_empty() {
+                return;
+            }
+            let column = width as f64 / days.len() as f64;
+            let height = height as f64;
+            let y = |seconds: i64| height - seconds as f64 / target as f64 * height;
+
+            let (r, g, b) = REMAINING_COLOR;
+            cr.set_source_rgba(r, g, b, 0.8);
+            for (index, day) in days.iter().enumerate() {
+                if let Some(remaining) = day.remaining {
+                    let left = index as f64 * column + column * 0.2;
+                    cr.rectangle(left, y(remaining), column * 0.6, height - y(remaining));
+                }
+            }
+            let _ = cr.fill();
+
+            // The even pace runs from the full target at the start of the
+            // week through the end of each day
+            let (r, g, b) = IDEAL_COLOR;
+            cr.set_source_rgba(r, g, b, 0.8);
+            cr.set_line_width(1.5);
+            cr.set_dash(&[4.0, 3.0], 0.0);
+            cr.move_to(0.0, y(target));
+            for (index, day) in days.iter().enumerate() {
+                cr.line_to((index + 1) as f64 * column, y(day.ideal));
+            }
+            let _ = cr.stroke();
+        });
+
+        Self {
+            container,
+            area,
+            burndown,
+        }
+    }
+
+    /// Replaces the burndown of `target_seconds` and its tooltip, then redraws
+    pub fn update(&self, target_seconds: i64, days: &[BurndownDay], tooltip: &str) {
+        *self.burndown.borrow_mut() = (target_seconds, days.to_vec());
+        self.container.set_tooltip_text(Some(tooltip));
+        self.area.queue_draw();
+    }
+}
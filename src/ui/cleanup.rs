@@ -0,0 +1,179 @@
+use adw::prelude::*;
+use chrono::{Months, Utc};
+use gtk4 as gtk;
+use rusqlite::Connection;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_projects_list, AppState};
+use crate::db;
+use crate::error;
+use crate::settings::Settings;
+
+/// Archives projects unused for the configured number of months.
+/// Returns how many projects were archived.
+pub fn auto_archive_projects(conn: &Connection) -> error::Result<usize> {
+    let months = Settings::load(conn).auto_archive_months;
+    if months == 0 {
+        return Ok(0);
+    }
+
+    match Utc::now().checked_sub_months(Months::new(months)) {
+        Some(cutoff) => db::archive_unused_projects(conn, cutoff),
+        None => Ok(0),
+    }
+}
+
+/// Reloads the empty projects into the cleanup list, returning a checkbox per project
+fn refresh_cleanup_list(
+    state: &Rc<RefCell<AppState>>,
+    list_box: &gtk::ListBox,
+    rows: &Rc<RefCell<Vec<(i64, gtk::CheckButton)>>>,
+) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+    rows.borrow_mut().clear();
+
+    let projects = match db::get_empty_projects(&state.borrow().db_conn) {
+        Ok(projects) => projects,
+        Err(e) => {
+            state.borrow().show_error(&format!("Failed to load projects: {}", e));
+            Vec::new()
+        }
+    };
+
+    if projects.is_empty() {
+        let empty_label = gtk::Label::builder()
+            .label("Every project has time entries")
+            .css_classes(["dim-label"])
+            .margin_top(20)
+            .margin_bottom(20)
+            .build();
+        list_box.append(&empty_label);
+        return;
+    }
+
+    for project in &projects {
+        let check = gtk::CheckButton::builder()
+            .label(&project.name)
+            .active(true)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(8)
+            .margin_end(8)
+            .build();
+        list_box.append(&check);
+        rows.borrow_mut().push((project.id, check));
+    }
+}
+
+/// Shows the cleanup dialog listing projects without entries for bulk archiving or deletion.
+/// `projects_list_box` is the Manage Projects list, refreshed after each action.
+pub fn show_cleanup_dialog(
+    state: Rc<RefCell<AppState>>,
+    parent: &impl IsA<gtk::Window>,
+    projects_list_box: &gtk::ListBox,
+) {
+    let dialog = adw::Window::builder()
+        .title("Clean Up Projects")
+        .default_width(350)
+        .default_height(400)
+        .modal(true)
+        .transient_for(parent)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(0)
+        .build();
+
+    let header_bar = adw::HeaderBar::builder()
+        .show_end_title_buttons(true)
+        .title_widget(&adw::WindowTitle::new("Clean Up Projects", "Projects without entries"))
+        .build();
+    content.append(&header_bar);
+
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .vexpand(true)
+        .build();
+
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+
+    scrolled_window.set_child(Some(&list_box));
+    content.append(&scrolled_window);
+
+    // Bulk action buttons
+    let actions_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(8)
+        .halign(gtk::Align::End)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_bottom(12)
+        .build();
+
+    let delete_button = gtk::Button::builder()
+        .label("Delete Selected")
+        .css_classes(["destructive-action"])
+        .build();
+    let archive_button = gtk::Button::builder()
+        .label("Archive Selected")
+        .css_classes(["suggested-action"])
+        .build();
+    actions_box.append(&delete_button);
+    actions_box.append(&archive_button);
+    content.append(&actions_box);
+
+    let rows: Rc<RefCell<Vec<(i64, gtk::CheckButton)>>> = Rc::new(RefCell::new(Vec::new()));
+    refresh_cleanup_list(&state, &list_box, &rows);
+
+    // Both actions share everything except the database call and the wording
+    let connect_action = |button: &gtk::Button,
+                          action: fn(&Connection, &[i64]) -> error::Result<()>,
+                          verb: &'static str| {
+        let state = state.clone();
+        let list_box = list_box.clone();
+        let rows = rows.clone();
+        let projects_list_box = projects_list_box.clone();
+        button.connect_clicked(move |_| {
+            let ids: Vec<i64> = rows
+                .borrow()
+                .iter()
+                .filter(|(_, check)| check.is_active())
+                .map(|(id, _)| *id)
+                .collect();
+            if ids.is_empty() {
+                return;
+            }
+
+            let result = action(&state.borrow().db_conn, &ids);
+            match result {
+                Ok(()) => state
+                    .borrow()
+                    .show_info(&format!("{} {} projects", verb, ids.len())),
+                Err(e) => state
+                    .borrow()
+                    .show_error(&format!("Failed to clean up projects: {}", e)),
+            }
+            refresh_cleanup_list(&state, &list_box, &rows);
+            refresh_projects_list(&state, &projects_list_box);
+            state.borrow_mut().refresh_projects();
+        });
+    };
+
+    connect_action(&archive_button, |conn, ids| db::set_projects_archived(conn, ids, true), "Archived");
+    connect_action(&delete_button, db::delete_projects, "Deleted");
+
+    dialog.set_content(Some(&content));
+    dialog.present();
+}
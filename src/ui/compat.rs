@@ -0,0 +1,379 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+
+/// Window width condition for [`add_width_breakpoint`], in sp
+#[derive(Debug, Clone, Copy)]
+pub enum WidthCondition {
+    MinWidth(f64),
+    MaxWidth(f64),
+}
+
+/// Runs `apply` when the window width starts matching the condition and
+/// `unapply` when it stops matching. Uses an adaptive breakpoint with
+/// libadwaita 1.4, otherwise follows the window's size, treating sp as pixels.
+#[cfg(feature = "adw_1_4")]
+pub fn add_width_breakpoint(
+    window: &adw::ApplicationWindow,
+    condition: WidthCondition,
+    apply: impl Fn() + 'static,
+    unapply: impl Fn() + 'static,
+) {
+    let (length_type, width) = match condition {
+        WidthCondition::MinWidth(width) => (adw::BreakpointConditionLengthType::MinWidth, width),
+        WidthCondition::MaxWidth(width) => (adw::BreakpointConditionLengthType::MaxWidth, width),
+    };
+    let breakpoint = adw::Breakpoint::new(adw::BreakpointCondition::new_length(
+        length_type,
+        width,
+        adw::LengthUnit::Sp,
+    ));
+    breakpoint.connect_apply(move |_| apply());
+    breakpoint.connect_unapply(move |_| unapply());
+    window.add_breakpoint(breakpoint);
+}
+
+/// Runs `apply` when the window width starts matching the condition and
+/// `unapply` when it stops matching. Uses an adaptive breakpoint with
+/// libadwaita 1.4, otherwise follows the window's size, treating sp as pixels.
+#[cfg(not(feature = "adw_1_4"))]
+pub fn add_width_breakpoint(
+    window: &adw::ApplicationWindow,
+    condition: WidthCondition,
+    apply: impl Fn() + 'static,
+    unapply: impl Fn() + 'static,
+) {
+    use std::cell::Cell;
+
+    let matches = move |window: &adw::ApplicationWindow| {
+        // The allocated width is 0 before the first map; fall back to the requested size
+        let width = match window.width() {
+            0 => window.default_width(),
+            width => width,
+        } as f64;
+        match condition {
+            WidthCondition::MinWidth(min) => width >= min,
+            WidthCondition::MaxWidth(max) => width <= max,
+        }
+    };
+
+    let applied = Cell::new(false);
+    let update = move |window: &adw::ApplicationWindow| {
+        let now_matches = matches(window);
+        if now_matches != applied.replace(now_matches) {
+            if now_matches {
+                apply();
+            } else {
+                unapply();
+            }
+        }
+    };
+    update(window);
+
+    // Resizing updates the default size; maximizing and fullscreen only change the allocation
+    let update = std::rc::Rc::new(update);
+    for property in ["default-width", "maximized", "fullscreened"] {
+        let update = update.clone();
+        window.connect_notify_local(Some(property), move |window, _| {
+            let update = update.clone();
+            let window = window.clone();
+            gtk4::glib::idle_add_local_once(move || update(&window));
+        });
+    }
+}
+
+/// Adds a titled page with an icon to a view stack
+/// (`ViewStack::add_titled_with_icon` needs libadwaita 1.2)
+pub fn add_view_page(
+    view_stack: &adw::ViewStack,
+    child: &impl IsA<gtk::Widget>,
+    name: &str,
+    title: &str,
+    icon_name: &str,
+) {
+    view_stack
+        .add_titled(child, Some(name), title)
+        .set_icon_name(Some(icon_name));
+}
+
+/// Asks for confirmation before a destructive action, running `on_confirm`
+/// when the user chooses `action_label`
+pub fn confirm_destructive(
+    parent: &impl IsA<gtk::Window>,
+    heading: &str,
+    body: &str,
+    action_label: &str,
+    on_confirm: impl Fn() + 'static,
+) {
+    ask(parent, heading, body, "Cancel", action_label, true, move |confirmed| {
+        if confirmed {
+            on_confirm();
+        }
+    });
+}
+
+/// Offers an action, such as a correction, running `on_confirm` when the
+/// user chooses `action_label`
+pub fn confirm_suggested(
+    parent: &impl IsA<gtk::Window>,
+    heading: &str,
+    body: &str,
+    action_label: &str,
+    on_confirm: impl Fn() + 'static,
+) {
+    ask(parent, heading, body, "Cancel", action_label, false, move |confirmed| {
+        if confirmed {
+            on_confirm();
+        }
+    });
+}
+
+/// Offers two ways forward, calling `on_choice` with true when the user
+/// chooses `action_label` and false for `cancel_label` or closing the dialog
+pub fn choose(
+    parent: &impl IsA<gtk::Window>,
+    heading: &str,
+    body: &str,
+    cancel_label: &str,
+    action_label: &str,
+    on_choice: impl Fn(bool) + 'static,
+) {
+    ask(parent, heading, body, cancel_label, action_label, false, on_choice);
+}
+
+#[cfg(feature = "adw_1_2")]
+fn ask(
+    parent: &impl IsA<gtk::Window>,
+    heading: &str,
+    body: &str,
+    cancel_label: &str,
+    action_label: &str,
+    destructive: bool,
+    on_choice: impl Fn(bool) + 'static,
+) {
+    let dialog = adw::MessageDialog::new(Some(parent), Some(heading), Some(body));
+    dialog.add_response("cancel", cancel_label);
+    dialog.add_response("confirm", action_label);
+    dialog.set_response_appearance(
+        "confirm",
+        if destructive {
+            adw::ResponseAppearance::Destructive
+        } else {
+            adw::ResponseAppearance::Suggested
+        },
+    );
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog.connect_response(None, move |_, response| {
+        on_choice(response == "confirm");
+    });
+    dialog.present();
+}
+
+#[cfg(not(feature = "adw_1_2"))]
+fn ask(
+    parent: &impl IsA<gtk::Window>,
+    heading: &str,
+    body: &str,
+    cancel_label: &str,
+    action_label: &str,
+    destructive: bool,
+    on_choice: impl Fn(bool) + 'static,
+) {
+    let dialog = gtk::MessageDialog::builder()
+        .transient_for(parent)
+        .modal(true)
+        .message_type(gtk::MessageType::Question)
+        .buttons(gtk::ButtonsType::None)
+        .text(heading)
+        .secondary_text(body)
+        .build();
+
+    dialog.add_button(cancel_label, gtk::ResponseType::Cancel);
+    dialog.add_button(action_label, gtk::ResponseType::Accept);
+
+    if let Some(button) = dialog.widget_for_response(gtk::ResponseType::Accept) {
+        button.add_css_class(if destructive {
+            "destructive-action"
+        } else {
+            "suggested-action"
+        });
+    }
+
+    dialog.connect_response(move |dialog, response| {
+        on_choice(response == gtk::ResponseType::Accept);
+        dialog.close();
+    });
+
+    dialog.present();
+}
+
+/// Shows an informational message with a single close button
+#[cfg(feature = "adw_1_2")]
+pub fn show_message(parent: &impl IsA<gtk::Window>, heading: &str, body: &str) {
+    let dialog = adw::MessageDialog::new(Some(parent), Some(heading), Some(body));
+    dialog.add_response("close", "Close");
+    dialog.set_default_response(Some("close"));
+    dialog.set_close_response("close");
+    dialog.present();
+}
+
+/// Shows an informational message with a single close button
+#[cfg(not(feature = "adw_1_2"))]
+pub fn show_message(parent: &impl IsA<gtk::Window>, heading: &str, body: &str) {
+    let dialog = gtk::MessageDialog::builder()
+        .transient_for(parent)
+        .modal(true)
+        .message_type(gtk::MessageType::Info)
+        .buttons(gtk::ButtonsType::Close)
+        .text(heading)
+        .secondary_text(body)
+        .build();
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+    dialog.present();
+}
+
+/// Shows an error message with a single close button when the app has no
+/// window to show it in. The dialog keeps the app running until closed.
+#[cfg(feature = "adw_1_2")]
+pub fn show_app_error(app: &impl IsA<gtk::Application>, heading: &str, body: &str) {
+    let dialog = adw::MessageDialog::new(None::<&gtk::Window>, Some(heading), Some(body));
+    dialog.set_application(Some(app));
+    dialog.add_response("close", "Close");
+    dialog.set_default_response(Some("close"));
+    dialog.set_close_response("close");
+    dialog.present();
+}
+
+/// Shows an error message with a single close button when the app has no
+/// window to show it in. The dialog keeps the app running until closed.
+#[cfg(not(feature = "adw_1_2"))]
+pub fn show_app_error(app: &impl IsA<gtk::Application>, heading: &str, body: &str) {
+    let dialog = gtk::MessageDialog::builder()
+        .application(app)
+        .message_type(gtk::MessageType::Error)
+        .buttons(gtk::ButtonsType::Close)
+        .text(heading)
+        .secondary_text(body)
+        .build();
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+    dialog.present();
+}
+
+/// Shows a toast with an Undo button running `undo`. Without libadwaita 1.2
+/// (`Toast::connect_button_clicked`) the toast has no button.
+#[cfg(feature = "adw_1_2")]
+pub fn add_undo_toast(overlay: &adw::ToastOverlay, title: &str, undo: impl Fn() + 'static) {
+    let toast = adw::Toast::builder()
+        .title(title)
+        .button_label("Undo")
+        .timeout(10)
+        .build();
+    toast.connect_button_clicked(move |_| undo());
+    overlay.add_toast(toast);
+}
+
+/// Shows a toast with an Undo button running `undo`. Without libadwaita 1.2
+/// (`Toast::connect_button_clicked`) the toast has no button.
+#[cfg(not(feature = "adw_1_2"))]
+pub fn add_undo_toast(overlay: &adw::ToastOverlay, title: &str, _undo: impl Fn() + 'static) {
+    overlay.add_toast(adw::Toast::builder().title(title).timeout(5).build());
+}
+
+/// Shows a toast with a button labelled `button_label` running `action`.
+/// Without libadwaita 1.2 (`Toast::connect_button_clicked`) the toast has
+/// no button.
+#[cfg(feature = "adw_1_2")]
+pub fn add_action_toast(overlay: &adw::ToastOverlay, title: &str, button_label: &str, action: impl Fn() + 'static) {
+    let toast = adw::Toast::builder()
+        .title(title)
+        .button_label(button_label)
+        .timeout(0)
+        .build();
+    toast.connect_button_clicked(move |_| action());
+    overlay.add_toast(toast);
+}
+
+/// Shows a toast with a button labelled `button_label` running `action`.
+/// Without libadwaita 1.2 (`Toast::connect_button_clicked`) the toast has
+/// no button.
+#[cfg(not(feature = "adw_1_2"))]
+pub fn add_action_toast(overlay: &adw::ToastOverlay, title: &str, _button_label: &str, _action: impl Fn() + 'static) {
+    overlay.add_toast(adw::Toast::builder().title(title).timeout(5).build());
+}
+
+/// Creates a dismissable banner showing `title` across the window. Uses
+/// `adw::Banner` with libadwaita 1.4, otherwise a box styled as a toolbar.
+#[cfg(feature = "adw_1_4")]
+pub fn create_banner(title: &str) -> gtk::Widget {
+    let banner = adw::Banner::builder()
+        .title(title)
+        .button_label("Dismiss")
+        .revealed(true)
+        .build();
+    banner.connect_button_clicked(|banner| banner.set_revealed(false));
+    banner.upcast()
+}
+
+/// Creates a dismissable banner showing `title` across the window. Uses
+/// `adw::Banner` with libadwaita 1.4, otherwise a box styled as a toolbar.
+#[cfg(not(feature = "adw_1_4"))]
+pub fn create_banner(title: &str) -> gtk::Widget {
+    let revealer = gtk::Revealer::builder().reveal_child(true).build();
+    let row = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(12)
+        .css_classes(["toolbar"])
+        .build();
+    row.append(
+        &gtk::Label::builder()
+            .label(title)
+            .wrap(true)
+            .hexpand(true)
+            .xalign(0.0)
+            .build(),
+    );
+    let dismiss_button = gtk::Button::with_label("Dismiss");
+    let revealer_for_dismiss = revealer.clone();
+    dismiss_button.connect_clicked(move |_| revealer_for_dismiss.set_reveal_child(false));
+    row.append(&dismiss_button);
+    revealer.set_child(Some(&row));
+    revealer.upcast()
+}
+
+/// Button showing a color and opening a chooser for any other one
+#[cfg(feature = "gtk_4_10")]
+pub type ColorButton = gtk::ColorDialogButton;
+
+/// Button showing a color and opening a chooser for any other one
+#[cfg(not(feature = "gtk_4_10"))]
+pub type ColorButton = gtk::ColorButton;
+
+/// Creates a color button showing `color`. Uses `ColorDialogButton` with
+/// GTK 4.10, otherwise the `ColorButton` it replaced.
+#[cfg(feature = "gtk_4_10")]
+pub fn create_color_button(color: &gtk::gdk::RGBA) -> ColorButton {
+    let button = gtk::ColorDialogButton::new(Some(
+        gtk::ColorDialog::builder()
+            .title("Project Color")
+            .with_alpha(false)
+            .build(),
+    ));
+    button.set_rgba(color);
+    button
+}
+
+/// Creates a color button showing `color`. Uses `ColorDialogButton` with
+/// GTK 4.10, otherwise the `ColorButton` it replaced.
+#[cfg(not(feature = "gtk_4_10"))]
+pub fn create_color_button(color: &gtk::gdk::RGBA) -> ColorButton {
+    let button = gtk::ColorButton::with_rgba(color);
+    button.set_title("Project Color");
+    button.set_use_alpha(false);
+    button
+}
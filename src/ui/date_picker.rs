@@ -0,0 +1,70 @@
+use chrono::{Datelike, NaiveDate};
+use gtk4 as gtk;
+use gtk4::glib;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Converts a date to the calendar's representation
+pub fn to_glib_date(date: NaiveDate) -> Option<glib::DateTime> {
+    glib::DateTime::from_local(date.year(), date.month() as i32, date.day() as i32, 0, 0, 0.0).ok()
+}
+
+/// Reads the selected date of a calendar
+pub fn calendar_date(calendar: &gtk::Calendar) -> Option<NaiveDate> {
+    let date = calendar.date();
+    NaiveDate::from_ymd_opt(date.year(), date.month() as u32, date.day_of_month() as u32)
+}
+
+/// Button showing a date that opens a calendar to pick another one
+#[derive(Clone)]
+pub struct DatePicker {
+    pub button: gtk::MenuButton,
+    calendar: gtk::Calendar,
+    /// Set while the date is changed from code, so the callback only sees user picks
+    updating: Rc<Cell<bool>>,
+}
+
+impl DatePicker {
+    pub fn new(date: NaiveDate, tooltip: &str) -> Self {
+        let calendar = gtk::Calendar::new();
+        let popover = gtk::Popover::builder().child(&calendar).build();
+        let button = gtk::MenuButton::builder()
+            .popover(&popover)
+            .tooltip_text(tooltip)
+            .build();
+
+        let picker = Self {
+            button,
+            calendar,
+            updating: Rc::new(Cell::new(false)),
+        };
+        picker.set_date(date);
+        picker
+    }
+
+    /// Shows `date` on the button and selects it in the calendar
+    pub fn set_date(&self, date: NaiveDate) {
+        self.button.set_label(&date.format("%b %-d, %Y").to_string());
+        if let Some(date) = to_glib_date(date) {
+            self.updating.set(true);
+            self.calendar.select_day(&date);
+            self.updating.set(false);
+        }
+    }
+
+    /// Calls `callback` with the date the user picks, closing the calendar
+    pub fn connect_date_picked(&self, callback: impl Fn(NaiveDate) + 'static) {
+        let button = self.button.clone();
+        let updating = self.updating.clone();
+        self.calendar.connect_day_selected(move |calendar| {
+            if updating.get() {
+                return;
+            }
+            if let Some(date) = calendar_date(calendar) {
+                button.set_label(&date.format("%b %-d, %Y").to_string());
+                button.popdown();
+                callback(date);
+            }
+        });
+    }
+}
@@ -0,0 +1,125 @@
+use gtk4 as gtk;
+use gtk4::gdk;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Height of the bar holding the blocks, in pixels
+const BAR_HEIGHT: f64 = 16.0;
+
+/// Color of entries without a project and of the empty track
+const NEUTRAL_COLOR: (f64, f64, f64) = (0.5, 0.5, 0.5);
+
+/// Color of the line marking now, the default accent color
+const NOW_COLOR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+/// What the timeline draws: blocks as day fractions with their color, and
+/// where now is when the shown day is today
+#[derive(Default)]
+struct TimelineData {
+    blocks: Vec<(f64, f64, Option<gdk::RGBA>)>,
+    now: Option<f64>,
+}
+
+/// Horizontal bar of the day from midnight to midnight, tracked time drawn
+/// as blocks in the project colors so gaps stand out
+#[derive(Clone)]
+pub struct DayTimeline {
+    pub container: gtk::Box,
+    area: gtk::DrawingArea,
+    data: Rc<RefCell<TimelineData>>,
+}
+
+impl DayTimeline {
+    pub fn new() -> Self {
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(2)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+
+        let area = gtk::DrawingArea::builder()
+            .content_height(BAR_HEIGHT as i32)
+            .hexpand(true)
+            .build();
+        container.append(&area);
+
+        // Hour labels under each quarter of the day
+        let hours = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .homogeneous(true)
+            .build();
+        for hour in ["00:00", "06:00", "12:00", "18:00"] {
+            hours.append(
+                &gtk::Label::builder()
+                    .label(hour)
+                    .xalign(0.0)
+                    .css_classes(["caption", "dim-label"])
+                    .build(),
+            );
+        }
+        container.append(&hours);
+
+        let data = Rc::new(RefCell::new(TimelineData::default()));
+        let data_for_draw = data.clone();
+        area.set_draw_func(move |_, cr, width, height| {
+            let data = data_for_draw.borrow();
+            let width = width as f64;
+            let height = height as f64;
+            let (r, g, b) = NEUTRAL_COLOR;
+
+            cr.set_source_rgba(r, g, b, 0.15);
+            cr.rectangle(0.0, 0.0, width, height);
+            let _ = cr.fill();
+
+            // Faint lines every six hours
+            cr.set_source_rgba(r, g, b, 0.4);
+            for quarter in 1..4 {
+                let x = (width * quarter as f64 / 4.0).round() + 0.5;
+                cr.rectangle(x, 0.0, 1.0, height);
+            }
+            let _ = cr.fill();
+
+            for (start, end, color) in &data.blocks {
+                match color {
+                    Some(color) => cr.set_source_rgba(
+                        color.red() as f64,
+                        color.green() as f64,
+                        color.blue() as f64,
+                        1.0,
+                    ),
+                    None => cr.set_source_rgba(r, g, b, 0.8),
+                }
+                // At least a pixel wide, so short entries stay visible
+                let x = start * width;
+                cr.rectangle(x, 0.0, ((end - start) * width).max(1.0), height);
+                let _ = cr.fill();
+            }
+
+            if let Some(now) = data.now {
+                let (r, g, b) = NOW_COLOR;
+                cr.set_source_rgb(r, g, b);
+                cr.rectangle((now * width).round() - 1.0, 0.0, 2.0, height);
+                let _ = cr.fill();
+            }
+        });
+
+        Self { container, area, data }
+    }
+
+    /// Replaces the blocks, given as day fractions with the project color
+    /// (`None` for no project), and the position of now, then redraws
+    pub fn update(&self, blocks: Vec<(f64, f64, Option<&str>)>, now: Option<f64>) {
+        let mut data = self.data.borrow_mut();
+        data.blocks = blocks
+            .into_iter()
+            .map(|(start, end, color)| (start, end, color.and_then(|c| gdk::RGBA::parse(c).ok())))
+            .collect();
+        data.now = now;
+        drop(data);
+        self.area.queue_draw();
+    }
+}
@@ -0,0 +1,101 @@
+use gtk4 as gtk;
+use gtk4::gdk;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+/// Width and height of the chart, in pixels
+const CHART_SIZE: i32 = 120;
+
+/// Thickness of the ring as a fraction of its outer radius
+const RING_WIDTH: f64 = 0.35;
+
+/// Color of the empty ring, shown when there is nothing to chart
+const NEUTRAL_COLOR: (f64, f64, f64) = (0.5, 0.5, 0.5);
+
+/// Donut chart of shares of a total, each slice in its own color, with the
+/// total in the middle. Legends are left to the caller, so the chart fits
+/// any breakdown of a range.
+#[derive(Clone)]
+pub struct DonutChart {
+    pub container: gtk::Overlay,
+    area: gtk::DrawingArea,
+    total_label: gtk::Label,
+    slices: Rc<RefCell<Vec<(f64, gdk::RGBA)>>>,
+}
+
+impl DonutChart {
+    pub fn new() -> Self {
+        let area = gtk::DrawingArea::builder()
+            .content_width(CHART_SIZE)
+            .content_height(CHART_SIZE)
+            .build();
+        let total_label = gtk::Label::builder()
+            .halign(gtk::Align::Center)
+            .valign(gtk::Align::Center)
+            .css_classes(["caption-heading", "monospace"])
+            .build();
+        let container = gtk::Overlay::builder()
+            .child(&area)
+            .halign(gtk::Align::Center)
+            .valign(gtk::Align::Center)
+            .build();
+        container.add_overlay(&total_label);
+
+        let slices: Rc<RefCell<Vec<(f64, gdk::RGBA)>>> = Rc::new(RefCell::new(Vec::new()));
+        let slices_for_draw = slices.clone();
+        area.set_draw_func(move |_, cr, width, height| {
+            let slices = slices_for_draw.borrow();
+            let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+            let outer = cx.min(cy);
+            let inner = outer * (1.0 - RING_WIDTH);
+
+            let total: f64 = slices.iter().map(|(value, _)| value).sum();
+            if total <= 0.0 {
+                let (r, g, b) = NEUTRAL_COLOR;
+                cr.set_source_rgba(r, g, b, 0.2);
+                cr.arc(cx, cy, outer, 0.0, 2.0 * PI);
+                cr.arc_negative(cx, cy, inner, 2.0 * PI, 0.0);
+                let _ = cr.fill();
+                return;
+            }
+
+            // Slices go clockwise from the top
+            let mut angle = -PI / 2.0;
+            for (value, color) in slices.iter() {
+                let sweep = value / total * 2.0 * PI;
+                cr.set_source_rgba(
+                    color.red() as f64,
+                    color.green() as f64,
+                    color.blue() as f64,
+                    1.0,
+                );
+                cr.arc(cx, cy, outer, angle, angle + sweep);
+                cr.arc_negative(cx, cy, inner, angle + sweep, angle);
+                cr.close_path();
+                let _ = cr.fill();
+                angle += sweep;
+            }
+        });
+
+        Self {
+            container,
+            area,
+            total_label,
+            slices,
+        }
+    }
+
+    /// Replaces the slices, given as values with their color, and the text
+    /// in the middle, then redraws. Slices without a value are left out.
+    pub fn update(&self, slices: Vec<(i64, &str)>, total_text: &str) {
+        *self.slices.borrow_mut() = slices
+            .into_iter()
+            .filter(|(value, _)| *value > 0)
+            .filter_map(|(value, color)| gdk::RGBA::parse(color).ok().map(|c| (value as f64, c)))
+            .collect();
+        self.total_label.set_label(total_text);
+        self.area.queue_draw();
+    }
+}
@@ -0,0 +1,259 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use gtk4::glib;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use super::AppState;
+use crate::db;
+use crate::duration::Rounding;
+use crate::export::{self, ExportFormat, ExportTimeZone};
+use crate::privacy::PrivacyMode;
+use crate::report::ReportRange;
+
+/// Save dialog choice picking the time zone of the exported timestamps
+const TIME_ZONE_CHOICE: &str = "time-zone";
+
+/// How often the progress dialog picks up news from the export thread
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Messages from the export thread to the progress dialog
+enum ExportEvent {
+    Progress(usize, usize),
+    /// Entries written, `None` when cancelled
+    Finished(Result<Option<usize>, String>),
+}
+
+/// Asks where to save an export of `range`, then writes it on a background
+/// thread while a progress dialog offers to cancel
+pub fn export_range(
+    state: &Rc<RefCell<AppState>>,
+    parent: &adw::Window,
+    toast_overlay: &adw::ToastOverlay,
+    range: ReportRange,
+) {
+    let chooser = gtk::FileChooserNative::new(
+        Some("Export Entries"),
+        Some(parent),
+        gtk::FileChooserAction::Save,
+        Some("Export"),
+        Some("Cancel"),
+    );
+    chooser.set_current_name(&export::default_file_name(range, ExportFormat::Csv));
+    for format in ExportFormat::ALL {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(format.label()));
+        filter.add_pattern(&format!("*.{}", format.extension()));
+        chooser.add_filter(&filter);
+    }
+    let time_zones: Vec<(String, String)> = ExportTimeZone::choices()
+        .into_iter()
+        .map(|time_zone| (time_zone.id(), time_zone.label()))
+        .collect();
+    let options: Vec<(&str, &str)> = time_zones
+        .iter()
+        .map(|(id, label)| (id.as_str(), label.as_str()))
+        .collect();
+    chooser.add_choice(TIME_ZONE_CHOICE, "Time zone", &options);
+    chooser.set_choice(TIME_ZONE_CHOICE, &ExportTimeZone::Local.id());
+
+    // The native dialog is only kept alive by its owner, so hold it until it answers
+    let pending = Rc::new(RefCell::new(Some(chooser.clone())));
+    let state = state.clone();
+    let parent = parent.clone();
+    let toast_overlay = toast_overlay.clone();
+    chooser.connect_response(move |chooser, response| {
+        pending.borrow_mut().take();
+        if response != gtk::ResponseType::Accept {
+            return;
+        }
+        let Some(path) = chooser.file().and_then(|file| file.path()) else {
+            return;
+        };
+        let time_zone = chooser
+            .choice(TIME_ZONE_CHOICE)
+            .and_then(|id| ExportTimeZone::from_id(&id))
+            .unwrap_or(ExportTimeZone::Local);
+        start_export(&state, &parent, &toast_overlay, range, time_zone, path);
+    });
+    chooser.show();
+}
+
+/// Runs the export on its own thread and connection, so the window stays
+/// responsive and entries are never all held in memory
+fn start_export(
+    state: &Rc<RefCell<AppState>>,
+    parent: &adw::Window,
+    toast_overlay: &adw::ToastOverlay,
+    range: ReportRange,
+    time_zone: ExportTimeZone,
+    path: PathBuf,
+) {
+    let state = state.borrow();
+    let db_path = match state.db_conn.path() {
+        Some(db_path) if !db_path.is_empty() => PathBuf::from(db_path),
+        _ => {
+            toast_overlay.add_toast(adw::Toast::new("Export needs a database file"));
+            return;
+        }
+    };
+    let rounding = state.settings.rounding;
+    let privacy_mode = state.settings.privacy_mode;
+    drop(state);
+
+    let (sender, receiver) = mpsc::channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let cancelled = cancelled.clone();
+        let path = path.clone();
+        thread::spawn(move || {
+            let result = write_export(&db_path, &path, range, time_zone, rounding, privacy_mode, |done, total| {
+                let _ = sender.send(ExportEvent::Progress(done, total));
+                !cancelled.load(Ordering::Relaxed)
+            });
+            // Don't leave a partial file behind
+            if !matches!(result, Ok(Some(_))) {
+                let _ = std::fs::remove_file(&path);
+            }
+            let _ = sender.send(ExportEvent::Finished(result));
+        });
+    }
+
+    let dialog = ProgressDialog::new(parent, &path);
+    {
+        let cancelled = cancelled.clone();
+        let cancel_button = dialog.cancel_button.clone();
+        dialog.cancel_button.connect_clicked(move |_| {
+            cancelled.store(true, Ordering::Relaxed);
+            cancel_button.set_sensitive(false);
+        });
+    }
+    // Closing the dialog cancels too
+    dialog.window.connect_close_request(move |_| {
+        cancelled.store(true, Ordering::Relaxed);
+        glib::Propagation::Proceed
+    });
+
+    let toast_overlay = toast_overlay.clone();
+    glib::timeout_add_local(POLL_INTERVAL, move || {
+        while let Ok(event) = receiver.try_recv() {
+            match event {
+                ExportEvent::Progress(done, total) => dialog.show_progress(done, total),
+                ExportEvent::Finished(result) => {
+                    dialog.window.destroy();
+                    let message = match result {
+                        Ok(Some(count)) => format!("Exported {} entries", count),
+                        Ok(None) => "Export cancelled".to_string(),
+                        Err(e) => format!("Failed to export: {}", e),
+                    };
+                    toast_overlay.add_toast(adw::Toast::new(&message));
+                    return glib::ControlFlow::Break;
+                }
+            }
+        }
+        glib::ControlFlow::Continue
+    });
+}
+
+/// Opens its own read-only connection and streams the export into a buffered
+/// file, never holding up the timer being started or stopped meanwhile
+fn write_export(
+    db_path: &std::path::Path,
+    path: &std::path::Path,
+    range: ReportRange,
+    time_zone: ExportTimeZone,
+    rounding: Rounding,
+    privacy_mode: PrivacyMode,
+    progress: impl FnMut(usize, usize) -> bool,
+) -> Result<Option<usize>, String> {
+    let conn = db::open_snapshot(db_path).map_err(|e| e.to_string())?;
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    export::export_range(
+        &conn,
+        range,
+        ExportFormat::from_path(path),
+        time_zone,
+        rounding,
+        privacy_mode,
+        BufWriter::new(file),
+        progress,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Modal dialog showing how far an export got, with a cancel button
+struct ProgressDialog {
+    window: adw::Window,
+    progress_bar: gtk::ProgressBar,
+    cancel_button: gtk::Button,
+}
+
+impl ProgressDialog {
+    fn new(parent: &adw::Window, path: &std::path::Path) -> Self {
+        let window = adw::Window::builder()
+            .title("Exporting")
+            .modal(true)
+            .transient_for(parent)
+            .default_width(360)
+            .resizable(false)
+            .build();
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .margin_top(18)
+            .margin_bottom(18)
+            .margin_start(18)
+            .margin_end(18)
+            .build();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        content.append(
+            &gtk::Label::builder()
+                .label(format!("Exporting to {}", file_name))
+                .halign(gtk::Align::Start)
+                .ellipsize(gtk::pango::EllipsizeMode::Middle)
+                .css_classes(["heading"])
+                .build(),
+        );
+
+        let progress_bar = gtk::ProgressBar::builder().show_text(true).build();
+        progress_bar.set_text(Some("Counting entries…"));
+        content.append(&progress_bar);
+
+        let cancel_button = gtk::Button::builder()
+            .label("Cancel")
+            .halign(gtk::Align::End)
+            .build();
+        content.append(&cancel_button);
+
+        window.set_content(Some(&content));
+        window.present();
+
+        Self {
+            window,
+            progress_bar,
+            cancel_button,
+        }
+    }
+
+    fn show_progress(&self, done: usize, total: usize) {
+        self.progress_bar.set_fraction(if total == 0 {
+            1.0
+        } else {
+            done as f64 / total as f64
+        });
+        self.progress_bar
+            .set_text(Some(&format!("{} of {} entries", done, total)));
+    }
+}
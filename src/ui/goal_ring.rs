@@ -0,0 +1,87 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use std::cell::Cell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+/// Space between the button and the outer edge of the ring, in pixels
+const RING_GAP: i32 = 6;
+
+/// Thickness of the ring, in pixels
+const RING_WIDTH: f64 = 4.0;
+
+/// Color of the ring while filling, the Adwaita blue
+const PROGRESS_COLOR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+/// Color of the ring once the goal is met, the Adwaita green
+const MET_COLOR: (f64, f64, f64) = (0.18, 0.76, 0.49);
+
+/// Color of the unfilled part of the ring
+const TRACK_COLOR: (f64, f64, f64) = (0.5, 0.5, 0.5);
+
+/// Ring around a circular button filling up as the day's tracked time
+/// approaches the daily goal, turning green once it is met
+#[derive(Clone)]
+pub struct GoalRing {
+    pub container: gtk::Overlay,
+    area: gtk::DrawingArea,
+    fraction: Rc<Cell<f64>>,
+}
+
+impl GoalRing {
+    /// Wraps `button`, which should have no margins of its own; margins go
+    /// on the container instead so the ring stays centered on the button
+    pub fn new(button: &gtk::Button) -> Self {
+        button.set_margin_top(RING_GAP);
+        button.set_margin_bottom(RING_GAP);
+        button.set_margin_start(RING_GAP);
+        button.set_margin_end(RING_GAP);
+
+        // The ring is drawn over the button's surroundings, clicks go through
+        let area = gtk::DrawingArea::builder().can_target(false).build();
+        let container = gtk::Overlay::builder()
+            .child(button)
+            .halign(gtk::Align::Center)
+            .build();
+        container.add_overlay(&area);
+
+        let fraction = Rc::new(Cell::new(0.0));
+        let fraction_for_draw = fraction.clone();
+        area.set_draw_func(move |_, cr, width, height| {
+            let fraction = fraction_for_draw.get();
+            let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+            let radius = cx.min(cy) - RING_WIDTH / 2.0;
+            cr.set_line_width(RING_WIDTH);
+
+            let (r, g, b) = TRACK_COLOR;
+            cr.set_source_rgba(r, g, b, 0.2);
+            cr.arc(cx, cy, radius, 0.0, 2.0 * PI);
+            let _ = cr.stroke();
+
+            if fraction > 0.0 {
+                let (r, g, b) = if fraction >= 1.0 { MET_COLOR } else { PROGRESS_COLOR };
+                cr.set_source_rgb(r, g, b);
+                cr.set_line_cap(gtk::cairo::LineCap::Round);
+                // Fills clockwise from the top
+                cr.arc(cx, cy, radius, -PI / 2.0, -PI / 2.0 + fraction * 2.0 * PI);
+                let _ = cr.stroke();
+            }
+        });
+
+        Self {
+            container,
+            area,
+            fraction,
+        }
+    }
+
+    /// Shows how far along the goal is, given as a fraction from 0 to 1,
+    /// with `tooltip` describing it. Redraws only when the ring changes.
+    pub fn update(&self, fraction: f64, tooltip: &str) {
+        self.container.set_tooltip_text(Some(tooltip));
+        if (self.fraction.get() - fraction).abs() > f64::EPSILON {
+            self.fraction.set(fraction);
+            self.area.queue_draw();
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use adw::prelude::*;
+use chrono::{Local, NaiveDate};
+use gtk4::glib;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::{
+    load_entry_projects, preserve_scroll_position, show_in_entries_section, update_day_sections,
+    AppState, ViewMode, WeekView,
+};
+use crate::db;
+
+/// Entries loaded at a time while scrolling back through the history
+const PAGE_SIZE: usize = 200;
+
+/// Distance from the bottom of the list, in pixels, at which older entries are loaded
+const LOAD_MORE_THRESHOLD: f64 = 400.0;
+
+/// Widgets of the All view and the entries loaded so far
+pub struct HistoryView {
+    view: WeekView,
+    /// Loaded entries, newest first; older pages are appended while scrolling
+    entries: Vec<db::TimeEntry>,
+    /// Set once the oldest entry has been loaded
+    exhausted: bool,
+}
+
+impl HistoryView {
+    fn new(state: &Rc<RefCell<AppState>>) -> Self {
+        let view = WeekView::new();
+        view.week_label.set_label("All Entries");
+        view.empty_label.set_label("No entries yet");
+        view.breakdown_slot.set_visible(false);
+        view.copy_button.set_visible(false);
+
+        // Load older entries when scrolled close to the end, or when the loaded
+        // days don't fill the window yet
+        let adjustment = view.scrolled_window.vadjustment();
+        let state_for_value = state.clone();
+        adjustment.connect_value_changed(move |_| schedule_load_more(state_for_value.clone()));
+        let state_for_upper = state.clone();
+        adjustment.connect_upper_notify(move |_| schedule_load_more(state_for_upper.clone()));
+
+        Self {
+            view,
+            entries: Vec::new(),
+            exhausted: false,
+        }
+    }
+
+    /// Day of the oldest loaded entry
+    pub fn oldest_day(&self) -> Option<NaiveDate> {
+        self.entries
+            .last()
+            .map(|entry| entry.start_time.with_timezone(&Local).date_naive())
+    }
+
+    fn near_bottom(&self) -> bool {
+        let adjustment = self.view.scrolled_window.vadjustment();
+        adjustment.value() + adjustment.page_size() >= adjustment.upper() - LOAD_MORE_THRESHOLD
+    }
+
+    /// Shows the loaded entries grouped by day, most recent first
+    fn update(&mut self, state: &AppState) {
+        let entries: Vec<db::TimeEntry> = self
+            .entries
+            .iter()
+            .filter(|e| state.entry_filter_matches(e))
+            .cloned()
+            .collect();
+        let projects = load_entry_projects(&state.db_conn, &entries);
+
+        self.view.total_label.set_label(&if self.exhausted {
+            format!("{} entries", entries.len())
+        } else {
+            format!("{} most recent entries, scroll for older ones", entries.len())
+        });
+
+        let mut entries_by_day: HashMap<NaiveDate, Vec<db::TimeEntry>> = HashMap::new();
+        for entry in entries {
+            let date = entry.start_time.with_timezone(&Local).date_naive();
+            entries_by_day.entry(date).or_default().push(entry);
+        }
+        let mut days: Vec<_> = entries_by_day.keys().cloned().collect();
+        days.sort_by(|a, b| b.cmp(a));
+
+        self.view.empty_label.set_visible(days.is_empty());
+
+        let scrolled_window = self.view.scrolled_window.clone();
+        preserve_scroll_position(&scrolled_window, || {
+            update_day_sections(&mut self.view, &days, &entries_by_day, &projects, state);
+        });
+    }
+}
+
+/// Refreshes the entries section for the All view, reloading the entries
+/// loaded so far so edits to any of them show up
+pub fn refresh_history_view(state: Rc<RefCell<AppState>>) {
+    let existing = state.borrow_mut().history_view.take();
+    let mut history_view = existing.unwrap_or_else(|| HistoryView::new(&state));
+    let state_borrow = state.borrow();
+    show_in_entries_section(&state_borrow.entries_section, &history_view.view.container);
+
+    let limit = history_view.entries.len().max(PAGE_SIZE);
+    match db::get_entries_page(&state_borrow.db_conn, None, limit) {
+        Ok(entries) => {
+            history_view.exhausted = entries.len() < limit;
+            history_view.entries = entries;
+        }
+        Err(e) => state_borrow.show_error(&format!("Failed to load entries: {}", e)),
+    }
+    history_view.update(&state_borrow);
+
+    drop(state_borrow);
+    state.borrow_mut().history_view = Some(history_view);
+}
+
+/// Loads the next page of older entries from an idle callback, outside of
+/// any refresh in progress
+fn schedule_load_more(state: Rc<RefCell<AppState>>) {
+    glib::idle_add_local_once(move || load_more(state));
+}
+
+/// Appends the next page of older entries while the All view is shown and
+/// scrolled close to its end
+fn load_more(state: Rc<RefCell<AppState>>) {
+    let Ok(mut state_mut) = state.try_borrow_mut() else {
+        return;
+    };
+    if state_mut.view_mode != ViewMode::All {
+        return;
+    }
+    // Missing while the view is being refreshed
+    let Some(mut history_view) = state_mut.history_view.take() else {
+        return;
+    };
+    drop(state_mut);
+
+    let state_borrow = state.borrow();
+    let mut loaded_more = false;
+    if !history_view.exhausted && history_view.near_bottom() {
+        match db::get_entries_page(&state_borrow.db_conn, history_view.entries.last(), PAGE_SIZE) {
+            Ok(page) => {
+                history_view.exhausted = page.len() < PAGE_SIZE;
+                history_view.entries.extend(page);
+                history_view.update(&state_borrow);
+                loaded_more = true;
+            }
+            Err(e) => state_borrow.show_error(&format!("Failed to load entries: {}", e)),
+        }
+    }
+
+    drop(state_borrow);
+    state.borrow_mut().history_view = Some(history_view);
+
+    // A filtered page may add no rows, so the list doesn't grow to trigger another load
+    if loaded_more {
+        if let Some(ref sidebar) = state.borrow().project_sidebar {
+            sidebar.update(&state.borrow());
+        }
+        schedule_load_more(state);
+    }
+}
@@ -0,0 +1,318 @@
+use adw::prelude::*;
+use chrono::{Local, NaiveDate};
+use gtk4 as gtk;
+use gtk4::gio;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+use crate::db;
+use crate::import::{self, Column};
+use crate::lock;
+
+/// Finds a project by name, ignoring case
+fn find_project<'a>(projects: &'a [db::Project], name: &str) -> Option<&'a db::Project> {
+    projects.iter().find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Reads the clipboard and opens the import dialog if it holds delimited rows
+pub fn paste_entries(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
+    let window_for_paste = window.clone();
+    window.clipboard().read_text_async(None::<&gio::Cancellable>, move |result| {
+        match result {
+            Ok(Some(text)) if text.contains('\t') || text.lines().count() > 1 => {
+                show_import_dialog(state, &window_for_paste, &text);
+            }
+            Ok(_) => state
+                .borrow()
+                .show_info("Clipboard doesn't contain spreadsheet rows"),
+            Err(e) => state
+                .borrow()
+                .show_error(&format!("Failed to read clipboard: {}", e)),
+        }
+    });
+}
+
+/// Parses all data rows with the current mapping
+fn parse_rows(
+    rows: &[Vec<String>],
+    columns: &[Column],
+    skip_header: bool,
+    projects: &[db::Project],
+    locked_until: Option<NaiveDate>,
+) -> Vec<Result<db::NewEntry, String>> {
+    let today = Local::now().date_naive();
+    rows.iter()
+        .skip(usize::from(skip_header))
+        .map(|cells| {
+            let parsed = import::parse_row(cells, columns, today)?;
+            if lock::is_locked(parsed.start_time, locked_until, &Local) {
+                return Err(format!(
+                    "{} is in the locked period ending {}",
+                    parsed.start_time.with_timezone(&Local).format("%b %d, %Y"),
+                    locked_until.unwrap_or_default().format("%b %d, %Y")
+                ));
+            }
+            let project_id = match parsed.project_name {
+                Some(ref name) => Some(
+                    find_project(projects, name)
+                        .map(|p| p.id)
+                        .ok_or_else(|| format!("Unknown project \"{}\"", name))?,
+                ),
+                None => None,
+            };
+            Ok(db::NewEntry {
+                project_id,
+                description: parsed.description,
+                start_time: parsed.start_time,
+                end_time: parsed.end_time,
+            })
+        })
+        .collect()
+}
+
+/// Rebuilds the preview list and the create button label
+fn refresh_preview(
+    state: &Rc<RefCell<AppState>>,
+    rows: &[Vec<String>],
+    columns: &[Column],
+    skip_header: bool,
+    preview_list: &gtk::ListBox,
+    create_button: &gtk::Button,
+) {
+    while let Some(child) = preview_list.first_child() {
+        preview_list.remove(&child);
+    }
+
+    let state_borrow = state.borrow();
+    let parsed = parse_rows(
+        rows,
+        columns,
+        skip_header,
+        &state_borrow.projects,
+        state_borrow.settings.locked_until,
+    );
+
+    for result in &parsed {
+        let label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+
+        match result {
+            Ok(entry) => {
+                let project_name = entry
+                    .project_id
+                    .and_then(|id| state_borrow.projects.iter().find(|p| p.id == id))
+                    .map(|p| format!("  •  {}", p.name))
+                    .unwrap_or_default();
+                label.set_label(&format!(
+                    "{} - {}  {}{}",
+                    entry.start_time.with_timezone(&Local).format("%b %d %H:%M"),
+                    entry.end_time.with_timezone(&Local).format("%H:%M"),
+                    if entry.description.is_empty() {
+                        "(no description)"
+                    } else {
+                        &entry.description
+                    },
+                    project_name
+                ));
+            }
+            Err(message) => {
+                label.set_label(message);
+                label.add_css_class("error");
+            }
+        }
+        preview_list.append(&label);
+    }
+
+    let valid = parsed.iter().filter(|r| r.is_ok()).count();
+    create_button.set_label(&format!("Create {} Entries", valid));
+    create_button.set_sensitive(valid > 0);
+}
+
+/// Shows the mapping and preview dialog for pasted delimited rows
+pub fn show_import_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow, text: &str) {
+    let rows = import::split_rows(text, import::detect_delimiter(text));
+    if rows.is_empty() {
+        return;
+    }
+    let (guessed_columns, has_header) = import::guess_columns(&rows);
+
+    let dialog = adw::Window::builder()
+        .title("Paste Entries")
+        .default_width(420)
+        .default_height(560)
+        .modal(true)
+        .transient_for(parent)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(0)
+        .build();
+
+    let header_bar = adw::HeaderBar::builder()
+        .show_end_title_buttons(true)
+        .title_widget(&adw::WindowTitle::new("Paste Entries", &format!("{} rows", rows.len())))
+        .build();
+    content.append(&header_bar);
+
+    let body = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+
+    let header_check = gtk::CheckButton::builder()
+        .label("First row is a header")
+        .active(has_header)
+        .build();
+    body.append(&header_check);
+
+    // Pasted rows may come from another tool's guesses, so they can wait in the review queue
+    let review_check = gtk::CheckButton::builder()
+        .label("Review entries before they count")
+        .build();
+    body.append(&review_check);
+
+    // Column mapping, one row per column with a sample value
+    let mapping_list = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let labels: Vec<&str> = Column::ALL.iter().map(|c| c.label()).collect();
+    let sample_row = rows.get(usize::from(has_header)).unwrap_or(&rows[0]);
+    let mut column_rows = Vec::new();
+    for (index, column) in guessed_columns.iter().enumerate() {
+        let column_row = adw::ComboRow::builder()
+            .title(format!("Column {}", index + 1))
+            .subtitle(sample_row.get(index).map(|s| s.as_str()).unwrap_or(""))
+            .model(&gtk::StringList::new(&labels))
+            .selected(Column::ALL.iter().position(|c| c == column).unwrap_or(0) as u32)
+            .build();
+        mapping_list.append(&column_row);
+        column_rows.push(column_row);
+    }
+    body.append(&mapping_list);
+
+    let preview_title = gtk::Label::builder()
+        .label("Preview")
+        .halign(gtk::Align::Start)
+        .css_classes(["heading"])
+        .build();
+    body.append(&preview_title);
+
+    let preview_list = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    body.append(&preview_list);
+
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .vexpand(true)
+        .child(&body)
+        .build();
+    content.append(&scrolled_window);
+
+    let create_button = gtk::Button::builder()
+        .css_classes(["suggested-action"])
+        .halign(gtk::Align::End)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_bottom(12)
+        .build();
+    content.append(&create_button);
+
+    let rows = Rc::new(rows);
+    let column_rows = Rc::new(column_rows);
+    let current_columns = {
+        let column_rows = column_rows.clone();
+        move || -> Vec<Column> {
+            column_rows
+                .iter()
+                .map(|row| Column::ALL.get(row.selected() as usize).copied().unwrap_or(Column::Ignore))
+                .collect()
+        }
+    };
+    let current_columns = Rc::new(current_columns);
+
+    let update_preview = {
+        let state = state.clone();
+        let rows = rows.clone();
+        let header_check = header_check.clone();
+        let preview_list = preview_list.clone();
+        let create_button = create_button.clone();
+        let current_columns = current_columns.clone();
+        Rc::new(move || {
+            refresh_preview(
+                &state,
+                &rows,
+                &current_columns(),
+                header_check.is_active(),
+                &preview_list,
+                &create_button,
+            );
+        })
+    };
+
+    for column_row in column_rows.iter() {
+        let update_preview = update_preview.clone();
+        column_row.connect_selected_notify(move |_| update_preview());
+    }
+    let update_for_header = update_preview.clone();
+    header_check.connect_toggled(move |_| update_for_header());
+    update_preview();
+
+    let state_for_create = state.clone();
+    let parent_for_create = parent.clone();
+    let dialog_for_create = dialog.clone();
+    create_button.connect_clicked(move |_| {
+        let result = {
+            let state_borrow = state_for_create.borrow();
+            let entries: Vec<db::NewEntry> = parse_rows(
+                &rows,
+                &current_columns(),
+                header_check.is_active(),
+                &state_borrow.projects,
+                state_borrow.settings.locked_until,
+            )
+            .into_iter()
+            .filter_map(|r| r.ok())
+            .collect();
+            if review_check.is_active() {
+                db::create_review_entries(&state_borrow.db_conn, &entries)
+            } else {
+                db::create_completed_entries(&state_borrow.db_conn, &entries)
+            }
+        };
+
+        match result {
+            Ok(count) => {
+                let message = if review_check.is_active() {
+                    format!("Added {} entries to review", count)
+                } else {
+                    format!("Created {} entries", count)
+                };
+                state_for_create.borrow().show_info(&message);
+                refresh_view(state_for_create.clone(), &parent_for_create);
+                dialog_for_create.close();
+            }
+            Err(e) => state_for_create
+                .borrow()
+                .show_error(&format!("Failed to create entries: {}", e)),
+        }
+    });
+
+    dialog.set_content(Some(&content));
+    dialog.present();
+}
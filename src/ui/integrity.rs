@@ -0,0 +1,178 @@
+use adw::prelude::*;
+use chrono::{Local, Utc};
+use gtk4 as gtk;
+use gtk4::glib;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{compat, refresh_view, AppState};
+use crate::integrity::{self, Finding, Problem};
+
+/// How often the app looks whether the weekly check is due while it runs
+const DUE_CHECK_INTERVAL_SECS: u32 = 60 * 60;
+
+/// Runs the integrity check once it is due, at startup and then while the
+/// app keeps running. Problems are announced with a toast opening the report.
+pub fn start_weekly_check(state: Rc<RefCell<AppState>>) {
+    let run = move || {
+        let findings = {
+            let state = state.borrow();
+            let today = Local::now().date_naive();
+            if !integrity::is_check_due(&state.db_conn, today) {
+                return;
+            }
+            let findings = integrity::check(&state.db_conn, Utc::now());
+            if let Err(e) = integrity::mark_checked(&state.db_conn, today) {
+                eprintln!("Failed to record the integrity check: {}", e);
+            }
+            match findings {
+                Ok(findings) => findings,
+                Err(e) => {
+                    eprintln!("Failed to check data integrity: {}", e);
+                    return;
+                }
+            }
+        };
+        if findings.is_empty() {
+            return;
+        }
+
+        let state_borrow = state.borrow();
+        let (Some(overlay), Some(window)) = (&state_borrow.toast_overlay, &state_borrow.window) else {
+            return;
+        };
+        let state = state.clone();
+        let window = window.clone();
+        compat::add_action_toast(
+            overlay,
+            &format!("Found {} entries needing repair", findings.len()),
+            "Review",
+            move || show_integrity_dialog(state.clone(), &window, findings.clone()),
+        );
+    };
+
+    glib::idle_add_local_once(run.clone());
+    glib::timeout_add_seconds_local(DUE_CHECK_INTERVAL_SECS, move || {
+        run();
+        glib::ControlFlow::Continue
+    });
+}
+
+/// Checks the data now, whether or not the weekly check is due, and shows
+/// the report
+pub fn check_now(state: Rc<RefCell<AppState>>, parent: &impl IsA<gtk::Window>) {
+    let findings = {
+        let state = state.borrow();
+        let findings = integrity::check(&state.db_conn, Utc::now());
+        let _ = integrity::mark_checked(&state.db_conn, Local::now().date_naive());
+        findings
+    };
+    match findings {
+        Ok(findings) => show_integrity_dialog(state, parent, findings),
+        Err(e) => state.borrow().show_failure("check data integrity", &e),
+    }
+}
+
+/// Applies the repair offered for `finding`. The running entry is stopped
+/// through the app state, so the timer and plugins see it stop.
+fn repair(state: &Rc<RefCell<AppState>>, finding: &Finding) -> bool {
+    let mut state = state.borrow_mut();
+    let running = state
+        .running_entry
+        .as_ref()
+        .filter(|entry| entry.id == finding.entry_id)
+        .map(|entry| entry.start_time);
+    if let (Problem::StaleRunning, Some(start_time)) = (finding.problem, running) {
+        return state.stop_timer_at(integrity::end_of_start_day(start_time, &Local), None);
+    }
+    match integrity::repair(&state.db_conn, finding, &Local) {
+        Ok(()) => true,
+        Err(e) => {
+            state.show_failure("repair entry", &e);
+            false
+        }
+    }
+}
+
+/// Shows the entries the integrity check found, each with a button applying
+/// its repair
+pub fn show_integrity_dialog(state: Rc<RefCell<AppState>>, parent: &impl IsA<gtk::Window>, findings: Vec<Finding>) {
+    let dialog = adw::Window::builder()
+        .title("Data Integrity")
+        .default_width(420)
+        .default_height(420)
+        .modal(true)
+        .transient_for(parent)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(0)
+        .build();
+
+    let header_bar = adw::HeaderBar::builder()
+        .show_end_title_buttons(true)
+        .title_widget(&adw::WindowTitle::new("Data Integrity", "Entries needing repair"))
+        .build();
+    content.append(&header_bar);
+
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .vexpand(true)
+        .build();
+
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+    let empty_label = gtk::Label::builder()
+        .label("No problems found")
+        .css_classes(["dim-label"])
+        .margin_top(20)
+        .margin_bottom(20)
+        .build();
+    list_box.set_placeholder(Some(&empty_label));
+
+    scrolled_window.set_child(Some(&list_box));
+    content.append(&scrolled_window);
+
+    for finding in findings {
+        let description = if finding.description.is_empty() {
+            "(no description)"
+        } else {
+            finding.description.as_str()
+        };
+        let row = adw::ActionRow::builder()
+            .title(finding.problem.label())
+            .subtitle(format!("{} · {}", description, finding.start_time))
+            .build();
+
+        let repair_button = gtk::Button::builder()
+            .label(finding.problem.repair_label())
+            .valign(gtk::Align::Center)
+            .build();
+        let state = state.clone();
+        let list_box_for_repair = list_box.clone();
+        let row_for_repair = row.clone();
+        repair_button.connect_clicked(move |_| {
+            if !repair(&state, &finding) {
+                return;
+            }
+            list_box_for_repair.remove(&row_for_repair);
+            let window = state.borrow().window.clone();
+            if let Some(window) = window {
+                refresh_view(state.clone(), &window);
+            }
+        });
+        row.add_suffix(&repair_button);
+        list_box.append(&row);
+    }
+
+    dialog.set_content(Some(&content));
+    dialog.present();
+}
@@ -0,0 +1,112 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::list_diff;
+
+/// A list box whose rows are keyed (by entry id, for instance) and only
+/// rebuilt when what they display changes. Unchanged rows keep their
+/// widgets, so focus and scroll position survive a refresh.
+/// Clones share the same rows.
+#[derive(Clone)]
+pub struct KeyedListBox<K> {
+    list_box: gtk::ListBox,
+    rows: Rc<RefCell<KeyedRows<K>>>,
+}
+
+/// Rows currently shown and the fingerprints they were built from
+struct KeyedRows<K> {
+    widgets: HashMap<K, gtk::ListBoxRow>,
+    fingerprints: Vec<(K, u64)>,
+}
+
+impl<K: Copy + Eq + Hash> KeyedListBox<K> {
+    /// Wraps a list box, showing `empty_text` while it has no rows
+    pub fn new(list_box: gtk::ListBox, empty_text: &str) -> Self {
+        let empty_label = gtk::Label::builder()
+            .label(empty_text)
+            .css_classes(["dim-label"])
+            .margin_top(20)
+            .margin_bottom(20)
+            .build();
+        list_box.set_placeholder(Some(&empty_label));
+
+        Self {
+            list_box,
+            rows: Rc::new(RefCell::new(KeyedRows {
+                widgets: HashMap::new(),
+                fingerprints: Vec::new(),
+            })),
+        }
+    }
+
+    /// The wrapped list box
+    pub fn list_box(&self) -> &gtk::ListBox {
+        &self.list_box
+    }
+
+    /// The row currently shown for `key`
+    pub fn row(&self, key: K) -> Option<gtk::ListBoxRow> {
+        self.rows.borrow().widgets.get(&key).cloned()
+    }
+
+    /// The key of a row shown by this list
+    pub fn key_of(&self, row: &gtk::ListBoxRow) -> Option<K> {
+        self.rows
+            .borrow()
+            .widgets
+            .iter()
+            .find(|(_, widget)| *widget == row)
+            .map(|(key, _)| *key)
+    }
+
+    /// Brings the list in line with `items`, given as `(key, fingerprint)` pairs
+    /// in display order. `build` is only called for new or changed rows.
+    pub fn update(&self, items: &[(K, u64)], mut build: impl FnMut(K) -> gtk::ListBoxRow) {
+        let mut rows = self.rows.borrow_mut();
+        let diff = list_diff::diff(&rows.fingerprints, items);
+
+        // Remember which row holds keyboard focus, it may be removed or rebuilt below
+        let focused_key = self
+            .list_box
+            .focus_child()
+            .and_then(|child| rows.widgets.iter().find(|(_, row)| child == **row).map(|(k, _)| *k));
+        let old_keys: Vec<K> = rows.fingerprints.iter().map(|(k, _)| *k).collect();
+
+        for key in diff.removed.iter().chain(&diff.changed) {
+            if let Some(row) = rows.widgets.remove(key) {
+                self.list_box.remove(&row);
+            }
+        }
+
+        for (position, (key, _)) in items.iter().enumerate() {
+            let row = rows.widgets.entry(*key).or_insert_with(|| build(*key)).clone();
+            let position = position as i32;
+
+            if self.list_box.row_at_index(position).as_ref() != Some(&row) {
+                if row.parent().is_some() {
+                    self.list_box.remove(&row);
+                }
+                self.list_box.insert(&row, position);
+            }
+        }
+
+        rows.fingerprints = items.to_vec();
+
+        // Move focus to the rebuilt row, or to the next one when the focused row is gone
+        if let Some(focused_key) = focused_key {
+            let new_keys: Vec<K> = items.iter().map(|(k, _)| *k).collect();
+            let still_focused = self.list_box.focus_child().is_some();
+            if !still_focused {
+                if let Some(row) = list_diff::focus_target(&old_keys, focused_key, &new_keys)
+                    .and_then(|index| self.list_box.row_at_index(index as i32))
+                {
+                    row.grab_focus();
+                }
+            }
+        }
+    }
+}
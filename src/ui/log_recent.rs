@@ -0,0 +1,108 @@
+use adw::prelude::*;
+use chrono::{Duration, Local, Utc};
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+use crate::db;
+use crate::smart_syntax;
+
+/// Opens a small prompt logging the block of `minutes` that just ended,
+/// such as a phone call, as a finished entry ending now. Asked from the
+/// tray, so it only sits on the main window when that is shown.
+pub fn show_log_recent_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow, minutes: u32) {
+    let title = format!("Log Last {} Minutes", minutes);
+    let dialog = adw::Window::builder()
+        .title(&title)
+        .default_width(360)
+        .resizable(false)
+        .build();
+    if parent.is_visible() {
+        dialog.set_transient_for(Some(parent));
+        dialog.set_modal(true);
+    } else if let Some(app) = parent.application() {
+        dialog.set_application(Some(&app));
+    }
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(18)
+        .margin_bottom(18)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+    let now = Local::now();
+    content.append(
+        &gtk::Label::builder()
+            .label(format!(
+                "From {} until now",
+                (now - Duration::minutes(minutes.into())).format("%H:%M")
+            ))
+            .xalign(0.0)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+
+    let description_entry = gtk::Entry::builder()
+        .placeholder_text("Description (@project sets the project)")
+        .activates_default(true)
+        .build();
+    content.append(&description_entry);
+
+    let mut labels = vec!["No Project".to_string()];
+    labels.extend(state.borrow().projects.iter().map(|project| project.name.clone()));
+    let project_dropdown = gtk::DropDown::from_strings(&labels.iter().map(|label| label.as_str()).collect::<Vec<_>>());
+    content.append(&project_dropdown);
+
+    let buttons_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .build();
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let log_button = gtk::Button::builder()
+        .label("Log")
+        .css_classes(["suggested-action"])
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&log_button);
+    content.append(&buttons_box);
+
+    dialog.set_content(Some(&content));
+    dialog.set_default_widget(Some(&log_button));
+
+    let dialog_for_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_for_cancel.close());
+
+    let dialog_for_log = dialog.clone();
+    let window = parent.clone();
+    let description_entry_for_log = description_entry.clone();
+    log_button.connect_clicked(move |_| {
+        // The block ends when it is logged, not when the prompt opened
+        let end_time = Utc::now();
+        let new_entry = {
+            let state = state.borrow();
+            let parsed = smart_syntax::parse_description(&description_entry_for_log.text(), &state.projects);
+            let selected = project_dropdown.selected() as usize;
+            let selected_project = selected
+                .checked_sub(1)
+                .and_then(|index| state.projects.get(index))
+                .map(|project| project.id);
+            db::NewEntry {
+                project_id: parsed.project_id.or(selected_project),
+                description: parsed.description,
+                start_time: end_time - Duration::minutes(minutes.into()),
+                end_time,
+            }
+        };
+        if state.borrow_mut().log_entry(&new_entry) {
+            dialog_for_log.close();
+            refresh_view(state.clone(), &window);
+        }
+    });
+
+    dialog.present();
+    description_entry.grab_focus();
+}
@@ -0,0 +1,226 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use gtk4::glib;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+
+/// Small detached window showing only the elapsed time, the description
+/// and the start/stop button. It isn't transient for the main window, so it
+/// stays open while the main window is hidden to the tray. GTK 4 can't ask
+/// the compositor to keep a window above others, so "always on top" is left
+/// to the window manager (e.g. Super+right click in GNOME). Likewise the
+/// monitor and corner it sits in are the compositor's to choose; the window
+/// manager's own placement rules (or snapping it with Super+arrows) apply.
+pub struct MiniTimer {
+    window: adw::Window,
+    elapsed_label: gtk::Label,
+    description_label: gtk::Label,
+    toggle_button: gtk::Button,
+    close_button: gtk::Button,
+    click_through: Cell<bool>,
+}
+
+impl MiniTimer {
+    /// Lets clicks through to the windows below and hides the buttons, or
+    /// makes the mini timer interactive again
+    pub fn set_click_through(&self, click_through: bool) {
+        self.click_through.set(click_through);
+        self.toggle_button.set_visible(!click_through);
+        self.close_button.set_visible(!click_through);
+        self.window.set_can_focus(!click_through);
+        self.apply_input_region();
+    }
+
+    /// Empties the surface's input region in click-through mode, or gives it
+    /// back the whole window. GTK recomputes the region when the window is
+    /// resized, so this is repeated on every update.
+    fn apply_input_region(&self) {
+        let Some(surface) = self.window.surface() else {
+            return;
+        };
+        let region = if self.click_through.get() {
+            gtk::cairo::Region::create()
+        } else {
+            gtk::cairo::Region::create_rectangle(&gtk::cairo::RectangleInt::new(
+                0,
+                0,
+                surface.width(),
+                surface.height(),
+            ))
+        };
+        surface.set_input_region(&region);
+    }
+
+    /// Updates the displayed values from the application state
+    pub fn update(&self, state: &AppState) {
+        if self.click_through.get() {
+            self.apply_input_region();
+        }
+        match &state.running_entry {
+            Some(entry) => {
+                self.elapsed_label.set_label(&state.format_elapsed(entry.start_time));
+                self.description_label.set_label(if entry.description.is_empty() {
+                    "(no description)"
+                } else {
+                    &entry.description
+                });
+                self.toggle_button.set_icon_name("media-playback-stop-symbolic");
+                self.toggle_button.set_tooltip_text(Some("Stop timer"));
+                self.toggle_button.remove_css_class("suggested-action");
+                self.toggle_button.add_css_class("destructive-action");
+            }
+            None => {
+                self.elapsed_label.set_label("00:00:00");
+                self.description_label.set_label("Not tracking");
+                self.toggle_button.set_icon_name("media-playback-start-symbolic");
+                self.toggle_button.set_tooltip_text(Some("Start timer"));
+                self.toggle_button.remove_css_class("destructive-action");
+                self.toggle_button.add_css_class("suggested-action");
+            }
+        }
+    }
+}
+
+/// Remembers whether the mini timer is open, for it to reopen at startup.
+/// A read-only database can't keep it, which is not worth an error.
+fn remember_open(state: &Rc<RefCell<AppState>>, open: bool) {
+    let mut state_borrow = state.borrow_mut();
+    if state_borrow.settings.mini_timer_open == open {
+        return;
+    }
+    state_borrow.settings.mini_timer_open = open;
+    if let Err(e) = state_borrow.settings.save(&state_borrow.db_conn) {
+        eprintln!("Failed to remember the mini timer: {}", e);
+    }
+}
+
+/// Opens the mini timer, or closes it when it is already open
+pub fn toggle_mini_timer(state: Rc<RefCell<AppState>>, main_window: &adw::ApplicationWindow) {
+    let existing = state.borrow_mut().mini_timer.take();
+    if let Some(mini_timer) = existing {
+        mini_timer.window.close();
+        return;
+    }
+    open_mini_timer(state, main_window);
+}
+
+/// Reopens the mini timer when it was open at the last quit
+pub fn restore_mini_timer(state: Rc<RefCell<AppState>>, main_window: &adw::ApplicationWindow) {
+    if state.borrow().settings.mini_timer_open && state.borrow().mini_timer.is_none() {
+        open_mini_timer(state, main_window);
+    }
+}
+
+/// Applies the click-through preference to the open mini timer, if any
+pub fn apply_click_through(state: &AppState) {
+    if let Some(ref mini_timer) = state.mini_timer {
+        mini_timer.set_click_through(state.settings.mini_timer_click_through);
+    }
+}
+
+fn open_mini_timer(state: Rc<RefCell<AppState>>, main_window: &adw::ApplicationWindow) {
+
+    let window = adw::Window::builder()
+        .title("Timer")
+        .default_width(260)
+        .resizable(false)
+        .build();
+    if let Some(app) = main_window.application() {
+        window.set_application(Some(&app));
+    }
+
+    let hbox = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(10)
+        .margin_top(8)
+        .margin_bottom(8)
+        .margin_start(12)
+        .margin_end(8)
+        .build();
+
+    let text_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(2)
+        .hexpand(true)
+        .valign(gtk::Align::Center)
+        .build();
+
+    let elapsed_label = gtk::Label::builder()
+        .halign(gtk::Align::Start)
+        .css_classes(["mini-timer-display"])
+        .build();
+    text_box.append(&elapsed_label);
+
+    let description_label = gtk::Label::builder()
+        .halign(gtk::Align::Start)
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .max_width_chars(20)
+        .css_classes(["dim-label", "caption"])
+        .build();
+    text_box.append(&description_label);
+    hbox.append(&text_box);
+
+    let toggle_button = gtk::Button::builder()
+        .css_classes(["circular"])
+        .valign(gtk::Align::Center)
+        .build();
+    hbox.append(&toggle_button);
+
+    let close_button = gtk::Button::builder()
+        .icon_name("window-close-symbolic")
+        .tooltip_text("Close mini timer")
+        .css_classes(["flat", "circular"])
+        .valign(gtk::Align::Center)
+        .build();
+    hbox.append(&close_button);
+
+    // The whole window can be dragged since it has no title bar
+    let handle = gtk::WindowHandle::builder()
+        .child(&hbox)
+        .build();
+    window.set_content(Some(&handle));
+
+    let state_for_toggle = state.clone();
+    let main_window_for_toggle = main_window.clone();
+    toggle_button.connect_clicked(move |_| {
+        if state_for_toggle.borrow_mut().toggle_timer() {
+            refresh_view(state_for_toggle.clone(), &main_window_for_toggle);
+        }
+    });
+
+    let window_for_close = window.clone();
+    close_button.connect_clicked(move |_| window_for_close.close());
+
+    let state_for_close = state.clone();
+    window.connect_close_request(move |_| {
+        state_for_close.borrow_mut().mini_timer = None;
+        remember_open(&state_for_close, false);
+        glib::Propagation::Proceed
+    });
+
+    // The input region lives on the surface, which only exists once realized
+    let state_for_realize = state.clone();
+    window.connect_realize(move |_| apply_click_through(&state_for_realize.borrow()));
+
+    let mini_timer = MiniTimer {
+        window: window.clone(),
+        elapsed_label,
+        description_label,
+        toggle_button,
+        close_button,
+        click_through: Cell::new(false),
+    };
+    mini_timer.set_click_through(state.borrow().settings.mini_timer_click_through);
+    mini_timer.update(&state.borrow());
+    state.borrow_mut().mini_timer = Some(mini_timer);
+    remember_open(&state, true);
+
+    // A click-through timer is only shown, without being activated
+    if state.borrow().settings.mini_timer_click_through {
+        window.set_visible(true);
+    } else {
+        window.present();
+    }
+}
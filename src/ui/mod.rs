@@ -1,21 +1,112 @@
 use adw::prelude::*;
-use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use gtk4 as gtk;
-use gtk4::glib;
+use gtk4::{gio, glib};
 use rusqlite::Connection;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
+use crate::autostart;
 use crate::db;
-use crate::tray::TrayManager;
+use crate::dbus::{StatusService, StatusSnapshot};
+use crate::dnd::DoNotDisturb;
+use crate::duplicate::{self, DuplicateTarget};
+use crate::duration::{self, Rounding, TrackedDuration};
+use crate::error::{self, Conflict, TimeTrackingError};
+use crate::forecast;
+use crate::list_diff;
+use crate::lock;
+use crate::plugins::PluginEvent;
+use crate::privacy;
+use crate::profile;
+use crate::report::{self, Grouping, ReportRange};
+use crate::settings::{CloseAction, Settings, ViewDates};
+use crate::smart_syntax;
+use crate::tags;
+use crate::timeline;
+use crate::tray::{RecentEntry, TrayCallback, TrayCallbacks, TrayManager, TrayUnavailable, RECENT_ENTRIES_LIMIT};
+use burndown_chart::BurndownChart;
+use date_picker::DatePicker;
+use day_timeline::DayTimeline;
+use donut_chart::DonutChart;
+use goal_ring::GoalRing;
+use history::HistoryView;
+use keyed_list::KeyedListBox;
+use mini_timer::MiniTimer;
+use month_view::MonthView;
+use sidebar::{ProjectFilter, ProjectSidebar};
+
+mod absence;
+mod adaptive;
+mod assign;
+mod burndown_chart;
+mod cleanup;
+mod compat;
+mod date_picker;
+mod day_timeline;
+mod donut_chart;
+mod export;
+mod goal_ring;
+mod history;
+mod import;
+mod integrity;
+mod keyed_list;
+mod log_recent;
+mod mini_timer;
+mod month_view;
+mod new_project;
+mod palette;
+mod pinned;
+mod plugins;
+mod preferences;
+mod profiles;
+mod project_import;
+mod reassign;
+mod reminders;
+mod reports;
+mod review;
+mod search;
+mod sidebar;
+mod stop_at;
+mod tag_filter;
+mod templates;
+mod widget;
+mod year_review;
+
+/// Window action starting the timer at a Unix timestamp, used to accept a corrected start time
+const START_TIMER_AT_ACTION: &str = "start-timer-at";
+
+/// Window action hiding or showing a project in the charts, by chart key
+const TOGGLE_CHART_PROJECT_ACTION: &str = "toggle-chart-project";
 
 /// View mode for the entries list
 #[derive(Clone, Copy, PartialEq)]
 pub enum ViewMode {
     Today,
     Week,
+    /// Calendar of one month, days tinted by their total
+    Month,
+    /// Days between two dates picked by the user
+    Custom,
+    /// Every entry, older days loaded while scrolling
+    All,
+}
+
+/// Key of a row in the Today list, which shows absences among the entries
+/// and a marker dividing the past from what is still to come. When grouped
+/// by project, headers with subtotals precede each project's entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TodayRow {
+    Entry(i64),
+    Absence(i64),
+    Now,
+    /// Header of a project's entries, `None` for No Project
+    ProjectHeader(Option<i64>),
+    /// Header of the absences, listed after the projects
+    AwayHeader,
 }
 
 /// Application state for managing timer
@@ -27,17 +118,74 @@ pub struct AppState {
     pub project_dropdown: gtk::DropDown,
     pub projects: Vec<db::Project>,
     pub db_conn: Connection,
-    pub entries_list_box: gtk::ListBox,
+    /// Today's entries and absences
+    pub today_list: KeyedListBox<TodayRow>,
     pub day_total_label: gtk::Label,
+    /// Today's entries drawn on a bar from midnight to midnight
+    pub today_timeline: DayTimeline,
+    /// Container of the Today view (day header and entries list)
+    pub today_view: gtk::Box,
+    /// Weekly view widgets, created the first time the view is shown
+    week_view: Option<WeekView>,
+    /// A day in the week shown by the Week view
+    pub shown_week: NaiveDate,
+    /// Month view widgets, created the first time the view is shown
+    month_view: Option<MonthView>,
+    /// A day in the month shown by the Month view
+    pub shown_month: NaiveDate,
+    /// Custom range view widgets, created the first time the view is shown
+    custom_view: Option<WeekView>,
+    /// Days shown by the Custom view
+    pub custom_range: ReportRange,
+    /// All view widgets and the entries loaded so far, created the first time the view is shown
+    history_view: Option<HistoryView>,
+    /// Search results widgets, created the first time something is searched
+    search_view: Option<WeekView>,
+    /// Text typed in the search bar; while set, matching entries replace the current view
+    pub search_text: String,
+    /// Set while a refresh is scheduled, so bursts of changes refresh once
+    refresh_pending: bool,
+    /// Detached mini timer window, while open
+    pub mini_timer: Option<MiniTimer>,
+    /// Project sidebar shown in the wide layout
+    pub project_sidebar: Option<ProjectSidebar>,
+    /// Entries shown in the Today and Week views, chosen from the project sidebar
+    pub project_filter: ProjectFilter,
+    /// Tags the shown entries must all have, lowercased, in the order they were added
+    pub tag_filter: Vec<String>,
+    /// Chips of the filtered tags, above the entries
+    pub tag_filter_box: Option<gtk::FlowBox>,
     pub window: Option<adw::ApplicationWindow>,
     pub view_mode: ViewMode,
-    pub view_toggle: gtk::Box,
     pub entries_section: gtk::Box,
     pub tray_manager: Option<Arc<Mutex<TrayManager>>>,
     pub toast_overlay: Option<adw::ToastOverlay>,
+    pub review_button: Option<gtk::Button>,
+    pub settings: Settings,
+    pub status_service: Option<StatusService>,
+    /// Sum of today's completed entries, refreshed with the view
+    pub today_completed_seconds: i64,
+    /// Projects hidden from the charts by clicking their legend, for this session only
+    pub hidden_chart_projects: HashSet<Option<i64>>,
+    /// Whether Manage Projects lists archived projects too, for this session only
+    pub show_archived_projects: bool,
+    /// The desktop's Do Not Disturb, turned on while deep work is tracked
+    pub do_not_disturb: DoNotDisturb,
+    /// Stops the running timer at an earlier time, shown only while it runs
+    pub stop_at_button: Option<gtk::Button>,
+    /// Tasks started with one click from the chips under the timer and the tray
+    pub pinned_tasks: Vec<db::PinnedTask>,
+    /// Chips of the pinned tasks, under the description and project
+    pub pinned_tasks_box: Option<gtk::FlowBox>,
+    /// Whether the next started entry is private, as set by a template
+    pub start_private: bool,
+    pub goal_ring: Option<GoalRing>,
+    /// Queue of events for enabled plugins, set once the plugin host runs
+    pub plugin_queue: Option<plugins::PluginQueue>,
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         timer_label: gtk::Label,
         start_stop_button: gtk::Button,
@@ -47,9 +195,21 @@ impl AppState {
         db_conn: Connection,
         entries_list_box: gtk::ListBox,
         day_total_label: gtk::Label,
-        view_toggle: gtk::Box,
+        today_timeline: DayTimeline,
+        today_view: gtk::Box,
         entries_section: gtk::Box,
     ) -> Self {
+        let settings = Settings::load(&db_conn);
+        let today = Local::now().date_naive();
+        let view_dates = settings
+            .remember_view_dates
+            .then(|| ViewDates::load(&db_conn))
+            .flatten()
+            .unwrap_or(ViewDates {
+                week: today,
+                month: today,
+                custom_range: ReportRange::month_of(today),
+            });
         Self {
             running_entry: None,
             timer_label,
@@ -58,14 +218,103 @@ impl AppState {
             project_dropdown,
             projects,
             db_conn,
-            entries_list_box,
+            today_list: KeyedListBox::new(entries_list_box, "No entries for today"),
             day_total_label,
+            today_timeline,
+            today_view,
+            week_view: None,
+            shown_week: view_dates.week,
+            month_view: None,
+            shown_month: view_dates.month,
+            custom_view: None,
+            custom_range: view_dates.custom_range,
+            history_view: None,
+            search_view: None,
+            search_text: String::new(),
+            refresh_pending: false,
+            mini_timer: None,
+            project_sidebar: None,
+            project_filter: ProjectFilter::All,
+            tag_filter: Vec::new(),
+            tag_filter_box: None,
             window: None,
             view_mode: ViewMode::Today,
-            view_toggle,
             entries_section,
             tray_manager: None,
             toast_overlay: None,
+            review_button: None,
+            settings,
+            status_service: None,
+            today_completed_seconds: 0,
+            hidden_chart_projects: HashSet::new(),
+            show_archived_projects: false,
+            do_not_disturb: DoNotDisturb::default(),
+            stop_at_button: None,
+            pinned_tasks: Vec::new(),
+            pinned_tasks_box: None,
+            start_private: false,
+            goal_ring: None,
+            plugin_queue: None,
+        }
+    }
+
+    /// Returns true when the entry passes the project and tag filters
+    pub fn entry_filter_matches(&self, entry: &db::TimeEntry) -> bool {
+        self.project_filter.matches(entry) && tags::matches_all(&entry.description, &self.tag_filter)
+    }
+
+    /// First and last day shown by the current view
+    pub fn view_range(&self) -> (NaiveDate, NaiveDate) {
+        match self.view_mode {
+            ViewMode::Today => {
+                let today = Local::now().date_naive();
+                (today, today)
+            }
+            ViewMode::Week => {
+                let range = ReportRange::week_of(self.shown_week);
+                (range.start, range.end)
+            }
+            ViewMode::Month => {
+                let range = ReportRange::month_of(self.shown_month);
+                (range.start, range.end)
+            }
+            ViewMode::Custom => (self.custom_range.start, self.custom_range.end),
+            ViewMode::All => {
+                // Totals cover the days loaded so far
+                let today = Local::now().date_naive();
+                let oldest = self.history_view.as_ref().and_then(HistoryView::oldest_day);
+                (oldest.unwrap_or(today).min(today), today)
+            }
+        }
+    }
+
+    /// Saves the dates shown by the Week, Month and Custom views, when they
+    /// are to be restored after a restart
+    pub fn save_view_dates(&self) {
+        if !self.settings.remember_view_dates {
+            return;
+        }
+        let view_dates = ViewDates {
+            week: self.shown_week,
+            month: self.shown_month,
+            custom_range: self.custom_range,
+        };
+        if let Err(e) = view_dates.save(&self.db_conn) {
+            self.show_error(&format!("Failed to save the shown dates: {}", e));
+        }
+    }
+
+    /// Turns Do Not Disturb on while a deep work project is tracked and back
+    /// off otherwise, when enabled in preferences
+    pub fn update_do_not_disturb(&mut self) {
+        let deep_work = self
+            .running_entry
+            .as_ref()
+            .and_then(|entry| entry.project_id)
+            .is_some_and(|id| self.projects.iter().any(|p| p.id == id && p.deep_work));
+        let active = self.settings.do_not_disturb && deep_work;
+        if let Err(e) = self.do_not_disturb.set_active(active) {
+            self.show_error(&format!("Failed to change Do Not Disturb: {}", e));
         }
     }
 
@@ -88,6 +337,19 @@ impl AppState {
         }
     }
 
+    /// Shows why `action`, such as "log entry", failed: the message itself
+    /// for what the user can act on, prefixed with the action otherwise
+    pub fn show_failure(&self, action: &str, e: &TimeTrackingError) {
+        match e {
+            TimeTrackingError::NotFound(_) | TimeTrackingError::Conflict(_) | TimeTrackingError::Validation(_) => {
+                self.show_error(&e.to_string())
+            }
+            TimeTrackingError::Io(_) | TimeTrackingError::Sqlite(_) => {
+                self.show_error(&format!("Failed to {}: {}", action, e))
+            }
+        }
+    }
+
     /// Shows an info toast message to the user
     pub fn show_info(&self, message: &str) {
         if let Some(ref overlay) = self.toast_overlay {
@@ -99,11 +361,26 @@ impl AppState {
         }
     }
 
+    /// Shows a toast offering to undo a change just made
+    pub fn show_undo(&self, message: &str, undo: impl Fn() + 'static) {
+        if let Some(ref overlay) = self.toast_overlay {
+            compat::add_undo_toast(overlay, message, undo);
+        }
+    }
+
     /// Sets the tray manager reference
     pub fn set_tray_manager(&mut self, tray_manager: Arc<Mutex<TrayManager>>) {
         self.tray_manager = Some(tray_manager);
     }
 
+    /// Whether a tray icon is shown, which can bring back a hidden window
+    pub fn has_tray(&self) -> bool {
+        self.tray_manager
+            .as_ref()
+            .and_then(|tray_manager| tray_manager.lock().ok().map(|manager| manager.is_started()))
+            .unwrap_or(false)
+    }
+
     /// Updates the system tray with current timer state
     pub fn update_tray(&self) {
         if let Some(ref tray_manager) = self.tray_manager {
@@ -116,16 +393,162 @@ impl AppState {
                 Some(entry) => entry.description.clone(),
                 None => String::new(),
             };
+            let running_seconds = self
+                .running_entry
+                .as_ref()
+                .map(|entry| duration::elapsed_seconds(entry.start_time, Utc::now()))
+                .unwrap_or(0);
+            let today_total = self
+                .settings
+                .duration_format
+                .format(self.today_completed_seconds + running_seconds);
 
             if let Ok(manager) = tray_manager.lock() {
-                manager.update(is_running, &elapsed, &description);
+                manager.set_click_action(self.settings.tray_click_action);
+                manager.update(is_running, running_seconds, &elapsed, &description, &today_total);
+            }
+        }
+    }
+
+    /// Offers the last few distinct tasks in the tray's "Recent" submenu
+    pub fn update_tray_recent(&self) {
+        let Some(ref tray_manager) = self.tray_manager else {
+            return;
+        };
+        let recent = match db::get_recent_entries(&self.db_conn, RECENT_ENTRIES_LIMIT) {
+            Ok(recent) => recent,
+            Err(e) => {
+                eprintln!("Failed to load recent entries for the tray: {}", e);
+                return;
+            }
+        };
+        let recent_entries = recent
+            .into_iter()
+            .map(|entry| {
+                let project = entry.project_id.and_then(|id| self.projects.iter().find(|p| p.id == id));
+                RecentEntry {
+                    label: match project {
+                        Some(project) => format!("{} · {}", entry.description, project.name),
+                        None => entry.description.clone(),
+                    },
+                    description: entry.description,
+                    project_id: entry.project_id,
+                }
+            })
+            .collect();
+        if let Ok(manager) = tray_manager.lock() {
+            manager.set_recent_entries(recent_entries);
+        }
+    }
+
+    /// Sets the review queue button reference
+    pub fn set_review_button(&mut self, review_button: gtk::Button) {
+        self.review_button = Some(review_button);
+    }
+
+    /// Updates the review queue badge with the number of entries needing review
+    pub fn update_review_badge(&self) {
+        if let Some(ref button) = self.review_button {
+            let count = db::count_review_entries(&self.db_conn).unwrap_or(0);
+            let badge = button
+                .child()
+                .and_then(|content| content.last_child())
+                .and_downcast::<gtk::Label>();
+            if let Some(badge) = badge {
+                badge.set_label(&count.to_string());
             }
+            button.set_visible(count > 0);
+        }
+    }
+
+    /// Sets the D-Bus status service
+    pub fn set_status_service(&mut self, status_service: StatusService) {
+        self.status_service = Some(status_service);
+    }
+
+    /// Recomputes the cached total of today's completed entries
+    pub fn reload_today_total(&mut self) {
+        let today = Local::now().date_naive();
+        let now = Utc::now();
+        self.today_completed_seconds = db::get_entries_for_date(&self.db_conn, today)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|e| e.end_time.is_some())
+                    .map(|e| duration::entry_seconds(e, now))
+                    .sum()
+            })
+            .unwrap_or(0);
+    }
+
+    /// Fills the ring around the start/stop button with today's tracked
+    /// time against the daily target
+    pub fn update_goal_ring(&self) {
+        let Some(ref goal_ring) = self.goal_ring else {
+            return;
+        };
+        let running_seconds = self
+            .running_entry
+            .as_ref()
+            .map(|entry| duration::elapsed_seconds(entry.start_time, Utc::now()))
+            .unwrap_or(0);
+        let tracked_seconds = self.today_completed_seconds + running_seconds;
+        let goal_seconds = self.settings.expected_daily_minutes as i64 * 60;
+        let format = self.settings.duration_format;
+        goal_ring.update(
+            forecast::goal_fraction(tracked_seconds, goal_seconds),
+            &format!(
+                "{} of {} daily target",
+                format.format(tracked_seconds),
+                format.format(goal_seconds)
+            ),
+        );
+    }
+
+    /// Publishes the current timer state on the D-Bus status interface
+    pub fn update_status_service(&self) {
+        if let Some(ref service) = self.status_service {
+            let elapsed_seconds = self
+                .running_entry
+                .as_ref()
+                .map(|entry| duration::elapsed_seconds(entry.start_time, Utc::now()))
+                .unwrap_or(0);
+            service.update(StatusSnapshot {
+                is_running: self.running_entry.is_some(),
+                // Other apps read the status, so private entries follow the privacy preference
+                description: self
+                    .running_entry
+                    .as_ref()
+                    .and_then(|entry| privacy::shared_entry(entry, self.settings.privacy_mode))
+                    .map(|entry| entry.description)
+                    .unwrap_or_default(),
+                elapsed_seconds,
+                today_total_seconds: self.today_completed_seconds + elapsed_seconds,
+            });
         }
     }
 
     /// Sets the window reference
     pub fn set_window(&mut self, window: adw::ApplicationWindow) {
         self.window = Some(window);
+        self.apply_density();
+    }
+
+    /// Applies the compact density preference to the window and timer section
+    pub fn apply_density(&self) {
+        let compact = self.settings.compact;
+        if let Some(ref window) = self.window {
+            if compact {
+                window.add_css_class("compact");
+            } else {
+                window.remove_css_class("compact");
+            }
+        }
+        self.timer_label.set_margin_top(if compact { 12 } else { 40 });
+        self.timer_label.set_margin_bottom(if compact { 8 } else { 20 });
+        if let Some(ref goal_ring) = self.goal_ring {
+            goal_ring.container.set_margin_bottom(if compact { 12 } else { 40 });
+        }
     }
 
     /// Gets the selected project_id from the dropdown
@@ -153,6 +576,12 @@ impl AppState {
         }
     }
 
+    /// Selects the default project from preferences, or "No Project" when
+    /// there is none or it was archived
+    pub fn reset_selected_project(&self) {
+        self.set_selected_project(self.settings.default_project_id);
+    }
+
     /// Updates the button appearance based on timer state
     pub fn update_button_appearance(&self) {
         if self.running_entry.is_some() {
@@ -166,25 +595,148 @@ impl AppState {
             self.start_stop_button.remove_css_class("destructive-action");
             self.start_stop_button.add_css_class("suggested-action");
         }
+        if let Some(ref button) = self.stop_at_button {
+            button.set_visible(self.running_entry.is_some());
+        }
+        if let Some(ref mini_timer) = self.mini_timer {
+            mini_timer.update(self);
+        }
     }
 
-    /// Starts a new time entry
-    /// Returns true if timer was started successfully
+    /// Starts a new time entry, or logs a finished one when the description
+    /// starts with a duration or time range, such as "1h30m standup"
+    /// Returns true if an entry was started or logged
     pub fn start_timer(&mut self) -> bool {
+        let text = self.description_entry.text();
+        match smart_syntax::parse_quick_time(&text) {
+            Some((quick, description)) => self.log_quick_entry(quick, description),
+            None => self.start_timer_at(Utc::now()),
+        }
+    }
+
+    /// Logs a finished entry from the quick syntax, with an "@Project"
+    /// mention in the description picking the project
+    /// Returns true if the entry was logged
+    fn log_quick_entry(&mut self, quick: smart_syntax::QuickTime, description: &str) -> bool {
+        let (start_time, end_time) = match smart_syntax::quick_entry_times(quick, Utc::now()) {
+            Ok(times) => times,
+            Err(e) => {
+                self.show_error(&e);
+                return false;
+            }
+        };
+
+        let parsed = smart_syntax::parse_description(description, &self.projects);
+        let new_entry = db::NewEntry {
+            project_id: parsed.project_id.or(self.get_selected_project_id()),
+            description: parsed.description,
+            start_time,
+            end_time,
+        };
+        if !self.log_entry(&new_entry) {
+            return false;
+        }
+        self.description_entry.set_text("");
+        self.reset_selected_project();
+        true
+    }
+
+    /// Logs a finished entry, unless it starts in the locked period or
+    /// overlaps another entry
+    /// Returns true if the entry was logged
+    pub fn log_entry(&mut self, new_entry: &db::NewEntry) -> bool {
+        match self.create_logged_entry(new_entry) {
+            Ok(()) => {
+                self.show_info(&format!(
+                    "Logged {} - {}",
+                    new_entry.start_time.with_timezone(&Local).format("%H:%M"),
+                    new_entry.end_time.with_timezone(&Local).format("%H:%M")
+                ));
+                true
+            }
+            Err(e) => {
+                self.show_failure("log entry", &e);
+                false
+            }
+        }
+    }
+
+    /// Refuses a change to an entry starting at `start_time` in the locked
+    /// period. Every path creating, changing or deleting entries checks this.
+    pub fn ensure_unlocked(&self, start_time: DateTime<Utc>) -> error::Result<()> {
+        if lock::is_locked(start_time, self.settings.locked_until, &Local) {
+            return Err(Conflict::Locked(self.settings.locked_until.unwrap_or_default()).into());
+        }
+        Ok(())
+    }
+
+    fn create_logged_entry(&self, new_entry: &db::NewEntry) -> error::Result<()> {
+        self.ensure_unlocked(new_entry.start_time)?;
+        if let Some(other) = db::find_overlapping_entry(&self.db_conn, new_entry.start_time, new_entry.end_time)? {
+            return Err(Conflict::Overlap(other.description).into());
+        }
+        db::create_completed_entry(&self.db_conn, new_entry)?;
+        Ok(())
+    }
+
+    /// Changes how long a completed entry lasted by moving its end time,
+    /// unless it is in the locked period or would then overlap another entry
+    /// Returns true if the entry changed and the list should be refreshed
+    pub fn set_entry_duration(&self, entry: &db::TimeEntry, seconds: i64) -> bool {
+        match self.change_entry_end(entry, entry.start_time + chrono::Duration::seconds(seconds)) {
+            Ok(()) => true,
+            Err(e) => {
+                self.show_failure("change duration", &e);
+                false
+            }
+        }
+    }
+
+    fn change_entry_end(&self, entry: &db::TimeEntry, end_time: DateTime<Utc>) -> error::Result<()> {
+        self.ensure_unlocked(entry.start_time)?;
+        if let Some(other) = db::find_overlapping_entry_except(&self.db_conn, Some(entry.id), entry.start_time, end_time)? {
+            return Err(Conflict::Overlap(other.description).into());
+        }
+        db::stop_entry(&self.db_conn, entry.id, end_time)
+    }
+
+    /// Starts a new time entry at `start_time`. A start inside the locked period,
+    /// which usually means the system clock is wrong, is blocked with a dialog
+    /// offering the first unlocked time instead. Returns true if started.
+    pub fn start_timer_at(&mut self, start_time: DateTime<Utc>) -> bool {
+        if self.ensure_unlocked(start_time).is_err() {
+            self.show_locked_start_dialog(start_time);
+            return false;
+        }
+
         // Add visual feedback - disable button temporarily
         self.start_stop_button.set_sensitive(false);
 
-        let start_time = Utc::now();
-        let description = self.description_entry.text().to_string();
+        // "@Project" in the description picks the project and is taken out
+        let parsed = smart_syntax::parse_description(&self.description_entry.text(), &self.projects);
+        if parsed.project_id.is_some() {
+            self.set_selected_project(parsed.project_id);
+            self.description_entry.set_text(&parsed.description);
+        }
+
+        let description = parsed.description;
         let project_id = self.get_selected_project_id();
         match db::create_entry(&self.db_conn, project_id, &description, start_time) {
-            Ok(entry) => {
+            Ok(mut entry) => {
+                if self.start_private {
+                    match db::set_entry_private(&self.db_conn, entry.id, true) {
+                        Ok(()) => entry.private = true,
+                        Err(e) => self.show_failure("mark entry private", &e),
+                    }
+                    self.set_start_private(false);
+                }
+                plugins::dispatch_entry(self, &entry, PluginEvent::TimerStarted);
                 self.running_entry = Some(entry);
                 self.update_button_appearance();
                 self.update_timer_display();
-                // Make description field and project dropdown non-editable while timer is running
+                // Make description field non-editable while timer is running.
+                // The project stays editable and moves the running entry.
                 self.description_entry.set_sensitive(false);
-                self.project_dropdown.set_sensitive(false);
                 self.start_stop_button.set_sensitive(true);
                 true
             }
@@ -196,25 +748,94 @@ impl AppState {
         }
     }
 
+    /// Explains why a start in the locked period was blocked and offers to
+    /// start at the first unlocked time through the window's start-timer-at action
+    fn show_locked_start_dialog(&self, start_time: DateTime<Utc>) {
+        let Some(ref window) = self.window else {
+            self.show_error("The start time is in the locked period");
+            return;
+        };
+        let locked_until = self.settings.locked_until.unwrap_or_default();
+        let corrected = lock::first_unlocked_time(start_time, self.settings.locked_until, &Local);
+        let corrected_local = corrected.with_timezone(&Local);
+
+        let window_for_action = window.clone();
+        compat::confirm_suggested(
+            window,
+            "Start Time Is Locked",
+            &format!(
+                "The system clock says {}, which is inside the locked period ending {}. \
+                 Check the system clock, or start the timer on the first unlocked day instead.",
+                start_time.with_timezone(&Local).format("%b %d, %Y %H:%M"),
+                locked_until.format("%b %d, %Y")
+            ),
+            &format!("Start at {}", corrected_local.format("%b %d %H:%M")),
+            move || {
+                let _ = WidgetExt::activate_action(
+                    &window_for_action,
+                    &format!("win.{}", START_TIMER_AT_ACTION),
+                    Some(&corrected.timestamp().to_variant()),
+                );
+            },
+        );
+    }
+
+    /// Explains why a change to an entry starting in the locked period was
+    /// blocked. Entries there are left as they are until the period is unlocked.
+    fn show_locked_entry_dialog(&self, heading: &str, start_time: DateTime<Utc>) {
+        let locked_until = self.settings.locked_until.unwrap_or_default();
+        let Some(ref window) = self.window else {
+            self.show_failure("change entry", &Conflict::Locked(locked_until).into());
+            return;
+        };
+        compat::show_message(
+            window,
+            heading,
+            &format!(
+                "The entry started {}, which is inside the locked period ending {}. \
+                 Unlock the period in Preferences to change it.",
+                start_time.with_timezone(&Local).format("%b %d, %Y %H:%M"),
+                locked_until.format("%b %d, %Y")
+            ),
+        );
+    }
+
     /// Stops the current time entry
     /// Returns true if timer was stopped successfully
     pub fn stop_timer(&mut self) -> bool {
+        self.stop_timer_at(Utc::now(), None)
+    }
+
+    /// Stops the running timer at `end_time`, which may be in the past, and
+    /// logs `follow_up` for the time since in the same transaction. An entry
+    /// started in the locked period is blocked with a dialog, like other edits.
+    /// Returns true if the timer was stopped
+    pub fn stop_timer_at(&mut self, end_time: DateTime<Utc>, follow_up: Option<db::NewEntry>) -> bool {
         if let Some(ref entry) = self.running_entry {
+            // The follow-up starts at the end, so it can't be locked if the entry isn't
+            if self.ensure_unlocked(entry.start_time).is_err() {
+                self.show_locked_entry_dialog("Running Entry Is Locked", entry.start_time);
+                return false;
+            }
+
             // Add visual feedback - disable button temporarily
             self.start_stop_button.set_sensitive(false);
 
-            let end_time = Utc::now();
-            match db::stop_entry(&self.db_conn, entry.id, end_time) {
+            match db::stop_entry_with_follow_up(&self.db_conn, entry.id, end_time, follow_up.as_ref()) {
                 Ok(()) => {
+                    let stopped = db::TimeEntry {
+                        end_time: Some(end_time),
+                        ..entry.clone()
+                    };
+                    plugins::dispatch_entry(self, &stopped, PluginEvent::TimerStopped);
                     self.running_entry = None;
                     self.update_button_appearance();
                     self.update_timer_display();
                     // Clear description field and make it editable again
                     self.description_entry.set_text("");
                     self.description_entry.set_sensitive(true);
-                    // Reset project dropdown to "No Project" and make it editable again
-                    self.project_dropdown.set_selected(0);
-                    self.project_dropdown.set_sensitive(true);
+                    // Reset project dropdown to the default project
+                    self.reset_selected_project();
                     self.start_stop_button.set_sensitive(true);
                     true
                 }
@@ -229,6 +850,38 @@ impl AppState {
         }
     }
 
+    /// Moves the running entry to another project without stopping it
+    /// Returns true if the entry changed and the list should be refreshed
+    pub fn set_running_project(&mut self, project_id: Option<i64>) -> bool {
+        let Some((entry_id, start_time)) = self
+            .running_entry
+            .as_ref()
+            .filter(|entry| entry.project_id != project_id)
+            .map(|entry| (entry.id, entry.start_time))
+        else {
+            return false;
+        };
+
+        let result = self
+            .ensure_unlocked(start_time)
+            .and_then(|()| db::set_entry_project(&self.db_conn, entry_id, project_id));
+        if let Err(e) = result {
+            self.show_failure("change project", &e);
+            self.set_selected_project(self.running_entry.as_ref().and_then(|e| e.project_id));
+            return false;
+        }
+
+        if let Some(ref mut entry) = self.running_entry {
+            entry.project_id = project_id;
+        }
+        self.set_selected_project(project_id);
+        let name = project_id
+            .and_then(|id| self.projects.iter().find(|p| p.id == id))
+            .map_or("No Project", |p| p.name.as_str());
+        self.show_info(&format!("Running entry moved to {}", name));
+        true
+    }
+
     /// Toggles the timer state (start if stopped, stop if running)
     /// Returns true if state changed and list should be refreshed
     pub fn toggle_timer(&mut self) -> bool {
@@ -239,14 +892,9 @@ impl AppState {
         }
     }
 
-    /// Formats elapsed time as HH:MM:SS
+    /// Formats elapsed time since start_time as HH:MM:SS
     pub fn format_elapsed(&self, start_time: DateTime<Utc>) -> String {
-        let elapsed = Utc::now().signed_duration_since(start_time);
-        let total_seconds = elapsed.num_seconds().max(0);
-        let hours = total_seconds / 3600;
-        let minutes = (total_seconds % 3600) / 60;
-        let seconds = total_seconds % 60;
-        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+        TrackedDuration::between(start_time, Utc::now()).clock()
     }
 
     /// Updates the timer label based on current state
@@ -256,29 +904,87 @@ impl AppState {
             None => "00:00:00".to_string(),
         };
         self.timer_label.set_label(&display);
-        // Also update the system tray
+        if let Some(ref mini_timer) = self.mini_timer {
+            mini_timer.update(self);
+        }
+        self.update_goal_ring();
+        // Also update the system tray and D-Bus status
         self.update_tray();
+        self.update_status_service();
     }
 
     /// Continues a time entry by starting a new entry with the same description and project
     /// Returns true if a new entry was started and list should be refreshed
     pub fn continue_entry(&mut self, entry: &db::TimeEntry) -> bool {
+        self.start_task(&entry.description, entry.project_id)
+    }
+
+    /// Continues the entry that stopped last. While a timer runs that is the
+    /// one before it, so this switches back to the previous task.
+    /// Returns true if a new entry was started and list should be refreshed
+    pub fn continue_last_entry(&mut self) -> bool {
+        match db::get_last_stopped_entry(&self.db_conn) {
+            Ok(Some(entry)) => self.continue_entry(&entry),
+            Ok(None) => {
+                self.show_info("No stopped entry to continue");
+                false
+            }
+            Err(e) => {
+                self.show_failure("find the last entry", &e);
+                false
+            }
+        }
+    }
+
+    /// Starts the timer on a description and project, stopping a running
+    /// timer first. Returns true if the timer was started.
+    pub fn start_task(&mut self, description: &str, project_id: Option<i64>) -> bool {
         // If a timer is currently running, stop it first
         if self.running_entry.is_some() {
             self.stop_timer();
         }
 
         // Set the description entry text
-        self.description_entry.set_text(&entry.description);
+        self.description_entry.set_text(description);
 
         // Set the project dropdown selection
-        self.set_selected_project(entry.project_id);
+        self.set_selected_project(project_id);
 
         // Start a new timer with the same description and project
         self.start_timer()
     }
 
-    /// Deletes a time entry by ID
+    /// Fills in the description, project and privacy of a template for the
+    /// next start. The running entry is left alone.
+    pub fn apply_template(&mut self, template: &db::EntryTemplate) {
+        if self.running_entry.is_some() {
+            self.show_info("Stop the timer to use a template");
+            return;
+        }
+        self.description_entry.set_text(&template.description);
+        self.set_selected_project(template.project_id);
+        self.set_start_private(template.private);
+        self.description_entry.grab_focus();
+    }
+
+    /// Sets whether the next started entry is private, shown as an icon in
+    /// the description field that clears it when clicked
+    pub fn set_start_private(&mut self, private: bool) {
+        self.start_private = private;
+        self.description_entry.set_secondary_icon_name(private.then_some("changes-prevent-symbolic"));
+        self.description_entry
+            .set_secondary_icon_tooltip_text(private.then_some("Starts as private. Click to start normally."));
+    }
+
+    /// Starts the pinned task with this id. Returns true if the timer was started.
+    pub fn start_pinned_task(&mut self, id: i64) -> bool {
+        let Some(task) = self.pinned_tasks.iter().find(|task| task.id == id).cloned() else {
+            return false;
+        };
+        self.start_task(&task.description, task.project_id)
+    }
+
+    /// Deletes a time entry by ID, unless it is in the locked period
     /// Returns true if entry was deleted and list should be refreshed
     pub fn delete_entry(&mut self, entry_id: i64) -> bool {
         // Don't allow deleting the currently running entry
@@ -289,6 +995,18 @@ impl AppState {
             }
         }
 
+        let entry = match db::get_entry_by_id(&self.db_conn, entry_id) {
+            Ok(entry) => entry,
+            Err(e) => {
+                self.show_failure("delete entry", &e);
+                return false;
+            }
+        };
+        if self.ensure_unlocked(entry.start_time).is_err() {
+            self.show_locked_entry_dialog("Entry Is Locked", entry.start_time);
+            return false;
+        }
+
         if let Err(e) = db::delete_entry(&self.db_conn, entry_id) {
             self.show_error(&format!("Failed to delete entry: {}", e));
             return false;
@@ -297,77 +1015,26 @@ impl AppState {
         true
     }
 
-    /// Refreshes the project dropdown with current projects from database
+    /// Refreshes the project dropdown with current projects from database,
+    /// recently used ones first
     pub fn refresh_projects(&mut self) {
         // Reload projects from database
-        match db::get_all_projects(&self.db_conn) {
-            Ok(projects) => self.projects = projects,
+        let recent = match load_dropdown_projects(&self.db_conn) {
+            Ok((projects, recent)) => {
+                self.projects = projects;
+                recent
+            }
             Err(e) => {
                 self.show_error(&format!("Failed to load projects: {}", e));
                 self.projects = Vec::new();
+                0
             }
-        }
-
-        // Build the list of project names with "No Project" as first option
-        let mut labels: Vec<String> = vec!["No Project".to_string()];
-        for project in &self.projects {
-            labels.push(project.name.clone());
-        }
-
-        let string_list = gtk::StringList::new(&labels.iter().map(|s| s.as_str()).collect::<Vec<_>>());
-        self.project_dropdown.set_model(Some(&string_list));
-
-        // Set up a custom factory to show colored indicators for projects
-        let factory = gtk::SignalListItemFactory::new();
-        let projects_for_bind = self.projects.clone();
-
-        factory.connect_setup(|_, list_item| {
-            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
-            let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 8);
-            let color_indicator = gtk::Box::builder()
-                .width_request(12)
-                .height_request(12)
-                .valign(gtk::Align::Center)
-                .build();
-            let label = gtk::Label::new(None);
-            label.set_halign(gtk::Align::Start);
-            hbox.append(&color_indicator);
-            hbox.append(&label);
-            list_item.set_child(Some(&hbox));
-        });
-
-        factory.connect_bind(move |_, list_item| {
-            let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
-            let item = list_item.item().and_downcast::<gtk::StringObject>().unwrap();
-            let text = item.string().to_string();
-
-            let hbox = list_item.child().and_downcast::<gtk::Box>().unwrap();
-            let color_indicator = hbox.first_child().and_downcast::<gtk::Box>().unwrap();
-            let label = hbox.last_child().and_downcast::<gtk::Label>().unwrap();
-
-            label.set_label(&text);
-
-            // Find the project by name and set color
-            if text == "No Project" {
-                color_indicator.set_visible(false);
-            } else if let Some(project) = projects_for_bind.iter().find(|p| p.name == text) {
-                color_indicator.set_visible(true);
-                let css_provider = gtk::CssProvider::new();
-                css_provider.load_from_data(&format!(
-                    "box {{ background-color: {}; border-radius: 6px; }}",
-                    project.color
-                ));
-                color_indicator.style_context().add_provider(
-                    &css_provider,
-                    gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
-                );
-            } else {
-                color_indicator.set_visible(false);
-            }
-        });
+        };
 
+        self.project_dropdown.set_model(Some(&project_dropdown_model(&self.projects, true)));
+        let factory = project_dropdown_factory(self.projects.clone(), recent);
         self.project_dropdown.set_factory(Some(&factory));
-        self.project_dropdown.set_selected(0);
+        self.set_selected_project(self.running_entry.as_ref().and_then(|e| e.project_id));
     }
 
 }
@@ -394,6 +1061,20 @@ fn apply_css_styles() {
             padding: 12px;
             background-color: alpha(@window_bg_color, 0.5);
         }
+        .absence-row {
+            background-color: alpha(@window_fg_color, 0.04);
+        }
+        .dropdown-tail-start {
+            border-top: 1px solid alpha(@window_fg_color, 0.15);
+            padding-top: 6px;
+        }
+        .now-line {
+            min-height: 1px;
+            background-color: alpha(@accent_color, 0.6);
+        }
+        .now-label {
+            color: @accent_color;
+        }
         .entry-action-button {
             min-width: 28px;
             min-height: 28px;
@@ -430,6 +1111,19 @@ fn apply_css_styles() {
             min-height: 8px;
             border-radius: 4px;
         }
+        .legend-button {
+            min-width: 0;
+            min-height: 0;
+            padding: 4px;
+        }
+        .legend-swatch {
+            min-width: 10px;
+            min-height: 10px;
+            border-radius: 5px;
+        }
+        .legend-button.hidden-project .legend-swatch {
+            opacity: 0.25;
+        }
         .weekly-summary {
             padding: 12px;
         }
@@ -444,14 +1138,110 @@ fn apply_css_styles() {
             padding: 8px 12px;
             background-color: alpha(@window_bg_color, 0.3);
         }
+        .review-badge {
+            font-weight: bold;
+            font-size: 0.9em;
+        }
+        .mini-timer-display {
+            font-family: monospace;
+            font-size: 20px;
+            font-weight: bold;
+        }
+        .compact .timer-display {
+            font-size: 28px;
+        }
+        .compact .start-stop-button {
+            min-width: 40px;
+            min-height: 40px;
+            border-radius: 20px;
+        }
+        .compact .day-header {
+            padding: 6px 12px;
+        }
+        .compact .day-section-header {
+            padding: 4px 12px;
+        }
+        .month-day {
+            padding: 4px 6px;
+            min-height: 40px;
+            border-radius: 6px;
+        }
+        .month-today {
+            box-shadow: inset 0 0 0 2px @accent_color;
+        }
+        .heat-swatch {
+            border-radius: 3px;
+        }
+        .heat-empty {
+            background-color: alpha(@window_fg_color, 0.04);
+        }
+        .heat-far-under {
+            background-color: alpha(#1c71d8, 0.45);
+        }
+        .heat-under {
+            background-color: alpha(#62a0ea, 0.3);
+        }
+        .heat-on-target {
+            background-color: alpha(#2ec27e, 0.3);
+        }
+        .heat-over {
+            background-color: alpha(#ff7800, 0.3);
+        }
+        .heat-far-over {
+            background-color: alpha(#e01b24, 0.45);
+        }
+        .heatmap-cell {
+            min-width: 14px;
+            min-height: 14px;
+            border-radius: 3px;
+        }
+        .heatmap-level-0 {
+            background-color: alpha(@window_fg_color, 0.06);
+        }
+        .heatmap-level-1 {
+            background-color: alpha(#2ec27e, 0.3);
+        }
+        .heatmap-level-2 {
+            background-color: alpha(#2ec27e, 0.55);
+        }
+        .heatmap-level-3 {
+            background-color: alpha(#26a269, 0.8);
+        }
+        .heatmap-level-4 {
+            background-color: #1a7f4b;
+        }
         "#,
     );
 
+    let display = gtk::gdk::Display::default().expect("Could not get default display");
     gtk::style_context_add_provider_for_display(
-        &gtk::gdk::Display::default().expect("Could not get default display"),
+        &display,
         &provider,
         gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
+
+    // Optional user stylesheet, loaded above the built-in styles so any class can be overridden
+    let user_css_path = get_user_css_path();
+    if user_css_path.is_file() {
+        let user_provider = gtk::CssProvider::new();
+        user_provider.connect_parsing_error(|_, section, error| {
+            eprintln!("Error in user stylesheet at {}: {}", section, error);
+        });
+        user_provider.load_from_path(&user_css_path);
+        gtk::style_context_add_provider_for_display(
+            &display,
+            &user_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_USER,
+        );
+    }
+}
+
+/// Returns the path of the optional user stylesheet in the XDG config directory
+fn get_user_css_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("time-tracking")
+        .join("style.css")
 }
 
 /// Creates the timer display label with large monospace font
@@ -469,42 +1259,74 @@ fn create_start_stop_button() -> gtk::Button {
     gtk::Button::builder()
         .icon_name("media-playback-start-symbolic")
         .css_classes(["circular", "start-stop-button", "suggested-action"])
-        .margin_bottom(40)
         .build()
 }
 
 /// Creates the description entry field
 fn create_description_entry() -> gtk::Entry {
     gtk::Entry::builder()
-        .placeholder_text("What are you working on?")
-        .margin_start(20)
-        .margin_end(20)
-        .margin_top(20)
-        .margin_bottom(10)
+        .placeholder_text("What are you working on? (@project sets the project, 1h30m logs past work)")
         .build()
 }
 
-/// Creates the project selector dropdown
-fn create_project_dropdown(projects: &[db::Project]) -> gtk::DropDown {
-    // Build the list of project names with "No Project" as first option
-    let mut labels: Vec<String> = vec!["No Project".to_string()];
-    for project in projects {
-        labels.push(project.name.clone());
-    }
+/// Describes the deadline of `project` for tooltips, e.g. "Due Mar 29, 3
+/// days left"
+fn deadline_tooltip(project: &db::Project) -> Option<String> {
+    let deadline = project.deadline?;
+    Some(format!(
+        "Due {}, {}",
+        deadline.format("%b %-d"),
+        forecast::format_deadline(deadline, Local::now().date_naive()).to_lowercase()
+    ))
+}
 
-    let string_list = gtk::StringList::new(&labels.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+/// How many recently used projects lead the dropdown
+const RECENT_PROJECTS_LIMIT: usize = 5;
 
+/// How far back entries count towards a project being recently used
+const RECENT_PROJECTS_DAYS: i64 = 30;
+
+/// Loads the active projects in dropdown order, with how many recently used
+/// projects lead the list
+fn load_dropdown_projects(conn: &Connection) -> error::Result<(Vec<db::Project>, usize)> {
+    let since = Utc::now() - chrono::Duration::days(RECENT_PROJECTS_DAYS);
+    db::get_dropdown_projects(conn, since, RECENT_PROJECTS_LIMIT)
+}
+
+/// Creates the project selector dropdown, `recent` being how many recently
+/// used projects lead `projects`. With `offer_new` the list ends with an
+/// item creating a project.
+fn create_project_dropdown(projects: &[db::Project], recent: usize, offer_new: bool) -> gtk::DropDown {
     let dropdown = gtk::DropDown::builder()
-        .model(&string_list)
+        .model(&project_dropdown_model(projects, offer_new))
         .selected(0)
         .margin_start(20)
         .margin_end(20)
         .margin_bottom(10)
         .build();
 
-    // Set up a custom factory to show colored indicators for projects
+    dropdown.set_factory(Some(&project_dropdown_factory(projects.to_vec(), recent)));
+    dropdown
+}
+
+/// Last item of the main window's project dropdown, creating a project
+const NEW_PROJECT_LABEL: &str = "New Project…";
+
+/// Names shown in the project dropdown, with "No Project" as first option
+/// and, with `offer_new`, `NEW_PROJECT_LABEL` as the last
+fn project_dropdown_model(projects: &[db::Project], offer_new: bool) -> gtk::StringList {
+    let mut labels: Vec<&str> = vec!["No Project"];
+    labels.extend(projects.iter().map(|project| project.name.as_str()));
+    if offer_new {
+        labels.push(NEW_PROJECT_LABEL);
+    }
+    gtk::StringList::new(&labels)
+}
+
+/// Shows projects in the dropdown with their color, setting the long tail
+/// apart from the `recent` projects leading the list
+fn project_dropdown_factory(projects: Vec<db::Project>, recent: usize) -> gtk::SignalListItemFactory {
     let factory = gtk::SignalListItemFactory::new();
-    let projects_for_bind = projects.to_vec();
 
     factory.connect_setup(|_, list_item| {
         let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
@@ -521,6 +1343,7 @@ fn create_project_dropdown(projects: &[db::Project]) -> gtk::DropDown {
         list_item.set_child(Some(&hbox));
     });
 
+    let show_tail = recent > 0 && recent < projects.len();
     factory.connect_bind(move |_, list_item| {
         let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
         let item = list_item.item().and_downcast::<gtk::StringObject>().unwrap();
@@ -532,12 +1355,25 @@ fn create_project_dropdown(projects: &[db::Project]) -> gtk::DropDown {
 
         label.set_label(&text);
 
+        // The first project after the recent ones starts the long tail, and
+        // the item creating a project follows the projects. "No Project"
+        // comes first, so the tail starts at position recent + 1.
+        let position = list_item.position() as usize;
+        let is_new_item = position == projects.len() + 1;
+        if (show_tail && position == recent + 1) || is_new_item {
+            hbox.add_css_class("dropdown-tail-start");
+        } else {
+            hbox.remove_css_class("dropdown-tail-start");
+        }
+
         // Find the project by name and set color
-        if text == "No Project" {
+        if text == "No Project" || is_new_item {
             // No color indicator for "No Project"
             color_indicator.set_visible(false);
-        } else if let Some(project) = projects_for_bind.iter().find(|p| p.name == text) {
+            hbox.set_tooltip_text(None);
+        } else if let Some(project) = projects.iter().find(|p| p.name == text) {
             color_indicator.set_visible(true);
+            hbox.set_tooltip_text(deadline_tooltip(project).as_deref());
             // Set the background color using inline CSS
             let css_provider = gtk::CssProvider::new();
             css_provider.load_from_data(&format!(
@@ -553,11 +1389,10 @@ fn create_project_dropdown(projects: &[db::Project]) -> gtk::DropDown {
         }
     });
 
-    dropdown.set_factory(Some(&factory));
-    dropdown
+    factory
 }
 
-/// Creates the view toggle (Today/Week) button group
+/// Creates the view toggle (Today/Week/Month/Custom/All) button group
 fn create_view_toggle() -> gtk::Box {
     let toggle_box = gtk::Box::builder()
         .orientation(gtk::Orientation::Horizontal)
@@ -579,102 +1414,178 @@ fn create_view_toggle() -> gtk::Box {
         .css_classes(["view-toggle-button"])
         .build();
 
-    // Link the toggle buttons together
-    week_button.set_group(Some(&today_button));
+    let month_button = gtk::ToggleButton::builder()
+        .label("Month")
+        .css_classes(["view-toggle-button"])
+        .build();
 
-    toggle_box.append(&today_button);
-    toggle_box.append(&week_button);
+    let custom_button = gtk::ToggleButton::builder()
+        .label("Custom")
+        .css_classes(["view-toggle-button"])
+        .build();
+
+    let all_button = gtk::ToggleButton::builder()
+        .label("All")
+        .css_classes(["view-toggle-button"])
+        .build();
+
+    // Link the toggle buttons together
+    week_button.set_group(Some(&today_button));
+    month_button.set_group(Some(&today_button));
+    custom_button.set_group(Some(&today_button));
+    all_button.set_group(Some(&today_button));
+
+    toggle_box.append(&today_button);
+    toggle_box.append(&week_button);
+    toggle_box.append(&month_button);
+    toggle_box.append(&custom_button);
+    toggle_box.append(&all_button);
 
     toggle_box
 }
 
-/// Gets the start and end dates for the current week (Monday to Sunday)
-fn get_current_week_range() -> (NaiveDate, NaiveDate) {
-    let today = Local::now().date_naive();
-    let weekday = today.weekday();
-    let days_since_monday = weekday.num_days_from_monday();
-    let monday = today - chrono::Duration::days(days_since_monday as i64);
-    let sunday = monday + chrono::Duration::days(6);
-    (monday, sunday)
+/// Calculates total duration for a list of entries with the rounding rule applied
+fn calculate_entries_duration(entries: &[db::TimeEntry], rounding: &Rounding) -> i64 {
+    rounding.total_seconds(entries, Utc::now())
 }
 
-/// Formats duration in seconds to HH:MM:SS string
-fn format_duration(total_seconds: i64) -> String {
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
-    let seconds = total_seconds % 60;
-    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+/// Chart key of a project as an action parameter; project ids start at 1,
+/// so 0 stands for entries without a project
+fn chart_project_key(project_id: Option<i64>) -> i64 {
+    project_id.unwrap_or(0)
 }
 
-/// Calculates total duration for a list of entries
-fn calculate_entries_duration(entries: &[db::TimeEntry]) -> i64 {
-    let mut total_seconds: i64 = 0;
-    for entry in entries {
-        let end = entry.end_time.unwrap_or_else(Utc::now);
-        let duration = end.signed_duration_since(entry.start_time).num_seconds().max(0);
-        total_seconds += duration;
+/// Hides a project from the charts, or shows it again, and redraws the
+/// weekly breakdown. The hidden set lasts until the app is closed.
+pub(crate) fn toggle_chart_project(state: &Rc<RefCell<AppState>>, project_id: Option<i64>) {
+    let window = {
+        let mut state = state.borrow_mut();
+        if !state.hidden_chart_projects.remove(&project_id) {
+            state.hidden_chart_projects.insert(project_id);
+        }
+        state.window.clone()
+    };
+    if let Some(window) = window {
+        refresh_view(state.clone(), &window);
+    }
+}
+
+/// Creates the legend marker of a chart row: a dot in the project color
+/// that hides the project's bar when clicked, or shows it again
+pub(crate) fn create_legend_button(name: &str, color: &str, hidden: bool) -> gtk::Button {
+    let swatch = gtk::Box::builder()
+        .valign(gtk::Align::Center)
+        .css_classes(["legend-swatch"])
+        .build();
+    let css_provider = gtk::CssProvider::new();
+    css_provider.load_from_data(&format!("box {{ background-color: {}; }}", color));
+    swatch
+        .style_context()
+        .add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+
+    let button = gtk::Button::builder()
+        .child(&swatch)
+        .valign(gtk::Align::Center)
+        .tooltip_text(if hidden {
+            format!("Show {} in the chart", name)
+        } else {
+            format!("Hide {} from the chart", name)
+        })
+        .css_classes(["flat", "circular", "legend-button"])
+        .build();
+    if hidden {
+        button.add_css_class("hidden-project");
     }
-    total_seconds
+    button
 }
 
-/// Creates the project breakdown bar chart for the weekly summary
+/// Creates the project breakdown for the weekly summary: a donut chart next
+/// to a bar per project with its share. Projects in `hidden` keep their row
+/// but lose their bar and slice, and the shares are of the visible total.
 fn create_project_breakdown(
     entries: &[db::TimeEntry],
     conn: &Connection,
+    settings: &Settings,
+    hidden: &HashSet<Option<i64>>,
 ) -> gtk::Box {
     let breakdown_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(18)
+        .margin_top(12)
+        .build();
+    let rows_box = gtk::Box::builder()
         .orientation(gtk::Orientation::Vertical)
         .spacing(6)
-        .margin_top(12)
+        .valign(gtk::Align::Center)
+        .hexpand(true)
         .build();
 
     // Calculate time per project
     let mut project_times: HashMap<Option<i64>, i64> = HashMap::new();
     let mut project_info: HashMap<Option<i64>, (String, String)> = HashMap::new(); // (name, color)
 
+    let now = Utc::now();
     for entry in entries {
-        let end = entry.end_time.unwrap_or_else(Utc::now);
-        let duration = end.signed_duration_since(entry.start_time).num_seconds().max(0);
+        let duration = settings.rounding.entry_seconds(entry, now);
         *project_times.entry(entry.project_id).or_insert(0) += duration;
 
         // Cache project info
-        if !project_info.contains_key(&entry.project_id) {
-            let (name, color) = if let Some(pid) = entry.project_id {
+        project_info.entry(entry.project_id).or_insert_with(|| {
+            if let Some(pid) = entry.project_id {
                 if let Ok(Some(project)) = db::get_project_by_id(conn, pid) {
-                    (project.name, project.color)
-                } else {
-                    ("No Project".to_string(), "#888888".to_string())
+                    return (project.name, project.color);
                 }
-            } else {
-                ("No Project".to_string(), "#888888".to_string())
-            };
-            project_info.insert(entry.project_id, (name, color));
-        }
+            }
+            ("No Project".to_string(), "#888888".to_string())
+        });
     }
 
     if project_times.is_empty() {
         return breakdown_box;
     }
 
-    // Find max time for scaling
-    let max_time = project_times.values().copied().max().unwrap_or(1) as f64;
+    // Find max time for scaling, ignoring hidden projects
+    let max_time =
+        report::visible_max_seconds(project_times.iter().map(|(id, time)| (*id, *time)), hidden) as f64;
 
     // Sort by time (descending)
-    let mut sorted_projects: Vec<_> = project_times.into_iter().collect();
-    sorted_projects.sort_by(|a, b| b.1.cmp(&a.1));
+    let mut sorted_projects: Vec<_> = project_times
+        .into_iter()
+        .map(|(project_id, duration)| (project_id, settings.rounding.round_total(duration)))
+        .collect();
+    sorted_projects.sort_by_key(|p| std::cmp::Reverse(p.1));
+    let visible_total = report::visible_total_seconds(sorted_projects.iter().copied(), hidden);
+
+    let donut = DonutChart::new();
+    donut.update(
+        sorted_projects
+            .iter()
+            .filter(|(project_id, _)| !hidden.contains(project_id))
+            .map(|(project_id, duration)| (*duration, project_info[project_id].1.as_str()))
+            .collect(),
+        &settings.duration_format.format(visible_total),
+    );
+    breakdown_box.append(&donut.container);
 
     for (project_id, duration) in sorted_projects {
         let (name, color) = project_info.get(&project_id).unwrap();
+        let is_hidden = hidden.contains(&project_id);
 
         let row = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
             .spacing(8)
             .build();
 
+        let legend_button = create_legend_button(name, color, is_hidden);
+        legend_button.set_action_name(Some(&format!("win.{}", TOGGLE_CHART_PROJECT_ACTION)));
+        legend_button.set_action_target_value(Some(&chart_project_key(project_id).to_variant()));
+        row.append(&legend_button);
+
         // Project name label
         let name_label = gtk::Label::builder()
             .label(name)
             .halign(gtk::Align::Start)
+            .selectable(true)
             .width_chars(15)
             .ellipsize(gtk::pango::EllipsizeMode::End)
             .build();
@@ -687,6 +1598,7 @@ fn create_project_breakdown(
             .height_request(8)
             .valign(gtk::Align::Center)
             .css_classes(["project-bar"])
+            .visible(!is_hidden)
             .build();
 
         let css_provider = gtk::CssProvider::new();
@@ -699,31 +1611,243 @@ fn create_project_breakdown(
             gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
         );
         row.append(&bar);
+        if is_hidden {
+            name_label.add_css_class("dim-label");
+        }
+
+        // Share of the visible total, left out for hidden projects
+        let share_label = gtk::Label::builder()
+            .label(if is_hidden { String::new() } else { report::format_share(duration, visible_total) })
+            .halign(gtk::Align::End)
+            .hexpand(true)
+            .width_chars(4)
+            .xalign(1.0)
+            .css_classes(["monospace", "dim-label"])
+            .build();
+        row.append(&share_label);
 
         // Duration label
         let duration_label = gtk::Label::builder()
-            .label(&format_duration(duration))
+            .label(settings.duration_format.format(duration))
             .halign(gtk::Align::End)
-            .hexpand(true)
+            .selectable(true)
             .css_classes(["monospace", "dim-label"])
             .build();
         row.append(&duration_label);
 
-        breakdown_box.append(&row);
+        rows_box.append(&row);
     }
+    breakdown_box.append(&rows_box);
 
     breakdown_box
 }
 
-/// Sets up the timer update callback that fires every second
-fn setup_timer_update(state: Rc<RefCell<AppState>>) {
+/// Sets up the timer update callback that fires every second.
+/// If ticks were missed (suspend, hibernate, clock change) the whole view is
+/// refreshed so row durations and totals are correct immediately.
+fn setup_timer_update(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
+    let window = window.clone();
+    let last_tick = Cell::new(Utc::now());
     glib::timeout_add_seconds_local(1, move || {
+        let now = Utc::now();
+        let previous_tick = last_tick.replace(now);
+        let missed_ticks = duration::is_clock_gap(previous_tick, now);
+        // The Today list's "now" marker moves with the minute
+        let new_minute = previous_tick.timestamp() / 60 != now.timestamp() / 60
+            && state.borrow().view_mode == ViewMode::Today;
+
         state.borrow().update_timer_display();
+        if missed_ticks || new_minute {
+            refresh_view(state.clone(), &window);
+        }
         glib::ControlFlow::Continue
     });
 }
 
+/// Keys acting on the focused entry row of a list, with the row action each runs
+const ENTRY_ROW_SHORTCUTS: [(&str, &str); 7] = [
+    ("Return", "row.continue"),
+    ("KP_Enter", "row.continue"),
+    ("F2", "row.edit"),
+    ("Delete", "row.delete"),
+    ("KP_Delete", "row.delete"),
+    ("<Control>c", "row.copy-description"),
+    ("<Control>d", "row.duplicate-today"),
+];
+
+/// Makes the entry rows of a list keyboard operable: Enter continues the
+/// focused entry, F2 edits its description, Delete deletes it with undo,
+/// Ctrl+C copies its description and Ctrl+D duplicates it to today. Rows provide these as "row." actions;
+/// keys handled by a focused button or text field inside the row win.
+fn add_entry_row_shortcuts(list_box: &gtk::ListBox) {
+    let controller = gtk::ShortcutController::new();
+    for (trigger, action) in ENTRY_ROW_SHORTCUTS {
+        let callback = gtk::CallbackAction::new(move |list_box, _| {
+            match list_box.focus_child() {
+                Some(row) if row.activate_action(action, None).is_ok() => glib::Propagation::Stop,
+                _ => glib::Propagation::Proceed,
+            }
+        });
+        controller.add_shortcut(gtk::Shortcut::new(
+            gtk::ShortcutTrigger::parse_string(trigger),
+            Some(callback),
+        ));
+    }
+    list_box.add_controller(controller);
+}
+
+/// Adds the actions behind `ENTRY_ROW_SHORTCUTS` to an entry row. `edit`
+/// starts editing the description in place.
+fn add_entry_row_actions(
+    row: &gtk::ListBoxRow,
+    entry: &db::TimeEntry,
+    state: &Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+    edit: impl Fn() + 'static,
+) {
+    let is_running = state.borrow().running_entry.as_ref().map(|e| e.id) == Some(entry.id);
+    let actions = gio::SimpleActionGroup::new();
+
+    let continue_action = gio::SimpleAction::new("continue", None);
+    continue_action.set_enabled(entry.end_time.is_some());
+    let entry_for_continue = entry.clone();
+    let state_for_continue = state.clone();
+    let window_for_continue = window.clone();
+    continue_action.connect_activate(move |_, _| {
+        if state_for_continue.borrow_mut().continue_entry(&entry_for_continue) {
+            refresh_view(state_for_continue.clone(), &window_for_continue);
+        }
+    });
+    actions.add_action(&continue_action);
+
+    let edit_action = gio::SimpleAction::new("edit", None);
+    edit_action.connect_activate(move |_, _| edit());
+    actions.add_action(&edit_action);
+
+    // Deleting from the keyboard skips the confirmation, the toast offers undo instead
+    let delete_action = gio::SimpleAction::new("delete", None);
+    delete_action.set_enabled(!is_running);
+    let entry_for_delete = entry.clone();
+    let state_for_delete = state.clone();
+    let window_for_delete = window.clone();
+    delete_action.connect_activate(move |_, _| {
+        if !state_for_delete.borrow_mut().delete_entry(entry_for_delete.id) {
+            return;
+        }
+        refresh_view(state_for_delete.clone(), &window_for_delete);
+
+        let state_for_undo = state_for_delete.clone();
+        let window_for_undo = window_for_delete.clone();
+        let entry_for_undo = entry_for_delete.clone();
+        state_for_delete.borrow().show_undo("Entry deleted", move || {
+            if let Err(e) = db::restore_entry(&state_for_undo.borrow().db_conn, &entry_for_undo) {
+                state_for_undo.borrow().show_error(&format!("Failed to restore entry: {}", e));
+                return;
+            }
+            refresh_view(state_for_undo.clone(), &window_for_undo);
+        });
+    });
+    actions.add_action(&delete_action);
+
+    let copy_action = gio::SimpleAction::new("copy-description", None);
+    let description = entry.description.clone();
+    let state_for_copy = state.clone();
+    let window_for_copy = window.clone();
+    copy_action.connect_activate(move |_, _| {
+        window_for_copy.clipboard().set_text(&description);
+        state_for_copy.borrow().show_info("Description copied");
+    });
+    actions.add_action(&copy_action);
+
+    for (name, target) in [
+        ("duplicate-today", DuplicateTarget::Today),
+        ("duplicate-now", DuplicateTarget::EndingNow),
+    ] {
+        let duplicate_action = gio::SimpleAction::new(name, None);
+        duplicate_action.set_enabled(entry.end_time.is_some());
+        let entry_for_duplicate = entry.clone();
+        let state_for_duplicate = state.clone();
+        let window_for_duplicate = window.clone();
+        duplicate_action.connect_activate(move |_, _| {
+            let Some(new_entry) = duplicate::duplicate_entry(&entry_for_duplicate, target, Utc::now(), &Local) else {
+                state_for_duplicate.borrow().show_error("The entry's start time doesn't exist today");
+                return;
+            };
+            if state_for_duplicate.borrow_mut().log_entry(&new_entry) {
+                refresh_view(state_for_duplicate.clone(), &window_for_duplicate);
+            }
+        });
+        actions.add_action(&duplicate_action);
+    }
+
+    row.insert_action_group("row", Some(&actions));
+}
+
 /// Creates a list box row for a time entry with action buttons
+/// Duration of a completed entry that turns into a text field when
+/// clicked. Typing a length such as "1:30" or "45m" moves the entry's end.
+fn create_duration_editor(
+    entry: &db::TimeEntry,
+    duration_str: &str,
+    state: &Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+) -> gtk::EditableLabel {
+    let duration_label = gtk::EditableLabel::builder()
+        .text(duration_str)
+        .editable(false)
+        .halign(gtk::Align::End)
+        .css_classes(["monospace"])
+        .tooltip_text("Click to change the duration")
+        .build();
+
+    // Editing starts from the actual length, not the rounded one shown
+    let seconds = entry.end_time.map_or(0, |end| (end - entry.start_time).num_seconds());
+    let actual = format!("{}:{:02}", seconds / 3600, seconds % 3600 / 60);
+    let actual_for_save = actual.clone();
+    let click = gtk::GestureClick::new();
+    let label_for_click = duration_label.clone();
+    click.connect_released(move |gesture, _, _, _| {
+        if label_for_click.is_editing() {
+            return;
+        }
+        gesture.set_state(gtk::EventSequenceState::Claimed);
+        label_for_click.set_text(&actual);
+        label_for_click.set_editable(true);
+        label_for_click.start_editing();
+    });
+    duration_label.add_controller(click);
+
+    let entry = entry.clone();
+    let duration_str = duration_str.to_string();
+    let state = state.clone();
+    let window = window.clone();
+    duration_label.connect_editing_notify(move |duration_label| {
+        if duration_label.is_editing() {
+            return;
+        }
+        duration_label.set_editable(false);
+        let text = duration_label.text();
+        if text.trim() == actual_for_save {
+            duration_label.set_text(&duration_str);
+            return;
+        }
+        let Some(seconds) = smart_syntax::parse_entry_duration(&text) else {
+            state
+                .borrow()
+                .show_error(&format!("Invalid duration \"{}\", use e.g. 1:30 or 45m", text));
+            duration_label.set_text(&duration_str);
+            return;
+        };
+        if !state.borrow().set_entry_duration(&entry, seconds) {
+            duration_label.set_text(&duration_str);
+            return;
+        }
+        refresh_view(state.clone(), &window);
+    });
+
+    duration_label
+}
+
 fn create_entry_row_with_actions(
     entry: &db::TimeEntry,
     state: Rc<RefCell<AppState>>,
@@ -732,13 +1856,17 @@ fn create_entry_row_with_actions(
     let row = gtk::ListBoxRow::builder()
         .selectable(false)
         .activatable(false)
+        .focusable(true)
         .build();
 
+    let compact = state.borrow().settings.compact;
+    let row_padding = if compact { 3 } else { 8 };
+
     let hbox = gtk::Box::builder()
         .orientation(gtk::Orientation::Horizontal)
         .spacing(12)
-        .margin_top(8)
-        .margin_bottom(8)
+        .margin_top(row_padding)
+        .margin_bottom(row_padding)
         .margin_start(12)
         .margin_end(12)
         .build();
@@ -779,13 +1907,77 @@ fn create_entry_row_with_actions(
         entry.description.clone()
     };
 
-    let desc_label = gtk::Label::builder()
-        .label(&description)
-        .halign(gtk::Align::Start)
-        .ellipsize(gtk::pango::EllipsizeMode::End)
+    // The description is edited in place on a double click or F2: Enter or
+    // leaving the field saves, Escape cancels. It isn't editable otherwise,
+    // so single clicks still focus the row.
+    let desc_label = gtk::EditableLabel::builder()
+        .text(&description)
+        .editable(false)
+        .hexpand(true)
+        .tooltip_text("Double-click to edit")
         .build();
     content_box.append(&desc_label);
 
+    let start_editing = {
+        let desc_label = desc_label.clone();
+        let original_description = entry.description.clone();
+        Rc::new(move || {
+            // The placeholder of an empty description isn't edited
+            desc_label.set_text(&original_description);
+            desc_label.set_editable(true);
+            desc_label.start_editing();
+        })
+    };
+
+    let double_click = gtk::GestureClick::new();
+    let start_editing_for_click = start_editing.clone();
+    double_click.connect_pressed(move |gesture, n_press, _, _| {
+        if n_press == 2 {
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+            start_editing_for_click();
+        }
+    });
+    desc_label.add_controller(double_click);
+
+    let entry_for_save = entry.clone();
+    let state_for_save = state.clone();
+    let window_for_save = window.clone();
+    desc_label.connect_editing_notify(move |desc_label| {
+        if desc_label.is_editing() {
+            return;
+        }
+        desc_label.set_editable(false);
+        let new_description = desc_label.text().trim().to_string();
+        if new_description == entry_for_save.description {
+            if new_description.is_empty() {
+                desc_label.set_text("(no description)");
+            }
+            return;
+        }
+        let result = {
+            let state_borrow = state_for_save.borrow();
+            state_borrow.ensure_unlocked(entry_for_save.start_time).and_then(|()| {
+                db::update_entry_details(
+                    &state_borrow.db_conn,
+                    entry_for_save.id,
+                    entry_for_save.project_id,
+                    &new_description,
+                )
+            })
+        };
+        if let Err(e) = result {
+            state_for_save.borrow().show_failure("update entry", &e);
+            desc_label.set_text(&description);
+            return;
+        }
+        refresh_view(state_for_save.clone(), &window_for_save);
+    });
+
+    add_entry_row_actions(&row, entry, &state, window, move || start_editing());
+
+    // Dragging the row onto a project of the sidebar moves the entry there
+    sidebar::add_entry_drag_source(&row, entry.id);
+
     // Project name (if any)
     let project_name = if let Some(project_id) = entry.project_id {
         db::get_project_by_id(&state.borrow().db_conn, project_id)
@@ -817,18 +2009,20 @@ fn create_entry_row_with_actions(
 
     // Duration
     let end = entry.end_time.unwrap_or_else(Utc::now);
-    let duration_secs = end.signed_duration_since(entry.start_time).num_seconds().max(0);
-    let hours = duration_secs / 3600;
-    let minutes = (duration_secs % 3600) / 60;
-    let seconds = duration_secs % 60;
-    let duration_str = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
+    let rounding = state.borrow().settings.rounding;
+    let duration_str = TrackedDuration::from_seconds(rounding.entry_seconds(entry, Utc::now())).clock();
 
-    let duration_label = gtk::Label::builder()
-        .label(&duration_str)
-        .halign(gtk::Align::End)
-        .css_classes(["monospace"])
-        .build();
-    time_box.append(&duration_label);
+    if entry.end_time.is_some() {
+        // Clicking the duration of a completed entry edits it, moving the end
+        time_box.append(&create_duration_editor(entry, &duration_str, &state, window));
+    } else {
+        let duration_label = gtk::Label::builder()
+            .label(&duration_str)
+            .halign(gtk::Align::End)
+            .css_classes(["monospace"])
+            .build();
+        time_box.append(&duration_label);
+    }
 
     // Start-end times
     let start_local = entry.start_time.with_timezone(&Local);
@@ -847,6 +2041,7 @@ fn create_entry_row_with_actions(
         .label(&time_range)
         .halign(gtk::Align::End)
         .css_classes(["dim-label", "caption"])
+        .visible(!compact)
         .build();
     time_box.append(&time_range_label);
 
@@ -859,6 +2054,60 @@ fn create_entry_row_with_actions(
         .valign(gtk::Align::Center)
         .build();
 
+    // Private toggle: private entries are left out of shared output
+    let private_button = gtk::ToggleButton::builder()
+        .icon_name(if entry.private {
+            "changes-prevent-symbolic"
+        } else {
+            "changes-allow-symbolic"
+        })
+        .tooltip_text(if entry.private {
+            "Private: hidden from exports and shared reports"
+        } else {
+            "Mark as private"
+        })
+        .active(entry.private)
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+
+    let entry_for_private = entry.clone();
+    let state_for_private = state.clone();
+    let window_for_private = window.clone();
+    private_button.connect_toggled(move |button| {
+        let result = {
+            let state_borrow = state_for_private.borrow();
+            state_borrow.ensure_unlocked(entry_for_private.start_time).and_then(|()| {
+                db::set_entry_private(&state_borrow.db_conn, entry_for_private.id, button.is_active())
+            })
+        };
+        // The refresh puts the toggle back when the change failed
+        if let Err(e) = result {
+            state_for_private.borrow().show_failure("update entry", &e);
+        }
+        refresh_view(state_for_private.clone(), &window_for_private);
+    });
+
+    actions_box.append(&private_button);
+
+    // Pin toggle: pinned description and project pairs start with one click
+    let pinned = pinned::is_pinned(&state.borrow(), entry);
+    let pin_button = gtk::ToggleButton::builder()
+        .icon_name("view-pin-symbolic")
+        .tooltip_text(if pinned { "Unpin this task" } else { "Pin this task" })
+        .active(pinned)
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+
+    let entry_for_pin = entry.clone();
+    let state_for_pin = state.clone();
+    let window_for_pin = window.clone();
+    pin_button.connect_toggled(move |_| {
+        pinned::toggle_pin(&state_for_pin, &entry_for_pin);
+        refresh_view(state_for_pin.clone(), &window_for_pin);
+    });
+
+    actions_box.append(&pin_button);
+
     // Continue button (only show for completed entries)
     if entry.end_time.is_some() {
         let continue_button = gtk::Button::builder()
@@ -872,11 +2121,22 @@ fn create_entry_row_with_actions(
         let window_for_continue = window.clone();
         continue_button.connect_clicked(move |_| {
             if state_for_continue.borrow_mut().continue_entry(&entry_for_continue) {
-                refresh_entries_list_with_actions(state_for_continue.clone(), &window_for_continue);
+                refresh_view(state_for_continue.clone(), &window_for_continue);
             }
         });
 
         actions_box.append(&continue_button);
+
+        let duplicate_menu = gio::Menu::new();
+        duplicate_menu.append(Some("Duplicate to Today"), Some("row.duplicate-today"));
+        duplicate_menu.append(Some("Duplicate Ending Now"), Some("row.duplicate-now"));
+        let duplicate_button = gtk::MenuButton::builder()
+            .icon_name("edit-copy-symbolic")
+            .tooltip_text("Duplicate this entry")
+            .menu_model(&duplicate_menu)
+            .css_classes(["flat", "entry-action-button"])
+            .build();
+        actions_box.append(&duplicate_button);
     }
 
     // Delete button (don't show for currently running entry)
@@ -894,43 +2154,22 @@ fn create_entry_row_with_actions(
         let window_for_delete = window.clone();
 
         delete_button.connect_clicked(move |_| {
-            // Create confirmation dialog
-            let dialog = gtk::MessageDialog::builder()
-                .transient_for(&window_for_delete)
-                .modal(true)
-                .message_type(gtk::MessageType::Question)
-                .buttons(gtk::ButtonsType::None)
-                .text("Delete Entry?")
-                .secondary_text(format!(
-                    "Are you sure you want to delete \"{}\"? This cannot be undone.",
-                    if entry_description.is_empty() {
-                        "(no description)"
-                    } else {
-                        &entry_description
-                    }
-                ))
-                .build();
-
-            dialog.add_button("Cancel", gtk::ResponseType::Cancel);
-            dialog.add_button("Delete", gtk::ResponseType::Accept);
-
-            // Style the delete button as destructive
-            if let Some(button) = dialog.widget_for_response(gtk::ResponseType::Accept) {
-                button.add_css_class("destructive-action");
-            }
+            let body = format!(
+                "Are you sure you want to delete \"{}\"? This cannot be undone.",
+                if entry_description.is_empty() {
+                    "(no description)"
+                } else {
+                    &entry_description
+                }
+            );
 
             let state_for_response = state_for_delete.clone();
             let window_for_response = window_for_delete.clone();
-            dialog.connect_response(move |dialog, response| {
-                if response == gtk::ResponseType::Accept {
-                    if state_for_response.borrow_mut().delete_entry(entry_id) {
-                        refresh_entries_list_with_actions(state_for_response.clone(), &window_for_response);
-                    }
+            compat::confirm_destructive(&window_for_delete, "Delete Entry?", &body, "Delete", move || {
+                if state_for_response.borrow_mut().delete_entry(entry_id) {
+                    refresh_view(state_for_response.clone(), &window_for_response);
                 }
-                dialog.close();
             });
-
-            dialog.present();
         });
 
         actions_box.append(&delete_button);
@@ -942,148 +2181,198 @@ fn create_entry_row_with_actions(
     row
 }
 
-/// Refreshes the entries list for today with action buttons
-fn refresh_entries_list_with_actions(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
-    let state_borrow = state.borrow();
+/// Persistent widgets of the weekly view, updated in place on refresh
+struct WeekView {
+    container: gtk::Box,
+    /// Holds view specific controls above the title, such as date pickers
+    controls: gtk::Box,
+    /// Jumps back to the current week, insensitive while it is shown
+    this_week_button: Option<gtk::Button>,
+    week_label: gtk::Label,
+    /// Copies `summary_text`, the shown totals per project as tab separated text
+    copy_button: gtk::Button,
+    summary_text: Rc<RefCell<String>>,
+    total_label: gtk::Label,
+    /// Projected total of the current week, hidden for other weeks and ranges
+    forecast_label: gtk::Label,
+    /// What is left of the weekly target, hidden without one and for ranges
+    target_label: gtk::Label,
+    burndown: BurndownChart,
+    /// Projects due soon or overdue, shown for the current week
+    deadlines_label: gtk::Label,
+    /// Holds the project breakdown, rebuilt only when its fingerprint changes
+    breakdown_slot: gtk::Box,
+    breakdown_fingerprint: Option<u64>,
+    scrolled_window: gtk::ScrolledWindow,
+    days_box: gtk::Box,
+    empty_label: gtk::Label,
+    days: HashMap<NaiveDate, DaySection>,
+}
 
-    // Remove all existing rows
-    while let Some(child) = state_borrow.entries_list_box.first_child() {
-        state_borrow.entries_list_box.remove(&child);
-    }
+/// Header and entries of one day in the weekly view
+struct DaySection {
+    container: gtk::Box,
+    total_label: gtk::Label,
+    entries: KeyedListBox<i64>,
+}
 
-    let today = Local::now().date_naive();
-    let entries = match db::get_entries_for_date(&state_borrow.db_conn, today) {
-        Ok(entries) => entries,
-        Err(e) => {
-            state_borrow.show_error(&format!("Failed to load entries: {}", e));
-            Vec::new()
-        }
-    };
+impl DaySection {
+    fn new(day: NaiveDate) -> Self {
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(0)
+            .build();
 
-    // Calculate total time for the day
-    let mut total_seconds: i64 = 0;
-    for entry in &entries {
-        let end = entry.end_time.unwrap_or_else(Utc::now);
-        let duration = end.signed_duration_since(entry.start_time).num_seconds().max(0);
-        total_seconds += duration;
-    }
+        // Day header
+        let day_header = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(8)
+            .css_classes(["day-section-header"])
+            .build();
 
-    // Update the day total label
-    let today_formatted = today.format("%A, %B %d").to_string();
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
-    let seconds = total_seconds % 60;
-    let total_str = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
-    state_borrow.day_total_label.set_markup(&format!(
-        "<b>{}</b>  •  Total: {}",
-        today_formatted,
-        total_str
-    ));
+        let day_name = gtk::Label::builder()
+            .label(day.format("%A, %B %d").to_string())
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .css_classes(["heading"])
+            .build();
+        day_header.append(&day_name);
 
-    if entries.is_empty() {
-        // Show empty state message
-        let empty_label = gtk::Label::builder()
-            .label("No entries for today")
-            .css_classes(["dim-label"])
-            .margin_top(20)
-            .margin_bottom(20)
+        let total_label = gtk::Label::builder()
+            .halign(gtk::Align::End)
+            .css_classes(["monospace"])
             .build();
-        state_borrow.entries_list_box.append(&empty_label);
-    } else {
-        // Need to drop the borrow to create rows with state reference
-        drop(state_borrow);
+        day_header.append(&total_label);
+
+        container.append(&day_header);
+
+        // Day entries list
+        let day_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .margin_start(12)
+            .margin_end(12)
+            .margin_bottom(8)
+            .build();
+        container.append(&day_list);
 
-        // Add entry rows with actions
-        for entry in entries {
-            let row = create_entry_row_with_actions(&entry, state.clone(), window);
-            state.borrow().entries_list_box.append(&row);
+        Self {
+            container,
+            total_label,
+            entries: KeyedListBox::new(day_list, ""),
         }
     }
 }
 
-/// Refreshes the entries section for weekly view
-fn refresh_weekly_view(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
-    let state_borrow = state.borrow();
+impl WeekView {
+    fn new() -> Self {
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(0)
+            .vexpand(true)
+            .build();
 
-    // Clear the entries section
-    let entries_section = &state_borrow.entries_section;
-    while let Some(child) = entries_section.first_child() {
-        entries_section.remove(&child);
-    }
+        // Create header with weekly total
+        let header_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(4)
+            .css_classes(["weekly-summary"])
+            .build();
 
-    // Get entries for the current week
-    let (week_start, week_end) = get_current_week_range();
-    let all_entries = match db::get_entries_for_date_range(&state_borrow.db_conn, week_start, week_end) {
-        Ok(entries) => entries,
-        Err(e) => {
-            state_borrow.show_error(&format!("Failed to load entries: {}", e));
-            Vec::new()
-        }
-    };
+        let controls = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .visible(false)
+            .build();
+        header_box.append(&controls);
 
-    // Calculate weekly total
-    let weekly_total_seconds = calculate_entries_duration(&all_entries);
+        // Title with a button copying the summary
+        let title_row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        let week_label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .xalign(0.0)
+            .selectable(true)
+            .css_classes(["title-4"])
+            .build();
+        title_row.append(&week_label);
+        let copy_button = gtk::Button::builder()
+            .icon_name("edit-copy-symbolic")
+            .tooltip_text("Copy Summary")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        title_row.append(&copy_button);
+        header_box.append(&title_row);
 
-    // Create header with weekly total
-    let header_box = gtk::Box::builder()
-        .orientation(gtk::Orientation::Vertical)
-        .spacing(4)
-        .css_classes(["weekly-summary"])
-        .build();
+        let total_label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .selectable(true)
+            .css_classes(["weekly-total", "monospace"])
+            .build();
+        let forecast_label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .selectable(true)
+            .visible(false)
+            .css_classes(["dim-label"])
+            .build();
 
-    let week_label = gtk::Label::builder()
-        .label(&format!(
-            "Week of {} - {}",
-            week_start.format("%b %d"),
-            week_end.format("%b %d, %Y")
-        ))
-        .halign(gtk::Align::Start)
-        .css_classes(["title-4"])
-        .build();
-    header_box.append(&week_label);
+        let totals_row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .build();
+        let target_label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .selectable(true)
+            .visible(false)
+            .css_classes(["dim-label"])
+            .build();
+        totals_row.append(&total_label);
+        totals_row.append(&forecast_label);
+        totals_row.append(&target_label);
+        header_box.append(&totals_row);
 
-    let total_label = gtk::Label::builder()
-        .label(&format!("Total: {}", format_duration(weekly_total_seconds)))
-        .halign(gtk::Align::Start)
-        .css_classes(["weekly-total", "monospace"])
-        .build();
-    header_box.append(&total_label);
+        let burndown = BurndownChart::new();
+        burndown.container.set_visible(false);
+        header_box.append(&burndown.container);
 
-    // Add project breakdown
-    let breakdown = create_project_breakdown(&all_entries, &state_borrow.db_conn);
-    header_box.append(&breakdown);
+        let deadlines_label = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .wrap(true)
+            .xalign(0.0)
+            .visible(false)
+            .margin_top(4)
+            .css_classes(["warning"])
+            .build();
+        header_box.append(&deadlines_label);
 
-    entries_section.append(&header_box);
+        let breakdown_slot = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+        header_box.append(&breakdown_slot);
 
-    // Add separator
-    let separator = gtk::Separator::new(gtk::Orientation::Horizontal);
-    separator.set_margin_top(8);
-    entries_section.append(&separator);
+        container.append(&header_box);
 
-    // Create scrolled window for day sections
-    let scrolled_window = gtk::ScrolledWindow::builder()
-        .hscrollbar_policy(gtk::PolicyType::Never)
-        .vscrollbar_policy(gtk::PolicyType::Automatic)
-        .vexpand(true)
-        .build();
+        // Add separator
+        let separator = gtk::Separator::new(gtk::Orientation::Horizontal);
+        separator.set_margin_top(8);
+        container.append(&separator);
 
-    let days_box = gtk::Box::builder()
-        .orientation(gtk::Orientation::Vertical)
-        .spacing(0)
-        .build();
+        // Create scrolled window for day sections
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .build();
 
-    // Group entries by day
-    let mut entries_by_day: HashMap<NaiveDate, Vec<db::TimeEntry>> = HashMap::new();
-    for entry in all_entries {
-        let date = entry.start_time.with_timezone(&Local).date_naive();
-        entries_by_day.entry(date).or_default().push(entry);
-    }
-
-    // Sort days (most recent first)
-    let mut days: Vec<_> = entries_by_day.keys().cloned().collect();
-    days.sort_by(|a, b| b.cmp(a));
+        let days_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(0)
+            .build();
 
-    if days.is_empty() {
         let empty_label = gtk::Label::builder()
             .label("No entries this week")
             .css_classes(["dim-label"])
@@ -1091,72 +2380,487 @@ fn refresh_weekly_view(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWi
             .margin_bottom(20)
             .build();
         days_box.append(&empty_label);
+
+        scrolled_window.set_child(Some(&days_box));
+        container.append(&scrolled_window);
+
+        Self {
+            container,
+            controls,
+            this_week_button: None,
+            week_label,
+            copy_button,
+            summary_text: Rc::new(RefCell::new(String::new())),
+            total_label,
+            forecast_label,
+            target_label,
+            burndown,
+            deadlines_label,
+            breakdown_slot,
+            breakdown_fingerprint: None,
+            scrolled_window,
+            days_box,
+            empty_label,
+            days: HashMap::new(),
+        }
+    }
+}
+
+/// Shows `view` as the only child of the entries section, keeping its widgets
+fn show_in_entries_section(entries_section: &gtk::Box, view: &gtk::Box) {
+    if entries_section.first_child().as_ref() == Some(view.upcast_ref()) {
+        return;
+    }
+    while let Some(child) = entries_section.first_child() {
+        entries_section.remove(&child);
+    }
+    entries_section.append(view);
+}
+
+/// Keeps the scroll position of `scrolled_window` across `update`, which may add,
+/// remove or refocus rows. The position is restored again once the new rows
+/// have been measured, since the next layout may otherwise clamp it.
+fn preserve_scroll_position(scrolled_window: &gtk::ScrolledWindow, update: impl FnOnce()) {
+    let adjustment = scrolled_window.vadjustment();
+    let value = adjustment.value();
+
+    update();
+
+    adjustment.set_value(value);
+    glib::idle_add_local_once(move || adjustment.set_value(value));
+}
+
+/// Loads the projects referenced by the given entries, including archived ones
+fn load_entry_projects(conn: &Connection, entries: &[db::TimeEntry]) -> HashMap<i64, db::Project> {
+    let mut projects = HashMap::new();
+    for project_id in entries.iter().filter_map(|e| e.project_id) {
+        if let std::collections::hash_map::Entry::Vacant(slot) = projects.entry(project_id) {
+            if let Ok(Some(project)) = db::get_project_by_id(conn, project_id) {
+                slot.insert(project);
+            }
+        }
+    }
+    projects
+}
+
+/// Fingerprint of everything an entry row displays, so unchanged rows are kept
+fn entry_row_fingerprint(
+    entry: &db::TimeEntry,
+    projects: &HashMap<i64, db::Project>,
+    settings: &Settings,
+    now: DateTime<Utc>,
+    is_running: bool,
+) -> u64 {
+    let project = entry
+        .project_id
+        .and_then(|id| projects.get(&id))
+        .map(|p| (&p.name, &p.color));
+    list_diff::fingerprint(&(
+        &entry.description,
+        entry.project_id,
+        project,
+        entry.start_time,
+        entry.end_time,
+        settings.rounding.entry_seconds(entry, now),
+        settings.compact,
+        entry.private,
+        is_running,
+    ))
+}
+
+/// Adds the From/To date pickers of the Custom view
+fn add_custom_range_controls(
+    state: &Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+    week_view: &WeekView,
+) {
+    let range = state.borrow().custom_range;
+    let start_picker = DatePicker::new(range.start, "First day");
+    let end_picker = DatePicker::new(range.end, "Last day");
+
+    // Reversed dates are swapped, so both pickers are updated from the new range
+    let pickers = [start_picker.clone(), end_picker.clone()];
+    for (index, picker) in pickers.iter().enumerate() {
+        let state = state.clone();
+        let window = window.clone();
+        let pickers = pickers.clone();
+        picker.connect_date_picked(move |date| {
+            let range = state.borrow().custom_range;
+            let range = if index == 0 {
+                ReportRange::new(date, range.end)
+            } else {
+                ReportRange::new(range.start, date)
+            };
+            pickers[0].set_date(range.start);
+            pickers[1].set_date(range.end);
+            if state.borrow().custom_range != range {
+                state.borrow_mut().custom_range = range;
+                state.borrow().save_view_dates();
+                refresh_view(state.clone(), &window);
+            }
+        });
+    }
+
+    week_view.controls.append(&start_picker.button);
+    week_view.controls.append(&gtk::Label::new(Some("–")));
+    week_view.controls.append(&end_picker.button);
+    week_view.controls.set_visible(true);
+}
+
+/// Adds the previous/next week arrows and the "This Week" button of the Week view
+fn add_week_navigation_controls(
+    state: &Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+    week_view: &mut WeekView,
+) {
+    let previous_button = gtk::Button::builder()
+        .icon_name("go-previous-symbolic")
+        .tooltip_text("Previous week")
+        .css_classes(["flat"])
+        .build();
+    let this_week_button = gtk::Button::builder()
+        .label("This Week")
+        .css_classes(["flat"])
+        .hexpand(true)
+        .halign(gtk::Align::Center)
+        .build();
+    let next_button = gtk::Button::builder()
+        .icon_name("go-next-symbolic")
+        .tooltip_text("Next week")
+        .css_classes(["flat"])
+        .build();
+
+    // Days to move by, or None to go back to the current week
+    for (button, offset) in [(&previous_button, Some(-7)), (&this_week_button, None), (&next_button, Some(7))] {
+        let state = state.clone();
+        let window = window.clone();
+        button.connect_clicked(move |_| {
+            let shown_week = match offset {
+                Some(days) => state.borrow().shown_week + chrono::Duration::days(days),
+                None => Local::now().date_naive(),
+            };
+            state.borrow_mut().shown_week = shown_week;
+            state.borrow().save_view_dates();
+            refresh_view(state.clone(), &window);
+        });
+    }
+
+    week_view.controls.append(&previous_button);
+    week_view.controls.append(&this_week_button);
+    week_view.controls.append(&next_button);
+    week_view.controls.set_visible(true);
+    week_view.this_week_button = Some(this_week_button);
+}
+
+/// Refreshes the entries section for the Week and Custom views, which list
+/// the entries of a range of days grouped by day
+fn refresh_range_view(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
+    let custom = state.borrow().view_mode == ViewMode::Custom;
+    let existing = if custom {
+        state.borrow_mut().custom_view.take()
     } else {
-        // Need to drop the borrow to create rows with state reference
-        let conn_ref = &state_borrow.db_conn;
-
-        for day in &days {
-            let day_entries = entries_by_day.get(day).unwrap();
-            let day_total = calculate_entries_duration(day_entries);
-
-            // Day header
-            let day_header = gtk::Box::builder()
-                .orientation(gtk::Orientation::Horizontal)
-                .spacing(8)
-                .css_classes(["day-section-header"])
-                .build();
+        state.borrow_mut().week_view.take()
+    };
+    let mut week_view = existing.unwrap_or_else(|| {
+        let mut week_view = WeekView::new();
+        if custom {
+            week_view.empty_label.set_label("No entries in this period");
+            add_custom_range_controls(&state, window, &week_view);
+        } else {
+            add_week_navigation_controls(&state, window, &mut week_view);
+        }
+        connect_copy_summary(&state, &week_view);
+        week_view
+    });
+    let state_borrow = state.borrow();
+    show_in_entries_section(&state_borrow.entries_section, &week_view.container);
 
-            let day_name = gtk::Label::builder()
-                .label(&day.format("%A, %B %d").to_string())
-                .halign(gtk::Align::Start)
-                .hexpand(true)
-                .css_classes(["heading"])
-                .build();
-            day_header.append(&day_name);
+    // Get entries for the shown days
+    let (week_start, week_end) = state_borrow.view_range();
+    let mut all_entries = match db::get_entries_for_date_range(&state_borrow.db_conn, week_start, week_end) {
+        Ok(entries) => entries,
+        Err(e) => {
+            state_borrow.show_error(&format!("Failed to load entries: {}", e));
+            Vec::new()
+        }
+    };
+    all_entries.retain(|e| state_borrow.entry_filter_matches(e));
 
-            let day_total_label = gtk::Label::builder()
-                .label(&format_duration(day_total))
-                .halign(gtk::Align::End)
-                .css_classes(["monospace"])
-                .build();
-            day_header.append(&day_total_label);
+    if custom {
+        week_view
+            .week_label
+            .set_label(&ReportRange::new(week_start, week_end).label());
+    } else {
+        let current_week = ReportRange::week_of(Local::now().date_naive());
+        if let Some(ref button) = week_view.this_week_button {
+            button.set_sensitive(current_week.start != week_start);
+        }
+        week_view.week_label.set_label(&format!(
+            "Week of {} - {}",
+            week_start.format("%b %d"),
+            week_end.format("%b %d, %Y")
+        ));
+    }
+    let projection = if custom {
+        None
+    } else {
+        week_projection(&all_entries, week_start, &state_borrow.settings)
+    };
+    week_view.forecast_label.set_visible(projection.is_some());
+    if let Some(seconds) = projection {
+        week_view.forecast_label.set_label(&forecast::format_projection(seconds));
+    }
+    let target_seconds = state_borrow.settings.weekly_target_minutes as i64 * 60;
+    let show_target = !custom && target_seconds > 0;
+    week_view.target_label.set_visible(show_target);
+    week_view.burndown.container.set_visible(show_target);
+    if show_target {
+        update_week_target(&week_view, &all_entries, week_start, target_seconds, &state_borrow.settings);
+    }
+    let today = Local::now().date_naive();
+    let deadlines = if !custom && ReportRange::week_of(today).start == week_start {
+        forecast::deadline_warnings(&state_borrow.projects, today)
+    } else {
+        Vec::new()
+    };
+    week_view.deadlines_label.set_visible(!deadlines.is_empty());
+    if !deadlines.is_empty() {
+        let warnings: Vec<String> = deadlines
+            .iter()
+            .map(|(project, deadline)| format!("{}: {}", project.name, forecast::format_deadline(*deadline, today)))
+            .collect();
+        week_view.deadlines_label.set_label(&warnings.join(" · "));
+    }
+    update_range_view(&mut week_view, all_entries, &state_borrow);
 
-            days_box.append(&day_header);
+    drop(state_borrow);
+    if custom {
+        state.borrow_mut().custom_view = Some(week_view);
+    } else {
+        state.borrow_mut().week_view = Some(week_view);
+    }
+}
 
-            // Day entries list
-            let day_list = gtk::ListBox::builder()
-                .selection_mode(gtk::SelectionMode::None)
-                .css_classes(["boxed-list"])
-                .margin_start(12)
-                .margin_end(12)
-                .margin_bottom(8)
-                .build();
+/// Projected total of the week starting `week_start` at the pace so far,
+/// `None` unless it is the current week
+fn week_projection(entries: &[db::TimeEntry], week_start: NaiveDate, settings: &Settings) -> Option<i64> {
+    let today = Local::now().date_naive();
+    let (today_entries, earlier_entries): (Vec<db::TimeEntry>, Vec<db::TimeEntry>) = entries
+        .iter()
+        .cloned()
+        .partition(|e| e.start_time.with_timezone(&Local).date_naive() == today);
+    forecast::projected_week_seconds(
+        week_start,
+        today,
+        settings.work_days_per_week,
+        calculate_entries_duration(&earlier_entries, &settings.rounding),
+        calculate_entries_duration(&today_entries, &settings.rounding),
+        settings.expected_daily_minutes as i64 * 60,
+    )
+}
+
+/// Shows what is left of the weekly target and burns it down per day
+fn update_week_target(
+    week_view: &WeekView,
+    entries: &[db::TimeEntry],
+    week_start: NaiveDate,
+    target_seconds: i64,
+    settings: &Settings,
+) {
+    let daily_seconds: [i64; 7] = std::array::from_fn(|index| {
+        let day = week_start + chrono::Days::new(index as u64);
+        let day_entries: Vec<db::TimeEntry> = entries
+            .iter()
+            .filter(|e| e.start_time.with_timezone(&Local).date_naive() == day)
+            .cloned()
+            .collect();
+        calculate_entries_duration(&day_entries, &settings.rounding)
+    });
+    let format = settings.duration_format;
+    let remaining = target_seconds - calculate_entries_duration(entries, &settings.rounding);
+    week_view.target_label.set_label(&if remaining > 0 {
+        format!("{} left of {}", format.format(remaining), format.format(target_seconds))
+    } else {
+        format!("Target of {} met", format.format(target_seconds))
+    });
 
-            for entry in day_entries {
-                let row = create_entry_row_compact(entry, conn_ref);
-                day_list.append(&row);
+    let burndown = forecast::week_burndown(
+        week_start,
+        Local::now().date_naive(),
+        settings.work_days_per_week,
+        target_seconds,
+        &daily_seconds,
+    );
+    let tooltip: Vec<String> = burndown
+        .iter()
+        .enumerate()
+        .map(|(index, day)| {
+            let weekday = (week_start + chrono::Days::new(index as u64)).format("%A");
+            match day.remaining {
+                Some(remaining) => format!(
+                    "{}: {} left, {} at an even pace",
+                    weekday,
+                    format.format(remaining),
+                    format.format(day.ideal)
+                ),
+                None => format!("{}: {} left at an even pace", weekday, format.format(day.ideal)),
             }
+        })
+        .collect();
+    week_view.burndown.update(target_seconds, &burndown, &tooltip.join("\n"));
+}
 
-            days_box.append(&day_list);
+/// The hidden chart projects in a stable order, for fingerprints
+fn hidden_projects_fingerprint(hidden: &HashSet<Option<i64>>) -> Vec<Option<i64>> {
+    let mut hidden: Vec<_> = hidden.iter().copied().collect();
+    hidden.sort();
+    hidden
+}
+
+/// Shows `all_entries` in a range view: the total, the project breakdown and
+/// the entries grouped by day. The title is set by the caller beforehand.
+fn update_range_view(week_view: &mut WeekView, all_entries: Vec<db::TimeEntry>, state_borrow: &AppState) {
+    let settings = &state_borrow.settings;
+    let weekly_total_seconds = calculate_entries_duration(&all_entries, &settings.rounding);
+    let duration_format = settings.duration_format;
+    let now = Utc::now();
+    let projects = load_entry_projects(&state_borrow.db_conn, &all_entries);
+
+    week_view
+        .total_label
+        .set_label(&format!("Total: {}", duration_format.format(weekly_total_seconds)));
+    let project_rows = report::group_entries(
+        &all_entries,
+        &projects,
+        Grouping::Project,
+        &settings.rounding,
+        now,
+        &Local,
+    );
+    *week_view.summary_text.borrow_mut() = report::rows_as_text(
+        &week_view.week_label.label(),
+        Grouping::Project.label(),
+        &project_rows,
+        weekly_total_seconds,
+        duration_format,
+    );
+
+    // Rebuild the project breakdown only when the entries it summarizes changed
+    let breakdown_fingerprint = list_diff::fingerprint(&(
+        all_entries
+            .iter()
+            .map(|e| entry_row_fingerprint(e, &projects, settings, now, false))
+            .collect::<Vec<_>>(),
+        duration_format,
+        hidden_projects_fingerprint(&state_borrow.hidden_chart_projects),
+    ));
+    if week_view.breakdown_fingerprint != Some(breakdown_fingerprint) {
+        while let Some(child) = week_view.breakdown_slot.first_child() {
+            week_view.breakdown_slot.remove(&child);
         }
+        let breakdown = create_project_breakdown(
+            &all_entries,
+            &state_borrow.db_conn,
+            settings,
+            &state_borrow.hidden_chart_projects,
+        );
+        week_view.breakdown_slot.append(&breakdown);
+        week_view.breakdown_fingerprint = Some(breakdown_fingerprint);
+    }
+
+    // Group entries by day
+    let mut entries_by_day: HashMap<NaiveDate, Vec<db::TimeEntry>> = HashMap::new();
+    for entry in all_entries {
+        let date = entry.start_time.with_timezone(&Local).date_naive();
+        entries_by_day.entry(date).or_default().push(entry);
     }
 
-    scrolled_window.set_child(Some(&days_box));
-    entries_section.append(&scrolled_window);
+    // Sort days (most recent first)
+    let mut days: Vec<_> = entries_by_day.keys().cloned().collect();
+    days.sort_by(|a, b| b.cmp(a));
+
+    week_view.empty_label.set_visible(days.is_empty());
+
+    let scrolled_window = week_view.scrolled_window.clone();
+    preserve_scroll_position(&scrolled_window, || {
+        update_day_sections(week_view, &days, &entries_by_day, &projects, state_borrow);
+    });
+}
+
+/// Copies the summary of a range view to the clipboard when its copy button is clicked
+fn connect_copy_summary(state: &Rc<RefCell<AppState>>, week_view: &WeekView) {
+    let state = state.clone();
+    let summary_text = week_view.summary_text.clone();
+    week_view.copy_button.connect_clicked(move |button| {
+        button.clipboard().set_text(&summary_text.borrow());
+        state.borrow().show_info("Summary copied");
+    });
+}
+
+/// Brings the day sections of the weekly view in line with `days`, most recent first
+fn update_day_sections(
+    week_view: &mut WeekView,
+    days: &[NaiveDate],
+    entries_by_day: &HashMap<NaiveDate, Vec<db::TimeEntry>>,
+    projects: &HashMap<i64, db::Project>,
+    state: &AppState,
+) {
+    let settings = &state.settings;
+    let duration_format = settings.duration_format;
+    let now = Utc::now();
+
+    // Drop sections of days that no longer have entries
+    let days_box = week_view.days_box.clone();
+    week_view.days.retain(|day, section| {
+        let keep = entries_by_day.contains_key(day);
+        if !keep {
+            days_box.remove(&section.container);
+        }
+        keep
+    });
+
+    let mut previous: Option<gtk::Widget> = None;
+    for day in days {
+        let day_entries = entries_by_day.get(day).unwrap();
+        let day_total = calculate_entries_duration(day_entries, &settings.rounding);
+
+        let section = week_view.days.entry(*day).or_insert_with(|| {
+            let section = DaySection::new(*day);
+            days_box.append(&section.container);
+            section
+        });
+        section.total_label.set_label(&duration_format.format(day_total));
+        days_box.reorder_child_after(&section.container, previous.as_ref());
+        previous = Some(section.container.clone().upcast());
+
+        let items: Vec<(i64, u64)> = day_entries
+            .iter()
+            .map(|e| (e.id, entry_row_fingerprint(e, projects, settings, now, false)))
+            .collect();
+        section.entries.update(&items, |id| {
+            let entry = day_entries.iter().find(|e| e.id == id).unwrap();
+            create_entry_row_compact(entry, &state.db_conn, settings)
+        });
+    }
 }
 
 /// Creates a compact entry row for weekly view (no action buttons)
-fn create_entry_row_compact(entry: &db::TimeEntry, conn: &Connection) -> gtk::ListBoxRow {
+fn create_entry_row_compact(
+    entry: &db::TimeEntry,
+    conn: &Connection,
+    settings: &Settings,
+) -> gtk::ListBoxRow {
     let row = gtk::ListBoxRow::builder()
         .selectable(false)
         .activatable(false)
         .build();
 
+    let row_padding = if settings.compact { 2 } else { 6 };
     let hbox = gtk::Box::builder()
         .orientation(gtk::Orientation::Horizontal)
         .spacing(8)
-        .margin_top(6)
-        .margin_bottom(6)
+        .margin_top(row_padding)
+        .margin_bottom(row_padding)
         .margin_start(8)
         .margin_end(8)
         .build();
@@ -1198,10 +2902,9 @@ fn create_entry_row_compact(entry: &db::TimeEntry, conn: &Connection) -> gtk::Li
     hbox.append(&desc_label);
 
     // Duration
-    let end = entry.end_time.unwrap_or_else(Utc::now);
-    let duration_secs = end.signed_duration_since(entry.start_time).num_seconds().max(0);
+    let duration_secs = settings.rounding.entry_seconds(entry, Utc::now());
     let duration_label = gtk::Label::builder()
-        .label(&format_duration(duration_secs))
+        .label(TrackedDuration::from_seconds(duration_secs).clock())
         .halign(gtk::Align::End)
         .css_classes(["monospace", "dim-label"])
         .build();
@@ -1211,94 +2914,329 @@ fn create_entry_row_compact(entry: &db::TimeEntry, conn: &Connection) -> gtk::Li
     row
 }
 
-/// Refreshes the view based on the current view mode
+/// Schedules a refresh of the current view. Refreshes requested in the same
+/// main loop iteration are coalesced into one.
 fn refresh_view(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
+    if state.borrow().refresh_pending {
+        return;
+    }
+    state.borrow_mut().refresh_pending = true;
+
+    let window = window.clone();
+    glib::idle_add_local_once(move || {
+        state.borrow_mut().refresh_pending = false;
+        refresh_view_now(state, &window);
+    });
+}
+
+/// Refreshes the view based on the current view mode
+fn refresh_view_now(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
+    // A filtered project may have been deleted meanwhile; archived ones keep their entries
+    let stale_filter = match state.borrow().project_filter {
+        ProjectFilter::Project(Some(id)) => {
+            !matches!(db::get_project_by_id(&state.borrow().db_conn, id), Ok(Some(_)))
+        }
+        _ => false,
+    };
+    if stale_filter {
+        state.borrow_mut().project_filter = ProjectFilter::All;
+    }
+    // Chips show project names and colors, which may have changed too
+    pinned::refresh_pinned_tasks(&state);
+    tag_filter::refresh_tag_filter_chips(&state);
+    state.borrow().update_tray_recent();
+
     let view_mode = state.borrow().view_mode;
+    let searching = !state.borrow().search_text.is_empty();
     match view_mode {
-        ViewMode::Today => refresh_today_view(state, window),
-        ViewMode::Week => refresh_weekly_view(state, window),
+        _ if searching => search::refresh_search_view(state.clone()),
+        ViewMode::Today => refresh_today_view(state.clone(), window),
+        ViewMode::Week | ViewMode::Custom => refresh_range_view(state.clone(), window),
+        ViewMode::Month => month_view::refresh_month_view(state.clone(), window),
+        ViewMode::All => history::refresh_history_view(state.clone()),
     }
+    if let Some(ref sidebar) = state.borrow().project_sidebar {
+        sidebar.update(&state.borrow());
+    }
+    state.borrow().update_review_badge();
+    state.borrow_mut().reload_today_total();
+    state.borrow().update_goal_ring();
+    state.borrow().update_status_service();
+    state.borrow_mut().update_do_not_disturb();
 }
 
-/// Refreshes the entries section for today view (similar to original but with view toggle support)
+/// Creates the container of the Today view: the day header, with buttons
+/// grouping the entries by project and adding an absence, and the day's
+/// timeline above the scrollable entries list
+fn create_today_view(
+    day_total_label: &gtk::Label,
+    today_timeline: &DayTimeline,
+    group_button: &gtk::ToggleButton,
+    add_absence_button: &gtk::Button,
+    entries_list_box: &gtk::ListBox,
+) -> gtk::Box {
+    let today_view = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(0)
+        .vexpand(true)
+        .build();
+    let header = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .css_classes(["day-header"])
+        .build();
+    day_total_label.set_hexpand(true);
+    header.append(day_total_label);
+    header.append(group_button);
+    header.append(add_absence_button);
+    today_view.append(&header);
+    today_view.append(&today_timeline.container);
+
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .vexpand(true)
+        .child(entries_list_box)
+        .build();
+    today_view.append(&scrolled_window);
+
+    today_view
+}
+
+/// Refreshes the entries section for today view, rebuilding only the rows that changed
 fn refresh_today_view(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
     let state_borrow = state.borrow();
+    show_in_entries_section(&state_borrow.entries_section, &state_borrow.today_view);
 
-    // Clear the entries section
-    let entries_section = &state_borrow.entries_section;
-    while let Some(child) = entries_section.first_child() {
-        entries_section.remove(&child);
-    }
-
-    // Recreate the day total label and entries list
     let today = Local::now().date_naive();
-    let entries = match db::get_entries_for_date(&state_borrow.db_conn, today) {
+    let mut entries = match db::get_entries_for_date(&state_borrow.db_conn, today) {
         Ok(entries) => entries,
         Err(e) => {
             state_borrow.show_error(&format!("Failed to load entries: {}", e));
             Vec::new()
         }
     };
+    entries.retain(|e| state_borrow.entry_filter_matches(e));
 
-    // Calculate total time for the day
-    let total_seconds = calculate_entries_duration(&entries);
+    // Absences have no project nor tags, so they are only shown unfiltered
+    let absences = if state_borrow.project_filter == ProjectFilter::All && state_borrow.tag_filter.is_empty() {
+        db::get_absences_for_date(&state_borrow.db_conn, today).unwrap_or_else(|e| {
+            state_borrow.show_error(&format!("Failed to load absences: {}", e));
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
 
-    // Add day header label
-    let today_formatted = today.format("%A, %B %d").to_string();
-    let total_str = format_duration(total_seconds);
+    // Calculate total time for the day; absences are not worked time
+    let total_seconds = calculate_entries_duration(&entries, &state_borrow.settings.rounding);
+    let away_seconds: i64 = absences
+        .iter()
+        .map(|a| (a.end_time - a.start_time).num_seconds().max(0))
+        .sum();
 
-    let day_total_label = gtk::Label::builder()
-        .use_markup(true)
-        .halign(gtk::Align::Start)
-        .css_classes(["day-header"])
-        .label(&format!("<b>{}</b>  •  Total: {}", today_formatted, total_str))
-        .build();
-    entries_section.append(&day_total_label);
-
-    // Update the original day_total_label reference too
+    // Update the day header label
+    let today_formatted = today.format("%A, %B %d").to_string();
+    let duration_format = state_borrow.settings.duration_format;
+    let away_str = if absences.is_empty() {
+        String::new()
+    } else {
+        format!("  •  Away: {}", duration_format.format(away_seconds))
+    };
     state_borrow.day_total_label.set_markup(&format!(
-        "<b>{}</b>  •  Total: {}",
+        "<b>{}</b>  •  Total: {}{}",
         today_formatted,
-        total_str
+        duration_format.format(total_seconds),
+        away_str
     ));
 
-    // Create scrollable window for entries list
-    let scrolled_window = gtk::ScrolledWindow::builder()
-        .hscrollbar_policy(gtk::PolicyType::Never)
-        .vscrollbar_policy(gtk::PolicyType::Automatic)
-        .vexpand(true)
+    let now = Utc::now();
+    let projects = load_entry_projects(&state_borrow.db_conn, &entries);
+    let blocks = timeline::day_blocks(&entries, today, now, &Local)
+        .into_iter()
+        .map(|block| {
+            let color = block.project_id.and_then(|id| projects.get(&id)).map(|p| p.color.as_str());
+            (block.start, block.end, color)
+        })
+        .collect();
+    state_borrow
+        .today_timeline
+        .update(blocks, Some(timeline::day_fraction(now, today, &Local)));
+
+    let running_id = state_borrow.running_entry.as_ref().map(|e| e.id);
+    let compact = state_borrow.settings.compact;
+    let entry_item = |e: &db::TimeEntry| {
+        let fingerprint =
+            entry_row_fingerprint(e, &projects, &state_borrow.settings, now, running_id == Some(e.id));
+        (TodayRow::Entry(e.id), fingerprint)
+    };
+    let absence_item =
+        |a: &db::Absence| (TodayRow::Absence(a.id), absence::absence_row_fingerprint(a, compact));
+
+    // Header label, color and subtotal per project header
+    let mut headers: HashMap<TodayRow, (String, Option<String>, String)> = HashMap::new();
+    let items: Vec<(TodayRow, u64)> = if state_borrow.settings.group_today_by_project {
+        // Projects longest first, each with its entries latest first
+        let mut items = Vec::new();
+        let groups = report::group_entries(
+            &entries,
+            &projects,
+            Grouping::Project,
+            &state_borrow.settings.rounding,
+            now,
+            &Local,
+        );
+        for group in groups {
+            let key = TodayRow::ProjectHeader(group.project_id);
+            let subtotal = duration_format.format(group.seconds);
+            items.push((key, list_diff::fingerprint(&(&group.label, &group.color, &subtotal))));
+            headers.insert(key, (group.label, group.color, subtotal));
+
+            let mut group_entries: Vec<&db::TimeEntry> = entries
+                .iter()
+                .filter(|e| e.project_id.filter(|id| projects.contains_key(id)) == group.project_id)
+                .collect();
+            group_entries.sort_by_key(|e| std::cmp::Reverse(e.start_time));
+            items.extend(group_entries.into_iter().map(entry_item));
+        }
+        if !absences.is_empty() {
+            let subtotal = duration_format.format(away_seconds);
+            items.push((TodayRow::AwayHeader, list_diff::fingerprint(&subtotal)));
+            headers.insert(TodayRow::AwayHeader, ("Away".to_string(), None, subtotal));
+
+            let mut sorted_absences: Vec<&db::Absence> = absences.iter().collect();
+            sorted_absences.sort_by_key(|a| std::cmp::Reverse(a.start_time));
+            items.extend(sorted_absences.into_iter().map(absence_item));
+        }
+        items
+    } else {
+        // Entries and absences interleaved by start time, latest first
+        let mut rows: Vec<(DateTime<Utc>, (TodayRow, u64))> = entries
+            .iter()
+            .map(|e| (e.start_time, entry_item(e)))
+            .chain(absences.iter().map(|a| (a.start_time, absence_item(a))))
+            .collect();
+        // Rows starting later, such as manual entries planned ahead, go above the
+        // marker; it comes first so an entry started this instant stays below it
+        if !rows.is_empty() {
+            let label = now.with_timezone(&Local).format("%H:%M").to_string();
+            rows.insert(0, (now, (TodayRow::Now, list_diff::fingerprint(&(&label, compact)))));
+        }
+        rows.sort_by_key(|(start_time, _)| std::cmp::Reverse(*start_time));
+        rows.into_iter().map(|(_, item)| item).collect()
+    };
+    let today_list = state_borrow.today_list.clone();
+
+    // Rows hold a state reference, so the borrow must be released before building them
+    drop(state_borrow);
+
+    let update = || {
+        today_list.update(&items, |key| match key {
+            TodayRow::Entry(id) => {
+                let entry = entries.iter().find(|e| e.id == id).unwrap();
+                create_entry_row_with_actions(entry, state.clone(), window)
+            }
+            TodayRow::Absence(id) => {
+                let absence = absences.iter().find(|a| a.id == id).unwrap();
+                absence::create_absence_row(absence, state.clone(), window)
+            }
+            TodayRow::Now => create_now_row(now, compact),
+            TodayRow::ProjectHeader(_) | TodayRow::AwayHeader => {
+                let (label, color, subtotal) = &headers[&key];
+                create_group_header_row(label, color.as_deref(), subtotal)
+            }
+        });
+    };
+    match today_list
+        .list_box()
+        .ancestor(gtk::ScrolledWindow::static_type())
+        .and_downcast::<gtk::ScrolledWindow>()
+    {
+        Some(scrolled_window) => preserve_scroll_position(&scrolled_window, update),
+        None => update(),
+    }
+}
+
+/// Creates the Today list's "now" marker: a thin line labelled with the time
+fn create_now_row(now: DateTime<Utc>, compact: bool) -> gtk::ListBoxRow {
+    let row_padding = if compact { 0 } else { 2 };
+    let hbox = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .margin_top(row_padding)
+        .margin_bottom(row_padding)
+        .margin_start(12)
+        .margin_end(12)
         .build();
+    hbox.append(
+        &gtk::Label::builder()
+            .label(format!("Now {}", now.with_timezone(&Local).format("%H:%M")))
+            .css_classes(["caption", "now-label"])
+            .build(),
+    );
+    hbox.append(
+        &gtk::Box::builder()
+            .hexpand(true)
+            .valign(gtk::Align::Center)
+            .css_classes(["now-line"])
+            .build(),
+    );
 
-    let entries_list_box = gtk::ListBox::builder()
-        .selection_mode(gtk::SelectionMode::None)
-        .css_classes(["boxed-list"])
+    gtk::ListBoxRow::builder()
+        .selectable(false)
+        .activatable(false)
+        .focusable(false)
+        .child(&hbox)
+        .build()
+}
+
+/// Creates the header of a group in the Today list: the project's color,
+/// its name and the subtotal of its entries
+fn create_group_header_row(label: &str, color: Option<&str>, subtotal: &str) -> gtk::ListBoxRow {
+    let hbox = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(8)
+        .css_classes(["day-section-header"])
         .build();
 
-    if entries.is_empty() {
-        let empty_label = gtk::Label::builder()
-            .label("No entries for today")
-            .css_classes(["dim-label"])
-            .margin_top(20)
-            .margin_bottom(20)
+    if let Some(color) = color {
+        let color_box = gtk::Box::builder()
+            .valign(gtk::Align::Center)
+            .css_classes(["legend-swatch"])
             .build();
-        entries_list_box.append(&empty_label);
-        scrolled_window.set_child(Some(&entries_list_box));
-        entries_section.append(&scrolled_window);
-    } else {
-        // Need to drop the borrow to create rows with state reference
-        drop(state_borrow);
-
-        // Add entry rows with actions
-        for entry in entries {
-            let row = create_entry_row_with_actions(&entry, state.clone(), window);
-            entries_list_box.append(&row);
-        }
-        scrolled_window.set_child(Some(&entries_list_box));
-        state.borrow().entries_section.append(&scrolled_window);
+        let css_provider = gtk::CssProvider::new();
+        css_provider.load_from_data(&format!("box {{ background-color: {}; }}", color));
+        color_box
+            .style_context()
+            .add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+        hbox.append(&color_box);
     }
+
+    hbox.append(
+        &gtk::Label::builder()
+            .label(label)
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .css_classes(["heading"])
+            .build(),
+    );
+    hbox.append(
+        &gtk::Label::builder()
+            .label(subtotal)
+            .halign(gtk::Align::End)
+            .css_classes(["monospace"])
+            .build(),
+    );
+
+    gtk::ListBoxRow::builder()
+        .selectable(false)
+        .activatable(false)
+        .focusable(false)
+        .child(&hbox)
+        .build()
 }
 
-/// Default project colors for the color picker
+/// Preset project colors offered next to the color picker
 const PROJECT_COLORS: &[&str] = &[
     "#3498db", // Blue
     "#e74c3c", // Red
@@ -1310,6 +3248,22 @@ const PROJECT_COLORS: &[&str] = &[
     "#607d8b", // Blue Grey
 ];
 
+/// Parses a project color such as "#3498db", black when it isn't a color
+fn parse_color(color: &str) -> gtk::gdk::RGBA {
+    gtk::gdk::RGBA::parse(color).unwrap_or(gtk::gdk::RGBA::BLACK)
+}
+
+/// Formats a color the way project colors are stored, e.g. "#3498db"
+fn format_color(rgba: &gtk::gdk::RGBA) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(rgba.red()),
+        channel(rgba.green()),
+        channel(rgba.blue())
+    )
+}
+
 /// Creates a row for a project in the project management dialog
 fn create_project_row(
     project: &db::Project,
@@ -1346,74 +3300,397 @@ fn create_project_row(
         gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
 
+    // Active projects are reordered by dragging the handle onto another row,
+    // setting their order in the dropdown
+    if !project.archived {
+        let handle = gtk::Image::builder()
+            .icon_name("list-drag-handle-symbolic")
+            .tooltip_text("Drag to reorder")
+            .css_classes(["dim-label"])
+            .build();
+        let drag_source = gtk::DragSource::new();
+        drag_source.set_actions(gtk::gdk::DragAction::MOVE);
+        drag_source.set_content(Some(&gtk::gdk::ContentProvider::for_value(&project.id.to_value())));
+        let row_for_icon = row.clone();
+        drag_source.connect_drag_begin(move |source, _| {
+            source.set_icon(Some(&gtk::WidgetPaintable::new(Some(&row_for_icon))), 0, 0);
+        });
+        handle.add_controller(drag_source);
+        hbox.append(&handle);
+
+        let drop_target = gtk::DropTarget::new(i64::static_type(), gtk::gdk::DragAction::MOVE);
+        let project_id_for_drop = project.id;
+        let state_for_drop = state.clone();
+        let projects_list_box_for_drop = projects_list_box.clone();
+        drop_target.connect_drop(move |_, value, _, _| {
+            let Ok(source_id) = value.get::<i64>() else {
+                return false;
+            };
+            if source_id == project_id_for_drop {
+                return false;
+            }
+            let result = db::move_project(&state_for_drop.borrow().db_conn, source_id, project_id_for_drop);
+            if let Err(e) = result {
+                state_for_drop.borrow().show_failure("reorder projects", &e);
+                return false;
+            }
+            // Rebuilding the list removes the row handling this drop, so wait
+            let state = state_for_drop.clone();
+            let projects_list_box = projects_list_box_for_drop.clone();
+            glib::idle_add_local_once(move || {
+                refresh_projects_list(&state, &projects_list_box);
+                state.borrow_mut().refresh_projects();
+            });
+            true
+        });
+        row.add_controller(drop_target);
+    }
+
     hbox.append(&color_box);
 
-    // Project name label
+    // Project name label, dimmed for archived projects
     let name_label = gtk::Label::builder()
         .label(&project.name)
         .halign(gtk::Align::Start)
         .hexpand(true)
         .build();
-    hbox.append(&name_label);
-
-    // Delete button
-    let delete_button = gtk::Button::builder()
-        .icon_name("user-trash-symbolic")
-        .tooltip_text("Delete project")
+    if project.archived {
+        name_label.add_css_class("dim-label");
+        name_label.set_tooltip_text(Some("Archived: hidden from the dropdown, entries kept in reports"));
+    }
+    hbox.append(&name_label);
+
+    // Code of projects imported from a project management tool
+    if let Some(ref code) = project.code {
+        hbox.append(
+            &gtk::Label::builder()
+                .label(code)
+                .css_classes(["caption", "monospace", "dim-label"])
+                .build(),
+        );
+    }
+
+    // Assign past entries by keyword
+    let assign_button = gtk::Button::builder()
+        .icon_name("edit-find-replace-symbolic")
+        .tooltip_text("Assign matching entries without a project")
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+
+    let project_for_assign = project.clone();
+    let state_for_assign = state.clone();
+    assign_button.connect_clicked(move |button| {
+        if let Some(dialog) = button.root().and_downcast::<gtk::Window>() {
+            assign::show_assign_dialog(state_for_assign.clone(), &dialog, &project_for_assign);
+        }
+    });
+
+    hbox.append(&assign_button);
+
+    // Move all entries to another project, keeping this one
+    let move_button = gtk::Button::builder()
+        .icon_name("mail-send-symbolic")
+        .tooltip_text("Move all entries to another project")
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+
+    let project_for_move = project.clone();
+    let state_for_move = state.clone();
+    let projects_list_box_for_move = projects_list_box.clone();
+    move_button.connect_clicked(move |button| {
+        let Some(dialog) = button.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+        let state_for_done = state_for_move.clone();
+        let projects_list_box_for_done = projects_list_box_for_move.clone();
+        reassign::show_move_entries_dialog(state_for_move.clone(), &dialog, &project_for_move, false, move || {
+            refresh_projects_list(&state_for_done, &projects_list_box_for_done)
+        });
+    });
+
+    hbox.append(&move_button);
+
+    // Deep work projects turn on Do Not Disturb while tracked
+    let deep_work_button = gtk::ToggleButton::builder()
+        .icon_name("notifications-disabled-symbolic")
+        .tooltip_text("Deep work: Do Not Disturb while tracked, if enabled in Preferences")
+        .active(project.deep_work)
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+
+    let project_id_for_deep_work = project.id;
+    let state_for_deep_work = state.clone();
+    deep_work_button.connect_toggled(move |button| {
+        let result = db::set_project_deep_work(
+            &state_for_deep_work.borrow().db_conn,
+            project_id_for_deep_work,
+            button.is_active(),
+        );
+        if let Err(e) = result {
+            state_for_deep_work.borrow().show_error(&format!("Failed to update project: {}", e));
+            return;
+        }
+        let mut state_borrow = state_for_deep_work.borrow_mut();
+        if let Some(project) = state_borrow.projects.iter_mut().find(|p| p.id == project_id_for_deep_work) {
+            project.deep_work = button.is_active();
+        }
+        state_borrow.update_do_not_disturb();
+    });
+
+    hbox.append(&deep_work_button);
+
+    hbox.append(&create_deadline_button(project, state.clone(), window));
+
+    // Archive finished projects, or bring archived ones back
+    let archive_button = gtk::Button::builder()
+        .icon_name(if project.archived { "edit-undo-symbolic" } else { "package-x-generic-symbolic" })
+        .tooltip_text(if project.archived {
+            "Unarchive project"
+        } else {
+            "Archive project: hide it from the dropdown, keeping its entries"
+        })
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+
+    let project_id_for_archive = project.id;
+    let archive = !project.archived;
+    let state_for_archive = state.clone();
+    let projects_list_box_for_archive = projects_list_box.clone();
+    archive_button.connect_clicked(move |_| {
+        let result = db::set_projects_archived(
+            &state_for_archive.borrow().db_conn,
+            &[project_id_for_archive],
+            archive,
+        );
+        if let Err(e) = result {
+            state_for_archive.borrow().show_error(&format!("Failed to update project: {}", e));
+            return;
+        }
+        refresh_projects_list(&state_for_archive, &projects_list_box_for_archive);
+        state_for_archive.borrow_mut().refresh_projects();
+    });
+
+    hbox.append(&archive_button);
+
+    hbox.append(&create_merge_button(project, state.clone(), projects_list_box, window));
+
+    // Delete button
+    let delete_button = gtk::Button::builder()
+        .icon_name("user-trash-symbolic")
+        .tooltip_text("Delete project")
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+
+    // Asks where the project's entries go before deleting it
+    let project_for_delete = project.clone();
+    let state_for_delete = state.clone();
+    let projects_list_box_clone = projects_list_box.clone();
+    delete_button.connect_clicked(move |button| {
+        let Some(dialog) = button.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+        let state_for_done = state_for_delete.clone();
+        let projects_list_box_for_done = projects_list_box_clone.clone();
+        reassign::show_move_entries_dialog(state_for_delete.clone(), &dialog, &project_for_delete, true, move || {
+            refresh_projects_list(&state_for_done, &projects_list_box_for_done)
+        });
+    });
+
+    hbox.append(&delete_button);
+
+    row.set_child(Some(&hbox));
+    row
+}
+
+/// Creates the deadline button of a project row, showing the days left and
+/// opening a calendar to set or clear the deadline
+fn create_deadline_button(
+    project: &db::Project,
+    state: Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+) -> gtk::MenuButton {
+    let calendar = gtk::Calendar::new();
+    let clear_button = gtk::Button::builder()
+        .label("Clear Deadline")
+        .css_classes(["flat"])
+        .build();
+    let popover_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(6)
+        .build();
+    popover_box.append(&calendar);
+    popover_box.append(&clear_button);
+    let popover = gtk::Popover::builder().child(&popover_box).build();
+    let button = gtk::MenuButton::builder()
+        .popover(&popover)
+        .css_classes(["flat", "entry-action-button"])
+        .build();
+
+    let show_deadline = {
+        let button = button.clone();
+        let clear_button = clear_button.clone();
+        move |deadline: Option<NaiveDate>| {
+            let today = Local::now().date_naive();
+            match deadline {
+                Some(deadline) => {
+                    button.set_label(&forecast::format_deadline(deadline, today));
+                    button.set_tooltip_text(Some(&format!("Due {}", deadline.format("%b %-d, %Y"))));
+                    if (deadline - today).num_days() <= forecast::DEADLINE_WARNING_DAYS {
+                        button.add_css_class("warning");
+                    } else {
+                        button.remove_css_class("warning");
+                    }
+                }
+                None => {
+                    button.set_icon_name("x-office-calendar-symbolic");
+                    button.set_tooltip_text(Some("Set deadline"));
+                    button.remove_css_class("warning");
+                }
+            }
+            clear_button.set_sensitive(deadline.is_some());
+        }
+    };
+    show_deadline(project.deadline);
+    if let Some(date) = project.deadline.and_then(date_picker::to_glib_date) {
+        calendar.select_day(&date);
+    }
+
+    // Saves the deadline, then refreshes the dropdown tooltips and the weekly summary
+    let set_deadline = {
+        let project_id = project.id;
+        let window = window.clone();
+        let popover = popover.clone();
+        Rc::new(move |deadline: Option<NaiveDate>| {
+            popover.popdown();
+            if let Err(e) = db::set_project_deadline(&state.borrow().db_conn, project_id, deadline) {
+                state.borrow().show_error(&format!("Failed to update project: {}", e));
+                return;
+            }
+            show_deadline(deadline);
+            state.borrow_mut().refresh_projects();
+            refresh_view(state.clone(), &window);
+        })
+    };
+    let set_deadline_for_calendar = set_deadline.clone();
+    calendar.connect_day_selected(move |calendar| {
+        if let Some(date) = date_picker::calendar_date(calendar) {
+            set_deadline_for_calendar(Some(date));
+        }
+    });
+    clear_button.connect_clicked(move |_| set_deadline(None));
+
+    button
+}
+
+/// Creates the merge button of a project row, listing the other active
+/// projects to move its entries to before deleting it, e.g. to clean up a
+/// duplicate created by an import or a typo
+fn create_merge_button(
+    project: &db::Project,
+    state: Rc<RefCell<AppState>>,
+    projects_list_box: &gtk::ListBox,
+    window: &adw::ApplicationWindow,
+) -> gtk::MenuButton {
+    let targets_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(2)
+        .build();
+    let popover_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(6)
+        .build();
+    popover_box.append(
+        &gtk::Label::builder()
+            .label(format!("Merge \"{}\" into", project.name))
+            .halign(gtk::Align::Start)
+            .css_classes(["heading"])
+            .build(),
+    );
+    popover_box.append(
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(300)
+            .child(&targets_box)
+            .build(),
+    );
+    let popover = gtk::Popover::builder().child(&popover_box).build();
+    let button = gtk::MenuButton::builder()
+        .icon_name("object-merge-symbolic")
+        .tooltip_text("Merge into another project")
+        .popover(&popover)
         .css_classes(["flat", "entry-action-button"])
         .build();
 
-    let project_id = project.id;
-    let project_name = project.name.clone();
-    let state_for_delete = state.clone();
-    let projects_list_box_clone = projects_list_box.clone();
-    let window_clone = window.clone();
-
-    delete_button.connect_clicked(move |_| {
-        // Create confirmation dialog
-        let dialog = gtk::MessageDialog::builder()
-            .transient_for(&window_clone)
-            .modal(true)
-            .message_type(gtk::MessageType::Question)
-            .buttons(gtk::ButtonsType::None)
-            .text("Delete Project?")
-            .secondary_text(format!(
-                "Are you sure you want to delete \"{}\"? Time entries will keep their descriptions but lose their project association.",
-                project_name
-            ))
-            .build();
-
-        dialog.add_button("Cancel", gtk::ResponseType::Cancel);
-        dialog.add_button("Delete", gtk::ResponseType::Accept);
-
-        // Style the delete button as destructive
-        if let Some(button) = dialog.widget_for_response(gtk::ResponseType::Accept) {
-            button.add_css_class("destructive-action");
+    // The targets are listed when opened, so projects created since are offered
+    let source = project.clone();
+    let projects_list_box = projects_list_box.clone();
+    let window = window.clone();
+    popover.connect_show(move |popover| {
+        while let Some(child) = targets_box.first_child() {
+            targets_box.remove(&child);
+        }
+        let targets: Vec<db::Project> = state
+            .borrow()
+            .projects
+            .iter()
+            .filter(|target| target.id != source.id)
+            .cloned()
+            .collect();
+        if targets.is_empty() {
+            targets_box.append(
+                &gtk::Label::builder()
+                    .label("No other projects")
+                    .css_classes(["dim-label"])
+                    .build(),
+            );
+        }
+        for target in targets {
+            let target_button = gtk::Button::builder()
+                .label(&target.name)
+                .css_classes(["flat"])
+                .build();
+            let state = state.clone();
+            let source = source.clone();
+            let popover = popover.clone();
+            let projects_list_box = projects_list_box.clone();
+            let window = window.clone();
+            target_button.connect_clicked(move |_| {
+                popover.popdown();
+                let body = format!(
+                    "All entries of \"{}\" will move to \"{}\", then \"{}\" is deleted. This can't be undone.",
+                    source.name, target.name, source.name
+                );
+                let state = state.clone();
+                let source_id = source.id;
+                let target = target.clone();
+                let projects_list_box = projects_list_box.clone();
+                let window_for_merge = window.clone();
+                compat::confirm_destructive(&window, "Merge Projects?", &body, "Merge", move || {
+                    let result = db::merge_projects(&state.borrow().db_conn, source_id, target.id);
+                    match result {
+                        Ok(moved) => {
+                            if let Some(ref mut running) = state.borrow_mut().running_entry {
+                                if running.project_id == Some(source_id) {
+                                    running.project_id = Some(target.id);
+                                }
+                            }
+                            state
+                                .borrow()
+                                .show_info(&format!("Moved {} entries to \"{}\"", moved, target.name));
+                            refresh_projects_list(&state, &projects_list_box);
+                            state.borrow_mut().refresh_projects();
+                            refresh_view(state.clone(), &window_for_merge);
+                        }
+                        Err(e) => state.borrow().show_error(&format!("Failed to merge projects: {}", e)),
+                    }
+                });
+            });
+            targets_box.append(&target_button);
         }
-
-        let state_for_response = state_for_delete.clone();
-        let projects_list_box_for_response = projects_list_box_clone.clone();
-        dialog.connect_response(move |dialog, response| {
-            if response == gtk::ResponseType::Accept {
-                if let Err(e) = db::delete_project(&state_for_response.borrow().db_conn, project_id) {
-                    state_for_response.borrow().show_error(&format!("Failed to delete project: {}", e));
-                } else {
-                    // Refresh the projects list in the dialog
-                    refresh_projects_list(&state_for_response, &projects_list_box_for_response);
-                    // Refresh the project dropdown in the main window
-                    state_for_response.borrow_mut().refresh_projects();
-                }
-            }
-            dialog.close();
-        });
-
-        dialog.present();
     });
 
-    hbox.append(&delete_button);
-
-    row.set_child(Some(&hbox));
-    row
+    button
 }
 
 /// Refreshes the projects list in the project management dialog
@@ -1423,13 +3700,21 @@ fn refresh_projects_list(state: &Rc<RefCell<AppState>>, projects_list_box: &gtk:
         projects_list_box.remove(&child);
     }
 
-    // Reload projects from database
-    let projects = match db::get_all_projects(&state.borrow().db_conn) {
-        Ok(projects) => projects,
-        Err(e) => {
-            state.borrow().show_error(&format!("Failed to load projects: {}", e));
+    // Reload projects from database, archived ones last when shown
+    let projects = {
+        let state = state.borrow();
+        let projects = if state.show_archived_projects {
+            db::get_all_projects(&state.db_conn).and_then(|mut projects| {
+                projects.extend(db::get_archived_projects(&state.db_conn)?);
+                Ok(projects)
+            })
+        } else {
+            db::get_all_projects(&state.db_conn)
+        };
+        projects.unwrap_or_else(|e| {
+            state.show_error(&format!("Failed to load projects: {}", e));
             Vec::new()
-        }
+        })
     };
 
     if projects.is_empty() {
@@ -1472,6 +3757,22 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
         .show_end_title_buttons(true)
         .title_widget(&adw::WindowTitle::new("Manage Projects", ""))
         .build();
+    let cleanup_button = gtk::Button::builder()
+        .label("Clean Up…")
+        .tooltip_text("Archive or delete projects without entries")
+        .build();
+    header_bar.pack_start(&cleanup_button);
+    let import_button = gtk::Button::builder()
+        .label("Import…")
+        .tooltip_text("Create or update projects from a CSV with codes, rates and budgets")
+        .build();
+    header_bar.pack_start(&import_button);
+    let show_archived_button = gtk::ToggleButton::builder()
+        .icon_name("package-x-generic-symbolic")
+        .tooltip_text("Show Archived Projects")
+        .active(state.borrow().show_archived_projects)
+        .build();
+    header_bar.pack_end(&show_archived_button);
     content.append(&header_bar);
 
     // Create new project section
@@ -1484,26 +3785,11 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
         .margin_bottom(12)
         .build();
 
-    // Color picker button
-    let selected_color = Rc::new(RefCell::new(PROJECT_COLORS[0].to_string()));
-    let color_button = gtk::MenuButton::builder()
-        .css_classes(["project-color-button"])
-        .tooltip_text("Select color")
-        .build();
-
-    // Set initial color on button
-    let initial_css = gtk::CssProvider::new();
-    initial_css.load_from_data(&format!(
-        "button {{ background-color: {}; }}",
-        selected_color.borrow()
-    ));
-    color_button.style_context().add_provider(
-        &initial_css,
-        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
-    );
+    // Color picker button, any color, with the palette as quick presets
+    let color_button = compat::create_color_button(&parse_color(PROJECT_COLORS[0]));
+    color_button.set_valign(gtk::Align::Center);
+    color_button.set_tooltip_text(Some("Select color"));
 
-    // Color picker popover
-    let color_popover = gtk::Popover::new();
     let colors_grid = gtk::FlowBox::builder()
         .max_children_per_line(4)
         .selection_mode(gtk::SelectionMode::None)
@@ -1512,9 +3798,13 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
         .margin_top(8)
         .margin_bottom(8)
         .build();
-
-    let color_button_ref = color_button.clone();
-    let selected_color_ref = selected_color.clone();
+    let presets_popover = gtk::Popover::builder().child(&colors_grid).build();
+    let presets_button = gtk::MenuButton::builder()
+        .popover(&presets_popover)
+        .tooltip_text("Preset colors")
+        .valign(gtk::Align::Center)
+        .css_classes(["flat"])
+        .build();
 
     for &color in PROJECT_COLORS {
         let color_option = gtk::Button::builder()
@@ -1528,30 +3818,18 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
             gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
         );
 
-        let color_str = color.to_string();
-        let selected_color_clone = selected_color_ref.clone();
-        let color_button_clone = color_button_ref.clone();
-        let popover_clone = color_popover.clone();
-
+        let color_button_clone = color_button.clone();
+        let popover_clone = presets_popover.clone();
         color_option.connect_clicked(move |_| {
-            *selected_color_clone.borrow_mut() = color_str.clone();
-            // Update the color button appearance
-            let css = gtk::CssProvider::new();
-            css.load_from_data(&format!("button {{ background-color: {}; }}", color_str));
-            color_button_clone.style_context().add_provider(
-                &css,
-                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
-            );
+            color_button_clone.set_rgba(&parse_color(color));
             popover_clone.popdown();
         });
 
         colors_grid.insert(&color_option, -1);
     }
 
-    color_popover.set_child(Some(&colors_grid));
-    color_button.set_popover(Some(&color_popover));
-
     new_project_box.append(&color_button);
+    new_project_box.append(&presets_button);
 
     // Project name entry
     let name_entry = gtk::Entry::builder()
@@ -1597,10 +3875,40 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
     // Initial load of projects
     refresh_projects_list(&state, &projects_list_box);
 
+    let state_for_archived = state.clone();
+    let projects_list_box_for_archived = projects_list_box.clone();
+    show_archived_button.connect_toggled(move |button| {
+        state_for_archived.borrow_mut().show_archived_projects = button.is_active();
+        refresh_projects_list(&state_for_archived, &projects_list_box_for_archived);
+    });
+
+    // Connect cleanup button to list empty projects
+    let state_for_cleanup = state.clone();
+    let dialog_for_cleanup = dialog.clone();
+    let projects_list_box_for_cleanup = projects_list_box.clone();
+    cleanup_button.connect_clicked(move |_| {
+        cleanup::show_cleanup_dialog(
+            state_for_cleanup.clone(),
+            &dialog_for_cleanup,
+            &projects_list_box_for_cleanup,
+        );
+    });
+
+    let state_for_import = state.clone();
+    let dialog_for_import = dialog.clone();
+    let projects_list_box_for_import = projects_list_box.clone();
+    import_button.connect_clicked(move |_| {
+        project_import::import_projects(
+            state_for_import.clone(),
+            &dialog_for_import,
+            &projects_list_box_for_import,
+        );
+    });
+
     // Connect add button click
     let state_for_add = state.clone();
     let name_entry_clone = name_entry.clone();
-    let selected_color_for_add = selected_color.clone();
+    let color_button_for_add = color_button.clone();
     let projects_list_box_clone = projects_list_box.clone();
 
     add_button.connect_clicked(move |_| {
@@ -1610,7 +3918,7 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
             return;
         }
 
-        let color = selected_color_for_add.borrow().clone();
+        let color = format_color(&color_button_for_add.rgba());
         if let Err(e) = db::create_project(&state_for_add.borrow().db_conn, &name, &color) {
             state_for_add.borrow().show_error(&format!("Failed to create project: {}", e));
         } else {
@@ -1625,7 +3933,7 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
 
     // Connect Enter key in name entry to add project
     let state_for_activate = state.clone();
-    let selected_color_for_activate = selected_color.clone();
+    let color_button_for_activate = color_button.clone();
     let projects_list_box_for_activate = projects_list_box.clone();
 
     name_entry.connect_activate(move |entry| {
@@ -1635,7 +3943,7 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
             return;
         }
 
-        let color = selected_color_for_activate.borrow().clone();
+        let color = format_color(&color_button_for_activate.rgba());
         if let Err(e) = db::create_project(&state_for_activate.borrow().db_conn, &name, &color) {
             state_for_activate.borrow().show_error(&format!("Failed to create project: {}", e));
         } else {
@@ -1652,14 +3960,24 @@ fn show_projects_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationW
     dialog.present();
 }
 
-/// Builds and returns the main application window with Adwaita styling.
-pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
+/// Builds the main application window with Adwaita styling and presents it,
+/// unless `start_hidden` asks to wait in the tray, which needs a tray icon.
+/// A `read_only` window opens the database without migrating it and has the
+/// start button disabled; changes made anyway fail with an error toast.
+pub fn build_window(app: &adw::Application, read_only: bool, start_hidden: bool) -> adw::ApplicationWindow {
     // Apply CSS styles
     apply_css_styles();
 
-    // Create a header bar with the app title
+    // Create a header bar with the app title, and the profile unless it's
+    // the default one
+    let subtitle = match (profile::current(), read_only) {
+        (Some(name), true) => format!("{} · Read-Only", name),
+        (Some(name), false) => name,
+        (None, true) => "Read-Only".to_string(),
+        (None, false) => String::new(),
+    };
     let header_bar = adw::HeaderBar::builder()
-        .title_widget(&adw::WindowTitle::new("Time Tracking", ""))
+        .title_widget(&adw::WindowTitle::new("Time Tracking", &subtitle))
         .build();
 
     // Create menu button to access projects
@@ -1669,6 +3987,27 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         .build();
     header_bar.pack_end(&menu_button);
 
+    // Create preferences button
+    let preferences_button = gtk::Button::builder()
+        .icon_name("preferences-system-symbolic")
+        .tooltip_text("Preferences")
+        .build();
+    header_bar.pack_end(&preferences_button);
+
+    // Create mini timer button
+    let mini_timer_button = gtk::Button::builder()
+        .icon_name("view-restore-symbolic")
+        .tooltip_text("Mini Timer (Ctrl+M)")
+        .build();
+    header_bar.pack_end(&mini_timer_button);
+
+    // Create reports button
+    let reports_button = gtk::Button::builder()
+        .icon_name("x-office-spreadsheet-symbolic")
+        .tooltip_text("Reports")
+        .build();
+    header_bar.pack_end(&reports_button);
+
     // Create help button for keyboard shortcuts
     let help_button = gtk::Button::builder()
         .icon_name("help-about-symbolic")
@@ -1676,36 +4015,80 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         .build();
     header_bar.pack_end(&help_button);
 
+    // Create review queue button (hidden while nothing needs review)
+    let review_button = review::create_review_button();
+    header_bar.pack_start(&review_button);
+
     // Create the description entry field
     let description_entry = create_description_entry();
 
     // Initialize database connection
-    let conn = db::init_db().expect("Failed to initialize database");
+    let conn = if read_only {
+        db::open_db_read_only(&db::get_db_path())
+    } else {
+        db::init_db()
+    }
+    .expect("Failed to initialize database");
+
+    // Archive projects unused for longer than the configured period
+    let auto_archived = if read_only {
+        0
+    } else {
+        cleanup::auto_archive_projects(&conn).unwrap_or_else(|e| {
+            eprintln!("Failed to auto-archive projects: {}", e);
+            0
+        })
+    };
 
-    // Load projects from database
-    let projects = db::get_all_projects(&conn).unwrap_or_default();
+    // Load projects from database, recently used ones first
+    let (projects, recent_projects) = load_dropdown_projects(&conn).unwrap_or_default();
 
     // Create the project selector dropdown
-    let project_dropdown = create_project_dropdown(&projects);
+    let project_dropdown = create_project_dropdown(&projects, recent_projects, true);
 
     // Create the timer display label
     let timer_label = create_timer_label();
 
     // Create the start/stop button
     let start_stop_button = create_start_stop_button();
+    start_stop_button.set_sensitive(!read_only);
 
     // Create the entries list box
     let entries_list_box = gtk::ListBox::builder()
         .selection_mode(gtk::SelectionMode::None)
         .css_classes(["boxed-list"])
         .build();
+    add_entry_row_shortcuts(&entries_list_box);
 
     // Create the day total label (header for entries section)
     let day_total_label = gtk::Label::builder()
         .use_markup(true)
         .halign(gtk::Align::Start)
-        .css_classes(["day-header"])
         .build();
+    let add_absence_button = gtk::Button::builder()
+        .icon_name("x-office-calendar-symbolic")
+        .tooltip_text("Add an absence, such as a doctor's visit")
+        .valign(gtk::Align::Center)
+        .css_classes(["flat"])
+        .build();
+
+    let group_by_project_button = gtk::ToggleButton::builder()
+        .icon_name("view-list-symbolic")
+        .tooltip_text("Group entries by project")
+        .valign(gtk::Align::Center)
+        .css_classes(["flat"])
+        .build();
+
+    let today_timeline = DayTimeline::new();
+
+    // Create the Today view holding the day header, timeline and entries list
+    let today_view = create_today_view(
+        &day_total_label,
+        &today_timeline,
+        &group_by_project_button,
+        &add_absence_button,
+        &entries_list_box,
+    );
 
     // Create the view toggle (Today/Week)
     let view_toggle = create_view_toggle();
@@ -1727,7 +4110,8 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         conn,
         entries_list_box.clone(),
         day_total_label.clone(),
-        view_toggle.clone(),
+        today_timeline.clone(),
+        today_view,
         entries_section.clone(),
     )));
 
@@ -1739,13 +4123,13 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
             state.borrow().description_entry.set_sensitive(false);
             // Restore project selection from running entry
             state.borrow().set_selected_project(running_entry.project_id);
-            state.borrow().project_dropdown.set_sensitive(false);
             state.borrow_mut().running_entry = Some(running_entry);
             state.borrow().update_button_appearance();
             state.borrow().update_timer_display();
         }
         Ok(None) => {
             // No running entry, timer is stopped
+            state.borrow().reset_selected_project();
         }
         Err(e) => {
             eprintln!("Failed to check for running entry: {}", e);
@@ -1754,20 +4138,43 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         }
     }
 
-    // Set up timer update callback
-    setup_timer_update(state.clone());
-
     // Button click handler will be connected after window is created
 
     // Create a vertical box to hold the header bar and content
     let content = gtk::Box::new(gtk::Orientation::Vertical, 0);
     content.append(&header_bar);
 
-    // Add description entry at full width
-    content.append(&description_entry);
+    // Timer page: description, project and the timer itself
+    let timer_page = gtk::Box::new(gtk::Orientation::Vertical, 0);
+
+    // Add description entry at full width, with the templates menu
+    let description_row = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .margin_start(20)
+        .margin_end(20)
+        .margin_top(20)
+        .margin_bottom(10)
+        .build();
+    description_entry.set_hexpand(true);
+    description_row.append(&description_entry);
+    description_row.append(&templates::create_templates_button(&state));
+    timer_page.append(&description_row);
+
+    let state_for_private = state.clone();
+    description_entry.connect_icon_press(move |_, position| {
+        if position == gtk::EntryIconPosition::Secondary {
+            state_for_private.borrow_mut().set_start_private(false);
+        }
+    });
 
     // Add project dropdown below description
-    content.append(&project_dropdown);
+    timer_page.append(&project_dropdown);
+
+    // Pinned tasks as one-click chips
+    let pinned_tasks_box = pinned::create_pinned_tasks_box();
+    timer_page.append(&pinned_tasks_box);
+    state.borrow_mut().pinned_tasks_box = Some(pinned_tasks_box);
 
     // Create timer section container
     let timer_section = gtk::Box::builder()
@@ -1775,20 +4182,33 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         .halign(gtk::Align::Center)
         .build();
     timer_section.append(&timer_label);
-    timer_section.append(&start_stop_button);
 
-    content.append(&timer_section);
+    // Stops the timer retroactively, for when stopping was forgotten
+    let stop_at_button = gtk::Button::builder()
+        .label("Stop at…")
+        .halign(gtk::Align::Center)
+        .margin_bottom(8)
+        .visible(state.borrow().running_entry.is_some())
+        .css_classes(["flat", "caption"])
+        .build();
+    timer_section.append(&stop_at_button);
+    state.borrow_mut().stop_at_button = Some(stop_at_button.clone());
 
-    // Add separator between timer and view toggle
-    let separator = gtk::Separator::new(gtk::Orientation::Horizontal);
-    separator.set_margin_top(10);
-    content.append(&separator);
+    // Progress towards the daily target, around the start/stop button
+    let goal_ring = GoalRing::new(&start_stop_button);
+    goal_ring.container.set_margin_bottom(40);
+    timer_section.append(&goal_ring.container);
+    state.borrow_mut().goal_ring = Some(goal_ring);
 
-    // Add view toggle
-    content.append(&view_toggle);
+    timer_page.append(&timer_section);
 
-    // Add entries section
-    content.append(&entries_section);
+    // Entries page: view toggle and entries section
+    let entries_page = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .vexpand(true)
+        .build();
+    entries_page.append(&view_toggle);
+    entries_page.append(&entries_section);
 
     // Wrap content in ToastOverlay for error notifications
     let toast_overlay = adw::ToastOverlay::new();
@@ -1800,12 +4220,56 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         .title("Time Tracking")
         .default_width(400)
         .default_height(600)
+        .width_request(360)
+        .height_request(294)
         .content(&toast_overlay)
         .build();
 
+    // Tag filter under the view toggle
+    entries_page.insert_child_after(&tag_filter::create_tag_filter_bar(&state, &window), Some(&view_toggle));
+
+    // Project sidebar, shown next to the column on wide windows
+    let project_sidebar = sidebar::create_project_sidebar(&state, &window);
+
+    // Single column on desktops, sidebar on wide windows, separate timer and entries pages on phones
+    content.append(&adaptive::create_adaptive_layout(
+        &window,
+        &timer_page,
+        &entries_page,
+        &project_sidebar.container,
+    ));
+    state.borrow_mut().project_sidebar = Some(project_sidebar);
+
     // Store window and toast overlay references in state
     state.borrow_mut().set_window(window.clone());
     state.borrow_mut().set_toast_overlay(toast_overlay);
+    state.borrow_mut().set_review_button(review_button.clone());
+
+    if auto_archived > 0 {
+        state
+            .borrow()
+            .show_info(&format!("Archived {} unused projects", auto_archived));
+    }
+
+    // Look for damaged entries weekly; a read-only window can't repair them
+    if !read_only {
+        integrity::start_weekly_check(state.clone());
+    }
+
+    // Recurring reminders, offering to start the timer from their notification
+    if !read_only {
+        reminders::start_reminders(app, state.clone(), &window);
+    }
+
+    // Quitting from the keyboard, Cmd+Q on macOS, as closing may only hide
+    let quit_action = gio::SimpleAction::new("quit", None);
+    let app_for_quit = app.clone();
+    quit_action.connect_activate(move |_, _| app_for_quit.quit());
+    app.add_action(&quit_action);
+    app.set_accels_for_action("app.quit", &["<Primary>q"]);
+
+    // Switching between profiles, each with its own database
+    header_bar.pack_start(&profiles::create_profile_button(state.clone(), &window));
 
     // Connect button click handler (needs window reference for list refresh)
     let state_for_button = state.clone();
@@ -1816,6 +4280,67 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         }
     });
 
+    let state_for_stop_at = state.clone();
+    let window_for_stop_at = window.clone();
+    stop_at_button.connect_clicked(move |_| {
+        stop_at::show_stop_at_dialog(state_for_stop_at.clone(), &window_for_stop_at);
+    });
+
+    // Picking a project while tracking moves the running entry. AppState changes the
+    // selection itself only while borrowed, so those changes are skipped here.
+    let state_for_project = state.clone();
+    let window_for_project = window.clone();
+    // The item creating a project opens a popover and gives the selection
+    // back to the project chosen before it, until the new one is created
+    let previous_selection = Rc::new(Cell::new(0));
+    project_dropdown.connect_selected_notify(move |dropdown| {
+        let selected = dropdown.selected();
+        let new_item = dropdown.model().map_or(0, |model| model.n_items()).saturating_sub(1);
+        if selected == new_item {
+            dropdown.set_selected(previous_selection.get());
+            new_project::show_new_project_popover(state_for_project.clone(), dropdown);
+            return;
+        }
+        previous_selection.set(selected);
+
+        let Ok(mut state) = state_for_project.try_borrow_mut() else {
+            return;
+        };
+        let project_id = state.get_selected_project_id();
+        if state.set_running_project(project_id) {
+            drop(state);
+            refresh_view(state_for_project.clone(), &window_for_project);
+        }
+    });
+
+    // Starts the timer at a corrected time offered by the locked period dialog
+    let start_at_action = gio::SimpleAction::new(START_TIMER_AT_ACTION, Some(glib::VariantTy::INT64));
+    let state_for_start_at = state.clone();
+    let window_for_start_at = window.clone();
+    start_at_action.connect_activate(move |_, parameter| {
+        let Some(start_time) = parameter
+            .and_then(|p| p.get::<i64>())
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        else {
+            return;
+        };
+        if state_for_start_at.borrow_mut().start_timer_at(start_time) {
+            refresh_view(state_for_start_at.clone(), &window_for_start_at);
+        }
+    });
+    window.add_action(&start_at_action);
+
+    // Hides or shows a project in the charts from its legend
+    let toggle_chart_action =
+        gio::SimpleAction::new(TOGGLE_CHART_PROJECT_ACTION, Some(glib::VariantTy::INT64));
+    let state_for_toggle_chart = state.clone();
+    toggle_chart_action.connect_activate(move |_, parameter| {
+        if let Some(key) = parameter.and_then(|p| p.get::<i64>()) {
+            toggle_chart_project(&state_for_toggle_chart, (key != 0).then_some(key));
+        }
+    });
+    window.add_action(&toggle_chart_action);
+
     // Connect menu button to show projects dialog
     let state_for_menu = state.clone();
     let window_for_menu = window.clone();
@@ -1823,6 +4348,51 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         show_projects_dialog(state_for_menu.clone(), &window_for_menu);
     });
 
+    // Connect preferences button to show the preferences window
+    let state_for_preferences = state.clone();
+    let window_for_preferences = window.clone();
+    preferences_button.connect_clicked(move |_| {
+        preferences::show_preferences_window(state_for_preferences.clone(), &window_for_preferences);
+    });
+
+    // Connect review button to show the review queue
+    let state_for_review = state.clone();
+    let window_for_review = window.clone();
+    review_button.connect_clicked(move |_| {
+        review::show_review_dialog(state_for_review.clone(), &window_for_review);
+    });
+
+    // Connect mini timer button to open or close the mini timer
+    let state_for_mini = state.clone();
+    let window_for_mini = window.clone();
+    mini_timer_button.connect_clicked(move |_| {
+        mini_timer::toggle_mini_timer(state_for_mini.clone(), &window_for_mini);
+    });
+
+    // Connect reports button to open the reports window
+    let state_for_reports = state.clone();
+    let window_for_reports = window.clone();
+    reports_button.connect_clicked(move |_| {
+        reports::show_reports_window(state_for_reports.clone(), &window_for_reports);
+    });
+
+    // Connect the Today header toggle to section entries by project
+    group_by_project_button.set_active(state.borrow().settings.group_today_by_project);
+    let state_for_group = state.clone();
+    let window_for_group = window.clone();
+    group_by_project_button.connect_toggled(move |button| {
+        state_for_group.borrow_mut().settings.group_today_by_project = button.is_active();
+        preferences::save_settings(&state_for_group);
+        refresh_view(state_for_group.clone(), &window_for_group);
+    });
+
+    // Connect the Today header button to record an absence
+    let state_for_absence = state.clone();
+    let window_for_absence = window.clone();
+    add_absence_button.connect_clicked(move |_| {
+        absence::show_add_absence_dialog(state_for_absence.clone(), &window_for_absence);
+    });
+
     // Connect help button to show shortcuts dialog
     let window_for_help = window.clone();
     help_button.connect_clicked(move |_| {
@@ -1831,7 +4401,10 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
 
     // Connect view toggle buttons
     let today_button = view_toggle.first_child().and_downcast::<gtk::ToggleButton>().unwrap();
-    let week_button = view_toggle.last_child().and_downcast::<gtk::ToggleButton>().unwrap();
+    let week_button = today_button.next_sibling().and_downcast::<gtk::ToggleButton>().unwrap();
+    let month_button = week_button.next_sibling().and_downcast::<gtk::ToggleButton>().unwrap();
+    let custom_button = month_button.next_sibling().and_downcast::<gtk::ToggleButton>().unwrap();
+    let all_button = view_toggle.last_child().and_downcast::<gtk::ToggleButton>().unwrap();
 
     let state_for_today = state.clone();
     let window_for_today = window.clone();
@@ -1851,19 +4424,126 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
         }
     });
 
+    let state_for_month = state.clone();
+    let window_for_month = window.clone();
+    month_button.connect_toggled(move |button| {
+        if button.is_active() {
+            state_for_month.borrow_mut().view_mode = ViewMode::Month;
+            refresh_view(state_for_month.clone(), &window_for_month);
+        }
+    });
+
+    let state_for_custom = state.clone();
+    let window_for_custom = window.clone();
+    custom_button.connect_toggled(move |button| {
+        if button.is_active() {
+            state_for_custom.borrow_mut().view_mode = ViewMode::Custom;
+            refresh_view(state_for_custom.clone(), &window_for_custom);
+        }
+    });
+
+    let state_for_all = state.clone();
+    let window_for_all = window.clone();
+    all_button.connect_toggled(move |button| {
+        if button.is_active() {
+            state_for_all.borrow_mut().view_mode = ViewMode::All;
+            refresh_view(state_for_all.clone(), &window_for_all);
+        }
+    });
+
     // Initial load of today's entries
     refresh_view(state.clone(), &window);
 
+    // Set up timer update callback
+    setup_timer_update(state.clone(), &window);
+
+    // Search bar above the view toggle, opened with Ctrl+F
+    let search_bar = search::create_search_bar(&state, &window);
+    entries_page.prepend(&search_bar);
+
     // Set up keyboard shortcuts
-    setup_keyboard_shortcuts(&window, state.clone(), &description_entry, &project_dropdown);
+    setup_keyboard_shortcuts(
+        &window,
+        state.clone(),
+        &description_entry,
+        &project_dropdown,
+        &search_bar,
+        &view_toggle,
+    );
 
-    // Set up system tray
-    setup_system_tray(app, state.clone(), &window);
+    // Set up system tray; without a host to show it, closing quits instead
+    // of hiding the window where nothing could bring it back
+    let tray = setup_system_tray(app, state.clone(), &window, &view_toggle, &search_bar);
+    let tray_message = match tray {
+        Ok(()) => None,
+        Err(TrayUnavailable::NoHost) => Some("No system tray found, so closing the window quits the app"),
+        Err(TrayUnavailable::Unsupported) => {
+            Some("The tray icon isn't available on this platform yet, so closing the window quits the app")
+        }
+    };
+    if let Some(message) = tray_message {
+        content.insert_child_after(&compat::create_banner(message), Some(&header_bar));
+    }
+
+    // Publish timer status on D-Bus for panel extensions
+    setup_status_service(app, state.clone());
+
+    // Send timer events to enabled plugins; a read-only window leaves them alone
+    if !read_only {
+        content.append(&plugins::start_plugin_host(state.clone(), &window));
+    }
+
+    // Started hidden, the window waits in the tray until shown from there;
+    // without a tray nothing could show it
+    if !start_hidden || tray.is_err() {
+        window.present();
+    }
+
+    // Reopen the mini timer left open at the last quit, once the window is shown
+    let state_for_restore = state.clone();
+    let window_for_restore = window.clone();
+    glib::idle_add_local_once(move || mini_timer::restore_mini_timer(state_for_restore, &window_for_restore));
+
+    // Give Do Not Disturb back to the desktop when quitting mid deep work
+    let state_for_shutdown = state.clone();
+    app.connect_shutdown(move |_| {
+        let mut state_borrow = state_for_shutdown.borrow_mut();
+        if let Err(e) = state_borrow.do_not_disturb.set_active(false) {
+            eprintln!("Failed to turn off Do Not Disturb: {}", e);
+        }
+    });
 
-    // Handle window close request - minimize to tray instead of quitting
+    // Handle window close request - minimize to tray, quit or ask, as set
+    // in preferences
+    let state_for_close = state.clone();
     window.connect_close_request(move |window| {
-        // Hide the window instead of closing when tray is active
-        window.set_visible(false);
+        // Without a tray icon to bring it back, closing the window quits
+        if !state_for_close.borrow().has_tray() {
+            return glib::Propagation::Proceed;
+        }
+        let close_action = state_for_close.borrow().settings.close_action;
+        match close_action {
+            CloseAction::HideToTray => window.set_visible(false),
+            // Quitting the app also closes the mini timer, unlike closing the window
+            CloseAction::Quit => quit_app(window),
+            CloseAction::Ask => {
+                let window_for_choice = window.clone();
+                compat::choose(
+                    window,
+                    "Quit Time Tracking?",
+                    "A running timer keeps counting while the app is closed. Minimizing keeps the app in the tray.",
+                    "Minimize to Tray",
+                    "Quit",
+                    move |quit| {
+                        if quit {
+                            quit_app(&window_for_choice);
+                        } else {
+                            window_for_choice.set_visible(false);
+                        }
+                    },
+                );
+            }
+        }
         // Return Propagation::Stop to prevent the default close behavior
         glib::Propagation::Stop
     });
@@ -1871,27 +4551,36 @@ pub fn build_window(app: &adw::Application) -> adw::ApplicationWindow {
     window
 }
 
-/// Shows the keyboard shortcuts help dialog
-fn show_shortcuts_dialog(parent: &adw::ApplicationWindow) {
-    let dialog = gtk::MessageDialog::builder()
-        .transient_for(parent)
-        .modal(true)
-        .message_type(gtk::MessageType::Info)
-        .buttons(gtk::ButtonsType::Close)
-        .text("Keyboard Shortcuts")
-        .secondary_text(
-            "Ctrl+S or Space — Start/Stop timer\n\
-             Ctrl+N — Focus description field\n\
-             Ctrl+P — Open project selector\n\
-             Escape — Stop timer if running\n\
-             F1 — Show this help"
-        )
-        .build();
+/// Quits the app the window belongs to
+fn quit_app(window: &adw::ApplicationWindow) {
+    match window.application() {
+        Some(app) => app.quit(),
+        None => window.destroy(),
+    }
+}
 
-    dialog.connect_response(|dialog, _| {
-        dialog.close();
-    });
-    dialog.present();
+/// Shows the keyboard shortcuts help dialog, ending with the app and schema versions
+fn show_shortcuts_dialog(parent: &adw::ApplicationWindow) {
+    let body = format!(
+        "Ctrl+S or Space — Start/Stop timer\n\
+         Ctrl+R — Continue the last stopped entry\n\
+         Ctrl+N — Focus description field\n\
+         Ctrl+V — Paste spreadsheet rows as entries (Today view)\n\
+         Ctrl+P — Open project selector\n\
+         Ctrl+Shift+P — Switch project of the running entry\n\
+         Ctrl+M — Open or close the mini timer\n\
+         Ctrl+F — Search all entries\n\
+         Ctrl+K — Open the command palette\n\
+         Escape — Stop timer if running\n\
+         Enter, F2, Delete, Ctrl+C — Continue, rename, delete or copy the focused entry\n\
+         Ctrl+D — Duplicate the focused entry to today\n\
+         Ctrl+Q — Quit, also when closing only hides the window\n\
+         F1 — Show this help\n\n\
+         Time Tracking {} (database schema {})",
+        env!("CARGO_PKG_VERSION"),
+        db::SCHEMA_VERSION
+    );
+    compat::show_message(parent, "Keyboard Shortcuts", &body);
 }
 
 /// Sets up keyboard shortcuts for the window
@@ -1900,6 +4589,8 @@ fn setup_keyboard_shortcuts(
     state: Rc<RefCell<AppState>>,
     description_entry: &gtk::Entry,
     project_dropdown: &gtk::DropDown,
+    search_bar: &gtk::SearchBar,
+    view_toggle: &gtk::Box,
 ) {
     let controller = gtk::EventControllerKey::new();
 
@@ -1907,9 +4598,12 @@ fn setup_keyboard_shortcuts(
     let window_for_key = window.clone();
     let description_entry_for_key = description_entry.clone();
     let project_dropdown_for_key = project_dropdown.clone();
+    let search_bar_for_key = search_bar.clone();
+    let view_toggle_for_key = view_toggle.clone();
 
     controller.connect_key_pressed(move |_, keyval, _keycode, modifier| {
         let ctrl = modifier.contains(gtk::gdk::ModifierType::CONTROL_MASK);
+        let shift = modifier.contains(gtk::gdk::ModifierType::SHIFT_MASK);
 
         match keyval {
             // Ctrl+S: Start/Stop timer
@@ -1919,6 +4613,13 @@ fn setup_keyboard_shortcuts(
                 }
                 glib::Propagation::Stop
             }
+            // Ctrl+R: Continue the last stopped entry
+            gtk::gdk::Key::r if ctrl => {
+                if state_for_key.borrow_mut().continue_last_entry() {
+                    refresh_view(state_for_key.clone(), &window_for_key);
+                }
+                glib::Propagation::Stop
+            }
             // Space: Start/Stop timer (only if not focused on text entry)
             gtk::gdk::Key::space if !description_entry_for_key.has_focus() => {
                 if state_for_key.borrow_mut().toggle_timer() {
@@ -1926,23 +4627,60 @@ fn setup_keyboard_shortcuts(
                 }
                 glib::Propagation::Stop
             }
+            // Ctrl+V: Paste spreadsheet rows as entries (only if not focused on text entry)
+            gtk::gdk::Key::v
+                if ctrl
+                    && !description_entry_for_key.has_focus()
+                    && state_for_key.borrow().view_mode == ViewMode::Today =>
+            {
+                import::paste_entries(state_for_key.clone(), &window_for_key);
+                glib::Propagation::Stop
+            }
             // Ctrl+N: Focus description field
             gtk::gdk::Key::n if ctrl => {
                 description_entry_for_key.grab_focus();
                 glib::Propagation::Stop
             }
+            // Ctrl+Shift+P: Pick another project for the running entry
+            gtk::gdk::Key::P | gtk::gdk::Key::p if ctrl && shift => {
+                if state_for_key.borrow().running_entry.is_some() {
+                    project_dropdown_for_key.activate();
+                } else {
+                    state_for_key.borrow().show_info("No timer running");
+                }
+                glib::Propagation::Stop
+            }
             // Ctrl+P: Open project selector popup
             gtk::gdk::Key::p if ctrl => {
                 // Activate the dropdown to show its popup
                 project_dropdown_for_key.activate();
                 glib::Propagation::Stop
             }
+            // Ctrl+F: Search all entries
+            gtk::gdk::Key::f if ctrl => {
+                search_bar_for_key.set_search_mode(true);
+                glib::Propagation::Stop
+            }
+            // Ctrl+K: Open the command palette
+            gtk::gdk::Key::k if ctrl => {
+                palette::show_command_palette(
+                    state_for_key.clone(),
+                    &window_for_key,
+                    &view_toggle_for_key,
+                    &search_bar_for_key,
+                );
+                glib::Propagation::Stop
+            }
+            // Ctrl+M: Open or close the mini timer
+            gtk::gdk::Key::m if ctrl => {
+                mini_timer::toggle_mini_timer(state_for_key.clone(), &window_for_key);
+                glib::Propagation::Stop
+            }
             // Escape: Stop timer if running
             gtk::gdk::Key::Escape => {
-                if state_for_key.borrow().running_entry.is_some() {
-                    if state_for_key.borrow_mut().stop_timer() {
-                        refresh_view(state_for_key.clone(), &window_for_key);
-                    }
+                let is_running = state_for_key.borrow().running_entry.is_some();
+                if is_running && state_for_key.borrow_mut().stop_timer() {
+                    refresh_view(state_for_key.clone(), &window_for_key);
                 }
                 glib::Propagation::Stop
             }
@@ -1958,12 +4696,36 @@ fn setup_keyboard_shortcuts(
     window.add_controller(controller);
 }
 
-/// Sets up the system tray integration
+/// Something asked of the window from the tray, whose callbacks run on the
+/// tray service's thread
+#[derive(Debug, Clone)]
+enum TrayCommand {
+    ToggleTimer,
+    ShowWindow,
+    ToggleWindow,
+    QuickStart,
+    ContinueLast,
+    /// Log the block of this many minutes that just ended
+    LogRecent(u32),
+    /// Start the pinned task with this id
+    StartPinned(i64),
+    /// Start a recent task with this description and project
+    StartRecent(String, Option<i64>),
+    Quit,
+}
+
+/// How often tray commands are picked up on the main loop
+const TRAY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Sets up the system tray integration, failing when there is no tray to
+/// show the icon in
 fn setup_system_tray(
     app: &adw::Application,
     state: Rc<RefCell<AppState>>,
     window: &adw::ApplicationWindow,
-) {
+    view_toggle: &gtk::Box,
+    search_bar: &gtk::SearchBar,
+) -> Result<(), TrayUnavailable> {
     let tray_manager = Arc::new(Mutex::new(TrayManager::new()));
 
     // Store tray manager in app state
@@ -1971,39 +4733,203 @@ fn setup_system_tray(
 
     // Initial tray state update
     state.borrow().update_tray();
+    pinned::refresh_pinned_tasks(&state);
+    state.borrow().update_tray_recent();
+
+    // GTK objects can't leave the main thread, so the tray's callbacks send
+    // commands that are handled on the main loop
+    let (sender, receiver) = std::sync::mpsc::channel::<TrayCommand>();
+    let callback = |command: TrayCommand| -> TrayCallback {
+        let sender = sender.clone();
+        Box::new(move || {
+            let _ = sender.send(command.clone());
+        })
+    };
 
-    // Create callbacks for tray actions
-    // Note: These callbacks are no-ops for now because Rc/GTK objects can't be sent across threads
-    // TODO: Implement proper channel-based communication for tray actions
-
-    let on_toggle_timer: Box<dyn Fn() + Send + Sync> = Box::new(|| {
-        // No-op - would need channel-based implementation
-    });
+    // Start the tray service
+    let started = if let Ok(mut manager) = tray_manager.lock() {
+        manager.start(TrayCallbacks {
+            toggle_timer: callback(TrayCommand::ToggleTimer),
+            show_window: callback(TrayCommand::ShowWindow),
+            toggle_window: callback(TrayCommand::ToggleWindow),
+            quick_start: callback(TrayCommand::QuickStart),
+            continue_last: callback(TrayCommand::ContinueLast),
+            log_recent: {
+                let sender = sender.clone();
+                Box::new(move |minutes| {
+                    let _ = sender.send(TrayCommand::LogRecent(minutes));
+                })
+            },
+            start_pinned: {
+                let sender = sender.clone();
+                Box::new(move |id| {
+                    let _ = sender.send(TrayCommand::StartPinned(id));
+                })
+            },
+            start_recent: {
+                let sender = sender.clone();
+                Box::new(move |description, project_id| {
+                    let _ = sender.send(TrayCommand::StartRecent(description, project_id));
+                })
+            },
+            quit: callback(TrayCommand::Quit),
+        })
+    } else {
+        Err(TrayUnavailable::Unsupported)
+    };
 
-    let on_show_window: Box<dyn Fn() + Send + Sync> = Box::new(|| {
-        // No-op - would need channel-based implementation
+    let app = app.clone();
+    let window = window.clone();
+    let view_toggle = view_toggle.clone();
+    let search_bar = search_bar.clone();
+    glib::timeout_add_local(TRAY_POLL_INTERVAL, move || {
+        while let Ok(command) = receiver.try_recv() {
+            match command {
+                TrayCommand::ToggleTimer => {
+                    if state.borrow_mut().toggle_timer() {
+                        refresh_view(state.clone(), &window);
+                    }
+                }
+                TrayCommand::ShowWindow => window.present(),
+                TrayCommand::ToggleWindow => {
+                    if window.is_visible() && window.is_active() {
+                        window.set_visible(false);
+                    } else {
+                        window.present();
+                    }
+                }
+                TrayCommand::QuickStart => {
+                    window.present();
+                    palette::show_command_palette(state.clone(), &window, &view_toggle, &search_bar);
+                }
+                TrayCommand::ContinueLast => {
+                    if state.borrow_mut().continue_last_entry() {
+                        refresh_view(state.clone(), &window);
+                    }
+                }
+                TrayCommand::LogRecent(minutes) => {
+                    log_recent::show_log_recent_dialog(state.clone(), &window, minutes);
+                }
+                TrayCommand::StartPinned(id) => {
+                    if state.borrow_mut().start_pinned_task(id) {
+                        refresh_view(state.clone(), &window);
+                    }
+                }
+                TrayCommand::StartRecent(description, project_id) => {
+                    if state.borrow_mut().start_task(&description, project_id) {
+                        refresh_view(state.clone(), &window);
+                    }
+                }
+                TrayCommand::Quit => app.quit(),
+            }
+        }
+        glib::ControlFlow::Continue
     });
 
-    let on_quit: Box<dyn Fn() + Send + Sync> = Box::new(|| {
-        // No-op - would need channel-based implementation
-    });
+    started
+}
 
-    // Start the tray service
-    if let Ok(mut manager) = tray_manager.lock() {
-        manager.start(on_toggle_timer, on_show_window, on_quit);
+/// Registers the D-Bus status object on the application's session bus connection
+fn setup_status_service(app: &adw::Application, state: Rc<RefCell<AppState>>) {
+    let Some(connection) = app.dbus_connection() else {
+        return;
     };
+
+    match StatusService::register(connection) {
+        Ok(service) => {
+            state.borrow_mut().set_status_service(service);
+            state.borrow().update_status_service();
+        }
+        Err(e) => eprintln!("Failed to register D-Bus status object: {}", e),
+    }
 }
 
-/// Runs the Adwaita application.
+/// Explains that the database comes from a newer version of the app, which
+/// this one could damage, offering to quit or to keep browsing it read-only
+fn show_newer_schema_dialog(app: &adw::Application, window: &adw::ApplicationWindow, schema_version: i64) {
+    let body = format!(
+        "This database was last used by a newer version of Time Tracking (schema {}, \
+         this version supports {}). Writing to it could damage data. Update the app \
+         to make changes, or browse the data read-only.",
+        schema_version,
+        db::SCHEMA_VERSION
+    );
+    let app = app.clone();
+    compat::choose(
+        window,
+        "Database From a Newer Version",
+        &body,
+        "Quit",
+        "Open Read-Only",
+        move |read_only| {
+            if !read_only {
+                app.quit();
+            }
+        },
+    );
+}
+
+/// Runs the Adwaita application. With `--widget`, shows the display-only widget instead.
 pub fn run_app() -> i32 {
+    // The widget, hidden, database and profile flags are handled here,
+    // GApplication would reject them as unknown
+    let widget_mode = std::env::args().any(|arg| arg == widget::WIDGET_FLAG);
+    let start_hidden = std::env::args().any(|arg| arg == autostart::HIDDEN_FLAG);
+    let value_flags = [db::DATABASE_FLAG, profile::PROFILE_FLAG];
+    let mut args: Vec<String> = Vec::new();
+    let mut all_args = std::env::args();
+    while let Some(arg) = all_args.next() {
+        if value_flags.contains(&arg.as_str()) {
+            all_args.next();
+        } else if arg != widget::WIDGET_FLAG
+            && arg != autostart::HIDDEN_FLAG
+            && !value_flags.iter().any(|flag| arg.starts_with(&format!("{}=", flag)))
+        {
+            args.push(arg);
+        }
+    }
+
+    // A mistyped profile would otherwise open the default database
+    let all_args: Vec<String> = std::env::args().collect();
+    if let Some(Err(e)) = profile::profile_arg(&all_args).map(|name| profile::validate_name(&name)) {
+        eprintln!("Invalid profile name: {}", e);
+        return 1;
+    }
+
     let app = adw::Application::builder()
-        .application_id("com.example.time-tracking")
+        .application_id(if widget_mode {
+            widget::WIDGET_APPLICATION_ID.to_string()
+        } else {
+            // Relative paths name the same database from anywhere
+            let database = db::chosen_db_path().map(|path| std::fs::canonicalize(&path).unwrap_or(path));
+            profile::application_id(profile::current().as_deref(), database.as_deref())
+        })
         .build();
 
-    app.connect_activate(|app| {
-        let window = build_window(app);
-        window.present();
+    app.connect_activate(move |app| {
+        if widget_mode {
+            if let Some(window) = widget::build_widget_window(app) {
+                window.present();
+            }
+        } else if let Some(window) = app
+            .windows()
+            .into_iter()
+            .find_map(|window| window.downcast::<adw::ApplicationWindow>().ok())
+        {
+            // Launched again, e.g. from the app menu after starting hidden:
+            // show the window already running instead of a second one
+            window.present();
+        } else {
+            // A database migrated by a newer version is only opened read-only
+            let schema_version = db::read_schema_version(&db::get_db_path()).unwrap_or(0);
+            let read_only = schema_version > db::SCHEMA_VERSION;
+            // The newer schema dialog needs the window shown
+            let window = build_window(app, read_only, start_hidden && !read_only);
+            if read_only {
+                show_newer_schema_dialog(app, &window, schema_version);
+            }
+        }
     });
 
-    app.run().into()
+    app.run_with_args(&args).into()
 }
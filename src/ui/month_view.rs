@@ -0,0 +1,252 @@
+use adw::prelude::*;
+use chrono::{Datelike, Local, NaiveDate, Utc};
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::{refresh_view, show_in_entries_section, AppState};
+use crate::db;
+use crate::list_diff;
+use crate::month::{self, Heat};
+use crate::report::{self, ReportRange};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Persistent widgets of the month view, updated in place on refresh
+pub struct MonthView {
+    container: gtk::Box,
+    month_label: gtk::Label,
+    total_label: gtk::Label,
+    grid: gtk::Grid,
+    /// Fingerprint of the shown day cells, the grid is rebuilt only when it changes
+    grid_fingerprint: Option<u64>,
+}
+
+/// Creates a legend swatch with its label
+fn create_legend_item(heat: Heat) -> gtk::Box {
+    let item = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(4)
+        .build();
+    item.append(
+        &gtk::Box::builder()
+            .width_request(12)
+            .height_request(12)
+            .valign(gtk::Align::Center)
+            .css_classes(["heat-swatch", heat.css_class()])
+            .build(),
+    );
+    item.append(
+        &gtk::Label::builder()
+            .label(heat.label())
+            .css_classes(["caption", "dim-label"])
+            .build(),
+    );
+    item
+}
+
+/// Creates the cell of one day: the day number above its total
+fn create_day_cell(day: NaiveDate, total: Option<&str>, heat: Heat, is_today: bool) -> gtk::Box {
+    let cell = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(2)
+        .hexpand(true)
+        .css_classes(["month-day", heat.css_class()])
+        .build();
+    if is_today {
+        cell.add_css_class("month-today");
+    }
+
+    cell.append(
+        &gtk::Label::builder()
+            .label(day.day().to_string())
+            .halign(gtk::Align::Start)
+            .css_classes(["caption-heading"])
+            .build(),
+    );
+    cell.append(
+        &gtk::Label::builder()
+            .label(total.unwrap_or(""))
+            .halign(gtk::Align::End)
+            .css_classes(["caption", "monospace"])
+            .build(),
+    );
+    cell
+}
+
+impl MonthView {
+    fn new(state: &Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) -> Self {
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(8)
+            .vexpand(true)
+            .css_classes(["weekly-summary"])
+            .build();
+
+        // Header: previous, month name and total, next
+        let header = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        let previous_button = gtk::Button::builder()
+            .icon_name("go-previous-symbolic")
+            .tooltip_text("Previous month")
+            .css_classes(["flat"])
+            .build();
+        header.append(&previous_button);
+
+        let title_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .hexpand(true)
+            .build();
+        let month_label = gtk::Label::builder()
+            .css_classes(["title-4"])
+            .build();
+        title_box.append(&month_label);
+        let total_label = gtk::Label::builder()
+            .selectable(true)
+            .css_classes(["monospace", "dim-label"])
+            .build();
+        title_box.append(&total_label);
+        header.append(&title_box);
+
+        let next_button = gtk::Button::builder()
+            .icon_name("go-next-symbolic")
+            .tooltip_text("Next month")
+            .css_classes(["flat"])
+            .build();
+        header.append(&next_button);
+        container.append(&header);
+
+        let grid = gtk::Grid::builder()
+            .row_spacing(4)
+            .column_spacing(4)
+            .column_homogeneous(true)
+            .build();
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .child(&grid)
+            .build();
+        container.append(&scrolled_window);
+
+        let legend = gtk::FlowBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .max_children_per_line(5)
+            .column_spacing(12)
+            .build();
+        for heat in Heat::LEGEND {
+            legend.insert(&create_legend_item(heat), -1);
+        }
+        container.append(&legend);
+
+        for (button, forward) in [(&previous_button, false), (&next_button, true)] {
+            let state = state.clone();
+            let window = window.clone();
+            button.connect_clicked(move |_| {
+                let shown = state.borrow().shown_month;
+                state.borrow_mut().shown_month = month::shift_month(shown, forward);
+                state.borrow().save_view_dates();
+                refresh_view(state.clone(), &window);
+            });
+        }
+
+        Self {
+            container,
+            month_label,
+            total_label,
+            grid,
+            grid_fingerprint: None,
+        }
+    }
+
+    /// Rebuilds the day cells from the per day totals
+    fn update_grid(&mut self, shown_month: NaiveDate, totals: &HashMap<NaiveDate, i64>, state: &AppState) {
+        let settings = &state.settings;
+        let expected_seconds = settings.expected_daily_minutes as i64 * 60;
+        let today = Local::now().date_naive();
+
+        let fingerprint = list_diff::fingerprint(&(
+            shown_month,
+            today,
+            expected_seconds,
+            settings.duration_format,
+            totals.iter().collect::<std::collections::BTreeMap<_, _>>(),
+        ));
+        if self.grid_fingerprint == Some(fingerprint) {
+            return;
+        }
+        self.grid_fingerprint = Some(fingerprint);
+
+        while let Some(child) = self.grid.first_child() {
+            self.grid.remove(&child);
+        }
+        for (column, name) in WEEKDAYS.iter().enumerate() {
+            self.grid.attach(
+                &gtk::Label::builder()
+                    .label(*name)
+                    .css_classes(["caption", "dim-label"])
+                    .build(),
+                column as i32,
+                0,
+                1,
+                1,
+            );
+        }
+        for (row, week) in month::month_weeks(shown_month).iter().enumerate() {
+            for (column, day) in week.iter().enumerate() {
+                let Some(day) = day else { continue };
+                let seconds = totals.get(day).copied().unwrap_or(0);
+                let total = (seconds > 0).then(|| settings.duration_format.format(seconds));
+                let cell = create_day_cell(
+                    *day,
+                    total.as_deref(),
+                    Heat::of(seconds, expected_seconds),
+                    *day == today,
+                );
+                self.grid.attach(&cell, column as i32, row as i32 + 1, 1, 1);
+            }
+        }
+    }
+}
+
+/// Refreshes the entries section for the month view
+pub fn refresh_month_view(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
+    let existing = state.borrow_mut().month_view.take();
+    let mut month_view = existing.unwrap_or_else(|| MonthView::new(&state, window));
+    let state_borrow = state.borrow();
+    show_in_entries_section(&state_borrow.entries_section, &month_view.container);
+
+    let shown_month = state_borrow.shown_month;
+    let range = ReportRange::month_of(shown_month);
+    let mut entries = match db::get_entries_for_date_range(&state_borrow.db_conn, range.start, range.end) {
+        Ok(entries) => entries,
+        Err(e) => {
+            state_borrow.show_error(&format!("Failed to load entries: {}", e));
+            Vec::new()
+        }
+    };
+    entries.retain(|e| state_borrow.entry_filter_matches(e));
+
+    // Totals per day with the rounding rule applied
+    let rounding = &state_borrow.settings.rounding;
+    let now = Utc::now();
+    let totals = report::daily_totals(&entries, rounding, now, &Local);
+
+    month_view
+        .month_label
+        .set_label(&shown_month.format("%B %Y").to_string());
+    month_view.total_label.set_label(&format!(
+        "Total: {}",
+        state_borrow
+            .settings
+            .duration_format
+            .format(rounding.total_seconds(&entries, now))
+    ));
+    month_view.update_grid(shown_month, &totals, &state_borrow);
+
+    drop(state_borrow);
+    state.borrow_mut().month_view = Some(month_view);
+}
@@ -0,0 +1,82 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{compat, format_color, parse_color, AppState, PROJECT_COLORS};
+use crate::db;
+
+/// Opens a popover on the project dropdown asking for the name and color
+/// of a new project, which is selected once created
+pub fn show_new_project_popover(state: Rc<RefCell<AppState>>, dropdown: &gtk::DropDown) {
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .margin_top(6)
+        .margin_bottom(6)
+        .margin_start(6)
+        .margin_end(6)
+        .build();
+
+    let name_entry = gtk::Entry::builder()
+        .placeholder_text("Project name")
+        .hexpand(true)
+        .build();
+    content.append(&name_entry);
+
+    // Suggest the next palette color, so new projects tell apart at a glance
+    let suggested = PROJECT_COLORS[state.borrow().projects.len() % PROJECT_COLORS.len()];
+    let color_button = compat::create_color_button(&parse_color(suggested));
+    color_button.set_valign(gtk::Align::Center);
+    color_button.set_tooltip_text(Some("Select color"));
+    content.append(&color_button);
+
+    let create_button = gtk::Button::builder()
+        .label("Create")
+        .css_classes(["suggested-action"])
+        .build();
+    content.append(&create_button);
+
+    let popover = gtk::Popover::builder().child(&content).build();
+    popover.set_parent(dropdown);
+    popover.connect_closed(|popover| popover.unparent());
+
+    let create = {
+        let popover = popover.clone();
+        let dropdown = dropdown.clone();
+        let name_entry = name_entry.clone();
+        move || {
+            let name = name_entry.text().trim().to_string();
+            if name.is_empty() {
+                state.borrow().show_error("Project name cannot be empty");
+                return;
+            }
+
+            let color = format_color(&color_button.rgba());
+            let project = match db::create_project(&state.borrow().db_conn, &name, &color) {
+                Ok(project) => project,
+                Err(e) => {
+                    state.borrow().show_error(&format!("Failed to create project: {}", e));
+                    return;
+                }
+            };
+            state.borrow_mut().refresh_projects();
+            popover.popdown();
+
+            // Selected without holding the state, so the dropdown's handler
+            // applies it to a running entry like any other pick
+            let position = state.borrow().projects.iter().position(|p| p.id == project.id);
+            if let Some(index) = position {
+                dropdown.set_selected((index + 1) as u32);
+            }
+        }
+    };
+    let create = Rc::new(create);
+
+    let create_for_button = create.clone();
+    create_button.connect_clicked(move |_| create_for_button());
+    name_entry.connect_activate(move |_| create());
+
+    popover.popup();
+    name_entry.grab_focus();
+}
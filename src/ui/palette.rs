@@ -0,0 +1,287 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use gtk4::glib;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{
+    absence, mini_timer, preferences, refresh_view, reports, review, show_projects_dialog, show_shortcuts_dialog,
+    stop_at, year_review, AppState,
+};
+use crate::db;
+use crate::fuzzy;
+
+/// Recent entries offered to start again
+const RECENT_LIMIT: usize = 20;
+
+/// Opens a dialog or window over the main window
+type ShowDialog = fn(Rc<RefCell<AppState>>, &adw::ApplicationWindow);
+
+/// Something the palette can do, run after the palette closes
+struct Command {
+    title: String,
+    /// Shortcut doing the same, shown next to the title
+    shortcut: Option<&'static str>,
+    run: Rc<dyn Fn()>,
+}
+
+impl Command {
+    fn new(title: impl Into<String>, shortcut: Option<&'static str>, run: impl Fn() + 'static) -> Self {
+        Self {
+            title: title.into(),
+            shortcut,
+            run: Rc::new(run),
+        }
+    }
+}
+
+/// Collects the commands available right now: the timer, the views from the
+/// view toggle, the dialogs and starting a recent entry again
+fn collect_commands(
+    state: &Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+    view_toggle: &gtk::Box,
+    search_bar: &gtk::SearchBar,
+) -> Vec<Command> {
+    let mut commands = Vec::new();
+
+    let is_running = state.borrow().running_entry.is_some();
+    let state_for_timer = state.clone();
+    let window_for_timer = window.clone();
+    commands.push(Command::new(
+        if is_running { "Stop timer" } else { "Start timer" },
+        Some("Ctrl+S"),
+        move || {
+            if state_for_timer.borrow_mut().toggle_timer() {
+                refresh_view(state_for_timer.clone(), &window_for_timer);
+            }
+        },
+    ));
+
+    let state_for_continue = state.clone();
+    let window_for_continue = window.clone();
+    commands.push(Command::new("Continue last entry", Some("Ctrl+R"), move || {
+        if state_for_continue.borrow_mut().continue_last_entry() {
+            refresh_view(state_for_continue.clone(), &window_for_continue);
+        }
+    }));
+
+    if is_running {
+        let state_for_stop_at = state.clone();
+        let window_for_stop_at = window.clone();
+        commands.push(Command::new("Stop timer at…", None, move || {
+            stop_at::show_stop_at_dialog(state_for_stop_at.clone(), &window_for_stop_at);
+        }));
+    }
+
+    let mut child = view_toggle.first_child();
+    while let Some(widget) = child {
+        child = widget.next_sibling();
+        let Ok(button) = widget.downcast::<gtk::ToggleButton>() else {
+            continue;
+        };
+        let label = button.label().unwrap_or_default();
+        commands.push(Command::new(format!("Switch to {} view", label), None, move || {
+            button.set_active(true);
+        }));
+    }
+
+    let search_bar = search_bar.clone();
+    commands.push(Command::new("Search entries", Some("Ctrl+F"), move || {
+        search_bar.set_search_mode(true);
+    }));
+
+    let dialogs: [(&str, Option<&'static str>, ShowDialog); 7] = [
+        ("Add absence", None, absence::show_add_absence_dialog),
+        ("Manage projects", None, show_projects_dialog),
+        ("Open reports", None, reports::show_reports_window),
+        ("Open year in review", None, year_review::show_year_review_window),
+        ("Open review queue", None, review::show_review_dialog),
+        ("Open preferences", None, preferences::show_preferences_window),
+        ("Open or close the mini timer", Some("Ctrl+M"), mini_timer::toggle_mini_timer),
+    ];
+    for (title, shortcut, show) in dialogs {
+        let state = state.clone();
+        let window = window.clone();
+        commands.push(Command::new(title, shortcut, move || show(state.clone(), &window)));
+    }
+
+    let window_for_help = window.clone();
+    commands.push(Command::new("Show keyboard shortcuts", Some("F1"), move || {
+        show_shortcuts_dialog(&window_for_help);
+    }));
+
+    let state_borrow = state.borrow();
+    let recent = db::get_recent_entries(&state_borrow.db_conn, RECENT_LIMIT).unwrap_or_else(|e| {
+        state_borrow.show_error(&format!("Failed to load recent entries: {}", e));
+        Vec::new()
+    });
+    for entry in recent {
+        let project = entry
+            .project_id
+            .and_then(|id| state_borrow.projects.iter().find(|p| p.id == id));
+        let title = match project {
+            Some(project) => format!("Start “{}” · {}", entry.description, project.name),
+            None => format!("Start “{}”", entry.description),
+        };
+        let state = state.clone();
+        let window = window.clone();
+        commands.push(Command::new(title, None, move || {
+            if state.borrow_mut().continue_entry(&entry) {
+                refresh_view(state.clone(), &window);
+            }
+        }));
+    }
+    drop(state_borrow);
+
+    commands
+}
+
+/// Creates a list row showing a command and its shortcut
+fn create_command_row(command: &Command) -> gtk::ListBoxRow {
+    let row_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(12)
+        .margin_top(8)
+        .margin_bottom(8)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    row_box.append(
+        &gtk::Label::builder()
+            .label(&command.title)
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build(),
+    );
+    if let Some(shortcut) = command.shortcut {
+        row_box.append(&gtk::Label::builder().label(shortcut).css_classes(["dim-label"]).build());
+    }
+    gtk::ListBoxRow::builder().child(&row_box).build()
+}
+
+/// Opens the command palette: a search field over every command, matched
+/// fuzzily as you type. Up and Down pick a command and Enter runs it.
+pub fn show_command_palette(
+    state: Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+    view_toggle: &gtk::Box,
+    search_bar: &gtk::SearchBar,
+) {
+    let commands = Rc::new(collect_commands(&state, window, view_toggle, search_bar));
+
+    let palette = adw::Window::builder()
+        .title("Commands")
+        .modal(true)
+        .transient_for(window)
+        .default_width(480)
+        .default_height(400)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(6)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    let search_entry = gtk::SearchEntry::builder()
+        .placeholder_text("Type a command")
+        .build();
+    content.append(&search_entry);
+
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::Browse)
+        .css_classes(["boxed-list"])
+        .build();
+    list_box.set_placeholder(Some(
+        &gtk::Label::builder()
+            .label("No matching commands")
+            .margin_top(12)
+            .margin_bottom(12)
+            .css_classes(["dim-label"])
+            .build(),
+    ));
+    content.append(
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vexpand(true)
+            .child(&list_box)
+            .build(),
+    );
+    palette.set_content(Some(&content));
+
+    // Indices into `commands` of the rows shown, best match first
+    let shown: Rc<RefCell<Vec<usize>>> = Rc::new(RefCell::new(Vec::new()));
+    let show_matches = {
+        let commands = commands.clone();
+        let list_box = list_box.clone();
+        let shown = shown.clone();
+        move |query: &str| {
+            while let Some(row) = list_box.row_at_index(0) {
+                list_box.remove(&row);
+            }
+            let matches: Vec<usize> =
+                fuzzy::filter(query, commands.iter().enumerate().collect(), |(_, command)| command.title.as_str())
+                    .into_iter()
+                    .map(|(index, command)| {
+                        list_box.append(&create_command_row(command));
+                        index
+                    })
+                    .collect();
+            list_box.select_row(list_box.row_at_index(0).as_ref());
+            *shown.borrow_mut() = matches;
+        }
+    };
+    show_matches("");
+    search_entry.connect_search_changed(move |entry| show_matches(&entry.text()));
+
+    // Closes the palette first, so commands opening dialogs aren't covered by it
+    let run_row = {
+        let palette = palette.clone();
+        move |row: &gtk::ListBoxRow| {
+            let Some(&index) = shown.borrow().get(row.index() as usize) else {
+                return;
+            };
+            let run = commands[index].run.clone();
+            palette.close();
+            glib::idle_add_local_once(move || run());
+        }
+    };
+    let run_row = Rc::new(run_row);
+
+    let run_row_for_activate = run_row.clone();
+    list_box.connect_row_activated(move |_, row| run_row_for_activate(row));
+
+    let list_box_for_enter = list_box.clone();
+    search_entry.connect_activate(move |_| {
+        if let Some(row) = list_box_for_enter.selected_row() {
+            run_row(&row);
+        }
+    });
+
+    let palette_for_stop = palette.clone();
+    search_entry.connect_stop_search(move |_| palette_for_stop.close());
+
+    // Up and Down move the selection while typing
+    let controller = gtk::EventControllerKey::new();
+    controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+    let list_box_for_keys = list_box.clone();
+    controller.connect_key_pressed(move |_, keyval, _keycode, _modifier| {
+        let step = match keyval {
+            gtk::gdk::Key::Down => 1,
+            gtk::gdk::Key::Up => -1,
+            _ => return glib::Propagation::Proceed,
+        };
+        let current = list_box_for_keys.selected_row().map(|row| row.index()).unwrap_or(-1);
+        if let Some(row) = list_box_for_keys.row_at_index(current + step) {
+            list_box_for_keys.select_row(Some(&row));
+        }
+        glib::Propagation::Stop
+    });
+    palette.add_controller(controller);
+
+    palette.present();
+}
@@ -0,0 +1,164 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+use crate::db;
+
+/// Creates the row of pinned task chips shown under the project dropdown,
+/// hidden while nothing is pinned
+pub fn create_pinned_tasks_box() -> gtk::FlowBox {
+    gtk::FlowBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .column_spacing(6)
+        .row_spacing(6)
+        .max_children_per_line(4)
+        .margin_start(20)
+        .margin_end(20)
+        .margin_bottom(10)
+        .visible(false)
+        .build()
+}
+
+/// Label of a pinned task, naming its project when it has one
+fn task_label(task: &db::PinnedTask, project: Option<&db::Project>) -> String {
+    let description = if task.description.is_empty() {
+        "(no description)"
+    } else {
+        task.description.as_str()
+    };
+    match project {
+        Some(project) => format!("{} · {}", description, project.name),
+        None => description.to_string(),
+    }
+}
+
+/// Creates the chip starting a pinned task; a right click unpins it
+fn create_chip(state: &Rc<RefCell<AppState>>, task: &db::PinnedTask) -> gtk::Button {
+    let project = state
+        .borrow()
+        .projects
+        .iter()
+        .find(|project| Some(project.id) == task.project_id)
+        .cloned();
+
+    let content = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    if let Some(ref project) = project {
+        let color_indicator = gtk::Box::builder()
+            .width_request(8)
+            .height_request(8)
+            .valign(gtk::Align::Center)
+            .build();
+        let css_provider = gtk::CssProvider::new();
+        css_provider.load_from_data(&format!(
+            "box {{ background-color: {}; border-radius: 4px; }}",
+            project.color
+        ));
+        color_indicator.style_context().add_provider(
+            &css_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+        content.append(&color_indicator);
+    }
+    content.append(
+        &gtk::Label::builder()
+            .label(if task.description.is_empty() { "(no description)" } else { &task.description })
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(18)
+            .build(),
+    );
+
+    let chip = gtk::Button::builder()
+        .child(&content)
+        .tooltip_text(format!("Start {}. Right-click to unpin.", task_label(task, project.as_ref())))
+        .css_classes(["pinned-task", "caption"])
+        .build();
+
+    let task_id = task.id;
+    let state_for_start = state.clone();
+    chip.connect_clicked(move |_| {
+        if state_for_start.borrow_mut().start_pinned_task(task_id) {
+            let window = state_for_start.borrow().window.clone();
+            if let Some(window) = window {
+                refresh_view(state_for_start.clone(), &window);
+            }
+        }
+    });
+
+    let unpin_gesture = gtk::GestureClick::builder()
+        .button(gtk::gdk::BUTTON_SECONDARY)
+        .build();
+    let state_for_unpin = state.clone();
+    unpin_gesture.connect_pressed(move |_, _, _, _| {
+        if let Err(e) = db::unpin_task(&state_for_unpin.borrow().db_conn, task_id) {
+            state_for_unpin.borrow().show_error(&format!("Failed to unpin task: {}", e));
+            return;
+        }
+        // Rebuilt on the next refresh rather than from the chip's own handler
+        let window = state_for_unpin.borrow().window.clone();
+        if let Some(window) = window {
+            refresh_view(state_for_unpin.clone(), &window);
+        }
+    });
+    chip.add_controller(unpin_gesture);
+
+    chip
+}
+
+/// Reloads the pinned tasks into the chips and the tray menu
+pub fn refresh_pinned_tasks(state: &Rc<RefCell<AppState>>) {
+    let pinned_tasks = db::get_pinned_tasks(&state.borrow().db_conn).unwrap_or_else(|e| {
+        state.borrow().show_error(&format!("Failed to load pinned tasks: {}", e));
+        Vec::new()
+    });
+    state.borrow_mut().pinned_tasks = pinned_tasks.clone();
+
+    let state_borrow = state.borrow();
+    if let Some(ref pinned_tasks_box) = state_borrow.pinned_tasks_box {
+        while let Some(child) = pinned_tasks_box.first_child() {
+            pinned_tasks_box.remove(&child);
+        }
+        for task in &pinned_tasks {
+            pinned_tasks_box.insert(&create_chip(state, task), -1);
+        }
+        pinned_tasks_box.set_visible(!pinned_tasks.is_empty());
+    }
+
+    if let Some(ref tray_manager) = state_borrow.tray_manager {
+        let labels = pinned_tasks
+            .iter()
+            .map(|task| {
+                let project = state_borrow.projects.iter().find(|project| Some(project.id) == task.project_id);
+                (task.id, task_label(task, project))
+            })
+            .collect();
+        if let Ok(manager) = tray_manager.lock() {
+            manager.set_pinned_tasks(labels);
+        }
+    }
+}
+
+/// Pins the description and project of `entry`, or unpins them when they
+/// are pinned already. The chips follow on the next view refresh.
+pub fn toggle_pin(state: &Rc<RefCell<AppState>>, entry: &db::TimeEntry) {
+    let result = {
+        let state = state.borrow();
+        db::find_pinned_task(&state.db_conn, &entry.description, entry.project_id).and_then(|pinned| match pinned {
+            Some(task) => db::unpin_task(&state.db_conn, task.id).map(|()| "Unpinned"),
+            None => db::pin_task(&state.db_conn, &entry.description, entry.project_id).map(|_| "Pinned"),
+        })
+    };
+    match result {
+        Ok(message) => state.borrow().show_info(message),
+        Err(e) => state.borrow().show_error(&format!("Failed to pin task: {}", e)),
+    }
+}
+
+/// Whether the description and project of `entry` are pinned
+pub fn is_pinned(state: &AppState, entry: &db::TimeEntry) -> bool {
+    state
+        .pinned_tasks
+        .iter()
+        .any(|task| task.description == entry.description && task.project_id == entry.project_id)
+}
@@ -0,0 +1,574 @@
+use adw::prelude::*;
+use chrono::Utc;
+use gtk4 as gtk;
+use gtk4::{gio, glib};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use super::preferences::save_settings;
+use super::{refresh_view, AppState};
+use crate::db;
+use crate::error::TimeTrackingError;
+use crate::plugins::{
+    self, Plugin, PluginCommand, PluginEntry, PluginEvent, PluginPermissions, PLUGIN_TIMEOUT,
+};
+use crate::privacy;
+
+/// How often replies from plugins are picked up
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often the outbox is checked for events due to be sent again
+const OUTBOX_INTERVAL: Duration = Duration::from_secs(30);
+
+/// One run of a plugin queued for the worker, with the event as the JSON
+/// line it is sent. Retries carry their outbox item and failed attempts.
+pub struct PluginJob {
+    plugin: Plugin,
+    event: String,
+    outbox: Option<(i64, u32)>,
+}
+
+/// Replies of one plugin to one event
+struct PluginReply {
+    job: PluginJob,
+    result: Result<Vec<Result<PluginCommand, String>>, String>,
+}
+
+/// Runs queued for the plugin worker, each event scoped to the plugin it goes to
+pub type PluginQueue = mpsc::Sender<Vec<PluginJob>>;
+
+/// The permissions of the plugin named `name`
+fn permissions(state: &AppState, name: &str) -> PluginPermissions {
+    state.settings.plugin_permissions.get(name).cloned().unwrap_or_default()
+}
+
+/// The entry as sent to plugins, with its project by name. Private entries
+/// follow the privacy preference for shared output.
+fn plugin_entry(state: &AppState, entry: &db::TimeEntry) -> Option<PluginEntry> {
+    let entry = privacy::shared_entry(entry, state.settings.privacy_mode)?;
+    Some(PluginEntry {
+        id: entry.id,
+        project: entry
+            .project_id
+            .and_then(|id| state.projects.iter().find(|p| p.id == id))
+            .map(|p| p.name.clone()),
+        description: entry.description,
+        project_id: entry.project_id,
+        start_time: entry.start_time,
+        end_time: entry.end_time,
+    })
+}
+
+/// Queues `event` for the enabled plugins, as each one's permissions let it
+/// see the event. Plugins run one at a time on a worker thread, so they see
+/// events in order and never block the window.
+pub fn dispatch(state: &AppState, event: PluginEvent) {
+    let Some(ref queue) = state.plugin_queue else {
+        return;
+    };
+    if state.settings.enabled_plugins.is_empty() {
+        return;
+    }
+    let jobs: Vec<PluginJob> = plugins::discover_plugins(&plugins::plugins_dir())
+        .into_iter()
+        .filter(|plugin| state.settings.enabled_plugins.contains(&plugin.name))
+        .filter_map(|plugin| {
+            let event = permissions(state, &plugin.name).scope(&event)?;
+            Some(PluginJob {
+                plugin,
+                event: event.to_json(),
+                outbox: None,
+            })
+        })
+        .collect();
+    if !jobs.is_empty() {
+        let _ = queue.send(jobs);
+    }
+}
+
+/// Tells the enabled plugins about an event of `entry`, such as
+/// `PluginEvent::TimerStarted`
+pub fn dispatch_entry(state: &AppState, entry: &db::TimeEntry, event: fn(PluginEntry) -> PluginEvent) {
+    if let Some(entry) = plugin_entry(state, entry) {
+        dispatch(state, event(entry));
+    }
+}
+
+/// Sends the outbox events that are due again to their plugins, giving up
+/// on those of plugins no longer enabled. `in_flight` holds the outbox items
+/// queued and not replied to yet, which are left alone.
+fn send_due_outbox(state: &AppState, in_flight: &RefCell<HashSet<i64>>) {
+    let Some(ref queue) = state.plugin_queue else {
+        return;
+    };
+    let items = match db::get_due_outbox_items(&state.db_conn, Utc::now()) {
+        Ok(items) => items,
+        Err(e) => {
+            state.show_error(&format!("Failed to load plugin outbox: {}", e));
+            return;
+        }
+    };
+    if items.is_empty() {
+        return;
+    }
+
+    let enabled: Vec<Plugin> = plugins::discover_plugins(&plugins::plugins_dir())
+        .into_iter()
+        .filter(|plugin| state.settings.enabled_plugins.contains(&plugin.name))
+        .collect();
+    let mut jobs = Vec::new();
+    for item in items {
+        if in_flight.borrow().contains(&item.id) {
+            continue;
+        }
+        match enabled.iter().find(|plugin| plugin.name == item.plugin) {
+            Some(plugin) => {
+                in_flight.borrow_mut().insert(item.id);
+                jobs.push(PluginJob {
+                    plugin: plugin.clone(),
+                    event: item.event,
+                    outbox: Some((item.id, item.attempts)),
+                });
+            }
+            None => {
+                let _ = db::record_outbox_failure(&state.db_conn, item.id, "plugin is not enabled", None);
+            }
+        }
+    }
+    if !jobs.is_empty() {
+        let _ = queue.send(jobs);
+    }
+}
+
+/// Keeps a failed run in the outbox to be sent again after a growing wait,
+/// or gives up on it after `plugins::MAX_DELIVERY_ATTEMPTS`
+fn record_failure(state: &AppState, job: &PluginJob, error: &str) {
+    let now = Utc::now();
+    let attempts = job.outbox.map_or(1, |(_, attempts)| attempts + 1);
+    let next_attempt_at = plugins::retry_delay(attempts).map(|delay| now + delay);
+    let result = match job.outbox {
+        Some((id, _)) => db::record_outbox_failure(&state.db_conn, id, error, next_attempt_at),
+        None => db::add_outbox_item(
+            &state.db_conn,
+            &job.plugin.name,
+            &job.event,
+            error,
+            next_attempt_at.unwrap_or(now),
+        )
+        .map(|_| ()),
+    };
+    if let Err(e) = result {
+        state.show_error(&format!("Failed to keep plugin event for retrying: {}", e));
+    } else if next_attempt_at.is_none() {
+        state.show_error(&format!(
+            "Plugin {} {}, gave up after {} attempts",
+            job.plugin.name, error, attempts
+        ));
+    }
+}
+
+/// Creates the strip under the window content counting the plugin events
+/// waiting to be sent again, hidden while there are none
+fn create_outbox_strip() -> (gtk::Box, gtk::Label, gtk::Button) {
+    let strip = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(4)
+        .margin_bottom(4)
+        .visible(false)
+        .build();
+    let label = gtk::Label::builder()
+        .halign(gtk::Align::Start)
+        .hexpand(true)
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .css_classes(["caption", "dim-label"])
+        .build();
+    let retry_button = gtk::Button::builder()
+        .label("Retry Now")
+        .valign(gtk::Align::Center)
+        .css_classes(["flat", "caption"])
+        .build();
+    strip.append(&label);
+    strip.append(&retry_button);
+    (strip, label, retry_button)
+}
+
+/// Shows how many plugin events are waiting to be sent again and how many
+/// were given up
+fn update_outbox_strip(state: &AppState, strip: &gtk::Box, label: &gtk::Label) {
+    let (pending, failed) = db::count_outbox_items(&state.db_conn).unwrap_or((0, 0));
+    let mut parts = Vec::new();
+    if pending > 0 {
+        parts.push(format!(
+            "{} plugin {} waiting to be sent again",
+            pending,
+            if pending == 1 { "event" } else { "events" }
+        ));
+    }
+    if failed > 0 {
+        parts.push(format!(
+            "{} plugin {} failed",
+            failed,
+            if failed == 1 { "event" } else { "events" }
+        ));
+    }
+    label.set_label(&parts.join(" · "));
+    label.set_tooltip_text(Some(&format!(
+        "Events a plugin fails to take are sent again after a growing wait, up to {} times",
+        plugins::MAX_DELIVERY_ATTEMPTS
+    )));
+    strip.set_visible(!parts.is_empty());
+}
+
+/// Starts the worker running plugins and applies their commands as they
+/// reply, then tells the plugins the app started. Events a plugin fails to
+/// take are kept in the outbox and sent again, which the returned strip
+/// counts.
+pub fn start_plugin_host(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) -> gtk::Box {
+    let (queue, jobs) = mpsc::channel::<Vec<PluginJob>>();
+    let (reply_sender, replies) = mpsc::channel();
+    thread::spawn(move || {
+        for batch in jobs {
+            for job in batch {
+                let result = plugins::run_plugin(&job.plugin, &job.event, PLUGIN_TIMEOUT);
+                if reply_sender.send(PluginReply { job, result }).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    state.borrow_mut().plugin_queue = Some(queue);
+
+    let (strip, strip_label, retry_button) = create_outbox_strip();
+    update_outbox_strip(&state.borrow(), &strip, &strip_label);
+    let in_flight: Rc<RefCell<HashSet<i64>>> = Rc::new(RefCell::new(HashSet::new()));
+
+    let window = window.clone();
+    let state_for_poll = state.clone();
+    let in_flight_for_poll = in_flight.clone();
+    let strip_for_poll = strip.clone();
+    let strip_label_for_poll = strip_label.clone();
+    glib::timeout_add_local(POLL_INTERVAL, move || {
+        let mut replied = false;
+        while let Ok(reply) = replies.try_recv() {
+            replied = true;
+            if let Some((id, _)) = reply.job.outbox {
+                in_flight_for_poll.borrow_mut().remove(&id);
+            }
+            match reply.result {
+                Ok(commands) => {
+                    if let Some((id, _)) = reply.job.outbox {
+                        let _ = db::delete_outbox_item(&state_for_poll.borrow().db_conn, id);
+                    }
+                    for command in commands {
+                        match command {
+                            Ok(command) => apply_command(&state_for_poll, &window, &reply.job.plugin.name, command),
+                            Err(e) => state_for_poll
+                                .borrow()
+                                .show_error(&format!("Plugin {}: {}", reply.job.plugin.name, e)),
+                        }
+                    }
+                }
+                Err(e) => record_failure(&state_for_poll.borrow(), &reply.job, &e),
+            }
+        }
+        if replied {
+            update_outbox_strip(&state_for_poll.borrow(), &strip_for_poll, &strip_label_for_poll);
+        }
+        glib::ControlFlow::Continue
+    });
+
+    let state_for_outbox = state.clone();
+    let in_flight_for_outbox = in_flight.clone();
+    glib::timeout_add_local(OUTBOX_INTERVAL, move || {
+        send_due_outbox(&state_for_outbox.borrow(), &in_flight_for_outbox);
+        glib::ControlFlow::Continue
+    });
+
+    let state_for_retry = state.clone();
+    let in_flight_for_retry = in_flight.clone();
+    let strip_for_retry = strip.clone();
+    retry_button.connect_clicked(move |_| {
+        let state = state_for_retry.borrow();
+        if let Err(e) = db::retry_outbox_now(&state.db_conn, Utc::now()) {
+            state.show_error(&format!("Failed to retry plugin events: {}", e));
+            return;
+        }
+        send_due_outbox(&state, &in_flight_for_retry);
+        update_outbox_strip(&state, &strip_for_retry, &strip_label);
+    });
+
+    // Events left over from the last run go out along with the start
+    send_due_outbox(&state.borrow(), &in_flight);
+    dispatch(&state.borrow(), PluginEvent::AppStarted);
+    strip
+}
+
+/// What running a command needs permission for, to explain a refusal
+fn command_permission(command: &PluginCommand) -> &'static str {
+    match command {
+        PluginCommand::Notify { .. } => "show notifications",
+        PluginCommand::StartTemplate { .. } => "start timers",
+        PluginCommand::AnnotateEntry { .. } => "annotate entries",
+    }
+}
+
+/// Carries out a command a plugin replied with, if its permissions allow it
+fn apply_command(
+    state: &Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+    plugin: &str,
+    command: PluginCommand,
+) {
+    let permissions = permissions(&state.borrow(), plugin);
+    if !permissions.allows(&command) {
+        state.borrow().show_error(&format!(
+            "Plugin {} isn't allowed to {}",
+            plugin,
+            command_permission(&command)
+        ));
+        return;
+    }
+
+    match command {
+        PluginCommand::Notify { title, body } => {
+            let notification = gio::Notification::new(&title);
+            if !body.is_empty() {
+                notification.set_body(Some(&body));
+            }
+            if let Some(app) = window.application() {
+                app.send_notification(Some(&format!("plugin-{}", plugin)), &notification);
+            }
+        }
+        PluginCommand::StartTemplate { description, project } => {
+            let started = {
+                let mut state = state.borrow_mut();
+                let project_id = match project {
+                    Some(ref name) => match state.projects.iter().find(|p| p.name.eq_ignore_ascii_case(name)) {
+                        Some(project) => Some(project.id),
+                        None => {
+                            state.show_error(&format!("Plugin {}: unknown project \"{}\"", plugin, name));
+                            return;
+                        }
+                    },
+                    None => None,
+                };
+                if !permissions.covers(project_id) {
+                    state.show_error(&format!("Plugin {} isn't allowed to track that project", plugin));
+                    return;
+                }
+                if state.running_entry.is_some() {
+                    state.stop_timer();
+                }
+                state.description_entry.set_text(&description);
+                state.set_selected_project(project_id);
+                state.start_timer_at(Utc::now())
+            };
+            if started {
+                refresh_view(state.clone(), window);
+            }
+        }
+        PluginCommand::AnnotateEntry { entry_id, text } => {
+            let result = {
+                let state = state.borrow();
+                match db::get_entry_by_id(&state.db_conn, entry_id) {
+                    // Entries the plugin can't see are reported as missing
+                    Ok(entry)
+                        if !plugin_entry(&state, &entry).is_some_and(|e| permissions.covers(e.project_id)) =>
+                    {
+                        Err(TimeTrackingError::NotFound("entry"))
+                    }
+                    Ok(entry) => {
+                        let description = format!("{} {}", entry.description, text).trim().to_string();
+                        state
+                            .ensure_unlocked(entry.start_time)
+                            .and_then(|()| {
+                                db::update_entry_details(&state.db_conn, entry_id, entry.project_id, &description)
+                            })
+                            .map(|()| description)
+                    }
+                    Err(e) => Err(e),
+                }
+            };
+            match result {
+                Ok(description) => {
+                    {
+                        let mut state_borrow = state.borrow_mut();
+                        let state_mut = &mut *state_borrow;
+                        if let Some(running) = state_mut.running_entry.as_mut().filter(|e| e.id == entry_id) {
+                            running.description = description.clone();
+                            state_mut.description_entry.set_text(&description);
+                        }
+                    }
+                    refresh_view(state.clone(), window);
+                }
+                Err(e) => state
+                    .borrow()
+                    .show_error(&format!("Plugin {}: failed to annotate entry {}: {}", plugin, entry_id, e)),
+            }
+        }
+    }
+}
+
+/// A command switch of the permissions dialog: its title, subtitle, state
+/// and how it changes the permissions
+type CommandSwitch = (&'static str, &'static str, bool, fn(&mut PluginPermissions, bool));
+
+/// Opens the permissions of the plugin named `plugin`: what it is sent,
+/// which projects it is limited to and which commands it may run. Changes
+/// are saved right away.
+pub fn show_permissions_dialog(state: Rc<RefCell<AppState>>, parent: &impl IsA<gtk::Window>, plugin: &str) {
+    let current = permissions(&state.borrow(), plugin);
+    let update = {
+        let state = state.clone();
+        let plugin = plugin.to_string();
+        Rc::new(move |change: &dyn Fn(&mut PluginPermissions)| {
+            change(state.borrow_mut().settings.plugin_permissions.entry(plugin.clone()).or_default());
+            save_settings(&state);
+        })
+    };
+    let switch_row = |title: &str, subtitle: &str, active: bool| {
+        let switch = gtk::Switch::builder()
+            .active(active)
+            .valign(gtk::Align::Center)
+            .build();
+        let row = adw::ActionRow::builder()
+            .title(title)
+            .subtitle(subtitle)
+            .activatable_widget(&switch)
+            .build();
+        row.add_suffix(&switch);
+        (row, switch)
+    };
+
+    let reads_group = adw::PreferencesGroup::builder()
+        .title("Sent to the Plugin")
+        .description(
+            "With every timer start and stop. Private entries follow the privacy preference for shared output.",
+        )
+        .build();
+    let times_row = adw::ActionRow::builder()
+        .title("Entry times and project")
+        .subtitle("Start, end, ID and project name of entries in scope")
+        .build();
+    times_row.add_suffix(&gtk::Label::builder().label("Always").css_classes(["dim-label"]).build());
+    reads_group.add(&times_row);
+    let (descriptions_row, descriptions_switch) =
+        switch_row("Entry descriptions", "What was worked on, as typed", current.descriptions);
+    reads_group.add(&descriptions_row);
+    let update_for_descriptions = update.clone();
+    descriptions_switch.connect_active_notify(move |switch| {
+        let active = switch.is_active();
+        update_for_descriptions(&|permissions| permissions.descriptions = active);
+    });
+
+    let projects_group = adw::PreferencesGroup::builder()
+        .title("Projects")
+        .description("Entries of other projects are never sent, and commands can't touch them")
+        .build();
+    let (all_projects_row, all_projects_switch) = switch_row(
+        "All projects",
+        "Includes entries without a project",
+        current.project_ids.is_none(),
+    );
+    projects_group.add(&all_projects_row);
+    let mut project_checks = Vec::new();
+    for project in state.borrow().projects.iter() {
+        let check = gtk::CheckButton::builder()
+            .active(current.project_ids.as_ref().is_some_and(|ids| ids.contains(&project.id)))
+            .valign(gtk::Align::Center)
+            .build();
+        let row = adw::ActionRow::builder()
+            .title(&project.name)
+            .activatable_widget(&check)
+            .build();
+        row.add_prefix(&check);
+        all_projects_switch
+            .bind_property("active", &row, "sensitive")
+            .invert_boolean()
+            .sync_create()
+            .build();
+        projects_group.add(&row);
+        project_checks.push((project.id, check));
+    }
+
+    // The scope is rebuilt from the switch and the checks on any change
+    let project_checks = Rc::new(project_checks);
+    let update_scope = {
+        let update = update.clone();
+        let all_projects_switch = all_projects_switch.clone();
+        let project_checks = project_checks.clone();
+        Rc::new(move || {
+            let project_ids = (!all_projects_switch.is_active()).then(|| {
+                project_checks
+                    .iter()
+                    .filter(|(_, check)| check.is_active())
+                    .map(|(id, _)| *id)
+                    .collect::<Vec<i64>>()
+            });
+            update(&|permissions| permissions.project_ids = project_ids.clone());
+        })
+    };
+    let update_for_switch = update_scope.clone();
+    all_projects_switch.connect_active_notify(move |_| update_for_switch());
+    for (_, check) in project_checks.iter() {
+        let update_scope = update_scope.clone();
+        check.connect_toggled(move |_| update_scope());
+    }
+
+    let writes_group = adw::PreferencesGroup::builder()
+        .title("Commands")
+        .description("What the plugin may do in reply to an event")
+        .build();
+    let commands: [CommandSwitch; 3] = [
+        ("Show notifications", "Desktop notifications with its own text", current.notify, |p, on| {
+            p.notify = on
+        }),
+        ("Start timers", "Stops the running timer and starts another", current.start_timers, |p, on| {
+            p.start_timers = on
+        }),
+        ("Annotate entries", "Appends text to entry descriptions", current.annotate_entries, |p, on| {
+            p.annotate_entries = on
+        }),
+    ];
+    for (title, subtitle, active, set) in commands {
+        let (row, switch) = switch_row(title, subtitle, active);
+        writes_group.add(&row);
+        let update = update.clone();
+        switch.connect_active_notify(move |switch| {
+            let active = switch.is_active();
+            update(&|permissions| set(permissions, active));
+        });
+    }
+
+    let page = adw::PreferencesPage::new();
+    page.add(&reads_group);
+    page.add(&projects_group);
+    page.add(&writes_group);
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .build();
+    content.append(
+        &adw::HeaderBar::builder()
+            .title_widget(&adw::WindowTitle::new("Plugin Permissions", plugin))
+            .build(),
+    );
+    content.append(&page);
+
+    let dialog = adw::Window::builder()
+        .title("Plugin Permissions")
+        .default_width(420)
+        .default_height(560)
+        .modal(true)
+        .transient_for(parent)
+        .content(&content)
+        .build();
+    dialog.present();
+}
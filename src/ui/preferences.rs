@@ -0,0 +1,760 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use gtk4::{gio, glib};
+use chrono::Local;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::date_picker::DatePicker;
+use super::{integrity, mini_timer, refresh_view, AppState};
+use crate::autostart;
+use crate::duration::{Rounding, RoundingMode, RoundingScope};
+use crate::plugins;
+use crate::privacy::PrivacyMode;
+use crate::report::ReportRange;
+use crate::settings::{
+    CloseAction, DurationFormat, TrayClickAction, AUTO_ARCHIVE_MONTHS, EXPECTED_DAILY_MINUTES, WEEKLY_TARGET_MINUTES,
+    WORK_DAYS_PER_WEEK,
+};
+
+/// Saves the current settings, showing an error toast on failure
+pub fn save_settings(state: &Rc<RefCell<AppState>>) {
+    let state_borrow = state.borrow();
+    if let Err(e) = state_borrow.settings.save(&state_borrow.db_conn) {
+        state_borrow.show_error(&format!("Failed to save preferences: {}", e));
+    }
+}
+
+/// Creates the "Display" preferences group
+fn create_display_group(
+    state: &Rc<RefCell<AppState>>,
+    parent: &adw::ApplicationWindow,
+) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Display")
+        .build();
+
+    // Duration format for totals and summaries
+    let labels: Vec<&str> = DurationFormat::ALL.iter().map(|f| f.label()).collect();
+    let current = state.borrow().settings.duration_format;
+    let duration_format_row = adw::ComboRow::builder()
+        .title("Duration format")
+        .subtitle("Used for day totals and weekly summaries")
+        .model(&gtk::StringList::new(&labels))
+        .selected(DurationFormat::ALL.iter().position(|f| *f == current).unwrap_or(0) as u32)
+        .build();
+
+    let state_for_format = state.clone();
+    let parent_for_format = parent.clone();
+    duration_format_row.connect_selected_notify(move |row| {
+        if let Some(format) = DurationFormat::ALL.get(row.selected() as usize) {
+            state_for_format.borrow_mut().settings.duration_format = *format;
+            save_settings(&state_for_format);
+            refresh_view(state_for_format.clone(), &parent_for_format);
+        }
+    });
+    group.add(&duration_format_row);
+
+    // Compact density for small screens
+    let compact_switch = gtk::Switch::builder()
+        .active(state.borrow().settings.compact)
+        .valign(gtk::Align::Center)
+        .build();
+    let compact_row = adw::ActionRow::builder()
+        .title("Compact layout")
+        .subtitle("Smaller timer and rows without start and end times")
+        .activatable_widget(&compact_switch)
+        .build();
+    compact_row.add_suffix(&compact_switch);
+
+    let state_for_compact = state.clone();
+    let parent_for_compact = parent.clone();
+    compact_switch.connect_active_notify(move |switch| {
+        state_for_compact.borrow_mut().settings.compact = switch.is_active();
+        save_settings(&state_for_compact);
+        state_for_compact.borrow().apply_density();
+        refresh_view(state_for_compact.clone(), &parent_for_compact);
+    });
+    group.add(&compact_row);
+
+    // Shown weeks, months and custom ranges kept across restarts
+    let remember_switch = gtk::Switch::builder()
+        .active(state.borrow().settings.remember_view_dates)
+        .valign(gtk::Align::Center)
+        .build();
+    let remember_row = adw::ActionRow::builder()
+        .title("Remember shown dates")
+        .subtitle("Week, Month and Custom views reopen where they were left after a restart")
+        .activatable_widget(&remember_switch)
+        .build();
+    remember_row.add_suffix(&remember_switch);
+
+    let state_for_remember = state.clone();
+    remember_switch.connect_active_notify(move |switch| {
+        state_for_remember.borrow_mut().settings.remember_view_dates = switch.is_active();
+        save_settings(&state_for_remember);
+        state_for_remember.borrow().save_view_dates();
+    });
+    group.add(&remember_row);
+
+    // A mini timer that can't be clicked never takes focus from other windows
+    let click_through_switch = gtk::Switch::builder()
+        .active(state.borrow().settings.mini_timer_click_through)
+        .valign(gtk::Align::Center)
+        .build();
+    let click_through_row = adw::ActionRow::builder()
+        .title("Click-through mini timer")
+        .subtitle("Clicks pass to the windows below and its buttons are hidden; close it with Ctrl+M")
+        .activatable_widget(&click_through_switch)
+        .build();
+    click_through_row.add_suffix(&click_through_switch);
+
+    let state_for_click_through = state.clone();
+    click_through_switch.connect_active_notify(move |switch| {
+        state_for_click_through.borrow_mut().settings.mini_timer_click_through = switch.is_active();
+        save_settings(&state_for_click_through);
+        mini_timer::apply_click_through(&state_for_click_through.borrow());
+    });
+    group.add(&click_through_row);
+
+    // Expected hours per day, the goal of the ring around the start/stop
+    // button and compared against in the month view
+    let labels: Vec<String> = EXPECTED_DAILY_MINUTES
+        .iter()
+        .map(|&minutes| match minutes % 60 {
+            0 => format!("{} hours", minutes / 60),
+            rest => format!("{}.{} hours", minutes / 60, rest * 10 / 60),
+        })
+        .collect();
+    let current = state.borrow().settings.expected_daily_minutes;
+    let expected_row = create_combo_row(
+        "Daily target",
+        "Fills the ring around the start button; month view days are colored by how their total compares",
+        &labels.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        EXPECTED_DAILY_MINUTES.iter().position(|&m| m == current).unwrap_or(0),
+    );
+
+    let state_for_expected = state.clone();
+    let parent_for_expected = parent.clone();
+    expected_row.connect_selected_notify(move |row| {
+        if let Some(&minutes) = EXPECTED_DAILY_MINUTES.get(row.selected() as usize) {
+            state_for_expected.borrow_mut().settings.expected_daily_minutes = minutes;
+            save_settings(&state_for_expected);
+            refresh_view(state_for_expected.clone(), &parent_for_expected);
+        }
+    });
+    group.add(&expected_row);
+
+    // Days worked per week, for projecting the current week's total
+    let labels: Vec<String> = WORK_DAYS_PER_WEEK
+        .iter()
+        .map(|&days| match days {
+            5 => "Monday to Friday".to_string(),
+            7 => "Every day".to_string(),
+            _ => format!("{} days from Monday", days),
+        })
+        .collect();
+    let current = state.borrow().settings.work_days_per_week;
+    let work_days_row = create_combo_row(
+        "Work days",
+        "The Week view projects its total from the pace on these days",
+        &labels.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        WORK_DAYS_PER_WEEK.iter().position(|&d| d == current).unwrap_or(0),
+    );
+
+    let state_for_work_days = state.clone();
+    let parent_for_work_days = parent.clone();
+    work_days_row.connect_selected_notify(move |row| {
+        if let Some(&days) = WORK_DAYS_PER_WEEK.get(row.selected() as usize) {
+            state_for_work_days.borrow_mut().settings.work_days_per_week = days;
+            save_settings(&state_for_work_days);
+            refresh_view(state_for_work_days.clone(), &parent_for_work_days);
+        }
+    });
+    group.add(&work_days_row);
+
+    // Hours to work in a week, burned down in the Week view
+    let labels: Vec<String> = WEEKLY_TARGET_MINUTES
+        .iter()
+        .map(|&minutes| match (minutes, minutes % 60) {
+            (0, _) => "None".to_string(),
+            (_, 0) => format!("{} hours", minutes / 60),
+            (_, rest) => format!("{}.{} hours", minutes / 60, rest * 10 / 60),
+        })
+        .collect();
+    let current = state.borrow().settings.weekly_target_minutes;
+    let weekly_target_row = create_combo_row(
+        "Weekly target",
+        "The Week view shows the hours left and burns them down per day",
+        &labels.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        WEEKLY_TARGET_MINUTES.iter().position(|&m| m == current).unwrap_or(0),
+    );
+
+    let state_for_weekly_target = state.clone();
+    let parent_for_weekly_target = parent.clone();
+    weekly_target_row.connect_selected_notify(move |row| {
+        if let Some(&minutes) = WEEKLY_TARGET_MINUTES.get(row.selected() as usize) {
+            state_for_weekly_target.borrow_mut().settings.weekly_target_minutes = minutes;
+            save_settings(&state_for_weekly_target);
+            refresh_view(state_for_weekly_target.clone(), &parent_for_weekly_target);
+        }
+    });
+    group.add(&weekly_target_row);
+
+    group
+}
+
+/// Creates a combo row offering the given labels with `selected` preselected
+fn create_combo_row(title: &str, subtitle: &str, labels: &[&str], selected: usize) -> adw::ComboRow {
+    adw::ComboRow::builder()
+        .title(title)
+        .subtitle(subtitle)
+        .model(&gtk::StringList::new(labels))
+        .selected(selected as u32)
+        .build()
+}
+
+/// Creates the "Rounding" preferences group. Rounding only changes displayed
+/// durations, the stored start and end times are never modified.
+fn create_rounding_group(
+    state: &Rc<RefCell<AppState>>,
+    parent: &adw::ApplicationWindow,
+) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Rounding")
+        .description("Recorded start and end times are always kept unrounded")
+        .build();
+
+    let current = state.borrow().settings.rounding;
+
+    let interval_labels: Vec<String> = Rounding::INTERVALS
+        .iter()
+        .map(|&minutes| {
+            if minutes == 0 {
+                "Off".to_string()
+            } else {
+                format!("{} minutes", minutes)
+            }
+        })
+        .collect();
+    let interval_row = create_combo_row(
+        "Round to",
+        "Interval durations are rounded to",
+        &interval_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        Rounding::INTERVALS
+            .iter()
+            .position(|&i| i == current.interval_minutes)
+            .unwrap_or(0),
+    );
+
+    let mode_labels: Vec<&str> = RoundingMode::ALL.iter().map(|m| m.label()).collect();
+    let mode_row = create_combo_row(
+        "Direction",
+        "How partial intervals are rounded",
+        &mode_labels,
+        RoundingMode::ALL.iter().position(|&m| m == current.mode).unwrap_or(0),
+    );
+
+    let scope_labels: Vec<&str> = RoundingScope::ALL.iter().map(|s| s.label()).collect();
+    let scope_row = create_combo_row(
+        "Apply to",
+        "Round entries as they stop or only totals",
+        &scope_labels,
+        RoundingScope::ALL.iter().position(|&s| s == current.scope).unwrap_or(0),
+    );
+
+    let update_rounding = {
+        let state = state.clone();
+        let parent = parent.clone();
+        let interval_row = interval_row.clone();
+        let mode_row = mode_row.clone();
+        let scope_row = scope_row.clone();
+        move || {
+            let rounding = Rounding {
+                interval_minutes: Rounding::INTERVALS
+                    .get(interval_row.selected() as usize)
+                    .copied()
+                    .unwrap_or(0),
+                mode: RoundingMode::ALL
+                    .get(mode_row.selected() as usize)
+                    .copied()
+                    .unwrap_or_default(),
+                scope: RoundingScope::ALL
+                    .get(scope_row.selected() as usize)
+                    .copied()
+                    .unwrap_or_default(),
+            };
+            mode_row.set_sensitive(rounding.interval_minutes > 0);
+            scope_row.set_sensitive(rounding.interval_minutes > 0);
+            state.borrow_mut().settings.rounding = rounding;
+            save_settings(&state);
+            refresh_view(state.clone(), &parent);
+        }
+    };
+
+    mode_row.set_sensitive(current.interval_minutes > 0);
+    scope_row.set_sensitive(current.interval_minutes > 0);
+
+    let update = Rc::new(update_rounding);
+    for row in [&interval_row, &mode_row, &scope_row] {
+        let update = update.clone();
+        row.connect_selected_notify(move |_| update());
+        group.add(row);
+    }
+
+    group
+}
+
+/// Creates the "Timer" preferences group
+fn create_timer_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Timer")
+        .build();
+
+    let (project_ids, labels): (Vec<Option<i64>>, Vec<String>) = {
+        let state = state.borrow();
+        std::iter::once((None, "No Project".to_string()))
+            .chain(state.projects.iter().map(|project| (Some(project.id), project.name.clone())))
+            .unzip()
+    };
+    let current = state.borrow().settings.default_project_id;
+    let default_project_row = create_combo_row(
+        "Default project",
+        "Selected at startup and whenever the timer stops",
+        &labels.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        project_ids.iter().position(|&id| id == current).unwrap_or(0),
+    );
+
+    let state_for_default = state.clone();
+    default_project_row.connect_selected_notify(move |row| {
+        if let Some(&project_id) = project_ids.get(row.selected() as usize) {
+            state_for_default.borrow_mut().settings.default_project_id = project_id;
+            save_settings(&state_for_default);
+            let state = state_for_default.borrow();
+            if state.running_entry.is_none() {
+                state.reset_selected_project();
+            }
+        }
+    });
+    group.add(&default_project_row);
+
+    group
+}
+
+/// Creates the "Privacy" preferences group
+fn create_privacy_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Privacy")
+        .description("Private entries always count toward your own totals")
+        .build();
+
+    let labels: Vec<&str> = PrivacyMode::ALL.iter().map(|m| m.label()).collect();
+    let current = state.borrow().settings.privacy_mode;
+    let privacy_row = create_combo_row(
+        "Private entries",
+        "In exports, shared reports and viewer mode",
+        &labels,
+        PrivacyMode::ALL.iter().position(|&m| m == current).unwrap_or(0),
+    );
+
+    let state_for_privacy = state.clone();
+    privacy_row.connect_selected_notify(move |row| {
+        if let Some(&mode) = PrivacyMode::ALL.get(row.selected() as usize) {
+            state_for_privacy.borrow_mut().settings.privacy_mode = mode;
+            save_settings(&state_for_privacy);
+        }
+    });
+    group.add(&privacy_row);
+
+    group
+}
+
+/// Creates the "Focus" preferences group
+fn create_focus_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Focus")
+        .description("Projects are marked as deep work in Manage Projects")
+        .build();
+
+    let dnd_switch = gtk::Switch::builder()
+        .active(state.borrow().settings.do_not_disturb)
+        .valign(gtk::Align::Center)
+        .build();
+    let dnd_row = adw::ActionRow::builder()
+        .title("Do Not Disturb for deep work")
+        .subtitle("Silences notifications on GNOME and Plasma while a deep work project is tracked")
+        .activatable_widget(&dnd_switch)
+        .build();
+    dnd_row.add_suffix(&dnd_switch);
+
+    let state_for_dnd = state.clone();
+    dnd_switch.connect_active_notify(move |switch| {
+        state_for_dnd.borrow_mut().settings.do_not_disturb = switch.is_active();
+        save_settings(&state_for_dnd);
+        state_for_dnd.borrow_mut().update_do_not_disturb();
+    });
+    group.add(&dnd_row);
+
+    group
+}
+
+/// Creates the "Plugins" preferences group, with a switch for each plugin
+/// found in the plugins directory
+fn create_plugins_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let dir = plugins::plugins_dir();
+    let group = adw::PreferencesGroup::builder()
+        .title("Plugins")
+        .description(format!(
+            "Executables in {} receive timer events and can reply with commands. \
+             Only enable plugins you trust.",
+            dir.display()
+        ))
+        .build();
+
+    let found = plugins::discover_plugins(&dir);
+    if found.is_empty() {
+        group.add(&adw::ActionRow::builder().title("No plugins found").build());
+    }
+    for plugin in found {
+        let switch = gtk::Switch::builder()
+            .active(state.borrow().settings.enabled_plugins.contains(&plugin.name))
+            .valign(gtk::Align::Center)
+            .build();
+        let row = adw::ActionRow::builder()
+            .title(&plugin.name)
+            .activatable_widget(&switch)
+            .build();
+        let permissions_button = gtk::Button::builder()
+            .icon_name("emblem-system-symbolic")
+            .tooltip_text("Permissions")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        row.add_suffix(&permissions_button);
+        row.add_suffix(&switch);
+
+        let state_for_permissions = state.clone();
+        let name_for_permissions = plugin.name.clone();
+        permissions_button.connect_clicked(move |button| {
+            if let Some(window) = button.root().and_downcast::<gtk::Window>() {
+                super::plugins::show_permissions_dialog(state_for_permissions.clone(), &window, &name_for_permissions);
+            }
+        });
+
+        let state_for_plugin = state.clone();
+        switch.connect_active_notify(move |switch| {
+            let enabled_plugins = &mut state_for_plugin.borrow_mut().settings.enabled_plugins;
+            enabled_plugins.retain(|name| *name != plugin.name);
+            if switch.is_active() {
+                enabled_plugins.push(plugin.name.clone());
+            }
+            save_settings(&state_for_plugin);
+        });
+        group.add(&row);
+    }
+
+    group
+}
+
+/// Asks the Background portal to start the app at login, or to stop doing
+/// so. `on_done` gets whether the portal agreed, once the user answered it.
+fn request_portal_autostart(enabled: bool, on_done: impl Fn(bool) + 'static) {
+    let connection = match gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>) {
+        Ok(connection) => connection,
+        Err(_) => return on_done(false),
+    };
+    let exec = std::env::current_exe().unwrap_or_else(|_| "time-tracking".into());
+    let options = glib::VariantDict::new(None);
+    options.insert("reason", "Start tracking time when you log in");
+    options.insert("autostart", enabled);
+    options.insert("commandline", vec![exec.to_string_lossy().to_string(), autostart::HIDDEN_FLAG.to_string()]);
+
+    let connection_for_reply = connection.clone();
+    connection.call(
+        Some("org.freedesktop.portal.Desktop"),
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.Background",
+        "RequestBackground",
+        Some(&("", options.end()).to_variant()),
+        Some(glib::VariantTy::new("(o)").unwrap()),
+        gio::DBusCallFlags::NONE,
+        -1,
+        None::<&gio::Cancellable>,
+        move |result| {
+            let Some(handle) = result.ok().and_then(|reply| reply.child_value(0).str().map(str::to_string)) else {
+                return on_done(false);
+            };
+            // The answer comes as a Response signal on the request's handle
+            let subscription = Rc::new(Cell::new(None));
+            let subscription_for_response = subscription.clone();
+            let id = connection_for_reply.signal_subscribe(
+                Some("org.freedesktop.portal.Desktop"),
+                Some("org.freedesktop.portal.Request"),
+                Some("Response"),
+                Some(&handle),
+                None,
+                gio::DBusSignalFlags::NO_MATCH_RULE,
+                move |connection, _, _, _, _, parameters| {
+                    if let Some(id) = subscription_for_response.take() {
+                        connection.signal_unsubscribe(id);
+                    }
+                    let granted = parameters
+                        .get::<(u32, HashMap<String, glib::Variant>)>()
+                        .is_some_and(|(response, results)| {
+                            response == 0
+                                && results.get("autostart").and_then(|value| value.get::<bool>()) == Some(enabled)
+                        });
+                    on_done(granted);
+                },
+            );
+            subscription.set(Some(id));
+        },
+    );
+}
+
+/// Creates the "Startup" preferences group
+fn create_startup_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Startup")
+        .build();
+
+    let flatpak = autostart::is_flatpak();
+    let enabled = if flatpak {
+        state.borrow().settings.portal_autostart
+    } else {
+        autostart::is_enabled()
+    };
+    let autostart_switch = gtk::Switch::builder()
+        .active(enabled)
+        .valign(gtk::Align::Center)
+        .build();
+    let autostart_row = adw::ActionRow::builder()
+        .title("Start at login")
+        .subtitle(if autostart::STARTS_HIDDEN {
+            "Opens in the system tray when you log in"
+        } else {
+            "Opens the window when you log in"
+        })
+        .activatable_widget(&autostart_switch)
+        .build();
+    autostart_row.add_suffix(&autostart_switch);
+
+    // Turning the switch back after a failure shouldn't try again
+    let reverting = Rc::new(Cell::new(false));
+    let state_for_switch = state.clone();
+    autostart_switch.connect_active_notify(move |switch| {
+        if reverting.replace(false) {
+            return;
+        }
+        let enabled = switch.is_active();
+        if flatpak {
+            let state = state_for_switch.clone();
+            let switch = switch.clone();
+            let reverting = reverting.clone();
+            request_portal_autostart(enabled, move |granted| {
+                if granted {
+                    state.borrow_mut().settings.portal_autostart = enabled;
+                    save_settings(&state);
+                    return;
+                }
+                state.borrow().show_error("Starting at login was not allowed");
+                reverting.set(true);
+                switch.set_active(!enabled);
+            });
+            return;
+        }
+
+        let result = std::env::current_exe().and_then(|exec| autostart::set_enabled(&exec, enabled));
+        if let Err(e) = result {
+            state_for_switch
+                .borrow()
+                .show_error(&format!("Failed to change starting at login: {}", e));
+            reverting.set(true);
+            switch.set_active(!enabled);
+        }
+    });
+    group.add(&autostart_row);
+
+    group
+}
+
+/// Creates the "System Tray" preferences group
+fn create_tray_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("System Tray")
+        .build();
+    // Without a tray to show the icon in, there is nothing to set up
+    if !state.borrow().has_tray() {
+        group.set_description(Some("No system tray was found on this desktop"));
+        group.set_sensitive(false);
+    }
+
+    let labels: Vec<&str> = TrayClickAction::ALL.iter().map(|a| a.label()).collect();
+    let current = state.borrow().settings.tray_click_action;
+    let click_row = create_combo_row(
+        "Left click",
+        "What clicking the tray icon does; right click opens its menu",
+        &labels,
+        TrayClickAction::ALL.iter().position(|&a| a == current).unwrap_or(0),
+    );
+
+    let state_for_click = state.clone();
+    click_row.connect_selected_notify(move |row| {
+        if let Some(&action) = TrayClickAction::ALL.get(row.selected() as usize) {
+            state_for_click.borrow_mut().settings.tray_click_action = action;
+            save_settings(&state_for_click);
+            state_for_click.borrow().update_tray();
+        }
+    });
+    group.add(&click_row);
+
+    let labels: Vec<&str> = CloseAction::ALL.iter().map(|a| a.label()).collect();
+    let current = state.borrow().settings.close_action;
+    let close_row = create_combo_row(
+        "Close button",
+        "What closing the window does while the tray icon is shown",
+        &labels,
+        CloseAction::ALL.iter().position(|&a| a == current).unwrap_or(0),
+    );
+
+    let state_for_close = state.clone();
+    close_row.connect_selected_notify(move |row| {
+        if let Some(&action) = CloseAction::ALL.get(row.selected() as usize) {
+            state_for_close.borrow_mut().settings.close_action = action;
+            save_settings(&state_for_close);
+        }
+    });
+    group.add(&close_row);
+
+    group
+}
+
+/// Creates the "Maintenance" preferences group
+fn create_maintenance_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Maintenance")
+        .build();
+
+    let labels: Vec<String> = AUTO_ARCHIVE_MONTHS
+        .iter()
+        .map(|&months| {
+            if months == 0 {
+                "Never".to_string()
+            } else {
+                format!("After {} months", months)
+            }
+        })
+        .collect();
+    let current = state.borrow().settings.auto_archive_months;
+    let auto_archive_row = create_combo_row(
+        "Archive unused projects",
+        "Checked at startup, archived projects keep their entries",
+        &labels.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        AUTO_ARCHIVE_MONTHS.iter().position(|&m| m == current).unwrap_or(0),
+    );
+
+    let state_for_archive = state.clone();
+    auto_archive_row.connect_selected_notify(move |row| {
+        if let Some(&months) = AUTO_ARCHIVE_MONTHS.get(row.selected() as usize) {
+            state_for_archive.borrow_mut().settings.auto_archive_months = months;
+            save_settings(&state_for_archive);
+        }
+    });
+    group.add(&auto_archive_row);
+
+    let integrity_row = adw::ActionRow::builder()
+        .title("Check data integrity")
+        .subtitle("Also runs weekly: finds timers left running, unreadable times, and orphaned or duplicate entries")
+        .build();
+    let check_button = gtk::Button::builder()
+        .label("Check Now")
+        .valign(gtk::Align::Center)
+        .build();
+    let state_for_check = state.clone();
+    check_button.connect_clicked(move |button| {
+        if let Some(window) = button.root().and_downcast::<gtk::Window>() {
+            integrity::check_now(state_for_check.clone(), &window);
+        }
+    });
+    integrity_row.add_suffix(&check_button);
+    group.add(&integrity_row);
+
+    group
+}
+
+/// Creates the "Locked Period" preferences group
+fn create_lock_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Locked Period")
+        .description("Timers and imports can't add entries on or before the locked day, e.g. once it is invoiced")
+        .build();
+
+    // Until something is locked, offer the end of last month
+    let locked_until = state.borrow().settings.locked_until;
+    let shown_date = Rc::new(Cell::new(locked_until.unwrap_or_else(|| {
+        ReportRange::month_of(Local::now().date_naive()).shifted(false).end
+    })));
+    let date_picker = DatePicker::new(shown_date.get(), "Last locked day");
+    date_picker.button.set_valign(gtk::Align::Center);
+    date_picker.button.set_sensitive(locked_until.is_some());
+
+    let lock_switch = gtk::Switch::builder()
+        .active(locked_until.is_some())
+        .valign(gtk::Align::Center)
+        .build();
+    let lock_row = adw::ActionRow::builder()
+        .title("Lock entries up to")
+        .activatable_widget(&lock_switch)
+        .build();
+    lock_row.add_suffix(&date_picker.button);
+    lock_row.add_suffix(&lock_switch);
+
+    let state_for_switch = state.clone();
+    let shown_date_for_switch = shown_date.clone();
+    let picker_button = date_picker.button.clone();
+    lock_switch.connect_active_notify(move |switch| {
+        picker_button.set_sensitive(switch.is_active());
+        state_for_switch.borrow_mut().settings.locked_until =
+            switch.is_active().then(|| shown_date_for_switch.get());
+        save_settings(&state_for_switch);
+    });
+
+    let state_for_date = state.clone();
+    date_picker.connect_date_picked(move |date| {
+        shown_date.set(date);
+        state_for_date.borrow_mut().settings.locked_until = Some(date);
+        save_settings(&state_for_date);
+    });
+    group.add(&lock_row);
+
+    group
+}
+
+/// Shows the preferences window
+pub fn show_preferences_window(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow) {
+    let page = adw::PreferencesPage::new();
+    page.add(&create_display_group(&state, parent));
+    page.add(&create_timer_group(&state));
+    page.add(&super::templates::create_templates_group(&state));
+    page.add(&super::reminders::create_reminders_group(&state));
+    page.add(&create_rounding_group(&state, parent));
+    page.add(&create_privacy_group(&state));
+    page.add(&create_focus_group(&state));
+    page.add(&create_lock_group(&state));
+    page.add(&create_startup_group(&state));
+    page.add(&create_tray_group(&state));
+    page.add(&create_plugins_group(&state));
+    page.add(&create_maintenance_group(&state));
+
+    let window = adw::PreferencesWindow::builder()
+        .title("Preferences")
+        .default_width(400)
+        .default_height(500)
+        .modal(true)
+        .transient_for(parent)
+        .search_enabled(false)
+        .build();
+    window.add(&page);
+    window.present();
+}
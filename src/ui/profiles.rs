@@ -0,0 +1,174 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use gtk4::{gio, glib};
+use std::cell::RefCell;
+use std::process::Command;
+use std::rc::Rc;
+
+use super::{quit_app, AppState};
+use crate::db;
+use crate::profile;
+
+/// Label of the profile using the database outside the profiles directory
+const DEFAULT_PROFILE_LABEL: &str = "Default";
+
+/// Menu listing the profiles, the current one checked, and creating new ones
+fn profile_menu() -> gio::Menu {
+    let profiles = gio::Menu::new();
+    let mut names = vec![String::new()];
+    names.extend(profile::list_profiles(&db::data_dir()));
+    // A profile opened by name shows up even before its database was written
+    if let Some(current) = profile::current().filter(|current| !names.contains(current)) {
+        names.push(current);
+    }
+    for name in names {
+        let label = if name.is_empty() { DEFAULT_PROFILE_LABEL } else { &name };
+        let item = gio::MenuItem::new(Some(label), None);
+        item.set_action_and_target_value(Some("win.switch-profile"), Some(&name.to_variant()));
+        profiles.append_item(&item);
+    }
+
+    let menu = gio::Menu::new();
+    menu.append_section(None, &profiles);
+    menu.append(Some("New Profile…"), Some("win.new-profile"));
+    menu
+}
+
+/// Opens the app with another profile, `None` being the default one, and
+/// quits this one. Profiles have their own application ids, so a profile
+/// already open is brought to the front instead.
+fn open_profile(state: &Rc<RefCell<AppState>>, window: &adw::ApplicationWindow, name: Option<&str>) {
+    let spawned = std::env::current_exe().and_then(|exe| {
+        let mut command = Command::new(exe);
+        if let Some(name) = name {
+            command.args([profile::PROFILE_FLAG, name]);
+        }
+        command.spawn()
+    });
+    match spawned {
+        Ok(_) => quit_app(window),
+        Err(e) => state.borrow().show_error(&format!("Failed to open the profile: {}", e)),
+    }
+}
+
+/// Opens a dialog naming a new profile, which is opened right away and gets
+/// its database on the first start
+fn show_new_profile_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow) {
+    let dialog = adw::Window::builder()
+        .title("New Profile")
+        .modal(true)
+        .transient_for(parent)
+        .default_width(360)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(18)
+        .margin_bottom(18)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+    content.append(
+        &gtk::Label::builder()
+            .label("Each profile has its own projects, entries and settings. The app restarts with the new profile.")
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+
+    let name_entry = gtk::Entry::builder()
+        .placeholder_text("Name, e.g. work or personal")
+        .activates_default(true)
+        .build();
+    content.append(&name_entry);
+
+    let error_label = gtk::Label::builder()
+        .xalign(0.0)
+        .wrap(true)
+        .visible(false)
+        .css_classes(["error"])
+        .build();
+    content.append(&error_label);
+
+    let buttons_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .build();
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let create_button = gtk::Button::builder()
+        .label("Create")
+        .css_classes(["suggested-action"])
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&create_button);
+    content.append(&buttons_box);
+
+    dialog.set_content(Some(&content));
+    dialog.set_default_widget(Some(&create_button));
+
+    let dialog_for_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_for_cancel.close());
+
+    let window = parent.clone();
+    let name_entry_for_create = name_entry.clone();
+    create_button.connect_clicked(move |_| {
+        let existing = profile::list_profiles(&db::data_dir());
+        let name = profile::validate_name(&name_entry_for_create.text()).and_then(|name| {
+            if existing.iter().any(|other| other.eq_ignore_ascii_case(&name)) {
+                return Err(format!("A profile named \"{}\" already exists", name));
+            }
+            Ok(name)
+        });
+        match name {
+            Ok(name) => open_profile(&state, &window, Some(&name)),
+            Err(e) => {
+                error_label.set_label(&e);
+                error_label.set_visible(true);
+            }
+        }
+    });
+
+    dialog.present();
+    name_entry.grab_focus();
+}
+
+/// Creates the header bar button switching between profiles, and the window
+/// actions behind its menu. It is hidden when a database was chosen with
+/// `--database` or the environment, as profiles don't apply then.
+pub fn create_profile_button(state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) -> gtk::MenuButton {
+    let current = profile::current();
+    let button = gtk::MenuButton::builder()
+        .icon_name("avatar-default-symbolic")
+        .tooltip_text(format!("Profile: {}", current.as_deref().unwrap_or(DEFAULT_PROFILE_LABEL)))
+        .visible(db::chosen_db_path().is_none())
+        .build();
+    // Rebuilt on every opening, to list profiles created meanwhile
+    button.set_create_popup_func(|button| button.set_menu_model(Some(&profile_menu())));
+
+    let switch_action = gio::SimpleAction::new_stateful(
+        "switch-profile",
+        Some(glib::VariantTy::STRING),
+        &current.clone().unwrap_or_default().to_variant(),
+    );
+    let state_for_switch = state.clone();
+    let window_for_switch = window.clone();
+    switch_action.connect_activate(move |_, parameter| {
+        let name = parameter.and_then(|parameter| parameter.str()).unwrap_or_default();
+        if name == current.as_deref().unwrap_or_default() {
+            return;
+        }
+        open_profile(&state_for_switch, &window_for_switch, (!name.is_empty()).then_some(name));
+    });
+    window.add_action(&switch_action);
+
+    let new_action = gio::SimpleAction::new("new-profile", None);
+    let window_for_new = window.clone();
+    new_action.connect_activate(move |_, _| show_new_profile_dialog(state.clone(), &window_for_new));
+    window.add_action(&new_action);
+
+    button
+}
@@ -0,0 +1,209 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_projects_list, AppState, PROJECT_COLORS};
+use crate::db;
+use crate::project_import::{self, PlannedProject, ProjectChange};
+
+/// Describes a planned project's code, rate and budget, e.g.
+/// "ACME-1 · 85.50/h · 40h/month"
+fn describe(planned: &PlannedProject) -> String {
+    let mut parts = vec![planned.code.clone()];
+    parts.extend(planned.hourly_rate_cents.map(project_import::format_rate));
+    parts.extend(planned.monthly_budget_minutes.map(project_import::format_budget));
+    parts.join(" · ")
+}
+
+/// Creates a preview row of a change, badged as new or updated
+fn create_change_row(change: &ProjectChange) -> adw::ActionRow {
+    let row = adw::ActionRow::builder()
+        .title(&change.planned().name)
+        .subtitle(describe(change.planned()))
+        .build();
+    let badge = match change {
+        ProjectChange::Create(_) => "New",
+        ProjectChange::Update(_, _) => "Update",
+    };
+    row.add_suffix(
+        &gtk::Label::builder()
+            .label(badge)
+            .css_classes(["caption", "dim-label"])
+            .build(),
+    );
+    row
+}
+
+/// Asks for a CSV of projects exported from a project management tool,
+/// then previews which projects it creates and which it updates, matched
+/// by code. `projects_list_box` is the Manage Projects list, refreshed
+/// after importing.
+pub fn import_projects(state: Rc<RefCell<AppState>>, parent: &adw::Window, projects_list_box: &gtk::ListBox) {
+    let chooser = gtk::FileChooserNative::new(
+        Some("Import Projects"),
+        Some(parent),
+        gtk::FileChooserAction::Open,
+        Some("Open"),
+        Some("Cancel"),
+    );
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some("CSV"));
+    filter.add_pattern("*.csv");
+    filter.add_pattern("*.tsv");
+    chooser.add_filter(&filter);
+
+    // The native dialog is only kept alive by its owner, so hold it until it answers
+    let pending = Rc::new(RefCell::new(Some(chooser.clone())));
+    let parent = parent.clone();
+    let projects_list_box = projects_list_box.clone();
+    chooser.connect_response(move |chooser, response| {
+        pending.borrow_mut().take();
+        if response != gtk::ResponseType::Accept {
+            return;
+        }
+        let Some(path) = chooser.file().and_then(|file| file.path()) else {
+            return;
+        };
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            .and_then(|text| project_import::parse_projects(&text));
+        match parsed {
+            Ok(rows) => show_preview_dialog(state.clone(), &parent, &projects_list_box, rows),
+            Err(e) => state.borrow().show_error(&e),
+        }
+    });
+    chooser.show();
+}
+
+/// Shows what importing the parsed rows does, importing the valid ones on
+/// confirmation in one transaction
+fn show_preview_dialog(
+    state: Rc<RefCell<AppState>>,
+    parent: &adw::Window,
+    projects_list_box: &gtk::ListBox,
+    rows: Vec<Result<PlannedProject, String>>,
+) {
+    let existing = db::get_coded_projects(&state.borrow().db_conn).unwrap_or_else(|e| {
+        state.borrow().show_error(&format!("Failed to load projects: {}", e));
+        Vec::new()
+    });
+    let (valid, errors): (Vec<_>, Vec<_>) = rows.into_iter().partition(|row| row.is_ok());
+    let changes = project_import::plan_changes(valid.into_iter().filter_map(|row| row.ok()).collect(), &existing);
+    let created = changes
+        .iter()
+        .filter(|change| matches!(change, ProjectChange::Create(_)))
+        .count();
+    let updated = changes.len() - created;
+
+    let dialog = adw::Window::builder()
+        .title("Import Projects")
+        .default_width(420)
+        .default_height(520)
+        .modal(true)
+        .transient_for(parent)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(0)
+        .build();
+    let header_bar = adw::HeaderBar::builder()
+        .show_end_title_buttons(true)
+        .title_widget(&adw::WindowTitle::new(
+            "Import Projects",
+            &format!("{} new, {} updated", created, updated),
+        ))
+        .build();
+    content.append(&header_bar);
+
+    let body = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+
+    if !errors.is_empty() {
+        let errors_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        for error in errors.iter().filter_map(|row| row.as_ref().err()) {
+            errors_list.append(
+                &gtk::Label::builder()
+                    .label(error)
+                    .halign(gtk::Align::Start)
+                    .wrap(true)
+                    .margin_top(6)
+                    .margin_bottom(6)
+                    .margin_start(12)
+                    .margin_end(12)
+                    .css_classes(["error"])
+                    .build(),
+            );
+        }
+        body.append(
+            &gtk::Label::builder()
+                .label("Skipped Rows")
+                .halign(gtk::Align::Start)
+                .css_classes(["heading"])
+                .build(),
+        );
+        body.append(&errors_list);
+    }
+
+    let changes_list = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    for change in &changes {
+        changes_list.append(&create_change_row(change));
+    }
+    if !changes.is_empty() {
+        body.append(&changes_list);
+    }
+
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .vexpand(true)
+        .child(&body)
+        .build();
+    content.append(&scrolled_window);
+
+    let import_button = gtk::Button::builder()
+        .label(format!("Import {} Projects", changes.len()))
+        .sensitive(!changes.is_empty())
+        .css_classes(["suggested-action"])
+        .halign(gtk::Align::End)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_bottom(12)
+        .build();
+    content.append(&import_button);
+
+    let dialog_for_import = dialog.clone();
+    let projects_list_box = projects_list_box.clone();
+    import_button.connect_clicked(move |_| {
+        let result = db::apply_project_changes(&state.borrow().db_conn, &changes, PROJECT_COLORS);
+        match result {
+            Ok(()) => {
+                state
+                    .borrow()
+                    .show_info(&format!("Created {} and updated {} projects", created, updated));
+                refresh_projects_list(&state, &projects_list_box);
+                state.borrow_mut().refresh_projects();
+                dialog_for_import.close();
+            }
+            Err(e) => state
+                .borrow()
+                .show_error(&format!("Failed to import projects: {}", e)),
+        }
+    });
+
+    dialog.set_content(Some(&content));
+    dialog.present();
+}
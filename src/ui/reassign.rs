@@ -0,0 +1,209 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+use crate::db;
+
+/// Describes how many entries a project has, e.g. "3 entries of Work"
+fn entries_text(count: usize, project: &db::Project) -> String {
+    match count {
+        1 => format!("1 entry of \"{}\"", project.name),
+        _ => format!("{} entries of \"{}\"", count, project.name),
+    }
+}
+
+/// Points the running entry at `target_id` when it was in `source_id`, as
+/// the entries moved in the database
+fn follow_running_entry(state: &Rc<RefCell<AppState>>, source_id: i64, target_id: Option<i64>) {
+    if let Some(ref mut running) = state.borrow_mut().running_entry {
+        if running.project_id == Some(source_id) {
+            running.project_id = target_id;
+        }
+    }
+}
+
+/// Refreshes the dropdown and the window after entries changed project
+fn refresh_after_move(state: &Rc<RefCell<AppState>>) {
+    state.borrow_mut().refresh_projects();
+    let window = state.borrow().window.clone();
+    if let Some(ref window) = window {
+        refresh_view(state.clone(), window);
+    }
+}
+
+/// Opens a dialog moving every entry of `project` to another project or to
+/// no project, in one transaction. With `delete` the project is deleted
+/// afterwards, otherwise the move can be undone from the toast that follows.
+/// `on_done` runs once the change was made, e.g. to refresh a project list.
+pub fn show_move_entries_dialog(
+    state: Rc<RefCell<AppState>>,
+    parent: &impl IsA<gtk::Window>,
+    project: &db::Project,
+    delete: bool,
+    on_done: impl Fn() + 'static,
+) {
+    let count = db::count_project_entries(&state.borrow().db_conn, project.id).unwrap_or_else(|e| {
+        state.borrow().show_failure("count entries", &e);
+        0
+    });
+
+    let dialog = adw::Window::builder()
+        .title(if delete { "Delete Project" } else { "Move Entries" })
+        .modal(true)
+        .transient_for(parent)
+        .default_width(380)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(18)
+        .margin_bottom(18)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+
+    let explanation = if delete {
+        format!(
+            "\"{}\" will be deleted. Choose where its {} go.",
+            project.name,
+            if count == 1 { "entry".to_string() } else { format!("{} entries", count) }
+        )
+    } else {
+        format!("{} will move to the chosen project.", entries_text(count, project))
+    };
+    content.append(
+        &gtk::Label::builder()
+            .label(&explanation)
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+
+    // Deleting keeps the entries without a project unless told otherwise;
+    // moving offers the other projects first
+    let others: Vec<db::Project> = state
+        .borrow()
+        .projects
+        .iter()
+        .filter(|other| other.id != project.id)
+        .cloned()
+        .collect();
+    let mut targets: Vec<Option<&db::Project>> = others.iter().map(Some).collect();
+    if delete {
+        targets.insert(0, None);
+    } else {
+        targets.push(None);
+    }
+    let labels: Vec<String> = targets
+        .iter()
+        .map(|target| match target {
+            Some(target) => format!("Move to {}", target.name),
+            None => "Keep without a project".to_string(),
+        })
+        .collect();
+    let target_ids: Vec<Option<i64>> = targets.iter().map(|target| target.map(|t| t.id)).collect();
+    let target_names: Vec<String> = targets
+        .iter()
+        .map(|target| target.map_or("No Project".to_string(), |t| t.name.clone()))
+        .collect();
+    let target_dropdown = gtk::DropDown::from_strings(&labels.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+    target_dropdown.set_visible(count > 0);
+    content.append(&target_dropdown);
+
+    let buttons_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .build();
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let action_button = gtk::Button::builder()
+        .label(if delete { "Delete" } else { "Move" })
+        .sensitive(delete || count > 0)
+        .css_classes([if delete { "destructive-action" } else { "suggested-action" }])
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&action_button);
+    content.append(&buttons_box);
+
+    dialog.set_content(Some(&content));
+
+    let dialog_for_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_for_cancel.close());
+
+    let dialog_for_action = dialog.clone();
+    let project = project.clone();
+    action_button.connect_clicked(move |_| {
+        let selected = target_dropdown.selected() as usize;
+        let target_id = target_ids.get(selected).copied().flatten();
+        let target_name = target_names.get(selected).cloned().unwrap_or_default();
+
+        // Either way every entry of the project changes, so none may be locked
+        let unlocked = {
+            let state_borrow = state.borrow();
+            db::get_first_project_entry(&state_borrow.db_conn, project.id)
+                .and_then(|first| first.map_or(Ok(()), |entry| state_borrow.ensure_unlocked(entry.start_time)))
+        };
+        if let Err(e) = unlocked {
+            state.borrow().show_failure("move entries", &e);
+            return;
+        }
+
+        if delete {
+            let result = match target_id {
+                Some(target_id) => db::merge_projects(&state.borrow().db_conn, project.id, target_id).map(|_| ()),
+                None => db::delete_project(&state.borrow().db_conn, project.id),
+            };
+            if let Err(e) = result {
+                state.borrow().show_failure("delete project", &e);
+                return;
+            }
+            dialog_for_action.close();
+            follow_running_entry(&state, project.id, target_id);
+            if target_id.is_some() && count > 0 {
+                state
+                    .borrow()
+                    .show_info(&format!("Moved {} to \"{}\"", entries_text(count, &project), target_name));
+            }
+            on_done();
+            refresh_after_move(&state);
+            return;
+        }
+
+        let moved = match db::move_project_entries(&state.borrow().db_conn, project.id, target_id) {
+            Ok(moved) => moved,
+            Err(e) => {
+                state.borrow().show_failure("move entries", &e);
+                return;
+            }
+        };
+        dialog_for_action.close();
+        follow_running_entry(&state, project.id, target_id);
+        on_done();
+        refresh_after_move(&state);
+
+        // Undoing moves exactly these entries back, not ones added since
+        let state_for_undo = state.clone();
+        let project_id = project.id;
+        let message = format!("Moved {} to \"{}\"", entries_text(moved.len(), &project), target_name);
+        state.borrow().show_undo(&message, move || {
+            let result = db::set_entries_project(&state_for_undo.borrow().db_conn, &moved, Some(project_id));
+            if let Err(e) = result {
+                state_for_undo.borrow().show_failure("undo", &e);
+                return;
+            }
+            if let Some(ref mut running) = state_for_undo.borrow_mut().running_entry {
+                if moved.contains(&running.id) {
+                    running.project_id = Some(project_id);
+                }
+            }
+            refresh_after_move(&state_for_undo);
+        });
+    });
+
+    dialog.present();
+}
@@ -0,0 +1,384 @@
+use adw::prelude::*;
+use chrono::Local;
+use gtk4 as gtk;
+use gtk4::{gio, glib};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+use crate::db;
+use crate::import;
+use crate::reminder::{self, Repeat};
+
+/// Application action starting the timer from the reminder with the given
+/// id, offered by its notification
+const START_REMINDER_ACTION: &str = "start-reminder";
+
+/// How often due reminders are looked for
+const CHECK_INTERVAL_SECS: u32 = 30;
+
+/// Loads the reminders, reporting a failure as an empty list
+fn load_reminders(state: &Rc<RefCell<AppState>>) -> Vec<db::Reminder> {
+    db::get_reminders(&state.borrow().db_conn).unwrap_or_else(|e| {
+        state.borrow().show_failure("load reminders", &e);
+        Vec::new()
+    })
+}
+
+/// The template a reminder starts the timer from, if it has one
+fn reminder_template(state: &AppState, reminder: &db::Reminder) -> Option<db::EntryTemplate> {
+    let template_id = reminder.template_id?;
+    db::get_templates(&state.db_conn)
+        .ok()?
+        .into_iter()
+        .find(|template| template.id == template_id)
+}
+
+/// Starts the timer from a reminder: with its template when it has one,
+/// otherwise with its name as the description. A running timer is stopped.
+fn start_from_reminder(state: &Rc<RefCell<AppState>>, window: &adw::ApplicationWindow, id: i64) {
+    let Some(reminder) = load_reminders(state).into_iter().find(|reminder| reminder.id == id) else {
+        return;
+    };
+    let template = reminder_template(&state.borrow(), &reminder).unwrap_or(db::EntryTemplate {
+        id: 0,
+        name: reminder.name.clone(),
+        description: reminder.name.clone(),
+        project_id: None,
+        private: false,
+    });
+
+    window.present();
+    let started = {
+        let mut state = state.borrow_mut();
+        if state.running_entry.is_some() {
+            state.stop_timer();
+        }
+        state.apply_template(&template);
+        state.start_timer()
+    };
+    if started {
+        refresh_view(state.clone(), window);
+    }
+}
+
+/// Shows the notification of a due reminder, with a button starting the timer
+fn notify(app: &adw::Application, state: &AppState, reminder: &db::Reminder) {
+    let notification = gio::Notification::new(&reminder.name);
+    let body = match reminder_template(state, reminder) {
+        Some(template) => format!("Start timer with template \"{}\"", template.name),
+        None => reminder::schedule_label(reminder.repeat, reminder.time),
+    };
+    notification.set_body(Some(&body));
+    notification.add_button_with_target_value(
+        "Start Timer",
+        &format!("app.{}", START_REMINDER_ACTION),
+        Some(&reminder.id.to_variant()),
+    );
+    app.send_notification(Some(&format!("reminder-{}", reminder.id)), &notification);
+}
+
+/// Registers the action of reminder notifications and looks for due
+/// reminders every half minute while the app runs. Reminders due while the
+/// app was closed are not shown.
+pub fn start_reminders(app: &adw::Application, state: Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) {
+    let start_action = gio::SimpleAction::new(START_REMINDER_ACTION, Some(glib::VariantTy::INT64));
+    let state_for_start = state.clone();
+    let window_for_start = window.clone();
+    start_action.connect_activate(move |_, parameter| {
+        if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+            start_from_reminder(&state_for_start, &window_for_start, id);
+        }
+    });
+    app.add_action(&start_action);
+
+    let last_check = Cell::new(Local::now().naive_local());
+    let app = app.clone();
+    glib::timeout_add_seconds_local(CHECK_INTERVAL_SECS, move || {
+        let now = Local::now().naive_local();
+        let reminders = load_reminders(&state);
+        for reminder in reminder::due_reminders(&reminders, last_check.get(), now) {
+            notify(&app, &state.borrow(), reminder);
+        }
+        last_check.set(now);
+        glib::ControlFlow::Continue
+    });
+}
+
+/// Describes a reminder's schedule and template for its row
+fn reminder_summary(state: &AppState, reminder: &db::Reminder) -> String {
+    let schedule = reminder::schedule_label(reminder.repeat, reminder.time);
+    match reminder_template(state, reminder) {
+        Some(template) => format!("{} · {}", schedule, template.name),
+        None => schedule,
+    }
+}
+
+/// Opens a dialog creating a reminder, or editing `reminder` when given.
+/// `on_saved` runs once it was saved.
+fn show_reminder_dialog(
+    state: Rc<RefCell<AppState>>,
+    parent: &impl IsA<gtk::Window>,
+    reminder: Option<db::Reminder>,
+    on_saved: impl Fn() + 'static,
+) {
+    let dialog = adw::Window::builder()
+        .title(if reminder.is_some() { "Edit Reminder" } else { "New Reminder" })
+        .modal(true)
+        .transient_for(parent)
+        .default_width(360)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(18)
+        .margin_bottom(18)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+
+    let name_entry = gtk::Entry::builder().placeholder_text("Name, e.g. Standup").build();
+    content.append(&name_entry);
+
+    let schedule_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .homogeneous(true)
+        .build();
+    let repeat_labels: Vec<&str> = Repeat::ALL.iter().map(|r| r.label()).collect();
+    let repeat_dropdown = gtk::DropDown::from_strings(&repeat_labels);
+    repeat_dropdown.set_tooltip_text(Some("Repeat"));
+    let time_entry = gtk::Entry::builder().placeholder_text("At, e.g. 9:30").build();
+    schedule_box.append(&repeat_dropdown);
+    schedule_box.append(&time_entry);
+    content.append(&schedule_box);
+
+    let templates = db::get_templates(&state.borrow().db_conn).unwrap_or_default();
+    let template_ids: Vec<Option<i64>> = std::iter::once(None)
+        .chain(templates.iter().map(|template| Some(template.id)))
+        .collect();
+    let template_labels: Vec<String> = std::iter::once("No template: the name is the description".to_string())
+        .chain(templates.iter().map(|template| template.name.clone()))
+        .collect();
+    let template_dropdown =
+        gtk::DropDown::from_strings(&template_labels.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+    template_dropdown.set_tooltip_text(Some("Template the timer is started from"));
+    content.append(&template_dropdown);
+
+    match reminder {
+        Some(ref reminder) => {
+            name_entry.set_text(&reminder.name);
+            time_entry.set_text(&reminder.time.format("%H:%M").to_string());
+            let repeat = Repeat::ALL.iter().position(|&r| r == reminder.repeat).unwrap_or(0);
+            repeat_dropdown.set_selected(repeat as u32);
+            let template = template_ids.iter().position(|&id| id == reminder.template_id).unwrap_or(0);
+            template_dropdown.set_selected(template as u32);
+        }
+        None => {
+            let weekdays = Repeat::ALL.iter().position(|&r| r == Repeat::Weekdays).unwrap_or(0);
+            repeat_dropdown.set_selected(weekdays as u32);
+        }
+    }
+
+    let error_label = gtk::Label::builder()
+        .xalign(0.0)
+        .wrap(true)
+        .visible(false)
+        .css_classes(["error"])
+        .build();
+    content.append(&error_label);
+
+    let buttons_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .build();
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let save_button = gtk::Button::builder()
+        .label("Save")
+        .css_classes(["suggested-action"])
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&save_button);
+    content.append(&buttons_box);
+
+    dialog.set_content(Some(&content));
+
+    let dialog_for_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_for_cancel.close());
+
+    let dialog_for_save = dialog.clone();
+    save_button.connect_clicked(move |_| {
+        let show_error = |message: &str| {
+            error_label.set_label(message);
+            error_label.set_visible(true);
+        };
+        let name = name_entry.text().trim().to_string();
+        if name.is_empty() {
+            show_error("Reminder name cannot be empty");
+            return;
+        }
+        let Some(time) = import::parse_time(&time_entry.text()) else {
+            show_error(&format!("Invalid time \"{}\"", time_entry.text().trim()));
+            return;
+        };
+        let repeat = Repeat::ALL
+            .get(repeat_dropdown.selected() as usize)
+            .copied()
+            .unwrap_or_default();
+        let template_id = template_ids.get(template_dropdown.selected() as usize).copied().flatten();
+
+        let conn = &state.borrow().db_conn;
+        let result = match reminder {
+            Some(ref reminder) => db::update_reminder(
+                conn,
+                &db::Reminder {
+                    name,
+                    repeat,
+                    time,
+                    template_id,
+                    ..reminder.clone()
+                },
+            ),
+            None => db::create_reminder(conn, &name, repeat, time, template_id).map(|_| ()),
+        };
+        match result {
+            Ok(()) => {
+                dialog_for_save.close();
+                on_saved();
+            }
+            Err(e) => show_error(&format!("Failed to save reminder: {}", e)),
+        }
+    });
+
+    dialog.present();
+}
+
+/// Fills the list with a row per reminder, each with a switch pausing it
+/// and buttons editing and deleting it
+fn fill_reminders_list(state: &Rc<RefCell<AppState>>, list_box: &gtk::ListBox) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+
+    for reminder in load_reminders(state) {
+        let summary = reminder_summary(&state.borrow(), &reminder);
+        let row = adw::ActionRow::builder()
+            .title(&reminder.name)
+            .subtitle(&summary)
+            .build();
+
+        let enabled_switch = gtk::Switch::builder()
+            .active(reminder.enabled)
+            .valign(gtk::Align::Center)
+            .tooltip_text("Enabled")
+            .build();
+        let edit_button = gtk::Button::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text("Edit reminder")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let delete_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text("Delete reminder")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        row.add_suffix(&edit_button);
+        row.add_suffix(&delete_button);
+        row.add_suffix(&enabled_switch);
+
+        let state_for_enabled = state.clone();
+        let reminder_for_enabled = reminder.clone();
+        enabled_switch.connect_active_notify(move |switch| {
+            let updated = db::Reminder {
+                enabled: switch.is_active(),
+                ..reminder_for_enabled.clone()
+            };
+            if let Err(e) = db::update_reminder(&state_for_enabled.borrow().db_conn, &updated) {
+                state_for_enabled.borrow().show_failure("update reminder", &e);
+            }
+        });
+
+        let state_for_edit = state.clone();
+        let list_box_for_edit = list_box.clone();
+        let reminder_for_edit = reminder.clone();
+        edit_button.connect_clicked(move |button| {
+            let Some(window) = button.root().and_downcast::<gtk::Window>() else {
+                return;
+            };
+            // The switch may have changed since the row was filled
+            let current = load_reminders(&state_for_edit)
+                .into_iter()
+                .find(|r| r.id == reminder_for_edit.id)
+                .unwrap_or_else(|| reminder_for_edit.clone());
+            let state = state_for_edit.clone();
+            let list_box = list_box_for_edit.clone();
+            show_reminder_dialog(state_for_edit.clone(), &window, Some(current), move || {
+                fill_reminders_list(&state, &list_box)
+            });
+        });
+
+        let state_for_delete = state.clone();
+        let list_box_for_delete = list_box.clone();
+        delete_button.connect_clicked(move |_| {
+            if let Err(e) = db::delete_reminder(&state_for_delete.borrow().db_conn, reminder.id) {
+                state_for_delete.borrow().show_failure("delete reminder", &e);
+                return;
+            }
+            fill_reminders_list(&state_for_delete, &list_box_for_delete);
+        });
+
+        list_box.append(&row);
+    }
+}
+
+/// Creates the "Reminders" preferences group, listing the reminders with a
+/// button adding one
+pub fn create_reminders_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Reminders")
+        .description("Notifications on a schedule, with a button starting the timer")
+        .build();
+
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    list_box.set_placeholder(Some(
+        &gtk::Label::builder()
+            .label("No reminders")
+            .css_classes(["dim-label"])
+            .margin_top(12)
+            .margin_bottom(12)
+            .build(),
+    ));
+    fill_reminders_list(state, &list_box);
+    group.add(&list_box);
+
+    let add_button = gtk::Button::builder()
+        .label("Add Reminder…")
+        .halign(gtk::Align::Start)
+        .margin_top(6)
+        .css_classes(["flat"])
+        .build();
+    let state_for_add = state.clone();
+    let list_box_for_add = list_box.clone();
+    add_button.connect_clicked(move |button| {
+        let Some(window) = button.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+        let state = state_for_add.clone();
+        let list_box = list_box_for_add.clone();
+        show_reminder_dialog(state_for_add.clone(), &window, None, move || {
+            fill_reminders_list(&state, &list_box)
+        });
+    });
+    group.add(&add_button);
+
+    group
+}
+
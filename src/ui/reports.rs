@@ -0,0 +1,1296 @@
+use adw::prelude::*;
+use chrono::{Datelike, Days, Local, NaiveDate, NaiveTime, Utc};
+use gtk4 as gtk;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use super::date_picker::{calendar_date, to_glib_date};
+use super::donut_chart::DonutChart;
+use super::year_review::{self, create_value_row};
+use super::{compat, create_legend_button, export, load_entry_projects, toggle_chart_project, AppState};
+use crate::db;
+use crate::export::{presence_csv, presence_file_name};
+use crate::month;
+use crate::privacy;
+use crate::report::{self, format_share, EstimateRow, Grouping, ReportRange, ReportRow};
+use crate::smart_syntax;
+use crate::stats::{self, DayPresence};
+
+/// Color of chart bars for rows without a project color
+const DEFAULT_BAR_COLOR: &str = "#3584e4";
+
+/// Column headings of the heatmap calendar, Monday first
+const WEEKDAY_INITIALS: [&str; 7] = ["M", "T", "W", "T", "F", "S", "S"];
+
+/// Row titles of the weekday averages, Monday first
+const WEEKDAY_NAMES: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// How far back the current streak is looked for, in days
+const STREAK_LOOKBACK_DAYS: u64 = 366;
+
+/// Computes a preset range from today's date
+type RangePreset = fn(NaiveDate) -> ReportRange;
+
+/// Widgets of the reports window that change with the selected range and grouping
+struct ReportsView {
+    range_button: gtk::MenuButton,
+    summary_label: gtk::Label,
+    table: gtk::Grid,
+    chart: gtk::Box,
+    /// Interruptions per day, with the trend against the previous period
+    focus: gtk::Box,
+    /// Heatmap calendar of the daily totals, a row per week
+    heatmap: gtk::Grid,
+    /// Averages per weekday, streak, typical start and stop, longest session
+    stats: gtk::Box,
+    /// Arrival, departure and presence against tracked time per day
+    presence: gtk::Box,
+    /// The shown presence as CSV, for the save button
+    presence_csv: RefCell<String>,
+    /// Estimated against tracked time per task, with the estimates editable
+    estimates: gtk::Box,
+    /// The shown tasks, kept to show them again when an estimate changes
+    estimate_rows: RefCell<Vec<EstimateRow>>,
+    /// The shown projects by id, kept likewise
+    project_estimate_rows: RefCell<Vec<(i64, EstimateRow)>>,
+    /// The shown report as tab separated text, for the copy button
+    text: RefCell<String>,
+    /// The shown rows and their grouping, kept to redraw the chart when a
+    /// project is hidden or shown
+    rows: RefCell<Vec<ReportRow>>,
+    grouping: Cell<Grouping>,
+    /// Read-only connection the reports are loaded from, apart from the one
+    /// starting and stopping the timer; `None` for an in-memory database
+    snapshot: Option<rusqlite::Connection>,
+}
+
+/// Creates a table cell label, selectable so numbers can be copied
+fn create_cell(text: &str, css_classes: &[&str], start: bool) -> gtk::Label {
+    gtk::Label::builder()
+        .label(text)
+        .selectable(true)
+        .halign(if start { gtk::Align::Start } else { gtk::Align::End })
+        .hexpand(start)
+        .ellipsize(if start {
+            gtk::pango::EllipsizeMode::End
+        } else {
+            gtk::pango::EllipsizeMode::None
+        })
+        .css_classes(css_classes.to_vec())
+        .build()
+}
+
+impl ReportsView {
+    /// Fills the table with the given rows and keeps them for the chart
+    fn show_rows(
+        &self,
+        range: ReportRange,
+        grouping: Grouping,
+        rows: Vec<ReportRow>,
+        total: i64,
+        state: &AppState,
+    ) {
+        let format = state.settings.duration_format;
+        let entry_count: usize = rows.iter().map(|row| row.entries).sum();
+        *self.text.borrow_mut() = report::rows_as_text(&range.label(), grouping.label(), &rows, total, format);
+
+        self.range_button.set_label(&range.label());
+        self.summary_label.set_label(&format!(
+            "Total {} · {} {}",
+            format.format(total),
+            entry_count,
+            if entry_count == 1 { "entry" } else { "entries" }
+        ));
+
+        while let Some(child) = self.table.first_child() {
+            self.table.remove(&child);
+        }
+        let headers = ["", "Entries", "Share", "Duration"];
+        for (column, header) in headers.iter().enumerate() {
+            let cell = create_cell(header, &["heading"], column == 0);
+            self.table.attach(&cell, column as i32, 0, 1, 1);
+        }
+        for (index, row) in rows.iter().enumerate() {
+            let line = index as i32 + 1;
+            self.table
+                .attach(&create_cell(&row.label, &[], true), 0, line, 1, 1);
+            self.table.attach(
+                &create_cell(&row.entries.to_string(), &["dim-label"], false),
+                1,
+                line,
+                1,
+                1,
+            );
+            self.table.attach(
+                &create_cell(&format_share(row.seconds, total), &["dim-label"], false),
+                2,
+                line,
+                1,
+                1,
+            );
+            self.table.attach(
+                &create_cell(&format.format(row.seconds), &["monospace"], false),
+                3,
+                line,
+                1,
+                1,
+            );
+        }
+
+        *self.rows.borrow_mut() = rows;
+        self.grouping.set(grouping);
+    }
+
+    /// Fills the focus page: how often work was interrupted in the range, each
+    /// day's count as a bar, and the change from the previous period
+    fn show_focus(&self, range: ReportRange, days: &[(NaiveDate, usize)], previous_total: usize) {
+        while let Some(child) = self.focus.first_child() {
+            self.focus.remove(&child);
+        }
+
+        let total: usize = days.iter().map(|(_, count)| count).sum();
+        let average = if days.is_empty() {
+            0.0
+        } else {
+            total as f64 / days.len() as f64
+        };
+        let previous_label = range.shifted(false).label();
+        let trend = match total.cmp(&previous_total) {
+            std::cmp::Ordering::Less => format!("{} fewer than {}", previous_total - total, previous_label),
+            std::cmp::Ordering::Greater => format!("{} more than {}", total - previous_total, previous_label),
+            std::cmp::Ordering::Equal => format!("Same as {}", previous_label),
+        };
+        self.focus.append(
+            &gtk::Label::builder()
+                .label(format!(
+                    "{} {} · {:.1} per day worked",
+                    total,
+                    if total == 1 { "interruption" } else { "interruptions" },
+                    average
+                ))
+                .halign(gtk::Align::Start)
+                .selectable(true)
+                .css_classes(["heading"])
+                .build(),
+        );
+        self.focus.append(
+            &gtk::Label::builder()
+                .label(trend)
+                .halign(gtk::Align::Start)
+                .css_classes(["dim-label"])
+                .build(),
+        );
+        self.focus.append(
+            &gtk::Label::builder()
+                .label("Counted when the timer was stopped before the next entry or the task changed")
+                .halign(gtk::Align::Start)
+                .wrap(true)
+                .xalign(0.0)
+                .css_classes(["caption", "dim-label"])
+                .build(),
+        );
+
+        let max_count = days.iter().map(|(_, count)| *count).max().unwrap_or(0).max(1) as f64;
+        for (date, count) in days {
+            let line = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(8)
+                .build();
+            line.append(
+                &gtk::Label::builder()
+                    .label(date.format("%a, %b %-d").to_string())
+                    .width_chars(18)
+                    .xalign(0.0)
+                    .build(),
+            );
+
+            let bar_area = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .hexpand(true)
+                .build();
+            let bar = gtk::Box::builder()
+                .width_request((*count as f64 / max_count * 400.0).max(4.0) as i32)
+                .height_request(16)
+                .valign(gtk::Align::Center)
+                .css_classes(["project-bar"])
+                .visible(*count > 0)
+                .build();
+            let css_provider = gtk::CssProvider::new();
+            css_provider.load_from_data(&format!("box {{ background-color: {}; }}", DEFAULT_BAR_COLOR));
+            bar.style_context()
+                .add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+            bar_area.append(&bar);
+            line.append(&bar_area);
+
+            line.append(
+                &gtk::Label::builder()
+                    .label(count.to_string())
+                    .selectable(true)
+                    .css_classes(["monospace", "dim-label"])
+                    .build(),
+            );
+            self.focus.append(&line);
+        }
+    }
+}
+
+impl ReportsView {
+    /// Fills the statistics page: the current streak, typical start and stop
+    /// times and longest focus session of the range, then the average per
+    /// weekday
+    fn show_stats(
+        &self,
+        range: ReportRange,
+        entries: &[db::TimeEntry],
+        totals: &HashMap<NaiveDate, i64>,
+        streak: u32,
+        state: &AppState,
+    ) {
+        while let Some(child) = self.stats.first_child() {
+            self.stats.remove(&child);
+        }
+        let format = state.settings.duration_format;
+        let time = |time: Option<NaiveTime>| {
+            time.map(|t| t.format("%H:%M").to_string())
+                .unwrap_or_else(|| "–".to_string())
+        };
+        let (start, stop) = stats::median_day_bounds(entries, &Local);
+        let longest = stats::longest_session(entries, Utc::now())
+            .map(|(start, seconds)| {
+                format!("{} · {}", format.format(seconds), start.with_timezone(&Local).format("%a, %b %-d"))
+            })
+            .unwrap_or_else(|| "–".to_string());
+
+        let habits = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        habits.append(&create_value_row(
+            "Current streak",
+            &if streak == 1 { "1 day".to_string() } else { format!("{} days", streak) },
+        ));
+        habits.append(&create_value_row("Typical start", &time(start)));
+        habits.append(&create_value_row("Typical stop", &time(stop)));
+        habits.append(&create_value_row("Longest focus session", &longest));
+        self.stats.append(&habits);
+
+        self.stats.append(
+            &gtk::Label::builder()
+                .label("Average per weekday")
+                .halign(gtk::Align::Start)
+                .margin_top(6)
+                .css_classes(["heading"])
+                .build(),
+        );
+        let weekdays = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        for (name, seconds) in WEEKDAY_NAMES.iter().zip(stats::weekday_averages(totals, range)) {
+            weekdays.append(&create_value_row(name, &format.format(seconds)));
+        }
+        self.stats.append(&weekdays);
+        self.stats.append(
+            &gtk::Label::builder()
+                .label("Typical times are medians of each day's first start and last stop. Weekday averages include days off.")
+                .halign(gtk::Align::Start)
+                .wrap(true)
+                .xalign(0.0)
+                .css_classes(["caption", "dim-label"])
+                .build(),
+        );
+    }
+
+    /// Fills the presence page: the range's presence against tracked time,
+    /// then per day when work began and ended and the time in between
+    fn show_presence(&self, days: &[DayPresence], state: &AppState) {
+        while let Some(child) = self.presence.first_child() {
+            self.presence.remove(&child);
+        }
+        *self.presence_csv.borrow_mut() = presence_csv(days, &Local);
+        let format = state.settings.duration_format;
+
+        let presence_total: i64 = days.iter().map(|day| day.presence_seconds).sum();
+        let tracked_total: i64 = days.iter().map(|day| day.tracked_seconds).sum();
+        self.presence.append(
+            &gtk::Label::builder()
+                .label(format!(
+                    "Present {} · tracked {}, {}",
+                    format.format(presence_total),
+                    format.format(tracked_total),
+                    format_share(tracked_total, presence_total)
+                ))
+                .halign(gtk::Align::Start)
+                .selectable(true)
+                .css_classes(["heading"])
+                .build(),
+        );
+
+        let table = gtk::Grid::builder()
+            .row_spacing(6)
+            .column_spacing(18)
+            .margin_top(6)
+            .build();
+        let headers = ["Day", "Arrived", "Left", "Presence", "Tracked"];
+        for (column, header) in headers.iter().enumerate() {
+            table.attach(&create_cell(header, &["heading"], column == 0), column as i32, 0, 1, 1);
+        }
+        for (index, day) in days.iter().enumerate() {
+            let line = index as i32 + 1;
+            let left = day
+                .left
+                .map(|left| left.with_timezone(&Local).format("%H:%M").to_string())
+                .unwrap_or_else(|| "–".to_string());
+            let cells = [
+                create_cell(&day.date.format("%a, %b %-d").to_string(), &[], true),
+                create_cell(&day.arrived.with_timezone(&Local).format("%H:%M").to_string(), &["monospace"], false),
+                create_cell(&left, &["monospace"], false),
+                create_cell(&format.format(day.presence_seconds), &["monospace"], false),
+                create_cell(&format.format(day.tracked_seconds), &["monospace", "dim-label"], false),
+            ];
+            for (column, cell) in cells.iter().enumerate() {
+                table.attach(cell, column as i32, line, 1, 1);
+            }
+        }
+        self.presence.append(&table);
+        self.presence.append(
+            &gtk::Label::builder()
+                .label("Arrived is the start of a day's first entry and left the end of its last. Presence includes the breaks in between.")
+                .halign(gtk::Align::Start)
+                .wrap(true)
+                .xalign(0.0)
+                .margin_top(6)
+                .css_classes(["caption", "dim-label"])
+                .build(),
+        );
+    }
+
+    /// Fills the heatmap calendar: a row per week of the range and a cell
+    /// per day, darker for more tracked time relative to the busiest day
+    fn show_heatmap(&self, range: ReportRange, totals: &HashMap<NaiveDate, i64>, state: &AppState) {
+        while let Some(child) = self.heatmap.first_child() {
+            self.heatmap.remove(&child);
+        }
+
+        for (column, initial) in WEEKDAY_INITIALS.iter().enumerate() {
+            self.heatmap.attach(
+                &gtk::Label::builder()
+                    .label(*initial)
+                    .css_classes(["caption", "dim-label"])
+                    .build(),
+                column as i32 + 1,
+                0,
+                1,
+                1,
+            );
+        }
+
+        let format = state.settings.duration_format;
+        let max_seconds = totals.values().copied().max().unwrap_or(0);
+        for (row, week) in month::range_weeks(range.start, range.end).iter().enumerate() {
+            let row = row as i32 + 1;
+            // Weeks are labelled with their first day in the range, months only when they change
+            if let Some(first_day) = week.iter().flatten().next() {
+                let label = if row == 1 || first_day.day() <= 7 {
+                    first_day.format("%b %-d").to_string()
+                } else {
+                    first_day.format("%-d").to_string()
+                };
+                self.heatmap.attach(
+                    &gtk::Label::builder()
+                        .label(label)
+                        .xalign(1.0)
+                        .css_classes(["caption", "dim-label"])
+                        .build(),
+                    0,
+                    row,
+                    1,
+                    1,
+                );
+            }
+
+            for (column, day) in week.iter().enumerate() {
+                let Some(day) = day else { continue };
+                let seconds = totals.get(day).copied().unwrap_or(0);
+                let level = month::heatmap_level(seconds, max_seconds);
+                let cell = gtk::Box::builder()
+                    .css_classes(["heatmap-cell", &format!("heatmap-level-{}", level)])
+                    .tooltip_text(format!("{}: {}", day.format("%a, %b %-d, %Y"), format.format(seconds)))
+                    .build();
+                self.heatmap.attach(&cell, column as i32 + 1, row, 1, 1);
+            }
+        }
+    }
+}
+
+/// What a section of the estimates page compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Estimated {
+    /// Entry descriptions, over the shown range
+    Tasks,
+    /// Projects, over all their tracked time
+    Projects,
+}
+
+impl Estimated {
+    fn noun(self) -> &'static str {
+        match self {
+            Estimated::Tasks => "tasks",
+            Estimated::Projects => "projects",
+        }
+    }
+}
+
+/// Fills the estimates page from the kept rows: a section for the tasks,
+/// then one for the projects
+fn show_estimates(state: &Rc<RefCell<AppState>>, view: &Rc<ReportsView>) {
+    while let Some(child) = view.estimates.first_child() {
+        view.estimates.remove(&child);
+    }
+    let tasks = view.estimate_rows.borrow().clone();
+    append_estimates(state, view, &tasks, Estimated::Tasks);
+    let projects: Vec<EstimateRow> = view
+        .project_estimate_rows
+        .borrow()
+        .iter()
+        .map(|(_, row)| row.clone())
+        .collect();
+    append_estimates(state, view, &projects, Estimated::Projects);
+}
+
+/// Appends a section of the estimates page: how many estimated tasks or
+/// projects ran over, then per row its estimate, tracked time and the
+/// difference, overruns highlighted. Estimates are edited in place and
+/// saved on Enter.
+fn append_estimates(
+    state: &Rc<RefCell<AppState>>,
+    view: &Rc<ReportsView>,
+    rows: &[EstimateRow],
+    estimated_kind: Estimated,
+) {
+    let format = state.borrow().settings.duration_format;
+
+    let estimated: Vec<&EstimateRow> = rows.iter().filter(|row| row.estimated_seconds.is_some()).collect();
+    let overruns = estimated.iter().filter(|row| row.overrun_seconds().is_some()).count();
+    let estimated_total: i64 = estimated.iter().filter_map(|row| row.estimated_seconds).sum();
+    let tracked_total: i64 = estimated.iter().map(|row| row.tracked_seconds).sum();
+    view.estimates.append(
+        &gtk::Label::builder()
+            .label(match (estimated.is_empty(), estimated_kind) {
+                (true, Estimated::Tasks) => "No estimated tasks tracked in this range".to_string(),
+                (true, Estimated::Projects) => "No estimated projects".to_string(),
+                (false, _) => format!(
+                    "{} of {} estimated {} ran over",
+                    overruns,
+                    estimated.len(),
+                    estimated_kind.noun()
+                ),
+            })
+            .halign(gtk::Align::Start)
+            .margin_top(if estimated_kind == Estimated::Projects { 18 } else { 0 })
+            .css_classes(["heading"])
+            .build(),
+    );
+    if !estimated.is_empty() {
+        view.estimates.append(
+            &gtk::Label::builder()
+                .label(format!(
+                    "Tracked {} against {} estimated, {}",
+                    format.format(tracked_total),
+                    format.format(estimated_total),
+                    report::format_estimate_difference(estimated_total, tracked_total)
+                ))
+                .halign(gtk::Align::Start)
+                .selectable(true)
+                .css_classes(["dim-label"])
+                .build(),
+        );
+    }
+
+    let table = gtk::Grid::builder()
+        .row_spacing(6)
+        .column_spacing(18)
+        .margin_top(6)
+        .build();
+    let first_header = match estimated_kind {
+        Estimated::Tasks => "Task",
+        Estimated::Projects => "Project",
+    };
+    let headers = [first_header, "Estimate", "Tracked", "Difference"];
+    for (column, header) in headers.iter().enumerate() {
+        table.attach(&create_cell(header, &["heading"], column == 0), column as i32, 0, 1, 1);
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let line = index as i32 + 1;
+        table.attach(&create_cell(&row.description, &[], true), 0, line, 1, 1);
+
+        let estimate_entry = gtk::Entry::builder()
+            .text(row.estimated_seconds.map(smart_syntax::format_duration).unwrap_or_default())
+            .placeholder_text(match estimated_kind {
+                Estimated::Tasks => "1h30m",
+                Estimated::Projects => "40h",
+            })
+            .tooltip_text("Estimate, such as 1h30m or 45m. Press Enter to save, clear to remove.")
+            .width_chars(7)
+            .max_width_chars(7)
+            .build();
+        table.attach(&estimate_entry, 1, line, 1, 1);
+        table.attach(
+            &create_cell(&format.format(row.tracked_seconds), &["monospace"], false),
+            2,
+            line,
+            1,
+            1,
+        );
+        let (difference, css_class) = match row.estimated_seconds {
+            Some(estimate) if row.overrun_seconds().is_some() => {
+                (report::format_estimate_difference(estimate, row.tracked_seconds), "error")
+            }
+            Some(estimate) => (report::format_estimate_difference(estimate, row.tracked_seconds), "success"),
+            None => ("–".to_string(), "dim-label"),
+        };
+        table.attach(&create_cell(&difference, &["monospace", css_class], false), 3, line, 1, 1);
+
+        let state = state.clone();
+        let view = view.clone();
+        let description = row.description.clone();
+        estimate_entry.connect_activate(move |entry| {
+            let text = entry.text().trim().to_string();
+            let seconds = if text.is_empty() {
+                None
+            } else {
+                match smart_syntax::parse_duration(&text) {
+                    Some(seconds) => Some(seconds),
+                    None => {
+                        state
+                            .borrow()
+                            .show_error("Estimates are durations such as 1h30m, 2h or 45m");
+                        return;
+                    }
+                }
+            };
+            match estimated_kind {
+                Estimated::Tasks => save_task_estimate(&state, &view, &description, seconds),
+                Estimated::Projects => save_project_estimate(&state, &view, index, seconds),
+            }
+        });
+    }
+    view.estimates.append(&table);
+    view.estimates.append(
+        &gtk::Label::builder()
+            .label(match estimated_kind {
+                Estimated::Tasks => {
+                    "Tasks are entry descriptions, matched ignoring case. Tracked time is within the shown range."
+                }
+                Estimated::Projects => {
+                    "Project estimates are totals, compared with all the time tracked on the project whatever the range."
+                }
+            })
+            .halign(gtk::Align::Start)
+            .wrap(true)
+            .xalign(0.0)
+            .margin_top(6)
+            .css_classes(["caption", "dim-label"])
+            .build(),
+    );
+}
+
+/// Saves the estimate of a task, then shows the estimates again
+fn save_task_estimate(state: &Rc<RefCell<AppState>>, view: &Rc<ReportsView>, description: &str, seconds: Option<i64>) {
+    if let Err(e) = db::set_task_estimate(&state.borrow().db_conn, description, seconds) {
+        state.borrow().show_error(&format!("Failed to save estimate: {}", e));
+        return;
+    }
+    if let Some(row) = view
+        .estimate_rows
+        .borrow_mut()
+        .iter_mut()
+        .find(|row| row.description == description)
+    {
+        row.estimated_seconds = seconds;
+    }
+    show_estimates(state, view);
+}
+
+/// Saves the estimate of the project shown at `index`, then shows the
+/// estimates again
+fn save_project_estimate(state: &Rc<RefCell<AppState>>, view: &Rc<ReportsView>, index: usize, seconds: Option<i64>) {
+    let Some(project_id) = view.project_estimate_rows.borrow().get(index).map(|(id, _)| *id) else {
+        return;
+    };
+    if let Err(e) = db::set_project_estimate(&state.borrow().db_conn, project_id, seconds) {
+        state.borrow().show_error(&format!("Failed to save estimate: {}", e));
+        return;
+    }
+    if let Some(project) = state.borrow_mut().projects.iter_mut().find(|p| p.id == project_id) {
+        project.estimate_seconds = seconds;
+    }
+    if let Some((_, row)) = view.project_estimate_rows.borrow_mut().get_mut(index) {
+        row.estimated_seconds = seconds;
+    }
+    show_estimates(state, view);
+}
+
+/// Draws the kept rows as bars with their share. When grouping by project,
+/// a donut chart of the shares comes first, each row has a legend dot that
+/// hides its bar and slice, and the bars are scaled against the longest
+/// visible project.
+fn show_chart(state: &Rc<RefCell<AppState>>, view: &Rc<ReportsView>) {
+    let (format, hidden) = {
+        let state = state.borrow();
+        (state.settings.duration_format, state.hidden_chart_projects.clone())
+    };
+    let by_project = view.grouping.get() == Grouping::Project;
+    let rows = view.rows.borrow();
+
+    while let Some(child) = view.chart.first_child() {
+        view.chart.remove(&child);
+    }
+    let max_seconds = if by_project {
+        report::visible_max_seconds(rows.iter().map(|row| (row.project_id, row.seconds)), &hidden)
+    } else {
+        rows.iter().map(|row| row.seconds).max().unwrap_or(0).max(1)
+    } as f64;
+    let visible_total = if by_project {
+        report::visible_total_seconds(rows.iter().map(|row| (row.project_id, row.seconds)), &hidden)
+    } else {
+        rows.iter().map(|row| row.seconds).sum()
+    };
+
+    // Project shares as a donut, above the bars it shares colors with
+    if by_project && !rows.is_empty() {
+        let donut = DonutChart::new();
+        donut.update(
+            rows.iter()
+                .filter(|row| !hidden.contains(&row.project_id))
+                .map(|row| (row.seconds, row.color.as_deref().unwrap_or(DEFAULT_BAR_COLOR)))
+                .collect(),
+            &format.format(visible_total),
+        );
+        donut.container.set_margin_bottom(6);
+        view.chart.append(&donut.container);
+    }
+
+    for row in rows.iter() {
+        let is_hidden = by_project && hidden.contains(&row.project_id);
+        let color = row.color.as_deref().unwrap_or(DEFAULT_BAR_COLOR);
+        let line = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(8)
+            .build();
+
+        if by_project {
+            let legend_button = create_legend_button(&row.label, color, is_hidden);
+            let state = state.clone();
+            let view = view.clone();
+            let project_id = row.project_id;
+            legend_button.connect_clicked(move |_| {
+                toggle_chart_project(&state, project_id);
+                show_chart(&state, &view);
+            });
+            line.append(&legend_button);
+        }
+
+        let name_label = gtk::Label::builder()
+            .label(&row.label)
+            .halign(gtk::Align::Start)
+            .width_chars(18)
+            .max_width_chars(18)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .xalign(0.0)
+            .build();
+        if is_hidden {
+            name_label.add_css_class("dim-label");
+        }
+        line.append(&name_label);
+
+        // Bars share the space left of the duration label proportionally
+        let bar_area = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .hexpand(true)
+            .build();
+        let fraction = row.seconds as f64 / max_seconds;
+        let bar = gtk::Box::builder()
+            .width_request((fraction * 400.0).max(4.0) as i32)
+            .height_request(16)
+            .valign(gtk::Align::Center)
+            .css_classes(["project-bar"])
+            .visible(!is_hidden)
+            .build();
+        let css_provider = gtk::CssProvider::new();
+        css_provider.load_from_data(&format!("box {{ background-color: {}; }}", color));
+        bar.style_context()
+            .add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+        bar_area.append(&bar);
+        line.append(&bar_area);
+
+        let share_label = gtk::Label::builder()
+            .label(if is_hidden { String::new() } else { format_share(row.seconds, visible_total) })
+            .width_chars(4)
+            .xalign(1.0)
+            .css_classes(["monospace", "dim-label"])
+            .build();
+        line.append(&share_label);
+
+        let duration_label = gtk::Label::builder()
+            .label(format.format(row.seconds))
+            .selectable(true)
+            .css_classes(["monospace", "dim-label"])
+            .build();
+        line.append(&duration_label);
+
+        view.chart.append(&line);
+    }
+}
+
+/// Asks where to save the shown presence as CSV, then writes it
+fn save_presence(view: &Rc<ReportsView>, parent: &adw::Window, toast_overlay: &adw::ToastOverlay, range: ReportRange) {
+    let chooser = gtk::FileChooserNative::new(
+        Some("Save Presence"),
+        Some(parent),
+        gtk::FileChooserAction::Save,
+        Some("Save"),
+        Some("Cancel"),
+    );
+    chooser.set_current_name(&presence_file_name(range));
+
+    // The native dialog is only kept alive by its owner, so hold it until it answers
+    let pending = Rc::new(RefCell::new(Some(chooser.clone())));
+    let view = view.clone();
+    let toast_overlay = toast_overlay.clone();
+    chooser.connect_response(move |chooser, response| {
+        pending.borrow_mut().take();
+        if response != gtk::ResponseType::Accept {
+            return;
+        }
+        let Some(path) = chooser.file().and_then(|file| file.path()) else {
+            return;
+        };
+        let message = match std::fs::write(&path, view.presence_csv.borrow().as_bytes()) {
+            Ok(()) => "Presence saved".to_string(),
+            Err(e) => format!("Failed to save {}: {}", path.display(), e),
+        };
+        toast_overlay.add_toast(adw::Toast::new(&message));
+    });
+    chooser.show();
+}
+
+/// Opens the snapshot connection of the reports window, if the database is a file
+fn open_snapshot(state: &AppState) -> Option<rusqlite::Connection> {
+    let path = state.db_conn.path().filter(|path| !path.is_empty())?;
+    db::open_snapshot(std::path::Path::new(path))
+        .map_err(|e| eprintln!("Failed to open a snapshot for reports: {}", e))
+        .ok()
+}
+
+/// Loads the report for the range and grouping and shows it
+fn refresh_report(
+    state: &Rc<RefCell<AppState>>,
+    view: &Rc<ReportsView>,
+    range: ReportRange,
+    grouping: Grouping,
+) {
+    let state_borrow = state.borrow();
+    let conn = view.snapshot.as_ref().unwrap_or(&state_borrow.db_conn);
+    let entries = db::get_entries_for_date_range(conn, range.start, range.end)
+        .unwrap_or_else(|e| {
+            state_borrow.show_error(&format!("Failed to load report: {}", e));
+            Vec::new()
+        })
+        .into_iter()
+        .filter(|entry| state_borrow.entry_filter_matches(entry))
+        .collect::<Vec<_>>();
+    let projects = load_entry_projects(conn, &entries);
+
+    // The rows name descriptions and get copied, so private entries are
+    // treated as in other shared output
+    let shared: Vec<db::TimeEntry> = entries
+        .iter()
+        .filter_map(|entry| privacy::shared_entry(entry, state_borrow.settings.privacy_mode))
+        .collect();
+
+    let now = Utc::now();
+    let rounding = &state_borrow.settings.rounding;
+    let rows = report::group_entries(&shared, &projects, grouping, rounding, now, &Local);
+    let total = rounding.total_seconds(&shared, now);
+    view.show_rows(range, grouping, rows, total, &state_borrow);
+
+    let previous = range.shifted(false);
+    let previous_total: usize =
+        db::get_entries_for_date_range(conn, previous.start, previous.end)
+            .map(|mut entries| {
+                entries.retain(|entry| state_borrow.entry_filter_matches(entry));
+                report::interruptions_by_day(&entries, &Local)
+                    .iter()
+                    .map(|(_, count)| count)
+                    .sum()
+            })
+            .unwrap_or(0);
+    view.show_focus(range, &report::interruptions_by_day(&entries, &Local), previous_total);
+    let totals = report::daily_totals(&entries, rounding, now, &Local);
+    view.show_heatmap(range, &totals, &state_borrow);
+    view.show_presence(&stats::day_presence(&entries, rounding, now, &Local), &state_borrow);
+
+    // The streak runs up to today whatever range is shown
+    let today = Local::now().date_naive();
+    let tracked_days: HashSet<NaiveDate> = db::get_daily_seconds_in_range(
+        conn,
+        today - Days::new(STREAK_LOOKBACK_DAYS),
+        today,
+    )
+    .map(|days| days.into_iter().filter(|(_, seconds)| *seconds > 0).map(|(day, _)| day).collect())
+    .unwrap_or_default();
+    view.show_stats(
+        range,
+        &entries,
+        &totals,
+        stats::current_streak(&tracked_days, today),
+        &state_borrow,
+    );
+    let estimates = db::get_task_estimates(conn).unwrap_or_else(|e| {
+        state_borrow.show_error(&format!("Failed to load estimates: {}", e));
+        HashMap::new()
+    });
+    *view.estimate_rows.borrow_mut() = report::estimate_rows(&shared, &estimates, rounding, now);
+    let project_seconds = db::get_project_total_seconds(conn).unwrap_or_else(|e| {
+        state_borrow.show_error(&format!("Failed to load project totals: {}", e));
+        HashMap::new()
+    });
+    *view.project_estimate_rows.borrow_mut() =
+        report::project_estimate_rows(&state_borrow.projects, &project_seconds, rounding);
+    drop(state_borrow);
+    show_chart(state, view);
+    show_estimates(state, view);
+}
+
+/// Opens the reports window: a date range with previous/next navigation,
+/// a grouping selector, and the result as a table, a donut and bar chart, the
+/// interruptions per day, a heatmap calendar of the daily totals, statistics
+/// such as averages per weekday and the current streak, arrival and
+/// departure per day, or estimated against tracked time per task and project
+pub fn show_reports_window(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow) {
+    let window = adw::Window::builder()
+        .title("Reports")
+        .default_width(640)
+        .default_height(520)
+        .build();
+    if let Some(app) = parent.application() {
+        window.set_application(Some(&app));
+    }
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .build();
+
+    // Table and chart pages, switched from the header bar
+    let view_stack = adw::ViewStack::builder().vexpand(true).build();
+    let view_switcher = adw::ViewSwitcher::builder()
+        .stack(&view_stack)
+        .policy(adw::ViewSwitcherPolicy::Wide)
+        .build();
+    let header_bar = adw::HeaderBar::builder()
+        .title_widget(&view_switcher)
+        .build();
+    let refresh_button = gtk::Button::builder()
+        .icon_name("view-refresh-symbolic")
+        .tooltip_text("Reload")
+        .build();
+    header_bar.pack_end(&refresh_button);
+    let copy_button = gtk::Button::builder()
+        .icon_name("edit-copy-symbolic")
+        .tooltip_text("Copy Report")
+        .build();
+    header_bar.pack_end(&copy_button);
+    let export_button = gtk::Button::builder()
+        .icon_name("document-save-as-symbolic")
+        .tooltip_text("Export Entries")
+        .build();
+    header_bar.pack_end(&export_button);
+    let year_button = gtk::Button::builder()
+        .icon_name("x-office-calendar-symbolic")
+        .tooltip_text("Year in Review")
+        .build();
+    header_bar.pack_start(&year_button);
+    content.append(&header_bar);
+
+    // Navigation: previous, range popover, next, then grouping
+    let toolbar = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .margin_top(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+
+    let previous_button = gtk::Button::builder()
+        .icon_name("go-previous-symbolic")
+        .tooltip_text("Previous period")
+        .build();
+    toolbar.append(&previous_button);
+
+    let range_button = gtk::MenuButton::builder()
+        .tooltip_text("Choose dates")
+        .build();
+    toolbar.append(&range_button);
+
+    let next_button = gtk::Button::builder()
+        .icon_name("go-next-symbolic")
+        .tooltip_text("Next period")
+        .build();
+    toolbar.append(&next_button);
+
+    let grouping_labels: Vec<&str> = Grouping::ALL.iter().map(|g| g.label()).collect();
+    let grouping_dropdown = gtk::DropDown::from_strings(&grouping_labels);
+    grouping_dropdown.set_tooltip_text(Some("Group by"));
+    grouping_dropdown.set_halign(gtk::Align::End);
+    grouping_dropdown.set_hexpand(true);
+    toolbar.append(&grouping_dropdown);
+    content.append(&toolbar);
+
+    // Range popover with presets and a custom range
+    let popover_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(6)
+        .build();
+    let presets_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .homogeneous(true)
+        .build();
+    let this_week_button = gtk::Button::with_label("This Week");
+    let this_month_button = gtk::Button::with_label("This Month");
+    let last_month_button = gtk::Button::with_label("Last Month");
+    let this_year_button = gtk::Button::with_label("This Year");
+    presets_box.append(&this_week_button);
+    presets_box.append(&this_month_button);
+    presets_box.append(&last_month_button);
+    presets_box.append(&this_year_button);
+    popover_box.append(&presets_box);
+
+    let calendars_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    let start_calendar = gtk::Calendar::new();
+    let end_calendar = gtk::Calendar::new();
+    for (title, calendar) in [("From", &start_calendar), ("To", &end_calendar)] {
+        let column = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(4)
+            .build();
+        column.append(
+            &gtk::Label::builder()
+                .label(title)
+                .halign(gtk::Align::Start)
+                .css_classes(["heading"])
+                .build(),
+        );
+        column.append(calendar);
+        calendars_box.append(&column);
+    }
+    popover_box.append(&calendars_box);
+
+    let apply_button = gtk::Button::builder()
+        .label("Show Range")
+        .halign(gtk::Align::End)
+        .css_classes(["suggested-action"])
+        .build();
+    popover_box.append(&apply_button);
+
+    let popover = gtk::Popover::builder().child(&popover_box).build();
+    range_button.set_popover(Some(&popover));
+
+    let summary_label = gtk::Label::builder()
+        .halign(gtk::Align::Start)
+        .selectable(true)
+        .margin_top(12)
+        .margin_start(12)
+        .css_classes(["dim-label"])
+        .build();
+    content.append(&summary_label);
+
+    let table = gtk::Grid::builder()
+        .row_spacing(6)
+        .column_spacing(18)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    compat::add_view_page(
+        &view_stack,
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&table)
+            .build(),
+        "table",
+        "Table",
+        "view-list-symbolic",
+    );
+
+    let chart = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(8)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    compat::add_view_page(
+        &view_stack,
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&chart)
+            .build(),
+        "chart",
+        "Chart",
+        "utilities-system-monitor-symbolic",
+    );
+
+    let focus = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(8)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    compat::add_view_page(
+        &view_stack,
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&focus)
+            .build(),
+        "focus",
+        "Focus",
+        "view-continuous-symbolic",
+    );
+
+    let heatmap = gtk::Grid::builder()
+        .row_spacing(3)
+        .column_spacing(3)
+        .halign(gtk::Align::Center)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    compat::add_view_page(
+        &view_stack,
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&heatmap)
+            .build(),
+        "calendar",
+        "Calendar",
+        "x-office-calendar-symbolic",
+    );
+
+    let stats = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(8)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    compat::add_view_page(
+        &view_stack,
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&stats)
+            .build(),
+        "statistics",
+        "Statistics",
+        "view-paged-symbolic",
+    );
+
+    let presence = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(8)
+        .build();
+    let save_presence_button = gtk::Button::builder()
+        .label("Save as CSV…")
+        .tooltip_text("Save the arrival and departure times of the shown range")
+        .halign(gtk::Align::End)
+        .build();
+    let presence_page = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(8)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    presence_page.append(&save_presence_button);
+    presence_page.append(&presence);
+    compat::add_view_page(
+        &view_stack,
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&presence_page)
+            .build(),
+        "presence",
+        "Presence",
+        "preferences-system-time-symbolic",
+    );
+
+    let estimates = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(8)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    compat::add_view_page(
+        &view_stack,
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&estimates)
+            .build(),
+        "estimates",
+        "Estimates",
+        "task-due-symbolic",
+    );
+    content.append(&view_stack);
+    let toast_overlay = adw::ToastOverlay::new();
+    toast_overlay.set_child(Some(&content));
+    window.set_content(Some(&toast_overlay));
+
+    let view = Rc::new(ReportsView {
+        range_button,
+        summary_label,
+        table,
+        chart,
+        focus,
+        heatmap,
+        stats,
+        presence,
+        presence_csv: RefCell::new(String::new()),
+        estimates,
+        estimate_rows: RefCell::new(Vec::new()),
+        project_estimate_rows: RefCell::new(Vec::new()),
+        text: RefCell::new(String::new()),
+        rows: RefCell::new(Vec::new()),
+        grouping: Cell::new(Grouping::default()),
+        snapshot: open_snapshot(&state.borrow()),
+    });
+    let range = Rc::new(Cell::new(ReportRange::week_of(Local::now().date_naive())));
+
+    // Shows a range, keeping the calendars in sync with it
+    let show_range: Rc<dyn Fn(ReportRange)> = {
+        let state = state.clone();
+        let view = view.clone();
+        let range = range.clone();
+        let grouping_dropdown = grouping_dropdown.clone();
+        let start_calendar = start_calendar.clone();
+        let end_calendar = end_calendar.clone();
+        Rc::new(move |new_range: ReportRange| {
+            range.set(new_range);
+            if let Some(date) = to_glib_date(new_range.start) {
+                start_calendar.select_day(&date);
+            }
+            if let Some(date) = to_glib_date(new_range.end) {
+                end_calendar.select_day(&date);
+            }
+            let grouping = Grouping::ALL
+                .get(grouping_dropdown.selected() as usize)
+                .copied()
+                .unwrap_or_default();
+            refresh_report(&state, &view, new_range, grouping);
+        })
+    };
+
+    let show_range_for_previous = show_range.clone();
+    let range_for_previous = range.clone();
+    previous_button.connect_clicked(move |_| {
+        show_range_for_previous(range_for_previous.get().shifted(false));
+    });
+
+    let show_range_for_next = show_range.clone();
+    let range_for_next = range.clone();
+    next_button.connect_clicked(move |_| {
+        show_range_for_next(range_for_next.get().shifted(true));
+    });
+
+    let show_range_for_grouping = show_range.clone();
+    let range_for_grouping = range.clone();
+    grouping_dropdown.connect_selected_notify(move |_| {
+        show_range_for_grouping(range_for_grouping.get());
+    });
+
+    let show_range_for_refresh = show_range.clone();
+    let range_for_refresh = range.clone();
+    refresh_button.connect_clicked(move |_| {
+        show_range_for_refresh(range_for_refresh.get());
+    });
+
+    // Copies the table as tab separated text, which also covers the chart
+    let view_for_copy = view.clone();
+    let toast_overlay_for_copy = toast_overlay.clone();
+    copy_button.connect_clicked(move |button| {
+        button.clipboard().set_text(&view_for_copy.text.borrow());
+        toast_overlay_for_copy.add_toast(adw::Toast::new("Report copied"));
+    });
+
+    // Saves the presence of the shown range for whoever asks for it
+    let view_for_presence = view.clone();
+    let window_for_presence = window.clone();
+    let range_for_presence = range.clone();
+    let toast_overlay_for_presence = toast_overlay.clone();
+    save_presence_button.connect_clicked(move |_| {
+        save_presence(
+            &view_for_presence,
+            &window_for_presence,
+            &toast_overlay_for_presence,
+            range_for_presence.get(),
+        );
+    });
+
+    // Exports the entries of the shown range
+    let state_for_export = state.clone();
+    let window_for_export = window.clone();
+    let range_for_export = range.clone();
+    export_button.connect_clicked(move |_| {
+        export::export_range(
+            &state_for_export,
+            &window_for_export,
+            &toast_overlay,
+            range_for_export.get(),
+        );
+    });
+
+    let state_for_year = state.clone();
+    let parent_for_year = parent.clone();
+    year_button.connect_clicked(move |_| {
+        year_review::show_year_review_window(state_for_year.clone(), &parent_for_year);
+    });
+
+    let presets: [(&gtk::Button, RangePreset); 4] = [
+        (&this_week_button, ReportRange::week_of),
+        (&this_month_button, ReportRange::month_of),
+        (&last_month_button, |today| ReportRange::month_of(today).shifted(false)),
+        (&this_year_button, ReportRange::year_of),
+    ];
+    for (button, preset) in presets {
+        let show_range = show_range.clone();
+        let popover = popover.clone();
+        button.connect_clicked(move |_| {
+            popover.popdown();
+            show_range(preset(Local::now().date_naive()));
+        });
+    }
+
+    let show_range_for_apply = show_range.clone();
+    apply_button.connect_clicked(move |_| {
+        if let (Some(start), Some(end)) = (calendar_date(&start_calendar), calendar_date(&end_calendar)) {
+            popover.popdown();
+            show_range_for_apply(ReportRange::new(start, end));
+        }
+    });
+
+    show_range(range.get());
+    window.present();
+}
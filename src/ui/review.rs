@@ -0,0 +1,280 @@
+use adw::prelude::*;
+use chrono::Local;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{create_project_dropdown, refresh_view, AppState};
+use crate::db;
+
+/// Widgets for a single entry in the review queue
+struct ReviewRow {
+    entry_id: i64,
+    check: gtk::CheckButton,
+    description_entry: gtk::Entry,
+    project_dropdown: gtk::DropDown,
+}
+
+/// Creates the header bar button showing how many entries need review
+pub fn create_review_button() -> gtk::Button {
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(4)
+        .build();
+    content.append(&gtk::Image::from_icon_name("checkbox-checked-symbolic"));
+    content.append(
+        &gtk::Label::builder()
+            .css_classes(["review-badge"])
+            .build(),
+    );
+
+    gtk::Button::builder()
+        .child(&content)
+        .tooltip_text("Entries needing review")
+        .visible(false)
+        .build()
+}
+
+/// Creates a row for an entry in the review dialog
+fn create_review_row(entry: &db::TimeEntry, projects: &[db::Project]) -> (gtk::ListBoxRow, ReviewRow) {
+    let row = gtk::ListBoxRow::builder()
+        .selectable(false)
+        .activatable(false)
+        .build();
+
+    let hbox = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(8)
+        .margin_top(6)
+        .margin_bottom(6)
+        .margin_start(8)
+        .margin_end(8)
+        .build();
+
+    let check = gtk::CheckButton::builder()
+        .active(true)
+        .valign(gtk::Align::Center)
+        .build();
+    hbox.append(&check);
+
+    let content_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(4)
+        .hexpand(true)
+        .build();
+
+    let description_entry = gtk::Entry::builder()
+        .text(&entry.description)
+        .placeholder_text("(no description)")
+        .build();
+    content_box.append(&description_entry);
+
+    let project_dropdown = create_project_dropdown(projects, 0, false);
+    project_dropdown.set_margin_start(0);
+    project_dropdown.set_margin_end(0);
+    project_dropdown.set_margin_bottom(0);
+    if let Some(index) = entry
+        .project_id
+        .and_then(|id| projects.iter().position(|p| p.id == id))
+    {
+        project_dropdown.set_selected((index + 1) as u32);
+    }
+    content_box.append(&project_dropdown);
+
+    // Date and start-end times
+    let start_local = entry.start_time.with_timezone(&Local);
+    let time_range = match entry.end_time {
+        Some(end) => format!(
+            "{}  {} - {}",
+            start_local.format("%b %d"),
+            start_local.format("%H:%M"),
+            end.with_timezone(&Local).format("%H:%M")
+        ),
+        None => format!("{}  {} - now", start_local.format("%b %d"), start_local.format("%H:%M")),
+    };
+    let time_label = gtk::Label::builder()
+        .label(&time_range)
+        .halign(gtk::Align::Start)
+        .css_classes(["dim-label", "caption"])
+        .build();
+    content_box.append(&time_label);
+
+    hbox.append(&content_box);
+    row.set_child(Some(&hbox));
+
+    (
+        row,
+        ReviewRow {
+            entry_id: entry.id,
+            check,
+            description_entry,
+            project_dropdown,
+        },
+    )
+}
+
+/// Reloads the review queue into the dialog list
+fn refresh_review_list(
+    state: &Rc<RefCell<AppState>>,
+    list_box: &gtk::ListBox,
+    rows: &Rc<RefCell<Vec<ReviewRow>>>,
+) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+    rows.borrow_mut().clear();
+
+    let state_borrow = state.borrow();
+    let entries = match db::get_review_entries(&state_borrow.db_conn) {
+        Ok(entries) => entries,
+        Err(e) => {
+            state_borrow.show_error(&format!("Failed to load review queue: {}", e));
+            Vec::new()
+        }
+    };
+
+    if entries.is_empty() {
+        let empty_label = gtk::Label::builder()
+            .label("Nothing to review")
+            .css_classes(["dim-label"])
+            .margin_top(20)
+            .margin_bottom(20)
+            .build();
+        list_box.append(&empty_label);
+        return;
+    }
+
+    for entry in &entries {
+        let (row, review_row) = create_review_row(entry, &state_borrow.projects);
+        list_box.append(&row);
+        rows.borrow_mut().push(review_row);
+    }
+}
+
+/// Shows the review queue dialog for confirming, editing or rejecting automatic entries
+pub fn show_review_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow) {
+    let dialog = adw::Window::builder()
+        .title("Review Entries")
+        .default_width(420)
+        .default_height(500)
+        .modal(true)
+        .transient_for(parent)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(0)
+        .build();
+
+    let header_bar = adw::HeaderBar::builder()
+        .show_end_title_buttons(true)
+        .title_widget(&adw::WindowTitle::new("Review Entries", "Added automatically"))
+        .build();
+    content.append(&header_bar);
+
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .vexpand(true)
+        .build();
+
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+
+    scrolled_window.set_child(Some(&list_box));
+    content.append(&scrolled_window);
+
+    // Bulk action buttons
+    let actions_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(8)
+        .halign(gtk::Align::End)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_bottom(12)
+        .build();
+
+    let reject_button = gtk::Button::builder()
+        .label("Reject Selected")
+        .css_classes(["destructive-action"])
+        .build();
+    let confirm_button = gtk::Button::builder()
+        .label("Confirm Selected")
+        .css_classes(["suggested-action"])
+        .build();
+    actions_box.append(&reject_button);
+    actions_box.append(&confirm_button);
+    content.append(&actions_box);
+
+    let rows: Rc<RefCell<Vec<ReviewRow>>> = Rc::new(RefCell::new(Vec::new()));
+    refresh_review_list(&state, &list_box, &rows);
+
+    let state_for_confirm = state.clone();
+    let list_box_for_confirm = list_box.clone();
+    let rows_for_confirm = rows.clone();
+    let parent_for_confirm = parent.clone();
+    confirm_button.connect_clicked(move |_| {
+        let result = {
+            let state_borrow = state_for_confirm.borrow();
+            let reviewed: Vec<db::ReviewedEntry> = rows_for_confirm
+                .borrow()
+                .iter()
+                .filter(|r| r.check.is_active())
+                .map(|row| {
+                    let selected = row.project_dropdown.selected() as usize;
+                    let project_id = if selected == 0 {
+                        None
+                    } else {
+                        state_borrow.projects.get(selected - 1).map(|p| p.id)
+                    };
+                    db::ReviewedEntry {
+                        id: row.entry_id,
+                        project_id,
+                        description: row.description_entry.text().to_string(),
+                    }
+                })
+                .collect();
+
+            db::confirm_entries(&state_borrow.db_conn, &reviewed).map(|_| reviewed.len())
+        };
+
+        match result {
+            Ok(count) => state_for_confirm
+                .borrow()
+                .show_info(&format!("Confirmed {} entries", count)),
+            Err(e) => state_for_confirm
+                .borrow()
+                .show_error(&format!("Failed to confirm entries: {}", e)),
+        }
+        refresh_review_list(&state_for_confirm, &list_box_for_confirm, &rows_for_confirm);
+        refresh_view(state_for_confirm.clone(), &parent_for_confirm);
+    });
+
+    let state_for_reject = state.clone();
+    let list_box_for_reject = list_box.clone();
+    let rows_for_reject = rows.clone();
+    let parent_for_reject = parent.clone();
+    reject_button.connect_clicked(move |_| {
+        let ids: Vec<i64> = rows_for_reject
+            .borrow()
+            .iter()
+            .filter(|r| r.check.is_active())
+            .map(|r| r.entry_id)
+            .collect();
+
+        if let Err(e) = db::reject_entries(&state_for_reject.borrow().db_conn, &ids) {
+            state_for_reject.borrow().show_error(&format!("Failed to reject entries: {}", e));
+        }
+        refresh_review_list(&state_for_reject, &list_box_for_reject, &rows_for_reject);
+        refresh_view(state_for_reject.clone(), &parent_for_reject);
+    });
+
+    dialog.set_content(Some(&content));
+    dialog.present();
+}
@@ -0,0 +1,84 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{connect_copy_summary, refresh_view, show_in_entries_section, update_range_view, AppState, WeekView};
+use crate::db;
+
+/// Most matches shown at once; the newest ones are kept
+const SEARCH_LIMIT: usize = 500;
+
+/// Creates the search bar shown above the entries. Typing in it replaces the
+/// current view with the matching entries from the whole history.
+pub fn create_search_bar(state: &Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) -> gtk::SearchBar {
+    let search_entry = gtk::SearchEntry::builder()
+        .placeholder_text("Search descriptions and projects")
+        .hexpand(true)
+        .build();
+    let search_bar = gtk::SearchBar::builder()
+        .child(&search_entry)
+        .show_close_button(true)
+        .build();
+    search_bar.connect_entry(&search_entry);
+
+    let state_for_search = state.clone();
+    let window_for_search = window.clone();
+    search_entry.connect_search_changed(move |entry| {
+        state_for_search.borrow_mut().search_text = entry.text().trim().to_string();
+        refresh_view(state_for_search.clone(), &window_for_search);
+    });
+
+    let search_bar_for_stop = search_bar.clone();
+    search_entry.connect_stop_search(move |_| {
+        search_bar_for_stop.set_search_mode(false);
+    });
+
+    // Closing the bar brings back the regular view
+    search_bar.connect_search_mode_enabled_notify(move |search_bar| {
+        if !search_bar.is_search_mode() {
+            search_entry.set_text("");
+        }
+    });
+
+    search_bar
+}
+
+/// Refreshes the entries section with the entries matching the search text
+pub fn refresh_search_view(state: Rc<RefCell<AppState>>) {
+    let existing = state.borrow_mut().search_view.take();
+    let mut search_view = existing.unwrap_or_else(|| {
+        let search_view = WeekView::new();
+        search_view.empty_label.set_label("No matching entries");
+        connect_copy_summary(&state, &search_view);
+        search_view
+    });
+    let state_borrow = state.borrow();
+    show_in_entries_section(&state_borrow.entries_section, &search_view.container);
+
+    let text = &state_borrow.search_text;
+    let mut entries = match db::search_entries(&state_borrow.db_conn, text, SEARCH_LIMIT) {
+        Ok(entries) => entries,
+        Err(e) => {
+            state_borrow.show_error(&format!("Failed to search entries: {}", e));
+            Vec::new()
+        }
+    };
+    let truncated = entries.len() == SEARCH_LIMIT;
+    entries.retain(|e| state_borrow.entry_filter_matches(e));
+
+    search_view
+        .week_label
+        .set_label(&format!("Results for “{}”", text));
+    update_range_view(&mut search_view, entries, &state_borrow);
+    if truncated {
+        let total = search_view.total_label.label();
+        search_view.total_label.set_label(&format!(
+            "{} · newest {} matches",
+            total, SEARCH_LIMIT
+        ));
+    }
+
+    drop(state_borrow);
+    state.borrow_mut().search_view = Some(search_view);
+}
@@ -0,0 +1,319 @@
+use adw::prelude::*;
+use chrono::Utc;
+use gtk4 as gtk;
+use gtk4::glib;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::keyed_list::KeyedListBox;
+use super::{refresh_view, AppState};
+use crate::db;
+use crate::list_diff;
+
+/// Which entries the Today and Week views show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ProjectFilter {
+    #[default]
+    All,
+    /// Entries of one project, or entries without a project for `None`
+    Project(Option<i64>),
+}
+
+impl ProjectFilter {
+    /// Returns true when the entry should be shown
+    pub fn matches(self, entry: &db::TimeEntry) -> bool {
+        match self {
+            ProjectFilter::All => true,
+            ProjectFilter::Project(project_id) => entry.project_id == project_id,
+        }
+    }
+}
+
+/// Id of an entry row being dragged, dropped onto a project of the sidebar
+/// to move the entry there. Its own type keeps it apart from other drags.
+#[derive(Debug, Clone, Copy, glib::Boxed)]
+#[boxed_type(name = "TimeTrackingDraggedEntry")]
+pub struct DraggedEntry(pub i64);
+
+/// Makes an entry row draggable onto the projects of the sidebar
+pub fn add_entry_drag_source(row: &gtk::ListBoxRow, entry_id: i64) {
+    let drag_source = gtk::DragSource::new();
+    drag_source.set_actions(gtk::gdk::DragAction::MOVE);
+    drag_source.set_content(Some(&gtk::gdk::ContentProvider::for_value(
+        &DraggedEntry(entry_id).to_value(),
+    )));
+    let row_for_icon = row.clone();
+    drag_source.connect_drag_begin(move |source, _| {
+        source.set_icon(Some(&gtk::WidgetPaintable::new(Some(&row_for_icon))), 0, 0);
+    });
+    row.add_controller(drag_source);
+}
+
+/// Moves an entry dropped onto the sidebar to `project_id`. The running
+/// entry moves like from the dropdown, others can be moved back from the toast.
+fn move_dropped_entry(state: &Rc<RefCell<AppState>>, entry_id: i64, project_id: Option<i64>) -> bool {
+    let is_running = state.borrow().running_entry.as_ref().map(|e| e.id) == Some(entry_id);
+    if is_running {
+        return state.borrow_mut().set_running_project(project_id);
+    }
+
+    let state_borrow = state.borrow();
+    let entry = match db::get_entry_by_id(&state_borrow.db_conn, entry_id) {
+        Ok(entry) if entry.project_id != project_id => entry,
+        Ok(_) => return false,
+        Err(e) => {
+            state_borrow.show_failure("move entry", &e);
+            return false;
+        }
+    };
+    let previous = entry.project_id;
+    let result = state_borrow
+        .ensure_unlocked(entry.start_time)
+        .and_then(|()| db::set_entry_project(&state_borrow.db_conn, entry_id, project_id));
+    if let Err(e) = result {
+        state_borrow.show_failure("move entry", &e);
+        return false;
+    }
+
+    let name = project_id
+        .and_then(|id| state_borrow.projects.iter().find(|p| p.id == id))
+        .map_or("No Project", |p| p.name.as_str());
+    let state_for_undo = state.clone();
+    state_borrow.show_undo(&format!("Entry moved to {}", name), move || {
+        if let Err(e) = db::set_entry_project(&state_for_undo.borrow().db_conn, entry_id, previous) {
+            state_for_undo.borrow().show_failure("undo", &e);
+            return;
+        }
+        let window = state_for_undo.borrow().window.clone();
+        if let Some(ref window) = window {
+            refresh_view(state_for_undo.clone(), window);
+        }
+    });
+    true
+}
+
+/// Sidebar of the wide layout listing projects with their totals for the
+/// current view. Selecting a project filters the entries list.
+pub struct ProjectSidebar {
+    pub container: gtk::Box,
+    list: KeyedListBox<ProjectFilter>,
+}
+
+/// Creates a sidebar row with a color indicator, a name and a total
+fn create_sidebar_row(name: &str, color: Option<&str>, total: &str) -> gtk::ListBoxRow {
+    let hbox = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(8)
+        .margin_top(8)
+        .margin_bottom(8)
+        .margin_start(8)
+        .margin_end(8)
+        .build();
+
+    let color_box = gtk::Box::builder()
+        .width_request(12)
+        .height_request(12)
+        .valign(gtk::Align::Center)
+        .css_classes(["project-color-indicator"])
+        .visible(color.is_some())
+        .build();
+    if let Some(color) = color {
+        let css_provider = gtk::CssProvider::new();
+        css_provider.load_from_data(&format!("box {{ background-color: {}; }}", color));
+        color_box.style_context().add_provider(
+            &css_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+    }
+    hbox.append(&color_box);
+
+    let name_label = gtk::Label::builder()
+        .label(name)
+        .halign(gtk::Align::Start)
+        .hexpand(true)
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .build();
+    hbox.append(&name_label);
+
+    let total_label = gtk::Label::builder()
+        .label(total)
+        .css_classes(["monospace", "dim-label", "caption"])
+        .build();
+    hbox.append(&total_label);
+
+    gtk::ListBoxRow::builder().child(&hbox).build()
+}
+
+/// Creates the project sidebar. Hiding it (when the window gets narrow) clears the filter.
+pub fn create_project_sidebar(
+    state: &Rc<RefCell<AppState>>,
+    window: &adw::ApplicationWindow,
+) -> ProjectSidebar {
+    let container = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .width_request(240)
+        .visible(false)
+        .css_classes(["project-sidebar"])
+        .build();
+
+    let title = gtk::Label::builder()
+        .label("Projects")
+        .halign(gtk::Align::Start)
+        .margin_top(12)
+        .margin_bottom(6)
+        .margin_start(12)
+        .css_classes(["heading"])
+        .build();
+    container.append(&title);
+
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::Single)
+        .css_classes(["navigation-sidebar"])
+        .build();
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .vexpand(true)
+        .child(&list_box)
+        .build();
+    container.append(&scrolled_window);
+
+    let list = KeyedListBox::new(list_box.clone(), "No projects");
+
+    let state_for_select = state.clone();
+    let window_for_select = window.clone();
+    let list_for_select = list.clone();
+    list_box.connect_row_selected(move |_, row| {
+        let Some(filter) = row.and_then(|row| list_for_select.key_of(row)) else {
+            return;
+        };
+        if state_for_select.borrow().project_filter != filter {
+            state_for_select.borrow_mut().project_filter = filter;
+            refresh_view(state_for_select.clone(), &window_for_select);
+        }
+    });
+
+    // Entry rows dropped onto a project move there; "All Projects" takes none
+    let drop_target = gtk::DropTarget::new(DraggedEntry::static_type(), gtk::gdk::DragAction::MOVE);
+    let list_for_motion = list.clone();
+    drop_target.connect_motion(move |_, _, y| {
+        let list_box = list_for_motion.list_box();
+        match list_box.row_at_y(y as i32) {
+            Some(row) if list_for_motion.key_of(&row) != Some(ProjectFilter::All) => {
+                list_box.drag_highlight_row(&row);
+                gtk::gdk::DragAction::MOVE
+            }
+            _ => {
+                list_box.drag_unhighlight_row();
+                gtk::gdk::DragAction::empty()
+            }
+        }
+    });
+    let list_for_leave = list.clone();
+    drop_target.connect_leave(move |_| list_for_leave.list_box().drag_unhighlight_row());
+    let state_for_drop = state.clone();
+    let window_for_drop = window.clone();
+    let list_for_drop = list.clone();
+    drop_target.connect_drop(move |_, value, _, y| {
+        list_for_drop.list_box().drag_unhighlight_row();
+        let Ok(DraggedEntry(entry_id)) = value.get::<DraggedEntry>() else {
+            return false;
+        };
+        let target = list_for_drop
+            .list_box()
+            .row_at_y(y as i32)
+            .and_then(|row| list_for_drop.key_of(&row));
+        let Some(ProjectFilter::Project(project_id)) = target else {
+            return false;
+        };
+        if !move_dropped_entry(&state_for_drop, entry_id, project_id) {
+            return false;
+        }
+        // The sidebar rows are rebuilt on refresh, so not while handling the drop
+        refresh_view(state_for_drop.clone(), &window_for_drop);
+        true
+    });
+    list_box.add_controller(drop_target);
+
+    let state_for_hide = state.clone();
+    let window_for_hide = window.clone();
+    container.connect_visible_notify(move |container| {
+        if !container.is_visible() && state_for_hide.borrow().project_filter != ProjectFilter::All {
+            state_for_hide.borrow_mut().project_filter = ProjectFilter::All;
+            refresh_view(state_for_hide.clone(), &window_for_hide);
+        }
+    });
+
+    ProjectSidebar { container, list }
+}
+
+impl ProjectSidebar {
+    /// Reloads the projects and their totals for the current view period
+    pub fn update(&self, state: &AppState) {
+        let (start, end) = state.view_range();
+        let entries = db::get_entries_for_date_range(&state.db_conn, start, end).unwrap_or_default();
+
+        let now = Utc::now();
+        let rounding = &state.settings.rounding;
+        let mut totals: HashMap<Option<i64>, i64> = HashMap::new();
+        for entry in &entries {
+            *totals.entry(entry.project_id).or_insert(0) += rounding.entry_seconds(entry, now);
+        }
+        let format_total = |seconds: i64| {
+            state
+                .settings
+                .duration_format
+                .format(rounding.round_total(seconds))
+        };
+
+        // (filter, name, color, total) in display order
+        let mut rows: Vec<(ProjectFilter, String, Option<String>, String)> = vec![(
+            ProjectFilter::All,
+            "All Projects".to_string(),
+            None,
+            format_total(totals.values().sum()),
+        )];
+        // An archived project stays listed while it is filtered by
+        let mut projects = state.projects.clone();
+        if let ProjectFilter::Project(Some(id)) = state.project_filter {
+            if !projects.iter().any(|p| p.id == id) {
+                if let Ok(Some(project)) = db::get_project_by_id(&state.db_conn, id) {
+                    projects.push(project);
+                }
+            }
+        }
+        for project in &projects {
+            rows.push((
+                ProjectFilter::Project(Some(project.id)),
+                project.name.clone(),
+                Some(project.color.clone()),
+                format_total(totals.get(&Some(project.id)).copied().unwrap_or(0)),
+            ));
+        }
+        if let Some(&seconds) = totals.get(&None) {
+            rows.push((
+                ProjectFilter::Project(None),
+                "No Project".to_string(),
+                None,
+                format_total(seconds),
+            ));
+        }
+
+        let items: Vec<(ProjectFilter, u64)> = rows
+            .iter()
+            .map(|row| (row.0, list_diff::fingerprint(row)))
+            .collect();
+        self.list.update(&items, |filter| {
+            let (_, name, color, total) = rows.iter().find(|row| row.0 == filter).unwrap();
+            create_sidebar_row(name, color.as_deref(), total)
+        });
+
+        // Rebuilt rows lose their selection
+        if let Some(row) = self.list.row(state.project_filter) {
+            if !row.is_selected() {
+                self.list.list_box().select_row(Some(&row));
+            }
+        }
+    }
+}
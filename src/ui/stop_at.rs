@@ -0,0 +1,133 @@
+use adw::prelude::*;
+use chrono::{Local, Utc};
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+use crate::db;
+use crate::import;
+use crate::smart_syntax;
+
+/// Opens a dialog stopping the running timer at an earlier time, such as
+/// 17:00 when it is 19:30 and stopping was forgotten, optionally logging
+/// what happened since. The stop and the follow-up entry are saved in one
+/// transaction.
+pub fn show_stop_at_dialog(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow) {
+    let Some(running) = state.borrow().running_entry.clone() else {
+        state.borrow().show_info("No timer running");
+        return;
+    };
+
+    let dialog = adw::Window::builder()
+        .title("Stop Timer At")
+        .modal(true)
+        .transient_for(parent)
+        .default_width(360)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(18)
+        .margin_bottom(18)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+    content.append(
+        &gtk::Label::builder()
+            .label(format!(
+                "Started at {}. A time later than now is taken as yesterday.",
+                running.start_time.with_timezone(&Local).format("%H:%M on %b %-d")
+            ))
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(["dim-label"])
+            .build(),
+    );
+
+    let time_entry = gtk::Entry::builder()
+        .text(Local::now().format("%H:%M").to_string())
+        .placeholder_text("Stop time, e.g. 17:00")
+        .input_purpose(gtk::InputPurpose::Digits)
+        .activates_default(true)
+        .build();
+    content.append(&time_entry);
+
+    let follow_up_check = gtk::CheckButton::with_label("Log what happened since");
+    content.append(&follow_up_check);
+    let follow_up_entry = gtk::Entry::builder()
+        .placeholder_text("Description (@project sets the project)")
+        .sensitive(false)
+        .activates_default(true)
+        .build();
+    content.append(&follow_up_entry);
+    follow_up_check
+        .bind_property("active", &follow_up_entry, "sensitive")
+        .build();
+
+    let error_label = gtk::Label::builder()
+        .xalign(0.0)
+        .wrap(true)
+        .visible(false)
+        .css_classes(["error"])
+        .build();
+    content.append(&error_label);
+
+    let buttons_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .build();
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let stop_button = gtk::Button::builder()
+        .label("Stop")
+        .css_classes(["destructive-action"])
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&stop_button);
+    content.append(&buttons_box);
+
+    dialog.set_content(Some(&content));
+    dialog.set_default_widget(Some(&stop_button));
+
+    let dialog_for_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_for_cancel.close());
+
+    let dialog_for_stop = dialog.clone();
+    let window = parent.clone();
+    let time_entry_for_stop = time_entry.clone();
+    stop_button.connect_clicked(move |_| {
+        let now = Utc::now();
+        let stop_time = import::parse_time(&time_entry_for_stop.text())
+            .ok_or_else(|| "Enter the time as HH:MM".to_string())
+            .and_then(|time| smart_syntax::resolve_stop_time(time, running.start_time, now, &Local));
+        let stop_time = match stop_time {
+            Ok(stop_time) => stop_time,
+            Err(e) => {
+                error_label.set_label(&e);
+                error_label.set_visible(true);
+                return;
+            }
+        };
+
+        let follow_up = follow_up_check.is_active().then(|| {
+            let state = state.borrow();
+            let parsed = smart_syntax::parse_description(&follow_up_entry.text(), &state.projects);
+            db::NewEntry {
+                project_id: parsed.project_id,
+                description: parsed.description,
+                start_time: stop_time,
+                end_time: now,
+            }
+        });
+        if state.borrow_mut().stop_timer_at(stop_time, follow_up) {
+            dialog_for_stop.close();
+            refresh_view(state.clone(), &window);
+        }
+    });
+
+    dialog.present();
+    time_entry.grab_focus();
+}
@@ -0,0 +1,130 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use gtk4::{gio, glib};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{refresh_view, AppState};
+use crate::db;
+
+/// Menu of the tags written in descriptions that aren't filtered by yet
+fn tag_menu(state: &AppState) -> gio::Menu {
+    let tags = db::get_tags(&state.db_conn).unwrap_or_else(|e| {
+        state.show_error(&format!("Failed to load tags: {}", e));
+        Vec::new()
+    });
+
+    let menu = gio::Menu::new();
+    let mut any = false;
+    for tag in tags.iter().filter(|tag| !state.tag_filter.contains(tag)) {
+        let item = gio::MenuItem::new(Some(&format!("#{}", tag)), None);
+        item.set_action_and_target_value(Some("win.filter-tag"), Some(&tag.to_variant()));
+        menu.append_item(&item);
+        any = true;
+    }
+    if !any {
+        // Without an action the item shows insensitive, as a hint
+        let hint = if tags.is_empty() { "Add #tags to descriptions to filter by them" } else { "Filtered by every tag" };
+        menu.append(Some(hint), None);
+    }
+    menu
+}
+
+/// Creates the bar filtering the entry views by tags: a button adding a tag
+/// to the filter, followed by the filtered tags as chips removing them again.
+/// Entries must have every filtered tag to be shown.
+pub fn create_tag_filter_bar(state: &Rc<RefCell<AppState>>, window: &adw::ApplicationWindow) -> gtk::Box {
+    let bar = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_bottom(6)
+        .build();
+
+    let button = gtk::MenuButton::builder()
+        .label("#")
+        .tooltip_text("Filter by Tag")
+        .valign(gtk::Align::Start)
+        .css_classes(["flat", "caption"])
+        .build();
+    // Rebuilt on every opening, to list tags written meanwhile
+    let state_for_menu = state.clone();
+    button.set_create_popup_func(move |button| button.set_menu_model(Some(&tag_menu(&state_for_menu.borrow()))));
+    bar.append(&button);
+
+    let chips = gtk::FlowBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .column_spacing(6)
+        .row_spacing(6)
+        .max_children_per_line(6)
+        .hexpand(true)
+        .visible(false)
+        .build();
+    bar.append(&chips);
+    state.borrow_mut().tag_filter_box = Some(chips);
+
+    let filter_action = gio::SimpleAction::new("filter-tag", Some(glib::VariantTy::STRING));
+    let state_for_filter = state.clone();
+    let window_for_filter = window.clone();
+    filter_action.connect_activate(move |_, parameter| {
+        let Some(tag) = parameter.and_then(|parameter| parameter.str()) else {
+            return;
+        };
+        if state_for_filter.borrow().tag_filter.iter().any(|filtered| filtered == tag) {
+            return;
+        }
+        state_for_filter.borrow_mut().tag_filter.push(tag.to_string());
+        refresh_view(state_for_filter.clone(), &window_for_filter);
+    });
+    window.add_action(&filter_action);
+
+    bar
+}
+
+/// Creates the chip of a filtered tag, removing it from the filter when clicked
+fn create_chip(state: &Rc<RefCell<AppState>>, tag: &str) -> gtk::Button {
+    let content = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    content.append(
+        &gtk::Label::builder()
+            .label(format!("#{}", tag))
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(18)
+            .build(),
+    );
+    content.append(&gtk::Image::from_icon_name("window-close-symbolic"));
+
+    let chip = gtk::Button::builder()
+        .child(&content)
+        .tooltip_text(format!("Stop filtering by #{}", tag))
+        .css_classes(["pill", "caption"])
+        .build();
+
+    let tag = tag.to_string();
+    let state_for_remove = state.clone();
+    chip.connect_clicked(move |_| {
+        state_for_remove.borrow_mut().tag_filter.retain(|filtered| *filtered != tag);
+        // Rebuilt on the next refresh rather than from the chip's own handler
+        let window = state_for_remove.borrow().window.clone();
+        if let Some(window) = window {
+            refresh_view(state_for_remove.clone(), &window);
+        }
+    });
+
+    chip
+}
+
+/// Shows the filtered tags as chips, hidden while no tag is filtered
+pub fn refresh_tag_filter_chips(state: &Rc<RefCell<AppState>>) {
+    let Some(chips) = state.borrow().tag_filter_box.clone() else {
+        return;
+    };
+    while let Some(child) = chips.first_child() {
+        chips.remove(&child);
+    }
+    let tags = state.borrow().tag_filter.clone();
+    for tag in &tags {
+        chips.insert(&create_chip(state, tag), -1);
+    }
+    chips.set_visible(!tags.is_empty());
+}
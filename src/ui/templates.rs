@@ -0,0 +1,315 @@
+use adw::prelude::*;
+use gtk4 as gtk;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::AppState;
+use crate::db;
+
+/// Loads the templates, reporting a failure as an empty list
+fn load_templates(state: &Rc<RefCell<AppState>>) -> Vec<db::EntryTemplate> {
+    db::get_templates(&state.borrow().db_conn).unwrap_or_else(|e| {
+        state.borrow().show_failure("load templates", &e);
+        Vec::new()
+    })
+}
+
+/// What a template fills in, e.g. "Daily standup · Work · Private"
+fn template_summary(state: &AppState, template: &db::EntryTemplate) -> String {
+    let mut parts = Vec::new();
+    if !template.description.is_empty() {
+        parts.push(template.description.clone());
+    }
+    if let Some(project) = state.projects.iter().find(|p| Some(p.id) == template.project_id) {
+        parts.push(project.name.clone());
+    }
+    if template.private {
+        parts.push("Private".to_string());
+    }
+    parts.join(" · ")
+}
+
+/// Creates the menu button next to the description field listing the
+/// templates, which pre-fill the timer when picked
+pub fn create_templates_button(state: &Rc<RefCell<AppState>>) -> gtk::MenuButton {
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .build();
+    let empty_label = gtk::Label::builder()
+        .label("No templates yet. Add them in Preferences.")
+        .css_classes(["dim-label"])
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    list_box.set_placeholder(Some(&empty_label));
+
+    let popover = gtk::Popover::builder().child(&list_box).build();
+    let button = gtk::MenuButton::builder()
+        .icon_name("view-list-symbolic")
+        .tooltip_text("Fill in from a template")
+        .popover(&popover)
+        .valign(gtk::Align::Center)
+        .build();
+
+    // Rebuilt on every opening, as templates are edited in Preferences
+    let templates = Rc::new(RefCell::new(Vec::new()));
+    let state_for_show = state.clone();
+    let templates_for_show = templates.clone();
+    let list_box_for_show = list_box.clone();
+    popover.connect_show(move |_| {
+        while let Some(child) = list_box_for_show.first_child() {
+            list_box_for_show.remove(&child);
+        }
+        let loaded = load_templates(&state_for_show);
+        for template in &loaded {
+            let summary = template_summary(&state_for_show.borrow(), template);
+            let row = adw::ActionRow::builder()
+                .title(&template.name)
+                .subtitle(&summary)
+                .activatable(true)
+                .build();
+            list_box_for_show.append(&row);
+        }
+        *templates_for_show.borrow_mut() = loaded;
+    });
+
+    let state_for_apply = state.clone();
+    let popover_for_apply = popover.clone();
+    list_box.connect_row_activated(move |_, row| {
+        let template = templates.borrow().get(row.index() as usize).cloned();
+        popover_for_apply.popdown();
+        if let Some(template) = template {
+            state_for_apply.borrow_mut().apply_template(&template);
+        }
+    });
+
+    button
+}
+
+/// Opens a dialog creating a template, or editing `template` when given.
+/// `on_saved` runs once it was saved.
+pub fn show_template_dialog(
+    state: Rc<RefCell<AppState>>,
+    parent: &impl IsA<gtk::Window>,
+    template: Option<db::EntryTemplate>,
+    on_saved: impl Fn() + 'static,
+) {
+    let dialog = adw::Window::builder()
+        .title(if template.is_some() { "Edit Template" } else { "New Template" })
+        .modal(true)
+        .transient_for(parent)
+        .default_width(360)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(12)
+        .margin_top(18)
+        .margin_bottom(18)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+
+    let name_entry = gtk::Entry::builder().placeholder_text("Template name").build();
+    content.append(&name_entry);
+
+    let description_entry = gtk::Entry::builder().placeholder_text("Description (optional)").build();
+    content.append(&description_entry);
+
+    let (project_ids, labels): (Vec<Option<i64>>, Vec<String>) = {
+        let state = state.borrow();
+        std::iter::once((None, "No Project".to_string()))
+            .chain(state.projects.iter().map(|project| (Some(project.id), project.name.clone())))
+            .unzip()
+    };
+    let project_dropdown = gtk::DropDown::from_strings(&labels.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+    project_dropdown.set_tooltip_text(Some("Project"));
+    content.append(&project_dropdown);
+
+    let private_check = gtk::CheckButton::with_label("Private: hidden from exports and shared reports");
+    content.append(&private_check);
+
+    if let Some(ref template) = template {
+        name_entry.set_text(&template.name);
+        description_entry.set_text(&template.description);
+        let position = project_ids.iter().position(|&id| id == template.project_id).unwrap_or(0);
+        project_dropdown.set_selected(position as u32);
+        private_check.set_active(template.private);
+    }
+
+    let error_label = gtk::Label::builder()
+        .xalign(0.0)
+        .wrap(true)
+        .visible(false)
+        .css_classes(["error"])
+        .build();
+    content.append(&error_label);
+
+    let buttons_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .halign(gtk::Align::End)
+        .build();
+    let cancel_button = gtk::Button::with_label("Cancel");
+    let save_button = gtk::Button::builder()
+        .label("Save")
+        .css_classes(["suggested-action"])
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&save_button);
+    content.append(&buttons_box);
+
+    dialog.set_content(Some(&content));
+
+    let dialog_for_cancel = dialog.clone();
+    cancel_button.connect_clicked(move |_| dialog_for_cancel.close());
+
+    let dialog_for_save = dialog.clone();
+    save_button.connect_clicked(move |_| {
+        let name = name_entry.text().trim().to_string();
+        if name.is_empty() {
+            error_label.set_label("Template name cannot be empty");
+            error_label.set_visible(true);
+            return;
+        }
+        let description = description_entry.text().trim().to_string();
+        let project_id = project_ids.get(project_dropdown.selected() as usize).copied().flatten();
+        let private = private_check.is_active();
+
+        let conn = &state.borrow().db_conn;
+        let result = match template {
+            Some(ref template) => db::update_template(
+                conn,
+                &db::EntryTemplate {
+                    id: template.id,
+                    name,
+                    description,
+                    project_id,
+                    private,
+                },
+            ),
+            None => db::create_template(conn, &name, &description, project_id, private).map(|_| ()),
+        };
+        match result {
+            Ok(()) => {
+                dialog_for_save.close();
+                on_saved();
+            }
+            Err(e) => {
+                error_label.set_label(&format!("Failed to save template: {}", e));
+                error_label.set_visible(true);
+            }
+        }
+    });
+
+    dialog.present();
+}
+
+/// Fills the list with a row per template, each with buttons editing and
+/// deleting it
+fn fill_templates_list(state: &Rc<RefCell<AppState>>, list_box: &gtk::ListBox) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+
+    for template in load_templates(state) {
+        let summary = template_summary(&state.borrow(), &template);
+        let row = adw::ActionRow::builder()
+            .title(&template.name)
+            .subtitle(&summary)
+            .build();
+
+        let edit_button = gtk::Button::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text("Edit template")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let delete_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text("Delete template")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        row.add_suffix(&edit_button);
+        row.add_suffix(&delete_button);
+
+        let state_for_edit = state.clone();
+        let list_box_for_edit = list_box.clone();
+        let template_for_edit = template.clone();
+        edit_button.connect_clicked(move |button| {
+            let Some(window) = button.root().and_downcast::<gtk::Window>() else {
+                return;
+            };
+            let state = state_for_edit.clone();
+            let list_box = list_box_for_edit.clone();
+            show_template_dialog(
+                state_for_edit.clone(),
+                &window,
+                Some(template_for_edit.clone()),
+                move || fill_templates_list(&state, &list_box),
+            );
+        });
+
+        let state_for_delete = state.clone();
+        let list_box_for_delete = list_box.clone();
+        delete_button.connect_clicked(move |_| {
+            if let Err(e) = db::delete_template(&state_for_delete.borrow().db_conn, template.id) {
+                state_for_delete.borrow().show_failure("delete template", &e);
+                return;
+            }
+            fill_templates_list(&state_for_delete, &list_box_for_delete);
+        });
+
+        list_box.append(&row);
+    }
+}
+
+/// Creates the "Templates" preferences group, listing the templates with a
+/// button adding one
+pub fn create_templates_group(state: &Rc<RefCell<AppState>>) -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::builder()
+        .title("Templates")
+        .description("Pre-fill the description, project and privacy from the menu next to the description field")
+        .build();
+
+    let list_box = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    list_box.set_placeholder(Some(
+        &gtk::Label::builder()
+            .label("No templates")
+            .css_classes(["dim-label"])
+            .margin_top(12)
+            .margin_bottom(12)
+            .build(),
+    ));
+    fill_templates_list(state, &list_box);
+    group.add(&list_box);
+
+    let add_button = gtk::Button::builder()
+        .label("Add Template…")
+        .halign(gtk::Align::Start)
+        .margin_top(6)
+        .css_classes(["flat"])
+        .build();
+    let state_for_add = state.clone();
+    let list_box_for_add = list_box.clone();
+    add_button.connect_clicked(move |button| {
+        let Some(window) = button.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+        let state = state_for_add.clone();
+        let list_box = list_box_for_add.clone();
+        show_template_dialog(state_for_add.clone(), &window, None, move || {
+            fill_templates_list(&state, &list_box)
+        });
+    });
+    group.add(&add_button);
+
+    group
+}
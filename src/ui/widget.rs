@@ -0,0 +1,123 @@
+use adw::prelude::*;
+use chrono::{Local, Utc};
+use gtk4 as gtk;
+use gtk4::glib;
+use rusqlite::Connection;
+
+use super::compat;
+use crate::db;
+use crate::duration::{self, TrackedDuration};
+use crate::privacy;
+use crate::settings::Settings;
+
+/// Command line flag starting the display-only widget instead of the main window
+pub const WIDGET_FLAG: &str = "--widget";
+
+/// Application id of the widget, distinct so it runs alongside the main window
+pub const WIDGET_APPLICATION_ID: &str = "com.example.time-tracking.Widget";
+
+/// Applies the transparent styling of the widget window
+fn apply_widget_css() {
+    let provider = gtk::CssProvider::new();
+    provider.load_from_data(
+        r#"
+        window.widget-mode {
+            background-color: transparent;
+            box-shadow: none;
+        }
+        .widget-timer {
+            font-family: monospace;
+            font-size: 32px;
+            font-weight: bold;
+            text-shadow: 0 1px 2px alpha(black, 0.6);
+        }
+        .widget-total {
+            font-family: monospace;
+            text-shadow: 0 1px 2px alpha(black, 0.6);
+        }
+        "#,
+    );
+
+    gtk::style_context_add_provider_for_display(
+        &gtk::gdk::Display::default().expect("Could not get default display"),
+        &provider,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+}
+
+/// Reads the running timer and today's total from the database and updates the labels
+fn update_widget(conn: &Connection, settings: &Settings, timer_label: &gtk::Label, total_label: &gtk::Label) {
+    let now = Utc::now();
+    let entries = db::get_entries_for_date(conn, Local::now().date_naive()).unwrap_or_default();
+    let total = settings.rounding.total_seconds(&entries, now);
+    total_label.set_label(&format!("Today {}", settings.duration_format.format(total)));
+
+    match db::get_running_entry(conn).ok().flatten() {
+        Some(entry) => {
+            timer_label.set_label(&TrackedDuration::from_seconds(duration::entry_seconds(&entry, now)).clock());
+            // The widget may be on screen while sharing it, so private entries follow the privacy preference
+            let description = privacy::shared_entry(&entry, settings.privacy_mode).map(|entry| entry.description);
+            timer_label.set_tooltip_text(description.as_deref());
+            timer_label.remove_css_class("dim-label");
+        }
+        None => {
+            timer_label.set_label("00:00:00");
+            timer_label.set_tooltip_text(None);
+            timer_label.add_css_class("dim-label");
+        }
+    }
+}
+
+/// Builds the display-only widget window: today's total and the running timer on a
+/// transparent, undecorated window meant for desktop overlays and window rules.
+/// It has no controls and only reads the database, so it runs next to the main window.
+/// Returns `None` after showing the error when the database can't be opened.
+pub fn build_widget_window(app: &adw::Application) -> Option<gtk::ApplicationWindow> {
+    apply_widget_css();
+
+    // Only reading, the widget can show a database from a newer version too
+    let conn = match db::init_db().or_else(|_| db::open_db_read_only(&db::get_db_path())) {
+        Ok(conn) => conn,
+        Err(e) => {
+            compat::show_app_error(app, "Widget Unavailable", &format!("Failed to open the database: {}", e));
+            return None;
+        }
+    };
+    let settings = Settings::load(&conn);
+
+    let vbox = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(2)
+        .margin_top(8)
+        .margin_bottom(8)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+
+    let timer_label = gtk::Label::builder()
+        .css_classes(["widget-timer"])
+        .build();
+    vbox.append(&timer_label);
+
+    let total_label = gtk::Label::builder()
+        .css_classes(["widget-total"])
+        .build();
+    vbox.append(&total_label);
+
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .title("Time Tracking Widget")
+        .decorated(false)
+        .resizable(false)
+        .css_classes(["widget-mode"])
+        .child(&vbox)
+        .build();
+
+    update_widget(&conn, &settings, &timer_label, &total_label);
+    glib::timeout_add_seconds_local(1, move || {
+        update_widget(&conn, &settings, &timer_label, &total_label);
+        glib::ControlFlow::Continue
+    });
+
+    Some(window)
+}
@@ -0,0 +1,180 @@
+use adw::prelude::*;
+use chrono::{Datelike, Local, NaiveDate};
+use gtk4 as gtk;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use super::AppState;
+use crate::db;
+use crate::report::{self, format_share, ReportRange};
+
+/// Projects listed under "Top Projects"
+const TOP_PROJECTS: usize = 5;
+
+/// Creates a row with a title and a selectable value at its end
+pub(super) fn create_value_row(title: &str, value: &str) -> adw::ActionRow {
+    let row = adw::ActionRow::builder().title(title).build();
+    row.add_suffix(
+        &gtk::Label::builder()
+            .label(value)
+            .selectable(true)
+            .css_classes(["monospace", "dim-label"])
+            .build(),
+    );
+    row
+}
+
+/// Creates a row of the top projects: a dot in the project color, the
+/// name, its share of the year and its total
+fn create_project_row(name: &str, color: &str, share: &str, value: &str) -> adw::ActionRow {
+    let row = create_value_row(name, value);
+    let swatch = gtk::Box::builder()
+        .valign(gtk::Align::Center)
+        .css_classes(["legend-swatch"])
+        .build();
+    let css_provider = gtk::CssProvider::new();
+    css_provider.load_from_data(&format!("box {{ background-color: {}; }}", color));
+    swatch
+        .style_context()
+        .add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+    row.add_prefix(&swatch);
+    row.add_suffix(
+        &gtk::Label::builder()
+            .label(share)
+            .width_chars(4)
+            .xalign(1.0)
+            .css_classes(["monospace", "dim-label"])
+            .build(),
+    );
+    row
+}
+
+/// Fills `page` with the review of `year`, replacing what it showed
+fn show_year(state: &AppState, page: &adw::PreferencesPage, groups: &RefCell<Vec<adw::PreferencesGroup>>, year: i32) {
+    for group in groups.borrow_mut().drain(..) {
+        page.remove(&group);
+    }
+
+    let range = ReportRange::year_of(NaiveDate::from_ymd_opt(year, 1, 1).unwrap());
+    let conn = &state.db_conn;
+    let daily = db::get_daily_seconds_in_range(conn, range.start, range.end).unwrap_or_else(|e| {
+        state.show_error(&format!("Failed to load the year: {}", e));
+        Vec::new()
+    });
+    let projects = db::get_project_seconds_in_range(conn, range.start, range.end).unwrap_or_else(|e| {
+        state.show_error(&format!("Failed to load the year's projects: {}", e));
+        Vec::new()
+    });
+    let review = report::year_review(&daily);
+    let format = state.settings.duration_format;
+    let busiest = |busiest: Option<(NaiveDate, i64)>, label: &dyn Fn(NaiveDate) -> String| {
+        busiest
+            .map(|(day, seconds)| format!("{} · {}", label(day), format.format(seconds)))
+            .unwrap_or_else(|| "–".to_string())
+    };
+
+    let totals_group = adw::PreferencesGroup::builder()
+        .title("Totals")
+        .description("Tracked time as recorded, without rounding")
+        .build();
+    totals_group.add(&create_value_row("Total", &format.format(review.total_seconds)));
+    totals_group.add(&create_value_row("Days tracked", &review.tracked_days.to_string()));
+    totals_group.add(&create_value_row(
+        "Average per tracked day",
+        &format.format(review.average_daily_seconds),
+    ));
+
+    let busiest_group = adw::PreferencesGroup::builder().title("Busiest").build();
+    busiest_group.add(&create_value_row(
+        "Month",
+        &busiest(review.busiest_month, &|day| day.format("%B").to_string()),
+    ));
+    busiest_group.add(&create_value_row(
+        "Week",
+        &busiest(review.busiest_week, &|day| format!("Week of {}", day.format("%b %-d"))),
+    ));
+    busiest_group.add(&create_value_row(
+        "Day",
+        &busiest(review.busiest_day, &|day| day.format("%a, %b %-d").to_string()),
+    ));
+
+    let projects_group = adw::PreferencesGroup::builder().title("Top Projects").build();
+    if projects.is_empty() {
+        projects_group.add(&adw::ActionRow::builder().title("Nothing tracked this year").build());
+    }
+    let year_total: i64 = projects.iter().map(|(_, seconds)| seconds).sum();
+    for (project_id, seconds) in projects.into_iter().take(TOP_PROJECTS) {
+        let project = project_id.and_then(|id| db::get_project_by_id(conn, id).ok().flatten());
+        let (name, color) = match project {
+            Some(project) => (project.name, project.color),
+            None => ("No Project".to_string(), "#888888".to_string()),
+        };
+        projects_group.add(&create_project_row(
+            &name,
+            &color,
+            &format_share(seconds, year_total),
+            &format.format(seconds),
+        ));
+    }
+
+    let mut groups = groups.borrow_mut();
+    for group in [totals_group, busiest_group, projects_group] {
+        page.add(&group);
+        groups.push(group);
+    }
+}
+
+/// Opens the year in review: the year's total, busiest month, week and
+/// day, average per tracked day and top projects, with previous/next year
+/// navigation. Everything is summed by the database rather than loaded.
+pub fn show_year_review_window(state: Rc<RefCell<AppState>>, parent: &adw::ApplicationWindow) {
+    let year = Rc::new(Cell::new(Local::now().year()));
+
+    let title = adw::WindowTitle::new("Year in Review", &year.get().to_string());
+    let header_bar = adw::HeaderBar::builder().title_widget(&title).build();
+    let previous_button = gtk::Button::builder()
+        .icon_name("go-previous-symbolic")
+        .tooltip_text("Previous year")
+        .build();
+    let next_button = gtk::Button::builder()
+        .icon_name("go-next-symbolic")
+        .tooltip_text("Next year")
+        .build();
+    header_bar.pack_start(&previous_button);
+    header_bar.pack_start(&next_button);
+
+    let page = adw::PreferencesPage::builder().vexpand(true).build();
+    let groups: Rc<RefCell<Vec<adw::PreferencesGroup>>> = Rc::new(RefCell::new(Vec::new()));
+    show_year(&state.borrow(), &page, &groups, year.get());
+
+    let content = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .build();
+    content.append(&header_bar);
+    content.append(&page);
+
+    let window = adw::Window::builder()
+        .title("Year in Review")
+        .default_width(420)
+        .default_height(560)
+        .content(&content)
+        .build();
+    if let Some(app) = parent.application() {
+        window.set_application(Some(&app));
+    }
+
+    for (button, step) in [(previous_button, -1), (next_button, 1)] {
+        let state = state.clone();
+        let page = page.clone();
+        let groups = groups.clone();
+        let year = year.clone();
+        let title = title.clone();
+        button.connect_clicked(move |_| {
+            year.set(year.get() + step);
+            title.set_subtitle(&year.get().to_string());
+            show_year(&state.borrow(), &page, &groups, year.get());
+        });
+    }
+
+    window.present();
+}